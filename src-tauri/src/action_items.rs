@@ -0,0 +1,180 @@
+//! Action items from meetings
+//!
+//! For modes with `extract_action_items`, a second LLM call after the
+//! dictation is saved pulls the tasks out of the transcript as JSON (owner,
+//! task and due date when spoken). They are stored next to the Markdown
+//! summary, so they can be exported to task managers later. Like titles,
+//! the extraction runs in the background and never delays the paste.
+
+use crate::database::ActionItem;
+use crate::db_worker::DbHandle;
+use crate::error::Result;
+use crate::providers::llm::LlmProvider;
+use crate::tasks::{self, Delivery};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::{AppHandle, Emitter};
+
+/// Event emitted when a history item got its action items
+pub const ACTION_ITEMS_EVENT: &str = "action-items";
+
+/// Only the start of very long meetings is sent to the LLM
+const MAX_PROMPT_CHARS: usize = 24000;
+
+/// Payload of `ACTION_ITEMS_EVENT`
+#[derive(Debug, Clone, Serialize)]
+pub struct ActionItemsUpdate {
+    pub history_id: String,
+    pub items: Vec<ActionItem>,
+}
+
+#[derive(Deserialize)]
+struct Extracted {
+    action_items: Vec<ActionItem>,
+}
+
+/// Schema the LLM's answer is validated against
+pub fn schema() -> Value {
+    json!({
+        "type": "object",
+        "required": ["action_items"],
+        "properties": {
+            "action_items": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "required": ["task"],
+                    "properties": {
+                        "owner": { "type": ["string", "null"] },
+                        "task": { "type": "string" },
+                        "due": { "type": ["string", "null"] }
+                    }
+                }
+            }
+        }
+    })
+}
+
+/// Build the prompt asking for the action items of a transcript
+pub fn prompt(transcript: &str) -> String {
+    let transcript: String = transcript.chars().take(MAX_PROMPT_CHARS).collect();
+    format!(
+        "List the action items agreed on in the following meeting transcript. For each, give the task, \
+         the person responsible as \"owner\" and the due date as \"due\" exactly as spoken (e.g. \"Friday\"). \
+         Use null for an owner or due date that was not mentioned. Do not invent tasks; return an empty list \
+         if there are none.\n\nTranscript:\n{}",
+        transcript
+    )
+}
+
+/// Parse the validated JSON answer, dropping empty tasks and blank fields
+pub fn parse(answer: &str) -> Result<Vec<ActionItem>> {
+    let extracted: Extracted = serde_json::from_str(answer)?;
+    let blank_to_none = |field: Option<String>| {
+        field
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    };
+    Ok(extracted
+        .action_items
+        .into_iter()
+        .filter(|item| !item.task.trim().is_empty())
+        .map(|item| ActionItem {
+            owner: blank_to_none(item.owner),
+            task: item.task.trim().to_string(),
+            due: blank_to_none(item.due),
+        })
+        .collect())
+}
+
+/// Ask the LLM for the action items of a transcript
+pub async fn extract(
+    provider: &dyn LlmProvider,
+    transcript: &str,
+    redact_names: Option<&[String]>,
+) -> Result<Vec<ActionItem>> {
+    if transcript.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    let answer = crate::structured::complete_json(
+        provider,
+        &prompt(transcript),
+        Some(&schema()),
+        redact_names,
+    )
+    .await?;
+    parse(&answer)
+}
+
+/// Extract and store the action items of a history item in the background
+///
+/// With a `tasks` delivery, the items are also created as tasks.
+pub fn spawn(
+    app_handle: AppHandle,
+    db: DbHandle,
+    provider: Box<dyn LlmProvider>,
+    history_id: String,
+    transcript: String,
+    redact_names: Option<Vec<String>>,
+    tasks: Option<Delivery>,
+) {
+    tauri::async_runtime::spawn(async move {
+        let items = match extract(provider.as_ref(), &transcript, redact_names.as_deref()).await {
+            Ok(items) => items,
+            Err(e) => {
+                log::warn!("Failed to extract action items: {}", e);
+                return;
+            }
+        };
+        log::info!("Extracted {} action items", items.len());
+
+        let id = history_id.clone();
+        let stored = items.clone();
+        if let Err(e) = db.call(move |db| db.set_action_items(&id, &stored)).await {
+            log::warn!("Failed to save action items: {}", e);
+            return;
+        }
+        let new_tasks = tasks::from_action_items(&items);
+        let _ = app_handle.emit(ACTION_ITEMS_EVENT, ActionItemsUpdate { history_id, items });
+        if let Some(delivery) = tasks {
+            tasks::deliver(delivery, new_tasks).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cleans_up_items() {
+        let answer = r#"{"action_items": [
+            {"owner": "Anna", "task": " Send the budget ", "due": "Friday"},
+            {"owner": "", "task": "Book a room", "due": null},
+            {"owner": null, "task": "  "}
+        ]}"#;
+        assert_eq!(
+            parse(answer).unwrap(),
+            vec![
+                ActionItem {
+                    owner: Some("Anna".to_string()),
+                    task: "Send the budget".to_string(),
+                    due: Some("Friday".to_string()),
+                },
+                ActionItem {
+                    owner: None,
+                    task: "Book a room".to_string(),
+                    due: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_schema_accepts_missing_owner() {
+        let value = json!({"action_items": [{"task": "Book a room", "owner": null}]});
+        assert!(crate::structured::validate(&value, &schema()).is_ok());
+        let value = json!({"action_items": [{"owner": "Anna"}]});
+        assert!(crate::structured::validate(&value, &schema()).is_err());
+    }
+}
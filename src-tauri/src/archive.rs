@@ -0,0 +1,120 @@
+//! Zip archives of history items
+//!
+//! An archive holds the recorded audio, the raw transcript, the final output
+//! and a `metadata.json` with providers, timings and segments, so a
+//! problematic dictation can be attached to an accuracy report as one file.
+
+use crate::database::{HistoryItem, Segment};
+use crate::error::Result;
+use serde::Serialize;
+use std::io::{Seek, Write};
+use std::path::Path;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+/// Contents of `metadata.json`
+#[derive(Debug, Serialize)]
+struct Metadata<'a> {
+    app_version: &'static str,
+    /// Name of the audio file in the archive, if the recording was kept
+    audio_file: Option<String>,
+    item: &'a HistoryItem,
+    segments: &'a [Segment],
+}
+
+/// Write a history item archive
+pub fn write_history_zip<W: Write + Seek>(
+    writer: W,
+    item: &HistoryItem,
+    segments: &[Segment],
+    audio: Option<&Path>,
+) -> Result<W> {
+    let mut zip = ZipWriter::new(writer);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    let audio_file = match audio {
+        Some(path) => {
+            let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("wav");
+            let name = format!("audio.{}", extension);
+            // Compressed audio formats do not shrink any further
+            let audio_options = if extension == "wav" {
+                options
+            } else {
+                options.compression_method(CompressionMethod::Stored)
+            };
+            zip.start_file(name.as_str(), audio_options)?;
+            zip.write_all(&std::fs::read(path)?)?;
+            Some(name)
+        }
+        None => None,
+    };
+
+    zip.start_file("transcript.txt", options)?;
+    zip.write_all(item.transcript_raw.as_bytes())?;
+
+    zip.start_file("output.txt", options)?;
+    zip.write_all(item.output_final.as_bytes())?;
+
+    let metadata = Metadata {
+        app_version: env!("CARGO_PKG_VERSION"),
+        audio_file,
+        item,
+        segments,
+    };
+    zip.start_file("metadata.json", options)?;
+    zip.write_all(serde_json::to_string_pretty(&metadata)?.as_bytes())?;
+
+    Ok(zip.finish()?)
+}
+
+/// Export a history item with its audio to a zip file
+pub fn export_history_zip(item: &HistoryItem, segments: &[Segment], dest: &Path) -> Result<()> {
+    let audio = item
+        .audio_path
+        .as_deref()
+        .map(Path::new)
+        .filter(|path| path.exists());
+    if item.audio_path.is_some() && audio.is_none() {
+        log::warn!(
+            "Audio of history item {} is missing, exporting without it",
+            item.id
+        );
+    }
+
+    let file = std::fs::File::create(dest)?;
+    write_history_zip(file, item, segments, audio)?;
+    log::info!("Exported history item {} to {:?}", item.id, dest);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Cursor, Read};
+
+    #[test]
+    fn test_write_history_zip() {
+        let item = HistoryItem {
+            id: "abc".to_string(),
+            transcript_raw: "um send the report".to_string(),
+            output_final: "Send the report.".to_string(),
+            ..HistoryItem::default()
+        };
+        let buffer = write_history_zip(Cursor::new(Vec::new()), &item, &[], None).unwrap();
+
+        let mut archive = zip::ZipArchive::new(buffer).unwrap();
+        let mut names: Vec<&str> = archive.file_names().collect();
+        names.sort();
+        assert_eq!(names, ["metadata.json", "output.txt", "transcript.txt"]);
+
+        let mut metadata = String::new();
+        archive
+            .by_name("metadata.json")
+            .unwrap()
+            .read_to_string(&mut metadata)
+            .unwrap();
+        let metadata: serde_json::Value = serde_json::from_str(&metadata).unwrap();
+        assert_eq!(metadata["item"]["id"], "abc");
+        assert!(metadata["audio_file"].is_null());
+    }
+}
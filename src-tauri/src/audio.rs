@@ -4,15 +4,31 @@
 
 use crate::error::{AppError, Result};
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
-use cpal::{Device, SampleFormat, StreamConfig};
+use cpal::{
+    BufferSize, Device, SampleFormat, SampleRate, Stream, StreamConfig, StreamError,
+    SupportedBufferSize, SupportedStreamConfig, SupportedStreamConfigRange,
+};
 use hound::{SampleFormat as HoundSampleFormat, WavSpec, WavWriter};
+use std::borrow::Cow;
+use std::fs::File;
+use std::ops::Range;
+use std::os::unix::fs::FileExt;
 use std::path::PathBuf;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 
 /// Audio sample rate for whisper.cpp (16kHz required)
 pub const WHISPER_SAMPLE_RATE: u32 = 16000;
 
+/// Scales RMS to the 0-1 level range (typical speech is around 0.1-0.3 RMS)
+pub const LEVEL_SCALE: f32 = 3.0;
+
+/// Samples at or above this magnitude are considered clipped
+pub const CLIP_LEVEL: f32 = 0.99;
+
+/// Share of clipped samples above which a recording is flagged (0.1%)
+const CLIPPED_FRACTION: f32 = 0.001;
+
 /// Audio input device information
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct AudioDevice {
@@ -20,9 +36,53 @@ pub struct AudioDevice {
     pub is_default: bool,
 }
 
+/// Sound system recorded through
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AudioBackend {
+    /// ALSA, which reaches PulseAudio and PipeWire through their plugins
+    #[default]
+    Default,
+    /// A JACK server (requires the `jack` feature)
+    Jack,
+}
+
+static BACKEND: Mutex<AudioBackend> = Mutex::new(AudioBackend::Default);
+
+/// JACK ports to record from instead of the system capture ports
+static JACK_PORTS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Record through `backend` from now on
+pub fn set_backend(backend: AudioBackend, jack_ports: &[String]) {
+    if let Ok(mut current) = BACKEND.lock() {
+        *current = backend;
+    }
+    if let Ok(mut ports) = JACK_PORTS.lock() {
+        *ports = jack_ports.to_vec();
+    }
+}
+
+fn backend() -> AudioBackend {
+    BACKEND.lock().map(|b| *b).unwrap_or_default()
+}
+
+/// The cpal host for the selected backend
+fn host() -> Result<cpal::Host> {
+    match backend() {
+        AudioBackend::Default => Ok(cpal::default_host()),
+        #[cfg(feature = "jack")]
+        AudioBackend::Jack => cpal::host_from_id(cpal::HostId::Jack)
+            .map_err(|e| AppError::Audio(format!("JACK not available: {}", e))),
+        #[cfg(not(feature = "jack"))]
+        AudioBackend::Jack => Err(AppError::Audio(
+            "JACK is not available in this build (enable the `jack` feature)".to_string(),
+        )),
+    }
+}
+
 /// Get list of available input devices
 pub fn get_input_devices() -> Result<Vec<AudioDevice>> {
-    let host = cpal::default_host();
+    let host = host()?;
     let default_device = host.default_input_device();
     let default_name = default_device
         .as_ref()
@@ -44,9 +104,126 @@ pub fn get_input_devices() -> Result<Vec<AudioDevice>> {
     Ok(result)
 }
 
+/// Stream settings replacing the device's default configuration
+///
+/// For devices that misbehave with the defaults cpal picks. Unset fields keep
+/// the default.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct StreamOverride {
+    /// Sample rate in Hz
+    pub sample_rate: Option<u32>,
+    /// Sample format: "f32", "i16" or "u16"
+    pub sample_format: Option<String>,
+    /// Buffer size in frames
+    pub buffer_size: Option<u32>,
+}
+
+/// A range of configurations an input device supports
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct SupportedInputConfig {
+    pub channels: u16,
+    pub min_sample_rate: u32,
+    pub max_sample_rate: u32,
+    pub sample_format: String,
+    /// Buffer size range in frames, if the backend reports one
+    pub min_buffer_size: Option<u32>,
+    pub max_buffer_size: Option<u32>,
+}
+
+/// List the configurations an input device supports
+pub fn probe_input_device(name: &str) -> Result<Vec<SupportedInputConfig>> {
+    let device = get_device_by_name(name)?;
+    let configs = device
+        .supported_input_configs()
+        .map_err(|e| AppError::Audio(e.to_string()))?
+        .map(|range| {
+            let (min_buffer_size, max_buffer_size) = match range.buffer_size() {
+                SupportedBufferSize::Range { min, max } => (Some(*min), Some(*max)),
+                SupportedBufferSize::Unknown => (None, None),
+            };
+            SupportedInputConfig {
+                channels: range.channels(),
+                min_sample_rate: range.min_sample_rate().0,
+                max_sample_rate: range.max_sample_rate().0,
+                sample_format: range.sample_format().to_string(),
+                min_buffer_size,
+                max_buffer_size,
+            }
+        })
+        .collect();
+    Ok(configs)
+}
+
+/// Sample formats the recorder can convert
+fn parse_sample_format(name: &str) -> Result<SampleFormat> {
+    match name {
+        "f32" => Ok(SampleFormat::F32),
+        "i16" => Ok(SampleFormat::I16),
+        "u16" => Ok(SampleFormat::U16),
+        _ => Err(AppError::Audio(format!(
+            "Unsupported sample format: {}",
+            name
+        ))),
+    }
+}
+
+/// Apply a sample rate and format override to the default configuration
+///
+/// Picks a supported configuration with the requested rate and format,
+/// preferring the default channel count.
+fn select_config(
+    default: SupportedStreamConfig,
+    supported: &[SupportedStreamConfigRange],
+    stream_override: &StreamOverride,
+) -> Result<SupportedStreamConfig> {
+    if stream_override.sample_rate.is_none() && stream_override.sample_format.is_none() {
+        return Ok(default);
+    }
+    let format = match &stream_override.sample_format {
+        Some(name) => parse_sample_format(name)?,
+        None => default.sample_format(),
+    };
+    let rate = SampleRate(
+        stream_override
+            .sample_rate
+            .unwrap_or(default.sample_rate().0),
+    );
+
+    let mut candidates: Vec<SupportedStreamConfig> = supported
+        .iter()
+        .filter(|range| range.sample_format() == format)
+        .filter_map(|range| range.try_with_sample_rate(rate))
+        .collect();
+    candidates.sort_by_key(|config| config.channels() != default.channels());
+    candidates.into_iter().next().ok_or_else(|| {
+        AppError::Audio(format!(
+            "The device does not support {} samples at {} Hz",
+            format, rate.0
+        ))
+    })
+}
+
+/// First preferred device that is present
+///
+/// Preferences are device names in order; an empty name stands for the
+/// default device and ends the search. Returns "" (the default device) when
+/// none of them is present.
+pub fn pick_device(preferences: &[String], available: &[String]) -> String {
+    for name in preferences {
+        if name.is_empty() || name == "default" {
+            break;
+        }
+        if available.contains(name) {
+            return name.clone();
+        }
+    }
+    String::new()
+}
+
 /// Get a specific input device by name
 pub fn get_device_by_name(name: &str) -> Result<Device> {
-    let host = cpal::default_host();
+    let host = host()?;
 
     if name.is_empty() || name == "default" {
         return host
@@ -59,26 +236,137 @@ pub fn get_device_by_name(name: &str) -> Result<Device> {
         .ok_or_else(|| AppError::Audio(format!("Device not found: {}", name)))
 }
 
+/// Recording buffer keeping at most `memory_cap` samples in memory
+///
+/// Older samples are spilled to an anonymous temp file, which has no name on
+/// disk and is removed by the OS once closed, and are read back when the
+/// samples are requested.
+#[derive(Default)]
+struct SampleBuffer {
+    memory: Vec<f32>,
+    spill: Option<Arc<File>>,
+    /// Samples in the spill file, all older than `memory`
+    spilled: usize,
+    /// Most samples kept in memory (0 for no limit)
+    memory_cap: usize,
+    /// Bumped on clear, so a spill finishing after it is dropped
+    generation: u64,
+}
+
+impl SampleBuffer {
+    fn len(&self) -> usize {
+        self.spilled + self.memory.len()
+    }
+
+    fn clear(&mut self) {
+        self.memory.clear();
+        self.spill = None;
+        self.spilled = 0;
+        self.generation += 1;
+    }
+    /// Samples from `offset` to the end, reading spilled samples back from disk
+    fn read_from(&self, offset: usize) -> Vec<f32> {
+        let mut samples = Vec::with_capacity(self.len().saturating_sub(offset));
+        let spill = self.spill.as_ref().filter(|_| offset < self.spilled);
+        if let Some(file) = spill {
+            let mut bytes = vec![0u8; (self.spilled - offset) * 4];
+            match file.read_exact_at(&mut bytes, offset as u64 * 4) {
+                Ok(()) => samples.extend(
+                    bytes
+                        .chunks_exact(4)
+                        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]])),
+                ),
+                Err(e) => log::error!("Failed to read spilled recording: {}", e),
+            }
+        }
+        let start = offset.saturating_sub(self.spilled);
+        samples.extend_from_slice(self.memory.get(start..).unwrap_or_default());
+        samples
+    }
+}
+
+/// Spill older samples if memory holds more than the cap
+///
+/// Half the cap stays in memory so the file is written in large chunks. The
+/// oldest samples are copied under the lock and written after releasing it, so
+/// the audio callback never waits on the disk; they stay in memory until then.
+fn enforce_cap(buffer: &Mutex<SampleBuffer>) {
+    let (chunk, offset, file, generation) = {
+        let Ok(buffer) = buffer.lock() else { return };
+        if buffer.memory_cap == 0 || buffer.memory.len() <= buffer.memory_cap {
+            return;
+        }
+        let count = buffer.memory.len() - buffer.memory_cap / 2;
+        (
+            buffer.memory[..count].to_vec(),
+            buffer.spilled,
+            buffer.spill.clone(),
+            buffer.generation,
+        )
+    };
+
+    let bytes: Vec<u8> = chunk
+        .iter()
+        .flat_map(|sample| sample.to_le_bytes())
+        .collect();
+    let result = file
+        .map_or_else(|| tempfile::tempfile().map(Arc::new), Ok)
+        .and_then(|file| file.write_all_at(&bytes, offset as u64 * 4).map(|_| file));
+
+    let Ok(mut buffer) = buffer.lock() else {
+        return;
+    };
+    if buffer.generation != generation {
+        return;
+    }
+    match result {
+        Ok(file) => {
+            buffer.spill = Some(file);
+            buffer.memory.drain(..chunk.len());
+            buffer.spilled += chunk.len();
+        }
+        Err(e) => {
+            log::warn!(
+                "Failed to spill recording to disk, keeping it in memory: {}",
+                e
+            );
+            buffer.memory_cap = 0;
+        }
+    }
+}
+
 /// Shared recording state (Send + Sync safe)
 #[derive(Clone)]
 pub struct RecordingHandle {
-    /// Audio samples buffer (f32 normalized)
-    samples: Arc<Mutex<Vec<f32>>>,
+    /// Audio samples buffer (f32 normalized), partly spilled to disk past the memory cap
+    samples: Arc<Mutex<SampleBuffer>>,
     /// Recording flag
     is_recording: Arc<AtomicBool>,
+    /// While set, captured audio is dropped instead of recorded
+    is_paused: Arc<AtomicBool>,
     /// Current audio level (RMS, 0.0 to 1.0)
     current_level: Arc<Mutex<f32>>,
     /// Peak level
     peak_level: Arc<Mutex<f32>>,
+    /// Name of the device being recorded from
+    device_name: Arc<Mutex<String>>,
+    /// Calibrated factor applied to the level
+    level_gain: Arc<Mutex<f32>>,
+    /// Number of clipped samples in the current recording
+    clipped_samples: Arc<AtomicUsize>,
 }
 
 impl RecordingHandle {
     pub fn new() -> Self {
         Self {
-            samples: Arc::new(Mutex::new(Vec::new())),
+            samples: Arc::new(Mutex::new(SampleBuffer::default())),
             is_recording: Arc::new(AtomicBool::new(false)),
+            is_paused: Arc::new(AtomicBool::new(false)),
             current_level: Arc::new(Mutex::new(0.0)),
             peak_level: Arc::new(Mutex::new(0.0)),
+            device_name: Arc::new(Mutex::new(String::new())),
+            level_gain: Arc::new(Mutex::new(1.0)),
+            clipped_samples: Arc::new(AtomicUsize::new(0)),
         }
     }
 
@@ -90,6 +378,20 @@ impl RecordingHandle {
         self.is_recording.store(recording, Ordering::SeqCst);
     }
 
+    pub fn is_paused(&self) -> bool {
+        self.is_paused.load(Ordering::SeqCst)
+    }
+
+    /// Pause or resume capturing; the level drops to zero while paused
+    pub fn set_paused(&self, paused: bool) {
+        self.is_paused.store(paused, Ordering::SeqCst);
+        if paused {
+            if let Ok(mut level) = self.current_level.lock() {
+                *level = 0.0;
+            }
+        }
+    }
+
     pub fn clear_samples(&self) {
         if let Ok(mut samples) = self.samples.lock() {
             samples.clear();
@@ -97,12 +399,32 @@ impl RecordingHandle {
     }
 
     pub fn get_samples(&self) -> Vec<f32> {
-        self.samples.lock().map(|s| s.clone()).unwrap_or_default()
+        self.samples_since(0)
+    }
+
+    /// Keep at most `bytes` of audio in memory while recording (0 for no limit)
+    pub fn set_memory_cap(&self, bytes: usize) {
+        if let Ok(mut samples) = self.samples.lock() {
+            samples.memory_cap = bytes / std::mem::size_of::<f32>();
+        }
+    }
+
+    /// Number of samples recorded so far
+    pub fn sample_count(&self) -> usize {
+        self.samples.lock().map(|s| s.len()).unwrap_or_default()
+    }
+
+    /// Copy of the samples recorded after `offset`
+    pub fn samples_since(&self, offset: usize) -> Vec<f32> {
+        self.samples
+            .lock()
+            .map(|s| s.read_from(offset))
+            .unwrap_or_default()
     }
 
     pub fn append_samples(&self, new_samples: Vec<f32>) {
         if let Ok(mut samples) = self.samples.lock() {
-            samples.extend(new_samples);
+            samples.memory.extend(new_samples);
         }
     }
 
@@ -116,11 +438,14 @@ impl RecordingHandle {
         let sum_sq: f32 = new_samples.iter().map(|s| s * s).sum();
         let rms = (sum_sq / new_samples.len() as f32).sqrt();
 
-        // Scale to 0-1 range (typical speech is around 0.1-0.3 RMS)
-        let level = (rms * 3.0).min(1.0);
+        // Scale to 0-1 range with the device's calibrated gain
+        let gain = self.level_gain.lock().map(|g| *g).unwrap_or(1.0);
+        let level = (rms * LEVEL_SCALE * gain).min(1.0);
 
         // Find peak
         let peak = new_samples.iter().map(|s| s.abs()).fold(0.0f32, |a, b| a.max(b));
+        self.clipped_samples
+            .fetch_add(count_clipped(new_samples), Ordering::SeqCst);
 
         if let Ok(mut l) = self.current_level.lock() {
             *l = level;
@@ -130,12 +455,35 @@ impl RecordingHandle {
         }
     }
 
+    /// Set the calibrated level gain of the input device (1.0 when uncalibrated)
+    pub fn set_level_gain(&self, gain: f32) {
+        if let Ok(mut level_gain) = self.level_gain.lock() {
+            *level_gain = gain;
+        }
+    }
+
     /// Get current audio level
     pub fn get_level(&self) -> (f32, f32) {
         let level = self.current_level.lock().map(|l| *l).unwrap_or(0.0);
         let peak = self.peak_level.lock().map(|p| *p).unwrap_or(0.0);
         (level, peak)
     }
+
+    /// Whether the current or last recording clipped noticeably
+    pub fn is_clipping(&self) -> bool {
+        is_clipped(
+            self.clipped_samples.load(Ordering::SeqCst),
+            self.sample_count(),
+        )
+    }
+
+    /// Name of the device of the current or last recording
+    pub fn device_name(&self) -> String {
+        self.device_name
+            .lock()
+            .map(|name| name.clone())
+            .unwrap_or_default()
+    }
 }
 
 impl Default for RecordingHandle {
@@ -147,20 +495,156 @@ impl Default for RecordingHandle {
 /// Callback type for audio level updates
 pub type LevelCallback = Box<dyn Fn(f32) + Send + 'static>;
 
+/// How often a lost input is reopened
+const RECONNECT_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Open a device with the configuration the override selects
+fn open_device(
+    device_name: &str,
+    stream_override: &StreamOverride,
+) -> Result<(Device, SupportedStreamConfig)> {
+    let device = get_device_by_name(device_name)?;
+    let supported: Vec<SupportedStreamConfigRange> = match device.supported_input_configs() {
+        Ok(configs) => configs.collect(),
+        Err(e) => {
+            log::warn!("Failed to list supported input configs: {}", e);
+            Vec::new()
+        }
+    };
+    let config = select_config(device.default_input_config()?, &supported, stream_override)?;
+    Ok((device, config))
+}
+
+/// Whether a stream error means the input is gone (unplugged, JACK server stopped)
+fn is_stream_lost(err: &StreamError) -> bool {
+    match err {
+        StreamError::DeviceNotAvailable => true,
+        StreamError::BackendSpecific { err } => err.description.contains("shut down"),
+    }
+}
+
+/// Build and start an input stream feeding `handle`
+///
+/// `lost` is set when the input goes away while the stream runs.
+fn build_stream(
+    device: &Device,
+    config: SupportedStreamConfig,
+    buffer_size: Option<u32>,
+    handle: &RecordingHandle,
+    lost: Arc<AtomicBool>,
+) -> Result<Stream> {
+    let source_sample_rate = config.sample_rate().0;
+    let channels = config.channels() as usize;
+    let sample_format = config.sample_format();
+
+    let err_fn = move |err: StreamError| {
+        log::error!("Audio stream error: {}", err);
+        if is_stream_lost(&err) {
+            lost.store(true, Ordering::SeqCst);
+        }
+    };
+
+    let mut stream_config: StreamConfig = config.into();
+    if let Some(frames) = buffer_size {
+        stream_config.buffer_size = BufferSize::Fixed(frames);
+    }
+
+    let stream = match sample_format {
+        SampleFormat::F32 => {
+            let handle = handle.clone();
+            device.build_input_stream(
+                &stream_config,
+                move |data: &[f32], _: &_| {
+                    if handle.is_recording() && !handle.is_paused() {
+                        let processed = process_audio_data(data, source_sample_rate, channels);
+                        handle.update_level(&processed);
+                        if let Ok(mut samples) = handle.samples.lock() {
+                            samples.memory.extend(processed);
+                        }
+                    }
+                },
+                err_fn,
+                None,
+            )?
+        }
+        SampleFormat::I16 => {
+            let handle = handle.clone();
+            device.build_input_stream(
+                &stream_config,
+                move |data: &[i16], _: &_| {
+                    if handle.is_recording() && !handle.is_paused() {
+                        let float_data: Vec<f32> =
+                            data.iter().map(|&s| s as f32 / i16::MAX as f32).collect();
+                        let processed =
+                            process_audio_data(&float_data, source_sample_rate, channels);
+                        handle.update_level(&processed);
+                        if let Ok(mut samples) = handle.samples.lock() {
+                            samples.memory.extend(processed);
+                        }
+                    }
+                },
+                err_fn,
+                None,
+            )?
+        }
+        SampleFormat::U16 => {
+            let handle = handle.clone();
+            device.build_input_stream(
+                &stream_config,
+                move |data: &[u16], _: &_| {
+                    if handle.is_recording() && !handle.is_paused() {
+                        let float_data: Vec<f32> = data
+                            .iter()
+                            .map(|&s| (s as f32 / u16::MAX as f32) * 2.0 - 1.0)
+                            .collect();
+                        let processed =
+                            process_audio_data(&float_data, source_sample_rate, channels);
+                        handle.update_level(&processed);
+                        if let Ok(mut samples) = handle.samples.lock() {
+                            samples.memory.extend(processed);
+                        }
+                    }
+                },
+                err_fn,
+                None,
+            )?
+        }
+        _ => {
+            return Err(AppError::Audio(format!(
+                "Unsupported sample format: {:?}",
+                sample_format
+            )))
+        }
+    };
+    stream.play()?;
+
+    if backend() == AudioBackend::Jack {
+        let ports = JACK_PORTS.lock().map(|p| p.clone()).unwrap_or_default();
+        if !ports.is_empty() {
+            crate::jack_ports::connect(&ports)?;
+        }
+    }
+    Ok(stream)
+}
+
 /// Start recording in a separate thread (returns immediately)
 /// The stream is managed in the spawned thread
 /// Optional level_callback is called with audio level (0.0-1.0) periodically
+///
+/// When the input goes away mid-recording (a USB device unplugged, the JACK
+/// server restarted), the audio so far is kept and the device is reopened
+/// until it is back or the recording stops.
 pub fn start_recording(
     handle: RecordingHandle,
     device_name: &str,
+    stream_override: &StreamOverride,
     level_callback: Option<LevelCallback>,
 ) -> Result<()> {
     if handle.is_recording() {
         return Err(AppError::RecordingInProgress);
     }
 
-    let device = get_device_by_name(device_name)?;
-    let config = device.default_input_config()?;
+    let (device, config) = open_device(device_name, stream_override)?;
 
     log::info!(
         "Starting recording on device: {} (format: {:?}, rate: {}, channels: {})",
@@ -170,123 +654,76 @@ pub fn start_recording(
         config.channels()
     );
 
+    if let Ok(mut name) = handle.device_name.lock() {
+        *name = device.name().unwrap_or_default();
+    }
     handle.clear_samples();
+    handle.clipped_samples.store(0, Ordering::SeqCst);
+    handle.set_paused(false);
     handle.set_recording(true);
 
-    let source_sample_rate = config.sample_rate().0;
-    let channels = config.channels() as usize;
-    let sample_format = config.sample_format();
+    let device_name = device_name.to_string();
+    let stream_override = stream_override.clone();
     let handle_clone = handle.clone();
 
     // Spawn a thread to manage the stream (Stream is not Send)
     std::thread::spawn(move || {
-        let err_fn = |err| {
-            log::error!("Audio stream error: {}", err);
-        };
-
-        let stream_config: StreamConfig = config.into();
-
-        let samples_ref = handle_clone.samples.clone();
-        let is_recording_ref = handle_clone.is_recording.clone();
-        let level_handle = handle_clone.clone();
-        let level_handle2 = handle_clone.clone();
-        let level_handle3 = handle_clone.clone();
-
-        let stream_result = match sample_format {
-            SampleFormat::F32 => device.build_input_stream(
-                &stream_config,
-                move |data: &[f32], _: &_| {
-                    if is_recording_ref.load(Ordering::SeqCst) {
-                        let processed = process_audio_data(data, source_sample_rate, channels);
-                        level_handle.update_level(&processed);
-                        if let Ok(mut samples) = samples_ref.lock() {
-                            samples.extend(processed);
-                        }
-                    }
-                },
-                err_fn,
-                None,
-            ),
-            SampleFormat::I16 => {
-                let samples_ref = handle_clone.samples.clone();
-                let is_recording_ref = handle_clone.is_recording.clone();
-                device.build_input_stream(
-                    &stream_config,
-                    move |data: &[i16], _: &_| {
-                        if is_recording_ref.load(Ordering::SeqCst) {
-                            let float_data: Vec<f32> =
-                                data.iter().map(|&s| s as f32 / i16::MAX as f32).collect();
-                            let processed = process_audio_data(&float_data, source_sample_rate, channels);
-                            level_handle2.update_level(&processed);
-                            if let Ok(mut samples) = samples_ref.lock() {
-                                samples.extend(processed);
-                            }
-                        }
-                    },
-                    err_fn,
-                    None,
-                )
-            }
-            SampleFormat::U16 => {
-                let samples_ref = handle_clone.samples.clone();
-                let is_recording_ref = handle_clone.is_recording.clone();
-                device.build_input_stream(
-                    &stream_config,
-                    move |data: &[u16], _: &_| {
-                        if is_recording_ref.load(Ordering::SeqCst) {
-                            let float_data: Vec<f32> = data
-                                .iter()
-                                .map(|&s| (s as f32 / u16::MAX as f32) * 2.0 - 1.0)
-                                .collect();
-                            let processed = process_audio_data(&float_data, source_sample_rate, channels);
-                            level_handle3.update_level(&processed);
-                            if let Ok(mut samples) = samples_ref.lock() {
-                                samples.extend(processed);
-                            }
-                        }
-                    },
-                    err_fn,
-                    None,
-                )
-            }
-            _ => {
-                log::error!("Unsupported sample format: {:?}", sample_format);
-                return;
-            }
-        };
-
-        match stream_result {
-            Ok(stream) => {
-                if let Err(e) = stream.play() {
-                    log::error!("Failed to play stream: {}", e);
+        let lost = Arc::new(AtomicBool::new(false));
+        let buffer_size = stream_override.buffer_size;
+        let mut stream =
+            match build_stream(&device, config, buffer_size, &handle_clone, lost.clone()) {
+                Ok(stream) => Some(stream),
+                Err(e) => {
+                    log::error!("Failed to start stream: {}", e);
                     handle_clone.set_recording(false);
                     return;
                 }
-
-                // Keep the thread alive while recording
-                // Also emit level updates via callback
-                let mut last_level_update = std::time::Instant::now();
-                while handle_clone.is_recording() {
-                    std::thread::sleep(std::time::Duration::from_millis(30));
-
-                    // Emit level callback every ~100ms
-                    if last_level_update.elapsed() >= std::time::Duration::from_millis(100) {
-                        if let Some(ref cb) = level_callback {
-                            let (level, _peak) = handle_clone.get_level();
-                            cb(level);
-                        }
-                        last_level_update = std::time::Instant::now();
+            };
+
+        // Keep the thread alive while recording
+        // Also emit level updates via callback
+        let mut last_level_update = std::time::Instant::now();
+        let mut last_reconnect = std::time::Instant::now();
+        while handle_clone.is_recording() {
+            std::thread::sleep(std::time::Duration::from_millis(30));
+
+            // Spill here rather than in the audio callback, which must not block on disk
+            enforce_cap(&handle_clone.samples);
+
+            if lost.swap(false, Ordering::SeqCst) {
+                log::warn!("Audio input lost, keeping the audio so far and reconnecting");
+                stream = None;
+                last_reconnect = std::time::Instant::now();
+            }
+            if stream.is_none() && last_reconnect.elapsed() >= RECONNECT_INTERVAL {
+                last_reconnect = std::time::Instant::now();
+                let reopened =
+                    open_device(&device_name, &stream_override).and_then(|(device, config)| {
+                        build_stream(&device, config, buffer_size, &handle_clone, lost.clone())
+                    });
+                match reopened {
+                    Ok(reopened) => {
+                        log::info!("Audio input reconnected");
+                        stream = Some(reopened);
                     }
+                    Err(e) => log::debug!("Audio input still unavailable: {}", e),
                 }
-
-                // Stream will be dropped here, stopping the recording
-                log::info!("Recording thread finished");
             }
-            Err(e) => {
-                log::error!("Failed to build stream: {}", e);
-                handle_clone.set_recording(false);
+
+            // Emit level callback every ~100ms
+            if last_level_update.elapsed() >= std::time::Duration::from_millis(100)
+                && !handle_clone.is_paused()
+            {
+                if let Some(ref cb) = level_callback {
+                    let (level, _peak) = handle_clone.get_level();
+                    cb(level);
+                }
+                last_level_update = std::time::Instant::now();
             }
         }
+
+        // Stream will be dropped here, stopping the recording
+        log::info!("Recording thread finished");
     });
 
     Ok(())
@@ -299,6 +736,7 @@ pub fn stop_recording(handle: &RecordingHandle) -> Result<Vec<f32>> {
     }
 
     handle.set_recording(false);
+    handle.set_paused(false);
 
     // Give the recording thread time to finish
     std::thread::sleep(std::time::Duration::from_millis(100));
@@ -309,6 +747,61 @@ pub fn stop_recording(handle: &RecordingHandle) -> Result<Vec<f32>> {
     Ok(samples)
 }
 
+/// Number of samples pegged at full scale
+pub fn count_clipped(samples: &[f32]) -> usize {
+    samples.iter().filter(|s| s.abs() >= CLIP_LEVEL).count()
+}
+
+/// Whether enough of a recording clipped to distort the transcription
+pub fn is_clipped(clipped: usize, total: usize) -> bool {
+    total > 0 && clipped as f32 / total as f32 >= CLIPPED_FRACTION
+}
+
+/// Append silence so the samples last at least `min_ms`
+pub fn pad_with_silence(samples: &[f32], min_ms: u64) -> Cow<'_, [f32]> {
+    let min_len = (min_ms * WHISPER_SAMPLE_RATE as u64 / 1000) as usize;
+    if samples.len() >= min_len {
+        return Cow::Borrowed(samples);
+    }
+    let mut padded = samples.to_vec();
+    padded.resize(min_len, 0.0);
+    Cow::Owned(padded)
+}
+
+/// Length of the frames compared when looking for a pause to split at
+const SPLIT_FRAME_MS: usize = 100;
+
+/// How far before a chunk's limit a pause is looked for
+const SPLIT_SEARCH_SECS: usize = 30;
+
+/// Split audio into chunks of at most `max_samples`
+///
+/// Each cut is placed in the quietest frame of the last seconds before the
+/// limit (the latest one on a tie), so words are rarely cut in half.
+pub fn split_at_silence(samples: &[f32], max_samples: usize) -> Vec<Range<usize>> {
+    let frame = WHISPER_SAMPLE_RATE as usize * SPLIT_FRAME_MS / 1000;
+    let search = WHISPER_SAMPLE_RATE as usize * SPLIT_SEARCH_SECS;
+    let max_samples = max_samples.max(2 * frame);
+    let energy = |at: usize| samples[at..at + frame].iter().map(|s| s * s).sum::<f32>();
+
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    while samples.len() - start > max_samples {
+        let limit = start + max_samples;
+        let lowest = limit.saturating_sub(search).max(start + frame);
+        let cut = (lowest..limit - frame + 1)
+            .step_by(frame)
+            .rev()
+            .map(|at| (at, energy(at)))
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map_or(limit, |(at, _)| at + frame / 2);
+        chunks.push(start..cut);
+        start = cut;
+    }
+    chunks.push(start..samples.len());
+    chunks
+}
+
 /// Process incoming audio data: convert to mono and resample to 16kHz
 fn process_audio_data(data: &[f32], source_rate: u32, channels: usize) -> Vec<f32> {
     // Convert to mono by averaging channels
@@ -370,6 +863,25 @@ pub fn save_wav(samples: &[f32], path: &PathBuf) -> Result<()> {
     Ok(())
 }
 
+/// Encode audio samples as an in-memory 16-bit WAV file (for upload)
+pub fn encode_wav(samples: &[f32]) -> Result<Vec<u8>> {
+    let spec = WavSpec {
+        channels: 1,
+        sample_rate: WHISPER_SAMPLE_RATE,
+        bits_per_sample: 16,
+        sample_format: HoundSampleFormat::Int,
+    };
+
+    let mut buffer = std::io::Cursor::new(Vec::new());
+    let mut writer = WavWriter::new(&mut buffer, spec)?;
+    for &sample in samples {
+        writer.write_sample((sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16)?;
+    }
+    writer.finalize()?;
+
+    Ok(buffer.into_inner())
+}
+
 /// Load audio samples from a WAV file (for reprocessing)
 pub fn load_wav(path: &PathBuf) -> Result<Vec<f32>> {
     let mut reader = hound::WavReader::open(path)?;
@@ -432,6 +944,101 @@ mod tests {
         assert!(resampled.len() > samples.len() / 4);
     }
 
+    #[test]
+    fn test_select_config() {
+        let default = SupportedStreamConfig::new(
+            2,
+            SampleRate(48000),
+            SupportedBufferSize::Unknown,
+            SampleFormat::F32,
+        );
+        let range = |channels, format| {
+            SupportedStreamConfigRange::new(
+                channels,
+                SampleRate(8000),
+                SampleRate(48000),
+                SupportedBufferSize::Unknown,
+                format,
+            )
+        };
+        let supported = [
+            range(1, SampleFormat::I16),
+            range(2, SampleFormat::I16),
+            range(2, SampleFormat::F32),
+        ];
+
+        let unchanged = select_config(default.clone(), &supported, &StreamOverride::default());
+        assert_eq!(unchanged.unwrap(), default);
+
+        let stream_override = StreamOverride {
+            sample_rate: Some(16000),
+            sample_format: Some("i16".to_string()),
+            buffer_size: None,
+        };
+        let config = select_config(default.clone(), &supported, &stream_override).unwrap();
+        assert_eq!(config.sample_rate(), SampleRate(16000));
+        assert_eq!(config.sample_format(), SampleFormat::I16);
+        assert_eq!(config.channels(), 2);
+
+        let unsupported = StreamOverride {
+            sample_rate: Some(96000),
+            ..StreamOverride::default()
+        };
+        assert!(select_config(default.clone(), &supported, &unsupported).is_err());
+        let unknown = StreamOverride {
+            sample_format: Some("i24".to_string()),
+            ..StreamOverride::default()
+        };
+        assert!(select_config(default, &supported, &unknown).is_err());
+    }
+
+    #[test]
+    fn test_pick_device() {
+        let names = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let preferences = names(&["USB Mic", "Headset"]);
+
+        assert_eq!(
+            pick_device(&preferences, &names(&["Built-in", "Headset"])),
+            "Headset"
+        );
+        assert_eq!(
+            pick_device(&preferences, &names(&["USB Mic", "Headset"])),
+            "USB Mic"
+        );
+        assert_eq!(pick_device(&preferences, &names(&["Built-in"])), "");
+        // The default device stops the search
+        assert_eq!(
+            pick_device(&names(&["", "Headset"]), &names(&["Headset"])),
+            ""
+        );
+    }
+
+    #[test]
+    fn test_sample_buffer_spills_past_cap() {
+        let buffer = Mutex::new(SampleBuffer {
+            memory_cap: 100,
+            ..SampleBuffer::default()
+        });
+        let samples: Vec<f32> = (0..250).map(|i| i as f32).collect();
+        for chunk in samples.chunks(30) {
+            buffer.lock().unwrap().memory.extend_from_slice(chunk);
+            enforce_cap(&buffer);
+        }
+
+        let mut buffer = buffer.into_inner().unwrap();
+
+        assert!(buffer.spilled > 0);
+        assert!(buffer.memory.len() <= 100);
+        assert_eq!(buffer.len(), 250);
+        assert_eq!(buffer.read_from(0), samples);
+        assert_eq!(buffer.read_from(240), samples[240..]);
+        assert_eq!(buffer.read_from(10), samples[10..]);
+
+        buffer.clear();
+        assert_eq!(buffer.len(), 0);
+        assert!(buffer.read_from(0).is_empty());
+    }
+
     #[test]
     fn test_calculate_duration() {
         // 16000 samples at 16kHz = 1 second = 1000 ms
@@ -439,4 +1046,47 @@ mod tests {
         // 8000 samples = 500 ms
         assert_eq!(calculate_duration_ms(8000), 500);
     }
+
+    #[test]
+    fn test_pad_with_silence() {
+        let samples = vec![0.5; 8000];
+        let padded = pad_with_silence(&samples, 1500);
+        assert_eq!(padded.len(), 24000);
+        assert_eq!(padded[7999], 0.5);
+        assert_eq!(padded[8000], 0.0);
+
+        assert!(matches!(pad_with_silence(&samples, 500), Cow::Borrowed(_)));
+        assert!(matches!(pad_with_silence(&samples, 0), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn test_split_at_silence() {
+        let rate = WHISPER_SAMPLE_RATE as usize;
+        // 100 s of tone with a pause from 70 s to 71 s
+        let mut samples = vec![0.3; 100 * rate];
+        samples[70 * rate..71 * rate].fill(0.0);
+
+        let chunks = split_at_silence(&samples, 90 * rate);
+        assert_eq!(chunks.len(), 2);
+        assert!((70 * rate..71 * rate).contains(&chunks[0].end));
+        assert_eq!(chunks[1], chunks[0].end..samples.len());
+
+        assert_eq!(
+            split_at_silence(&samples, 200 * rate),
+            vec![0..samples.len()]
+        );
+        // Without a pause the cuts stay close to the limit
+        let chunks = split_at_silence(&samples, 10 * rate);
+        assert!(chunks.len() <= 12);
+        assert!(chunks.iter().all(|c| c.len() <= 10 * rate));
+        assert_eq!(chunks.last().unwrap().end, samples.len());
+    }
+
+    #[test]
+    fn test_clipping() {
+        assert_eq!(count_clipped(&[0.5, -1.0, 0.995, -0.2, 1.0]), 3);
+        assert!(!is_clipped(0, 0));
+        assert!(!is_clipped(5, 16000));
+        assert!(is_clipped(16, 16000));
+    }
 }
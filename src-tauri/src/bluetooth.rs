@@ -0,0 +1,241 @@
+//! Bluetooth headset profiles
+//!
+//! A Bluetooth headset playing in the high-quality A2DP profile has no
+//! microphone. When recording starts, the sound server switches it to the
+//! headset profile (HSP/HFP), which takes a moment and garbles the first
+//! seconds. This detects that case from `pactl list cards` so the user can be
+//! warned, and can switch the profile before capture starts and back after.
+
+use crate::error::{AppError, Result};
+use std::process::{Command, Output};
+use std::time::{Duration, Instant};
+
+/// How long to wait for the headset microphone after switching
+const SWITCH_TIMEOUT: Duration = Duration::from_millis(2000);
+
+/// A profile offered by a card
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CardProfile {
+    pub name: String,
+    pub sources: u32,
+    pub available: bool,
+}
+
+/// A Bluetooth card as listed by `pactl list cards`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BluetoothCard {
+    /// Card name, e.g. `bluez_card.00_1B_66_AA_BB_CC`
+    pub name: String,
+    /// Device name shown to the user
+    pub description: String,
+    pub active_profile: String,
+    pub profiles: Vec<CardProfile>,
+}
+
+impl BluetoothCard {
+    /// Device address as it appears in sink and source names
+    pub fn address(&self) -> &str {
+        self.name.trim_start_matches("bluez_card.")
+    }
+
+    /// Profile with a microphone to switch to, preferring wideband (mSBC) speech
+    pub fn headset_profile(&self) -> Option<&str> {
+        let headset = self
+            .profiles
+            .iter()
+            .filter(|p| p.available && p.sources > 0 && p.name != "off");
+        headset
+            .clone()
+            .find(|p| p.name.contains("msbc"))
+            .or_else(|| headset.clone().next())
+            .map(|p| p.name.as_str())
+    }
+
+    /// Whether the active profile is playback only (A2DP)
+    pub fn is_playback_only(&self) -> bool {
+        self.active_profile != "off"
+            && self
+                .profiles
+                .iter()
+                .any(|p| p.name == self.active_profile && p.sources == 0)
+    }
+}
+
+/// Parse `pactl list cards`, keeping the Bluetooth cards
+pub fn parse_cards(output: &str) -> Vec<BluetoothCard> {
+    let mut cards = Vec::new();
+    let mut current: Option<BluetoothCard> = None;
+    let mut in_profiles = false;
+
+    for line in output.lines() {
+        let trimmed = line.trim();
+        if line.starts_with("Card #") {
+            cards.extend(current.take());
+            current = Some(BluetoothCard {
+                name: String::new(),
+                description: String::new(),
+                active_profile: String::new(),
+                profiles: Vec::new(),
+            });
+            in_profiles = false;
+            continue;
+        }
+        let card = match current.as_mut() {
+            Some(card) => card,
+            None => continue,
+        };
+
+        if let Some(name) = trimmed.strip_prefix("Name: ") {
+            card.name = name.to_string();
+        } else if let Some(profile) = trimmed.strip_prefix("Active Profile: ") {
+            card.active_profile = profile.to_string();
+        } else if let Some(description) = trimmed.strip_prefix("device.description = ") {
+            card.description = description.trim_matches('"').to_string();
+        } else if trimmed == "Profiles:" {
+            in_profiles = true;
+        } else if in_profiles && line.starts_with("\t\t") {
+            if let Some((name, details)) = trimmed.split_once(": ") {
+                card.profiles.push(CardProfile {
+                    name: name.to_string(),
+                    sources: count_after(details, "sources: "),
+                    available: !details.contains("available: no"),
+                });
+            }
+        } else if !line.starts_with("\t\t") {
+            in_profiles = false;
+        }
+    }
+    cards.extend(current);
+    cards.retain(|card| card.name.starts_with("bluez_card."));
+    cards
+}
+
+/// The number following `label` in a profile description
+fn count_after(details: &str, label: &str) -> u32 {
+    details
+        .split_once(label)
+        .and_then(|(_, rest)| {
+            rest.split(|c: char| !c.is_ascii_digit())
+                .next()
+                .and_then(|n| n.parse().ok())
+        })
+        .unwrap_or(0)
+}
+
+/// Run `pactl` without blocking the async runtime
+async fn pactl(args: &[&str]) -> Result<String> {
+    let output = tokio::process::Command::new("pactl")
+        .args(args)
+        .output()
+        .await;
+    pactl_output(args, output)
+}
+
+fn pactl_output(args: &[&str], output: std::io::Result<Output>) -> Result<String> {
+    let output = output.map_err(|e| AppError::Audio(format!("pactl not available: {}", e)))?;
+    if !output.status.success() {
+        return Err(AppError::Audio(format!(
+            "pactl {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// The headset that will switch profiles when recording starts
+///
+/// That is a Bluetooth card in A2DP that can switch to a headset profile and
+/// is the default output, which the sound server moves to the headset
+/// profile once a recording opens.
+pub async fn pending_switch() -> Option<BluetoothCard> {
+    let cards = parse_cards(&pactl(&["list", "cards"]).await.ok()?);
+    let default_sink = pactl(&["get-default-sink"]).await.ok()?;
+    cards.into_iter().find(|card| {
+        card.is_playback_only()
+            && card.headset_profile().is_some()
+            && default_sink.contains(card.address())
+    })
+}
+
+/// Switch the headset to its microphone profile and wait until the microphone is up
+///
+/// Returns the profile to restore afterwards.
+pub async fn switch_to_headset(card: &BluetoothCard) -> Result<String> {
+    let profile = card
+        .headset_profile()
+        .ok_or_else(|| AppError::Audio(format!("{} has no headset profile", card.description)))?;
+    pactl(&["set-card-profile", &card.name, profile]).await?;
+    log::info!("Switched {} to {}", card.description, profile);
+
+    let started = Instant::now();
+    while started.elapsed() < SWITCH_TIMEOUT {
+        let sources = pactl(&["list", "short", "sources"])
+            .await
+            .unwrap_or_default();
+        if sources
+            .lines()
+            .any(|line| line.contains(card.address()) && !line.contains(".monitor"))
+        {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    Ok(card.active_profile.clone())
+}
+
+/// Put a card back into the profile it had before recording
+///
+/// Blocks, so it also completes while the app shuts down.
+pub fn restore(card_name: &str, profile: &str) {
+    let args = ["set-card-profile", card_name, profile];
+    match pactl_output(&args, Command::new("pactl").args(args).output()) {
+        Ok(_) => log::info!("Restored {} to {}", card_name, profile),
+        Err(e) => log::warn!("Failed to restore Bluetooth profile: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CARDS: &str = "Card #41
+\tName: alsa_card.pci-0000_00_1f.3
+\tDriver: alsa
+\tProfiles:
+\t\tinput:analog-stereo: Analog Stereo Input (sinks: 0, sources: 1, priority: 65, available: yes)
+\tActive Profile: input:analog-stereo
+Card #42
+\tName: bluez_card.00_1B_66_AA_BB_CC
+\tDriver: module-bluez5-device.c
+\tProperties:
+\t\tdevice.description = \"WH-1000XM4\"
+\tProfiles:
+\t\toff: Off (sinks: 0, sources: 0, priority: 0, available: yes)
+\t\ta2dp-sink: High Fidelity Playback (A2DP Sink) (sinks: 1, sources: 0, priority: 18, available: yes)
+\t\theadset-head-unit: Headset Head Unit (HSP/HFP) (sinks: 1, sources: 1, priority: 1, available: yes)
+\t\theadset-head-unit-msbc: Headset Head Unit (HSP/HFP, codec mSBC) (sinks: 1, sources: 1, priority: 2, available: yes)
+\tActive Profile: a2dp-sink
+";
+
+    #[test]
+    fn test_parse_cards() {
+        let cards = parse_cards(CARDS);
+        assert_eq!(cards.len(), 1);
+        let card = &cards[0];
+        assert_eq!(card.description, "WH-1000XM4");
+        assert_eq!(card.address(), "00_1B_66_AA_BB_CC");
+        assert_eq!(card.profiles.len(), 4);
+        assert!(card.is_playback_only());
+        assert_eq!(card.headset_profile(), Some("headset-head-unit-msbc"));
+    }
+
+    #[test]
+    fn test_headset_active() {
+        let mut card = parse_cards(CARDS).remove(0);
+        card.active_profile = "headset-head-unit".to_string();
+        assert!(!card.is_playback_only());
+        card.active_profile = "off".to_string();
+        assert!(!card.is_playback_only());
+    }
+}
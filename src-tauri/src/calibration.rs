@@ -0,0 +1,129 @@
+//! Input level calibration
+//!
+//! Records a few seconds of the user speaking, measures the speech level and
+//! the noise floor, and recommends a gain for the level meter so normal
+//! speech lands in the same range on every microphone. The result is stored
+//! per device in `input_calibration`.
+
+use crate::audio::{self, RecordingHandle, StreamOverride, LEVEL_SCALE, WHISPER_SAMPLE_RATE};
+use crate::error::{AppError, Result};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Level normal speech should reach on the meter
+const TARGET_LEVEL: f32 = 0.6;
+
+/// Range of gains that can be recommended
+const MIN_GAIN: f32 = 0.25;
+const MAX_GAIN: f32 = 8.0;
+
+/// Below this RMS the input is considered silent
+const SILENCE_RMS: f32 = 0.001;
+
+/// Length of the windows the level is measured over (100 ms)
+const WINDOW: usize = WHISPER_SAMPLE_RATE as usize / 10;
+
+/// Calibration of one input device
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InputCalibration {
+    /// Factor applied to the input level
+    pub gain: f32,
+    /// Typical RMS while speaking
+    pub speech_rms: f32,
+    /// RMS of the background noise
+    pub noise_rms: f32,
+    /// Whether the input clipped while measuring
+    pub clipping: bool,
+    pub measured_at: String,
+}
+
+/// RMS of every full window
+fn window_levels(samples: &[f32]) -> Vec<f32> {
+    samples
+        .chunks_exact(WINDOW)
+        .map(|window| (window.iter().map(|s| s * s).sum::<f32>() / WINDOW as f32).sqrt())
+        .collect()
+}
+
+/// Value at `fraction` (0.0 to 1.0) of the sorted levels
+fn percentile(sorted: &[f32], fraction: f32) -> f32 {
+    let index = ((sorted.len() - 1) as f32 * fraction).round() as usize;
+    sorted[index]
+}
+
+/// Measure speech and noise levels and recommend a gain
+pub fn analyze(samples: &[f32]) -> Result<InputCalibration> {
+    let mut levels = window_levels(samples);
+    if levels.is_empty() {
+        return Err(AppError::Audio("The recording is too short".to_string()));
+    }
+    levels.sort_by(|a, b| a.total_cmp(b));
+
+    let speech_rms = percentile(&levels, 0.9);
+    if speech_rms < SILENCE_RMS {
+        return Err(AppError::Audio(
+            "No speech detected; check that the microphone is not muted".to_string(),
+        ));
+    }
+    let noise_rms = percentile(&levels, 0.1);
+    let gain = (TARGET_LEVEL / (speech_rms * LEVEL_SCALE)).clamp(MIN_GAIN, MAX_GAIN);
+    let clipping = audio::count_clipped(samples) > 0;
+
+    Ok(InputCalibration {
+        gain,
+        speech_rms,
+        noise_rms,
+        clipping,
+        measured_at: chrono::Utc::now().to_rfc3339(),
+    })
+}
+
+/// Record from `device_name` for `duration` and analyze it
+///
+/// Blocks for the whole duration; run it off the async runtime.
+pub fn measure(
+    device_name: &str,
+    stream_override: &StreamOverride,
+    duration: Duration,
+) -> Result<InputCalibration> {
+    let handle = RecordingHandle::new();
+    audio::start_recording(handle.clone(), device_name, stream_override, None)?;
+    std::thread::sleep(duration);
+    let samples = audio::stop_recording(&handle)?;
+    analyze(&samples)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tone(amplitude: f32, windows: usize) -> Vec<f32> {
+        (0..WINDOW * windows)
+            .map(|i| if i % 2 == 0 { amplitude } else { -amplitude })
+            .collect()
+    }
+
+    #[test]
+    fn test_analyze() {
+        // Quiet background with speech in between
+        let mut samples = tone(0.002, 10);
+        samples.extend(tone(0.05, 20));
+        samples.extend(tone(0.002, 10));
+
+        let calibration = analyze(&samples).unwrap();
+        assert!((calibration.speech_rms - 0.05).abs() < 1e-4);
+        assert!((calibration.noise_rms - 0.002).abs() < 1e-4);
+        assert!((calibration.gain - 4.0).abs() < 0.01);
+        assert!(!calibration.clipping);
+    }
+
+    #[test]
+    fn test_analyze_limits() {
+        assert!(analyze(&tone(0.0, 10)).is_err());
+        assert!(analyze(&[0.1; 10]).is_err());
+
+        let loud = analyze(&tone(1.0, 10)).unwrap();
+        assert_eq!(loud.gain, MIN_GAIN);
+        assert!(loud.clipping);
+    }
+}
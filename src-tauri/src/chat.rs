@@ -0,0 +1,206 @@
+//! Slack and Matrix posting
+//!
+//! Modes with `post_to` send their final output to team chat after pasting,
+//! e.g. a Standup mode posting to the team channel. Slack uses an incoming
+//! webhook whose URL is saved as `slack` in the keyring; Matrix posts to a
+//! room on `matrix_homeserver` with the access token saved as `matrix`.
+//! Markdown outputs are sent to Matrix with their HTML rendering as well.
+
+use crate::error::{AppError, Result};
+use crate::i18n::t_args;
+use crate::notify;
+use crate::providers::{http_client, is_local_url};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+/// Chat a mode's output is posted to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "service", rename_all = "lowercase")]
+pub enum ChatTarget {
+    /// The channel of the Slack incoming webhook
+    Slack,
+    /// A Matrix room, e.g. `!abc123:matrix.org`
+    Matrix { room_id: String },
+}
+
+impl ChatTarget {
+    /// Name the webhook URL or access token is saved under in the keyring
+    pub fn key_name(&self) -> &'static str {
+        match self {
+            ChatTarget::Slack => "slack",
+            ChatTarget::Matrix { .. } => "matrix",
+        }
+    }
+
+    /// Whether posts stay on the local network: only for a Matrix
+    /// homeserver on this machine or a private address
+    pub fn is_local(&self, matrix_homeserver: &str) -> bool {
+        matches!(self, ChatTarget::Matrix { .. }) && is_local_url(matrix_homeserver)
+    }
+
+    /// Service name for messages
+    pub fn label(&self) -> &'static str {
+        match self {
+            ChatTarget::Slack => "Slack",
+            ChatTarget::Matrix { .. } => "Matrix",
+        }
+    }
+}
+
+/// A post and the credentials to send it with
+#[derive(Debug, Clone)]
+pub struct Post {
+    pub target: ChatTarget,
+    /// Slack webhook URL or Matrix access token
+    pub secret: Option<String>,
+    pub matrix_homeserver: String,
+}
+
+/// Body of a Slack webhook message
+fn slack_body(text: &str) -> Value {
+    json!({ "text": text })
+}
+
+/// Content of a Matrix `m.room.message` event
+fn matrix_body(text: &str, html: Option<&str>) -> Value {
+    let mut body = json!({ "msgtype": "m.text", "body": text });
+    if let Some(html) = html {
+        body["format"] = json!("org.matrix.custom.html");
+        body["formatted_body"] = json!(html);
+    }
+    body
+}
+
+/// URL a Matrix message is sent to, with the room id and transaction id escaped
+fn matrix_url(homeserver: &str, room_id: &str, txn_id: &str) -> Result<Url> {
+    let mut url = Url::parse(homeserver.trim())
+        .map_err(|e| AppError::Config(format!("Invalid Matrix homeserver URL: {}", e)))?;
+    url.path_segments_mut()
+        .map_err(|_| AppError::Config("Invalid Matrix homeserver URL".to_string()))?
+        .pop_if_empty()
+        .extend([
+            "_matrix",
+            "client",
+            "v3",
+            "rooms",
+            room_id.trim(),
+            "send",
+            "m.room.message",
+            txn_id,
+        ]);
+    Ok(url)
+}
+
+/// Post `text` to the target; `html` is its rendering for clients that show it
+pub async fn send(post: &Post, text: &str, html: Option<&str>) -> Result<()> {
+    let secret = post.secret.as_deref().ok_or_else(|| {
+        AppError::Config(format!(
+            "No {} credentials saved as `{}` in the keyring",
+            post.target.label(),
+            post.target.key_name()
+        ))
+    })?;
+    let request = match &post.target {
+        ChatTarget::Slack => http_client().post(secret).json(&slack_body(text)),
+        ChatTarget::Matrix { room_id } => {
+            let txn_id = uuid::Uuid::new_v4().to_string();
+            http_client()
+                .put(matrix_url(&post.matrix_homeserver, room_id, &txn_id)?)
+                .bearer_auth(secret)
+                .json(&matrix_body(text, html))
+        }
+    };
+
+    // The Slack webhook URL is the secret, so it is left out of errors
+    let response = request
+        .send()
+        .await
+        .map_err(|e| AppError::Http(e.without_url()))?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let text = response.text().await.unwrap_or_default();
+        return Err(AppError::Provider(format!(
+            "{} error {}: {}",
+            post.target.label(),
+            status,
+            text
+        )));
+    }
+    Ok(())
+}
+
+/// Post `text` to each target in the background, notifying about failures
+pub fn spawn(posts: Vec<Post>, text: String, html: Option<String>) {
+    tauri::async_runtime::spawn(async move {
+        for post in posts {
+            match send(&post, &text, html.as_deref()).await {
+                Ok(()) => log::info!("Posted output to {}", post.target.label()),
+                Err(e) => {
+                    log::error!("Failed to post output: {}", e);
+                    notify::send(
+                        &t_args("chat.failed", &[("service", post.target.label())]),
+                        &e.to_string(),
+                    );
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_target_from_mode_json() {
+        let targets: Vec<ChatTarget> = serde_json::from_str(
+            r#"[{"service": "slack"}, {"service": "matrix", "room_id": "!abc:matrix.org"}]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            targets,
+            vec![
+                ChatTarget::Slack,
+                ChatTarget::Matrix {
+                    room_id: "!abc:matrix.org".to_string()
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn test_is_local() {
+        let matrix = ChatTarget::Matrix {
+            room_id: "!abc:example.org".to_string(),
+        };
+        assert!(matrix.is_local("http://192.168.1.5:8008"));
+        assert!(!matrix.is_local("https://matrix.org"));
+        assert!(!ChatTarget::Slack.is_local("http://localhost:8008"));
+    }
+
+    #[test]
+    fn test_matrix_url_escapes_room_id() {
+        let url = matrix_url("https://matrix.example.org/", "!abc:example.org", "t1").unwrap();
+        assert_eq!(
+            url.as_str(),
+            "https://matrix.example.org/_matrix/client/v3/rooms/!abc:example.org/send/m.room.message/t1"
+        );
+        let url = matrix_url("https://example.org", "#team/room", "t1").unwrap();
+        assert!(url.as_str().contains("/rooms/%23team%2Froom/send/"));
+    }
+
+    #[test]
+    fn test_matrix_body() {
+        assert_eq!(
+            matrix_body("- done", Some("<ul><li>done</li></ul>")),
+            json!({
+                "msgtype": "m.text",
+                "body": "- done",
+                "format": "org.matrix.custom.html",
+                "formatted_body": "<ul><li>done</li></ul>"
+            })
+        );
+        assert_eq!(matrix_body("hi", None)["format"], Value::Null);
+    }
+}
@@ -0,0 +1,192 @@
+//! Command-line argument handling
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+/// Action requested on the command line, forwarded to a running instance
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CliAction {
+    /// Show the main window
+    Show,
+    /// Start or stop recording
+    Toggle,
+    /// Abort in-flight processing
+    Cancel,
+    /// Toggle incognito dictation
+    Incognito,
+    /// Record an instruction that refines the last output
+    Refine,
+    /// Pause or resume the current recording
+    Pause,
+    /// Switch to the next mode (e.g. bound to scrolling up on a panel module)
+    NextMode,
+    /// Switch to the previous mode
+    PreviousMode,
+}
+
+/// Options parsed from the command line
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CliOptions {
+    /// Run only the tray, hotkey and processing pipeline without webview windows
+    pub headless: bool,
+    /// Run as a long-lived background service the UI attaches to (implies headless)
+    pub daemon: bool,
+    /// Action to perform (used when forwarded to an already running instance)
+    pub action: Option<CliAction>,
+}
+
+/// Parse command-line arguments (excluding the program name)
+pub fn parse_args<I: IntoIterator<Item = String>>(args: I) -> CliOptions {
+    let mut options = CliOptions {
+        headless: std::env::var("WHISPERTRAY_HEADLESS")
+            .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+            .unwrap_or(false),
+        daemon: false,
+        action: None,
+    };
+
+    for arg in args {
+        match arg.as_str() {
+            "--headless" => options.headless = true,
+            "--daemon" => {
+                options.daemon = true;
+                options.headless = true;
+            }
+            "--show" => options.action = Some(CliAction::Show),
+            "--toggle" => options.action = Some(CliAction::Toggle),
+            "--cancel" => options.action = Some(CliAction::Cancel),
+            "--incognito" => options.action = Some(CliAction::Incognito),
+            "--refine" => options.action = Some(CliAction::Refine),
+            "--pause" => options.action = Some(CliAction::Pause),
+            "--next-mode" => options.action = Some(CliAction::NextMode),
+            "--previous-mode" => options.action = Some(CliAction::PreviousMode),
+            other => log::warn!("Ignoring unknown argument: {}", other),
+        }
+    }
+
+    options
+}
+
+/// `Some(follow)` for `status [--follow]`, which prints the running instance's status
+pub fn status_command<I: IntoIterator<Item = String>>(args: I) -> Option<bool> {
+    let mut args = args.into_iter();
+    if args.next()? != "status" {
+        return None;
+    }
+    Some(args.any(|arg| arg == "--follow" || arg == "-f"))
+}
+
+/// Handle a launch of a second instance: run its action in this (primary) instance
+pub fn handle_second_instance(handle: &AppHandle, args: Vec<String>) {
+    let options = parse_args(args.into_iter().skip(1));
+    let action = options.action.unwrap_or(CliAction::Show);
+    log::info!("Second instance started, running {:?}", action);
+    run_action(handle, action);
+}
+
+/// Run a command-line action
+pub fn run_action(handle: &AppHandle, action: CliAction) {
+    match action {
+        CliAction::Show => {
+            if let Err(e) = crate::daemon::attach_ui(handle) {
+                log::error!("Failed to open the main window: {}", e);
+            }
+        }
+        CliAction::Toggle => crate::hotkey::toggle_recording(handle),
+        CliAction::Refine => crate::hotkey::toggle_refinement(handle),
+        CliAction::Cancel => {
+            if let Some(processing) =
+                handle.try_state::<std::sync::Arc<crate::pipeline::ProcessingControl>>()
+            {
+                processing.cancel();
+            }
+        }
+        CliAction::Incognito => {
+            let handle = handle.clone();
+            tauri::async_runtime::spawn(async move {
+                let _ = crate::tray::toggle_incognito(&handle).await;
+            });
+        }
+        CliAction::Pause => {
+            let handle = handle.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = crate::tray::toggle_pause(&handle).await {
+                    log::warn!("Cannot pause: {}", e);
+                }
+            });
+        }
+        CliAction::NextMode | CliAction::PreviousMode => {
+            let handle = handle.clone();
+            tauri::async_runtime::spawn(async move {
+                let forward = action == CliAction::NextMode;
+                if let Err(e) = crate::tray::cycle_mode(&handle, forward).await {
+                    log::warn!("Cannot switch mode: {}", e);
+                }
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_headless() {
+        assert!(parse_args(args(&["--headless"])).headless);
+        let daemon = parse_args(args(&["--daemon"]));
+        assert!(daemon.daemon && daemon.headless);
+    }
+
+    #[test]
+    fn test_parse_actions() {
+        assert_eq!(
+            parse_args(args(&["--toggle"])).action,
+            Some(CliAction::Toggle)
+        );
+        assert_eq!(parse_args(args(&["--show"])).action, Some(CliAction::Show));
+        assert_eq!(
+            parse_args(args(&["--cancel"])).action,
+            Some(CliAction::Cancel)
+        );
+        assert_eq!(
+            parse_args(args(&["--incognito"])).action,
+            Some(CliAction::Incognito)
+        );
+        assert_eq!(
+            parse_args(args(&["--refine"])).action,
+            Some(CliAction::Refine)
+        );
+        assert_eq!(
+            parse_args(args(&["--pause"])).action,
+            Some(CliAction::Pause)
+        );
+        assert_eq!(
+            parse_args(args(&["--next-mode"])).action,
+            Some(CliAction::NextMode)
+        );
+        assert_eq!(
+            parse_args(args(&["--previous-mode"])).action,
+            Some(CliAction::PreviousMode)
+        );
+        assert_eq!(parse_args(Vec::new()).action, None);
+    }
+
+    #[test]
+    fn test_status_command() {
+        assert_eq!(status_command(args(&["status"])), Some(false));
+        assert_eq!(status_command(args(&["status", "--follow"])), Some(true));
+        assert_eq!(status_command(args(&["--toggle"])), None);
+        assert_eq!(status_command(Vec::new()), None);
+    }
+
+    #[test]
+    fn test_unknown_args_ignored() {
+        assert_eq!(parse_args(args(&["--unknown"])), parse_args(Vec::new()));
+    }
+}
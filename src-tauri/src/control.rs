@@ -0,0 +1,54 @@
+//! Fallback control window for desktops without a system tray
+//!
+//! GNOME without the AppIndicator extension has no StatusNotifier host, so the
+//! tray icon never appears. In that case a small always-available window with
+//! a record button, mode picker and status is shown instead.
+
+use crate::error::Result;
+use log::info;
+use std::process::Command;
+use tauri::{AppHandle, Manager, WebviewUrl, WebviewWindowBuilder};
+
+const CONTROL_LABEL: &str = "control";
+
+/// Check whether a StatusNotifier host is available on the session bus
+pub fn tray_supported() -> bool {
+    let output = Command::new("dbus-send")
+        .args([
+            "--session",
+            "--print-reply",
+            "--dest=org.freedesktop.DBus",
+            "/org/freedesktop/DBus",
+            "org.freedesktop.DBus.NameHasOwner",
+            "string:org.kde.StatusNotifierWatcher",
+        ])
+        .output();
+
+    match output {
+        Ok(output) if output.status.success() => {
+            String::from_utf8_lossy(&output.stdout).contains("boolean true")
+        }
+        // Without dbus-send we cannot tell; assume the tray works
+        _ => true,
+    }
+}
+
+/// Show the control window, creating it if needed
+pub fn show_control_window(handle: &AppHandle) -> Result<()> {
+    if let Some(window) = handle.get_webview_window(CONTROL_LABEL) {
+        let _ = window.show();
+        let _ = window.set_focus();
+        return Ok(());
+    }
+
+    WebviewWindowBuilder::new(handle, CONTROL_LABEL, WebviewUrl::App("/control".into()))
+        .title("WhisperTray")
+        .inner_size(320.0, 180.0)
+        .resizable(false)
+        .always_on_top(true)
+        .visible(true)
+        .build()?;
+
+    info!("Control window created (no system tray available)");
+    Ok(())
+}
@@ -0,0 +1,290 @@
+//! Learning from user corrections
+//!
+//! When the user edits the output of a history item, the changed word spans
+//! are stored as (original → corrected) pairs. Relevant pairs are added to
+//! LLM prompts, and pairs seen repeatedly are applied directly to outputs of
+//! modes without AI processing, so recurring misrecognitions of names and
+//! jargon stop repeating. The corrected terms also serve as vocabulary that
+//! cloud speech providers are asked to favor.
+
+use crate::modes::Mode;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// Longest span (in words) that is learned as a correction
+const MAX_SPAN_WORDS: usize = 4;
+
+/// Longest text (in words) that is diffed; the diff table grows with the
+/// product of both lengths, so edits of longer outputs are not learned from
+const MAX_DIFF_WORDS: usize = 500;
+
+/// Corrections seen this often are applied without an LLM
+pub const MIN_REPEATS_FOR_REPLACEMENT: u32 = 2;
+
+/// Most corrections added to a single prompt
+const MAX_PROMPT_CORRECTIONS: usize = 10;
+
+/// Most terms passed to speech providers as vocabulary
+const MAX_VOCABULARY_TERMS: usize = 50;
+
+/// A learned correction
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Correction {
+    pub original: String,
+    pub corrected: String,
+    /// How many times the user made this correction
+    pub count: u32,
+}
+
+/// Word-level diff of an output and its edited version.
+///
+/// Returns the replaced spans; pure insertions, deletions and long rewrites
+/// are not learned, and neither are texts longer than `MAX_DIFF_WORDS`.
+pub fn extract_corrections(original: &str, corrected: &str) -> Vec<(String, String)> {
+    let a: Vec<&str> = original.split_whitespace().collect();
+    let b: Vec<&str> = corrected.split_whitespace().collect();
+    if a.len() > MAX_DIFF_WORDS || b.len() > MAX_DIFF_WORDS {
+        return Vec::new();
+    }
+
+    // Longest common subsequence table over words (ignoring surrounding punctuation)
+    let mut lcs = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if same_word(a[i], b[j]) {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut pairs = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    let mut removed: Vec<&str> = Vec::new();
+    let mut added: Vec<&str> = Vec::new();
+
+    let mut flush = |removed: &mut Vec<&str>, added: &mut Vec<&str>| {
+        if !removed.is_empty()
+            && !added.is_empty()
+            && removed.len() <= MAX_SPAN_WORDS
+            && added.len() <= MAX_SPAN_WORDS
+        {
+            let from = trim_punctuation(&removed.join(" ")).to_string();
+            let to = trim_punctuation(&added.join(" ")).to_string();
+            if !from.is_empty() && !to.is_empty() && from != to {
+                pairs.push((from, to));
+            }
+        }
+        removed.clear();
+        added.clear();
+    };
+
+    while i < a.len() || j < b.len() {
+        if i < a.len() && j < b.len() && same_word(a[i], b[j]) {
+            flush(&mut removed, &mut added);
+            i += 1;
+            j += 1;
+        } else if j < b.len() && (i == a.len() || lcs[i][j + 1] >= lcs[i + 1][j]) {
+            added.push(b[j]);
+            j += 1;
+        } else {
+            removed.push(a[i]);
+            i += 1;
+        }
+    }
+    flush(&mut removed, &mut added);
+
+    pairs
+}
+
+fn trim_punctuation(word: &str) -> &str {
+    word.trim_matches(|c: char| !c.is_alphanumeric())
+}
+
+fn same_word(a: &str, b: &str) -> bool {
+    trim_punctuation(a) == trim_punctuation(b)
+}
+
+fn word_regex(phrase: &str) -> Option<Regex> {
+    Regex::new(&format!(r"(?i)\b{}\b", regex::escape(phrase))).ok()
+}
+
+/// Corrections whose original text occurs in the transcript, most frequent first
+pub fn relevant<'a>(transcript: &str, corrections: &'a [Correction]) -> Vec<&'a Correction> {
+    let mut matches: Vec<&Correction> = corrections
+        .iter()
+        .filter(|c| word_regex(&c.original).is_some_and(|re| re.is_match(transcript)))
+        .collect();
+    matches.sort_by_key(|c| std::cmp::Reverse(c.count));
+    matches.truncate(MAX_PROMPT_CORRECTIONS);
+    matches
+}
+
+/// Prompt preamble listing corrections, empty if there are none
+pub fn prompt_hint(corrections: &[&Correction]) -> String {
+    if corrections.is_empty() {
+        return String::new();
+    }
+
+    let lines: Vec<String> = corrections
+        .iter()
+        .map(|c| format!("- \"{}\" should be \"{}\"", c.original, c.corrected))
+        .collect();
+    format!(
+        "The user has corrected these transcription mistakes before:\n{}\n\n",
+        lines.join("\n")
+    )
+}
+
+/// Replace frequently corrected phrases directly
+pub fn apply(text: &str, corrections: &[Correction]) -> String {
+    corrections
+        .iter()
+        .filter(|c| c.count >= MIN_REPEATS_FOR_REPLACEMENT)
+        .fold(text.to_string(), |acc, c| match word_regex(&c.original) {
+            Some(re) => re.replace_all(&acc, c.corrected.as_str()).into_owned(),
+            None => acc,
+        })
+}
+
+/// Corrected terms, most frequent first, as vocabulary for speech recognition
+pub fn vocabulary(corrections: &[Correction]) -> Vec<String> {
+    let mut sorted: Vec<&Correction> = corrections.iter().collect();
+    sorted.sort_by_key(|c| std::cmp::Reverse(c.count));
+
+    let mut terms: Vec<String> = Vec::new();
+    for correction in sorted {
+        let term = correction.corrected.trim();
+        if !term.is_empty() && !terms.iter().any(|t| t.eq_ignore_ascii_case(term)) {
+            terms.push(term.to_string());
+        }
+    }
+    terms.truncate(MAX_VOCABULARY_TERMS);
+    terms
+}
+
+/// Add vocabulary terms missing from a comma-separated hotword list
+pub fn merge_hotwords(hotwords: Option<&str>, vocabulary: &[String]) -> Option<String> {
+    let mut terms: Vec<String> = hotwords
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .map(str::to_string)
+        .collect();
+    for term in vocabulary {
+        if !terms.iter().any(|t| t.eq_ignore_ascii_case(term)) {
+            terms.push(term.clone());
+        }
+    }
+    (!terms.is_empty()).then(|| terms.join(", "))
+}
+
+/// `mode` with the corrected terms added to its hotwords for cloud speech providers
+///
+/// Local whisper.cpp is left alone; its output is fixed by `apply` instead.
+pub fn boost_mode(mode: &Mode, corrections: &[Correction]) -> Mode {
+    let mut mode = mode.clone();
+    if !mode.stt_provider.is_local() {
+        mode.decoding.hotwords =
+            merge_hotwords(mode.decoding.hotwords.as_deref(), &vocabulary(corrections));
+    }
+    mode
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn correction(original: &str, corrected: &str, count: u32) -> Correction {
+        Correction {
+            original: original.to_string(),
+            corrected: corrected.to_string(),
+            count,
+        }
+    }
+
+    #[test]
+    fn test_extract_corrections() {
+        assert_eq!(
+            extract_corrections(
+                "Ask Jon about the cube cuddle migration.",
+                "Ask John about the Kubernetes migration."
+            ),
+            vec![
+                ("Jon".to_string(), "John".to_string()),
+                ("cube cuddle".to_string(), "Kubernetes".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extract_ignores_insertions_and_rewrites() {
+        assert!(extract_corrections("see you soon", "see you very soon").is_empty());
+        assert!(extract_corrections("hello there", "hello there.").is_empty());
+        assert!(extract_corrections(
+            "one two three four five",
+            "completely different words here now"
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn test_extract_skips_long_texts() {
+        let original = format!("{} cube cuddle", "word ".repeat(MAX_DIFF_WORDS));
+        let corrected = format!("{} Kubernetes", "word ".repeat(MAX_DIFF_WORDS));
+        assert!(extract_corrections(&original, &corrected).is_empty());
+    }
+
+    #[test]
+    fn test_relevant_and_hint() {
+        let corrections = vec![
+            correction("Jon", "John", 1),
+            correction("cube cuddle", "Kubernetes", 3),
+        ];
+        let relevant = relevant("the Cube cuddle cluster is down", &corrections);
+        assert_eq!(relevant.len(), 1);
+        assert!(prompt_hint(&relevant).contains("\"cube cuddle\" should be \"Kubernetes\""));
+        assert!(prompt_hint(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_apply_requires_repeats() {
+        let corrections = vec![
+            correction("Jon", "John", 1),
+            correction("cube cuddle", "Kubernetes", 2),
+        ];
+        assert_eq!(
+            apply("Jon runs cube cuddle", &corrections),
+            "Jon runs Kubernetes"
+        );
+    }
+
+    #[test]
+    fn test_vocabulary() {
+        let corrections = vec![
+            correction("Jon", "John", 1),
+            correction("cube cuddle", "Kubernetes", 3),
+            correction("cube netes", "kubernetes", 1),
+        ];
+        let terms = vocabulary(&corrections);
+        assert_eq!(terms, vec!["Kubernetes", "John"]);
+        assert_eq!(
+            merge_hotwords(Some("Grafana, john"), &terms).as_deref(),
+            Some("Grafana, john, Kubernetes")
+        );
+        assert_eq!(merge_hotwords(None, &[]), None);
+
+        let mode = Mode {
+            stt_provider: crate::modes::SttProvider::OpenAI,
+            ..Mode::default()
+        };
+        let boosted = boost_mode(&mode, &corrections);
+        assert_eq!(
+            boosted.decoding.hotwords.as_deref(),
+            Some("Kubernetes, John")
+        );
+        assert_eq!(boost_mode(&Mode::default(), &corrections), Mode::default());
+    }
+}
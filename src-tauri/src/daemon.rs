@@ -0,0 +1,165 @@
+//! Daemon mode and control socket
+//!
+//! `whispertray --daemon` keeps the recording and processing pipeline running
+//! in the background without windows, typically as a systemd user service.
+//! The UI attaches on demand: `whispertray --show` opens the main window in
+//! the daemon, and closing it (or its web process crashing) leaves the
+//! pipeline running. Command-line actions are sent over a control socket, so
+//! they reach the running instance without starting a second app.
+
+use crate::cli::{self, CliAction, CliOptions};
+use crate::error::Result;
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, WebviewWindowBuilder};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader as AsyncBufReader};
+use tokio::net::UnixStream;
+
+/// How long a client waits for the running instance to answer
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Name of the socket the running instance accepts actions on
+const SOCKET_NAME: &str = "control.sock";
+
+/// One line sent to the control socket
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ControlRequest {
+    pub action: CliAction,
+}
+
+/// The answer to a request
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ControlResponse {
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Socket the running instance accepts actions on
+pub fn socket_path() -> PathBuf {
+    crate::status::runtime_dir().join(SOCKET_NAME)
+}
+
+/// Parse a request line and run its action
+fn handle_request(handle: &AppHandle, line: &str) -> ControlResponse {
+    match serde_json::from_str::<ControlRequest>(line) {
+        Ok(request) => {
+            log::info!("Control socket: {:?}", request.action);
+            cli::run_action(handle, request.action);
+            ControlResponse {
+                ok: true,
+                error: None,
+            }
+        }
+        Err(e) => ControlResponse {
+            ok: false,
+            error: Some(format!("Invalid request: {}", e)),
+        },
+    }
+}
+
+/// Accept actions on the control socket
+pub fn start(handle: &AppHandle) {
+    let handle = handle.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = serve(handle).await {
+            log::warn!("Control socket unavailable: {}", e);
+        }
+    });
+}
+
+async fn serve(handle: AppHandle) -> std::io::Result<()> {
+    let (listener, path) = crate::status::bind_private_socket(SOCKET_NAME)?;
+    let uid = crate::status::current_uid()?;
+    log::info!("Accepting actions on {:?}", path);
+
+    loop {
+        let (stream, _) = listener.accept().await?;
+        // Actions control recording, so only this user may send them
+        if !stream.peer_cred().is_ok_and(|cred| cred.uid() == uid) {
+            log::warn!("Rejected a control connection from another user");
+            continue;
+        }
+        let handle = handle.clone();
+        tauri::async_runtime::spawn(async move {
+            let _ = serve_client(&handle, stream).await;
+        });
+    }
+}
+
+async fn serve_client(handle: &AppHandle, stream: UnixStream) -> std::io::Result<()> {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = AsyncBufReader::new(reader).lines();
+    while let Some(line) = lines.next_line().await? {
+        let response = handle_request(handle, &line);
+        let line = serde_json::to_string(&response).unwrap_or_default();
+        writer.write_all(format!("{}\n", line).as_bytes()).await?;
+    }
+    Ok(())
+}
+
+/// Send an action to the running instance
+///
+/// Fails when no instance listens, so the caller can start the app instead.
+pub fn send(action: CliAction) -> std::io::Result<ControlResponse> {
+    let mut stream = std::os::unix::net::UnixStream::connect(socket_path())?;
+    stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+    let request = serde_json::to_string(&ControlRequest { action })?;
+    stream.write_all(format!("{}\n", request).as_bytes())?;
+
+    let mut line = String::new();
+    BufReader::new(stream).read_line(&mut line)?;
+    Ok(serde_json::from_str(&line)?)
+}
+
+/// Show the main window, creating it in daemon mode where it does not exist yet
+pub fn attach_ui(handle: &AppHandle) -> Result<()> {
+    let window = match handle.get_webview_window("main") {
+        Some(window) => window,
+        None => {
+            let daemon = handle.try_state::<CliOptions>().is_some_and(|o| o.daemon);
+            let config = handle
+                .config()
+                .app
+                .windows
+                .iter()
+                .find(|w| w.label == "main")
+                .cloned();
+            match config {
+                Some(config) if daemon => {
+                    WebviewWindowBuilder::from_config(handle, &config)?.build()?
+                }
+                _ => {
+                    log::info!("Running headless, not opening the main window");
+                    return Ok(());
+                }
+            }
+        }
+    };
+    window.show()?;
+    window.set_focus()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_format() {
+        let request = ControlRequest {
+            action: CliAction::Toggle,
+        };
+        let line = serde_json::to_string(&request).unwrap();
+        assert_eq!(line, r#"{"action":"toggle"}"#);
+        assert_eq!(
+            serde_json::from_str::<ControlRequest>(&line).unwrap(),
+            request
+        );
+
+        let response: ControlResponse = serde_json::from_str(r#"{"ok":true}"#).unwrap();
+        assert!(response.ok && response.error.is_none());
+    }
+}
@@ -1,13 +1,23 @@
 //! SQLite database for history storage
 
+use crate::corrections::Correction;
 use crate::error::{AppError, Result};
 use chrono::{DateTime, Utc};
-use rusqlite::{params, Connection};
+use rusqlite::{params, Connection, OpenFlags};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// How long a statement waits for a lock held by another connection
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Read connections `ReaderPool` keeps open between uses
+const MAX_IDLE_READERS: usize = 4;
 
 /// History item stored in the database
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct HistoryItem {
     pub id: String,
     pub created_at: DateTime<Utc>,
@@ -21,6 +31,109 @@ pub struct HistoryItem {
     pub llm_model: Option<String>,
     pub duration_ms: u64,
     pub error: Option<String>,
+    /// Average transcription confidence (0.0-1.0), if the provider reports one
+    #[serde(default)]
+    pub confidence: Option<f32>,
+    /// Short LLM-generated title for the history list
+    #[serde(default)]
+    pub title: Option<String>,
+    /// Time spent in each pipeline stage
+    #[serde(default)]
+    pub latencies: Option<StageLatencies>,
+    /// Endpoints that served the transcription and AI processing
+    #[serde(default)]
+    pub provider_metadata: Option<ProviderMetadata>,
+    /// The input clipped while recording, a common cause of poor transcriptions
+    #[serde(default)]
+    pub clipped: bool,
+    /// Live transcript file written while recording, deleted with the item
+    #[serde(default)]
+    pub transcript_path: Option<String>,
+}
+
+/// Milliseconds a dictation spent in each pipeline stage
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StageLatencies {
+    pub recording_ms: u64,
+    pub saving_ms: u64,
+    pub transcribing_ms: u64,
+    pub llm_ms: u64,
+    pub pasting_ms: u64,
+}
+
+/// Where a dictation's provider requests were served
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProviderMetadata {
+    /// URL (or local model file) that transcribed the audio
+    pub stt_endpoint: Option<String>,
+    pub stt_retries: u32,
+    /// URL of the LLM that processed the transcript
+    pub llm_endpoint: Option<String>,
+    pub llm_retries: u32,
+}
+
+/// Columns selected for history items, in `row_to_history_item` order
+const HISTORY_COLUMNS: &str = "id, created_at, mode_key, audio_path, transcript_raw, output_final,
+    stt_provider, stt_model, llm_provider, llm_model, duration_ms, error, confidence, title,
+    latencies, provider_metadata, clipped, transcript_path";
+
+/// Map a row selected with `HISTORY_COLUMNS` to a history item
+fn row_to_history_item(row: &rusqlite::Row) -> rusqlite::Result<HistoryItem> {
+    Ok(HistoryItem {
+        id: row.get(0)?,
+        created_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(1)?)
+            .map(|dt| dt.with_timezone(&Utc))
+            .unwrap_or_else(|_| Utc::now()),
+        mode_key: row.get(2)?,
+        audio_path: row.get(3)?,
+        transcript_raw: row.get(4)?,
+        output_final: row.get(5)?,
+        stt_provider: row.get(6)?,
+        stt_model: row.get(7)?,
+        llm_provider: row.get(8)?,
+        llm_model: row.get(9)?,
+        duration_ms: row.get::<_, i64>(10)? as u64,
+        error: row.get(11)?,
+        confidence: row.get(12)?,
+        title: row.get(13)?,
+        latencies: row
+            .get::<_, Option<String>>(14)?
+            .and_then(|json| serde_json::from_str(&json).ok()),
+        provider_metadata: row
+            .get::<_, Option<String>>(15)?
+            .and_then(|json| serde_json::from_str(&json).ok()),
+        clipped: row.get(16)?,
+        transcript_path: row.get(17)?,
+    })
+}
+
+/// Timed transcription segment belonging to a history item
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Segment {
+    pub start_ms: u64,
+    pub end_ms: u64,
+    pub text: String,
+    /// Average token probability (0.0-1.0), if the provider reports one
+    pub confidence: Option<f32>,
+    pub speaker: Option<String>,
+}
+
+/// Task extracted from a meeting, with its owner and due date when spoken
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ActionItem {
+    pub owner: Option<String>,
+    pub task: String,
+    /// Due date as spoken, e.g. "Friday" or "2024-06-01"
+    pub due: Option<String>,
+}
+
+/// Audio file of a merged history item, placed at an offset in the combined timeline
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AudioPart {
+    pub offset_ms: u64,
+    pub path: String,
 }
 
 /// Database manager
@@ -37,11 +150,25 @@ impl Database {
         }
 
         let conn = Connection::open(path)?;
+        // With a write-ahead log, readers never wait for the writer
+        conn.pragma_update_and_check(None, "journal_mode", "WAL", |row| row.get::<_, String>(0))?;
+        conn.pragma_update(None, "synchronous", "NORMAL")?;
+        conn.busy_timeout(BUSY_TIMEOUT)?;
         let db = Database { conn };
         db.init_schema()?;
         Ok(db)
     }
 
+    /// Open a read-only connection to an existing database
+    pub fn open_reader(path: &Path) -> Result<Self> {
+        let conn = Connection::open_with_flags(
+            path,
+            OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )?;
+        conn.busy_timeout(BUSY_TIMEOUT)?;
+        Ok(Database { conn })
+    }
+
     /// Initialize database schema
     fn init_schema(&self) -> Result<()> {
         self.conn.execute(
@@ -62,6 +189,14 @@ impl Database {
             [],
         )?;
 
+        // Columns added after the initial schema
+        self.ensure_column("history_items", "confidence", "REAL")?;
+        self.ensure_column("history_items", "title", "TEXT")?;
+        self.ensure_column("history_items", "latencies", "TEXT")?;
+        self.ensure_column("history_items", "provider_metadata", "TEXT")?;
+        self.ensure_column("history_items", "clipped", "INTEGER NOT NULL DEFAULT 0")?;
+        self.ensure_column("history_items", "transcript_path", "TEXT")?;
+
         // Create index for faster queries
         self.conn.execute(
             "CREATE INDEX IF NOT EXISTS idx_history_created_at ON history_items(created_at DESC)",
@@ -73,6 +208,82 @@ impl Database {
             [],
         )?;
 
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS segments (
+                history_id TEXT NOT NULL,
+                position INTEGER NOT NULL,
+                start_ms INTEGER NOT NULL,
+                end_ms INTEGER NOT NULL,
+                text TEXT NOT NULL,
+                confidence REAL,
+                speaker TEXT,
+                PRIMARY KEY (history_id, position)
+            )",
+            [],
+        )?;
+
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS audio_parts (
+                history_id TEXT NOT NULL,
+                position INTEGER NOT NULL,
+                offset_ms INTEGER NOT NULL,
+                path TEXT NOT NULL,
+                PRIMARY KEY (history_id, position)
+            )",
+            [],
+        )?;
+
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS embeddings (
+                history_id TEXT PRIMARY KEY,
+                model TEXT NOT NULL,
+                vector BLOB NOT NULL
+            )",
+            [],
+        )?;
+
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS action_items (
+                history_id TEXT NOT NULL,
+                position INTEGER NOT NULL,
+                owner TEXT,
+                task TEXT NOT NULL,
+                due TEXT,
+                PRIMARY KEY (history_id, position)
+            )",
+            [],
+        )?;
+
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS corrections (
+                original TEXT NOT NULL,
+                corrected TEXT NOT NULL,
+                count INTEGER NOT NULL,
+                updated_at TEXT NOT NULL,
+                PRIMARY KEY (original, corrected)
+            )",
+            [],
+        )?;
+
+        Ok(())
+    }
+
+    /// Add a column to an existing table if it is missing
+    fn ensure_column(&self, table: &str, column: &str, definition: &str) -> Result<()> {
+        let mut stmt = self
+            .conn
+            .prepare(&format!("SELECT name FROM pragma_table_info('{}')", table))?;
+        let exists = stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .filter_map(|r| r.ok())
+            .any(|name| name == column);
+
+        if !exists {
+            self.conn.execute(
+                &format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition),
+                [],
+            )?;
+        }
         Ok(())
     }
 
@@ -81,8 +292,11 @@ impl Database {
         self.conn.execute(
             "INSERT INTO history_items (
                 id, created_at, mode_key, audio_path, transcript_raw, output_final,
-                stt_provider, stt_model, llm_provider, llm_model, duration_ms, error
-            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+                stt_provider, stt_model, llm_provider, llm_model, duration_ms, error,
+                confidence, title, latencies, provider_metadata, clipped, transcript_path
+            ) VALUES (
+                ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18
+            )",
             params![
                 item.id,
                 item.created_at.to_rfc3339(),
@@ -96,6 +310,17 @@ impl Database {
                 item.llm_model,
                 item.duration_ms as i64,
                 item.error,
+                item.confidence,
+                item.title,
+                item.latencies
+                    .map(|latencies| serde_json::to_string(&latencies))
+                    .transpose()?,
+                item.provider_metadata
+                    .as_ref()
+                    .map(serde_json::to_string)
+                    .transpose()?,
+                item.clipped,
+                item.transcript_path,
             ],
         )?;
         Ok(())
@@ -103,33 +328,15 @@ impl Database {
 
     /// Get all history items (paginated)
     pub fn get_history(&self, limit: usize, offset: usize) -> Result<Vec<HistoryItem>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, created_at, mode_key, audio_path, transcript_raw, output_final,
-                    stt_provider, stt_model, llm_provider, llm_model, duration_ms, error
-             FROM history_items
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM history_items
              ORDER BY created_at DESC
              LIMIT ?1 OFFSET ?2",
-        )?;
+            HISTORY_COLUMNS
+        ))?;
 
         let items = stmt
-            .query_map(params![limit as i64, offset as i64], |row| {
-                Ok(HistoryItem {
-                    id: row.get(0)?,
-                    created_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(1)?)
-                        .map(|dt| dt.with_timezone(&Utc))
-                        .unwrap_or_else(|_| Utc::now()),
-                    mode_key: row.get(2)?,
-                    audio_path: row.get(3)?,
-                    transcript_raw: row.get(4)?,
-                    output_final: row.get(5)?,
-                    stt_provider: row.get(6)?,
-                    stt_model: row.get(7)?,
-                    llm_provider: row.get(8)?,
-                    llm_model: row.get(9)?,
-                    duration_ms: row.get::<_, i64>(10)? as u64,
-                    error: row.get(11)?,
-                })
-            })?
+            .query_map(params![limit as i64, offset as i64], row_to_history_item)?
             .filter_map(|r| r.ok())
             .collect();
 
@@ -138,32 +345,14 @@ impl Database {
 
     /// Get a single history item by ID
     pub fn get_history_item(&self, id: &str) -> Result<Option<HistoryItem>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, created_at, mode_key, audio_path, transcript_raw, output_final,
-                    stt_provider, stt_model, llm_provider, llm_model, duration_ms, error
-             FROM history_items
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM history_items
              WHERE id = ?1",
-        )?;
+            HISTORY_COLUMNS
+        ))?;
 
         let item = stmt
-            .query_row(params![id], |row| {
-                Ok(HistoryItem {
-                    id: row.get(0)?,
-                    created_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(1)?)
-                        .map(|dt| dt.with_timezone(&Utc))
-                        .unwrap_or_else(|_| Utc::now()),
-                    mode_key: row.get(2)?,
-                    audio_path: row.get(3)?,
-                    transcript_raw: row.get(4)?,
-                    output_final: row.get(5)?,
-                    stt_provider: row.get(6)?,
-                    stt_model: row.get(7)?,
-                    llm_provider: row.get(8)?,
-                    llm_model: row.get(9)?,
-                    duration_ms: row.get::<_, i64>(10)? as u64,
-                    error: row.get(11)?,
-                })
-            })
+            .query_row(params![id], row_to_history_item)
             .ok();
 
         Ok(item)
@@ -191,12 +380,315 @@ impl Database {
         Ok(())
     }
 
+    /// Replace the transcript of a history item and the provider that produced it
+    pub fn update_history_transcript(&self, item: &HistoryItem) -> Result<()> {
+        self.conn.execute(
+            "UPDATE history_items SET
+                transcript_raw = ?2,
+                stt_provider = ?3,
+                stt_model = ?4,
+                confidence = ?5
+             WHERE id = ?1",
+            params![
+                item.id,
+                item.transcript_raw,
+                item.stt_provider,
+                item.stt_model,
+                item.confidence,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Set the title of a history item
+    pub fn set_history_title(&self, id: &str, title: &str) -> Result<()> {
+        self.conn.execute(
+            "UPDATE history_items SET title = ?2 WHERE id = ?1",
+            params![id, title],
+        )?;
+        Ok(())
+    }
+
+    /// Store the stage latencies of a history item
+    pub fn set_history_latencies(&self, id: &str, latencies: &StageLatencies) -> Result<()> {
+        self.conn.execute(
+            "UPDATE history_items SET latencies = ?2 WHERE id = ?1",
+            params![id, serde_json::to_string(latencies)?],
+        )?;
+        Ok(())
+    }
+
+    /// Replace the segments stored for a history item
+    pub fn insert_segments(&self, history_id: &str, segments: &[Segment]) -> Result<()> {
+        self.conn.execute(
+            "DELETE FROM segments WHERE history_id = ?1",
+            params![history_id],
+        )?;
+
+        let mut stmt = self.conn.prepare(
+            "INSERT INTO segments (
+                history_id, position, start_ms, end_ms, text, confidence, speaker
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        )?;
+
+        for (position, segment) in segments.iter().enumerate() {
+            stmt.execute(params![
+                history_id,
+                position as i64,
+                segment.start_ms as i64,
+                segment.end_ms as i64,
+                segment.text,
+                segment.confidence,
+                segment.speaker,
+            ])?;
+        }
+        Ok(())
+    }
+
+    /// Get the segments of a history item in order
+    pub fn get_segments(&self, history_id: &str) -> Result<Vec<Segment>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT start_ms, end_ms, text, confidence, speaker
+             FROM segments
+             WHERE history_id = ?1
+             ORDER BY position",
+        )?;
+
+        let segments = stmt
+            .query_map(params![history_id], |row| {
+                Ok(Segment {
+                    start_ms: row.get::<_, i64>(0)? as u64,
+                    end_ms: row.get::<_, i64>(1)? as u64,
+                    text: row.get(2)?,
+                    confidence: row.get(3)?,
+                    speaker: row.get(4)?,
+                })
+            })?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(segments)
+    }
+
+    /// Replace the action items stored for a history item
+    pub fn set_action_items(&self, history_id: &str, items: &[ActionItem]) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            "DELETE FROM action_items WHERE history_id = ?1",
+            params![history_id],
+        )?;
+        for (position, item) in items.iter().enumerate() {
+            tx.execute(
+                "INSERT INTO action_items (history_id, position, owner, task, due)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![history_id, position as i64, item.owner, item.task, item.due],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Get the action items of a history item in order
+    pub fn get_action_items(&self, history_id: &str) -> Result<Vec<ActionItem>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT owner, task, due
+             FROM action_items
+             WHERE history_id = ?1
+             ORDER BY position",
+        )?;
+
+        let items = stmt
+            .query_map(params![history_id], |row| {
+                Ok(ActionItem {
+                    owner: row.get(0)?,
+                    task: row.get(1)?,
+                    due: row.get(2)?,
+                })
+            })?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(items)
+    }
+
+    /// Replace the audio parts stored for a merged history item
+    pub fn insert_audio_parts(&self, history_id: &str, parts: &[AudioPart]) -> Result<()> {
+        self.conn.execute(
+            "DELETE FROM audio_parts WHERE history_id = ?1",
+            params![history_id],
+        )?;
+
+        let mut stmt = self.conn.prepare(
+            "INSERT INTO audio_parts (history_id, position, offset_ms, path)
+             VALUES (?1, ?2, ?3, ?4)",
+        )?;
+
+        for (position, part) in parts.iter().enumerate() {
+            stmt.execute(params![
+                history_id,
+                position as i64,
+                part.offset_ms as i64,
+                part.path,
+            ])?;
+        }
+        Ok(())
+    }
+
+    /// Get the audio parts of a merged history item in order
+    pub fn get_audio_parts(&self, history_id: &str) -> Result<Vec<AudioPart>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT offset_ms, path
+             FROM audio_parts
+             WHERE history_id = ?1
+             ORDER BY position",
+        )?;
+
+        let parts = stmt
+            .query_map(params![history_id], |row| {
+                Ok(AudioPart {
+                    offset_ms: row.get::<_, i64>(0)? as u64,
+                    path: row.get(1)?,
+                })
+            })?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(parts)
+    }
+
+    /// Every audio file referenced by the history, with the id of its item
+    pub fn get_audio_references(&self) -> Result<Vec<(String, String)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, audio_path FROM history_items WHERE audio_path IS NOT NULL
+             UNION
+             SELECT history_id, path FROM audio_parts",
+        )?;
+
+        let references = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(references)
+    }
+
+    /// Drop a history item's reference to an audio file
+    pub fn remove_audio_reference(&self, history_id: &str, path: &str) -> Result<()> {
+        self.conn.execute(
+            "UPDATE history_items SET audio_path = NULL WHERE id = ?1 AND audio_path = ?2",
+            params![history_id, path],
+        )?;
+        self.conn.execute(
+            "DELETE FROM audio_parts WHERE history_id = ?1 AND path = ?2",
+            params![history_id, path],
+        )?;
+        Ok(())
+    }
+
+    /// Store the embedding of a history item
+    pub fn insert_embedding(&self, history_id: &str, model: &str, vector: &[f32]) -> Result<()> {
+        let bytes: Vec<u8> = vector.iter().flat_map(|v| v.to_le_bytes()).collect();
+        self.conn.execute(
+            "INSERT OR REPLACE INTO embeddings (history_id, model, vector) VALUES (?1, ?2, ?3)",
+            params![history_id, model, bytes],
+        )?;
+        Ok(())
+    }
+
+    /// Get all embeddings computed with `model`, keyed by history item ID
+    pub fn get_embeddings(&self, model: &str) -> Result<Vec<(String, Vec<f32>)>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT history_id, vector FROM embeddings WHERE model = ?1")?;
+
+        let embeddings = stmt
+            .query_map(params![model], |row| {
+                let bytes: Vec<u8> = row.get(1)?;
+                let vector = bytes
+                    .chunks_exact(4)
+                    .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                    .collect();
+                Ok((row.get(0)?, vector))
+            })?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(embeddings)
+    }
+
+    /// Get non-empty history items that have no embedding for `model` yet, newest first
+    pub fn get_history_without_embedding(
+        &self,
+        model: &str,
+        limit: usize,
+    ) -> Result<Vec<HistoryItem>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM history_items
+             WHERE id NOT IN (SELECT history_id FROM embeddings WHERE model = ?1)
+               AND TRIM(output_final) != ''
+             ORDER BY created_at DESC
+             LIMIT ?2",
+            HISTORY_COLUMNS
+        ))?;
+
+        let items = stmt
+            .query_map(params![model, limit as i64], row_to_history_item)?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(items)
+    }
+
     /// Delete a history item
     pub fn delete_history(&self, id: &str) -> Result<()> {
-        self.conn.execute("DELETE FROM history_items WHERE id = ?1", params![id])?;
+        self.conn
+            .execute("DELETE FROM segments WHERE history_id = ?1", params![id])?;
+        self.conn
+            .execute("DELETE FROM audio_parts WHERE history_id = ?1", params![id])?;
+        self.conn
+            .execute("DELETE FROM embeddings WHERE history_id = ?1", params![id])?;
+        self.conn.execute(
+            "DELETE FROM action_items WHERE history_id = ?1",
+            params![id],
+        )?;
+        self.conn
+            .execute("DELETE FROM history_items WHERE id = ?1", params![id])?;
         Ok(())
     }
 
+    /// Delete several history items in one transaction
+    pub fn delete_history_items(&self, ids: &[String]) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        for id in ids {
+            tx.execute("DELETE FROM segments WHERE history_id = ?1", params![id])?;
+            tx.execute("DELETE FROM audio_parts WHERE history_id = ?1", params![id])?;
+            tx.execute("DELETE FROM embeddings WHERE history_id = ?1", params![id])?;
+            tx.execute(
+                "DELETE FROM action_items WHERE history_id = ?1",
+                params![id],
+            )?;
+            tx.execute("DELETE FROM history_items WHERE id = ?1", params![id])?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Ids of the history items created before `before`, or of all items with `None`
+    pub fn get_history_ids_before(&self, before: Option<DateTime<Utc>>) -> Result<Vec<String>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id FROM history_items WHERE ?1 IS NULL OR created_at < ?1
+             ORDER BY created_at",
+        )?;
+        let ids = stmt
+            .query_map(params![before.map(|before| before.to_rfc3339())], |row| {
+                row.get(0)
+            })?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(ids)
+    }
+
     /// Get total count of history items
     pub fn get_history_count(&self) -> Result<usize> {
         let count: i64 = self
@@ -208,47 +700,146 @@ impl Database {
     /// Search history by text
     pub fn search_history(&self, query: &str, limit: usize) -> Result<Vec<HistoryItem>> {
         let search_pattern = format!("%{}%", query);
-        let mut stmt = self.conn.prepare(
-            "SELECT id, created_at, mode_key, audio_path, transcript_raw, output_final,
-                    stt_provider, stt_model, llm_provider, llm_model, duration_ms, error
-             FROM history_items
-             WHERE transcript_raw LIKE ?1 OR output_final LIKE ?1
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM history_items
+             WHERE transcript_raw LIKE ?1 OR output_final LIKE ?1 OR title LIKE ?1
              ORDER BY created_at DESC
              LIMIT ?2",
-        )?;
+            HISTORY_COLUMNS
+        ))?;
 
         let items = stmt
-            .query_map(params![search_pattern, limit as i64], |row| {
-                Ok(HistoryItem {
-                    id: row.get(0)?,
-                    created_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(1)?)
-                        .map(|dt| dt.with_timezone(&Utc))
-                        .unwrap_or_else(|_| Utc::now()),
-                    mode_key: row.get(2)?,
-                    audio_path: row.get(3)?,
-                    transcript_raw: row.get(4)?,
-                    output_final: row.get(5)?,
-                    stt_provider: row.get(6)?,
-                    stt_model: row.get(7)?,
-                    llm_provider: row.get(8)?,
-                    llm_model: row.get(9)?,
-                    duration_ms: row.get::<_, i64>(10)? as u64,
-                    error: row.get(11)?,
+            .query_map(params![search_pattern, limit as i64], row_to_history_item)?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(items)
+    }
+
+    /// Record a user correction, counting repeats
+    pub fn record_correction(&self, original: &str, corrected: &str) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO corrections (original, corrected, count, updated_at)
+             VALUES (?1, ?2, 1, ?3)
+             ON CONFLICT (original, corrected)
+             DO UPDATE SET count = count + 1, updated_at = excluded.updated_at",
+            params![original, corrected, Utc::now().to_rfc3339()],
+        )?;
+        Ok(())
+    }
+
+    /// Get learned corrections, most recent first
+    pub fn get_corrections(&self) -> Result<Vec<Correction>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT original, corrected, count FROM corrections ORDER BY updated_at DESC",
+        )?;
+
+        let corrections = stmt
+            .query_map([], |row| {
+                Ok(Correction {
+                    original: row.get(0)?,
+                    corrected: row.get(1)?,
+                    count: row.get::<_, i64>(2)? as u32,
                 })
             })?
             .filter_map(|r| r.ok())
             .collect();
 
-        Ok(items)
+        Ok(corrections)
+    }
+
+    /// Forget a learned correction
+    pub fn delete_correction(&self, original: &str, corrected: &str) -> Result<()> {
+        self.conn.execute(
+            "DELETE FROM corrections WHERE original = ?1 AND corrected = ?2",
+            params![original, corrected],
+        )?;
+        Ok(())
     }
 
     /// Clear all history
     pub fn clear_history(&self) -> Result<()> {
+        self.conn.execute("DELETE FROM segments", [])?;
+        self.conn.execute("DELETE FROM audio_parts", [])?;
+        self.conn.execute("DELETE FROM embeddings", [])?;
+        self.conn.execute("DELETE FROM action_items", [])?;
         self.conn.execute("DELETE FROM history_items", [])?;
         Ok(())
     }
 }
 
+/// Read-only connections for history views and exports; cheap to clone
+///
+/// Writes go through the database worker thread, which runs them one at a
+/// time. Readers from the pool do not queue behind them, and in WAL mode they
+/// see the last committed state without waiting for writes in progress.
+#[derive(Clone)]
+pub struct ReaderPool {
+    path: PathBuf,
+    idle: Arc<Mutex<Vec<Database>>>,
+}
+
+impl ReaderPool {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            idle: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Run `f` on a reader connection on the blocking thread pool
+    pub async fn call<T, F>(&self, f: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Database) -> Result<T> + Send + 'static,
+    {
+        let pool = self.clone();
+        tokio::task::spawn_blocking(move || {
+            let reader = pool.get()?;
+            f(&reader)
+        })
+        .await
+        .map_err(|_| AppError::Config("The database call failed".to_string()))?
+    }
+
+    /// Take an idle connection or open a new one
+    pub fn get(&self) -> Result<PooledReader<'_>> {
+        let idle = self.idle.lock().ok().and_then(|mut idle| idle.pop());
+        let db = match idle {
+            Some(db) => db,
+            None => Database::open_reader(&self.path)?,
+        };
+        Ok(PooledReader {
+            pool: self,
+            db: Some(db),
+        })
+    }
+}
+
+/// A connection borrowed from a `ReaderPool`, returned to it when dropped
+pub struct PooledReader<'a> {
+    pool: &'a ReaderPool,
+    db: Option<Database>,
+}
+
+impl Deref for PooledReader<'_> {
+    type Target = Database;
+
+    fn deref(&self) -> &Database {
+        self.db.as_ref().expect("connection is only taken on drop")
+    }
+}
+
+impl Drop for PooledReader<'_> {
+    fn drop(&mut self) {
+        if let (Some(db), Ok(mut idle)) = (self.db.take(), self.pool.idle.lock()) {
+            if idle.len() < MAX_IDLE_READERS {
+                idle.push(db);
+            }
+        }
+    }
+}
+
 /// Get the database path
 pub fn get_database_path() -> Result<PathBuf> {
     let data_dir = directories::ProjectDirs::from("com", "whispertray", "WhisperTray")
@@ -302,6 +893,12 @@ mod tests {
             llm_model: None,
             duration_ms: 1000,
             error: None,
+            confidence: None,
+            title: None,
+            latencies: None,
+            provider_metadata: None,
+            clipped: false,
+            transcript_path: None,
         };
 
         db.insert_history(&item).unwrap();
@@ -309,6 +906,37 @@ mod tests {
         let retrieved = db.get_history_item("test-id").unwrap().unwrap();
         assert_eq!(retrieved.id, "test-id");
         assert_eq!(retrieved.transcript_raw, "Hello world");
+        assert_eq!(retrieved.title, None);
+
+        db.set_history_title("test-id", "Greeting").unwrap();
+        let retrieved = db.get_history_item("test-id").unwrap().unwrap();
+        assert_eq!(retrieved.title.as_deref(), Some("Greeting"));
+
+        assert_eq!(retrieved.latencies, None);
+        let latencies = StageLatencies {
+            transcribing_ms: 850,
+            pasting_ms: 40,
+            ..StageLatencies::default()
+        };
+        db.set_history_latencies("test-id", &latencies).unwrap();
+        let retrieved = db.get_history_item("test-id").unwrap().unwrap();
+        assert_eq!(retrieved.latencies, Some(latencies));
+        assert_eq!(retrieved.provider_metadata, None);
+
+        let metadata = ProviderMetadata {
+            stt_endpoint: Some("http://gpu-box:8000/v1/audio/transcriptions".to_string()),
+            stt_retries: 1,
+            ..ProviderMetadata::default()
+        };
+        let item = HistoryItem {
+            id: "with-metadata".to_string(),
+            provider_metadata: Some(metadata.clone()),
+            ..item
+        };
+        db.insert_history(&item).unwrap();
+        let retrieved = db.get_history_item("with-metadata").unwrap().unwrap();
+        assert_eq!(retrieved.provider_metadata, Some(metadata));
+        assert!(!retrieved.clipped);
     }
 
     #[test]
@@ -332,6 +960,12 @@ mod tests {
                 llm_model: None,
                 duration_ms: 1000,
                 error: None,
+                confidence: None,
+                title: None,
+                latencies: None,
+                provider_metadata: None,
+                clipped: false,
+                transcript_path: None,
             };
             db.insert_history(&item).unwrap();
         }
@@ -343,6 +977,34 @@ mod tests {
         assert_eq!(items.len(), 2);
     }
 
+    #[test]
+    fn test_update_history_transcript() {
+        let dir = tempdir().unwrap();
+        let db = Database::new(&dir.path().join("test.db")).unwrap();
+
+        let mut item = HistoryItem {
+            id: "draft".to_string(),
+            transcript_raw: "Hello wold".to_string(),
+            output_final: "Hello wold".to_string(),
+            stt_provider: "whispercpp".to_string(),
+            stt_model: "tiny.en".to_string(),
+            ..HistoryItem::default()
+        };
+        db.insert_history(&item).unwrap();
+
+        item.transcript_raw = "Hello world".to_string();
+        item.stt_model = "large-v3".to_string();
+        item.confidence = Some(0.9);
+        item.output_final = "ignored".to_string();
+        db.update_history_transcript(&item).unwrap();
+
+        let retrieved = db.get_history_item("draft").unwrap().unwrap();
+        assert_eq!(retrieved.transcript_raw, "Hello world");
+        assert_eq!(retrieved.stt_model, "large-v3");
+        assert_eq!(retrieved.confidence, Some(0.9));
+        assert_eq!(retrieved.output_final, "Hello wold");
+    }
+
     #[test]
     fn test_delete_history() {
         let dir = tempdir().unwrap();
@@ -362,6 +1024,12 @@ mod tests {
             llm_model: None,
             duration_ms: 1000,
             error: None,
+            confidence: None,
+            title: None,
+            latencies: None,
+            provider_metadata: None,
+            clipped: false,
+            transcript_path: None,
         };
 
         db.insert_history(&item).unwrap();
@@ -370,4 +1038,231 @@ mod tests {
         db.delete_history("test-id").unwrap();
         assert!(db.get_history_item("test-id").unwrap().is_none());
     }
+
+    #[test]
+    fn test_delete_history_items() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("test.db");
+        let db = Database::new(&path).unwrap();
+
+        let now = Utc::now();
+        for (i, age_days) in [3, 2, 0].iter().enumerate() {
+            let item = HistoryItem {
+                id: format!("id-{}", i),
+                created_at: now - chrono::Duration::days(*age_days),
+                mode_key: "voice_to_text".to_string(),
+                audio_path: None,
+                transcript_raw: "Hello".to_string(),
+                output_final: "Hello".to_string(),
+                stt_provider: "whispercpp".to_string(),
+                stt_model: "base.en".to_string(),
+                llm_provider: None,
+                llm_model: None,
+                duration_ms: 1000,
+                error: None,
+                confidence: None,
+                title: None,
+                latencies: None,
+                provider_metadata: None,
+                clipped: false,
+                transcript_path: None,
+            };
+            db.insert_history(&item).unwrap();
+        }
+
+        let old = db
+            .get_history_ids_before(Some(now - chrono::Duration::days(1)))
+            .unwrap();
+        assert_eq!(old, vec!["id-0".to_string(), "id-1".to_string()]);
+        assert_eq!(db.get_history_ids_before(None).unwrap().len(), 3);
+
+        db.delete_history_items(&old).unwrap();
+        assert_eq!(db.get_history_count().unwrap(), 1);
+        assert!(db.get_history_item("id-2").unwrap().is_some());
+    }
+
+    #[test]
+    fn test_reader_during_write() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("test.db");
+        let db = Database::new(&path).unwrap();
+        db.insert_history(&HistoryItem {
+            id: "test-id".to_string(),
+            ..HistoryItem::default()
+        })
+        .unwrap();
+
+        let pool = ReaderPool::new(path);
+        let tx = db.conn.unchecked_transaction().unwrap();
+        tx.execute("DELETE FROM history_items", []).unwrap();
+        // The open write transaction does not block readers
+        assert_eq!(pool.get().unwrap().get_history_count().unwrap(), 1);
+        tx.commit().unwrap();
+        assert_eq!(pool.get().unwrap().get_history_count().unwrap(), 0);
+        assert_eq!(pool.idle.lock().unwrap().len(), 1);
+
+        assert!(pool
+            .get()
+            .unwrap()
+            .insert_history(&HistoryItem::default())
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_reader_call() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("test.db");
+        let db = Database::new(&path).unwrap();
+        db.insert_history(&HistoryItem {
+            id: "test-id".to_string(),
+            ..HistoryItem::default()
+        })
+        .unwrap();
+
+        let pool = ReaderPool::new(path);
+        let item = pool
+            .call(|db| db.get_history_item("test-id"))
+            .await
+            .unwrap();
+        assert!(item.is_some());
+        assert_eq!(pool.idle.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_segments_roundtrip() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("test.db");
+        let db = Database::new(&path).unwrap();
+
+        let item = HistoryItem {
+            id: "test-id".to_string(),
+            created_at: Utc::now(),
+            mode_key: "voice_to_text".to_string(),
+            audio_path: None,
+            transcript_raw: "Hello world".to_string(),
+            output_final: "Hello world".to_string(),
+            stt_provider: "whispercpp".to_string(),
+            stt_model: "base.en".to_string(),
+            llm_provider: None,
+            llm_model: None,
+            duration_ms: 2000,
+            error: None,
+            confidence: Some(0.85),
+            title: None,
+            latencies: None,
+            provider_metadata: None,
+            clipped: false,
+            transcript_path: None,
+        };
+        db.insert_history(&item).unwrap();
+
+        let segments = vec![
+            Segment {
+                start_ms: 0,
+                end_ms: 900,
+                text: "Hello".to_string(),
+                confidence: Some(0.9),
+                speaker: None,
+            },
+            Segment {
+                start_ms: 900,
+                end_ms: 2000,
+                text: " world".to_string(),
+                confidence: None,
+                speaker: Some("A".to_string()),
+            },
+        ];
+        db.insert_segments("test-id", &segments).unwrap();
+        let retrieved = db.get_history_item("test-id").unwrap().unwrap();
+        assert_eq!(retrieved.confidence, Some(0.85));
+        assert_eq!(db.get_segments("test-id").unwrap(), segments);
+
+        // Deleting the item removes its segments
+        db.delete_history("test-id").unwrap();
+        assert!(db.get_segments("test-id").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_action_items_roundtrip() {
+        let dir = tempdir().unwrap();
+        let db = Database::new(&dir.path().join("test.db")).unwrap();
+        let item = HistoryItem {
+            id: "meeting".to_string(),
+            ..HistoryItem::default()
+        };
+        db.insert_history(&item).unwrap();
+
+        let items = vec![
+            ActionItem {
+                owner: Some("Anna".to_string()),
+                task: "Send the budget".to_string(),
+                due: Some("Friday".to_string()),
+            },
+            ActionItem {
+                owner: None,
+                task: "Book a room".to_string(),
+                due: None,
+            },
+        ];
+        db.set_action_items("meeting", &items).unwrap();
+        assert_eq!(db.get_action_items("meeting").unwrap(), items);
+
+        db.set_action_items("meeting", &items[1..]).unwrap();
+        assert_eq!(db.get_action_items("meeting").unwrap(), &items[1..]);
+
+        db.delete_history("meeting").unwrap();
+        assert!(db.get_action_items("meeting").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_embeddings_roundtrip() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("test.db");
+        let db = Database::new(&path).unwrap();
+
+        for (id, text) in [("a", "First"), ("b", "Second"), ("c", " ")] {
+            let item = HistoryItem {
+                id: id.to_string(),
+                output_final: text.to_string(),
+                ..HistoryItem::default()
+            };
+            db.insert_history(&item).unwrap();
+        }
+        db.insert_embedding("a", "ollama:nomic", &[0.5, -1.25])
+            .unwrap();
+
+        assert_eq!(
+            db.get_embeddings("ollama:nomic").unwrap(),
+            [("a".to_string(), vec![0.5, -1.25])]
+        );
+        assert!(db.get_embeddings("openai:small").unwrap().is_empty());
+
+        let missing = db
+            .get_history_without_embedding("ollama:nomic", 10)
+            .unwrap();
+        assert_eq!(missing.len(), 1);
+        assert_eq!(missing[0].id, "b");
+
+        db.delete_history("a").unwrap();
+        assert!(db.get_embeddings("ollama:nomic").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_corrections_count_repeats() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("test.db");
+        let db = Database::new(&path).unwrap();
+
+        db.record_correction("Jon", "John").unwrap();
+        db.record_correction("Jon", "John").unwrap();
+        db.record_correction("cube cuddle", "Kubernetes").unwrap();
+
+        let corrections = db.get_corrections().unwrap();
+        assert_eq!(corrections.len(), 2);
+        let jon = corrections.iter().find(|c| c.original == "Jon").unwrap();
+        assert_eq!(jon.count, 2);
+
+        db.delete_correction("Jon", "John").unwrap();
+        assert_eq!(db.get_corrections().unwrap().len(), 1);
+    }
 }
@@ -0,0 +1,101 @@
+//! Database worker thread
+//!
+//! The database connection is owned by a dedicated thread. Async code sends
+//! it closures and awaits their results instead of locking a mutex around
+//! the connection, so a slow query never blocks a runtime thread and a
+//! panicking query fails only its own call instead of poisoning the
+//! database for the rest of the session.
+
+use crate::database::Database;
+use crate::error::{AppError, Result};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc;
+use tokio::sync::oneshot;
+
+type Job = Box<dyn FnOnce(&Database) + Send>;
+
+/// Handle to the database worker; cheap to clone
+#[derive(Clone)]
+pub struct DbHandle {
+    jobs: mpsc::Sender<Job>,
+}
+
+impl DbHandle {
+    /// Move the database onto its own thread
+    pub fn spawn(db: Database) -> Result<Self> {
+        let (jobs, queue) = mpsc::channel::<Job>();
+        std::thread::Builder::new()
+            .name("database".to_string())
+            .spawn(move || {
+                for job in queue {
+                    if panic::catch_unwind(AssertUnwindSafe(|| job(&db))).is_err() {
+                        log::error!("A database call panicked");
+                    }
+                }
+            })?;
+        Ok(Self { jobs })
+    }
+
+    fn send(&self, job: Job) -> Result<()> {
+        self.jobs
+            .send(job)
+            .map_err(|_| AppError::Config("The database worker has stopped".to_string()))
+    }
+
+    /// Run `f` on the database thread and wait for its result
+    pub async fn call<T, F>(&self, f: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Database) -> Result<T> + Send + 'static,
+    {
+        let (reply, result) = oneshot::channel();
+        self.send(Box::new(move |db| {
+            let _ = reply.send(f(db));
+        }))?;
+        result
+            .await
+            .map_err(|_| AppError::Config("The database call failed".to_string()))?
+    }
+
+    /// Like `call`, blocking the current thread; not for use on the async runtime
+    pub fn call_blocking<T, F>(&self, f: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Database) -> Result<T> + Send + 'static,
+    {
+        let (reply, result) = mpsc::channel();
+        self.send(Box::new(move |db| {
+            let _ = reply.send(f(db));
+        }))?;
+        result
+            .recv()
+            .map_err(|_| AppError::Config("The database call failed".to_string()))?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::HistoryItem;
+
+    #[tokio::test]
+    async fn test_call_survives_panic() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Database::new(&dir.path().join("test.db")).unwrap();
+        let handle = DbHandle::spawn(db).unwrap();
+
+        handle
+            .call(|db| db.insert_history(&HistoryItem::default()))
+            .await
+            .unwrap();
+        let failed: Result<()> = handle.call(|_| panic!("query failed")).await;
+        assert!(failed.is_err());
+
+        let count = handle.call(|db| db.get_history_count()).await.unwrap();
+        assert_eq!(count, 1);
+        assert_eq!(
+            handle.call_blocking(|db| db.get_history_count()).unwrap(),
+            1
+        );
+    }
+}
@@ -0,0 +1,110 @@
+//! Status on the session bus
+//!
+//! Publishes the recording state, active mode and input level as properties
+//! of `com.whispertray.WhisperTray1` at `/com/whispertray/WhisperTray`, with
+//! `PropertiesChanged` signals, so a GNOME Shell extension or a custom panel
+//! widget can show the status where there is no tray.
+
+use crate::status::{status_name, PublicStatus};
+use std::time::Duration;
+use tauri::AppHandle;
+use tokio::sync::watch;
+use zbus::{dbus_interface, ConnectionBuilder, SignalContext};
+
+pub const BUS_NAME: &str = "com.whispertray.WhisperTray";
+pub const OBJECT_PATH: &str = "/com/whispertray/WhisperTray";
+
+/// Shortest time between two batches of property changes (limits level updates)
+const UPDATE_INTERVAL: Duration = Duration::from_millis(100);
+
+struct StatusInterface {
+    status: PublicStatus,
+}
+
+#[dbus_interface(name = "com.whispertray.WhisperTray1")]
+impl StatusInterface {
+    /// "ready", "recording", "paused", "processing", "loading" or "error"
+    #[dbus_interface(property)]
+    fn state(&self) -> String {
+        status_name(self.status.status).to_string()
+    }
+
+    /// Key of the active mode
+    #[dbus_interface(property)]
+    fn mode(&self) -> String {
+        self.status.mode.clone()
+    }
+
+    /// Display name of the active mode
+    #[dbus_interface(property)]
+    fn mode_name(&self) -> String {
+        self.status.mode_name.clone()
+    }
+
+    /// Input level while recording, 0.0 to 1.0
+    #[dbus_interface(property)]
+    fn level(&self) -> f64 {
+        self.status.level as f64
+    }
+}
+
+impl StatusInterface {
+    /// Store `status` and signal the properties that changed
+    async fn update(&mut self, status: PublicStatus, ctxt: &SignalContext<'_>) -> zbus::Result<()> {
+        let previous = std::mem::replace(&mut self.status, status);
+        if previous.status != self.status.status {
+            self.state_changed(ctxt).await?;
+        }
+        if previous.mode != self.status.mode {
+            self.mode_changed(ctxt).await?;
+        }
+        if previous.mode_name != self.status.mode_name {
+            self.mode_name_changed(ctxt).await?;
+        }
+        if previous.level != self.status.level {
+            self.level_changed(ctxt).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Register the status object on the session bus
+pub fn start(handle: &AppHandle) {
+    let receiver = match crate::status::subscribe(handle) {
+        Some(receiver) => receiver,
+        None => return,
+    };
+
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = serve(receiver).await {
+            log::warn!("D-Bus status service unavailable: {}", e);
+        }
+    });
+}
+
+async fn serve(mut receiver: watch::Receiver<PublicStatus>) -> zbus::Result<()> {
+    let interface = StatusInterface {
+        status: receiver.borrow().clone(),
+    };
+    let connection = ConnectionBuilder::session()?
+        .name(BUS_NAME)?
+        .serve_at(OBJECT_PATH, interface)?
+        .build()
+        .await?;
+    let interface = connection
+        .object_server()
+        .interface::<_, StatusInterface>(OBJECT_PATH)
+        .await?;
+    log::info!("Publishing status on D-Bus as {}", BUS_NAME);
+
+    while receiver.changed().await.is_ok() {
+        let status = receiver.borrow_and_update().clone();
+        interface
+            .get_mut()
+            .await
+            .update(status, interface.signal_context())
+            .await?;
+        tokio::time::sleep(UPDATE_INTERVAL).await;
+    }
+    Ok(())
+}
@@ -0,0 +1,182 @@
+//! Echo-cancelled capture
+//!
+//! When recording while speakers play (dictating during a call, music in the
+//! background), the far-end voice ends up in the transcript. With
+//! `echo_cancellation` enabled, capture goes through an echo-cancelled source
+//! of the sound server: the one named in `echo_cancel_source`, or one created
+//! on demand by loading `module-echo-cancel` with the WebRTC canceller through
+//! `pactl`, which works on PulseAudio and PipeWire. The capture stream opens
+//! on the default device and is then moved to that source.
+
+use crate::error::{AppError, Result};
+use std::process::Command;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Source created when no echo-cancelled source is configured
+pub const SOURCE_NAME: &str = "whispertray_echo_cancel";
+
+/// Sink the module creates alongside the source; playback through it is cancelled
+pub const SINK_NAME: &str = "whispertray_echo_cancel_sink";
+
+/// How often to look for the capture stream after recording starts
+const MOVE_ATTEMPTS: u32 = 20;
+
+/// Pause between looks for the capture stream
+const MOVE_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Index of the module loaded by this app, unloaded when no longer needed
+static LOADED_MODULE: Mutex<Option<u32>> = Mutex::new(None);
+
+/// Whether `pactl list short sources` output contains the source
+pub fn has_source(listing: &str, name: &str) -> bool {
+    listing
+        .lines()
+        .any(|line| line.split('\t').nth(1) == Some(name))
+}
+
+fn pactl(args: &[&str]) -> Result<String> {
+    // Untranslated output, as it is parsed
+    let output = Command::new("pactl")
+        .env("LC_ALL", "C")
+        .args(args)
+        .output()
+        .map_err(|e| AppError::Audio(format!("pactl not available: {}", e)))?;
+    if !output.status.success() {
+        return Err(AppError::Audio(format!(
+            "pactl {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Make sure an echo-cancelled source exists and return its name
+///
+/// `configured` names an existing source; when empty, `module-echo-cancel`
+/// is loaded on top of the default source unless it already is.
+pub fn ensure_source(configured: &str) -> Result<String> {
+    let sources = pactl(&["list", "short", "sources"])?;
+    if !configured.is_empty() {
+        if !has_source(&sources, configured) {
+            return Err(AppError::Audio(format!(
+                "Echo-cancelled source not found: {}",
+                configured
+            )));
+        }
+        return Ok(configured.to_string());
+    }
+    if has_source(&sources, SOURCE_NAME) {
+        return Ok(SOURCE_NAME.to_string());
+    }
+
+    let index = pactl(&[
+        "load-module",
+        "module-echo-cancel",
+        "aec_method=webrtc",
+        &format!("source_name={}", SOURCE_NAME),
+        &format!("sink_name={}", SINK_NAME),
+    ])?;
+    let index = index
+        .trim()
+        .parse::<u32>()
+        .map_err(|_| AppError::Audio(format!("Unexpected pactl output: {}", index.trim())))?;
+    if let Ok(mut loaded) = LOADED_MODULE.lock() {
+        *loaded = Some(index);
+    }
+    log::info!("Loaded module-echo-cancel (module {})", index);
+    Ok(SOURCE_NAME.to_string())
+}
+
+/// Ids of the capture streams of process `pid` in `pactl list source-outputs` output
+pub fn source_outputs_of(listing: &str, pid: u32) -> Vec<u32> {
+    let pid = format!("\"{}\"", pid);
+    let mut outputs = Vec::new();
+    let mut current: Option<u32> = None;
+    for line in listing.lines().map(str::trim) {
+        if let Some(id) = line.strip_prefix("Source Output #") {
+            current = id.parse().ok();
+        } else if line.strip_prefix("application.process.id = ") == Some(pid.as_str()) {
+            outputs.extend(current);
+        }
+    }
+    outputs
+}
+
+/// Move this app's capture stream to `source` once the sound server lists it
+///
+/// The stream has to be opened on the default device, which the sound server
+/// serves. It appears shortly after recording starts, so this runs in the
+/// background.
+pub fn move_capture(source: String) {
+    std::thread::spawn(move || {
+        let pid = std::process::id();
+        for _ in 0..MOVE_ATTEMPTS {
+            let outputs = pactl(&["list", "source-outputs"])
+                .map(|listing| source_outputs_of(&listing, pid))
+                .unwrap_or_default();
+            if !outputs.is_empty() {
+                for id in outputs {
+                    if let Err(e) = pactl(&["move-source-output", &id.to_string(), &source]) {
+                        log::warn!("Failed to record through echo-cancelled source: {}", e);
+                    }
+                }
+                return;
+            }
+            std::thread::sleep(MOVE_INTERVAL);
+        }
+        log::warn!("Capture stream not found, recording without echo cancellation");
+    });
+}
+
+/// Unload the module if this app loaded it
+pub fn unload() {
+    let index = LOADED_MODULE
+        .lock()
+        .ok()
+        .and_then(|mut loaded| loaded.take());
+    if let Some(index) = index {
+        match pactl(&["unload-module", &index.to_string()]) {
+            Ok(_) => log::info!("Unloaded module-echo-cancel (module {})", index),
+            Err(e) => log::warn!("Failed to unload module-echo-cancel: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_has_source() {
+        let listing = "\
+49\talsa_input.pci-0000_00_1f.3.analog-stereo\tPipeWire\ts32le 2ch 48000Hz\tSUSPENDED
+52\twhispertray_echo_cancel\tPipeWire\tfloat32le 1ch 48000Hz\tRUNNING
+";
+        assert!(has_source(listing, SOURCE_NAME));
+        assert!(has_source(
+            listing,
+            "alsa_input.pci-0000_00_1f.3.analog-stereo"
+        ));
+        assert!(!has_source(listing, "echo"));
+        assert!(!has_source("", SOURCE_NAME));
+    }
+
+    #[test]
+    fn test_source_outputs_of() {
+        let listing = "\
+Source Output #71
+\tDriver: PipeWire
+\tProperties:
+\t\tapplication.name = \"ALSA plug-in [whispertray]\"
+\t\tapplication.process.id = \"4242\"
+
+Source Output #75
+\tProperties:
+\t\tapplication.process.id = \"1234\"
+";
+        assert_eq!(source_outputs_of(listing, 4242), vec![71]);
+        assert!(source_outputs_of(listing, 99).is_empty());
+    }
+}
@@ -52,11 +52,24 @@ pub enum AppError {
 
     #[error("Operation cancelled")]
     Cancelled,
+
+    #[error("Blocked by local-only mode: {0}")]
+    LocalOnly(String),
+
+    #[error("Nothing to refine yet")]
+    NothingToRefine,
+}
+
+impl AppError {
+    /// Message in the user's language (falls back to English)
+    pub fn localized(&self) -> String {
+        crate::i18n::error_message(self)
+    }
 }
 
 impl From<AppError> for String {
     fn from(error: AppError) -> Self {
-        error.to_string()
+        error.localized()
     }
 }
 
@@ -108,6 +121,12 @@ impl From<hound::Error> for AppError {
     }
 }
 
+impl From<zip::result::ZipError> for AppError {
+    fn from(err: zip::result::ZipError) -> Self {
+        AppError::Io(std::io::Error::other(err))
+    }
+}
+
 impl From<tauri::Error> for AppError {
     fn from(err: tauri::Error) -> Self {
         AppError::Tauri(err.to_string())
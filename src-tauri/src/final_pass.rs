@@ -0,0 +1,160 @@
+//! Two-stage draft/final transcription
+//!
+//! With `final_stt_model` set, a dictation is transcribed and pasted with the
+//! mode's model as a draft (a fast one such as tiny or base), then transcribed
+//! again in the background with the final provider and model, e.g. large-v3
+//! or a cloud server. The history item gets the better transcript, the mode
+//! runs again over it, and a notification can offer the result for copying.
+
+use crate::corrections;
+use crate::db_worker::DbHandle;
+use crate::error::{AppError, Result};
+use crate::modes::{Mode, SttProvider as SttProviderType};
+use crate::notify;
+use crate::providers::stt::{self, SttServer};
+use crate::redact;
+use crate::state::SharedState;
+use serde::Serialize;
+use std::borrow::Cow;
+use tauri::{AppHandle, Emitter, Manager};
+
+/// Event emitted when a history item got its final transcript
+pub const FINAL_TRANSCRIPT_EVENT: &str = "history-final";
+
+/// Payload of `FINAL_TRANSCRIPT_EVENT`
+#[derive(Debug, Clone, Serialize)]
+pub struct FinalTranscript {
+    pub history_id: String,
+    pub output: String,
+}
+
+/// A draft dictation to transcribe again
+pub struct FinalPass {
+    pub history_id: String,
+    /// The dictation's mode with the final STT provider and model
+    pub mode: Mode,
+    pub samples: Vec<f32>,
+    pub server: SttServer,
+    pub language: String,
+    pub local_only: bool,
+    /// Boost cloud providers with the learned corrections
+    pub learn_corrections: bool,
+    pub silence_padding_ms: u64,
+    /// Names to redact from the stored transcript, if history is redacted
+    pub redact_names: Option<Vec<String>>,
+    pub notify: bool,
+}
+
+/// Whether the final provider and model differ from the mode's draft ones
+pub fn is_needed(mode: &Mode, provider: &SttProviderType, model: &str) -> bool {
+    let model = model.trim();
+    !model.is_empty() && (*provider != mode.stt_provider || model != mode.stt_model)
+}
+
+/// Transcribe the dictation with the final model and update its history item
+///
+/// Returns the new output, or `None` if the item is gone or nothing was recognized.
+async fn run(app_handle: &AppHandle, db: DbHandle, pass: &FinalPass) -> Result<Option<String>> {
+    let corrections = if pass.learn_corrections {
+        db.call(|db| db.get_corrections()).await.unwrap_or_default()
+    } else {
+        Vec::new()
+    };
+    let mode = corrections::boost_mode(&pass.mode, &corrections);
+    let provider = stt::create_stt_provider(&mode, &pass.server, pass.local_only).await?;
+    let samples = if pass.mode.stt_provider == SttProviderType::WhisperCpp {
+        crate::audio::pad_with_silence(&pass.samples, pass.silence_padding_ms)
+    } else {
+        Cow::Borrowed(pass.samples.as_slice())
+    };
+    let transcription = provider
+        .transcribe_detailed(&samples, Some(&pass.language))
+        .await?;
+    if transcription.text.trim().is_empty() {
+        return Ok(None);
+    }
+
+    let id = pass.history_id.clone();
+    let mut item = match db.call(move |db| db.get_history_item(&id)).await? {
+        Some(item) => item,
+        None => return Ok(None),
+    };
+    let mut segments = transcription.segments;
+    item.confidence = stt::average_confidence(&segments);
+    item.transcript_raw = transcription.text;
+    if let Some(names) = &pass.redact_names {
+        item.transcript_raw = redact::redact(&item.transcript_raw, names).text;
+        for segment in &mut segments {
+            segment.text = redact::redact(&segment.text, names).text;
+        }
+    }
+    item.stt_provider = format!("{:?}", pass.mode.stt_provider).to_lowercase();
+    item.stt_model = pass.mode.stt_model.clone();
+    db.call(move |db| {
+        db.update_history_transcript(&item)?;
+        db.insert_segments(&item.id, &segments)
+    })
+    .await?;
+
+    // Run the mode again over the new transcript
+    let state = app_handle
+        .try_state::<SharedState>()
+        .ok_or_else(|| AppError::Config("Application state not initialized".to_string()))?;
+    let output = crate::commands::reprocess_in_background(
+        state.inner(),
+        &pass.history_id,
+        pass.mode.key.clone(),
+    )
+    .await
+    .map_err(AppError::Provider)?;
+    Ok(Some(output))
+}
+
+/// Run the final transcription in the background
+pub fn spawn(app_handle: AppHandle, db: DbHandle, pass: FinalPass) {
+    tauri::async_runtime::spawn(async move {
+        log::info!(
+            "Starting final transcription with {:?} {}",
+            pass.mode.stt_provider,
+            pass.mode.stt_model
+        );
+        let output = match run(&app_handle, db, &pass).await {
+            Ok(Some(output)) => output,
+            Ok(None) => return,
+            Err(e) => {
+                log::warn!("Final transcription failed: {}", e);
+                return;
+            }
+        };
+
+        log::info!("Final transcription complete: {} chars", output.len());
+        let _ = app_handle.emit(
+            FINAL_TRANSCRIPT_EVENT,
+            FinalTranscript {
+                history_id: pass.history_id,
+                output: output.clone(),
+            },
+        );
+        if pass.notify {
+            notify::final_transcript(output);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_needed() {
+        let mode = Mode {
+            stt_provider: SttProviderType::WhisperCpp,
+            stt_model: "base.en".to_string(),
+            ..Mode::default()
+        };
+        assert!(is_needed(&mode, &SttProviderType::WhisperCpp, "large-v3"));
+        assert!(is_needed(&mode, &SttProviderType::OpenAI, "base.en"));
+        assert!(!is_needed(&mode, &SttProviderType::WhisperCpp, "base.en"));
+        assert!(!is_needed(&mode, &SttProviderType::OpenAI, " "));
+    }
+}
@@ -0,0 +1,192 @@
+//! Flashcard export
+//!
+//! Turns history items into front/back pairs, e.g. the spoken phrase and its
+//! translation from a translate mode. Anki reads the tab-separated format
+//! directly through File → Import, with the note type, deck and tag column
+//! set by its header lines; the CSV format suits other flashcard apps.
+
+use crate::database::HistoryItem;
+use serde::{Deserialize, Serialize};
+
+/// Output file format
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FlashcardFormat {
+    /// Tab-separated text with Anki import headers
+    #[default]
+    Anki,
+    Csv,
+}
+
+/// History item field shown on a card side
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CardField {
+    Transcript,
+    Output,
+    Title,
+    Mode,
+    Date,
+}
+
+impl CardField {
+    fn value(self, item: &HistoryItem) -> String {
+        match self {
+            CardField::Transcript => item.transcript_raw.trim().to_string(),
+            CardField::Output => item.output_final.trim().to_string(),
+            CardField::Title => item.title.clone().unwrap_or_default(),
+            CardField::Mode => item.mode_key.clone(),
+            CardField::Date => item.created_at.format("%Y-%m-%d").to_string(),
+        }
+    }
+}
+
+/// How history items map to cards
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FlashcardOptions {
+    pub format: FlashcardFormat,
+    pub front: CardField,
+    pub back: CardField,
+    /// Anki deck the cards are imported into
+    pub deck: String,
+    /// Tags added to every card, besides the item's mode
+    pub tags: Vec<String>,
+}
+
+impl Default for FlashcardOptions {
+    fn default() -> Self {
+        Self {
+            format: FlashcardFormat::Anki,
+            front: CardField::Transcript,
+            back: CardField::Output,
+            deck: "WhisperTray".to_string(),
+            tags: Vec::new(),
+        }
+    }
+}
+
+/// Render the cards for `items`, skipping items with an empty side
+///
+/// Returns the file contents and the number of cards.
+pub fn export(items: &[HistoryItem], options: &FlashcardOptions) -> (String, usize) {
+    let separator = match options.format {
+        FlashcardFormat::Anki => '\t',
+        FlashcardFormat::Csv => ',',
+    };
+
+    let mut out = String::new();
+    match options.format {
+        FlashcardFormat::Anki => {
+            out.push_str("#separator:tab\n#html:false\n#notetype:Basic\n");
+            if !options.deck.trim().is_empty() {
+                out.push_str(&format!("#deck:{}\n", options.deck.trim()));
+            }
+            out.push_str("#tags column:3\n");
+        }
+        FlashcardFormat::Csv => out.push_str("Front,Back,Tags\n"),
+    }
+
+    let mut cards = 0;
+    for item in items {
+        let front = options.front.value(item);
+        let back = options.back.value(item);
+        if front.is_empty() || back.is_empty() {
+            continue;
+        }
+        let tags = std::iter::once(item.mode_key.as_str())
+            .chain(options.tags.iter().map(String::as_str))
+            .map(tag)
+            .filter(|tag| !tag.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+        let row = [front, back, tags]
+            .iter()
+            .map(|value| quote(value, separator))
+            .collect::<Vec<_>>()
+            .join(&separator.to_string());
+        out.push_str(&row);
+        out.push('\n');
+        cards += 1;
+    }
+    (out, cards)
+}
+
+/// Tags are separated by spaces, so spaces inside a tag become underscores
+fn tag(name: &str) -> String {
+    name.split_whitespace().collect::<Vec<_>>().join("_")
+}
+
+/// Quote a field that contains the separator, quotes or line breaks
+fn quote(value: &str, separator: char) -> String {
+    if value.contains([separator, '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn item(transcript: &str, output: &str) -> HistoryItem {
+        HistoryItem {
+            id: "1".to_string(),
+            created_at: Utc::now(),
+            mode_key: "translate".to_string(),
+            audio_path: None,
+            transcript_raw: transcript.to_string(),
+            output_final: output.to_string(),
+            stt_provider: "whisper_cpp".to_string(),
+            stt_model: "base".to_string(),
+            llm_provider: None,
+            llm_model: None,
+            duration_ms: 1000,
+            error: None,
+            confidence: None,
+            title: None,
+            latencies: None,
+            provider_metadata: None,
+            clipped: false,
+            transcript_path: None,
+        }
+    }
+
+    #[test]
+    fn test_anki_export() {
+        let items = [
+            item("Where is the station?", "Wo ist der Bahnhof?"),
+            item("Two lines", "Zwei\n\"Zeilen\""),
+            item("Nothing came back", ""),
+        ];
+        let options = FlashcardOptions {
+            tags: vec!["german travel".to_string()],
+            ..FlashcardOptions::default()
+        };
+        let (content, cards) = export(&items, &options);
+        assert_eq!(cards, 2);
+        assert_eq!(
+            content,
+            "#separator:tab\n#html:false\n#notetype:Basic\n#deck:WhisperTray\n#tags column:3\n\
+             Where is the station?\tWo ist der Bahnhof?\ttranslate german_travel\n\
+             Two lines\t\"Zwei\n\"\"Zeilen\"\"\"\ttranslate german_travel\n"
+        );
+    }
+
+    #[test]
+    fn test_csv_export_with_swapped_sides() {
+        let options = FlashcardOptions {
+            format: FlashcardFormat::Csv,
+            front: CardField::Output,
+            back: CardField::Transcript,
+            ..FlashcardOptions::default()
+        };
+        let (content, _) = export(&[item("Good morning, all", "Guten Morgen")], &options);
+        assert_eq!(
+            content,
+            "Front,Back,Tags\nGuten Morgen,\"Good morning, all\",translate\n"
+        );
+    }
+}
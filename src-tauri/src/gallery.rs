@@ -0,0 +1,182 @@
+//! Mode template gallery
+//!
+//! A gallery is a JSON index listing mode files by URL:
+//!
+//! ```json
+//! {"modes": [{"key": "standup", "name": "Standup", "description": "...", "url": "standup.json"}]}
+//! ```
+//!
+//! Relative URLs are resolved against the index URL. Installed modes are
+//! validated and never replace builtin modes; key collisions get a numeric
+//! suffix unless the user chooses to overwrite a custom mode.
+
+use crate::error::{AppError, Result};
+use crate::modes::Mode;
+use serde::{Deserialize, Serialize};
+
+/// A mode listed in the gallery index
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GalleryEntry {
+    pub key: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub author: String,
+    pub url: String,
+    /// Whether a mode with this key is already installed (filled in locally)
+    #[serde(default)]
+    pub installed: bool,
+}
+
+#[derive(Deserialize)]
+struct GalleryIndex {
+    #[serde(default)]
+    modes: Vec<GalleryEntry>,
+}
+
+async fn fetch_text(url: &str) -> Result<String> {
+    let response = reqwest::Client::new()
+        .get(url)
+        .timeout(std::time::Duration::from_secs(15))
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        return Err(AppError::Config(format!(
+            "Failed to fetch {}: HTTP {}",
+            url,
+            response.status()
+        )));
+    }
+
+    Ok(response.text().await?)
+}
+
+/// Resolve a possibly relative entry URL against the index URL
+fn resolve_url(index_url: &str, url: &str) -> Result<String> {
+    let base = reqwest::Url::parse(index_url)
+        .map_err(|e| AppError::Config(format!("Invalid gallery URL: {}", e)))?;
+    let resolved = base
+        .join(url)
+        .map_err(|e| AppError::Config(format!("Invalid mode URL '{}': {}", url, e)))?;
+    Ok(resolved.to_string())
+}
+
+/// Parse a gallery index, resolving entry URLs
+fn parse_index(index_url: &str, body: &str) -> Result<Vec<GalleryEntry>> {
+    let index: GalleryIndex = serde_json::from_str(body)?;
+    index
+        .modes
+        .into_iter()
+        .map(|entry| {
+            Ok(GalleryEntry {
+                url: resolve_url(index_url, &entry.url)?,
+                ..entry
+            })
+        })
+        .collect()
+}
+
+/// Fetch the gallery index
+pub async fn browse(index_url: &str) -> Result<Vec<GalleryEntry>> {
+    if index_url.trim().is_empty() {
+        return Err(AppError::Config("No mode gallery configured".to_string()));
+    }
+
+    let body = fetch_text(index_url).await?;
+    parse_index(index_url, &body)
+}
+
+/// Fetch and validate a mode file
+pub async fn fetch_mode(url: &str) -> Result<Mode> {
+    let body = fetch_text(url).await?;
+    parse_mode(&body)
+}
+
+fn parse_mode(body: &str) -> Result<Mode> {
+    let mut mode: Mode = serde_json::from_str(body)?;
+    mode.validate()?;
+
+    // Downloaded modes are always custom and start out unpinned
+    mode.builtin = false;
+    mode.favorite = false;
+    mode.sort_order = None;
+    // Which services see the audio, clipboard and output is up to the user, not
+    // the mode's author
+    let defaults = Mode::default();
+    if !mode.stt_provider.is_local() {
+        mode.stt_provider = defaults.stt_provider;
+        mode.stt_model = defaults.stt_model;
+    }
+    mode.llm_provider = defaults.llm_provider;
+    mode.llm_model = defaults.llm_model;
+    mode.clipboard_context = false;
+    mode.extract_action_items = false;
+    mode.post_to.clear();
+    mode.task_target = None;
+    Ok(mode)
+}
+
+/// Pick a key that does not collide with an existing mode
+pub fn unique_key(key: &str, exists: impl Fn(&str) -> bool) -> String {
+    if !exists(key) {
+        return key.to_string();
+    }
+
+    (2..)
+        .map(|n| format!("{}_{}", key, n))
+        .find(|candidate| !exists(candidate))
+        .unwrap_or_else(|| key.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_index_resolves_urls() {
+        let entries = parse_index(
+            "https://example.com/gallery/index.json",
+            r#"{"modes": [
+                {"key": "standup", "name": "Standup", "url": "modes/standup.json"},
+                {"key": "tweet", "name": "Tweet", "url": "https://cdn.example.org/tweet.json"}
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            entries[0].url,
+            "https://example.com/gallery/modes/standup.json"
+        );
+        assert_eq!(entries[1].url, "https://cdn.example.org/tweet.json");
+        assert!(!entries[0].installed);
+    }
+
+    #[test]
+    fn test_parse_mode_marks_custom() {
+        let mode = parse_mode(
+            r#"{"key": "standup", "name": "Standup", "description": "Daily standup notes",
+                "builtin": true, "favorite": true, "ai_processing": true,
+                "prompt_template": "Summarize: {{transcript}}", "post_to": [{"service": "slack"}],
+                "task_target": {"service": "todoist"}, "llm_provider": "openai",
+                "llm_model": "gpt-4o", "clipboard_context": true, "extract_action_items": true}"#,
+        )
+        .unwrap();
+        assert!(!mode.builtin);
+        assert!(!mode.favorite);
+        assert!(mode.post_to.is_empty());
+        assert!(mode.task_target.is_none());
+        assert_eq!(mode.llm_provider, Mode::default().llm_provider);
+        assert!(mode.llm_model.is_empty());
+        assert!(!mode.clipboard_context && !mode.extract_action_items);
+
+        assert!(parse_mode(r#"{"key": "Bad Key", "name": "x", "description": ""}"#).is_err());
+    }
+
+    #[test]
+    fn test_unique_key() {
+        let taken = ["note", "note_2"];
+        assert_eq!(unique_key("standup", |k| taken.contains(&k)), "standup");
+        assert_eq!(unique_key("note", |k| taken.contains(&k)), "note_3");
+    }
+}
@@ -0,0 +1,460 @@
+//! History maintenance
+//!
+//! A meeting captured as several stop/start recordings can be merged into one
+//! history item. Transcripts and outputs are concatenated in chronological
+//! order, segments are shifted onto a combined timeline and every recording
+//! is kept as an audio part of the merged item.
+//!
+//! Accidental double recordings (e.g. a double hotkey press) are detected as
+//! consecutive items with near-identical transcripts a few seconds apart.
+
+use crate::database::{AudioPart, Database, HistoryItem, Segment};
+use crate::error::{AppError, Result};
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Transcripts at least this similar count as the same dictation
+const DUPLICATE_SIMILARITY: f32 = 0.9;
+
+/// A history item with its segments and audio files
+#[derive(Debug, Clone)]
+pub struct Record {
+    pub item: HistoryItem,
+    pub segments: Vec<Segment>,
+    pub audio: Vec<AudioPart>,
+}
+
+/// Load a history item with its segments and audio files
+pub fn load_record(db: &Database, id: &str) -> Result<Record> {
+    let item = db
+        .get_history_item(id)?
+        .ok_or_else(|| AppError::Config(format!("History item not found: {}", id)))?;
+    let segments = db.get_segments(id)?;
+
+    // Unmerged items keep their single recording in `audio_path`
+    let mut audio = db.get_audio_parts(id)?;
+    if audio.is_empty() {
+        if let Some(path) = &item.audio_path {
+            audio.push(AudioPart {
+                offset_ms: 0,
+                path: path.clone(),
+            });
+        }
+    }
+
+    Ok(Record {
+        item,
+        segments,
+        audio,
+    })
+}
+
+/// Join non-empty texts with a blank line
+fn join_texts<'a>(texts: impl Iterator<Item = &'a str>) -> String {
+    texts
+        .map(str::trim)
+        .filter(|text| !text.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// Combine records into one, in chronological order
+pub fn merge(mut records: Vec<Record>) -> Result<Record> {
+    if records.len() < 2 {
+        return Err(AppError::Config(
+            "Select at least two history items to merge".to_string(),
+        ));
+    }
+    records.sort_by_key(|record| record.item.created_at);
+
+    let mut segments = Vec::new();
+    let mut audio = Vec::new();
+    let mut offset_ms = 0;
+    for record in &records {
+        segments.extend(record.segments.iter().map(|segment| Segment {
+            start_ms: segment.start_ms + offset_ms,
+            end_ms: segment.end_ms + offset_ms,
+            ..segment.clone()
+        }));
+        audio.extend(record.audio.iter().map(|part| AudioPart {
+            offset_ms: part.offset_ms + offset_ms,
+            path: part.path.clone(),
+        }));
+        offset_ms += record.item.duration_ms;
+    }
+
+    // Confidence weighted by the duration of the items that report one
+    let (weighted, weight) = records
+        .iter()
+        .filter_map(|r| r.item.confidence.map(|c| (c, r.item.duration_ms.max(1))))
+        .fold((0.0, 0u64), |(sum, total), (c, ms)| {
+            (sum + c * ms as f32, total + ms)
+        });
+    let confidence = (weight > 0).then(|| weighted / weight as f32);
+
+    let first = &records[0].item;
+    let item = HistoryItem {
+        id: Uuid::new_v4().to_string(),
+        created_at: first.created_at,
+        mode_key: first.mode_key.clone(),
+        audio_path: audio.first().map(|part| part.path.clone()),
+        transcript_raw: join_texts(records.iter().map(|r| r.item.transcript_raw.as_str())),
+        output_final: join_texts(records.iter().map(|r| r.item.output_final.as_str())),
+        stt_provider: first.stt_provider.clone(),
+        stt_model: first.stt_model.clone(),
+        llm_provider: first.llm_provider.clone(),
+        llm_model: first.llm_model.clone(),
+        duration_ms: offset_ms,
+        error: None,
+        confidence,
+        title: None,
+        latencies: None,
+        provider_metadata: None,
+        clipped: false,
+        transcript_path: records.iter().find_map(|r| r.item.transcript_path.clone()),
+    };
+
+    Ok(Record {
+        item,
+        segments,
+        audio,
+    })
+}
+
+/// Merge history items into a new item, replacing the originals
+///
+/// Audio files are not deleted; they become parts of the merged item.
+pub fn merge_items(db: &Database, ids: &[String]) -> Result<HistoryItem> {
+    let records = ids
+        .iter()
+        .map(|id| load_record(db, id))
+        .collect::<Result<Vec<_>>>()?;
+    let merged = merge(records)?;
+
+    db.insert_history(&merged.item)?;
+    db.insert_segments(&merged.item.id, &merged.segments)?;
+    db.insert_audio_parts(&merged.item.id, &merged.audio)?;
+    for id in ids {
+        db.delete_history(id)?;
+    }
+
+    log::info!("Merged {} history items into {}", ids.len(), merged.item.id);
+    Ok(merged.item)
+}
+
+/// Delete a history item with its audio and live transcript files
+pub fn delete_item(db: &Database, id: &str) -> Result<()> {
+    // Merged items own every recording they were combined from
+    let mut paths: Vec<String> = db
+        .get_audio_parts(id)?
+        .into_iter()
+        .map(|part| part.path)
+        .collect();
+    if let Some(item) = db.get_history_item(id)? {
+        paths.extend(item.audio_path);
+        paths.extend(item.transcript_path);
+    }
+
+    for path in paths {
+        let _ = std::fs::remove_file(path);
+    }
+    db.delete_history(id)
+}
+
+/// Delete several items and their audio and live transcript files
+///
+/// The rows go in one transaction, so a failure leaves the history as it
+/// was; the files are removed afterwards, reporting progress in
+/// percent. Returns the number of deleted items.
+pub fn delete_items(
+    db: &Database,
+    ids: &[String],
+    mut on_progress: impl FnMut(u8),
+) -> Result<usize> {
+    let mut paths = Vec::new();
+    for id in ids {
+        paths.extend(db.get_audio_parts(id)?.into_iter().map(|part| part.path));
+        if let Some(item) = db.get_history_item(id)? {
+            paths.extend(item.audio_path);
+            paths.extend(item.transcript_path);
+        }
+    }
+
+    db.delete_history_items(ids)?;
+
+    for (i, path) in paths.iter().enumerate() {
+        let _ = std::fs::remove_file(path);
+        on_progress(((i + 1) * 100 / paths.len()) as u8);
+    }
+    Ok(ids.len())
+}
+
+/// Parse the cut-off of `clear_history`: RFC 3339, or a date meaning local midnight
+pub fn parse_before_date(date: &str) -> Result<DateTime<Utc>> {
+    let date = date.trim();
+    if let Ok(time) = DateTime::parse_from_rfc3339(date) {
+        return Ok(time.with_timezone(&Utc));
+    }
+    NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .ok()
+        .and_then(|day| day.and_hms_opt(0, 0, 0))
+        .and_then(|midnight| midnight.and_local_timezone(Local).earliest())
+        .map(|time| time.with_timezone(&Utc))
+        .ok_or_else(|| AppError::Config(format!("Invalid date: {}", date)))
+}
+
+/// Lowercase words of a transcript, ignoring punctuation
+fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric() && c != '\'')
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Word overlap between two transcripts (0.0-1.0)
+pub fn similarity(a: &str, b: &str) -> f32 {
+    let (a, b) = (words(a), words(b));
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for word in &a {
+        *counts.entry(word).or_default() += 1;
+    }
+    let common = b
+        .iter()
+        .filter(|word| match counts.get_mut(word.as_str()) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                true
+            }
+            _ => false,
+        })
+        .count();
+
+    2.0 * common as f32 / (a.len() + b.len()) as f32
+}
+
+/// Whether `later` repeats `earlier` within `window_secs`
+pub fn is_duplicate(earlier: &HistoryItem, later: &HistoryItem, window_secs: u64) -> bool {
+    let gap = later.created_at - earlier.created_at;
+    gap >= chrono::Duration::zero()
+        && gap <= chrono::Duration::seconds(window_secs as i64)
+        && similarity(&earlier.transcript_raw, &later.transcript_raw) >= DUPLICATE_SIMILARITY
+}
+
+/// Ids of redundant items among `items` (oldest first)
+///
+/// Of two duplicates the one with the longer transcript is kept, the earlier
+/// one on a tie.
+pub fn find_duplicates(items: &[HistoryItem], window_secs: u64) -> Vec<String> {
+    let mut redundant = Vec::new();
+    let mut kept = match items.first() {
+        Some(item) => item,
+        None => return redundant,
+    };
+
+    for item in &items[1..] {
+        if !is_duplicate(kept, item, window_secs) {
+            kept = item;
+        } else if item.transcript_raw.len() > kept.transcript_raw.len() {
+            redundant.push(kept.id.clone());
+            kept = item;
+        } else {
+            redundant.push(item.id.clone());
+        }
+    }
+    redundant
+}
+
+/// Remove duplicate recordings from the whole history, returning how many were removed
+pub fn remove_duplicates(db: &Database, window_secs: u64) -> Result<usize> {
+    let mut items = db.get_history(db.get_history_count()?, 0)?;
+    items.reverse();
+
+    let redundant = find_duplicates(&items, window_secs);
+    for id in &redundant {
+        delete_item(db, id)?;
+    }
+
+    if !redundant.is_empty() {
+        log::info!("Removed {} duplicate history items", redundant.len());
+    }
+    Ok(redundant.len())
+}
+
+/// Remove the newest item or the one before it if they are duplicates
+///
+/// Returns the id of the removed item.
+pub fn dedup_latest(db: &Database, window_secs: u64) -> Result<Option<String>> {
+    let mut items = db.get_history(2, 0)?;
+    items.reverse();
+
+    match find_duplicates(&items, window_secs).pop() {
+        Some(id) => {
+            delete_item(db, &id)?;
+            log::info!("Removed duplicate history item {}", id);
+            Ok(Some(id))
+        }
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn record(id: &str, minutes: i64, text: &str, audio: Option<&str>) -> Record {
+        Record {
+            item: HistoryItem {
+                id: id.to_string(),
+                created_at: Utc::now() + Duration::minutes(minutes),
+                mode_key: "meeting".to_string(),
+                audio_path: audio.map(str::to_string),
+                transcript_raw: text.to_string(),
+                output_final: text.to_uppercase(),
+                duration_ms: 2000,
+                confidence: Some(0.8),
+                ..HistoryItem::default()
+            },
+            segments: vec![Segment {
+                start_ms: 500,
+                end_ms: 1500,
+                text: text.to_string(),
+                confidence: None,
+                speaker: None,
+            }],
+            audio: audio
+                .map(|path| AudioPart {
+                    offset_ms: 0,
+                    path: path.to_string(),
+                })
+                .into_iter()
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_merge_in_chronological_order() {
+        let merged = merge(vec![
+            record("b", 5, "second part", Some("/b.wav")),
+            record("a", 0, "first part", Some("/a.wav")),
+            record("c", 9, "", None),
+        ])
+        .unwrap();
+
+        assert_eq!(merged.item.transcript_raw, "first part\n\nsecond part");
+        assert_eq!(merged.item.output_final, "FIRST PART\n\nSECOND PART");
+        assert_eq!(merged.item.duration_ms, 6000);
+        assert_eq!(merged.item.audio_path.as_deref(), Some("/a.wav"));
+        assert_eq!(merged.item.confidence, Some(0.8));
+
+        let starts: Vec<u64> = merged.segments.iter().map(|s| s.start_ms).collect();
+        assert_eq!(starts, [500, 2500, 4500]);
+        assert_eq!(
+            merged.audio,
+            [
+                AudioPart {
+                    offset_ms: 0,
+                    path: "/a.wav".to_string()
+                },
+                AudioPart {
+                    offset_ms: 2000,
+                    path: "/b.wav".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_similarity() {
+        assert_eq!(similarity("Send the report.", "send the report"), 1.0);
+        assert!(similarity("send the report today", "send the report") > 0.8);
+        assert!(similarity("send the report", "call me tomorrow") < 0.1);
+        assert_eq!(similarity("", ""), 0.0);
+    }
+
+    #[test]
+    fn test_find_duplicates() {
+        let items: Vec<HistoryItem> = [
+            (0, "Send the report to Sam."),
+            (3, "send the report to Sam"),
+            (40, "Send the report to Sam."),
+            (42, "Send the report to Sam, please."),
+        ]
+        .iter()
+        .enumerate()
+        .map(|(i, (secs, text))| HistoryItem {
+            id: i.to_string(),
+            created_at: chrono::DateTime::UNIX_EPOCH + Duration::seconds(*secs),
+            transcript_raw: text.to_string(),
+            ..HistoryItem::default()
+        })
+        .collect();
+
+        // The longer transcript of each pair is kept
+        assert_eq!(find_duplicates(&items, 10), ["1", "2"]);
+        assert_eq!(find_duplicates(&items, 2), ["2"]);
+    }
+
+    #[test]
+    fn test_merge_needs_two_items() {
+        assert!(merge(vec![record("a", 0, "only", None)]).is_err());
+    }
+
+    #[test]
+    fn test_merge_items_replaces_originals() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Database::new(&dir.path().join("test.db")).unwrap();
+        for r in [
+            record("a", 0, "one", Some("/a.wav")),
+            record("b", 1, "two", Some("/b.wav")),
+        ] {
+            db.insert_history(&r.item).unwrap();
+            db.insert_segments(&r.item.id, &r.segments).unwrap();
+        }
+
+        let merged = merge_items(&db, &["b".to_string(), "a".to_string()]).unwrap();
+        assert!(db.get_history_item("a").unwrap().is_none());
+        assert!(db.get_history_item("b").unwrap().is_none());
+        assert_eq!(db.get_segments(&merged.id).unwrap().len(), 2);
+
+        let reloaded = load_record(&db, &merged.id).unwrap();
+        assert_eq!(reloaded.item.transcript_raw, "one\n\ntwo");
+        assert_eq!(reloaded.audio.len(), 2);
+    }
+
+    #[test]
+    fn test_delete_items_removes_audio() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Database::new(&dir.path().join("test.db")).unwrap();
+        let audio = dir.path().join("a.wav");
+        std::fs::write(&audio, b"RIFF").unwrap();
+        let transcript = dir.path().join("b.txt");
+        std::fs::write(&transcript, "[00:00:00] two\n").unwrap();
+        let mut b = record("b", 1, "two", None);
+        b.item.transcript_path = Some(transcript.to_string_lossy().to_string());
+        for r in [record("a", 0, "one", Some(audio.to_str().unwrap())), b] {
+            db.insert_history(&r.item).unwrap();
+        }
+
+        let mut reported = Vec::new();
+        let ids = ["a".to_string(), "b".to_string()];
+        assert_eq!(delete_items(&db, &ids, |p| reported.push(p)).unwrap(), 2);
+        assert_eq!(db.get_history_count().unwrap(), 0);
+        assert!(!audio.exists());
+        assert!(!transcript.exists());
+        assert_eq!(reported, [50, 100]);
+    }
+
+    #[test]
+    fn test_parse_before_date() {
+        let time = parse_before_date("2024-03-01T12:00:00Z").unwrap();
+        assert_eq!(time.to_rfc3339(), "2024-03-01T12:00:00+00:00");
+        assert!(parse_before_date("2024-03-01").is_ok());
+        assert!(parse_before_date("yesterday").is_err());
+    }
+}
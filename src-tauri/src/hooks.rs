@@ -0,0 +1,190 @@
+//! External command hooks
+//!
+//! At configurable points of the pipeline the text is piped through a
+//! user-specified command (run with `sh -c`), and its stdout becomes the new
+//! text. This allows arbitrary custom processing without forking the app. A
+//! failing or slow hook is logged and the text passes through unchanged.
+
+use crate::error::{AppError, Result};
+use serde::{Deserialize, Serialize};
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+/// Point of the pipeline a hook runs at
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookPoint {
+    /// Raw transcript, before snippets and AI processing
+    PostTranscription,
+    /// Output of the LLM
+    PostLlm,
+    /// Final text, right before it is saved and pasted
+    PrePaste,
+}
+
+impl HookPoint {
+    /// Name passed to the command in `WHISPERTRAY_HOOK`
+    pub fn name(&self) -> &'static str {
+        match self {
+            HookPoint::PostTranscription => "post_transcription",
+            HookPoint::PostLlm => "post_llm",
+            HookPoint::PrePaste => "pre_paste",
+        }
+    }
+}
+
+/// Hook commands; empty commands are skipped
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Hooks {
+    pub post_transcription: String,
+    pub post_llm: String,
+    pub pre_paste: String,
+    /// A hook running longer than this is killed
+    pub timeout_secs: u64,
+}
+
+impl Default for Hooks {
+    fn default() -> Self {
+        Self {
+            post_transcription: String::new(),
+            post_llm: String::new(),
+            pre_paste: String::new(),
+            timeout_secs: 10,
+        }
+    }
+}
+
+impl Hooks {
+    /// Command configured for a hook point, if any
+    pub fn command(&self, point: HookPoint) -> Option<&str> {
+        let command = match point {
+            HookPoint::PostTranscription => &self.post_transcription,
+            HookPoint::PostLlm => &self.post_llm,
+            HookPoint::PrePaste => &self.pre_paste,
+        };
+        Some(command.trim()).filter(|c| !c.is_empty())
+    }
+}
+
+/// Pipe `text` through `command` and return its stdout
+///
+/// The hook point and mode key are available to the command as
+/// `WHISPERTRAY_HOOK` and `WHISPERTRAY_MODE`. One trailing newline is removed.
+pub async fn run(
+    command: &str,
+    text: &str,
+    point: HookPoint,
+    mode_key: &str,
+    timeout: Duration,
+) -> Result<String> {
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .env("WHISPERTRAY_HOOK", point.name())
+        .env("WHISPERTRAY_MODE", mode_key)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()?;
+
+    // Write stdin concurrently so a hook that outputs before reading everything cannot deadlock
+    let mut stdin = child.stdin.take();
+    let input = text.to_string();
+    let writer = async move {
+        if let Some(stdin) = stdin.as_mut() {
+            let _ = stdin.write_all(input.as_bytes()).await;
+        }
+    };
+
+    let (output, _) = tokio::time::timeout(timeout, async {
+        tokio::join!(child.wait_with_output(), writer)
+    })
+    .await
+    .map_err(|_| AppError::Config("Hook timed out".to_string()))?;
+    let output = output?;
+
+    if !output.status.success() {
+        return Err(AppError::Config(format!(
+            "Hook failed ({}): {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stdout = stdout.strip_suffix('\n').unwrap_or(&stdout);
+    Ok(stdout.strip_suffix('\r').unwrap_or(stdout).to_string())
+}
+
+/// Run the hook for `point` if configured, keeping `text` if it fails
+pub async fn apply(hooks: &Hooks, point: HookPoint, text: String, mode_key: &str) -> String {
+    let command = match hooks.command(point) {
+        Some(command) => command,
+        None => return text,
+    };
+
+    let timeout = Duration::from_secs(hooks.timeout_secs.max(1));
+    match run(command, &text, point, mode_key, timeout).await {
+        Ok(output) => output,
+        Err(e) => {
+            log::warn!("{} hook failed, keeping text: {}", point.name(), e);
+            text
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    #[tokio::test]
+    async fn test_run_pipes_text() {
+        let output = run("tr a-z A-Z", "hello\n", HookPoint::PostLlm, "note", TIMEOUT)
+            .await
+            .unwrap();
+        assert_eq!(output, "HELLO");
+
+        let output = run(
+            "printf '%s:%s' \"$WHISPERTRAY_HOOK\" \"$WHISPERTRAY_MODE\"",
+            "",
+            HookPoint::PrePaste,
+            "email",
+            TIMEOUT,
+        )
+        .await
+        .unwrap();
+        assert_eq!(output, "pre_paste:email");
+    }
+
+    #[tokio::test]
+    async fn test_apply_keeps_text_on_failure() {
+        let hooks = Hooks {
+            post_llm: "echo oops >&2; exit 3".to_string(),
+            ..Hooks::default()
+        };
+        let text = apply(&hooks, HookPoint::PostLlm, "keep me".to_string(), "note").await;
+        assert_eq!(text, "keep me");
+
+        // Unconfigured hook points pass text through
+        let text = apply(&hooks, HookPoint::PrePaste, "as is".to_string(), "note").await;
+        assert_eq!(text, "as is");
+    }
+
+    #[tokio::test]
+    async fn test_run_times_out() {
+        let result = run(
+            "sleep 5",
+            "",
+            HookPoint::PostTranscription,
+            "note",
+            Duration::from_millis(100),
+        )
+        .await;
+        assert!(result.is_err());
+    }
+}
@@ -10,6 +10,15 @@ use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut};
 /// Default hotkey for toggling recording
 pub const DEFAULT_HOTKEY: &str = "Ctrl+Space";
 
+/// Hotkey for toggling incognito dictation
+pub const INCOGNITO_HOTKEY: &str = "Ctrl+Shift+Space";
+
+/// Hotkey for recording a follow-up instruction for the last output
+pub const REFINE_HOTKEY: &str = "Ctrl+Alt+Space";
+
+/// Hotkey for pasting a recent output again; repeated presses step back through the history
+pub const QUICK_PASTE_HOTKEY: &str = "Ctrl+Alt+V";
+
 /// Set up the global hotkey for recording toggle
 pub fn setup_hotkey(app: &tauri::App) -> Result<()> {
     let handle = app.handle().clone();
@@ -18,6 +27,18 @@ pub fn setup_hotkey(app: &tauri::App) -> Result<()> {
     let shortcut: Shortcut = DEFAULT_HOTKEY.parse()
         .map_err(|e| crate::error::AppError::Config(format!("Invalid hotkey: {}", e)))?;
 
+    let incognito_shortcut: Shortcut = INCOGNITO_HOTKEY
+        .parse()
+        .map_err(|e| AppError::Config(format!("Invalid hotkey: {}", e)))?;
+
+    let refine_shortcut: Shortcut = REFINE_HOTKEY
+        .parse()
+        .map_err(|e| AppError::Config(format!("Invalid hotkey: {}", e)))?;
+
+    let quick_paste_shortcut: Shortcut = QUICK_PASTE_HOTKEY
+        .parse()
+        .map_err(|e| AppError::Config(format!("Invalid hotkey: {}", e)))?;
+
     info!("Registering global hotkey: {}", DEFAULT_HOTKEY);
 
     // Register the shortcut
@@ -26,7 +47,18 @@ pub fn setup_hotkey(app: &tauri::App) -> Result<()> {
             .with_handler(move |_app, shortcut_ref, event| {
                 if event.state == tauri_plugin_global_shortcut::ShortcutState::Pressed {
                     info!("Hotkey pressed: {:?}", shortcut_ref);
-                    toggle_recording(&handle);
+                    if *shortcut_ref == incognito_shortcut {
+                        let handle = handle.clone();
+                        tauri::async_runtime::spawn(async move {
+                            let _ = crate::tray::toggle_incognito(&handle).await;
+                        });
+                    } else if *shortcut_ref == refine_shortcut {
+                        toggle_refinement(&handle);
+                    } else if *shortcut_ref == quick_paste_shortcut {
+                        crate::quick_paste::press(&handle);
+                    } else {
+                        toggle_recording(&handle);
+                    }
                 }
             })
             .build(),
@@ -37,12 +69,46 @@ pub fn setup_hotkey(app: &tauri::App) -> Result<()> {
         .register(shortcut)
         .map_err(|e| AppError::Config(format!("Failed to register hotkey: {}", e)))?;
 
+    // The incognito hotkey is optional, it may be taken by another application
+    if let Err(e) = app.global_shortcut().register(incognito_shortcut) {
+        log::warn!(
+            "Failed to register incognito hotkey {}: {}",
+            INCOGNITO_HOTKEY,
+            e
+        );
+    }
+
+    if let Err(e) = app.global_shortcut().register(refine_shortcut) {
+        log::warn!(
+            "Failed to register refinement hotkey {}: {}",
+            REFINE_HOTKEY,
+            e
+        );
+    }
+
+    if let Err(e) = app.global_shortcut().register(quick_paste_shortcut) {
+        log::warn!(
+            "Failed to register quick paste hotkey {}: {}",
+            QUICK_PASTE_HOTKEY,
+            e
+        );
+    }
+
     info!("Global hotkey registered successfully");
     Ok(())
 }
 
 /// Toggle recording state
-fn toggle_recording(handle: &AppHandle) {
+pub fn toggle_recording(handle: &AppHandle) {
+    toggle(handle, false);
+}
+
+/// Toggle a refinement recording: its transcript is applied as an instruction to the last output
+pub fn toggle_refinement(handle: &AppHandle) {
+    toggle(handle, true);
+}
+
+fn toggle(handle: &AppHandle, refine: bool) {
     let handle = handle.clone();
     tauri::async_runtime::spawn(async move {
         if let Some(state_arc) = handle.try_state::<SharedState>() {
@@ -81,9 +147,14 @@ fn toggle_recording(handle: &AppHandle) {
                     let _ = update_tray_icon_for_level(&handle_for_callback, level);
                 });
 
+                crate::state::prepare_bluetooth(&state_arc).await;
                 let start_result = {
                     let mut state = state_arc.lock().await;
-                    let result = state.start_recording_with_callback(Some(level_callback));
+                    let result = if refine {
+                        state.start_refinement_with_callback(Some(level_callback))
+                    } else {
+                        state.start_recording_with_callback(Some(level_callback))
+                    };
                     if result.is_ok() {
                         let _ = update_tray_icon(&handle, RecordingStatus::Recording);
                         let _ = update_tray_menu(&handle, &state).await;
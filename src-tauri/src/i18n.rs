@@ -0,0 +1,574 @@
+//! Localized user-visible strings
+//!
+//! Tray items, tooltips, notifications and error messages are looked
+//! up by key in per-language tables. The language follows `Settings.language`
+//! (the dictation language), or the system locale when that is "auto";
+//! missing translations fall back to English.
+
+use crate::error::AppError;
+use std::sync::RwLock;
+
+/// Languages with translated strings
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    English,
+    German,
+    French,
+    Spanish,
+}
+
+static LANGUAGE: RwLock<Language> = RwLock::new(Language::English);
+
+impl Language {
+    /// Match a language code or locale such as "de", "de-AT" or "de_DE.UTF-8"
+    pub fn from_code(code: &str) -> Option<Language> {
+        let code = code.trim().to_lowercase();
+        let primary = code.split(['_', '-', '.', '@']).next().unwrap_or_default();
+        match primary {
+            "en" => Some(Language::English),
+            "de" => Some(Language::German),
+            "fr" => Some(Language::French),
+            "es" => Some(Language::Spanish),
+            _ => None,
+        }
+    }
+
+    fn table(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Language::English => EN,
+            Language::German => DE,
+            Language::French => FR,
+            Language::Spanish => ES,
+        }
+    }
+}
+
+/// Language from the system locale environment variables
+fn system_language() -> Option<Language> {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|value| !value.is_empty())
+        .and_then(|value| Language::from_code(&value))
+}
+
+/// Resolve the UI language from the language setting
+pub fn resolve(setting: &str) -> Language {
+    Language::from_code(setting)
+        .or_else(system_language)
+        .unwrap_or(Language::English)
+}
+
+/// Switch the language used for backend strings
+pub fn set_language(setting: &str) {
+    let language = resolve(setting);
+    if let Ok(mut current) = LANGUAGE.write() {
+        *current = language;
+    }
+}
+
+fn current() -> Language {
+    LANGUAGE
+        .read()
+        .map(|language| *language)
+        .unwrap_or(Language::English)
+}
+
+fn lookup(language: Language, key: &'static str) -> &'static str {
+    let find = |table: &'static [(&'static str, &'static str)]| {
+        table.iter().find(|(k, _)| *k == key).map(|(_, text)| *text)
+    };
+    find(language.table()).or_else(|| find(EN)).unwrap_or(key)
+}
+
+/// Translate a string
+pub fn t(key: &'static str) -> &'static str {
+    lookup(current(), key)
+}
+
+/// Translate a string and fill in `{name}` arguments
+pub fn t_args(key: &'static str, args: &[(&str, &str)]) -> String {
+    args.iter().fold(t(key).to_string(), |text, (name, value)| {
+        text.replace(&format!("{{{}}}", name), value)
+    })
+}
+
+/// User-facing error message in the current language
+pub fn error_message(error: &AppError) -> String {
+    match error {
+        AppError::RecordingInProgress => t("error.recording_in_progress").to_string(),
+        AppError::NoRecordingInProgress => t("error.no_recording").to_string(),
+        AppError::ModelNotLoaded => t("error.model_not_loaded").to_string(),
+        AppError::Cancelled => t("error.cancelled").to_string(),
+        AppError::NothingToRefine => t("error.nothing_to_refine").to_string(),
+        AppError::ModeNotFound(key) => t_args("error.mode_not_found", &[("mode", key)]),
+        AppError::LocalOnly(reason) => t_args("error.local_only", &[("reason", reason)]),
+        AppError::Audio(detail) => t_args("error.audio", &[("detail", detail)]),
+        AppError::Transcription(detail) => t_args("error.transcription", &[("detail", detail)]),
+        AppError::Database(e) => t_args("error.database", &[("detail", &e.to_string())]),
+        AppError::Io(e) => t_args("error.io", &[("detail", &e.to_string())]),
+        AppError::Json(e) => t_args("error.json", &[("detail", &e.to_string())]),
+        AppError::Provider(detail) => t_args("error.provider", &[("detail", detail)]),
+        AppError::Config(detail) => t_args("error.config", &[("detail", detail)]),
+        AppError::Keyring(detail) => t_args("error.keyring", &[("detail", detail)]),
+        AppError::Clipboard(detail) => t_args("error.clipboard", &[("detail", detail)]),
+        AppError::Tauri(detail) => t_args("error.tauri", &[("detail", detail)]),
+        AppError::Http(e) => t_args("error.http", &[("detail", &e.to_string())]),
+    }
+}
+
+const EN: &[(&str, &str)] = &[
+    ("tray.start_recording", "Start Recording"),
+    ("tray.stop_recording", "Stop Recording"),
+    ("tray.pause_recording", "Pause Recording"),
+    ("tray.resume_recording", "Resume Recording"),
+    ("tray.cancel_processing", "Cancel Processing"),
+    ("tray.mode", "Mode"),
+    ("tray.input_device", "Input Device"),
+    ("tray.default_device", "Default"),
+    ("tray.language", "Language"),
+    ("tray.auto_language", "Auto-detect"),
+    ("tray.auto_paste", "Auto-paste"),
+    ("tray.context_awareness", "Context Awareness"),
+    ("tray.local_only", "Local-only Mode"),
+    ("tray.incognito", "Incognito"),
+    ("tray.transcribe_file", "Transcribe File..."),
+    ("tray.history", "History..."),
+    ("tray.settings", "Settings..."),
+    ("tray.update_available", "Update to {version} available..."),
+    ("tray.quit", "Quit"),
+    ("tray.audio_files", "Audio Files"),
+    ("tooltip.click_to_record", "Click to record"),
+    ("tooltip.loading", "Loading model..."),
+    ("tooltip.recording", "Recording..."),
+    ("tooltip.paused", "Paused"),
+    ("tooltip.processing", "Processing..."),
+    ("tooltip.ready", "Ready (click to record)"),
+    ("tooltip.error", "Error"),
+    ("tooltip.mode", "Mode: {mode}"),
+    ("tooltip.local_only", "[local-only]"),
+    ("tooltip.incognito", "[incognito]"),
+    (
+        "notify.low_confidence.title",
+        "Please double-check the dictation",
+    ),
+    (
+        "notify.low_confidence.body",
+        "Transcription confidence was low ({percent}%). The pasted text may contain mistakes.",
+    ),
+    (
+        "notify.focus_changed.title",
+        "Dictation copied to clipboard",
+    ),
+    (
+        "notify.focus_changed.body",
+        "The focused window changed while processing, so the text was not pasted.",
+    ),
+    ("notify.too_short.title", "Recording too short"),
+    (
+        "notify.too_short.body",
+        "Recordings shorter than {min} ms are discarded. Hold the hotkey a little longer, or lower the minimum in the settings.",
+    ),
+    ("notify.clipping.title", "Microphone input clipped"),
+    (
+        "notify.clipping.body",
+        "The recording was too loud and distorted, which often garbles the transcription. Lower the input volume or move away from the microphone.",
+    ),
+    ("notify.bluetooth.title", "Bluetooth headset switches profile"),
+    (
+        "notify.bluetooth.body",
+        "{device} changes to its headset profile when recording starts, so the first seconds may be garbled and playback drops to call quality. Enable early Bluetooth switching in the settings to switch before recording.",
+    ),
+    ("notify.complete.title", "Dictation complete"),
+    ("notify.final.title", "Final transcript ready"),
+    ("notify.mode_problem.title", "Mode \"{mode}\" cannot run as configured"),
+    ("notify.cannot_record.title", "Cannot start recording"),
+    ("notify.rerun.title", "{mode} version copied to clipboard"),
+    ("action.copy", "Copy"),
+    ("action.copy_again", "Copy again"),
+    ("action.open_history", "Open in history"),
+    ("action.open_settings", "Open Settings"),
+    ("action.rerun", "Re-run with {mode}"),
+    ("progress.download_model", "Downloading model {model}"),
+    ("progress.download_done", "Model {model} downloaded"),
+    ("progress.transcribe_file", "Transcribing {file}"),
+    ("progress.transcribe_done", "Transcription finished"),
+    ("progress.delete_history", "Deleting {count} history items"),
+    ("progress.delete_done", "Deleted {count} history items"),
+    ("progress.failed", "Failed: {error}"),
+    ("quick_paste.title", "Paste {position} of {count}"),
+    ("quick_paste.empty", "No dictations to paste yet"),
+    ("tasks.created", "Created {count} tasks in {service}"),
+    ("tasks.dry_run", "Dry run: {count} tasks for {service}"),
+    ("tasks.failed", "Could not create tasks in {service}"),
+    ("chat.failed", "Could not post to {service}"),
+    (
+        "error.recording_in_progress",
+        "Recording already in progress",
+    ),
+    ("error.no_recording", "No recording in progress"),
+    ("error.model_not_loaded", "Model not loaded"),
+    ("error.cancelled", "Operation cancelled"),
+    ("error.nothing_to_refine", "Nothing to refine yet"),
+    ("error.mode_not_found", "Mode not found: {mode}"),
+    ("error.local_only", "Blocked by local-only mode: {reason}"),    ("error.audio", "Audio error: {detail}"),
+    ("error.transcription", "Transcription error: {detail}"),
+    ("error.database", "Database error: {detail}"),
+    ("error.io", "IO error: {detail}"),
+    ("error.json", "JSON error: {detail}"),
+    ("error.provider", "Provider error: {detail}"),
+    ("error.config", "Configuration error: {detail}"),
+    ("error.keyring", "Keyring error: {detail}"),
+    ("error.clipboard", "Clipboard error: {detail}"),
+    ("error.tauri", "Application error: {detail}"),
+    ("error.http", "Network error: {detail}"),
+];
+
+const DE: &[(&str, &str)] = &[
+    ("tray.start_recording", "Aufnahme starten"),
+    ("tray.stop_recording", "Aufnahme beenden"),
+    ("tray.pause_recording", "Aufnahme pausieren"),
+    ("tray.resume_recording", "Aufnahme fortsetzen"),
+    ("tray.cancel_processing", "Verarbeitung abbrechen"),
+    ("tray.mode", "Modus"),
+    ("tray.input_device", "Eingabegerät"),
+    ("tray.default_device", "Standard"),
+    ("tray.language", "Sprache"),
+    ("tray.auto_language", "Automatisch erkennen"),
+    ("tray.auto_paste", "Automatisch einfügen"),
+    ("tray.context_awareness", "Kontext einbeziehen"),
+    ("tray.local_only", "Nur lokal"),
+    ("tray.incognito", "Inkognito"),
+    ("tray.transcribe_file", "Datei transkribieren..."),
+    ("tray.history", "Verlauf..."),
+    ("tray.settings", "Einstellungen..."),
+    ("tray.update_available", "Update auf {version} verfügbar..."),
+    ("tray.quit", "Beenden"),
+    ("tray.audio_files", "Audiodateien"),
+    ("tooltip.click_to_record", "Zum Aufnehmen klicken"),
+    ("tooltip.loading", "Modell wird geladen..."),
+    ("tooltip.recording", "Aufnahme läuft..."),
+    ("tooltip.paused", "Pausiert"),
+    ("tooltip.processing", "Wird verarbeitet..."),
+    ("tooltip.ready", "Bereit (zum Aufnehmen klicken)"),
+    ("tooltip.error", "Fehler"),
+    ("tooltip.mode", "Modus: {mode}"),
+    ("tooltip.local_only", "[nur lokal]"),
+    ("tooltip.incognito", "[inkognito]"),
+    ("notify.low_confidence.title", "Bitte das Diktat prüfen"),
+    (
+        "notify.low_confidence.body",
+        "Die Erkennung war unsicher ({percent} %). Der eingefügte Text kann Fehler enthalten.",
+    ),
+    (
+        "notify.focus_changed.title",
+        "Diktat in die Zwischenablage kopiert",
+    ),
+    (
+        "notify.focus_changed.body",
+        "Das aktive Fenster hat sich während der Verarbeitung geändert, daher wurde der Text nicht eingefügt.",
+    ),
+    ("notify.too_short.title", "Aufnahme zu kurz"),
+    (
+        "notify.too_short.body",
+        "Aufnahmen unter {min} ms werden verworfen. Halte die Tastenkombination etwas länger oder verringere das Minimum in den Einstellungen.",
+    ),
+    ("notify.clipping.title", "Mikrofoneingang übersteuert"),
+    (
+        "notify.clipping.body",
+        "Die Aufnahme war zu laut und verzerrt, was die Transkription oft unbrauchbar macht. Verringere die Eingangslautstärke oder gehe etwas weiter weg vom Mikrofon.",
+    ),
+    ("notify.bluetooth.title", "Bluetooth-Headset wechselt das Profil"),
+    (
+        "notify.bluetooth.body",
+        "{device} wechselt beim Aufnahmestart in das Headset-Profil, daher können die ersten Sekunden verzerrt sein und die Wiedergabe fällt auf Telefonqualität. Aktiviere in den Einstellungen den frühen Bluetooth-Wechsel, um vor der Aufnahme umzuschalten.",
+    ),
+    ("notify.complete.title", "Diktat fertig"),
+    ("notify.final.title", "Endgültige Transkription fertig"),
+    ("notify.mode_problem.title", "Modus \"{mode}\" ist so nicht nutzbar"),
+    ("notify.cannot_record.title", "Aufnahme kann nicht starten"),
+    ("notify.rerun.title", "{mode}-Fassung in die Zwischenablage kopiert"),
+    ("action.copy", "Kopieren"),
+    ("action.copy_again", "Erneut kopieren"),
+    ("action.open_history", "Im Verlauf öffnen"),
+    ("action.open_settings", "Einstellungen öffnen"),
+    ("action.rerun", "Mit {mode} neu erstellen"),
+    ("progress.download_model", "Modell {model} wird heruntergeladen"),
+    ("progress.download_done", "Modell {model} heruntergeladen"),
+    ("progress.transcribe_file", "{file} wird transkribiert"),
+    ("progress.transcribe_done", "Transkription abgeschlossen"),
+    ("progress.delete_history", "{count} Verlaufseinträge werden gelöscht"),
+    ("progress.delete_done", "{count} Verlaufseinträge gelöscht"),
+    ("progress.failed", "Fehlgeschlagen: {error}"),
+    ("quick_paste.title", "Einfügen {position} von {count}"),
+    ("quick_paste.empty", "Noch keine Diktate zum Einfügen"),
+    ("tasks.created", "{count} Aufgaben in {service} erstellt"),
+    ("tasks.dry_run", "Probelauf: {count} Aufgaben für {service}"),
+    ("tasks.failed", "Aufgaben in {service} konnten nicht erstellt werden"),
+    ("chat.failed", "Beitrag in {service} fehlgeschlagen"),
+    ("error.recording_in_progress", "Es läuft bereits eine Aufnahme"),
+    ("error.no_recording", "Keine laufende Aufnahme"),
+    ("error.model_not_loaded", "Modell nicht geladen"),
+    ("error.cancelled", "Vorgang abgebrochen"),
+    ("error.nothing_to_refine", "Noch nichts zum Überarbeiten"),
+    ("error.mode_not_found", "Modus nicht gefunden: {mode}"),
+    ("error.local_only", "Im Nur-lokal-Modus gesperrt: {reason}"),    ("error.audio", "Audiofehler: {detail}"),
+    ("error.transcription", "Transkriptionsfehler: {detail}"),
+    ("error.database", "Datenbankfehler: {detail}"),
+    ("error.io", "Dateifehler: {detail}"),
+    ("error.json", "JSON-Fehler: {detail}"),
+    ("error.provider", "Anbieterfehler: {detail}"),
+    ("error.config", "Konfigurationsfehler: {detail}"),
+    ("error.keyring", "Schlüsselbundfehler: {detail}"),
+    ("error.clipboard", "Zwischenablagefehler: {detail}"),
+    ("error.tauri", "Anwendungsfehler: {detail}"),
+    ("error.http", "Netzwerkfehler: {detail}"),
+];
+
+const FR: &[(&str, &str)] = &[
+    ("tray.start_recording", "Démarrer l'enregistrement"),
+    ("tray.stop_recording", "Arrêter l'enregistrement"),
+    ("tray.pause_recording", "Mettre l'enregistrement en pause"),
+    ("tray.resume_recording", "Reprendre l'enregistrement"),
+    ("tray.cancel_processing", "Annuler le traitement"),
+    ("tray.mode", "Mode"),
+    ("tray.input_device", "Périphérique d'entrée"),
+    ("tray.default_device", "Par défaut"),
+    ("tray.language", "Langue"),
+    ("tray.auto_language", "Détection automatique"),
+    ("tray.auto_paste", "Collage automatique"),
+    ("tray.context_awareness", "Prise en compte du contexte"),
+    ("tray.local_only", "Mode local uniquement"),
+    ("tray.incognito", "Navigation privée"),
+    ("tray.transcribe_file", "Transcrire un fichier..."),
+    ("tray.history", "Historique..."),
+    ("tray.settings", "Paramètres..."),
+    ("tray.update_available", "Mise à jour {version} disponible..."),
+    ("tray.quit", "Quitter"),
+    ("tray.audio_files", "Fichiers audio"),
+    ("tooltip.click_to_record", "Cliquer pour enregistrer"),
+    ("tooltip.loading", "Chargement du modèle..."),
+    ("tooltip.recording", "Enregistrement..."),
+    ("tooltip.paused", "En pause"),
+    ("tooltip.processing", "Traitement..."),
+    ("tooltip.ready", "Prêt (cliquer pour enregistrer)"),
+    ("tooltip.error", "Erreur"),
+    ("tooltip.mode", "Mode : {mode}"),
+    ("tooltip.local_only", "[local uniquement]"),
+    ("tooltip.incognito", "[privé]"),
+    ("notify.low_confidence.title", "Vérifiez la dictée"),
+    (
+        "notify.low_confidence.body",
+        "La reconnaissance était incertaine ({percent} %). Le texte collé peut contenir des erreurs.",
+    ),
+    (
+        "notify.focus_changed.title",
+        "Dictée copiée dans le presse-papiers",
+    ),
+    (
+        "notify.focus_changed.body",
+        "La fenêtre active a changé pendant le traitement, le texte n'a donc pas été collé.",
+    ),
+    ("notify.too_short.title", "Enregistrement trop court"),
+    (
+        "notify.too_short.body",
+        "Les enregistrements de moins de {min} ms sont ignorés. Maintenez le raccourci un peu plus longtemps ou réduisez le minimum dans les paramètres.",
+    ),
+    ("notify.clipping.title", "Entrée du microphone saturée"),
+    (
+        "notify.clipping.body",
+        "L'enregistrement était trop fort et distordu, ce qui brouille souvent la transcription. Baissez le volume d'entrée ou éloignez-vous du microphone.",
+    ),
+    ("notify.bluetooth.title", "Le casque Bluetooth change de profil"),
+    (
+        "notify.bluetooth.body",
+        "{device} passe en profil casque au début de l'enregistrement : les premières secondes peuvent être déformées et la lecture passe en qualité téléphone. Activez le basculement Bluetooth anticipé dans les paramètres pour changer avant l'enregistrement.",
+    ),
+    ("notify.complete.title", "Dictée terminée"),
+    ("notify.final.title", "Transcription finale prête"),
+    ("notify.mode_problem.title", "Le mode « {mode} » ne peut pas fonctionner ainsi"),
+    ("notify.cannot_record.title", "Impossible de démarrer l'enregistrement"),
+    ("notify.rerun.title", "Version {mode} copiée dans le presse-papiers"),
+    ("action.copy", "Copier"),
+    ("action.copy_again", "Copier à nouveau"),
+    ("action.open_history", "Ouvrir dans l'historique"),
+    ("action.open_settings", "Ouvrir les paramètres"),
+    ("action.rerun", "Refaire avec {mode}"),
+    ("progress.download_model", "Téléchargement du modèle {model}"),
+    ("progress.download_done", "Modèle {model} téléchargé"),
+    ("progress.transcribe_file", "Transcription de {file}"),
+    ("progress.transcribe_done", "Transcription terminée"),
+    ("progress.delete_history", "Suppression de {count} éléments de l'historique"),
+    ("progress.delete_done", "{count} éléments de l'historique supprimés"),
+    ("progress.failed", "Échec : {error}"),
+    ("quick_paste.title", "Coller {position} sur {count}"),
+    ("quick_paste.empty", "Aucune dictée à coller pour l'instant"),
+    ("tasks.created", "{count} tâches créées dans {service}"),
+    ("tasks.dry_run", "Essai : {count} tâches pour {service}"),
+    ("tasks.failed", "Impossible de créer les tâches dans {service}"),
+    ("chat.failed", "Impossible de publier sur {service}"),
+    ("error.recording_in_progress", "Un enregistrement est déjà en cours"),
+    ("error.no_recording", "Aucun enregistrement en cours"),
+    ("error.model_not_loaded", "Modèle non chargé"),
+    ("error.cancelled", "Opération annulée"),
+    ("error.nothing_to_refine", "Rien à retravailler pour l'instant"),
+    ("error.mode_not_found", "Mode introuvable : {mode}"),
+    ("error.local_only", "Bloqué par le mode local : {reason}"),    ("error.audio", "Erreur audio : {detail}"),
+    ("error.transcription", "Erreur de transcription : {detail}"),
+    ("error.database", "Erreur de base de données : {detail}"),
+    ("error.io", "Erreur de fichier : {detail}"),
+    ("error.json", "Erreur JSON : {detail}"),
+    ("error.provider", "Erreur du fournisseur : {detail}"),
+    ("error.config", "Erreur de configuration : {detail}"),
+    ("error.keyring", "Erreur du trousseau : {detail}"),
+    ("error.clipboard", "Erreur du presse-papiers : {detail}"),
+    ("error.tauri", "Erreur de l'application : {detail}"),
+    ("error.http", "Erreur réseau : {detail}"),
+];
+
+const ES: &[(&str, &str)] = &[
+    ("tray.start_recording", "Iniciar grabación"),
+    ("tray.stop_recording", "Detener grabación"),
+    ("tray.pause_recording", "Pausar grabación"),
+    ("tray.resume_recording", "Reanudar grabación"),
+    ("tray.cancel_processing", "Cancelar procesamiento"),
+    ("tray.mode", "Modo"),
+    ("tray.input_device", "Dispositivo de entrada"),
+    ("tray.default_device", "Predeterminado"),
+    ("tray.language", "Idioma"),
+    ("tray.auto_language", "Detección automática"),
+    ("tray.auto_paste", "Pegado automático"),
+    ("tray.context_awareness", "Reconocimiento de contexto"),
+    ("tray.local_only", "Modo solo local"),
+    ("tray.incognito", "Incógnito"),
+    ("tray.transcribe_file", "Transcribir archivo..."),
+    ("tray.history", "Historial..."),
+    ("tray.settings", "Ajustes..."),
+    ("tray.update_available", "Versión {version} disponible..."),
+    ("tray.quit", "Salir"),
+    ("tray.audio_files", "Archivos de audio"),
+    ("tooltip.click_to_record", "Haz clic para grabar"),
+    ("tooltip.loading", "Cargando modelo..."),
+    ("tooltip.recording", "Grabando..."),
+    ("tooltip.paused", "En pausa"),
+    ("tooltip.processing", "Procesando..."),
+    ("tooltip.ready", "Listo (haz clic para grabar)"),
+    ("tooltip.error", "Error"),
+    ("tooltip.mode", "Modo: {mode}"),
+    ("tooltip.local_only", "[solo local]"),
+    ("tooltip.incognito", "[incógnito]"),
+    ("notify.low_confidence.title", "Revisa el dictado"),
+    (
+        "notify.low_confidence.body",
+        "El reconocimiento fue poco fiable ({percent} %). El texto pegado puede contener errores.",
+    ),
+    (
+        "notify.focus_changed.title",
+        "Dictado copiado al portapapeles",
+    ),
+    (
+        "notify.focus_changed.body",
+        "La ventana activa cambió durante el procesamiento, así que el texto no se pegó.",
+    ),
+    ("notify.too_short.title", "Grabación demasiado corta"),
+    (
+        "notify.too_short.body",
+        "Las grabaciones de menos de {min} ms se descartan. Mantén pulsado el atajo un poco más o reduce el mínimo en los ajustes.",
+    ),
+    ("notify.clipping.title", "Entrada del micrófono saturada"),
+    (
+        "notify.clipping.body",
+        "La grabación estaba demasiado alta y distorsionada, lo que suele estropear la transcripción. Baja el volumen de entrada o aléjate del micrófono.",
+    ),
+    ("notify.bluetooth.title", "El auricular Bluetooth cambia de perfil"),
+    (
+        "notify.bluetooth.body",
+        "{device} cambia al perfil de auricular al empezar a grabar, así que los primeros segundos pueden sonar distorsionados y la reproducción baja a calidad de llamada. Activa el cambio anticipado de Bluetooth en los ajustes para cambiar antes de grabar.",
+    ),
+    ("notify.complete.title", "Dictado terminado"),
+    ("notify.final.title", "Transcripción final lista"),
+    ("notify.mode_problem.title", "El modo \"{mode}\" no puede funcionar así"),
+    ("notify.cannot_record.title", "No se puede iniciar la grabación"),
+    (
+        "notify.rerun.title",
+        "Versión {mode} copiada al portapapeles",
+    ),
+    ("action.copy", "Copiar"),
+    ("action.copy_again", "Copiar de nuevo"),
+    ("action.open_history", "Abrir en el historial"),
+    ("action.open_settings", "Abrir la configuración"),
+    ("action.rerun", "Repetir con {mode}"),
+    ("progress.download_model", "Descargando el modelo {model}"),
+    ("progress.download_done", "Modelo {model} descargado"),
+    ("progress.transcribe_file", "Transcribiendo {file}"),
+    ("progress.transcribe_done", "Transcripción terminada"),
+    ("progress.delete_history", "Eliminando {count} elementos del historial"),
+    ("progress.delete_done", "{count} elementos del historial eliminados"),
+    ("progress.failed", "Error: {error}"),
+    ("quick_paste.title", "Pegar {position} de {count}"),
+    ("quick_paste.empty", "Todavía no hay dictados para pegar"),
+    ("tasks.created", "{count} tareas creadas en {service}"),
+    ("tasks.dry_run", "Prueba: {count} tareas para {service}"),
+    ("tasks.failed", "No se pudieron crear las tareas en {service}"),
+    ("chat.failed", "No se pudo publicar en {service}"),
+    (
+        "error.recording_in_progress",
+        "Ya hay una grabación en curso",
+    ),
+    ("error.no_recording", "No hay ninguna grabación en curso"),
+    ("error.model_not_loaded", "Modelo no cargado"),
+    ("error.cancelled", "Operación cancelada"),
+    ("error.nothing_to_refine", "Todavía no hay nada que refinar"),
+    ("error.mode_not_found", "Modo no encontrado: {mode}"),
+    (
+        "error.local_only",
+        "Bloqueado por el modo solo local: {reason}",
+    ),    ("error.audio", "Error de audio: {detail}"),
+    ("error.transcription", "Error de transcripción: {detail}"),
+    ("error.database", "Error de base de datos: {detail}"),
+    ("error.io", "Error de archivo: {detail}"),
+    ("error.json", "Error de JSON: {detail}"),
+    ("error.provider", "Error del proveedor: {detail}"),
+    ("error.config", "Error de configuración: {detail}"),
+    ("error.keyring", "Error del llavero: {detail}"),
+    ("error.clipboard", "Error del portapapeles: {detail}"),
+    ("error.tauri", "Error de la aplicación: {detail}"),
+    ("error.http", "Error de red: {detail}"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_code() {
+        assert_eq!(Language::from_code("de"), Some(Language::German));
+        assert_eq!(Language::from_code("de_DE.UTF-8"), Some(Language::German));
+        assert_eq!(Language::from_code("fr-CA"), Some(Language::French));
+        assert_eq!(Language::from_code("auto"), None);
+        assert_eq!(Language::from_code(""), None);
+    }
+
+    #[test]
+    fn test_tables_are_complete() {
+        for language in [Language::German, Language::French, Language::Spanish] {
+            for (key, _) in EN {
+                assert!(
+                    language.table().iter().any(|(k, _)| k == key),
+                    "{:?} is missing {}",
+                    language,
+                    key
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_lookup() {
+        assert_eq!(
+            lookup(Language::German, "tray.start_recording"),
+            "Aufnahme starten"
+        );
+        assert_eq!(lookup(Language::German, "unknown.key"), "unknown.key");
+    }
+}
@@ -0,0 +1,407 @@
+//! Runtime rendering of tray icons
+//!
+//! Icons are drawn as RGBA buffers (microphone glyph, state color and an
+//! optional input level meter) so they can be sized for the panel and adapt
+//! to dark/light themes instead of shipping a fixed set of PNGs.
+
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+
+/// Default tray icon edge length in pixels
+pub const DEFAULT_ICON_SIZE: u32 = 32;
+
+/// Tray icon drawing style
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TrayIconStyle {
+    /// Colored glyph reflecting the recording state
+    #[default]
+    Color,
+    /// Monochrome glyph matching GNOME/KDE symbolic panel icons
+    Symbolic,
+}
+
+/// Panel theme used to pick icon contrast
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TrayIconTheme {
+    /// Detect from the desktop settings
+    #[default]
+    Auto,
+    Dark,
+    Light,
+}
+
+impl TrayIconTheme {
+    /// Whether the panel is dark; `Auto` asks the desktop settings
+    pub fn is_dark(&self) -> bool {
+        match self {
+            TrayIconTheme::Auto => detect_dark_theme(),
+            TrayIconTheme::Dark => true,
+            TrayIconTheme::Light => false,
+        }
+    }
+}
+
+/// Privacy marker drawn in the corner of every icon
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PrivacyMark {
+    #[default]
+    None,
+    /// Padlock (local-only mode)
+    Lock,
+    /// Hat brim and glasses (incognito dictation)
+    Incognito,
+}
+
+/// Options controlling how tray icons are rendered
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IconOptions {
+    pub style: TrayIconStyle,
+    /// Light glyphs for a dark panel, resolved once from `TrayIconTheme`
+    pub dark: bool,
+    pub size: u32,
+    pub privacy: PrivacyMark,
+}
+
+impl IconOptions {
+    /// Icon edge length, clamped to a sane range
+    pub fn pixel_size(&self) -> u32 {
+        self.size.clamp(16, 256)
+    }
+}
+
+impl Default for IconOptions {
+    fn default() -> Self {
+        Self {
+            style: TrayIconStyle::Color,
+            dark: true,
+            size: DEFAULT_ICON_SIZE,
+            privacy: PrivacyMark::None,
+        }
+    }
+}
+
+/// What the icon should show
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IconState {
+    /// State color (RGB) for colored icons
+    pub color: [u8; 3],
+    /// Whether a state badge is drawn in symbolic mode
+    pub badge: Badge,
+    /// Input level (0.0-1.0) shown as meter bars while recording
+    pub level: Option<f32>,
+}
+
+/// State badge for symbolic icons, which cannot rely on color
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Badge {
+    None,
+    /// Filled dot (recording)
+    Filled,
+    /// Hollow ring (busy)
+    Ring,
+}
+
+/// Detect whether the desktop uses a dark panel theme
+pub fn detect_dark_theme() -> bool {
+    if let Ok(theme) = std::env::var("GTK_THEME") {
+        return theme.to_lowercase().contains("dark");
+    }
+
+    // GNOME 42+ and most GTK desktops
+    if let Some(scheme) = gsettings_get("org.gnome.desktop.interface", "color-scheme") {
+        if scheme.contains("dark") {
+            return true;
+        }
+        if scheme.contains("light") {
+            return false;
+        }
+    }
+
+    if let Some(theme) = gsettings_get("org.gnome.desktop.interface", "gtk-theme") {
+        return theme.to_lowercase().contains("dark");
+    }
+
+    // Most panels are dark; white glyphs are the safer default
+    true
+}
+
+fn gsettings_get(schema: &str, key: &str) -> Option<String> {
+    let output = Command::new("gsettings")
+        .args(["get", schema, key])
+        .output()
+        .ok()?;
+
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Render a tray icon as an RGBA buffer of `size * size` pixels
+pub fn render_icon(state: &IconState, options: &IconOptions) -> Vec<u8> {
+    let size = options.pixel_size();
+    let foreground = if options.dark {
+        [0xF2, 0xF2, 0xF2]
+    } else {
+        [0x2E, 0x34, 0x36]
+    };
+    let glyph_color = match options.style {
+        TrayIconStyle::Color if options.dark => state.color,
+        // Darken state colors so they stay readable on light panels
+        TrayIconStyle::Color => state.color.map(|c| (c as f32 * 0.75) as u8),
+        TrayIconStyle::Symbolic => foreground,
+    };
+
+    let mut buffer = vec![0u8; (size * size * 4) as usize];
+    let scale = size as f32;
+
+    for py in 0..size {
+        for px in 0..size {
+            // Sample at pixel centers in normalized [0, 1] coordinates
+            let x = (px as f32 + 0.5) / scale;
+            let y = (py as f32 + 0.5) / scale;
+            let aa = 1.0 / scale;
+
+            let mut color = [0u8; 3];
+            let mut alpha = 0.0f32;
+
+            let glyph = coverage(mic_distance(x, y), aa);
+            if glyph > 0.0 {
+                color = glyph_color;
+                alpha = glyph;
+            }
+
+            if let Some(level) = state.level {
+                let bars = coverage(level_bars_distance(x, y, level), aa);
+                if bars > alpha {
+                    color = foreground;
+                    alpha = bars;
+                }
+            }
+
+            let privacy = coverage(privacy_distance(x, y, options.privacy), aa);
+            if privacy > alpha {
+                color = foreground;
+                alpha = privacy;
+            }
+
+            if options.style == TrayIconStyle::Symbolic {
+                let badge = coverage(badge_distance(x, y, state.badge), aa);
+                if badge > alpha {
+                    color = foreground;
+                    alpha = badge;
+                }
+            }
+
+            let i = ((py * size + px) * 4) as usize;
+            buffer[i..i + 3].copy_from_slice(&color);
+            buffer[i + 3] = (alpha.clamp(0.0, 1.0) * 255.0) as u8;
+        }
+    }
+
+    buffer
+}
+
+/// Anti-aliased coverage from a signed distance (negative = inside)
+fn coverage(distance: f32, aa: f32) -> f32 {
+    (0.5 - distance / aa).clamp(0.0, 1.0)
+}
+
+/// Distance from point to a line segment
+fn segment_distance(x: f32, y: f32, (ax, ay): (f32, f32), (bx, by): (f32, f32)) -> f32 {
+    let (dx, dy) = (bx - ax, by - ay);
+    let len_sq = dx * dx + dy * dy;
+    let t = if len_sq == 0.0 {
+        0.0
+    } else {
+        (((x - ax) * dx + (y - ay) * dy) / len_sq).clamp(0.0, 1.0)
+    };
+    let (cx, cy) = (ax + t * dx, ay + t * dy);
+    ((x - cx).powi(2) + (y - cy).powi(2)).sqrt()
+}
+
+/// Signed distance to the microphone glyph
+fn mic_distance(x: f32, y: f32) -> f32 {
+    let cx = 0.42;
+    let stroke = 0.035;
+
+    // Capsule body
+    let body = segment_distance(x, y, (cx, 0.24), (cx, 0.44)) - 0.13;
+
+    // U-shaped holder around the lower half of the body
+    let holder = if y >= 0.44 {
+        (((x - cx).powi(2) + (y - 0.44).powi(2)).sqrt() - 0.22).abs() - stroke
+    } else {
+        segment_distance(x, y, (cx - 0.22, 0.38), (cx - 0.22, 0.44)).min(segment_distance(
+            x,
+            y,
+            (cx + 0.22, 0.38),
+            (cx + 0.22, 0.44),
+        )) - stroke
+    };
+
+    // Stem and base
+    let stem = segment_distance(x, y, (cx, 0.66), (cx, 0.84)) - stroke;
+    let base = segment_distance(x, y, (cx - 0.14, 0.86), (cx + 0.14, 0.86)) - stroke;
+
+    body.min(holder).min(stem).min(base)
+}
+
+/// Signed distance to the level meter: three bars lit by input level
+fn level_bars_distance(x: f32, y: f32, level: f32) -> f32 {
+    let thresholds = [0.05, 0.3, 0.6];
+    let mut distance = f32::MAX;
+
+    for (i, threshold) in thresholds.iter().enumerate() {
+        if level < *threshold {
+            break;
+        }
+        let bx = 0.76 + i as f32 * 0.09;
+        let top = 0.86 - 0.18 * (i + 1) as f32;
+        distance = distance.min(segment_distance(x, y, (bx, top), (bx, 0.86)) - 0.03);
+    }
+
+    distance
+}
+
+/// Signed distance to the symbolic state badge
+fn badge_distance(x: f32, y: f32, badge: Badge) -> f32 {
+    let d = ((x - 0.84).powi(2) + (y - 0.16).powi(2)).sqrt();
+    match badge {
+        Badge::None => f32::MAX,
+        Badge::Filled => d - 0.12,
+        Badge::Ring => (d - 0.1).abs() - 0.03,
+    }
+}
+
+/// Signed distance to the privacy marker in the bottom-left corner
+fn privacy_distance(x: f32, y: f32, mark: PrivacyMark) -> f32 {
+    match mark {
+        PrivacyMark::None => f32::MAX,
+        PrivacyMark::Lock => {
+            // Rectangular body with a half-ring shackle on top
+            let (dx, dy) = ((x - 0.14).abs() - 0.1, (y - 0.87).abs() - 0.07);
+            let body = (dx.max(0.0).powi(2) + dy.max(0.0).powi(2)).sqrt() + dx.max(dy).min(0.0);
+            let shackle = if y <= 0.8 {
+                (((x - 0.14).powi(2) + (y - 0.8).powi(2)).sqrt() - 0.06).abs() - 0.02
+            } else {
+                f32::MAX
+            };
+            body.min(shackle)
+        }
+        PrivacyMark::Incognito => {
+            let brim = segment_distance(x, y, (0.03, 0.77), (0.24, 0.77)) - 0.02;
+            let left = ((x - 0.075).powi(2) + (y - 0.87).powi(2)).sqrt() - 0.045;
+            let right = ((x - 0.19).powi(2) + (y - 0.87).powi(2)).sqrt() - 0.045;
+            brim.min(left).min(right)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(style: TrayIconStyle) -> IconOptions {
+        IconOptions {
+            style,
+            dark: true,
+            size: 32,
+            privacy: PrivacyMark::None,
+        }
+    }
+
+    fn pixel(buffer: &[u8], size: u32, x: u32, y: u32) -> &[u8] {
+        let i = ((y * size + x) * 4) as usize;
+        &buffer[i..i + 4]
+    }
+
+    #[test]
+    fn test_render_icon_size() {
+        let state = IconState {
+            color: [0x2E, 0xCC, 0x40],
+            badge: Badge::None,
+            level: None,
+        };
+        let buffer = render_icon(&state, &options(TrayIconStyle::Color));
+        assert_eq!(buffer.len(), 32 * 32 * 4);
+
+        let large = IconOptions {
+            size: 64,
+            ..options(TrayIconStyle::Color)
+        };
+        assert_eq!(render_icon(&state, &large).len(), 64 * 64 * 4);
+    }
+
+    #[test]
+    fn test_glyph_uses_state_color() {
+        let state = IconState {
+            color: [0xFF, 0x41, 0x36],
+            badge: Badge::None,
+            level: None,
+        };
+        let buffer = render_icon(&state, &options(TrayIconStyle::Color));
+        // Center of the capsule body
+        let center = pixel(&buffer, 32, 13, 11);
+        assert_eq!(&center[..3], &[0xFF, 0x41, 0x36]);
+        assert_eq!(center[3], 255);
+        // Corner stays transparent
+        assert_eq!(pixel(&buffer, 32, 0, 0)[3], 0);
+    }
+
+    #[test]
+    fn test_symbolic_icon_is_monochrome() {
+        let state = IconState {
+            color: [0xFF, 0x41, 0x36],
+            badge: Badge::Filled,
+            level: None,
+        };
+        let buffer = render_icon(&state, &options(TrayIconStyle::Symbolic));
+        for px in buffer.chunks(4).filter(|p| p[3] > 0) {
+            assert_eq!(&px[..3], &[0xF2, 0xF2, 0xF2]);
+        }
+    }
+
+    #[test]
+    fn test_level_bars_drawn_only_with_level() {
+        let quiet = IconState {
+            color: [0xFF, 0x41, 0x36],
+            badge: Badge::None,
+            level: Some(0.0),
+        };
+        let loud = IconState {
+            level: Some(1.0),
+            ..quiet
+        };
+        let opts = options(TrayIconStyle::Color);
+        // Bottom of the rightmost bar
+        assert_eq!(pixel(&render_icon(&quiet, &opts), 32, 30, 26)[3], 0);
+        assert!(pixel(&render_icon(&loud, &opts), 32, 30, 26)[3] > 0);
+    }
+
+    #[test]
+    fn test_privacy_mark() {
+        let state = IconState {
+            color: [0x2E, 0xCC, 0x40],
+            badge: Badge::None,
+            level: None,
+        };
+        let plain = options(TrayIconStyle::Color);
+        let locked = IconOptions {
+            privacy: PrivacyMark::Lock,
+            ..plain
+        };
+        // Center of the padlock body
+        assert_eq!(pixel(&render_icon(&state, &plain), 32, 4, 28)[3], 0);
+        assert_eq!(pixel(&render_icon(&state, &locked), 32, 4, 28)[3], 255);
+
+        let incognito = IconOptions {
+            privacy: PrivacyMark::Incognito,
+            ..plain
+        };
+        // Center of the left lens
+        assert_eq!(pixel(&render_icon(&state, &incognito), 32, 2, 27)[3], 255);
+    }
+}
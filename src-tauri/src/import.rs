@@ -0,0 +1,283 @@
+//! Import of transcripts made elsewhere
+//!
+//! Plain text, SRT and WebVTT files become history items so they can be
+//! searched and reprocessed with any mode. Subtitle cues are kept as segments;
+//! an accompanying audio file is copied next to the app's own recordings.
+
+use crate::database::{get_audio_dir, Database, HistoryItem, Segment};
+use crate::error::{AppError, Result};
+use chrono::Utc;
+use std::path::Path;
+use uuid::Uuid;
+
+/// Mode imported transcripts are filed under until they are reprocessed
+const IMPORT_MODE: &str = "voice_to_text";
+
+/// Format of an imported transcript file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TranscriptFormat {
+    Text,
+    Srt,
+    Vtt,
+}
+
+impl TranscriptFormat {
+    /// Guess the format from the file extension
+    pub fn from_path(path: &Path) -> Self {
+        match path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_lowercase())
+            .as_deref()
+        {
+            Some("srt") => TranscriptFormat::Srt,
+            Some("vtt") => TranscriptFormat::Vtt,
+            _ => TranscriptFormat::Text,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            TranscriptFormat::Text => "text",
+            TranscriptFormat::Srt => "srt",
+            TranscriptFormat::Vtt => "vtt",
+        }
+    }
+}
+
+/// Transcript text with its timed segments, if the format has any
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Transcript {
+    pub text: String,
+    pub segments: Vec<Segment>,
+}
+
+/// Parse an `HH:MM:SS,mmm`, `HH:MM:SS.mmm` or `MM:SS.mmm` timestamp
+fn parse_timestamp(value: &str) -> Option<u64> {
+    let (clock, millis) = value.trim().split_once([',', '.'])?;
+    let millis: u64 = millis.parse().ok()?;
+    let parts: Vec<u64> = clock
+        .split(':')
+        .map(|p| p.parse().ok())
+        .collect::<Option<_>>()?;
+    let seconds = match parts.as_slice() {
+        [h, m, s] => h * 3600 + m * 60 + s,
+        [m, s] => m * 60 + s,
+        _ => return None,
+    };
+    Some(seconds * 1000 + millis)
+}
+
+/// Split a WebVTT `<v Speaker>` voice tag off a cue and drop other markup
+fn cue_text(text: &str) -> (String, Option<String>) {
+    let speaker = text
+        .strip_prefix("<v ")
+        .or_else(|| text.strip_prefix("<v."))
+        .and_then(|rest| rest.split_once('>'))
+        .map(|(name, _)| name.trim().to_string())
+        .filter(|name| !name.is_empty());
+
+    let mut plain = String::with_capacity(text.len());
+    let mut in_tag = false;
+    for c in text.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => plain.push(c),
+            _ => {}
+        }
+    }
+    (plain.trim().to_string(), speaker)
+}
+
+/// Parse SRT or WebVTT cues into segments
+fn parse_cues(content: &str) -> Vec<Segment> {
+    let content = content.replace("\r\n", "\n");
+    let mut segments = Vec::new();
+
+    for block in content.split("\n\n") {
+        let mut lines = block.lines().skip_while(|line| !line.contains("-->"));
+        let timing = match lines.next() {
+            Some(timing) => timing,
+            None => continue,
+        };
+        let (start, end) = match timing.split_once("-->") {
+            Some(range) => range,
+            None => continue,
+        };
+        // WebVTT cue settings follow the end timestamp
+        let end = end.split_whitespace().next().unwrap_or_default();
+        let (start_ms, end_ms) = match (parse_timestamp(start), parse_timestamp(end)) {
+            (Some(start_ms), Some(end_ms)) => (start_ms, end_ms),
+            _ => continue,
+        };
+
+        let (text, speaker) = cue_text(&lines.collect::<Vec<_>>().join(" "));
+        if text.is_empty() {
+            continue;
+        }
+        segments.push(Segment {
+            start_ms,
+            end_ms,
+            text,
+            confidence: None,
+            speaker,
+        });
+    }
+
+    segments
+}
+
+/// Parse the content of a transcript file
+pub fn parse(content: &str, format: TranscriptFormat) -> Result<Transcript> {
+    let transcript = match format {
+        TranscriptFormat::Text => Transcript {
+            text: content.trim().to_string(),
+            segments: Vec::new(),
+        },
+        TranscriptFormat::Srt | TranscriptFormat::Vtt => {
+            let segments = parse_cues(content);
+            let text = segments
+                .iter()
+                .map(|s| s.text.as_str())
+                .collect::<Vec<_>>()
+                .join(" ");
+            Transcript { text, segments }
+        }
+    };
+
+    if transcript.text.is_empty() {
+        return Err(AppError::Config(format!(
+            "No transcript text found in {} file",
+            format.name()
+        )));
+    }
+    Ok(transcript)
+}
+
+/// Copy an audio file into the recordings directory
+fn copy_audio(source: &Path, id: &str) -> Result<String> {
+    let extension = source.extension().and_then(|e| e.to_str()).unwrap_or("wav");
+    let audio_dir = get_audio_dir()?;
+    std::fs::create_dir_all(&audio_dir)?;
+
+    let dest = audio_dir.join(format!("{}.{}", id, extension));
+    std::fs::copy(source, &dest)?;
+    Ok(dest.to_string_lossy().to_string())
+}
+
+/// Duration of the imported recording, from the audio if readable or the last cue
+fn duration_ms(transcript: &Transcript, audio: Option<&Path>) -> u64 {
+    let from_audio = audio
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|e| e.eq_ignore_ascii_case("wav"))
+        })
+        .and_then(|path| crate::audio::load_wav(&path.to_path_buf()).ok())
+        .map(|samples| crate::audio::calculate_duration_ms(samples.len()));
+
+    from_audio
+        .or_else(|| transcript.segments.iter().map(|s| s.end_ms).max())
+        .unwrap_or(0)
+}
+
+/// Import a transcript file, optionally with its audio, as a new history item
+pub fn import_transcript(
+    db: &Database,
+    transcript_path: &Path,
+    audio_path: Option<&Path>,
+) -> Result<HistoryItem> {
+    let format = TranscriptFormat::from_path(transcript_path);
+    let content = std::fs::read_to_string(transcript_path)?;
+    let transcript = parse(&content, format)?;
+
+    let id = Uuid::new_v4().to_string();
+    let audio = match audio_path {
+        Some(path) => Some(copy_audio(path, &id)?),
+        None => None,
+    };
+
+    let item = HistoryItem {
+        id,
+        created_at: Utc::now(),
+        mode_key: IMPORT_MODE.to_string(),
+        audio_path: audio,
+        transcript_raw: transcript.text.clone(),
+        output_final: transcript.text.clone(),
+        stt_provider: "import".to_string(),
+        stt_model: format.name().to_string(),
+        llm_provider: None,
+        llm_model: None,
+        duration_ms: duration_ms(&transcript, audio_path),
+        error: None,
+        confidence: None,
+        title: None,
+        latencies: None,
+        provider_metadata: None,
+        clipped: false,
+        transcript_path: None,
+    };
+
+    db.insert_history(&item)?;
+    db.insert_segments(&item.id, &transcript.segments)?;
+    log::info!(
+        "Imported {} transcript {:?} as history item {}",
+        format.name(),
+        transcript_path,
+        item.id
+    );
+    Ok(item)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_srt() {
+        let content = "1\r\n00:00:01,000 --> 00:00:03,500\r\nHello there.\r\n\r\n\
+                       2\r\n00:00:04,000 --> 00:00:06,250\r\nSecond line\r\ncontinues.\r\n";
+        let transcript = parse(content, TranscriptFormat::Srt).unwrap();
+        assert_eq!(transcript.text, "Hello there. Second line continues.");
+        assert_eq!(transcript.segments.len(), 2);
+        assert_eq!(transcript.segments[1].start_ms, 4000);
+        assert_eq!(transcript.segments[1].end_ms, 6250);
+    }
+
+    #[test]
+    fn test_parse_vtt() {
+        let content = "WEBVTT\n\nNOTE exported elsewhere\n\n\
+                       intro\n00:01.000 --> 00:02.500 align:start\n<v Alex>Good <b>morning</b>\n\n\
+                       01:00:00.000 --> 01:00:01.000\nBye\n";
+        let transcript = parse(content, TranscriptFormat::Vtt).unwrap();
+        assert_eq!(transcript.text, "Good morning Bye");
+        assert_eq!(transcript.segments[0].start_ms, 1000);
+        assert_eq!(transcript.segments[0].speaker.as_deref(), Some("Alex"));
+        assert_eq!(transcript.segments[1].start_ms, 3_600_000);
+    }
+
+    #[test]
+    fn test_parse_text_and_empty() {
+        let transcript = parse("  just some notes\n", TranscriptFormat::Text).unwrap();
+        assert_eq!(transcript.text, "just some notes");
+        assert!(transcript.segments.is_empty());
+
+        assert!(parse("WEBVTT\n\n", TranscriptFormat::Vtt).is_err());
+    }
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(
+            TranscriptFormat::from_path(Path::new("talk.SRT")),
+            TranscriptFormat::Srt
+        );
+        assert_eq!(
+            TranscriptFormat::from_path(Path::new("talk.vtt")),
+            TranscriptFormat::Vtt
+        );
+        assert_eq!(
+            TranscriptFormat::from_path(Path::new("notes.md")),
+            TranscriptFormat::Text
+        );
+    }
+}
@@ -1,26 +1,163 @@
 //! Recording indicator window management
+//!
+//! The indicator is placed in a corner of the monitor showing the focused
+//! window, the primary monitor or a named one, scaled for that monitor's DPI.
+//! Wayland compositors place windows themselves, so placement only takes
+//! effect on X11 and XWayland.
 
 use crate::error::Result;
+use crate::window::Rect;
 use log::info;
-use serde::Serialize;
-use tauri::{AppHandle, Emitter, Manager, WebviewUrl, WebviewWindowBuilder};
+use serde::{Deserialize, Serialize};
+use tauri::{
+    AppHandle, Emitter, Manager, Monitor, PhysicalPosition, WebviewUrl, WebviewWindow,
+    WebviewWindowBuilder,
+};
 
 const INDICATOR_LABEL: &str = "recording";
 
+/// Indicator size in logical pixels
+const INDICATOR_WIDTH: f64 = 200.0;
+const INDICATOR_HEIGHT: f64 = 60.0;
+/// Distance from the screen edge in logical pixels
+const INDICATOR_MARGIN: f64 = 50.0;
+
 #[derive(Clone, Serialize)]
 pub struct AudioLevel {
     pub level: f32,
     pub peak: f32,
 }
 
-/// Show the recording indicator window
-pub fn show_indicator(handle: &AppHandle) -> Result<()> {
+/// Screen position of the indicator on its monitor
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum IndicatorCorner {
+    TopLeft,
+    #[default]
+    TopCenter,
+    TopRight,
+    BottomLeft,
+    BottomCenter,
+    BottomRight,
+}
+
+/// Where to show the indicator
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndicatorPlacement {
+    /// "focused" (monitor of the focused window), "primary" or a monitor name
+    pub monitor: String,
+    pub corner: IndicatorCorner,
+}
+
+/// A monitor's area in physical pixels and its scale factor
+#[derive(Debug, Clone, PartialEq)]
+pub struct MonitorArea {
+    pub name: String,
+    pub area: Rect,
+    pub scale: f64,
+}
+
+impl MonitorArea {
+    fn from_monitor(monitor: &Monitor) -> Self {
+        Self {
+            name: monitor.name().cloned().unwrap_or_default(),
+            area: Rect {
+                x: monitor.position().x,
+                y: monitor.position().y,
+                width: monitor.size().width,
+                height: monitor.size().height,
+            },
+            scale: monitor.scale_factor(),
+        }
+    }
+}
+
+/// Pick the monitor for the indicator, falling back to the primary and then the first monitor
+pub fn choose_monitor<'a>(
+    monitors: &'a [MonitorArea],
+    preference: &str,
+    focused: Option<Rect>,
+    primary: Option<&str>,
+) -> Option<&'a MonitorArea> {
+    let chosen = match preference {
+        "focused" => focused.and_then(|rect| {
+            let center = rect.center();
+            monitors.iter().find(|m| m.area.contains(center))
+        }),
+        "primary" => None,
+        name => monitors.iter().find(|m| m.name == name),
+    };
+    chosen
+        .or_else(|| primary.and_then(|name| monitors.iter().find(|m| m.name == name)))
+        .or_else(|| monitors.first())
+}
+
+/// Physical position of the indicator in a corner of a monitor, scaled for its DPI
+pub fn indicator_position(monitor: &MonitorArea, corner: IndicatorCorner) -> (i32, i32) {
+    let width = (INDICATOR_WIDTH * monitor.scale) as i32;
+    let height = (INDICATOR_HEIGHT * monitor.scale) as i32;
+    let margin = (INDICATOR_MARGIN * monitor.scale) as i32;
+    let area = monitor.area;
+
+    let x = match corner {
+        IndicatorCorner::TopLeft | IndicatorCorner::BottomLeft => area.x + margin,
+        IndicatorCorner::TopCenter | IndicatorCorner::BottomCenter => {
+            area.x + (area.width as i32 - width) / 2
+        }
+        IndicatorCorner::TopRight | IndicatorCorner::BottomRight => {
+            area.x + area.width as i32 - width - margin
+        }
+    };
+    let y = match corner {
+        IndicatorCorner::TopLeft | IndicatorCorner::TopCenter | IndicatorCorner::TopRight => {
+            area.y + margin
+        }
+        _ => area.y + area.height as i32 - height - margin,
+    };
+    (x, y)
+}
+
+/// Move the indicator to its place for the current monitor layout
+fn place_indicator(
+    handle: &AppHandle,
+    window: &WebviewWindow,
+    placement: &IndicatorPlacement,
+    focused: Option<Rect>,
+) {
+    let monitors: Vec<MonitorArea> = match handle.available_monitors() {
+        Ok(monitors) => monitors.iter().map(MonitorArea::from_monitor).collect(),
+        Err(e) => {
+            log::warn!("Failed to list monitors: {}", e);
+            return;
+        }
+    };
+    let primary = handle
+        .primary_monitor()
+        .ok()
+        .flatten()
+        .and_then(|m| m.name().cloned());
+
+    let chosen = choose_monitor(&monitors, &placement.monitor, focused, primary.as_deref());
+    if let Some(monitor) = chosen {
+        let (x, y) = indicator_position(monitor, placement.corner);
+        let _ = window.set_position(PhysicalPosition::new(x, y));
+    }
+}
+
+/// Show the recording indicator window without taking focus from the target window
+///
+/// `focused` is the focused window's area, used when placing on its monitor.
+pub fn show_indicator(
+    handle: &AppHandle,
+    placement: &IndicatorPlacement,
+    focused: Option<Rect>,
+) -> Result<()> {
     // Try to get existing window or create new one
     if let Some(window) = handle.get_webview_window(INDICATOR_LABEL) {
         // Navigate to the recording route and show
         let _ = window.eval("window.location.href = '/recording'");
+        place_indicator(handle, &window, placement, focused);
         let _ = window.show();
-        let _ = window.set_focus();
         info!("Recording indicator shown");
     } else {
         // Create the window if it doesn't exist
@@ -30,26 +167,17 @@ pub fn show_indicator(handle: &AppHandle) -> Result<()> {
             WebviewUrl::App("/recording".into()),
         )
         .title("")
-        .inner_size(200.0, 60.0)
+        .inner_size(INDICATOR_WIDTH, INDICATOR_HEIGHT)
         .decorations(false)
         .transparent(true)
         .always_on_top(true)
         .skip_taskbar(true)
         .resizable(false)
+        .focused(false)
         .visible(true)
         .build()?;
 
-        // Position near top-center of screen
-        if let Ok(monitor) = window.current_monitor() {
-            if let Some(monitor) = monitor {
-                let size = monitor.size();
-                let x = (size.width as i32 - 200) / 2;
-                let y = 50;
-                let _ = window.set_position(tauri::Position::Physical(
-                    tauri::PhysicalPosition::new(x, y),
-                ));
-            }
-        }
+        place_indicator(handle, &window, placement, focused);
 
         info!("Recording indicator window created");
     }
@@ -79,3 +207,62 @@ pub fn emit_audio_level(handle: &AppHandle, level: f32, peak: f32) {
 pub fn emit_processing(handle: &AppHandle, processing: bool) {
     let _ = handle.emit_to(INDICATOR_LABEL, "recording-processing", processing);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn monitor(name: &str, x: i32, width: u32, scale: f64) -> MonitorArea {
+        MonitorArea {
+            name: name.to_string(),
+            area: Rect {
+                x,
+                y: 0,
+                width,
+                height: 1080,
+            },
+            scale,
+        }
+    }
+
+    #[test]
+    fn test_choose_monitor() {
+        let monitors = [
+            monitor("DP-1", 0, 1920, 1.0),
+            monitor("HDMI-1", 1920, 3840, 2.0),
+        ];
+        let window = Rect {
+            x: 2500,
+            y: 100,
+            width: 800,
+            height: 600,
+        };
+
+        let chosen = |preference, focused| {
+            choose_monitor(&monitors, preference, focused, Some("DP-1")).map(|m| m.name.as_str())
+        };
+        assert_eq!(chosen("focused", Some(window)), Some("HDMI-1"));
+        // Unknown focus falls back to the primary monitor
+        assert_eq!(chosen("focused", None), Some("DP-1"));
+        assert_eq!(chosen("primary", Some(window)), Some("DP-1"));
+        assert_eq!(chosen("HDMI-1", None), Some("HDMI-1"));
+        assert_eq!(chosen("DVI-0", None), Some("DP-1"));
+    }
+
+    #[test]
+    fn test_indicator_position() {
+        let hidpi = monitor("HDMI-1", 1920, 3840, 2.0);
+        assert_eq!(
+            indicator_position(&hidpi, IndicatorCorner::TopCenter),
+            (1920 + (3840 - 400) / 2, 100)
+        );
+        assert_eq!(
+            indicator_position(&hidpi, IndicatorCorner::BottomRight),
+            (1920 + 3840 - 400 - 100, 1080 - 120 - 100)
+        );
+        assert_eq!(
+            indicator_position(&monitor("DP-1", 0, 1920, 1.0), IndicatorCorner::TopLeft),
+            (50, 50)
+        );
+    }
+}
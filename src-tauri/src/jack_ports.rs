@@ -0,0 +1,128 @@
+//! JACK capture ports
+//!
+//! With `audio_backend` set to `jack`, cpal records through its own JACK
+//! client and connects it to the system capture ports. `jack_ports` connects
+//! other ports instead, e.g. one input of an audio interface or the output of
+//! a mixer application. Builds without the `jack` feature have no JACK
+//! support.
+
+use crate::error::Result;
+
+/// Pair source ports with the recorder's inputs, cycling through the inputs
+pub fn pair_ports(sources: &[String], inputs: &[String]) -> Vec<(String, String)> {
+    if inputs.is_empty() {
+        return Vec::new();
+    }
+    sources
+        .iter()
+        .enumerate()
+        .map(|(i, source)| (source.clone(), inputs[i % inputs.len()].clone()))
+        .collect()
+}
+
+#[cfg(feature = "jack")]
+mod client {
+    use super::pair_ports;
+    use crate::error::{AppError, Result};
+    use jack::{Client, ClientOptions, PortFlags};
+
+    /// Client cpal registers for recording
+    const RECORDER_CLIENT: &str = "cpal_client_in";
+
+    fn connect_client() -> Result<Client> {
+        Client::new("whispertray_ports", ClientOptions::NO_START_SERVER)
+            .map(|(client, _)| client)
+            .map_err(|e| AppError::Audio(format!("JACK server not available: {}", e)))
+    }
+
+    pub fn capture_ports() -> Result<Vec<String>> {
+        let client = connect_client()?;
+        let ports = client.ports(None, Some("audio"), PortFlags::IS_OUTPUT);
+        Ok(ports
+            .into_iter()
+            .filter(|port| !port.starts_with(RECORDER_CLIENT))
+            .collect())
+    }
+
+    pub fn connect(sources: &[String]) -> Result<()> {
+        let client = connect_client()?;
+        let pattern = format!("^{}[^:]*:", RECORDER_CLIENT);
+        let inputs = client.ports(Some(&pattern), Some("audio"), PortFlags::IS_INPUT);
+        if inputs.is_empty() {
+            return Err(AppError::Audio(
+                "The recorder has no JACK ports yet".to_string(),
+            ));
+        }
+
+        // Replace the automatic connections to the system ports
+        let outputs = client.ports(None, Some("audio"), PortFlags::IS_OUTPUT);
+        for input in &inputs {
+            if let Some(port) = client.port_by_name(input) {
+                for output in &outputs {
+                    if port.is_connected_to(output).unwrap_or(false) {
+                        let _ = client.disconnect_ports_by_name(output, input);
+                    }
+                }
+            }
+        }
+        for (source, input) in pair_ports(sources, &inputs) {
+            client
+                .connect_ports_by_name(&source, &input)
+                .map_err(|e| AppError::Audio(format!("Failed to connect {}: {}", source, e)))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(not(feature = "jack"))]
+mod client {
+    use crate::error::{AppError, Result};
+
+    fn unavailable<T>() -> Result<T> {
+        Err(AppError::Audio(
+            "JACK is not available in this build (enable the `jack` feature)".to_string(),
+        ))
+    }
+
+    pub fn capture_ports() -> Result<Vec<String>> {
+        unavailable()
+    }
+
+    pub fn connect(_sources: &[String]) -> Result<()> {
+        unavailable()
+    }
+}
+
+/// Ports that can be recorded from
+pub fn capture_ports() -> Result<Vec<String>> {
+    client::capture_ports()
+}
+
+/// Connect the recorder to `sources` instead of the system capture ports
+pub fn connect(sources: &[String]) -> Result<()> {
+    client::connect(sources)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pair_ports() {
+        let sources = vec![
+            "system:capture_3".to_string(),
+            "system:capture_4".to_string(),
+            "mixer:out_1".to_string(),
+        ];
+        let inputs = vec![
+            "cpal_client_in:in_0".to_string(),
+            "cpal_client_in:in_1".to_string(),
+        ];
+        let pairs = pair_ports(&sources, &inputs);
+        assert_eq!(pairs.len(), 3);
+        assert_eq!(pairs[0].1, "cpal_client_in:in_0");
+        assert_eq!(pairs[1].1, "cpal_client_in:in_1");
+        assert_eq!(pairs[2], ("mixer:out_1".to_string(), inputs[0].clone()));
+        assert!(pair_ports(&sources, &[]).is_empty());
+    }
+}
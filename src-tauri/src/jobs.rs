@@ -0,0 +1,477 @@
+//! Job queue
+//!
+//! Dictations, file transcriptions and reprocessing runs are tracked as jobs
+//! with a status, progress and their own cancellation token. Background jobs
+//! (files and reprocessing) take turns in a lane of their own, so a batch of
+//! files queues up instead of piling onto the providers, while dictations
+//! start right away. While a dictation runs, background jobs pause at their
+//! next yield point, e.g. between upload chunks, so the dictation has the
+//! providers to itself. The frontend lists the jobs and follows them through
+//! `JOB_PROGRESS_EVENT`.
+
+use crate::error::{AppError, Result};
+use crate::providers::stt::YieldPoint;
+use chrono::{DateTime, Utc};
+use futures_util::FutureExt;
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter};
+use tokio::sync::{watch, OwnedSemaphorePermit, Semaphore};
+use tokio_util::sync::CancellationToken;
+
+/// Event emitted with a job whenever its status or progress changes
+pub const JOB_PROGRESS_EVENT: &str = "job-progress";
+
+/// Finished jobs kept for the job list
+const MAX_FINISHED_JOBS: usize = 20;
+
+/// Background jobs running at the same time
+const BACKGROUND_SLOTS: usize = 1;
+
+/// What a job does
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobKind {
+    Dictation,
+    File,
+    Reprocess,
+}
+
+impl JobKind {
+    /// Whether the job waits for a slot in the background lane
+    pub fn is_background(self) -> bool {
+        self != JobKind::Dictation
+    }
+}
+
+/// Where a job is in its life
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    /// Waiting for a dictation to finish
+    Paused,
+    Done,
+    Failed,
+    Cancelled,
+}
+
+impl JobStatus {
+    pub fn is_finished(self) -> bool {
+        matches!(
+            self,
+            JobStatus::Done | JobStatus::Failed | JobStatus::Cancelled
+        )
+    }
+}
+
+/// A tracked unit of work
+#[derive(Debug, Clone, Serialize)]
+pub struct Job {
+    pub id: String,
+    pub kind: JobKind,
+    /// Shown in the job list, e.g. the file name
+    pub label: String,
+    pub status: JobStatus,
+    /// Percent done, if the job reports it
+    pub progress: Option<u8>,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+struct Entry {
+    job: Job,
+    token: CancellationToken,
+}
+
+type ChangeListener = Box<dyn Fn(&Job) + Send + Sync>;
+
+/// All jobs of the session (also managed by Tauri)
+pub struct JobQueue {
+    jobs: Mutex<Vec<Entry>>,
+    background: Arc<Semaphore>,
+    /// Number of dictations in progress
+    dictations: watch::Sender<usize>,
+    on_change: ChangeListener,
+}
+
+impl JobQueue {
+    /// Create a queue that reports every change to `on_change`
+    pub fn new(on_change: impl Fn(&Job) + Send + Sync + 'static) -> Self {
+        Self {
+            jobs: Mutex::new(Vec::new()),
+            background: Arc::new(Semaphore::new(BACKGROUND_SLOTS)),
+            dictations: watch::channel(0).0,
+            on_change: Box::new(on_change),
+        }
+    }
+
+    /// Create a queue that emits `JOB_PROGRESS_EVENT`
+    pub fn with_events(app_handle: AppHandle) -> Self {
+        Self::new(move |job| {
+            let _ = app_handle.emit(JOB_PROGRESS_EVENT, job);
+        })
+    }
+
+    /// Register a job and wait for its turn
+    ///
+    /// Background jobs also wait for running dictations. Cancelling `token`
+    /// while the job is queued gives `AppError::Cancelled`.
+    pub async fn start(
+        self: &Arc<Self>,
+        kind: JobKind,
+        label: impl Into<String>,
+        token: CancellationToken,
+    ) -> Result<JobHandle> {
+        let id = uuid::Uuid::new_v4().to_string();
+        let job = Job {
+            id: id.clone(),
+            kind,
+            label: label.into(),
+            status: JobStatus::Queued,
+            progress: None,
+            error: None,
+            created_at: Utc::now(),
+        };
+        (self.on_change)(&job);
+        if let Ok(mut jobs) = self.jobs.lock() {
+            jobs.push(Entry {
+                job,
+                token: token.clone(),
+            });
+        }
+
+        let mut handle = JobHandle {
+            queue: self.clone(),
+            id,
+            kind,
+            token,
+            permit: None,
+            finished: false,
+        };
+        if kind.is_background() {
+            let permit = tokio::select! {
+                _ = handle.token.cancelled() => return Err(AppError::Cancelled),
+                permit = self.background.clone().acquire_owned() => permit,
+            };
+            handle.permit = permit.ok();
+            tokio::select! {
+                _ = handle.token.cancelled() => return Err(AppError::Cancelled),
+                _ = self.idle() => {}
+            }
+        } else {
+            self.dictations.send_modify(|count| *count += 1);
+        }
+        self.update(&handle.id, |job| job.status = JobStatus::Running);
+        Ok(handle)
+    }
+
+    /// All jobs, newest first
+    pub fn list(&self) -> Vec<Job> {
+        match self.jobs.lock() {
+            Ok(jobs) => jobs.iter().rev().map(|entry| entry.job.clone()).collect(),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    /// Cancel a queued or running job, returning whether it was found
+    pub fn cancel(&self, id: &str) -> bool {
+        let jobs = match self.jobs.lock() {
+            Ok(jobs) => jobs,
+            Err(_) => return false,
+        };
+        match jobs
+            .iter()
+            .find(|entry| entry.job.id == id && !entry.job.status.is_finished())
+        {
+            Some(entry) => {
+                log::info!("Cancelling job {}", entry.job.label);
+                entry.token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Whether a dictation is in progress
+    pub fn dictating(&self) -> bool {
+        *self.dictations.borrow() > 0
+    }
+
+    /// Wait until no dictation is in progress
+    async fn idle(&self) {
+        let _ = self
+            .dictations
+            .subscribe()
+            .wait_for(|count| *count == 0)
+            .await;
+    }
+
+    /// Pause a background job while a dictation is in progress
+    async fn yield_to_dictations(&self, id: &str) {
+        if !self.dictating() {
+            return;
+        }
+        log::debug!("Pausing job {} for a dictation", id);
+        self.update(id, |job| job.status = JobStatus::Paused);
+        self.idle().await;
+        self.update(id, |job| job.status = JobStatus::Running);
+    }
+
+    fn update(&self, id: &str, change: impl FnOnce(&mut Job)) {
+        let job = {
+            let mut jobs = match self.jobs.lock() {
+                Ok(jobs) => jobs,
+                Err(_) => return,
+            };
+            let job = match jobs.iter_mut().find(|entry| entry.job.id == id) {
+                Some(entry) => {
+                    change(&mut entry.job);
+                    entry.job.clone()
+                }
+                None => return,
+            };
+            if job.status.is_finished() {
+                prune(&mut jobs);
+            }
+            job
+        };
+        (self.on_change)(&job);
+    }
+}
+
+/// Drop the oldest finished jobs beyond `MAX_FINISHED_JOBS`
+fn prune(jobs: &mut Vec<Entry>) {
+    let finished = jobs
+        .iter()
+        .filter(|entry| entry.job.status.is_finished())
+        .count();
+    let mut excess = finished.saturating_sub(MAX_FINISHED_JOBS);
+    jobs.retain(|entry| {
+        let drop = excess > 0 && entry.job.status.is_finished();
+        if drop {
+            excess -= 1;
+        }
+        !drop
+    });
+}
+
+/// A started job; finish it with its result
+///
+/// Dropping an unfinished handle marks the job as failed, or as cancelled
+/// if its token fired.
+pub struct JobHandle {
+    queue: Arc<JobQueue>,
+    id: String,
+    kind: JobKind,
+    token: CancellationToken,
+    permit: Option<OwnedSemaphorePermit>,
+    finished: bool,
+}
+
+impl JobHandle {
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Token that fires when the job is cancelled
+    pub fn token(&self) -> &CancellationToken {
+        &self.token
+    }
+
+    /// Report progress in percent
+    pub fn progress(&self, percent: u8) {
+        self.queue
+            .update(&self.id, |job| job.progress = Some(percent.min(100)));
+    }
+
+    /// Progress reporter that can be handed to providers
+    pub fn reporter(&self) -> impl Fn(u8) + Send + Sync + 'static {
+        let queue = self.queue.clone();
+        let id = self.id.clone();
+        move |percent| queue.update(&id, |job| job.progress = Some(percent.min(100)))
+    }
+
+    /// Yield point that pauses the job while a dictation is in progress
+    ///
+    /// Handed to providers that work in steps; dictations never pause.
+    pub fn yield_point(&self) -> YieldPoint {
+        let queue = self.queue.clone();
+        let id = self.id.clone();
+        let background = self.kind.is_background();
+        Arc::new(move || {
+            let queue = queue.clone();
+            let id = id.clone();
+            async move {
+                if background {
+                    queue.yield_to_dictations(&id).await;
+                }
+            }
+            .boxed()
+        })
+    }
+
+    /// Record the outcome of the job
+    pub fn finish<T, E: Display>(mut self, result: &std::result::Result<T, E>) {
+        let (status, error) = match result {
+            Ok(_) => (JobStatus::Done, None),
+            Err(_) if self.token.is_cancelled() => (JobStatus::Cancelled, None),
+            Err(e) => (JobStatus::Failed, Some(e.to_string())),
+        };
+        self.close(status, error);
+    }
+
+    fn close(&mut self, status: JobStatus, error: Option<String>) {
+        self.finished = true;
+        self.permit = None;
+        if !self.kind.is_background() {
+            self.queue
+                .dictations
+                .send_modify(|count| *count = count.saturating_sub(1));
+        }
+        self.queue.update(&self.id, |job| {
+            job.status = status;
+            job.error = error;
+            if status == JobStatus::Done {
+                job.progress = Some(100);
+            }
+        });
+    }
+}
+
+impl Drop for JobHandle {
+    fn drop(&mut self) {
+        if !self.finished {
+            let status = if self.token.is_cancelled() {
+                JobStatus::Cancelled
+            } else {
+                JobStatus::Failed
+            };
+            self.close(status, None);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_job_lifecycle() {
+        let queue = Arc::new(JobQueue::new(|_| {}));
+        let job = queue
+            .start(JobKind::Dictation, "Note", CancellationToken::new())
+            .await
+            .unwrap();
+        job.progress(40);
+        assert_eq!(queue.list()[0].status, JobStatus::Running);
+        assert_eq!(queue.list()[0].progress, Some(40));
+
+        let id = job.id().to_string();
+        assert!(queue.cancel(&id));
+        assert!(job.token().is_cancelled());
+        job.finish::<(), _>(&Err(AppError::Cancelled));
+        assert_eq!(queue.list()[0].status, JobStatus::Cancelled);
+        assert!(!queue.cancel(&id));
+
+        let job = queue
+            .start(JobKind::File, "talk.wav", CancellationToken::new())
+            .await
+            .unwrap();
+        drop(job);
+        assert_eq!(queue.list()[0].status, JobStatus::Failed);
+    }
+
+    #[tokio::test]
+    async fn test_background_jobs_take_turns() {
+        let queue = Arc::new(JobQueue::new(|_| {}));
+        let first = queue
+            .start(JobKind::File, "a.wav", CancellationToken::new())
+            .await
+            .unwrap();
+
+        let waiting = tokio::spawn({
+            let queue = queue.clone();
+            async move {
+                queue
+                    .start(JobKind::Reprocess, "b", CancellationToken::new())
+                    .await
+                    .map(|job| job.id().to_string())
+            }
+        });
+        tokio::task::yield_now().await;
+        assert_eq!(queue.list()[0].status, JobStatus::Queued);
+
+        // Dictations don't wait for the background lane
+        let dictation = queue
+            .start(JobKind::Dictation, "Note", CancellationToken::new())
+            .await
+            .unwrap();
+        dictation.finish::<(), AppError>(&Ok(()));
+
+        first.finish::<(), AppError>(&Ok(()));
+        let id = waiting.await.unwrap().unwrap();
+        let second = queue.list().into_iter().find(|j| j.id == id).unwrap();
+        assert!(second.status.is_finished());
+    }
+
+    #[tokio::test]
+    async fn test_background_jobs_pause_for_dictations() {
+        let queue = Arc::new(JobQueue::new(|_| {}));
+        let file = queue
+            .start(JobKind::File, "a.wav", CancellationToken::new())
+            .await
+            .unwrap();
+        let dictation = queue
+            .start(JobKind::Dictation, "Note", CancellationToken::new())
+            .await
+            .unwrap();
+        assert!(queue.dictating());
+
+        // Dictations pass their own yield points
+        dictation.yield_point()().await;
+
+        let paused = tokio::spawn(file.yield_point()());
+        tokio::task::yield_now().await;
+        let status = |id: &str| {
+            queue
+                .list()
+                .into_iter()
+                .find(|j| j.id == id)
+                .unwrap()
+                .status
+        };
+        assert_eq!(status(file.id()), JobStatus::Paused);
+
+        dictation.finish::<(), AppError>(&Ok(()));
+        assert!(!queue.dictating());
+        paused.await.unwrap();
+        assert_eq!(status(file.id()), JobStatus::Running);
+    }
+
+    #[test]
+    fn test_prune_keeps_recent_finished_jobs() {
+        let mut jobs = Vec::new();
+        for i in 0..MAX_FINISHED_JOBS + 5 {
+            jobs.push(Entry {
+                job: Job {
+                    id: i.to_string(),
+                    kind: JobKind::File,
+                    label: String::new(),
+                    status: JobStatus::Done,
+                    progress: None,
+                    error: None,
+                    created_at: Utc::now(),
+                },
+                token: CancellationToken::new(),
+            });
+        }
+        jobs[0].job.status = JobStatus::Running;
+        prune(&mut jobs);
+        assert_eq!(jobs.len(), MAX_FINISHED_JOBS + 1);
+        assert_eq!(jobs[0].job.id, "0");
+        assert_eq!(jobs[1].job.id, "6");
+    }
+}
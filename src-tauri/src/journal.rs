@@ -0,0 +1,121 @@
+//! Daily voice journal
+//!
+//! When enabled, every dictation (or only those from selected modes) is
+//! appended to a per-day Markdown file such as `2024-05-14.md`, with the time
+//! and mode as a heading.
+
+use crate::database::HistoryItem;
+use crate::error::{AppError, Result};
+use chrono::{DateTime, Local, NaiveDate};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// The user's documents directory
+pub fn documents_dir() -> Result<PathBuf> {
+    let user_dirs = directories::UserDirs::new()
+        .ok_or_else(|| AppError::Config("Could not determine home directory".to_string()))?;
+    Ok(user_dirs
+        .document_dir()
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| user_dirs.home_dir().join("Documents")))
+}
+
+/// Journal directory used when none is configured
+pub fn default_dir() -> Result<PathBuf> {
+    Ok(documents_dir()?.join("WhisperTray Journal"))
+}
+
+/// Expand a leading `~/` in a configured path
+pub fn expand_home(path: &str) -> Result<PathBuf> {
+    match path.strip_prefix("~/") {
+        Some(rest) => {
+            let user_dirs = directories::UserDirs::new().ok_or_else(|| {
+                AppError::Config("Could not determine home directory".to_string())
+            })?;
+            Ok(user_dirs.home_dir().join(rest))
+        }
+        None => Ok(PathBuf::from(path)),
+    }
+}
+
+/// Resolve the configured journal directory
+pub fn resolve_dir(configured: &str) -> Result<PathBuf> {
+    match configured.trim() {
+        "" => default_dir(),
+        dir => expand_home(dir),
+    }
+}
+
+/// Whether dictations in `mode_key` go to the journal (no modes selected means all)
+pub fn includes_mode(modes: &[String], mode_key: &str) -> bool {
+    modes.is_empty() || modes.iter().any(|m| m == mode_key)
+}
+
+/// Journal file for a day
+pub fn day_path(dir: &Path, date: NaiveDate) -> PathBuf {
+    dir.join(format!("{}.md", date.format("%Y-%m-%d")))
+}
+
+/// Markdown for one journal entry
+fn format_entry(time: DateTime<Local>, mode_name: &str, text: &str) -> String {
+    format!(
+        "## {} · {}\n\n{}\n\n",
+        time.format("%H:%M"),
+        mode_name,
+        text.trim()
+    )
+}
+
+/// Append a dictation to the journal file of the day it was made
+pub fn append(dir: &Path, item: &HistoryItem, mode_name: &str) -> Result<PathBuf> {
+    let time = item.created_at.with_timezone(&Local);
+    let path = day_path(dir, time.date_naive());
+    std::fs::create_dir_all(dir)?;
+
+    let is_new = !path.exists();
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)?;
+    if is_new {
+        write!(file, "# {}\n\n", time.format("%A, %B %-d, %Y"))?;
+    }
+    file.write_all(format_entry(time, mode_name, &item.output_final).as_bytes())?;
+
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_includes_mode() {
+        assert!(includes_mode(&[], "email"));
+        let modes = vec!["note".to_string()];
+        assert!(includes_mode(&modes, "note"));
+        assert!(!includes_mode(&modes, "email"));
+    }
+
+    #[test]
+    fn test_append_creates_day_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let created_at = Local.with_ymd_and_hms(2024, 5, 14, 9, 5, 0).unwrap();
+        let item = HistoryItem {
+            created_at: created_at.into(),
+            output_final: "Call the plumber.\n".to_string(),
+            ..HistoryItem::default()
+        };
+
+        let path = append(dir.path(), &item, "Note").unwrap();
+        append(dir.path(), &item, "Note").unwrap();
+        assert_eq!(path, dir.path().join("2024-05-14.md"));
+        assert_eq!(
+            std::fs::read_to_string(path).unwrap(),
+            "# Tuesday, May 14, 2024\n\n\
+             ## 09:05 · Note\n\nCall the plumber.\n\n\
+             ## 09:05 · Note\n\nCall the plumber.\n\n"
+        );
+    }
+}
@@ -0,0 +1,240 @@
+//! Keyboard layout handling for keystroke injection
+//!
+//! Tools like ydotool emit raw evdev keycodes and assume a US layout, so on
+//! other layouts "z" comes out as "y" and umlauts are lost. This module maps
+//! each character to the US key that produces it on the active XKB layout and
+//! falls back to Unicode hex input (Ctrl+Shift+U) for anything unmapped.
+
+use std::process::Command;
+
+/// A piece of text prepared for keystroke injection
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeySegment {
+    /// Text translated to the US characters whose keys produce it on the active layout
+    Keys(String),
+    /// Character that must be entered via Unicode input
+    Unicode(char),
+}
+
+/// Detect the active XKB layout (e.g. "us", "de"), defaulting to "us"
+pub fn detect_layout() -> String {
+    if let Ok(layout) = std::env::var("XKB_DEFAULT_LAYOUT") {
+        if let Some(first) = first_layout(&layout) {
+            return first;
+        }
+    }
+
+    // X11 (and XWayland) expose the layout through setxkbmap
+    if let Some(layout) = query_command("setxkbmap", &["-query"], "layout:") {
+        return layout;
+    }
+
+    // systemd-localed knows the configured layout on most distributions
+    if let Some(layout) = query_command("localectl", &["status"], "X11 Layout:") {
+        return layout;
+    }
+
+    "us".to_string()
+}
+
+/// Run a command and extract the first layout from the line with the given prefix
+fn query_command(cmd: &str, args: &[&str], prefix: &str) -> Option<String> {
+    let output = Command::new(cmd).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::trim)
+        .find_map(|line| line.strip_prefix(prefix))
+        .and_then(first_layout)
+}
+
+/// Take the first entry of a comma-separated layout list
+fn first_layout(layouts: &str) -> Option<String> {
+    layouts
+        .split(',')
+        .next()
+        .map(|l| l.trim().to_lowercase())
+        .filter(|l| !l.is_empty())
+}
+
+/// Character translation table for a layout: (desired char, US char on the same key)
+fn layout_table(layout: &str) -> Option<&'static [(char, char)]> {
+    match layout {
+        "us" => Some(&[]),
+        "de" => Some(&[
+            ('y', 'z'),
+            ('z', 'y'),
+            ('Y', 'Z'),
+            ('Z', 'Y'),
+            ('"', '@'),
+            ('§', '#'),
+            ('&', '^'),
+            ('/', '&'),
+            ('(', '*'),
+            (')', '('),
+            ('=', ')'),
+            ('ß', '-'),
+            ('?', '_'),
+            ('ü', '['),
+            ('Ü', '{'),
+            ('+', ']'),
+            ('*', '}'),
+            ('ö', ';'),
+            ('Ö', ':'),
+            ('ä', '\''),
+            ('Ä', '"'),
+            ('#', '\\'),
+            ('\'', '|'),
+            (';', '<'),
+            (':', '>'),
+            ('-', '/'),
+            ('_', '?'),
+            ('°', '~'),
+        ]),
+        "gb" => Some(&[
+            ('"', '@'),
+            ('@', '"'),
+            ('£', '#'),
+            ('#', '\\'),
+            ('~', '|'),
+        ]),
+        _ => None,
+    }
+}
+
+/// Characters typed with a dead key on a layout: (desired char, US chars on
+/// the same keys), where the space completes the dead key
+fn dead_key_table(layout: &str) -> &'static [(char, &'static str)] {
+    match layout {
+        "de" => &[('`', "+ "), ('´', "= "), ('^', "` ")],
+        _ => &[],
+    }
+}
+
+/// Plan keystrokes for text on the given layout.
+///
+/// Returns `None` if the layout is unknown, in which case callers should
+/// prefer a clipboard paste over typing.
+pub fn plan_keystrokes(text: &str, layout: &str) -> Option<Vec<KeySegment>> {
+    let table = layout_table(layout)?;
+    let dead_keys = dead_key_table(layout);
+    let mut segments = Vec::new();
+    let mut keys = String::new();
+
+    for c in text.chars() {
+        if let Some((_, sequence)) = dead_keys.iter().find(|(from, _)| *from == c) {
+            keys.push_str(sequence);
+            continue;
+        }
+        let mapped = table
+            .iter()
+            .find(|(from, _)| *from == c)
+            .map(|(_, to)| *to)
+            .or_else(|| {
+                // ASCII characters type as-is unless their US key produces
+                // something else on this layout
+                let displaced = table.iter().any(|(_, to)| *to == c);
+                (c.is_ascii() && !displaced).then_some(c)
+            });
+
+        match mapped {
+            Some(key) => keys.push(key),
+            None => {
+                if !keys.is_empty() {
+                    segments.push(KeySegment::Keys(std::mem::take(&mut keys)));
+                }
+                segments.push(KeySegment::Unicode(c));
+            }
+        }
+    }
+
+    if !keys.is_empty() {
+        segments.push(KeySegment::Keys(keys));
+    }
+
+    Some(segments)
+}
+
+/// Hex digits for Unicode input of a character, translated for the layout
+pub fn unicode_hex_keys(c: char, layout: &str) -> String {
+    let hex = format!("{:x}", c as u32);
+    match plan_keystrokes(&hex, layout) {
+        Some(segments) => segments
+            .into_iter()
+            .filter_map(|s| match s {
+                KeySegment::Keys(k) => Some(k),
+                KeySegment::Unicode(_) => None,
+            })
+            .collect(),
+        None => hex,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_us_layout_passes_ascii_through() {
+        let plan = plan_keystrokes("Hello, world!", "us").unwrap();
+        assert_eq!(plan, vec![KeySegment::Keys("Hello, world!".to_string())]);
+    }
+
+    #[test]
+    fn test_us_layout_uses_unicode_for_accents() {
+        let plan = plan_keystrokes("café", "us").unwrap();
+        assert_eq!(
+            plan,
+            vec![
+                KeySegment::Keys("caf".to_string()),
+                KeySegment::Unicode('é'),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_german_layout_mapping() {
+        let plan = plan_keystrokes("Größe: zwölf", "de").unwrap();
+        assert_eq!(plan, vec![KeySegment::Keys("Gr;-e> yw;lf".to_string())]);
+    }
+
+    #[test]
+    fn test_german_layout_dead_keys() {
+        let plan = plan_keystrokes("`ls` ^2", "de").unwrap();
+        assert_eq!(plan, vec![KeySegment::Keys("+ ls+  ` 2".to_string())]);
+    }
+
+    #[test]
+    fn test_german_layout_displaced_keys_use_unicode() {
+        // '@' needs AltGr on German layouts, so it cannot be typed via the US '@' key
+        let plan = plan_keystrokes("a@b", "de").unwrap();
+        assert_eq!(
+            plan,
+            vec![
+                KeySegment::Keys("a".to_string()),
+                KeySegment::Unicode('@'),
+                KeySegment::Keys("b".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unknown_layout() {
+        assert!(plan_keystrokes("text", "xx").is_none());
+    }
+
+    #[test]
+    fn test_first_layout() {
+        assert_eq!(first_layout("de,us"), Some("de".to_string()));
+        assert_eq!(first_layout(""), None);
+    }
+
+    #[test]
+    fn test_unicode_hex_keys() {
+        assert_eq!(unicode_hex_keys('é', "us"), "e9");
+        assert_eq!(unicode_hex_keys('€', "de"), "20ac");
+    }
+}
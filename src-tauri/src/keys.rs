@@ -0,0 +1,135 @@
+//! API key cache
+//!
+//! API keys live in the system keyring. Some keyrings ask for the password
+//! on every access, so with `cache_api_keys` the keys are read once and kept
+//! in memory until they are locked again or the app quits.
+
+use crate::error::{AppError, Result};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Keyring service the keys are stored under
+const SERVICE: &str = "whispertray";
+
+/// Names API keys are stored under in the keyring
+pub const API_KEY_NAMES: [&str; 8] = [
+    "openai",
+    "anthropic",
+    "deepgram",
+    "stt_server",
+    "todoist",
+    "caldav",
+    "slack",
+    "matrix",
+];
+
+/// API keys read this session, by provider name (`None` if no key is saved)
+#[derive(Default)]
+pub struct KeyCache {
+    keys: Mutex<HashMap<String, Option<String>>>,
+}
+
+impl KeyCache {
+    /// Get the key for `provider`, from the cache if `cached` and it was read before
+    pub fn get(&self, provider: &str, cached: bool) -> Result<Option<String>> {
+        let provider = provider.to_lowercase();
+        if cached {
+            if let Some(key) = self
+                .keys
+                .lock()
+                .ok()
+                .and_then(|k| k.get(&provider).cloned())
+            {
+                return Ok(key);
+            }
+        }
+
+        let key = read(&provider)?;
+        if cached {
+            self.remember(&provider, key.clone());
+        }
+        Ok(key)
+    }
+
+    /// Update the cached key after it was saved or deleted
+    pub fn remember(&self, provider: &str, key: Option<String>) {
+        if let Ok(mut keys) = self.keys.lock() {
+            keys.insert(provider.to_lowercase(), key);
+        }
+    }
+
+    /// Read all keys, so the keyring is only asked once
+    pub fn unlock(&self) {
+        for provider in API_KEY_NAMES {
+            if let Err(e) = self.get(provider, true) {
+                log::warn!("{}", e);
+            }
+        }
+    }
+
+    /// Forget all cached keys, returning how many were cached
+    pub fn lock(&self) -> usize {
+        match self.keys.lock() {
+            Ok(mut keys) => {
+                let count = keys.len();
+                keys.clear();
+                count
+            }
+            Err(_) => 0,
+        }
+    }
+}
+
+fn entry(provider: &str) -> Result<keyring::Entry> {
+    let key_name = format!("{}_api_key", provider.to_lowercase());
+    keyring::Entry::new(SERVICE, &key_name)
+        .map_err(|e| AppError::Keyring(format!("Failed to access keyring: {}", e)))
+}
+
+/// Read the key saved for `provider` from the keyring
+pub fn read(provider: &str) -> Result<Option<String>> {
+    match entry(provider)?.get_password() {
+        Ok(password) => Ok(Some(password)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(AppError::Keyring(format!("Failed to get API key: {}", e))),
+    }
+}
+
+/// Save the key for `provider` to the keyring
+pub fn save(provider: &str, key: &str) -> Result<()> {
+    entry(provider)?
+        .set_password(key)
+        .map_err(|e| AppError::Keyring(format!("Failed to save API key: {}", e)))
+}
+
+/// Delete the key for `provider` from the keyring
+pub fn delete(provider: &str) -> Result<()> {
+    match entry(provider)?.delete_password() {
+        Ok(_) => Ok(()),
+        Err(keyring::Error::NoEntry) => Ok(()), // Already deleted
+        Err(e) => Err(AppError::Keyring(format!(
+            "Failed to delete API key: {}",
+            e
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cached_keys_until_locked() {
+        let cache = KeyCache::default();
+        cache.remember("OpenAI", Some("sk-test".to_string()));
+        cache.remember("anthropic", None);
+
+        assert_eq!(
+            cache.get("openai", true).unwrap().as_deref(),
+            Some("sk-test")
+        );
+        assert_eq!(cache.get("anthropic", true).unwrap(), None);
+        assert_eq!(cache.lock(), 2);
+        assert_eq!(cache.lock(), 0);
+    }
+}
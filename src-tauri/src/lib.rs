@@ -3,48 +3,171 @@
 //! This application provides voice-to-text transcription with optional
 //! AI post-processing, all accessible from the system tray.
 
+pub mod action_items;
+pub mod archive;
 pub mod audio;
+pub mod bluetooth;
+pub mod calibration;
+pub mod chat;
+pub mod cli;
 pub mod commands;
+pub mod config_bundle;
+pub mod control;
+pub mod corrections;
+pub mod daemon;
 pub mod database;
+pub mod db_worker;
+pub mod dbus;
+pub mod debug_bundle;
+pub mod echo_cancel;
 pub mod error;
+pub mod final_pass;
+pub mod flashcards;
+pub mod gallery;
+pub mod history;
+pub mod hooks;
 pub mod hotkey;
+pub mod i18n;
+pub mod icon;
+pub mod import;
 pub mod indicator;
+pub mod jack_ports;
+pub mod jobs;
+pub mod journal;
+pub mod keymap;
+pub mod keys;
+pub mod live;
+pub mod logging;
+pub mod mail;
+pub mod mode_check;
 pub mod modes;
+pub mod notify;
+pub mod orphans;
 pub mod paste;
+pub mod paste_benchmark;
+pub mod pipeline;
+pub mod plugins;
 pub mod providers;
+pub mod quick_paste;
+pub mod rag;
+pub mod redact;
+pub mod rewrite;
+pub mod router;
+pub mod sanitize;
+pub mod share;
+pub mod shutdown;
+pub mod snippets;
 pub mod state;
+pub mod status;
+pub mod structured;
+pub mod style;
+pub mod sync;
+pub mod tasks;
+pub mod titles;
 pub mod tray;
+pub mod uinput;
+pub mod update;
+pub mod window;
 
 use log::info;
-use state::AppState;
+use state::{AppState, RecordingStatus};
 use std::sync::Arc;
 use tauri::Manager;
 use tokio::sync::Mutex;
 
 /// Initialize and run the Tauri application
 pub fn run() {
+    // `whispertray status [--follow]` only reads the running instance's status
+    if let Some(follow) = cli::status_command(std::env::args().skip(1)) {
+        let ok = status::print_status(follow);
+        std::process::exit(if ok { 0 } else { 1 });
+    }
+
     // Initialize logging
-    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+    logging::init();
 
     info!("Starting WhisperTray...");
 
+    let options = cli::parse_args(std::env::args().skip(1));
+
+    // Hand actions to a running instance over its control socket instead of starting a second app
+    if let Some(action) = options.action {
+        if let Ok(response) = daemon::send(action) {
+            if let Some(error) = response.error {
+                log::error!("{}", error);
+            }
+            std::process::exit(if response.ok { 0 } else { 1 });
+        }
+    }
+    let daemon_mode = options.daemon;
+
     tauri::Builder::default()
+        // Must be registered first so a second launch exits before initializing anything
+        .plugin(tauri_plugin_single_instance::init(|app, args, _cwd| {
+            cli::handle_second_instance(app, args);
+        }))
         .plugin(tauri_plugin_clipboard_manager::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_deep_link::init())
-        .setup(|app| {
+        .setup(move |app| {
             info!("Setting up application...");
 
+            // Create webview windows unless running headless
+            if options.headless {
+                info!("Running headless, no webview windows will be created");
+            } else {
+                for window in app.config().app.windows.clone() {
+                    tauri::WebviewWindowBuilder::from_config(app.handle(), &window)?.build()?;
+                }
+            }
+            app.manage(options.clone());
+
             // Initialize application state
-            let state = Arc::new(Mutex::new(AppState::new(app.handle().clone())?));
+            let app_state = AppState::new(app.handle().clone())?;
+            let settings = app_state.settings.clone();
+            i18n::set_language(&settings.language);
+            let processing = app_state.processing.clone();
+            let jobs = app_state.jobs.clone();
+            let keys = app_state.keys.clone();
+            let state = Arc::new(Mutex::new(app_state));
 
             // Store state in app
             app.manage(state.clone());
+            app.manage(processing);
+            app.manage(jobs);
+            app.manage(database::ReaderPool::new(database::get_database_path()?));
+            app.manage(quick_paste::QuickPaste::default());
+
+            audio::set_backend(settings.audio_backend, &settings.jack_ports);
+
+            // Pick the paste/clipboard strategy for this session
+            paste::set_preferred_backend(settings.paste_backend);
+            paste::init_strategy();
 
             // Set up system tray
-            tray::setup_tray(app)?;
+            tray::setup_tray(app, &settings)?;
+
+            // Ask the keyring once, so it doesn't prompt during the first dictation
+            if settings.cache_api_keys {
+                tauri::async_runtime::spawn_blocking(move || keys.unlock());
+            }
+
+            // Publish the status on D-Bus and for bar modules
+            status::init(app, &settings.status_file);
+            dbus::start(app.handle());
+            daemon::start(app.handle());
+
+            // Without a StatusNotifier host the tray is invisible, offer a control window instead
+            if !control::tray_supported() {
+                if options.headless {
+                    log::warn!("No system tray available; use the global hotkey to record");
+                } else {
+                    log::warn!("No system tray available, opening control window");
+                    control::show_control_window(app.handle())?;
+                }
+            }
 
             // Set up global hotkey (Ctrl+Space by default)
             if let Err(e) = hotkey::setup_hotkey(app) {
@@ -59,10 +182,29 @@ pub fn run() {
                 if let Err(e) = state.load_modes().await {
                     log::error!("Failed to load modes: {}", e);
                 }
+                let preload = state.startup_preload();
                 // Update tray menu with loaded modes
                 if let Err(e) = tray::update_tray_menu(&app_handle, &state).await {
                     log::error!("Failed to update tray menu: {}", e);
                 }
+                // Sync only once the local modes are known
+                sync::start(app_handle.clone(), state_clone.clone());
+                drop(state);
+
+                // Stay yellow until the models are loaded, unless a recording started meanwhile
+                if let Some(preload) = preload {
+                    let started = std::time::Instant::now();
+                    preload.await;
+                    info!("Models preloaded in {:?}", started.elapsed());
+                }
+                let mut state = state_clone.lock().await;
+                if matches!(
+                    state.status,
+                    RecordingStatus::Loading | RecordingStatus::Ready
+                ) {
+                    state.status = RecordingStatus::Ready;
+                    let _ = tray::update_tray_icon(&app_handle, RecordingStatus::Ready);
+                }
             });
 
             // Initialize database
@@ -74,30 +216,134 @@ pub fn run() {
                 }
             });
 
+            // Look for a new release unless a package manager handles updates
+            if settings.check_for_updates
+                && !settings.local_only
+                && update::is_self_managed()
+                && update::repository().is_some()
+            {
+                let app_handle = app.handle().clone();
+                let state_clone = state.clone();
+                tauri::async_runtime::spawn(async move {
+                    match update::check().await {
+                        Ok(update) if update.update_available => {
+                            info!("Update available: {}", update.latest_version);
+                            let mut state = state_clone.lock().await;
+                            state.update = Some(update);
+                            let _ = tray::update_tray_menu(&app_handle, &state).await;
+                        }
+                        Ok(_) => {}
+                        Err(e) => log::warn!("{}", e),
+                    }
+                });
+            }
+
             info!("Application setup complete");
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             commands::start_recording,
+            commands::start_refinement,
             commands::stop_recording,
             commands::get_recording_status,
+            commands::cancel_processing,
+            commands::list_jobs,
+            commands::cancel_job,
+            commands::toggle_incognito,
+            commands::list_windows,
+            commands::get_target_window,
+            commands::set_target_window,
+            commands::target_last_focused_window,
+            commands::toggle_pause,
             commands::get_modes,
             commands::set_active_mode,
+            commands::browse_mode_gallery,
+            commands::install_mode_from_url,
+            commands::cycle_mode,
+            commands::reorder_modes,
+            commands::set_mode_visibility,
+            commands::save_mode,
+            commands::validate_mode,
+            commands::reset_mode,
+            commands::get_mode_revisions,
+            commands::restore_mode_revision,
             commands::get_active_mode,
             commands::get_input_devices,
             commands::set_input_device,
+            commands::probe_input_device,
+            commands::get_jack_ports,
             commands::transcribe_file,
+            commands::is_model_downloaded,
+            commands::download_model,
+            commands::list_stt_models,
+            commands::list_languages,
+            commands::list_ollama_models,
             commands::get_history,
             commands::get_history_item,
+            commands::get_history_segments,
+            commands::get_action_items,
+            commands::preview_tasks,
+            commands::create_tasks,
             commands::reprocess_history_item,
+            commands::reprocess_with_prompt,
+            commands::update_history_output,
+            commands::get_corrections,
+            commands::delete_correction,
             commands::delete_history_item,
+            commands::delete_history_items,
+            commands::clear_history,
+            commands::get_snippets,
+            commands::save_snippet,
+            commands::delete_snippet,
+            commands::get_rewrite_rules,
+            commands::save_rewrite_rule,
+            commands::delete_rewrite_rule,
+            commands::reorder_rewrite_rules,
+            commands::test_rewrite_rules,
+            commands::list_plugins,
+            commands::reload_plugins,
+            commands::set_plugin_enabled,
             commands::export_history_item,
+            commands::export_history_zip,
+            commands::export_flashcards,
+            commands::share_history_item,
+            commands::compose_history_email,
+            commands::post_history_item,
+            commands::create_debug_bundle,
+            commands::export_config_bundle,
+            commands::import_config_bundle,
+            commands::check_for_updates,
+            commands::import_transcript,
+            commands::merge_history_items,
+            commands::remove_duplicate_history,
+            commands::scan_orphaned_audio,
+            commands::get_history_audio,
             commands::get_settings,
             commands::update_settings,
+            commands::test_embedding_provider,
+            commands::index_history_embeddings,
             commands::save_api_key,
             commands::delete_api_key,
             commands::has_api_key,
+            commands::lock_keys,
+            commands::check_paste_backend,
+            commands::get_paste_diagnostics,
+            commands::benchmark_paste_backends,
+            commands::calibrate_input_level,
+            commands::install_uinput_rule,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(move |app, event| {
+            match event {
+                // The daemon outlives its windows; only an explicit quit ends it
+                tauri::RunEvent::ExitRequested { code, api, .. }
+                    if daemon_mode && code.is_none() =>
+                {
+                    api.prevent_exit();
+                }
+                tauri::RunEvent::Exit => shutdown::run(app),
+                _ => {}
+            }
+        });
 }
@@ -0,0 +1,278 @@
+//! Live meeting transcript
+//!
+//! While a mode with `live_transcript` records, the audio is transcribed in
+//! chunks and each finalized segment is appended to a transcript file right
+//! away. A crash or an empty battery then loses at most the last chunk
+//! instead of the whole meeting; the usual full transcription still runs
+//! when the recording stops. The file belongs to the history item of the
+//! recording and is written redacted when `redact_history` is on.
+//!
+//! With a streaming provider configured, audio is sent over WebSocket instead
+//! and interim results are emitted as live captions.
+
+use crate::audio::{RecordingHandle, WHISPER_SAMPLE_RATE};
+use crate::database::Segment;
+use crate::error::{AppError, Result};
+use crate::providers::streaming::{self, StreamConfig, StreamEvent};
+use crate::providers::stt::SttProvider;
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+/// Event emitted with interim and final streaming results
+pub const LIVE_CAPTION_EVENT: &str = "live-caption";
+
+/// Length of the audio chunks transcribed while recording
+const CHUNK_SECS: usize = 30;
+
+/// How often the recording is checked for a complete chunk
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// How often new audio is sent to a streaming provider
+const FRAME_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Payload of `LIVE_CAPTION_EVENT`
+#[derive(Debug, Clone, Serialize)]
+pub struct Caption {
+    pub text: String,
+    /// Interim captions are replaced by the next caption, final ones are kept
+    pub is_final: bool,
+}
+
+/// Directory for live transcripts used when none is configured
+pub fn default_dir() -> Result<PathBuf> {
+    let data_dir = directories::ProjectDirs::from("com", "whispertray", "WhisperTray")
+        .ok_or_else(|| AppError::Config("Could not determine data directory".to_string()))?
+        .data_dir()
+        .to_path_buf();
+
+    Ok(data_dir.join("transcripts"))
+}
+
+/// Resolve the configured transcript directory
+pub fn resolve_dir(configured: &str) -> Result<PathBuf> {
+    match configured.trim() {
+        "" => default_dir(),
+        dir => crate::journal::expand_home(dir),
+    }
+}
+
+/// Transcript file for a recording started at `started`
+pub fn transcript_path(dir: &Path, mode_key: &str, started: DateTime<Local>) -> PathBuf {
+    dir.join(format!(
+        "{}-{}.txt",
+        started.format("%Y-%m-%d_%H%M%S"),
+        mode_key
+    ))
+}
+
+/// Format milliseconds as `HH:MM:SS`
+fn format_clock(ms: u64) -> String {
+    let secs = ms / 1000;
+    format!("{:02}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+/// Transcript line for a segment, e.g. `[00:12:34] Alex: Let's start.`
+pub fn format_line(segment: &Segment) -> String {
+    let clock = format_clock(segment.start_ms);
+    let text = segment.text.trim();
+    match &segment.speaker {
+        Some(speaker) => format!("[{}] {}: {}\n", clock, speaker, text),
+        None => format!("[{}] {}\n", clock, text),
+    }
+}
+
+/// Transcribe one chunk and append its segments to the transcript file
+async fn write_chunk(
+    provider: &dyn SttProvider,
+    language: &str,
+    path: &Path,
+    chunk: &[f32],
+    offset_samples: usize,
+    redact_names: Option<&[String]>,
+) -> Result<()> {
+    let transcription = provider.transcribe_detailed(chunk, Some(language)).await?;
+    let offset_ms = crate::audio::calculate_duration_ms(offset_samples);
+
+    let mut segments = transcription.segments;
+    if segments.is_empty() && !transcription.text.trim().is_empty() {
+        // Providers without timing still get the chunk start as timestamp
+        segments.push(Segment {
+            start_ms: 0,
+            end_ms: crate::audio::calculate_duration_ms(chunk.len()),
+            text: transcription.text,
+            confidence: None,
+            speaker: None,
+        });
+    }
+
+    let lines: String = segments
+        .iter()
+        .filter(|segment| !segment.text.trim().is_empty())
+        .map(|segment| {
+            format_line(&Segment {
+                start_ms: segment.start_ms + offset_ms,
+                ..segment.clone()
+            })
+        })
+        .collect();
+
+    append_lines(path, &lines, redact_names)
+}
+
+/// Append lines to the transcript file, redacting names and PII if
+/// `redact_names` is set, and flush them to disk
+fn append_lines(path: &Path, lines: &str, redact_names: Option<&[String]>) -> Result<()> {
+    let lines = match redact_names {
+        Some(names) => crate::redact::redact(lines, names).text,
+        None => lines.to_string(),
+    };
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    file.write_all(lines.as_bytes())?;
+    file.sync_data()?;
+    Ok(())
+}
+
+/// Transcribe the recording in chunks until it stops, appending to `path`
+///
+/// The remaining audio is transcribed once the recording stops; the samples
+/// stay in the handle until the next recording starts, which cannot happen
+/// while the stopped recording is still being processed.
+pub fn spawn(
+    handle: RecordingHandle,
+    provider: Box<dyn SttProvider>,
+    language: String,
+    path: PathBuf,
+    redact_names: Option<Vec<String>>,
+) {
+    log::info!("Writing live transcript to {:?}", path);
+    tauri::async_runtime::spawn(async move {
+        let chunk_samples = CHUNK_SECS * WHISPER_SAMPLE_RATE as usize;
+        let mut offset = 0;
+
+        loop {
+            tokio::time::sleep(POLL_INTERVAL).await;
+            let recording = handle.is_recording();
+            if recording && handle.sample_count() < offset + chunk_samples {
+                continue;
+            }
+
+            let mut chunk = handle.samples_since(offset);
+            if recording {
+                chunk.truncate(chunk_samples);
+            }
+            if !chunk.is_empty() {
+                let redact_names = redact_names.as_deref();
+                let written =
+                    write_chunk(&*provider, &language, &path, &chunk, offset, redact_names).await;
+                if let Err(e) = written {
+                    log::warn!("Failed to update live transcript: {}", e);
+                }
+                offset += chunk.len();
+            }
+
+            if !recording {
+                break;
+            }
+        }
+        log::info!("Live transcript finished: {:?}", path);
+    });
+}
+
+/// Stream the recording to a WebSocket provider until it stops, appending
+/// final segments to `path` and emitting captions
+pub fn spawn_streaming(
+    app_handle: AppHandle,
+    handle: RecordingHandle,
+    config: StreamConfig,
+    path: PathBuf,
+    redact_names: Option<Vec<String>>,
+) {
+    log::info!("Streaming live transcript to {:?}", path);
+    tauri::async_runtime::spawn(async move {
+        let session = match streaming::connect(&config).await {
+            Ok(session) => session,
+            Err(e) => {
+                log::warn!("Failed to start streaming transcription: {}", e);
+                return;
+            }
+        };
+        let (audio, mut events) = (session.audio, session.events);
+
+        tauri::async_runtime::spawn(async move {
+            let mut offset = 0;
+            loop {
+                tokio::time::sleep(FRAME_INTERVAL).await;
+                let recording = handle.is_recording();
+                let samples = handle.samples_since(offset);
+                offset += samples.len();
+                if !samples.is_empty() && audio.send(samples).await.is_err() {
+                    break;
+                }
+                if !recording {
+                    break;
+                }
+            }
+        });
+
+        while let Some(event) = events.recv().await {
+            let caption = match event {
+                StreamEvent::Interim(text) => Caption {
+                    text,
+                    is_final: false,
+                },
+                StreamEvent::Final(segment) => {
+                    let line = format_line(&segment);
+                    if let Err(e) = append_lines(&path, &line, redact_names.as_deref()) {
+                        log::warn!("Failed to update live transcript: {}", e);
+                    }
+                    Caption {
+                        text: segment.text,
+                        is_final: true,
+                    }
+                }
+            };
+            let _ = app_handle.emit(LIVE_CAPTION_EVENT, caption);
+        }
+        log::info!("Live transcript finished: {:?}", path);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_format_line() {
+        let segment = Segment {
+            start_ms: 3_754_500,
+            end_ms: 3_756_000,
+            text: " Let's start. ".to_string(),
+            confidence: None,
+            speaker: None,
+        };
+        assert_eq!(format_line(&segment), "[01:02:34] Let's start.\n");
+
+        let segment = Segment {
+            speaker: Some("Alex".to_string()),
+            ..segment
+        };
+        assert_eq!(format_line(&segment), "[01:02:34] Alex: Let's start.\n");
+    }
+
+    #[test]
+    fn test_transcript_path() {
+        let started = Local.with_ymd_and_hms(2024, 5, 14, 9, 5, 7).unwrap();
+        assert_eq!(
+            transcript_path(Path::new("/t"), "meeting", started),
+            Path::new("/t/2024-05-14_090507-meeting.txt")
+        );
+    }
+}
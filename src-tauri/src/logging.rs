@@ -0,0 +1,165 @@
+//! Logging setup
+//!
+//! Logs go to stderr and to `logs/whispertray.log` in the data directory, so
+//! recent logs can be attached to bug reports. The log of the previous run is
+//! kept as `whispertray.1.log`, and a log reaching `MAX_LOG_FILE_BYTES` takes
+//! its place. Records from the `log` macros are forwarded to tracing, and
+//! closing spans are logged with their duration. Log lines never contain
+//! dictated text, only its length.
+
+use crate::error::{AppError, Result};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tracing_subscriber::fmt::format::FmtSpan;
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::EnvFilter;
+
+const LOG_FILE: &str = "whispertray.log";
+const PREVIOUS_LOG_FILE: &str = "whispertray.1.log";
+
+/// Size at which the log file is rotated
+const MAX_LOG_FILE_BYTES: u64 = 5 * 1024 * 1024;
+
+/// Directory holding the log files
+pub fn log_dir() -> Result<PathBuf> {
+    let data_dir = directories::ProjectDirs::from("com", "whispertray", "WhisperTray")
+        .ok_or_else(|| AppError::Config("Could not determine data directory".to_string()))?
+        .data_dir()
+        .to_path_buf();
+
+    Ok(data_dir.join("logs"))
+}
+
+/// Log files, current run first
+pub fn log_files() -> Vec<PathBuf> {
+    log_dir()
+        .map(|dir| vec![dir.join(LOG_FILE), dir.join(PREVIOUS_LOG_FILE)])
+        .unwrap_or_default()
+}
+
+/// Log file that is moved to the previous log once it reaches `max_bytes`
+struct RotatingFile {
+    dir: PathBuf,
+    file: File,
+    len: u64,
+    max_bytes: u64,
+}
+
+impl RotatingFile {
+    /// Keep the previous log and start a new one
+    fn open(dir: &Path, max_bytes: u64) -> Result<Self> {
+        std::fs::create_dir_all(dir)?;
+        let path = dir.join(LOG_FILE);
+        if path.exists() {
+            std::fs::rename(&path, dir.join(PREVIOUS_LOG_FILE))?;
+        }
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            file: File::create(path)?,
+            len: 0,
+            max_bytes,
+        })
+    }
+
+    fn rotate(&mut self) -> std::io::Result<()> {
+        std::fs::rename(self.dir.join(LOG_FILE), self.dir.join(PREVIOUS_LOG_FILE))?;
+        self.file = File::create(self.dir.join(LOG_FILE))?;
+        self.len = 0;
+        Ok(())
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if self.len > 0 && self.len + buf.len() as u64 > self.max_bytes {
+            self.rotate()?;
+        }
+        let written = self.file.write(buf)?;
+        self.len += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// Install the global subscriber (`RUST_LOG` overrides the default `info` level)
+pub fn init() {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+
+    let file_layer = match log_dir().and_then(|dir| RotatingFile::open(&dir, MAX_LOG_FILE_BYTES)) {
+        Ok(file) => Some(
+            tracing_subscriber::fmt::layer()
+                .with_ansi(false)
+                .with_span_events(FmtSpan::CLOSE)
+                .with_writer(Mutex::new(file)),
+        ),
+        Err(e) => {
+            eprintln!("Failed to open log file: {}", e);
+            None
+        }
+    };
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(tracing_subscriber::fmt::layer().with_span_events(FmtSpan::CLOSE))
+        .with(file_layer)
+        .init();
+}
+
+/// Last `max_bytes` of a file, starting at a line boundary
+pub fn tail(path: &Path, max_bytes: u64) -> Result<String> {
+    let mut file = File::open(path)?;
+    let len = file.metadata()?.len();
+    let start = len.saturating_sub(max_bytes);
+    file.seek(SeekFrom::Start(start))?;
+
+    let mut buffer = Vec::new();
+    file.read_to_end(&mut buffer)?;
+    let text = String::from_utf8_lossy(&buffer);
+    if start == 0 {
+        return Ok(text.into_owned());
+    }
+    // Drop the partial first line
+    Ok(match text.find('\n') {
+        Some(newline) => text[newline + 1..].to_string(),
+        None => String::new(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_tail() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("test.log");
+        std::fs::write(&path, "first line\nsecond line\nthird line\n").unwrap();
+
+        assert_eq!(
+            tail(&path, 1024).unwrap(),
+            "first line\nsecond line\nthird line\n"
+        );
+        assert_eq!(tail(&path, 15).unwrap(), "third line\n");
+    }
+
+    #[test]
+    fn test_rotating_file() {
+        let dir = tempdir().unwrap();
+        std::fs::write(dir.path().join(LOG_FILE), "last run\n").unwrap();
+
+        let mut file = RotatingFile::open(dir.path(), 16).unwrap();
+        let read = |name| std::fs::read_to_string(dir.path().join(name)).unwrap();
+        assert_eq!(read(PREVIOUS_LOG_FILE), "last run\n");
+
+        file.write_all(b"first line\n").unwrap();
+        file.write_all(b"second line\n").unwrap();
+        assert_eq!(read(PREVIOUS_LOG_FILE), "first line\n");
+        assert_eq!(read(LOG_FILE), "second line\n");
+    }
+}
@@ -0,0 +1,145 @@
+//! Mail client handoff
+//!
+//! Modes with `open_in_mail_client` open a new message in the user's mail
+//! client instead of pasting. The `Subject:` line the Email mode writes first
+//! goes into the subject field and the rest into the body. `xdg-email` picks
+//! the configured client; without it or when it fails, a `mailto:` URL is opened.
+
+use crate::error::{AppError, Result};
+use std::time::Duration;
+use tokio::process::Command;
+
+/// Subject labels the LLM may use, by language
+const SUBJECT_LABELS: [&str; 4] = ["subject", "betreff", "objet", "asunto"];
+
+/// How long to wait for `xdg-email` or `xdg-open` to report a failure
+const LAUNCH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A message to be composed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Draft {
+    pub subject: Option<String>,
+    pub body: String,
+}
+
+impl Draft {
+    /// Split an Email mode output into subject and body
+    pub fn parse(output: &str) -> Self {
+        let output = output.trim();
+        let (first, rest) = output.split_once('\n').unwrap_or((output, ""));
+        match subject(first) {
+            Some(subject) => Draft {
+                subject: Some(subject.to_string()),
+                body: rest.trim().to_string(),
+            },
+            None => Draft {
+                subject: None,
+                body: output.to_string(),
+            },
+        }
+    }
+
+    /// `mailto:` URL with the subject and body filled in
+    pub fn mailto_url(&self) -> String {
+        let mut fields = Vec::new();
+        if let Some(subject) = &self.subject {
+            fields.push(format!("subject={}", percent_encode(subject)));
+        }
+        // Line breaks in mailto bodies must be CRLF
+        let body = self.body.replace("\r\n", "\n").replace('\n', "\r\n");
+        fields.push(format!("body={}", percent_encode(&body)));
+        format!("mailto:?{}", fields.join("&"))
+    }
+}
+
+/// The subject of a `Subject: ...` line, also when set in bold or as a heading
+fn subject(line: &str) -> Option<&str> {
+    let line = line.trim().trim_start_matches('#').trim().trim_matches('*');
+    let (label, subject) = line.split_once(':')?;
+    let label = label.trim().trim_matches('*').to_lowercase();
+    if !SUBJECT_LABELS.contains(&label.as_str()) {
+        return None;
+    }
+    Some(subject.trim().trim_matches('*').trim()).filter(|subject| !subject.is_empty())
+}
+
+/// Percent-encode everything but unreserved characters (RFC 3986)
+fn percent_encode(text: &str) -> String {
+    text.bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+/// Run a launcher, failing on a non-zero exit status
+///
+/// A launcher still running after `LAUNCH_TIMEOUT` started the client in the
+/// foreground and counts as success; tokio reaps it once it exits.
+async fn launch(mut command: Command) -> std::result::Result<(), String> {
+    let mut child = command.spawn().map_err(|e| e.to_string())?;
+    match tokio::time::timeout(LAUNCH_TIMEOUT, child.wait()).await {
+        Ok(Ok(status)) if status.success() => Ok(()),
+        Ok(Ok(status)) => Err(status.to_string()),
+        Ok(Err(e)) => Err(e.to_string()),
+        Err(_) => Ok(()),
+    }
+}
+
+/// Open a new message in the mail client
+pub async fn compose(draft: &Draft) -> Result<()> {
+    let mut command = Command::new("xdg-email");
+    command.arg("--utf8");
+    if let Some(subject) = &draft.subject {
+        command.arg("--subject").arg(subject);
+    }
+    command.arg("--body").arg(&draft.body);
+    match launch(command).await {
+        Ok(()) => return Ok(()),
+        Err(e) => log::debug!("xdg-email failed ({}), opening a mailto: URL", e),
+    }
+
+    let mut command = Command::new("xdg-open");
+    command.arg(draft.mailto_url());
+    launch(command)
+        .await
+        .map_err(|e| AppError::Config(format!("Failed to open mail client: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_subject_line() {
+        let draft = Draft::parse("Subject: Budget for Q3\n\nHi Anna,\n\nthe budget is fine.");
+        assert_eq!(draft.subject.as_deref(), Some("Budget for Q3"));
+        assert_eq!(draft.body, "Hi Anna,\n\nthe budget is fine.");
+
+        let draft = Draft::parse("**Betreff:** Termin am Freitag\nHallo Jonas,");
+        assert_eq!(draft.subject.as_deref(), Some("Termin am Freitag"));
+        assert_eq!(draft.body, "Hallo Jonas,");
+    }
+
+    #[test]
+    fn test_parse_without_subject() {
+        let draft = Draft::parse("Hi Anna,\nRe: the budget is fine.");
+        assert_eq!(draft.subject, None);
+        assert_eq!(draft.body, "Hi Anna,\nRe: the budget is fine.");
+    }
+
+    #[test]
+    fn test_mailto_url() {
+        let draft = Draft {
+            subject: Some("Q3 & Q4".to_string()),
+            body: "Hi Anna,\nsee you".to_string(),
+        };
+        assert_eq!(
+            draft.mailto_url(),
+            "mailto:?subject=Q3%20%26%20Q4&body=Hi%20Anna%2C%0D%0Asee%20you"
+        );
+    }
+}
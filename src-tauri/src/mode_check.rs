@@ -0,0 +1,315 @@
+//! Mode validation
+//!
+//! Checks that a mode's providers can actually run: the whisper model is
+//! downloaded, API keys are present and servers answer with the configured
+//! model. Each problem comes with a suggested fix, so a broken mode shows up
+//! when it is saved or activated rather than when a dictation fails.
+
+use crate::modes::{LlmProvider, Mode, SttProvider};
+use crate::notify;
+use crate::providers::{llm, stt};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+/// Event emitted with the problems found after a mode was saved or activated
+pub const MODE_PROBLEMS_EVENT: &str = "mode-problems";
+
+/// Pipeline stage a problem affects
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProblemStage {
+    Stt,
+    Llm,
+}
+
+/// Something that keeps a mode from working
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ModeProblem {
+    pub stage: ProblemStage,
+    pub message: String,
+    /// What the user can do about it
+    pub fix: String,
+    /// Whether a dictation with the mode would fail
+    pub blocking: bool,
+}
+
+impl ModeProblem {
+    fn new(stage: ProblemStage, message: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            stage,
+            message: message.into(),
+            fix: fix.into(),
+            blocking: true,
+        }
+    }
+}
+
+/// Payload of `MODE_PROBLEMS_EVENT`
+#[derive(Debug, Clone, Serialize)]
+pub struct ModeProblems {
+    pub mode_key: String,
+    pub problems: Vec<ModeProblem>,
+}
+
+/// What the checks need besides the mode itself
+#[derive(Debug, Clone)]
+pub struct CheckContext {
+    pub local_only: bool,
+    pub stt_server: stt::SttServer,
+    /// Key for the mode's LLM provider, if it needs one
+    pub llm_api_key: Option<String>,
+}
+
+/// Problems that can be found without asking any server
+pub fn check_offline(mode: &Mode, ctx: &CheckContext) -> Vec<ModeProblem> {
+    let mut problems = Vec::new();
+    let stt_stage = ProblemStage::Stt;
+
+    if ctx.local_only && !mode.stt_provider.is_local() {
+        problems.push(ModeProblem::new(
+            stt_stage,
+            format!(
+                "{:?} speech recognition needs network access, but local-only mode is on",
+                mode.stt_provider
+            ),
+            "Switch the mode to whisper.cpp or turn off local-only mode",
+        ));
+    }
+    match &mode.stt_provider {
+        SttProvider::WhisperCpp if mode.stt_model.trim().is_empty() => {
+            problems.push(ModeProblem::new(
+                stt_stage,
+                "No whisper model is selected",
+                "Choose a model in the mode's settings",
+            ));
+        }
+        SttProvider::WhisperCpp if !stt::is_model_downloaded(&mode.stt_model) => {
+            problems.push(ModeProblem {
+                // Downloaded on first use unless that is blocked
+                blocking: ctx.local_only,
+                ..ModeProblem::new(
+                    stt_stage,
+                    format!("Whisper model '{}' is not downloaded", mode.stt_model),
+                    "Download it in Settings; otherwise the first dictation downloads it",
+                )
+            });
+        }
+        SttProvider::WhisperCpp => {}
+        SttProvider::OpenAI => {
+            if ctx.stt_server.base_url.trim().is_empty() && ctx.stt_server.api_key.is_none() {
+                problems.push(ModeProblem::new(
+                    stt_stage,
+                    "OpenAI API key missing",
+                    "Add your OpenAI key in Settings, or set a transcription server URL",
+                ));
+            }
+        }
+        SttProvider::Deepgram => problems.push(ModeProblem::new(
+            stt_stage,
+            "Deepgram is not supported yet",
+            "Choose another speech provider",
+        )),
+        SttProvider::Custom(name) => problems.push(ModeProblem::new(
+            stt_stage,
+            format!("Unknown speech provider: {}", name),
+            "Choose another speech provider",
+        )),
+    }
+
+    if !mode.ai_processing {
+        return problems;
+    }
+    let llm_stage = ProblemStage::Llm;
+    if ctx.local_only && !mode.llm_provider.is_local() {
+        problems.push(ModeProblem::new(
+            llm_stage,
+            format!(
+                "{:?} AI processing needs network access, but local-only mode is on",
+                mode.llm_provider
+            ),
+            "Switch the mode to Ollama or turn off local-only mode",
+        ));
+    }
+    if mode.llm_model.trim().is_empty() {
+        problems.push(ModeProblem::new(
+            llm_stage,
+            "No AI model is selected",
+            "Choose a model in the mode's settings",
+        ));
+    }
+    match &mode.llm_provider {
+        LlmProvider::OpenAI | LlmProvider::Anthropic if ctx.llm_api_key.is_none() => {
+            problems.push(ModeProblem::new(
+                llm_stage,
+                format!("{:?} API key missing", mode.llm_provider),
+                format!("Add your {:?} key in Settings", mode.llm_provider),
+            ));
+        }
+        LlmProvider::Custom(name) => problems.push(ModeProblem::new(
+            llm_stage,
+            format!("Unknown AI provider: {}", name),
+            "Choose another AI provider",
+        )),
+        _ => {}
+    }
+    problems
+}
+
+/// The first problem found offline that would make a dictation fail
+pub fn blocking_problem(mode: &Mode, ctx: &CheckContext) -> Option<ModeProblem> {
+    check_offline(mode, ctx).into_iter().find(|p| p.blocking)
+}
+
+/// Whether Ollama's model list contains `model` (a missing tag means `latest`)
+fn has_ollama_model(models: &[llm::OllamaModel], model: &str) -> bool {
+    let model = model.trim();
+    models
+        .iter()
+        .any(|m| m.name == model || m.name == format!("{}:latest", model))
+}
+
+/// All problems, asking the servers whether they answer and have the model
+///
+/// Servers are only asked when nothing is wrong with the stage offline.
+pub async fn validate(mode: &Mode, ctx: &CheckContext) -> Vec<ModeProblem> {
+    let mut problems = check_offline(mode, ctx);
+    let stage_ok = |problems: &[ModeProblem], stage| problems.iter().all(|p| p.stage != stage);
+
+    if mode.stt_provider == SttProvider::OpenAI && stage_ok(&problems, ProblemStage::Stt) {
+        let base_url = match ctx.stt_server.base_url.trim() {
+            "" => "https://api.openai.com",
+            url => url,
+        };
+        match stt::list_server_models(base_url, ctx.stt_server.api_key.as_deref()).await {
+            Ok(models) if !models.is_empty() && !models.contains(&mode.stt_model) => {
+                problems.push(ModeProblem::new(
+                    ProblemStage::Stt,
+                    format!("{} has no model '{}'", base_url, mode.stt_model),
+                    format!("Choose one of: {}", models.join(", ")),
+                ));
+            }
+            Ok(_) => {}
+            Err(e) => problems.push(ModeProblem::new(
+                ProblemStage::Stt,
+                format!("{} is not reachable: {}", base_url, e),
+                "Check the server URL and API key, and that the server is running",
+            )),
+        }
+    }
+
+    if mode.ai_processing
+        && mode.llm_provider == LlmProvider::Ollama
+        && stage_ok(&problems, ProblemStage::Llm)
+    {
+        match llm::list_ollama_models().await {
+            Ok(models) if !has_ollama_model(&models, &mode.llm_model) => {
+                problems.push(ModeProblem::new(
+                    ProblemStage::Llm,
+                    format!("Ollama model '{}' is not installed", mode.llm_model),
+                    format!("Run `ollama pull {}`", mode.llm_model.trim()),
+                ));
+            }
+            Ok(_) => {}
+            Err(e) => problems.push(ModeProblem::new(
+                ProblemStage::Llm,
+                e.to_string(),
+                "Start Ollama with `ollama serve`",
+            )),
+        }
+    }
+    problems
+}
+
+/// Validate a mode in the background, reporting problems by event and notification
+pub fn spawn(app_handle: AppHandle, mode: Mode, ctx: CheckContext) {
+    tauri::async_runtime::spawn(async move {
+        let problems = validate(&mode, &ctx).await;
+        if let Some(problem) = problems.first() {
+            log::warn!("Mode {} has {} problems", mode.key, problems.len());
+            notify::mode_problem(
+                &app_handle,
+                &crate::i18n::t_args("notify.mode_problem.title", &[("mode", &mode.name)]),
+                &format!("{}. {}", problem.message, problem.fix),
+            );
+        }
+        let _ = app_handle.emit(
+            MODE_PROBLEMS_EVENT,
+            ModeProblems {
+                mode_key: mode.key,
+                problems,
+            },
+        );
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context() -> CheckContext {
+        CheckContext {
+            local_only: false,
+            stt_server: stt::SttServer::default(),
+            llm_api_key: None,
+        }
+    }
+
+    #[test]
+    fn test_check_offline() {
+        let mode = Mode {
+            stt_provider: SttProvider::OpenAI,
+            stt_model: "whisper-1".to_string(),
+            ai_processing: true,
+            llm_provider: LlmProvider::Anthropic,
+            llm_model: "claude-3-5-haiku-latest".to_string(),
+            ..Mode::default()
+        };
+        let problems = check_offline(&mode, &context());
+        assert_eq!(problems.len(), 2);
+        assert_eq!(problems[0].stage, ProblemStage::Stt);
+        assert_eq!(problems[1].message, "Anthropic API key missing");
+
+        let ctx = CheckContext {
+            local_only: true,
+            stt_server: stt::SttServer {
+                base_url: "http://localhost:8000".to_string(),
+                ..context().stt_server
+            },
+            llm_api_key: Some("key".to_string()),
+        };
+        let problems = check_offline(&mode, &ctx);
+        assert_eq!(problems.len(), 2);
+        assert!(problems.iter().all(|p| p.message.contains("local-only")));
+    }
+
+    #[test]
+    fn test_missing_whisper_model() {
+        let mode = Mode {
+            stt_provider: SttProvider::WhisperCpp,
+            stt_model: "no-such-model".to_string(),
+            ..Mode::default()
+        };
+        let problems = check_offline(&mode, &context());
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].message.contains("not downloaded"));
+        assert!(blocking_problem(&mode, &context()).is_none());
+
+        let ctx = CheckContext {
+            local_only: true,
+            ..context()
+        };
+        assert!(blocking_problem(&mode, &ctx).is_some());
+    }
+
+    #[test]
+    fn test_has_ollama_model() {
+        let models = vec![llm::OllamaModel {
+            name: "llama3.2:latest".to_string(),
+            size: 0,
+            modified_at: String::new(),
+        }];
+        assert!(has_ollama_model(&models, "llama3.2"));
+        assert!(has_ollama_model(&models, "llama3.2:latest"));
+        assert!(!has_ollama_model(&models, "llama3.2:1b"));
+    }
+}
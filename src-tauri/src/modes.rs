@@ -2,11 +2,22 @@
 //!
 //! Modes define how transcription and AI processing behave.
 //! They are stored as JSON files in ~/.config/whispertray/modes/
+//!
+//! Files for builtin modes only hold the fields that differ from the shipped
+//! template, so template updates still reach edited modes. Replaced versions
+//! of a mode are kept in `modes/.history/<key>/` so edits can be undone.
 
+use crate::chat::ChatTarget;
 use crate::error::{AppError, Result};
+use crate::style::StyleRules;
+use crate::tasks::TaskTarget;
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+/// Revisions kept per mode
+const MAX_REVISIONS: usize = 10;
 
 /// STT provider options
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -24,6 +35,13 @@ impl Default for SttProvider {
     }
 }
 
+impl SttProvider {
+    /// Whether the provider runs on this machine
+    pub fn is_local(&self) -> bool {
+        matches!(self, SttProvider::WhisperCpp)
+    }
+}
+
 /// LLM provider options
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
@@ -40,6 +58,13 @@ impl Default for LlmProvider {
     }
 }
 
+impl LlmProvider {
+    /// Whether the provider runs on this machine
+    pub fn is_local(&self) -> bool {
+        matches!(self, LlmProvider::Ollama)
+    }
+}
+
 /// Output format options
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
 #[serde(rename_all = "lowercase")]
@@ -47,10 +72,28 @@ pub enum OutputFormat {
     #[default]
     Plain,
     Markdown,
+    /// JSON validated against the mode's `json_schema`
+    Json,
+}
+
+/// Decoding options sent to server-side whisper (Speaches, faster-whisper-server)
+///
+/// Unset options keep the server's defaults.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct DecodingOptions {
+    /// Sampling temperature (0.0-1.0); 0 is deterministic
+    pub temperature: Option<f32>,
+    /// Beam search width
+    pub beam_size: Option<u32>,
+    /// Skip silence with the server's voice activity detection
+    pub vad_filter: Option<bool>,
+    /// Words and names the decoder should favor
+    pub hotwords: Option<String>,
 }
 
 /// A dictation mode configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Mode {
     /// Unique identifier for the mode
     pub key: String,
@@ -93,6 +136,66 @@ pub struct Mode {
     /// Whether this is a built-in mode
     #[serde(default)]
     pub builtin: bool,
+
+    /// Position in mode lists (lower first, unset modes go last)
+    #[serde(default)]
+    pub sort_order: Option<i32>,
+
+    /// Pinned to the top of the tray menu
+    #[serde(default)]
+    pub favorite: bool,
+
+    /// Hidden from the tray menu and mode cycling
+    #[serde(default)]
+    pub hidden: bool,
+
+    /// Keep the recorded audio (unset follows the global setting)
+    #[serde(default)]
+    pub keep_audio: Option<bool>,
+
+    /// Expected JSON schema for `OutputFormat::Json`
+    #[serde(default)]
+    pub json_schema: Option<serde_json::Value>,
+
+    /// Candidate mode keys; if set, an LLM picks one of them after transcription
+    #[serde(default)]
+    pub route_to: Vec<String>,
+
+    /// Capture the clipboard as {{context}} even when context awareness is off
+    #[serde(default)]
+    pub clipboard_context: bool,
+
+    /// Append finalized segments to a transcript file while recording
+    #[serde(default)]
+    pub live_transcript: bool,
+
+    /// Extract action items (owner, task, due date) as JSON after saving
+    #[serde(default)]
+    pub extract_action_items: bool,
+
+    /// Create tasks from each dictation in Todoist or a CalDAV task list
+    #[serde(default)]
+    pub task_target: Option<TaskTarget>,
+
+    /// Open the output as a new message in the mail client instead of pasting it
+    #[serde(default)]
+    pub open_in_mail_client: bool,
+
+    /// Slack channels and Matrix rooms the output is posted to after pasting
+    #[serde(default)]
+    pub post_to: Vec<ChatTarget>,
+
+    /// Decoding options for OpenAI-compatible STT servers
+    #[serde(default)]
+    pub decoding: DecodingOptions,
+
+    /// Tone rules added to the prompt and enforced on the output
+    #[serde(default)]
+    pub style: StyleRules,
+
+    /// Builtin mode whose template differs from the shipped one (computed on load)
+    #[serde(default, skip_deserializing)]
+    pub customized: bool,
 }
 
 fn default_stt_model() -> String {
@@ -113,10 +216,130 @@ impl Default for Mode {
             prompt_template: String::new(),
             output_format: OutputFormat::Plain,
             builtin: true,
+            sort_order: None,
+            favorite: false,
+            hidden: false,
+            keep_audio: None,
+            json_schema: None,
+            route_to: Vec::new(),
+            clipboard_context: false,
+            live_transcript: false,
+            extract_action_items: false,
+            task_target: None,
+            open_in_mail_client: false,
+            post_to: Vec::new(),
+            decoding: DecodingOptions::default(),
+            style: StyleRules::default(),
+            customized: false,
+        }
+    }
+}
+
+impl Mode {
+    /// Whether recordings in this mode keep their audio file
+    pub fn keeps_audio(&self, default: bool) -> bool {
+        self.keep_audio.unwrap_or(default)
+    }
+
+    /// Fail if the mode needs a network provider while local-only mode is active
+    pub fn check_local_only(&self) -> Result<()> {
+        if !self.stt_provider.is_local() {
+            return Err(AppError::LocalOnly(format!(
+                "mode '{}' uses the cloud speech provider {:?}",
+                self.name, self.stt_provider
+            )));
+        }
+        if self.ai_processing && !self.llm_provider.is_local() {
+            return Err(AppError::LocalOnly(format!(
+                "mode '{}' uses the cloud AI provider {:?}",
+                self.name, self.llm_provider
+            )));
+        }
+        Ok(())
+    }
+
+    /// Check that a mode loaded from outside the app is well-formed
+    pub fn validate(&self) -> Result<()> {
+        let valid_key = !self.key.is_empty()
+            && self
+                .key
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-');
+        if !valid_key {
+            return Err(AppError::Config(format!(
+                "invalid mode key '{}': use lowercase letters, digits, '_' or '-'",
+                self.key
+            )));
+        }
+        if self.name.trim().is_empty() {
+            return Err(AppError::Config(format!("mode '{}' has no name", self.key)));
+        }
+        if self.ai_processing && !self.prompt_template.contains("{{transcript}}") {
+            return Err(AppError::Config(format!(
+                "mode '{}' prompt template must contain {{{{transcript}}}}",
+                self.key
+            )));
+        }
+        if let Some(temperature) = self.decoding.temperature {
+            if !(0.0..=1.0).contains(&temperature) {
+                return Err(AppError::Config(format!(
+                    "mode '{}' decoding temperature must be between 0 and 1",
+                    self.key
+                )));
+            }
+        }
+        if self.decoding.beam_size == Some(0) {
+            return Err(AppError::Config(format!(
+                "mode '{}' beam size must be at least 1",
+                self.key
+            )));
+        }
+        if let Some(schema) = &self.json_schema {
+            if !schema.is_object() {
+                return Err(AppError::Config(format!(
+                    "mode '{}' JSON schema must be an object",
+                    self.key
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether two modes differ only in display preferences
+    pub fn same_template(&self, other: &Mode) -> bool {
+        let template = |mode: &Mode| Mode {
+            sort_order: None,
+            favorite: false,
+            hidden: false,
+            customized: false,
+            ..mode.clone()
+        };
+        template(self) == template(other)
+    }
+
+    /// Set the builtin/customized flags from the shipped templates
+    pub fn mark_builtin(&mut self) {
+        match builtin_mode(&self.key) {
+            Some(shipped) => {
+                self.builtin = true;
+                self.customized = !self.same_template(&shipped);
+            }
+            None => {
+                self.builtin = false;
+                self.customized = false;
+            }
         }
     }
 }
 
+/// A replaced version of a mode
+#[derive(Debug, Clone, Serialize)]
+pub struct ModeRevision {
+    /// Milliseconds since the epoch when this version was replaced
+    pub id: i64,
+    pub mode: Mode,
+}
+
 /// Get the modes directory path
 pub fn get_modes_dir() -> Result<PathBuf> {
     let config_dir = directories::ProjectDirs::from("com", "whispertray", "WhisperTray")
@@ -142,6 +365,8 @@ pub fn create_builtin_modes() -> Vec<Mode> {
             prompt_template: String::new(),
             output_format: OutputFormat::Plain,
             builtin: true,
+            sort_order: Some(0),
+            ..Mode::default()
         },
         Mode {
             key: "message".to_string(),
@@ -172,6 +397,9 @@ Transcript to clean up:
 Cleaned message:"#.to_string(),
             output_format: OutputFormat::Plain,
             builtin: true,
+            sort_order: Some(1),
+            keep_audio: None,
+            ..Mode::default()
         },
         Mode {
             key: "email".to_string(),
@@ -206,6 +434,8 @@ Transcript:
 Email:"#.to_string(),
             output_format: OutputFormat::Plain,
             builtin: true,
+            sort_order: Some(2),
+            ..Mode::default()
         },
         Mode {
             key: "note".to_string(),
@@ -236,6 +466,8 @@ Transcript:
 Notes:"#.to_string(),
             output_format: OutputFormat::Markdown,
             builtin: true,
+            sort_order: Some(3),
+            ..Mode::default()
         },
         Mode {
             key: "meeting".to_string(),
@@ -269,6 +501,10 @@ Transcript:
 Meeting Summary:"#.to_string(),
             output_format: OutputFormat::Markdown,
             builtin: true,
+            sort_order: Some(4),
+            keep_audio: Some(true),
+            extract_action_items: true,
+            ..Mode::default()
         },
         Mode {
             key: "super".to_string(),
@@ -302,10 +538,147 @@ Transcript:
 Output:"#.to_string(),
             output_format: OutputFormat::Plain,
             builtin: true,
+            sort_order: Some(5),
+            ..Mode::default()
+        },
+        Mode {
+            key: "auto".to_string(),
+            name: "Auto".to_string(),
+            description: "Detects whether you dictated a message, email or note and formats it accordingly".to_string(),
+            stt_provider: SttProvider::WhisperCpp,
+            stt_model: "base.en".to_string(),
+            llm_provider: LlmProvider::Ollama,
+            llm_model: "llama3.2".to_string(),
+            builtin: true,
+            sort_order: Some(6),
+            route_to: vec!["message".to_string(), "email".to_string(), "note".to_string()],
+            ..Mode::default()
+        },
+        Mode {
+            key: "ask".to_string(),
+            name: "Ask".to_string(),
+            description: "Answers a spoken question instead of transcribing it".to_string(),
+            stt_provider: SttProvider::WhisperCpp,
+            stt_model: "base.en".to_string(),
+            ai_processing: true,
+            llm_provider: LlmProvider::Ollama,
+            llm_model: "llama3.2".to_string(),
+            prompt_template: r#"You are a helpful assistant. The user asked a question by voice; the text will be inserted where their cursor is.
+
+Instructions:
+- Answer the question directly and concisely
+- If the question refers to "this" or "the text", use the clipboard content below
+- Fix obvious transcription errors in the question before answering
+- Output only the answer, without repeating the question or adding a preamble
+- Answer in {{language}} unless the question asks for another language
+
+{{#if context}}
+Clipboard content:
+{{context}}
+{{/if}}
+
+Question:
+{{transcript}}
+
+Answer:"#.to_string(),
+            output_format: OutputFormat::Plain,
+            builtin: true,
+            sort_order: Some(7),
+            keep_audio: None,
+            clipboard_context: true,
+            ..Mode::default()
         },
     ]
 }
 
+/// The shipped template of a builtin mode
+pub fn builtin_mode(key: &str) -> Option<Mode> {
+    create_builtin_modes().into_iter().find(|m| m.key == key)
+}
+
+/// Fields of a builtin mode that differ from its shipped template
+fn override_fields(mode: &Mode, shipped: &Mode) -> Result<Map<String, Value>> {
+    let mode = Mode {
+        customized: false,
+        ..mode.clone()
+    };
+    match (serde_json::to_value(&mode)?, serde_json::to_value(shipped)?) {
+        (Value::Object(fields), Value::Object(defaults)) => Ok(fields
+            .into_iter()
+            .filter(|(name, value)| defaults.get(name) != Some(value))
+            .collect()),
+        _ => Ok(Map::new()),
+    }
+}
+
+/// Whether a mode differs from what ships (custom modes always do)
+pub fn is_customized(mode: &Mode) -> bool {
+    !matches!(serialize_mode(mode), Ok(None))
+}
+
+/// Serialize a mode for its file, or `None` if a builtin mode is unchanged
+fn serialize_mode(mode: &Mode) -> Result<Option<String>> {
+    let shipped = match builtin_mode(&mode.key) {
+        Some(shipped) => shipped,
+        None => return Ok(Some(serde_json::to_string_pretty(mode)?)),
+    };
+
+    let mut fields = override_fields(mode, &shipped)?;
+    if fields.is_empty() {
+        return Ok(None);
+    }
+    fields.insert("key".to_string(), Value::String(mode.key.clone()));
+    Ok(Some(serde_json::to_string_pretty(&Value::Object(fields))?))
+}
+
+/// Parse a mode file, applying builtin overrides onto the shipped template
+fn parse_mode(content: &str) -> Result<Mode> {
+    let value: Value = serde_json::from_str(content)?;
+    let shipped = value["key"].as_str().and_then(builtin_mode);
+
+    let mut mode: Mode = match (shipped, value) {
+        (Some(shipped), Value::Object(fields)) => {
+            let mut merged = serde_json::to_value(&shipped)?;
+            if let Value::Object(defaults) = &mut merged {
+                defaults.extend(fields);
+            }
+            serde_json::from_value(merged)?
+        }
+        (_, value) => serde_json::from_value(value)?,
+    };
+    mode.mark_builtin();
+    Ok(mode)
+}
+
+/// Whether a builtin mode file holds fields equal to the shipped template, as
+/// the full files written before overrides existed do
+fn has_shipped_fields(content: &str, mode: &Mode) -> Result<bool> {
+    let shipped = match builtin_mode(&mode.key) {
+        Some(shipped) => shipped,
+        None => return Ok(false),
+    };
+    let fields = match serde_json::from_str(content)? {
+        Value::Object(fields) => fields,
+        _ => return Ok(false),
+    };
+    // The file also holds the key
+    Ok(fields.len() > override_fields(mode, &shipped)?.len() + 1)
+}
+
+/// Rewrite a full builtin mode file as overrides, so template updates reach
+/// the fields the user never changed
+async fn migrate_builtin_file(path: &Path, content: &str, mode: &Mode) -> Result<()> {
+    if !has_shipped_fields(content, mode)? {
+        return Ok(());
+    }
+    log::info!("Migrating mode file {} to overrides", mode.key);
+    match serialize_mode(mode)? {
+        Some(content) => tokio::fs::write(path, content).await?,
+        None => tokio::fs::remove_file(path).await?,
+    }
+    Ok(())
+}
+
 /// Load all modes from the modes directory and combine with built-ins
 pub async fn load_modes() -> Result<HashMap<String, Mode>> {
     let mut modes = HashMap::new();
@@ -315,17 +688,28 @@ pub async fn load_modes() -> Result<HashMap<String, Mode>> {
         modes.insert(mode.key.clone(), mode);
     }
 
-    // Load custom modes from config directory
+    // Load custom modes and builtin overrides from config directory
     let modes_dir = get_modes_dir()?;
 
     if modes_dir.exists() {
         let mut entries = tokio::fs::read_dir(&modes_dir).await?;
         while let Some(entry) = entries.next_entry().await? {
             let path = entry.path();
-            if path.extension().map_or(false, |ext| ext == "json") {
-                match load_mode_from_file(&path).await {
-                    Ok(mode) => {
-                        log::info!("Loaded custom mode: {}", mode.key);
+            if path.extension().is_some_and(|ext| ext == "json") {
+                let loaded = match tokio::fs::read_to_string(&path).await {
+                    Ok(content) => parse_mode(&content).map(|mode| (content, mode)),
+                    Err(e) => Err(e.into()),
+                };
+                match loaded {
+                    Ok((content, mut mode)) => {
+                        // Mode files written before ordering existed keep the builtin position
+                        if mode.sort_order.is_none() {
+                            mode.sort_order = modes.get(&mode.key).and_then(|m| m.sort_order);
+                        }
+                        if let Err(e) = migrate_builtin_file(&path, &content, &mode).await {
+                            log::warn!("Failed to migrate mode file {:?}: {}", path, e);
+                        }
+                        log::info!("Loaded mode file: {}", mode.key);
                         modes.insert(mode.key.clone(), mode);
                     }
                     Err(e) => {
@@ -335,12 +719,7 @@ pub async fn load_modes() -> Result<HashMap<String, Mode>> {
             }
         }
     } else {
-        // Create modes directory and save built-in modes
         tokio::fs::create_dir_all(&modes_dir).await?;
-        for mode in create_builtin_modes() {
-            let path = modes_dir.join(format!("{}.json", mode.key));
-            save_mode_to_file(&mode, &path).await?;
-        }
     }
 
     Ok(modes)
@@ -349,23 +728,31 @@ pub async fn load_modes() -> Result<HashMap<String, Mode>> {
 /// Load a single mode from a JSON file
 pub async fn load_mode_from_file(path: &PathBuf) -> Result<Mode> {
     let content = tokio::fs::read_to_string(path).await?;
-    let mode: Mode = serde_json::from_str(&content)?;
-    Ok(mode)
+    parse_mode(&content)
 }
 
-/// Save a mode to a JSON file
-pub async fn save_mode_to_file(mode: &Mode, path: &PathBuf) -> Result<()> {
-    let content = serde_json::to_string_pretty(mode)?;
-    tokio::fs::write(path, content).await?;
-    Ok(())
-}
-
-/// Save a mode (creates or updates)
+/// Save a mode (creates or updates), keeping the replaced version as a revision
 pub async fn save_mode(mode: &Mode) -> Result<()> {
     let modes_dir = get_modes_dir()?;
     tokio::fs::create_dir_all(&modes_dir).await?;
     let path = modes_dir.join(format!("{}.json", mode.key));
-    save_mode_to_file(mode, &path).await
+
+    if path.exists() {
+        let previous = tokio::fs::read_to_string(&path).await?;
+        // Reordering or pinning alone does not create a revision
+        let changed = !parse_mode(&previous).is_ok_and(|p| p.same_template(mode));
+        if changed {
+            save_revision(&revisions_dir(&modes_dir, &mode.key), &previous).await?;
+        }
+    }
+
+    match serialize_mode(mode)? {
+        Some(content) => tokio::fs::write(path, content).await?,
+        // Unchanged builtin modes need no file
+        None if path.exists() => tokio::fs::remove_file(path).await?,
+        None => {}
+    }
+    Ok(())
 }
 
 /// Delete a custom mode
@@ -380,6 +767,148 @@ pub async fn delete_mode(key: &str) -> Result<()> {
     Ok(())
 }
 
+/// Restore the shipped template of a builtin mode, keeping its display preferences
+pub async fn reset_mode(current: &Mode) -> Result<Mode> {
+    let shipped = builtin_mode(&current.key)
+        .ok_or_else(|| AppError::Config(format!("'{}' is not a builtin mode", current.key)))?;
+    let mode = Mode {
+        sort_order: current.sort_order,
+        favorite: current.favorite,
+        hidden: current.hidden,
+        ..shipped
+    };
+    save_mode(&mode).await?;
+    Ok(mode)
+}
+
+fn revisions_dir(modes_dir: &Path, key: &str) -> PathBuf {
+    modes_dir.join(".history").join(key)
+}
+
+/// Revision ids in a history directory, newest first
+async fn revision_ids(dir: &Path) -> Result<Vec<i64>> {
+    let mut ids = Vec::new();
+    if dir.exists() {
+        let mut entries = tokio::fs::read_dir(dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if let Some(id) = path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .and_then(|s| s.parse().ok())
+            {
+                ids.push(id);
+            }
+        }
+    }
+    ids.sort_by_key(|id| std::cmp::Reverse(*id));
+    Ok(ids)
+}
+
+/// Store a replaced mode file, dropping the oldest revisions beyond the limit
+async fn save_revision(dir: &Path, content: &str) -> Result<()> {
+    tokio::fs::create_dir_all(dir).await?;
+
+    let ids = revision_ids(dir).await?;
+    let now = chrono::Utc::now().timestamp_millis();
+    let id = ids.first().map_or(now, |latest| now.max(latest + 1));
+    tokio::fs::write(dir.join(format!("{}.json", id)), content).await?;
+
+    for old in ids.iter().skip(MAX_REVISIONS - 1) {
+        tokio::fs::remove_file(dir.join(format!("{}.json", old))).await?;
+    }
+    Ok(())
+}
+
+/// Previous versions of a mode, newest first
+pub async fn list_revisions(key: &str) -> Result<Vec<ModeRevision>> {
+    let dir = revisions_dir(&get_modes_dir()?, key);
+    let mut revisions = Vec::new();
+    for id in revision_ids(&dir).await? {
+        match load_mode_from_file(&dir.join(format!("{}.json", id))).await {
+            Ok(mode) => revisions.push(ModeRevision { id, mode }),
+            Err(e) => log::warn!("Skipping unreadable revision {} of {}: {}", id, key, e),
+        }
+    }
+    Ok(revisions)
+}
+
+/// Load a previous version of a mode
+pub async fn load_revision(key: &str, id: i64) -> Result<Mode> {
+    let path = revisions_dir(&get_modes_dir()?, key).join(format!("{}.json", id));
+    if !path.exists() {
+        return Err(AppError::Config(format!(
+            "Mode '{}' has no revision {}",
+            key, id
+        )));
+    }
+    load_mode_from_file(&path).await
+}
+
+/// Sort modes for display: favorites first, then by sort order, then by name
+pub fn sort_modes(modes: &mut [&Mode]) {
+    modes.sort_by(|a, b| {
+        b.favorite
+            .cmp(&a.favorite)
+            .then_with(|| {
+                a.sort_order
+                    .unwrap_or(i32::MAX)
+                    .cmp(&b.sort_order.unwrap_or(i32::MAX))
+            })
+            .then_with(|| a.name.cmp(&b.name))
+    });
+}
+
+/// Full display order with the given keys first; the other modes follow in their previous order
+pub fn reordered_keys(modes: &HashMap<String, Mode>, keys: &[String]) -> Vec<String> {
+    let mut rest: Vec<&Mode> = modes.values().filter(|m| !keys.contains(&m.key)).collect();
+    rest.sort_by(|a, b| {
+        a.sort_order
+            .unwrap_or(i32::MAX)
+            .cmp(&b.sort_order.unwrap_or(i32::MAX))
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    keys.iter()
+        .cloned()
+        .chain(rest.into_iter().map(|m| m.key.clone()))
+        .collect()
+}
+
+/// Prompt used to apply a spoken instruction to the previous output
+const REFINE_TEMPLATE: &str = r#"You are a helpful assistant that revises text according to a spoken instruction.
+
+Instructions:
+- Apply the instruction to the text below
+- Keep everything the instruction does not ask to change
+- Output only the revised text, without preamble or explanation
+
+Instruction:
+{{instruction}}
+
+Text:
+{{text}}
+
+Revised text:"#;
+
+/// Render the refinement prompt for a previous output and a spoken instruction
+pub fn render_refine_prompt(previous: &str, instruction: &str) -> String {
+    REFINE_TEMPLATE
+        .replace("{{instruction}}", instruction.trim())
+        .replace("{{text}}", previous.trim())
+}
+
+/// Render a one-off prompt for a transcript
+///
+/// A prompt with `{{transcript}}` is used as a template, anything else as an
+/// instruction applied to the transcript.
+pub fn render_custom_prompt(prompt: &str, transcript: &str, language: &str) -> String {
+    if prompt.contains("{{transcript}}") {
+        render_prompt(prompt, transcript, None, language)
+    } else {
+        render_refine_prompt(transcript, prompt)
+    }
+}
+
 /// Render a prompt template with the given variables
 pub fn render_prompt(template: &str, transcript: &str, context: Option<&str>, language: &str) -> String {
     let mut result = template.to_string();
@@ -417,6 +946,46 @@ mod tests {
         assert!(modes.iter().any(|m| m.key == "email"));
     }
 
+    #[test]
+    fn test_validate() {
+        for mode in create_builtin_modes() {
+            assert!(mode.validate().is_ok(), "{}", mode.key);
+        }
+
+        let bad_key = Mode {
+            key: "../evil".to_string(),
+            ..Mode::default()
+        };
+        assert!(bad_key.validate().is_err());
+
+        let no_transcript = Mode {
+            key: "x".to_string(),
+            ai_processing: true,
+            prompt_template: "Summarize".to_string(),
+            ..Mode::default()
+        };
+        assert!(no_transcript.validate().is_err());
+
+        let hot = Mode {
+            decoding: DecodingOptions {
+                temperature: Some(1.5),
+                ..DecodingOptions::default()
+            },
+            ..Mode::default()
+        };
+        assert!(hot.validate().is_err());
+    }
+
+    #[test]
+    fn test_builtin_routes_exist() {
+        let modes = create_builtin_modes();
+        for mode in modes.iter().filter(|m| !m.route_to.is_empty()) {
+            for key in &mode.route_to {
+                assert!(modes.iter().any(|m| &m.key == key && m.route_to.is_empty()));
+            }
+        }
+    }
+
     #[test]
     fn test_mode_serialization() {
         let mode = Mode::default();
@@ -425,6 +994,168 @@ mod tests {
         assert_eq!(mode.key, deserialized.key);
     }
 
+    #[test]
+    fn test_builtin_overrides() {
+        let shipped = builtin_mode("email").unwrap();
+        assert_eq!(serialize_mode(&shipped).unwrap(), None);
+
+        let edited = Mode {
+            prompt_template: "Write a terse email: {{transcript}}".to_string(),
+            favorite: true,
+            ..shipped.clone()
+        };
+        let content = serialize_mode(&edited).unwrap().unwrap();
+        let fields: Value = serde_json::from_str(&content).unwrap();
+        assert_eq!(fields.as_object().unwrap().len(), 3);
+        assert!(fields.get("name").is_none());
+
+        let loaded = parse_mode(&content).unwrap();
+        assert!(loaded.builtin && loaded.customized);
+        assert_eq!(loaded.prompt_template, edited.prompt_template);
+        assert_eq!(loaded.name, shipped.name);
+
+        // Display preferences alone do not count as a customization
+        let pinned = parse_mode(r#"{"key": "email", "favorite": true}"#).unwrap();
+        assert!(pinned.favorite && !pinned.customized);
+    }
+
+    #[test]
+    fn test_full_builtin_file_needs_migration() {
+        let edited = Mode {
+            prompt_template: "Write a terse email: {{transcript}}".to_string(),
+            ..builtin_mode("email").unwrap()
+        };
+        let full = serde_json::to_string_pretty(&edited).unwrap();
+        let loaded = parse_mode(&full).unwrap();
+        assert!(has_shipped_fields(&full, &loaded).unwrap());
+
+        let content = serialize_mode(&loaded).unwrap().unwrap();
+        let migrated = parse_mode(&content).unwrap();
+        assert_eq!(migrated, loaded);
+        assert!(!has_shipped_fields(&content, &migrated).unwrap());
+
+        let custom = Mode {
+            key: "standup".to_string(),
+            builtin: false,
+            ..Mode::default()
+        };
+        let content = serialize_mode(&custom).unwrap().unwrap();
+        assert!(!has_shipped_fields(&content, &custom).unwrap());
+    }
+
+    #[test]
+    fn test_custom_mode_file() {
+        let custom = Mode {
+            key: "standup".to_string(),
+            builtin: false,
+            ..Mode::default()
+        };
+        let content = serialize_mode(&custom).unwrap().unwrap();
+        let loaded = parse_mode(&content).unwrap();
+        assert_eq!(loaded, custom);
+    }
+
+    #[test]
+    fn test_keeps_audio() {
+        let mode = Mode::default();
+        assert!(mode.keeps_audio(true));
+        assert!(!mode.keeps_audio(false));
+
+        let meeting = Mode {
+            keep_audio: Some(true),
+            ..Mode::default()
+        };
+        assert!(meeting.keeps_audio(false));
+    }
+
+    #[test]
+    fn test_check_local_only() {
+        let local = Mode {
+            ai_processing: true,
+            llm_provider: LlmProvider::Ollama,
+            ..Mode::default()
+        };
+        assert!(local.check_local_only().is_ok());
+
+        let cloud_llm = Mode {
+            llm_provider: LlmProvider::OpenAI,
+            ..local.clone()
+        };
+        assert!(cloud_llm.check_local_only().is_err());
+
+        // A cloud LLM is irrelevant when AI processing is off
+        let no_ai = Mode {
+            ai_processing: false,
+            ..cloud_llm
+        };
+        assert!(no_ai.check_local_only().is_ok());
+
+        let cloud_stt = Mode {
+            stt_provider: SttProvider::Deepgram,
+            ..Mode::default()
+        };
+        assert!(cloud_stt.check_local_only().is_err());
+    }
+
+    #[test]
+    fn test_sort_modes() {
+        let modes = create_builtin_modes();
+        let mut custom = Mode {
+            key: "custom".to_string(),
+            name: "Aardvark".to_string(),
+            builtin: false,
+            sort_order: None,
+            ..Mode::default()
+        };
+        {
+            let mut sorted: Vec<&Mode> = modes.iter().collect();
+            sorted.push(&custom);
+            sort_modes(&mut sorted);
+            assert_eq!(sorted[0].key, "voice_to_text");
+            assert_eq!(sorted.last().unwrap().key, "custom");
+        }
+
+        custom.favorite = true;
+        let mut sorted: Vec<&Mode> = modes.iter().collect();
+        sorted.push(&custom);
+        sort_modes(&mut sorted);
+        assert_eq!(sorted[0].key, "custom");
+    }
+
+    #[test]
+    fn test_reordered_keys() {
+        let mut modes: HashMap<String, Mode> = create_builtin_modes()
+            .into_iter()
+            .map(|m| (m.key.clone(), m))
+            .collect();
+        let custom = Mode {
+            key: "custom".to_string(),
+            builtin: false,
+            ..Mode::default()
+        };
+        assert_eq!(custom.sort_order, None);
+        modes.insert(custom.key.clone(), custom);
+
+        let keys = vec!["message".to_string(), "voice_to_text".to_string()];
+        let order = reordered_keys(&modes, &keys);
+        assert_eq!(order.len(), modes.len());
+        assert_eq!(&order[..2], &keys[..]);
+        assert_eq!(order.last().unwrap(), "custom");
+        let builtins: Vec<&str> = order[2..order.len() - 1]
+            .iter()
+            .map(|k| k.as_str())
+            .collect();
+        let mut expected: Vec<&Mode> = modes
+            .values()
+            .filter(|m| m.builtin && !keys.contains(&m.key))
+            .collect();
+        expected.sort_by_key(|m| m.sort_order);
+        assert_eq!(
+            builtins,
+            expected.iter().map(|m| m.key.as_str()).collect::<Vec<_>>()
+        );
+    }
+
     #[test]
     fn test_render_prompt_basic() {
         let template = "Transcript: {{transcript}}\nLanguage: {{language}}";
@@ -448,4 +1179,22 @@ mod tests {
         assert!(!result.contains("Context:"));
         assert!(result.contains("Hello"));
     }
+
+    #[test]
+    fn test_render_refine_prompt() {
+        let result = render_refine_prompt("Hi Bob, see you at 5.\n", " make it more formal ");
+        assert!(result.contains("Instruction:\nmake it more formal\n"));
+        assert!(result.contains("Text:\nHi Bob, see you at 5.\n"));
+    }
+
+    #[test]
+    fn test_render_custom_prompt() {
+        let result =
+            render_custom_prompt("Summarize in {{language}}: {{transcript}}", "notes", "de");
+        assert_eq!(result, "Summarize in de: notes");
+
+        let result = render_custom_prompt("turn into a haiku", "notes", "en");
+        assert!(result.contains("Instruction:\nturn into a haiku\n"));
+        assert!(result.contains("Text:\nnotes\n"));
+    }
 }
@@ -0,0 +1,414 @@
+//! Desktop notifications
+//!
+//! Notifications go through `notify-send` so they work with any
+//! freedesktop-compliant notification daemon without extra bindings.
+//! Action buttons use `notify-send --action --wait` (libnotify 0.7.9+), which
+//! prints the chosen action; older versions get a plain notification.
+
+use crate::i18n::{t, t_args};
+use crate::state::SharedState;
+use serde::Serialize;
+use std::process::Command;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+
+/// Event emitted when a dictation falls below the confidence threshold
+pub const LOW_CONFIDENCE_EVENT: &str = "low-confidence";
+
+/// Event asking the main window to show a history item
+pub const OPEN_HISTORY_ITEM_EVENT: &str = "open-history-item";
+
+/// Event with the progress of model downloads and file transcriptions
+pub const OPERATION_PROGRESS_EVENT: &str = "operation-progress";
+
+/// Longest output excerpt shown in a completion notification
+const BODY_CHARS: usize = 120;
+
+/// Progress notifications are refreshed in steps of this many percent
+const PROGRESS_STEP: u8 = 5;
+
+/// Longest wait for the user to pick an action, after which the buttons stop working
+const ACTION_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+/// Payload of the low-confidence event
+#[derive(Debug, Clone, Serialize)]
+pub struct LowConfidence {
+    pub id: String,
+    pub confidence: f32,
+}
+
+/// Show a desktop notification, logging if no notification service is available
+pub fn send(summary: &str, body: &str) {
+    let result = Command::new("notify-send")
+        .args([
+            "--app-name=WhisperTray",
+            "--icon=audio-input-microphone",
+            summary,
+            body,
+        ])
+        .status();
+
+    if let Err(e) = result {
+        log::warn!("Failed to send notification: {}", e);
+    }
+}
+
+/// Show a notification, replacing the one with `replace_id` if given
+///
+/// Returns the id of the notification for the next replacement, or `None`
+/// if `notify-send` is too old to print it.
+pub fn send_replacing(summary: &str, body: &str, replace_id: Option<&str>) -> Option<String> {
+    let mut args = vec![
+        "--app-name=WhisperTray".to_string(),
+        "--icon=audio-input-microphone".to_string(),
+        "--print-id".to_string(),
+    ];
+    if let Some(id) = replace_id {
+        args.push(format!("--replace-id={}", id));
+    }
+    args.push(summary.to_string());
+    args.push(body.to_string());
+
+    match Command::new("notify-send").args(&args).output() {
+        Ok(output) if output.status.success() => {
+            let id = String::from_utf8_lossy(&output.stdout).trim().to_string();
+            (!id.is_empty()).then_some(id)
+        }
+        Ok(_) => {
+            send(summary, body);
+            None
+        }
+        Err(e) => {
+            log::warn!("Failed to send notification: {}", e);
+            None
+        }
+    }
+}
+
+/// Show a notification with action buttons, calling `on_action` with the chosen action key.
+///
+/// Waits for the user in a background task for up to `ACTION_TIMEOUT`; falls
+/// back to a plain notification if the notification tool does not support actions.
+pub fn send_with_actions<F>(summary: &str, body: &str, actions: &[(&str, String)], on_action: F)
+where
+    F: FnOnce(String) + Send + 'static,
+{
+    let mut args = vec![
+        "--app-name=WhisperTray".to_string(),
+        "--icon=audio-input-microphone".to_string(),
+        "--wait".to_string(),
+    ];
+    args.extend(
+        actions
+            .iter()
+            .map(|(key, label)| format!("--action={}={}", key, label)),
+    );
+    args.push(summary.to_string());
+    args.push(body.to_string());
+
+    let (summary, body) = (summary.to_string(), body.to_string());
+    tauri::async_runtime::spawn(async move {
+        let output = tokio::process::Command::new("notify-send")
+            .args(&args)
+            .kill_on_drop(true)
+            .output();
+        match tokio::time::timeout(ACTION_TIMEOUT, output).await {
+            Ok(Ok(output)) if output.status.success() => {
+                let action = String::from_utf8_lossy(&output.stdout).trim().to_string();
+                if !action.is_empty() {
+                    on_action(action);
+                }
+            }
+            Ok(Ok(_)) => {
+                log::debug!("notify-send does not support actions, sending plain notification");
+                tauri::async_runtime::spawn_blocking(move || send(&summary, &body));
+            }
+            Ok(Err(e)) => log::warn!("Failed to send notification: {}", e),
+            Err(_) => log::debug!("No notification action chosen in time"),
+        }
+    });
+}
+
+/// A finished dictation, for the completion notification
+pub struct Completion {
+    pub history_id: String,
+    pub output: String,
+    /// Mode offered for re-running (key and display name)
+    pub rerun_mode: Option<(String, String)>,
+}
+
+/// Notify that a dictation finished, offering to copy, open or re-run it
+pub fn dictation_complete(handle: &AppHandle, completion: Completion) {
+    let mut actions = vec![
+        ("copy", t("action.copy_again").to_string()),
+        ("history", t("action.open_history").to_string()),
+    ];
+    if let Some((_, name)) = &completion.rerun_mode {
+        actions.push(("rerun", t_args("action.rerun", &[("mode", name)])));
+    }
+
+    let body = excerpt(&completion.output, BODY_CHARS);
+    let handle = handle.clone();
+    send_with_actions(t("notify.complete.title"), &body, &actions, move |action| {
+        handle_action(&handle, &action, completion)
+    });
+}
+
+fn handle_action(handle: &AppHandle, action: &str, completion: Completion) {
+    log::info!("Notification action: {}", action);
+    match action {
+        "copy" => {
+            if let Err(e) = crate::paste::copy_and_paste(&completion.output, false) {
+                log::error!("Failed to copy output: {}", e);
+            }
+        }
+        "history" => {
+            crate::tray::show_window(handle, "main");
+            let _ = handle.emit("navigate", "/history");
+            let _ = handle.emit(OPEN_HISTORY_ITEM_EVENT, &completion.history_id);
+        }
+        "rerun" => {
+            let (mode_key, mode_name) = match completion.rerun_mode {
+                Some(mode) => mode,
+                None => return,
+            };
+            let handle = handle.clone();
+            tauri::async_runtime::spawn(async move {
+                let state = match handle.try_state::<SharedState>() {
+                    Some(state) => state,
+                    None => return,
+                };
+                let result = crate::commands::reprocess(
+                    state.inner(),
+                    &handle,
+                    &completion.history_id,
+                    mode_key,
+                )
+                .await;
+                match result {
+                    Ok(output) => {
+                        let _ = crate::paste::copy_and_paste(&output, false);
+                        send(
+                            &t_args("notify.rerun.title", &[("mode", &mode_name)]),
+                            &excerpt(&output, BODY_CHARS),
+                        );
+                    }
+                    Err(e) => log::error!("Failed to re-run dictation: {}", e),
+                }
+            });
+        }
+        _ => {}
+    }
+}
+
+/// Notify that a dictation's final transcript is ready, offering to copy it
+pub fn final_transcript(output: String) {
+    let actions = [("copy", t("action.copy").to_string())];
+    let body = excerpt(&output, BODY_CHARS);
+    send_with_actions(t("notify.final.title"), &body, &actions, move |action| {
+        if action == "copy" {
+            if let Err(e) = crate::paste::copy_and_paste(&output, false) {
+                log::error!("Failed to copy output: {}", e);
+            }
+        }
+    });
+}
+
+/// Warn that a mode cannot run as configured, offering to open the settings
+pub fn mode_problem(handle: &AppHandle, title: &str, body: &str) {
+    let actions = [("settings", t("action.open_settings").to_string())];
+    let handle = handle.clone();
+    send_with_actions(title, body, &actions, move |action| {
+        if action == "settings" {
+            crate::tray::show_window(&handle, "main");
+            let _ = handle.emit("navigate", "/settings");
+        }
+    });
+}
+
+/// Start of a text for notification bodies
+pub fn excerpt(text: &str, max_chars: usize) -> String {
+    let text = text.trim();
+    if text.chars().count() <= max_chars {
+        text.to_string()
+    } else {
+        let cut: String = text.chars().take(max_chars).collect();
+        format!("{}…", cut.trim_end())
+    }
+}
+
+/// Payload of the operation progress event
+#[derive(Debug, Clone, Serialize)]
+pub struct OperationProgressUpdate {
+    pub title: String,
+    pub percent: u8,
+    pub done: bool,
+}
+
+/// Progress of a long operation, shown as a notification updated in place
+/// and in the tray tooltip
+pub struct OperationProgress {
+    handle: AppHandle,
+    title: String,
+    /// Notification texts and percentages for the task running `notify-send`
+    notifications: UnboundedSender<(String, Option<u8>)>,
+    shown: Option<u8>,
+}
+
+impl OperationProgress {
+    /// Start showing progress for an operation
+    pub fn start(handle: &AppHandle, title: String) -> Self {
+        let (notifications, updates) = unbounded_channel();
+        tauri::async_runtime::spawn(show_progress(title.clone(), updates));
+        let mut progress = Self {
+            handle: handle.clone(),
+            title,
+            notifications,
+            shown: None,
+        };
+        progress.update(0);
+        progress
+    }
+
+    /// Report progress in percent; display updates are throttled
+    pub fn update(&mut self, percent: u8) {
+        let percent = percent.min(100);
+        if !progress_step_reached(self.shown, percent) {
+            return;
+        }
+        self.shown = Some(percent);
+
+        let body = format!("{}%", percent);
+        self.notify(&body, Some(percent));
+        crate::tray::show_progress(&self.handle, &format!("{} {}", self.title, body));
+        let _ = self.handle.emit(
+            OPERATION_PROGRESS_EVENT,
+            OperationProgressUpdate {
+                title: self.title.clone(),
+                percent,
+                done: false,
+            },
+        );
+    }
+
+    /// Replace the progress notification with a final message
+    pub fn finish(&mut self, message: &str) {
+        self.notify(message, None);
+        crate::tray::clear_progress(&self.handle);
+        let _ = self.handle.emit(
+            OPERATION_PROGRESS_EVENT,
+            OperationProgressUpdate {
+                title: self.title.clone(),
+                percent: self.shown.unwrap_or(0),
+                done: true,
+            },
+        );
+    }
+
+    /// Queue the notification text; `notify-send` runs in the background so
+    /// callers in download loops and progress callbacks never wait on it
+    fn notify(&self, body: &str, percent: Option<u8>) {
+        let _ = self.notifications.send((body.to_string(), percent));
+    }
+}
+
+/// Send or replace the progress notification for each update, in order
+async fn show_progress(title: String, mut updates: UnboundedReceiver<(String, Option<u8>)>) {
+    let mut notification_id: Option<String> = None;
+    while let Some((body, percent)) = updates.recv().await {
+        let mut args = vec![
+            "--app-name=WhisperTray".to_string(),
+            "--icon=audio-input-microphone".to_string(),
+            "--print-id".to_string(),
+        ];
+        if let Some(id) = &notification_id {
+            args.push(format!("--replace-id={}", id));
+        }
+        if let Some(percent) = percent {
+            args.push(format!("--hint=int:value:{}", percent));
+        }
+        args.push(title.clone());
+        args.push(body.clone());
+
+        let output = tokio::process::Command::new("notify-send")
+            .args(&args)
+            .output()
+            .await;
+        match output {
+            Ok(output) if output.status.success() => {
+                let id = String::from_utf8_lossy(&output.stdout).trim().to_string();
+                if !id.is_empty() {
+                    notification_id = Some(id);
+                }
+            }
+            // notify-send without --print-id: fall back to separate notifications at the ends
+            Ok(_) if percent.is_none() || percent == Some(0) => {
+                let title = title.clone();
+                tauri::async_runtime::spawn_blocking(move || send(&title, &body));
+            }
+            Ok(_) => {}
+            Err(e) => log::warn!("Failed to send notification: {}", e),
+        }
+    }
+}
+
+/// Whether progress moved far enough to refresh the display
+fn progress_step_reached(shown: Option<u8>, percent: u8) -> bool {
+    match shown {
+        None => true,
+        Some(shown) => {
+            percent > shown && (percent == 100 || percent >= shown.saturating_add(PROGRESS_STEP))
+        }
+    }
+}
+
+/// Progress in percent of a download of known size
+pub fn download_percent(downloaded: u64, total: Option<u64>) -> u8 {
+    match total {
+        Some(total) if total > 0 => (downloaded.min(total) * 100 / total) as u8,
+        _ => 0,
+    }
+}
+
+/// Tell the user a dictation may be inaccurate
+pub fn warn_low_confidence(handle: &AppHandle, id: &str, confidence: f32) {
+    log::info!("Low transcription confidence: {:.2}", confidence);
+
+    let _ = handle.emit(
+        LOW_CONFIDENCE_EVENT,
+        LowConfidence {
+            id: id.to_string(),
+            confidence,
+        },
+    );
+
+    send(
+        t("notify.low_confidence.title"),
+        &t_args(
+            "notify.low_confidence.body",
+            &[("percent", &format!("{:.0}", confidence * 100.0))],
+        ),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress_step_reached() {
+        assert!(progress_step_reached(None, 0));
+        assert!(!progress_step_reached(Some(40), 43));
+        assert!(progress_step_reached(Some(40), 45));
+        assert!(progress_step_reached(Some(98), 100));
+        assert!(!progress_step_reached(Some(100), 100));
+    }
+
+    #[test]
+    fn test_download_percent() {
+        assert_eq!(download_percent(50, Some(200)), 25);
+        assert_eq!(download_percent(300, Some(200)), 100);
+        assert_eq!(download_percent(50, None), 0);
+    }
+}
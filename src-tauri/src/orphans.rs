@@ -0,0 +1,159 @@
+//! Orphaned audio files
+//!
+//! A crash between saving a recording and storing its history item leaves a
+//! WAV in the audio directory that nothing refers to, and files deleted by
+//! hand leave history items pointing at nothing. This finds both, and can
+//! delete the stray files and drop the dangling references.
+
+use crate::database::Database;
+use crate::error::Result;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+/// Files younger than this may belong to a recording still being processed
+pub const MIN_ORPHAN_AGE: Duration = Duration::from_secs(60 * 60);
+
+/// A history item whose audio file is gone
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MissingAudio {
+    pub history_id: String,
+    pub path: String,
+}
+
+/// Result of a scan
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct OrphanReport {
+    /// Files in the audio directory no history item refers to
+    pub orphaned_files: Vec<String>,
+    /// Total size of the orphaned files
+    pub orphaned_bytes: u64,
+    pub missing_audio: Vec<MissingAudio>,
+    /// Whether the orphans and dangling references were removed
+    pub cleaned: bool,
+}
+
+/// Compare the audio directory with the history
+///
+/// Files modified within `min_age` are skipped.
+pub fn scan(db: &Database, audio_dir: &Path, min_age: Duration) -> Result<OrphanReport> {
+    let references = db.get_audio_references()?;
+    let referenced: HashSet<&Path> = references
+        .iter()
+        .map(|(_, path)| Path::new(path.as_str()))
+        .collect();
+
+    let mut report = OrphanReport::default();
+    let entries = match std::fs::read_dir(audio_dir) {
+        Ok(entries) => entries.filter_map(|entry| entry.ok()).collect(),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+        Err(e) => return Err(e.into()),
+    };
+    for entry in entries {
+        let metadata = match entry.metadata() {
+            Ok(metadata) if metadata.is_file() => metadata,
+            _ => continue,
+        };
+        let path = entry.path();
+        if referenced.contains(path.as_path()) {
+            continue;
+        }
+        let age = metadata
+            .modified()
+            .ok()
+            .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+            .unwrap_or_default();
+        if age < min_age {
+            continue;
+        }
+        report.orphaned_bytes += metadata.len();
+        report
+            .orphaned_files
+            .push(path.to_string_lossy().to_string());
+    }
+    report.orphaned_files.sort();
+
+    report.missing_audio = references
+        .into_iter()
+        .filter(|(_, path)| !Path::new(path).exists())
+        .map(|(history_id, path)| MissingAudio { history_id, path })
+        .collect();
+    Ok(report)
+}
+
+/// Delete the orphaned files and drop the references to missing ones
+pub fn clean(db: &Database, report: &mut OrphanReport) -> Result<()> {
+    for path in &report.orphaned_files {
+        if let Err(e) = std::fs::remove_file(path) {
+            log::warn!("Failed to remove {}: {}", path, e);
+        }
+    }
+    for missing in &report.missing_audio {
+        db.remove_audio_reference(&missing.history_id, &missing.path)?;
+    }
+    report.cleaned = true;
+    log::info!(
+        "Removed {} orphaned audio files and {} missing audio references",
+        report.orphaned_files.len(),
+        report.missing_audio.len()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::HistoryItem;
+
+    #[test]
+    fn test_scan_and_clean() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Database::new(&dir.path().join("test.db")).unwrap();
+        let audio_dir = dir.path().join("audio");
+        std::fs::create_dir_all(&audio_dir).unwrap();
+
+        let kept = audio_dir.join("kept.wav");
+        let stray = audio_dir.join("stray.wav");
+        std::fs::write(&kept, b"RIFF").unwrap();
+        std::fs::write(&stray, b"RIFF").unwrap();
+        let gone = audio_dir.join("gone.wav");
+        for (id, path) in [("a", &kept), ("b", &gone)] {
+            db.insert_history(&HistoryItem {
+                id: id.to_string(),
+                audio_path: Some(path.to_string_lossy().to_string()),
+                ..HistoryItem::default()
+            })
+            .unwrap();
+        }
+
+        let mut report = scan(&db, &audio_dir, Duration::ZERO).unwrap();
+        assert_eq!(report.orphaned_files, [stray.to_string_lossy()]);
+        assert_eq!(report.orphaned_bytes, 4);
+        assert_eq!(
+            report.missing_audio,
+            [MissingAudio {
+                history_id: "b".to_string(),
+                path: gone.to_string_lossy().to_string(),
+            }]
+        );
+
+        // Fresh files may still be in use
+        assert!(scan(&db, &audio_dir, MIN_ORPHAN_AGE)
+            .unwrap()
+            .orphaned_files
+            .is_empty());
+
+        clean(&db, &mut report).unwrap();
+        assert!(!stray.exists());
+        assert!(kept.exists());
+        assert!(db
+            .get_history_item("b")
+            .unwrap()
+            .unwrap()
+            .audio_path
+            .is_none());
+        let rescan = scan(&db, &audio_dir, Duration::ZERO).unwrap();
+        assert!(rescan.orphaned_files.is_empty() && rescan.missing_audio.is_empty());
+    }
+}
@@ -4,15 +4,25 @@
 //! - X11: enigo (libxdo)
 //! - Wayland: wtype or ydotool
 //! - Fallback: clipboard only
+//!
+//! The backend is chosen once at startup from the session type, compositor
+//! and installed tools, and can be re-detected from the settings UI. A backend
+//! picked by the paste benchmark takes precedence while its tool is installed.
 
 use crate::error::{AppError, Result};
+use crate::keymap::{self, KeySegment};
+use crate::window::FocusedWindow;
 use arboard::Clipboard;
-use std::process::Command;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
 use std::thread;
 use std::time::Duration;
 
 /// Paste backend detection result
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum PasteBackend {
     /// X11 with enigo/libxdo
     Enigo,
@@ -24,27 +34,302 @@ pub enum PasteBackend {
     ClipboardOnly,
 }
 
-/// Detect the best available paste backend
-pub fn detect_backend() -> PasteBackend {
-    if is_wayland() {
-        // On Wayland, try wtype first, then ydotool
-        if is_command_available("wtype") {
-            log::info!("Paste backend: wtype (Wayland)");
-            PasteBackend::Wtype
-        } else if is_command_available("ydotool") {
-            log::info!("Paste backend: ydotool (Wayland)");
-            PasteBackend::Ydotool
-        } else {
-            log::warn!("No Wayland paste backend available. Install wtype or ydotool for auto-paste.");
-            PasteBackend::ClipboardOnly
+/// Key combination that triggers a paste in the target application
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PasteChord {
+    #[default]
+    CtrlV,
+    /// Terminals and some Electron apps
+    CtrlShiftV,
+    /// X11 primary-style paste understood by most toolkits
+    ShiftInsert,
+}
+
+impl PasteChord {
+    /// Modifier names held during the key press (wtype/ydotool naming)
+    fn modifiers(&self) -> &'static [&'static str] {
+        match self {
+            PasteChord::CtrlV => &["ctrl"],
+            PasteChord::CtrlShiftV => &["ctrl", "shift"],
+            PasteChord::ShiftInsert => &["shift"],
         }
-    } else {
+    }
+
+    /// Key pressed with the modifiers
+    fn key(&self) -> &'static str {
+        match self {
+            PasteChord::CtrlV | PasteChord::CtrlShiftV => "v",
+            PasteChord::ShiftInsert => "Insert",
+        }
+    }
+}
+
+/// How to paste into the focused application
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PasteConfig {
+    pub chord: PasteChord,
+    /// Wait before pasting, so focus settles and the hotkey is released
+    pub delay_ms: u64,
+}
+
+impl Default for PasteConfig {
+    fn default() -> Self {
+        Self {
+            chord: PasteChord::CtrlV,
+            delay_ms: 200,
+        }
+    }
+}
+
+/// Paste settings for applications matching a window class
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PasteOverride {
+    /// Case-insensitive substring of the window class (e.g. "kitty", "slack")
+    pub window_class: String,
+    #[serde(default)]
+    pub chord: Option<PasteChord>,
+    #[serde(default)]
+    pub delay_ms: Option<u64>,
+}
+
+/// Apply the first override matching the focused window to the base config
+pub fn resolve_paste_config(
+    base: PasteConfig,
+    overrides: &[PasteOverride],
+    window: Option<&FocusedWindow>,
+) -> PasteConfig {
+    let matching = window.and_then(|w| overrides.iter().find(|o| w.class_matches(&o.window_class)));
+
+    match matching {
+        Some(o) => PasteConfig {
+            chord: o.chord.unwrap_or(base.chord),
+            delay_ms: o.delay_ms.unwrap_or(base.delay_ms),
+        },
+        None => base,
+    }
+}
+
+/// How text is put on the clipboard
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClipboardBackend {
+    /// arboard (X11 selection, also reachable through XWayland)
+    Arboard,
+    /// wl-copy from wl-clipboard (native Wayland)
+    WlCopy,
+}
+
+/// Paste-related tools found in PATH
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct PasteTools {
+    pub xdotool: bool,
+    pub wtype: bool,
+    pub ydotool: bool,
+    pub wl_copy: bool,
+}
+
+impl PasteTools {
+    fn detect() -> Self {
+        Self {
+            xdotool: is_command_available("xdotool"),
+            wtype: is_command_available("wtype"),
+            ydotool: is_command_available("ydotool"),
+            wl_copy: is_command_available("wl-copy"),
+        }
+    }
+}
+
+/// Detected environment and the paste strategy chosen for it
+#[derive(Debug, Clone, Serialize)]
+pub struct PasteDiagnostics {
+    /// "wayland" or "x11"
+    pub session_type: String,
+    /// Desktop/compositor name (e.g. "GNOME", "sway"), empty if unknown
+    pub compositor: String,
+    pub tools: PasteTools,
+    pub backend: PasteBackend,
+    pub clipboard: ClipboardBackend,
+    /// Explanations of limitations and how to enable auto-paste
+    pub notes: Vec<String>,
+}
+
+/// Strategy detected at startup (refreshed by `detect_diagnostics`)
+static STRATEGY: Mutex<Option<(PasteBackend, ClipboardBackend)>> = Mutex::new(None);
+
+/// Backend chosen by the paste benchmark, used instead of the detected one
+static PREFERRED: Mutex<Option<PasteBackend>> = Mutex::new(None);
+
+/// Detect the compositor or desktop environment
+fn detect_compositor() -> String {
+    if std::env::var("SWAYSOCK").is_ok() {
+        return "sway".to_string();
+    }
+    if std::env::var("HYPRLAND_INSTANCE_SIGNATURE").is_ok() {
+        return "Hyprland".to_string();
+    }
+    std::env::var("XDG_CURRENT_DESKTOP")
+        .or_else(|_| std::env::var("DESKTOP_SESSION"))
+        .unwrap_or_default()
+}
+
+/// Pick the paste backend for an environment
+fn choose_backend(wayland: bool, compositor: &str, tools: &PasteTools) -> PasteBackend {
+    if !wayland {
         // On X11, use enigo (libxdo)
-        log::info!("Paste backend: enigo (X11)");
-        PasteBackend::Enigo
+        return PasteBackend::Enigo;
+    }
+
+    // Mutter lacks the virtual keyboard protocol wtype relies on
+    let wtype_supported = !compositor.to_lowercase().contains("gnome");
+
+    if tools.wtype && wtype_supported {
+        PasteBackend::Wtype
+    } else if tools.ydotool {
+        PasteBackend::Ydotool
+    } else {
+        PasteBackend::ClipboardOnly
+    }
+}
+
+/// Whether `backend` can run in an environment (`x11` includes XWayland)
+fn backend_available(backend: PasteBackend, wayland: bool, x11: bool, tools: &PasteTools) -> bool {
+    match backend {
+        PasteBackend::Enigo => x11,
+        PasteBackend::Wtype => wayland && tools.wtype,
+        PasteBackend::Ydotool => tools.ydotool,
+        PasteBackend::ClipboardOnly => true,
+    }
+}
+
+/// Backends that can simulate input in an environment
+fn available_backends(wayland: bool, x11: bool, tools: &PasteTools) -> Vec<PasteBackend> {
+    [
+        PasteBackend::Enigo,
+        PasteBackend::Wtype,
+        PasteBackend::Ydotool,
+    ]
+    .into_iter()
+    .filter(|backend| backend_available(*backend, wayland, x11, tools))
+    .collect()
+}
+
+/// Backends worth benchmarking in the current session
+pub fn candidate_backends() -> Vec<PasteBackend> {
+    let x11 = std::env::var_os("DISPLAY").is_some();
+    available_backends(is_wayland(), x11, &PasteTools::detect())
+}
+
+/// Use `backend` instead of the detected one (`None` restores detection)
+pub fn set_preferred_backend(backend: Option<PasteBackend>) {
+    if let Ok(mut preferred) = PREFERRED.lock() {
+        *preferred = backend;
+    }
+    // Re-detect on next use
+    if let Ok(mut strategy) = STRATEGY.lock() {
+        *strategy = None;
+    }
+}
+
+/// Pick the clipboard backend for an environment
+fn choose_clipboard(wayland: bool, tools: &PasteTools) -> ClipboardBackend {
+    if wayland && tools.wl_copy {
+        ClipboardBackend::WlCopy
+    } else {
+        ClipboardBackend::Arboard
+    }
+}
+
+/// Detect the environment, choose a strategy and remember it
+pub fn detect_diagnostics() -> PasteDiagnostics {
+    let wayland = is_wayland();
+    let compositor = detect_compositor();
+    let tools = PasteTools::detect();
+    let x11 = std::env::var_os("DISPLAY").is_some();
+    let preferred = PREFERRED.lock().ok().and_then(|p| *p);
+    let mut notes = Vec::new();
+    let backend = match preferred {
+        Some(backend) if backend_available(backend, wayland, x11, &tools) => backend,
+        Some(backend) => {
+            notes.push(format!(
+                "{:?} was chosen by the paste benchmark but is no longer available.",
+                backend
+            ));
+            choose_backend(wayland, &compositor, &tools)
+        }
+        None => choose_backend(wayland, &compositor, &tools),
+    };
+    let clipboard = choose_clipboard(wayland, &tools);
+
+    if wayland && compositor.to_lowercase().contains("gnome") && tools.wtype {
+        notes.push(
+            "GNOME does not support wtype; ydotool is used for auto-paste instead.".to_string(),
+        );
+    }
+    match backend {
+        PasteBackend::ClipboardOnly => notes.push(
+            "No paste backend available. Install wtype or ydotool for auto-paste; text is copied to the clipboard."
+                .to_string(),
+        ),
+        PasteBackend::Ydotool => {
+            let check = crate::uinput::check_paste_backend();
+            notes.extend(check.checks.into_iter().filter(|c| !c.ok).map(|c| {
+                format!("{}: {}", c.detail, c.fix.unwrap_or_default())
+            }));
+        }
+        PasteBackend::Enigo | PasteBackend::Wtype => {}
+    }
+    if wayland && !tools.wl_copy {
+        notes.push("wl-copy not found; the clipboard is set through XWayland.".to_string());
+    }
+
+    if let Ok(mut strategy) = STRATEGY.lock() {
+        *strategy = Some((backend, clipboard));
+    }
+
+    PasteDiagnostics {
+        session_type: if wayland { "wayland" } else { "x11" }.to_string(),
+        compositor,
+        tools,
+        backend,
+        clipboard,
+        notes,
     }
 }
 
+/// Detect and log the paste strategy (called at startup)
+pub fn init_strategy() {
+    let diagnostics = detect_diagnostics();
+    log::info!(
+        "Paste strategy: {:?} with {:?} clipboard ({} session, compositor: {})",
+        diagnostics.backend,
+        diagnostics.clipboard,
+        diagnostics.session_type,
+        if diagnostics.compositor.is_empty() {
+            "unknown"
+        } else {
+            &diagnostics.compositor
+        }
+    );
+    for note in &diagnostics.notes {
+        log::warn!("{}", note);
+    }
+}
+
+/// Current paste and clipboard strategy, detecting it on first use
+fn strategy() -> (PasteBackend, ClipboardBackend) {
+    let cached = STRATEGY.lock().ok().and_then(|s| *s);
+    cached.unwrap_or_else(|| {
+        let diagnostics = detect_diagnostics();
+        (diagnostics.backend, diagnostics.clipboard)
+    })
+}
+
+/// Detect the best available paste backend
+pub fn detect_backend() -> PasteBackend {
+    strategy().0
+}
+
 /// Check if a command is available in PATH
 fn is_command_available(cmd: &str) -> bool {
     Command::new("which")
@@ -56,48 +341,105 @@ fn is_command_available(cmd: &str) -> bool {
 
 /// Copy text to clipboard and optionally paste/type it
 pub fn copy_and_paste(text: &str, should_paste: bool) -> Result<()> {
-    // Copy to clipboard first (always useful as backup)
-    let mut clipboard = Clipboard::new()
-        .map_err(|e| AppError::Clipboard(format!("Failed to access clipboard: {}", e)))?;
+    let config = PasteConfig::default();
+    copy_and_paste_rich(text, None, should_paste.then_some(&config))
+}
 
-    clipboard
-        .set_text(text)
-        .map_err(|e| AppError::Clipboard(format!("Failed to set clipboard text: {}", e)))?;
+/// Copy text (plus an optional HTML alternative) to clipboard and paste it if `paste` is set
+///
+/// With HTML present, rich editors such as email clients and word processors
+/// receive the formatted version while plain-text targets get `text`.
+pub fn copy_and_paste_rich(
+    text: &str,
+    html: Option<&str>,
+    paste: Option<&PasteConfig>,
+) -> Result<()> {
+    // Copy to clipboard first (always useful as backup)
+    // wl-copy only offers a single MIME type, so rich content goes through arboard
+    if html.is_none() && strategy().1 == ClipboardBackend::WlCopy {
+        set_clipboard_wl_copy(text)?;
+    } else {
+        let mut clipboard = Clipboard::new()
+            .map_err(|e| AppError::Clipboard(format!("Failed to access clipboard: {}", e)))?;
+
+        match html {
+            Some(html) => clipboard
+                .set_html(html, Some(text))
+                .map_err(|e| AppError::Clipboard(format!("Failed to set clipboard HTML: {}", e)))?,
+            None => clipboard
+                .set_text(text)
+                .map_err(|e| AppError::Clipboard(format!("Failed to set clipboard text: {}", e)))?,
+        }
+    }
 
-    log::info!("Text copied to clipboard ({} chars)", text.len());
+    log::info!(
+        "Text copied to clipboard ({} chars{})",
+        text.len(),
+        if html.is_some() { ", with HTML" } else { "" }
+    );
 
-    if should_paste {
+    if let Some(config) = paste {
         // On Wayland, prefer typing directly over Ctrl+V simulation
-        // as it's more reliable across different compositors
-        if is_wayland() {
+        // as it's more reliable across different compositors.
+        // Typing would drop the formatting, so rich output is always pasted.
+        if is_wayland() && html.is_none() {
             log::info!("Wayland detected, typing text directly");
-            if let Err(e) = type_text(text) {
+            if let Err(e) = type_text_after(text, config.delay_ms) {
                 log::warn!("Direct typing failed ({}), trying paste fallback", e);
-                paste()?;
+                paste_with(config)?;
             }
         } else {
-            paste()?;
+            paste_with(config)?;
         }
     }
 
     Ok(())
 }
 
+/// Set the clipboard with wl-copy (Wayland)
+fn set_clipboard_wl_copy(text: &str) -> Result<()> {
+    let mut child = Command::new("wl-copy")
+        .stdin(Stdio::piped())
+        .spawn()
+        .map_err(|e| AppError::Clipboard(format!("Failed to run wl-copy: {}", e)))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(text.as_bytes())
+            .map_err(|e| AppError::Clipboard(format!("Failed to write to wl-copy: {}", e)))?;
+    }
+
+    let status = child
+        .wait()
+        .map_err(|e| AppError::Clipboard(format!("Failed to wait for wl-copy: {}", e)))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(AppError::Clipboard("wl-copy failed".to_string()))
+    }
+}
+
 /// Simulate Ctrl+V paste using the best available backend
 pub fn paste() -> Result<()> {
+    paste_with(&PasteConfig::default())
+}
+
+/// Simulate the configured paste chord using the best available backend
+pub fn paste_with(config: &PasteConfig) -> Result<()> {
     let backend = detect_backend();
+    let chord = config.chord;
 
     // Delay to ensure clipboard is ready and user has released hotkey
-    thread::sleep(Duration::from_millis(200));
+    thread::sleep(Duration::from_millis(config.delay_ms));
 
     match backend {
-        PasteBackend::Enigo => paste_enigo(),
+        PasteBackend::Enigo => paste_enigo(chord),
         PasteBackend::Wtype => {
             // Try wtype, fall back to ydotool if it fails (compositor may not support virtual keyboard)
-            if let Err(e) = paste_wtype() {
+            if let Err(e) = paste_wtype(chord) {
                 log::warn!("wtype failed ({}), trying ydotool fallback", e);
                 if is_command_available("ydotool") {
-                    paste_ydotool()
+                    paste_ydotool(chord)
                 } else {
                     log::warn!("No fallback available, text is in clipboard");
                     Ok(())
@@ -106,7 +448,7 @@ pub fn paste() -> Result<()> {
                 Ok(())
             }
         }
-        PasteBackend::Ydotool => paste_ydotool(),
+        PasteBackend::Ydotool => paste_ydotool(chord),
         PasteBackend::ClipboardOnly => {
             log::info!("No paste backend available, text is in clipboard");
             Ok(())
@@ -115,38 +457,66 @@ pub fn paste() -> Result<()> {
 }
 
 /// Paste using enigo (X11/libxdo)
-fn paste_enigo() -> Result<()> {
-    use enigo::{Enigo, Keyboard, Settings};
+fn paste_enigo(chord: PasteChord) -> Result<()> {
+    use enigo::{Enigo, Key, Keyboard, Settings};
 
     let mut enigo = Enigo::new(&Settings::default())
         .map_err(|e| AppError::Clipboard(format!("Failed to create input simulator: {}", e)))?;
 
-    // Simulate Ctrl+V
-    enigo
-        .key(enigo::Key::Control, enigo::Direction::Press)
-        .map_err(|e| AppError::Clipboard(format!("Failed to press Ctrl: {}", e)))?;
+    let (modifiers, key): (&[Key], Key) = match chord {
+        PasteChord::CtrlV => (&[Key::Control], Key::Unicode('v')),
+        PasteChord::CtrlShiftV => (&[Key::Control, Key::Shift], Key::Unicode('v')),
+        PasteChord::ShiftInsert => (&[Key::Shift], Key::Insert),
+    };
+
+    for modifier in modifiers {
+        enigo
+            .key(*modifier, enigo::Direction::Press)
+            .map_err(|e| AppError::Clipboard(format!("Failed to press {:?}: {}", modifier, e)))?;
+    }
 
     thread::sleep(Duration::from_millis(20));
 
     enigo
-        .key(enigo::Key::Unicode('v'), enigo::Direction::Click)
-        .map_err(|e| AppError::Clipboard(format!("Failed to press V: {}", e)))?;
+        .key(key, enigo::Direction::Click)
+        .map_err(|e| AppError::Clipboard(format!("Failed to press {:?}: {}", key, e)))?;
 
     thread::sleep(Duration::from_millis(20));
 
-    enigo
-        .key(enigo::Key::Control, enigo::Direction::Release)
-        .map_err(|e| AppError::Clipboard(format!("Failed to release Ctrl: {}", e)))?;
+    for modifier in modifiers.iter().rev() {
+        enigo
+            .key(*modifier, enigo::Direction::Release)
+            .map_err(|e| AppError::Clipboard(format!("Failed to release {:?}: {}", modifier, e)))?;
+    }
 
     log::info!("Paste completed (enigo/X11)");
     Ok(())
 }
 
+/// Arguments for `wtype` to press a chord, e.g. `-M ctrl -k v -m ctrl`
+fn wtype_chord_args(chord: PasteChord) -> Vec<&'static str> {
+    let mut args = Vec::new();
+    for modifier in chord.modifiers() {
+        args.extend(["-M", *modifier]);
+    }
+    args.extend(["-k", chord.key()]);
+    for modifier in chord.modifiers().iter().rev() {
+        args.extend(["-m", *modifier]);
+    }
+    args
+}
+
+/// Key combination for `ydotool key`, e.g. `ctrl+shift+v`
+fn ydotool_chord(chord: PasteChord) -> String {
+    let mut keys: Vec<String> = chord.modifiers().iter().map(|m| m.to_string()).collect();
+    keys.push(chord.key().to_lowercase());
+    keys.join("+")
+}
+
 /// Paste using wtype (Wayland)
-fn paste_wtype() -> Result<()> {
-    // wtype -M ctrl -k v -m ctrl
+fn paste_wtype(chord: PasteChord) -> Result<()> {
     let output = Command::new("wtype")
-        .args(["-M", "ctrl", "-k", "v", "-m", "ctrl"])
+        .args(wtype_chord_args(chord))
         .output()
         .map_err(|e| AppError::Clipboard(format!("Failed to run wtype: {}", e)))?;
 
@@ -163,10 +533,10 @@ fn paste_wtype() -> Result<()> {
 }
 
 /// Paste using ydotool (works on both X11 and Wayland)
-fn paste_ydotool() -> Result<()> {
+fn paste_ydotool(chord: PasteChord) -> Result<()> {
     // Use ydotool key with key names (works with newer versions)
     let output = Command::new("ydotool")
-        .args(["key", "ctrl+v"])
+        .args(["key", &ydotool_chord(chord)])
         .output()
         .map_err(|e| AppError::Clipboard(format!("Failed to run ydotool: {}", e)))?;
 
@@ -182,10 +552,42 @@ fn paste_ydotool() -> Result<()> {
     }
 }
 
+/// Insert text through a specific backend, the way `copy_and_paste` would
+///
+/// Used by the paste benchmark: Wayland sessions type the text, X11 sessions
+/// copy it and press Ctrl+V. There is no fallback to other backends.
+pub fn insert_with_backend(backend: PasteBackend, text: &str) -> Result<()> {
+    if is_wayland() {
+        match backend {
+            PasteBackend::Enigo => type_text_enigo(text),
+            PasteBackend::Wtype => type_text_wtype(text),
+            PasteBackend::Ydotool => type_text_ydotool(text),
+            PasteBackend::ClipboardOnly => Err(AppError::Clipboard(
+                "No typing backend available".to_string(),
+            )),
+        }
+    } else {
+        copy_and_paste(text, false)?;
+        match backend {
+            PasteBackend::Enigo => paste_enigo(PasteChord::CtrlV),
+            PasteBackend::Wtype => paste_wtype(PasteChord::CtrlV),
+            PasteBackend::Ydotool => paste_ydotool(PasteChord::CtrlV),
+            PasteBackend::ClipboardOnly => Err(AppError::Clipboard(
+                "No paste backend available".to_string(),
+            )),
+        }
+    }
+}
+
 /// Type text directly (alternative to paste for some applications)
 pub fn type_text(text: &str) -> Result<()> {
+    type_text_after(text, PasteConfig::default().delay_ms)
+}
+
+/// Type text directly after waiting `delay_ms`
+fn type_text_after(text: &str, delay_ms: u64) -> Result<()> {
     // Delay to ensure user has released hotkey and focus is correct
-    thread::sleep(Duration::from_millis(200));
+    thread::sleep(Duration::from_millis(delay_ms));
 
     let backend = detect_backend();
 
@@ -213,6 +615,9 @@ pub fn type_text(text: &str) -> Result<()> {
 }
 
 /// Type text using enigo
+///
+/// libxdo resolves each character through the active X keymap and remaps a
+/// spare keycode for characters the layout lacks, so no translation is needed.
 fn type_text_enigo(text: &str) -> Result<()> {
     use enigo::{Enigo, Keyboard, Settings};
 
@@ -248,26 +653,77 @@ fn type_text_wtype(text: &str) -> Result<()> {
 }
 
 /// Type text using ydotool
+///
+/// ydotool emits US keycodes, so the text is first mapped through the active
+/// keyboard layout; characters without a key fall back to Unicode input.
 fn type_text_ydotool(text: &str) -> Result<()> {
+    let layout = keymap::detect_layout();
+    let segments = keymap::plan_keystrokes(text, &layout).ok_or_else(|| {
+        AppError::Clipboard(format!(
+            "Keyboard layout '{}' is not supported for direct typing",
+            layout
+        ))
+    })?;
+
+    for segment in segments {
+        match segment {
+            KeySegment::Keys(keys) => run_ydotool_type(&keys)?,
+            KeySegment::Unicode(c) => {
+                // GTK/IBus Unicode entry: Ctrl+Shift+U, hex code, Space
+                run_ydotool(&["key", "ctrl+shift+u"])?;
+                run_ydotool_type(&keymap::unicode_hex_keys(c, &layout))?;
+                run_ydotool(&["key", "space"])?;
+            }
+        }
+    }
+
+    log::info!(
+        "Text typed ({} chars) via ydotool (layout: {})",
+        text.len(),
+        layout
+    );
+    Ok(())
+}
+
+/// Type already layout-mapped keys with ydotool
+fn run_ydotool_type(keys: &str) -> Result<()> {
     // Use --delay 0 to start immediately (we handle delay ourselves)
     // Use --key-delay for reasonable typing speed
+    run_ydotool(&["type", "--delay", "50", "--key-delay", "0", "--", keys])
+}
+
+/// Run a ydotool subcommand
+fn run_ydotool(args: &[&str]) -> Result<()> {
     let output = Command::new("ydotool")
-        .args(["type", "--delay", "50", "--key-delay", "0", "--", text])
+        .args(args)
         .output()
         .map_err(|e| AppError::Clipboard(format!("Failed to run ydotool: {}", e)))?;
 
     if output.status.success() {
-        log::info!("Text typed ({} chars) via ydotool", text.len());
         Ok(())
     } else {
         let stderr = String::from_utf8_lossy(&output.stderr);
         Err(AppError::Clipboard(format!(
-            "ydotool type failed: {}",
+            "ydotool {} failed: {}",
+            args.first().copied().unwrap_or_default(),
             stderr.trim()
         )))
     }
 }
 
+/// Render Markdown output to HTML for rich clipboard content
+pub fn markdown_to_html(markdown: &str) -> String {
+    use pulldown_cmark::{html, Options, Parser};
+
+    let parser = Parser::new_ext(
+        markdown,
+        Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH,
+    );
+    let mut output = String::new();
+    html::push_html(&mut output, parser);
+    output
+}
+
 /// Get text from clipboard
 pub fn get_clipboard_text() -> Result<String> {
     let mut clipboard = Clipboard::new()
@@ -353,6 +809,119 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_choose_backend() {
+        let all = PasteTools {
+            xdotool: true,
+            wtype: true,
+            ydotool: true,
+            wl_copy: true,
+        };
+        assert_eq!(choose_backend(false, "KDE", &all), PasteBackend::Enigo);
+        assert_eq!(choose_backend(true, "sway", &all), PasteBackend::Wtype);
+        assert_eq!(
+            choose_backend(true, "ubuntu:GNOME", &all),
+            PasteBackend::Ydotool
+        );
+
+        let wtype_only = PasteTools {
+            wtype: true,
+            ..PasteTools::default()
+        };
+        assert_eq!(
+            choose_backend(true, "GNOME", &wtype_only),
+            PasteBackend::ClipboardOnly
+        );
+        assert_eq!(
+            choose_backend(true, "", &PasteTools::default()),
+            PasteBackend::ClipboardOnly
+        );
+    }
+
+    #[test]
+    fn test_choose_clipboard() {
+        let tools = PasteTools {
+            wl_copy: true,
+            ..PasteTools::default()
+        };
+        assert_eq!(choose_clipboard(true, &tools), ClipboardBackend::WlCopy);
+        assert_eq!(choose_clipboard(false, &tools), ClipboardBackend::Arboard);
+        assert_eq!(
+            choose_clipboard(true, &PasteTools::default()),
+            ClipboardBackend::Arboard
+        );
+    }
+
+    #[test]
+    fn test_available_backends() {
+        let all = PasteTools {
+            xdotool: true,
+            wtype: true,
+            ydotool: true,
+            wl_copy: true,
+        };
+        assert_eq!(
+            available_backends(true, true, &all),
+            vec![
+                PasteBackend::Enigo,
+                PasteBackend::Wtype,
+                PasteBackend::Ydotool
+            ]
+        );
+        assert_eq!(
+            available_backends(false, true, &all),
+            vec![PasteBackend::Enigo, PasteBackend::Ydotool]
+        );
+        assert!(available_backends(true, false, &PasteTools::default()).is_empty());
+    }
+
+    #[test]
+    fn test_chord_args() {
+        assert_eq!(
+            wtype_chord_args(PasteChord::CtrlV),
+            vec!["-M", "ctrl", "-k", "v", "-m", "ctrl"]
+        );
+        assert_eq!(
+            wtype_chord_args(PasteChord::CtrlShiftV),
+            vec!["-M", "ctrl", "-M", "shift", "-k", "v", "-m", "shift", "-m", "ctrl"]
+        );
+        assert_eq!(ydotool_chord(PasteChord::CtrlShiftV), "ctrl+shift+v");
+        assert_eq!(ydotool_chord(PasteChord::ShiftInsert), "shift+insert");
+    }
+
+    #[test]
+    fn test_resolve_paste_config() {
+        let base = PasteConfig::default();
+        let overrides = vec![PasteOverride {
+            window_class: "kitty".to_string(),
+            chord: Some(PasteChord::CtrlShiftV),
+            delay_ms: None,
+        }];
+        let terminal = FocusedWindow {
+            id: "1".to_string(),
+            class: "kitty".to_string(),
+            title: String::new(),
+        };
+        let editor = FocusedWindow {
+            class: "code".to_string(),
+            ..terminal.clone()
+        };
+
+        let config = resolve_paste_config(base, &overrides, Some(&terminal));
+        assert_eq!(config.chord, PasteChord::CtrlShiftV);
+        assert_eq!(config.delay_ms, base.delay_ms);
+
+        assert_eq!(resolve_paste_config(base, &overrides, Some(&editor)), base);
+        assert_eq!(resolve_paste_config(base, &overrides, None), base);
+    }
+
+    #[test]
+    fn test_markdown_to_html() {
+        let html = markdown_to_html("## Notes\n\n- **First** item\n- Second item");
+        assert!(html.contains("<h2>Notes</h2>"));
+        assert!(html.contains("<li><strong>First</strong> item</li>"));
+    }
+
     #[test]
     fn test_get_paste_info() {
         let info = get_paste_info();
@@ -0,0 +1,176 @@
+//! Paste backend benchmark
+//!
+//! Opens a small undecorated test window with a text field, inserts a marker
+//! text through every available paste backend and measures whether and how
+//! fast it arrives. The fastest working backend becomes the paste backend.
+//! The clipboard text is restored afterwards.
+
+use crate::error::Result;
+use crate::paste::{self, PasteBackend};
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Listener, WebviewUrl, WebviewWindow, WebviewWindowBuilder};
+
+const TEST_LABEL: &str = "paste-test";
+
+/// Emitted by the test window with the current content of its text field
+pub const PASTE_TEST_INPUT_EVENT: &str = "paste-test-input";
+
+/// Emitted to the test window to clear its text field
+pub const PASTE_TEST_RESET_EVENT: &str = "paste-test-reset";
+
+/// How long to wait for the marker text to arrive
+const ARRIVAL_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Result of one backend
+#[derive(Debug, Clone, Serialize)]
+pub struct BackendResult {
+    pub backend: PasteBackend,
+    pub success: bool,
+    /// Time from sending the text until it arrived in the test window
+    pub latency_ms: Option<u64>,
+    pub error: Option<String>,
+}
+
+/// Results of a benchmark run
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchmarkReport {
+    pub results: Vec<BackendResult>,
+    /// Backend now used for pasting, if any worked
+    pub selected: Option<PasteBackend>,
+}
+
+/// The working backend with the lowest latency
+pub fn fastest(results: &[BackendResult]) -> Option<PasteBackend> {
+    results
+        .iter()
+        .filter(|r| r.success)
+        .min_by_key(|r| r.latency_ms.unwrap_or(u64::MAX))
+        .map(|r| r.backend)
+}
+
+/// Test every available backend against the test window
+///
+/// Blocks while keys are simulated; run it off the async runtime.
+pub fn run(handle: &AppHandle) -> Result<BenchmarkReport> {
+    let backends = paste::candidate_backends();
+    if backends.is_empty() {
+        return Ok(BenchmarkReport {
+            results: Vec::new(),
+            selected: None,
+        });
+    }
+
+    let clipboard = paste::get_clipboard_text().ok();
+    let received = Arc::new(Mutex::new(String::new()));
+    let received_clone = received.clone();
+    let listener = handle.listen(PASTE_TEST_INPUT_EVENT, move |event| {
+        if let Ok(text) = serde_json::from_str::<String>(event.payload()) {
+            if let Ok(mut received) = received_clone.lock() {
+                *received = text;
+            }
+        }
+    });
+
+    let results = open_test_window(handle).map(|window| {
+        // Give the page time to load
+        thread::sleep(Duration::from_millis(800));
+        let results: Vec<BackendResult> = backends
+            .into_iter()
+            .enumerate()
+            .map(|(i, backend)| test_backend(&window, &received, backend, i))
+            .collect();
+        let _ = window.close();
+        results
+    });
+    handle.unlisten(listener);
+
+    if let Some(text) = clipboard {
+        let _ = paste::copy_and_paste(&text, false);
+    }
+
+    let results = results?;
+    let selected = fastest(&results);
+    log::info!("Paste benchmark: {:?}, selected {:?}", results, selected);
+    Ok(BenchmarkReport { results, selected })
+}
+
+fn open_test_window(handle: &AppHandle) -> Result<WebviewWindow> {
+    let window =
+        WebviewWindowBuilder::new(handle, TEST_LABEL, WebviewUrl::App("/paste-test".into()))
+            .title("WhisperTray paste test")
+            .inner_size(240.0, 80.0)
+            .decorations(false)
+            .always_on_top(true)
+            .skip_taskbar(true)
+            .resizable(false)
+            .focused(true)
+            .visible(true)
+            .build()?;
+    Ok(window)
+}
+
+fn test_backend(
+    window: &WebviewWindow,
+    received: &Mutex<String>,
+    backend: PasteBackend,
+    index: usize,
+) -> BackendResult {
+    if let Ok(mut received) = received.lock() {
+        received.clear();
+    }
+    let _ = window.emit_to(TEST_LABEL, PASTE_TEST_RESET_EVENT, ());
+    let _ = window.set_focus();
+    thread::sleep(Duration::from_millis(300));
+
+    let marker = format!("whispertray{}", index);
+    let start = Instant::now();
+    let failed = |error: String| BackendResult {
+        backend,
+        success: false,
+        latency_ms: None,
+        error: Some(error),
+    };
+
+    if let Err(e) = paste::insert_with_backend(backend, &marker) {
+        return failed(e.to_string());
+    }
+
+    while start.elapsed() < ARRIVAL_TIMEOUT {
+        let arrived = received.lock().is_ok_and(|r| r.contains(&marker));
+        if arrived {
+            return BackendResult {
+                backend,
+                success: true,
+                latency_ms: Some(start.elapsed().as_millis() as u64),
+                error: None,
+            };
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    failed("The text did not arrive in the test window".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fastest() {
+        let result = |backend, success, latency_ms| BackendResult {
+            backend,
+            success,
+            latency_ms,
+            error: None,
+        };
+        let results = [
+            result(PasteBackend::Enigo, false, None),
+            result(PasteBackend::Wtype, true, Some(120)),
+            result(PasteBackend::Ydotool, true, Some(80)),
+        ];
+        assert_eq!(fastest(&results), Some(PasteBackend::Ydotool));
+        assert_eq!(fastest(&results[..1]), None);
+    }
+}
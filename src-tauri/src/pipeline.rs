@@ -0,0 +1,212 @@
+//! Processing pipeline progress reporting
+//!
+//! Emits a typed `pipeline-progress` event for every stage of a dictation
+//! (recording → saving → transcribing → llm → pasting) with timings, so the
+//! frontend, overlay and notifications can show what is actually happening.
+
+use crate::database::StageLatencies;
+use crate::error::{AppError, Result};
+use serde::Serialize;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
+use tauri::{AppHandle, Emitter};
+use tokio_util::sync::CancellationToken;
+
+/// Event name for pipeline progress updates
+pub const PIPELINE_PROGRESS_EVENT: &str = "pipeline-progress";
+
+/// Pipeline stages in processing order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PipelineStage {
+    Recording,
+    Saving,
+    Transcribing,
+    Llm,
+    Pasting,
+    /// Pipeline finished successfully
+    Done,
+    /// Pipeline aborted with an error
+    Failed,
+}
+
+/// Time spent in a completed stage
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct StageTiming {
+    pub stage: PipelineStage,
+    pub duration_ms: u64,
+}
+
+/// Payload of the `pipeline-progress` event
+#[derive(Debug, Clone, Serialize)]
+pub struct PipelineProgress {
+    /// Stage that just started
+    pub stage: PipelineStage,
+    /// Timing of the stage that just finished
+    pub previous: Option<StageTiming>,
+    /// Time since the pipeline started
+    pub elapsed_ms: u64,
+    /// Error message for the failed stage
+    pub error: Option<String>,
+}
+
+/// Tracks stage transitions and emits progress events
+pub struct ProgressReporter {
+    app_handle: AppHandle,
+    started: Instant,
+    stage: PipelineStage,
+    stage_started: Instant,
+    timings: Vec<StageTiming>,
+}
+
+impl ProgressReporter {
+    /// Start tracking a pipeline whose recording stage began at `recording_started`
+    pub fn new(app_handle: AppHandle, recording_started: Option<Instant>) -> Self {
+        let started = recording_started.unwrap_or_else(Instant::now);
+        Self {
+            app_handle,
+            started,
+            stage: PipelineStage::Recording,
+            stage_started: started,
+            timings: Vec::new(),
+        }
+    }
+
+    /// Move to the next stage
+    pub fn stage(&mut self, stage: PipelineStage) {
+        self.transition(stage, None);
+    }
+
+    /// Mark the pipeline as finished
+    pub fn finish(&mut self) {
+        self.transition(PipelineStage::Done, None);
+    }
+
+    /// Mark the pipeline as failed in the current stage
+    pub fn fail(&mut self, error: &str) {
+        self.transition(PipelineStage::Failed, Some(error.to_string()));
+    }
+
+    /// Timings of all completed stages
+    pub fn timings(&self) -> &[StageTiming] {
+        &self.timings
+    }
+
+    /// Time spent per stage so far, counting the current stage up to now
+    pub fn latencies(&self) -> StageLatencies {
+        let current = StageTiming {
+            stage: self.stage,
+            duration_ms: self.stage_started.elapsed().as_millis() as u64,
+        };
+
+        let mut latencies = StageLatencies::default();
+        for timing in self.timings.iter().chain(std::iter::once(&current)) {
+            let total = match timing.stage {
+                PipelineStage::Recording => &mut latencies.recording_ms,
+                PipelineStage::Saving => &mut latencies.saving_ms,
+                PipelineStage::Transcribing => &mut latencies.transcribing_ms,
+                PipelineStage::Llm => &mut latencies.llm_ms,
+                PipelineStage::Pasting => &mut latencies.pasting_ms,
+                PipelineStage::Done | PipelineStage::Failed => continue,
+            };
+            *total += timing.duration_ms;
+        }
+        latencies
+    }
+
+    fn transition(&mut self, stage: PipelineStage, error: Option<String>) {
+        let now = Instant::now();
+        let previous = StageTiming {
+            stage: self.stage,
+            duration_ms: now.duration_since(self.stage_started).as_millis() as u64,
+        };
+        self.timings.push(previous);
+        self.stage = stage;
+        self.stage_started = now;
+
+        let progress = PipelineProgress {
+            stage,
+            previous: Some(previous),
+            elapsed_ms: now.duration_since(self.started).as_millis() as u64,
+            error,
+        };
+
+        log::debug!(
+            "Pipeline stage {:?} ({:?} took {} ms)",
+            stage,
+            previous.stage,
+            previous.duration_ms
+        );
+        let _ = self.app_handle.emit(PIPELINE_PROGRESS_EVENT, &progress);
+    }
+}
+
+/// Cancellation handle for in-flight processing.
+///
+/// Managed separately from `AppState` so it can be triggered while the
+/// pipeline holds the state lock.
+#[derive(Default)]
+pub struct ProcessingControl {
+    token: Mutex<Option<CancellationToken>>,
+    /// Set once the app is quitting
+    shutting_down: AtomicBool,
+}
+
+impl ProcessingControl {
+    /// Start a new cancellable processing run
+    pub fn begin(&self) -> CancellationToken {
+        let token = CancellationToken::new();
+        if let Ok(mut current) = self.token.lock() {
+            *current = Some(token.clone());
+        }
+        token
+    }
+
+    /// Finish the current processing run
+    pub fn end(&self) {
+        if let Ok(mut current) = self.token.lock() {
+            *current = None;
+        }
+    }
+
+    /// Cancel the current processing run, returning whether one was active
+    pub fn cancel(&self) -> bool {
+        match self.token.lock().ok().and_then(|current| current.clone()) {
+            Some(token) => {
+                log::info!("Cancelling in-flight processing");
+                token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Check whether processing is in progress
+    pub fn is_active(&self) -> bool {
+        self.token.lock().map(|t| t.is_some()).unwrap_or(false)
+    }
+
+    /// Cancel the current processing run because the app is quitting
+    pub fn shut_down(&self) -> bool {
+        self.shutting_down.store(true, Ordering::SeqCst);
+        self.cancel()
+    }
+
+    /// Whether a cancellation came from quitting the app
+    pub fn is_shutting_down(&self) -> bool {
+        self.shutting_down.load(Ordering::SeqCst)
+    }
+}
+
+/// Run a provider call, aborting with `AppError::Cancelled` if the token fires
+pub async fn run_cancellable<T>(
+    token: &CancellationToken,
+    future: impl Future<Output = Result<T>>,
+) -> Result<T> {
+    tokio::select! {
+        _ = token.cancelled() => Err(AppError::Cancelled),
+        result = future => result,
+    }
+}
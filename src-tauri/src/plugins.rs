@@ -0,0 +1,465 @@
+//! WebAssembly plugins
+//!
+//! Third-party plugins are WebAssembly components implementing the
+//! `whispertray:plugin` world (`wit/plugin.wit`), placed as `*.wasm` files in
+//! the `plugins` folder of the config directory. A plugin can process the
+//! final text, receive it after pasting, or provide prompt context. Plugins
+//! are off until enabled and run sandboxed: they cannot import anything but a
+//! log function, so they have no file system or network access, and every
+//! call runs in a fresh instance with limited fuel, memory and wall-clock
+//! time. Calls run on the blocking thread pool, off the async runtime.
+//!
+//! The host requires the `plugins` feature; other builds list the plugin
+//! files but never run them.
+
+use crate::error::{AppError, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Processing stage a plugin takes part in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Capability {
+    /// Rewrites the final text before it is pasted
+    TextProcessor,
+    /// Receives the final text after it was pasted
+    OutputSink,
+    /// Adds text to the `{{context}}` of AI modes
+    ContextProvider,
+}
+
+/// A plugin found in the plugins folder
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginInfo {
+    /// File name without the `.wasm` extension
+    pub id: String,
+    pub name: String,
+    pub version: String,
+    pub description: String,
+    pub capabilities: Vec<Capability>,
+    pub enabled: bool,
+    /// Why the plugin could not be loaded
+    pub error: Option<String>,
+}
+
+impl PluginInfo {
+    fn unloaded(id: String, error: String) -> Self {
+        Self {
+            name: id.clone(),
+            id,
+            version: String::new(),
+            description: String::new(),
+            capabilities: Vec::new(),
+            enabled: false,
+            error: Some(error),
+        }
+    }
+
+    /// Whether the plugin should run for `capability`
+    #[cfg_attr(not(feature = "plugins"), allow(dead_code))]
+    fn runs(&self, capability: Capability) -> bool {
+        self.enabled && self.error.is_none() && self.capabilities.contains(&capability)
+    }
+}
+
+/// Folder plugins are loaded from
+pub fn plugins_dir() -> Result<PathBuf> {
+    let config_dir = directories::ProjectDirs::from("com", "whispertray", "WhisperTray")
+        .ok_or_else(|| AppError::Config("Could not determine config directory".to_string()))?
+        .config_dir()
+        .to_path_buf();
+
+    Ok(config_dir.join("plugins"))
+}
+
+/// Plugin ids and files in `dir`, sorted by id
+fn plugin_files(dir: &Path) -> Vec<(String, PathBuf)> {
+    let mut files: Vec<(String, PathBuf)> = std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "wasm"))
+        .filter_map(|path| {
+            let id = path.file_stem()?.to_str()?.to_string();
+            Some((id, path))
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+/// Join context from several sources, skipping empty ones
+pub fn join_context<I: IntoIterator<Item = Option<String>>>(parts: I) -> Option<String> {
+    let parts: Vec<String> = parts
+        .into_iter()
+        .flatten()
+        .filter(|part| !part.trim().is_empty())
+        .collect();
+    (!parts.is_empty()).then(|| parts.join("\n\n"))
+}
+
+#[cfg(feature = "plugins")]
+mod host {
+    use super::{plugin_files, Capability, PluginInfo};
+    use std::path::Path;
+    use std::sync::OnceLock;
+    use std::time::Duration;
+    use wasmtime::component::{Component, Linker};
+    use wasmtime::{Config, Engine, Store, StoreLimits, StoreLimitsBuilder};
+
+    mod bindings {
+        wasmtime::component::bindgen!({
+            path: "wit/plugin.wit",
+            world: "plugin",
+        });
+    }
+
+    use bindings::{Plugin, PluginPre};
+
+    /// Fuel available to a single call (roughly one unit per instruction)
+    const FUEL_PER_CALL: u64 = 1_000_000_000;
+    /// Memory available to a plugin instance
+    const MAX_MEMORY_BYTES: usize = 64 * 1024 * 1024;
+    /// Core module instances per plugin; components adapt their imports and
+    /// exports with a few helper modules besides the main one
+    const MAX_CORE_INSTANCES: usize = 16;
+    /// Interval at which the engine's epoch advances
+    const EPOCH_TICK: Duration = Duration::from_millis(100);
+    /// Epochs a single call may take before it is interrupted (5 seconds)
+    const DEADLINE_TICKS: u64 = 50;
+
+    /// Engine shared by all hosts, with a thread advancing its epoch so calls
+    /// that sleep in the host or spin cheaply still hit their deadline
+    fn engine() -> &'static Engine {
+        static ENGINE: OnceLock<Engine> = OnceLock::new();
+        ENGINE.get_or_init(|| {
+            let mut config = Config::new();
+            config
+                .wasm_component_model(true)
+                .consume_fuel(true)
+                .epoch_interruption(true);
+            let engine = Engine::new(&config).expect("Default wasmtime config is valid");
+            let ticker = engine.clone();
+            std::thread::spawn(move || loop {
+                std::thread::sleep(EPOCH_TICK);
+                ticker.increment_epoch();
+            });
+            engine
+        })
+    }
+
+    /// Per-call store data
+    struct HostState {
+        plugin: String,
+        limits: StoreLimits,
+    }
+
+    impl bindings::PluginImports for HostState {
+        fn log(&mut self, message: String) {
+            log::info!("[plugin {}] {}", self.plugin, message);
+        }
+    }
+
+    impl From<bindings::Capability> for Capability {
+        fn from(capability: bindings::Capability) -> Self {
+            match capability {
+                bindings::Capability::TextProcessor => Capability::TextProcessor,
+                bindings::Capability::OutputSink => Capability::OutputSink,
+                bindings::Capability::ContextProvider => Capability::ContextProvider,
+            }
+        }
+    }
+
+    #[derive(Clone)]
+    struct LoadedPlugin {
+        info: PluginInfo,
+        pre: Option<PluginPre<HostState>>,
+    }
+
+    /// Loaded plugins
+    #[derive(Clone)]
+    pub struct PluginHost {
+        plugins: Vec<LoadedPlugin>,
+    }
+
+    impl PluginHost {
+        /// Load the plugins in `dir`; plugins not listed in `enabled` stay off
+        pub fn load(dir: &Path, enabled: &[String]) -> Self {
+            let plugins = plugin_files(dir)
+                .into_iter()
+                .map(|(id, path)| {
+                    let loaded = Self::load_plugin(engine(), &id, &path);
+                    match loaded {
+                        Ok((info, pre)) => {
+                            log::info!("Loaded plugin {} ({})", info.name, id);
+                            LoadedPlugin {
+                                info,
+                                pre: Some(pre),
+                            }
+                        }
+                        Err(e) => {
+                            log::warn!("Failed to load plugin {}: {}", id, e);
+                            LoadedPlugin {
+                                info: PluginInfo::unloaded(id, e.to_string()),
+                                pre: None,
+                            }
+                        }
+                    }
+                })
+                .collect();
+
+            let mut host = Self { plugins };
+            host.set_enabled(enabled);
+            host
+        }
+
+        fn load_plugin(
+            engine: &Engine,
+            id: &str,
+            path: &Path,
+        ) -> wasmtime::Result<(PluginInfo, PluginPre<HostState>)> {
+            let component = Component::from_file(engine, path)?;
+            let mut linker = Linker::new(engine);
+            Plugin::add_to_linker(&mut linker, |state: &mut HostState| state)?;
+            let pre = PluginPre::new(linker.instantiate_pre(&component)?)?;
+
+            let (mut store, plugin) = Self::instantiate(engine, id, &pre)?;
+            let info = plugin.call_info(&mut store)?;
+            let info = PluginInfo {
+                id: id.to_string(),
+                name: info.name,
+                version: info.version,
+                description: info.description,
+                capabilities: info.capabilities.into_iter().map(Into::into).collect(),
+                enabled: false,
+                error: None,
+            };
+            Ok((info, pre))
+        }
+
+        fn instantiate(
+            engine: &Engine,
+            id: &str,
+            pre: &PluginPre<HostState>,
+        ) -> wasmtime::Result<(Store<HostState>, Plugin)> {
+            let state = HostState {
+                plugin: id.to_string(),
+                limits: StoreLimitsBuilder::new()
+                    .memory_size(MAX_MEMORY_BYTES)
+                    .instances(MAX_CORE_INSTANCES)
+                    .build(),
+            };
+            let mut store = Store::new(engine, state);
+            store.limiter(|state| &mut state.limits);
+            store.set_fuel(FUEL_PER_CALL)?;
+            store.set_epoch_deadline(DEADLINE_TICKS);
+            let plugin = pre.instantiate(&mut store)?;
+            Ok((store, plugin))
+        }
+
+        /// Run `call` on a fresh instance of every enabled plugin with `capability`
+        fn each<F>(&self, capability: Capability, mut call: F)
+        where
+            F: FnMut(&PluginInfo, &mut Store<HostState>, &Plugin) -> wasmtime::Result<()>,
+        {
+            for plugin in self.plugins.iter().filter(|p| p.info.runs(capability)) {
+                let pre = match &plugin.pre {
+                    Some(pre) => pre,
+                    None => continue,
+                };
+                let result = Self::instantiate(engine(), &plugin.info.id, pre)
+                    .and_then(|(mut store, instance)| call(&plugin.info, &mut store, &instance));
+                if let Err(e) = result {
+                    log::warn!("Plugin {} failed: {}", plugin.info.id, e);
+                }
+            }
+        }
+
+        pub fn list(&self) -> Vec<PluginInfo> {
+            self.plugins.iter().map(|p| p.info.clone()).collect()
+        }
+
+        pub fn set_enabled(&mut self, enabled: &[String]) {
+            for plugin in &mut self.plugins {
+                plugin.info.enabled = plugin.pre.is_some() && enabled.contains(&plugin.info.id);
+            }
+        }
+
+        pub(super) fn run_text_processors(&self, text: String, mode_key: &str) -> String {
+            let mut text = text;
+            self.each(Capability::TextProcessor, |info, store, plugin| {
+                match plugin.call_process_text(&mut *store, &text, mode_key)? {
+                    Ok(output) => text = output,
+                    Err(e) => log::warn!("Plugin {} rejected the text: {}", info.id, e),
+                }
+                Ok(())
+            });
+            text
+        }
+
+        pub(super) fn run_output_sinks(&self, text: &str, mode_key: &str) {
+            self.each(Capability::OutputSink, |info, store, plugin| {
+                if let Err(e) = plugin.call_consume_output(&mut *store, text, mode_key)? {
+                    log::warn!("Plugin {} could not take the output: {}", info.id, e);
+                }
+                Ok(())
+            });
+        }
+
+        pub(super) fn run_context_providers(&self, mode_key: &str) -> Option<String> {
+            let mut parts = Vec::new();
+            self.each(Capability::ContextProvider, |_, store, plugin| {
+                parts.push(plugin.call_provide_context(&mut *store, mode_key)?);
+                Ok(())
+            });
+            super::join_context(parts)
+        }
+    }
+}
+
+#[cfg(not(feature = "plugins"))]
+mod host {
+    use super::{plugin_files, PluginInfo};
+    use std::path::Path;
+
+    /// Plugin files, listed but never run in builds without the `plugins` feature
+    #[derive(Clone)]
+    pub struct PluginHost {
+        plugins: Vec<PluginInfo>,
+    }
+
+    impl PluginHost {
+        pub fn load(dir: &Path, _enabled: &[String]) -> Self {
+            let error = "Plugins are not available in this build (enable the `plugins` feature)";
+            let plugins = plugin_files(dir)
+                .into_iter()
+                .map(|(id, _)| PluginInfo::unloaded(id, error.to_string()))
+                .collect();
+            Self { plugins }
+        }
+
+        pub fn list(&self) -> Vec<PluginInfo> {
+            self.plugins.clone()
+        }
+
+        pub fn set_enabled(&mut self, _enabled: &[String]) {}
+
+        pub(super) fn run_text_processors(&self, text: String, _mode_key: &str) -> String {
+            text
+        }
+
+        pub(super) fn run_output_sinks(&self, _text: &str, _mode_key: &str) {}
+
+        pub(super) fn run_context_providers(&self, _mode_key: &str) -> Option<String> {
+            None
+        }
+    }
+}
+
+pub use host::PluginHost;
+
+impl PluginHost {
+    /// Load the plugins in the plugins folder
+    pub fn load_default(enabled: &[String]) -> Self {
+        match plugins_dir() {
+            Ok(dir) => Self::load(&dir, enabled),
+            Err(e) => {
+                log::warn!("Failed to locate plugins folder: {}", e);
+                Self::load(Path::new(""), enabled)
+            }
+        }
+    }
+
+    /// Pass the final text through the enabled text processors
+    pub async fn process_text(&self, text: String, mode_key: &str) -> String {
+        let (host, mode_key, original) = (self.clone(), mode_key.to_string(), text.clone());
+        tokio::task::spawn_blocking(move || host.run_text_processors(text, &mode_key))
+            .await
+            .unwrap_or_else(|e| {
+                log::warn!("Plugin task failed: {}", e);
+                original
+            })
+    }
+
+    /// Hand the final text to the enabled output sinks in the background
+    pub fn consume_output(&self, text: &str, mode_key: &str) {
+        let (host, text, mode_key) = (self.clone(), text.to_string(), mode_key.to_string());
+        tauri::async_runtime::spawn_blocking(move || host.run_output_sinks(&text, &mode_key));
+    }
+
+    /// Prompt context from the enabled context providers
+    pub async fn provide_context(&self, mode_key: &str) -> Option<String> {
+        let (host, mode_key) = (self.clone(), mode_key.to_string());
+        tokio::task::spawn_blocking(move || host.run_context_providers(&mode_key))
+            .await
+            .unwrap_or_else(|e| {
+                log::warn!("Plugin task failed: {}", e);
+                None
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plugin_files() {
+        let dir = std::env::temp_dir().join(format!("whispertray-plugins-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for name in ["b.wasm", "a.wasm", "notes.txt"] {
+            std::fs::write(dir.join(name), b"").unwrap();
+        }
+
+        let ids: Vec<String> = plugin_files(&dir).into_iter().map(|(id, _)| id).collect();
+        assert_eq!(ids, ["a", "b"]);
+
+        // Invalid components are listed with an error and never run
+        let host = PluginHost::load(&dir, &["a".to_string()]);
+        let plugins = host.list();
+        assert_eq!(plugins.len(), 2);
+        assert!(plugins.iter().all(|p| p.error.is_some() && !p.enabled));
+        assert_eq!(host.run_text_processors("keep".to_string(), "note"), "keep");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(feature = "plugins")]
+    #[test]
+    fn test_run_component() {
+        let dir =
+            std::env::temp_dir().join(format!("whispertray-component-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let wasm = wat::parse_str(include_str!("../wit/test-plugin.wat")).unwrap();
+        std::fs::write(dir.join("rewrite.wasm"), wasm).unwrap();
+
+        let host = PluginHost::load(&dir, &["rewrite".to_string()]);
+        let plugins = host.list();
+        assert_eq!(plugins[0].error, None);
+        assert_eq!(plugins[0].name, "Test");
+        assert_eq!(
+            plugins[0].capabilities,
+            [Capability::TextProcessor, Capability::ContextProvider]
+        );
+        assert!(plugins[0].enabled);
+        assert_eq!(
+            host.run_text_processors("hello".to_string(), "note"),
+            "rewritten"
+        );
+
+        // The context provider never returns and is stopped
+        assert_eq!(host.run_context_providers("note"), None);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_join_context() {
+        assert_eq!(join_context([None, Some("  ".to_string())]), None);
+        assert_eq!(
+            join_context([Some("a".to_string()), None, Some("b".to_string())]),
+            Some("a\n\nb".to_string())
+        );
+    }
+}
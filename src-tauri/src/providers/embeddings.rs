@@ -0,0 +1,417 @@
+//! Embedding provider implementations for semantic search
+//!
+//! Ollama and OpenAI are reached over HTTP. The local ONNX backend runs a
+//! sentence-transformers export (`model.onnx` + `tokenizer.json`) from the
+//! embeddings directory and needs the `onnx` build feature.
+
+use crate::error::{AppError, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Embedding provider options
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum EmbeddingProviderType {
+    #[default]
+    Ollama,
+    OpenAI,
+    Onnx,
+}
+
+impl EmbeddingProviderType {
+    /// Whether the provider runs on this machine
+    pub fn is_local(&self) -> bool {
+        !matches!(self, EmbeddingProviderType::OpenAI)
+    }
+}
+
+/// Embedding provider trait
+#[async_trait]
+pub trait EmbeddingProvider: Send + Sync {
+    /// Embed each text as a vector
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>>;
+
+    /// Get the provider name
+    fn name(&self) -> &str;
+}
+
+/// Cosine similarity of two vectors (0.0 if either is empty or zero)
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    dot / (norm_a * norm_b)
+}
+
+/// Result of a provider self-test
+#[derive(Debug, Clone, Serialize)]
+pub struct EmbeddingCheck {
+    pub provider: String,
+    pub dimensions: usize,
+    pub elapsed_ms: u64,
+}
+
+/// Embed a sample sentence to verify the provider works
+pub async fn check(provider: &dyn EmbeddingProvider) -> Result<EmbeddingCheck> {
+    let started = std::time::Instant::now();
+    let vectors = provider
+        .embed(&["The quick brown fox jumps over the lazy dog.".to_string()])
+        .await?;
+    let dimensions = vectors.first().map(Vec::len).unwrap_or_default();
+    if dimensions == 0 {
+        return Err(AppError::Provider(format!(
+            "{} returned no embedding",
+            provider.name()
+        )));
+    }
+
+    Ok(EmbeddingCheck {
+        provider: provider.name().to_string(),
+        dimensions,
+        elapsed_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+/// Directory holding a local ONNX embedding model
+pub fn get_embedding_model_dir(model: &str) -> Result<PathBuf> {
+    let data_dir = directories::ProjectDirs::from("com", "whispertray", "WhisperTray")
+        .ok_or_else(|| AppError::Config("Could not determine data directory".to_string()))?
+        .data_dir()
+        .to_path_buf();
+
+    Ok(data_dir.join("embeddings").join(model))
+}
+
+/// Ollama provider for local embeddings
+pub struct OllamaEmbeddings {
+    base_url: String,
+    model: String,
+}
+
+impl OllamaEmbeddings {
+    pub fn new(model: String) -> Self {
+        Self {
+            base_url: super::llm::ollama_base_url(),
+            model,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct OllamaEmbedRequest<'a> {
+    model: &'a str,
+    input: &'a [String],
+}
+
+#[derive(Deserialize)]
+struct OllamaEmbedResponse {
+    embeddings: Vec<Vec<f32>>,
+}
+
+#[async_trait]
+impl EmbeddingProvider for OllamaEmbeddings {
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        let url = format!("{}/api/embed", self.base_url);
+        let request = OllamaEmbedRequest {
+            model: &self.model,
+            input: texts,
+        };
+
+        let response = reqwest::Client::new()
+            .post(&url)
+            .json(&request)
+            .timeout(std::time::Duration::from_secs(60))
+            .send()
+            .await
+            .map_err(|e| AppError::Provider(format!("Ollama request failed: {}", e)))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(AppError::Provider(format!(
+                "Ollama error ({}): {}",
+                status, body
+            )));
+        }
+
+        let result: OllamaEmbedResponse = response
+            .json()
+            .await
+            .map_err(|e| AppError::Provider(format!("Failed to parse Ollama response: {}", e)))?;
+        Ok(result.embeddings)
+    }
+
+    fn name(&self) -> &str {
+        "Ollama"
+    }
+}
+
+/// OpenAI embeddings provider
+pub struct OpenAiEmbeddings {
+    api_key: String,
+    model: String,
+}
+
+impl OpenAiEmbeddings {
+    pub fn new(api_key: String, model: String) -> Self {
+        Self { api_key, model }
+    }
+}
+
+#[derive(Serialize)]
+struct OpenAiEmbedRequest<'a> {
+    model: &'a str,
+    input: &'a [String],
+}
+
+#[derive(Deserialize)]
+struct OpenAiEmbedResponse {
+    data: Vec<OpenAiEmbedding>,
+}
+
+#[derive(Deserialize)]
+struct OpenAiEmbedding {
+    index: usize,
+    embedding: Vec<f32>,
+}
+
+#[async_trait]
+impl EmbeddingProvider for OpenAiEmbeddings {
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        let request = OpenAiEmbedRequest {
+            model: &self.model,
+            input: texts,
+        };
+
+        let response = reqwest::Client::new()
+            .post("https://api.openai.com/v1/embeddings")
+            .header("Authorization", format!("Bearer {}", self.api_key))
+            .json(&request)
+            .timeout(std::time::Duration::from_secs(60))
+            .send()
+            .await
+            .map_err(|e| AppError::Provider(format!("OpenAI request failed: {}", e)))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(AppError::Provider(format!(
+                "OpenAI error ({}): {}",
+                status, body
+            )));
+        }
+
+        let mut result: OpenAiEmbedResponse = response
+            .json()
+            .await
+            .map_err(|e| AppError::Provider(format!("Failed to parse OpenAI response: {}", e)))?;
+        result.data.sort_by_key(|e| e.index);
+        Ok(result.data.into_iter().map(|e| e.embedding).collect())
+    }
+
+    fn name(&self) -> &str {
+        "OpenAI"
+    }
+}
+
+/// Mean of the token vectors whose attention mask is set
+#[cfg_attr(not(feature = "onnx"), allow(dead_code))]
+fn mean_pool(hidden: &[f32], mask: &[i64], dimensions: usize) -> Vec<f32> {
+    let mut pooled = vec![0.0; dimensions];
+    let mut count = 0.0;
+    for (token, _) in hidden.chunks(dimensions).zip(mask).filter(|(_, &m)| m != 0) {
+        for (sum, value) in pooled.iter_mut().zip(token) {
+            *sum += value;
+        }
+        count += 1.0;
+    }
+    if count > 0.0 {
+        pooled.iter_mut().for_each(|v| *v /= count);
+    }
+    pooled
+}
+
+#[cfg(feature = "onnx")]
+mod onnx {
+    use super::{mean_pool, EmbeddingProvider};
+    use crate::error::{AppError, Result};
+    use async_trait::async_trait;
+    use ort::session::Session;
+    use ort::value::Tensor;
+    use std::path::Path;
+    use std::sync::{Arc, Mutex};
+    use tokenizers::Tokenizer;
+
+    fn onnx_error(e: impl std::fmt::Display) -> AppError {
+        AppError::Provider(format!("ONNX embedding failed: {}", e))
+    }
+
+    /// Local sentence-transformers model run with ONNX Runtime
+    pub struct OnnxEmbeddings {
+        session: Arc<Mutex<Session>>,
+        tokenizer: Arc<Tokenizer>,
+    }
+
+    impl OnnxEmbeddings {
+        /// Load `model.onnx` and `tokenizer.json` from a model directory
+        pub fn load(dir: &Path) -> Result<Self> {
+            if !dir.join("model.onnx").exists() {
+                return Err(AppError::Provider(format!(
+                    "No ONNX embedding model found in {:?}",
+                    dir
+                )));
+            }
+            let session = Session::builder()
+                .and_then(|builder| builder.commit_from_file(dir.join("model.onnx")))
+                .map_err(onnx_error)?;
+            let tokenizer = Tokenizer::from_file(dir.join("tokenizer.json")).map_err(onnx_error)?;
+
+            Ok(Self {
+                session: Arc::new(Mutex::new(session)),
+                tokenizer: Arc::new(tokenizer),
+            })
+        }
+    }
+
+    /// Embed one text with the loaded model
+    fn embed_one(session: &mut Session, tokenizer: &Tokenizer, text: &str) -> Result<Vec<f32>> {
+        let encoding = tokenizer.encode(text, true).map_err(onnx_error)?;
+        let ids: Vec<i64> = encoding.get_ids().iter().map(|&id| id as i64).collect();
+        let mask: Vec<i64> = encoding
+            .get_attention_mask()
+            .iter()
+            .map(|&m| m as i64)
+            .collect();
+        let shape = [1, ids.len()];
+
+        let wants_type_ids = session
+            .inputs
+            .iter()
+            .any(|input| input.name == "token_type_ids");
+        let mut inputs = ort::inputs![
+            "input_ids" => Tensor::from_array((shape, ids.clone())).map_err(onnx_error)?,
+            "attention_mask" => Tensor::from_array((shape, mask.clone())).map_err(onnx_error)?,
+        ];
+        if wants_type_ids {
+            let type_ids = vec![0i64; ids.len()];
+            inputs.push((
+                "token_type_ids".into(),
+                Tensor::from_array((shape, type_ids))
+                    .map_err(onnx_error)?
+                    .into(),
+            ));
+        }
+
+        let outputs = session.run(inputs).map_err(onnx_error)?;
+        let (dims, hidden) = outputs[0].try_extract_tensor::<f32>().map_err(onnx_error)?;
+        let dimensions = dims.last().copied().unwrap_or_default() as usize;
+        Ok(mean_pool(hidden, &mask, dimensions))
+    }
+
+    #[async_trait]
+    impl EmbeddingProvider for OnnxEmbeddings {
+        async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+            let session = self.session.clone();
+            let tokenizer = self.tokenizer.clone();
+            let texts = texts.to_vec();
+
+            tokio::task::spawn_blocking(move || {
+                let mut session = session.lock().unwrap_or_else(|e| e.into_inner());
+                texts
+                    .iter()
+                    .map(|text| embed_one(&mut session, &tokenizer, text))
+                    .collect()
+            })
+            .await
+            .map_err(onnx_error)?
+        }
+
+        fn name(&self) -> &str {
+            "ONNX"
+        }
+    }
+}
+
+#[cfg(feature = "onnx")]
+pub use onnx::OnnxEmbeddings;
+
+/// Create an embedding provider based on configuration
+pub fn create_embedding_provider(
+    provider_type: &EmbeddingProviderType,
+    model: &str,
+    api_key: Option<&str>,
+    local_only: bool,
+) -> Result<Box<dyn EmbeddingProvider>> {
+    if local_only && !provider_type.is_local() {
+        return Err(AppError::LocalOnly(format!(
+            "{:?} embeddings require network access",
+            provider_type
+        )));
+    }
+
+    match provider_type {
+        EmbeddingProviderType::Ollama => Ok(Box::new(OllamaEmbeddings::new(model.to_string()))),
+        EmbeddingProviderType::OpenAI => {
+            let key =
+                api_key.ok_or_else(|| AppError::Provider("OpenAI API key required".to_string()))?;
+            Ok(Box::new(OpenAiEmbeddings::new(
+                key.to_string(),
+                model.to_string(),
+            )))
+        }
+        #[cfg(feature = "onnx")]
+        EmbeddingProviderType::Onnx => Ok(Box::new(OnnxEmbeddings::load(
+            &get_embedding_model_dir(model)?,
+        )?)),
+        #[cfg(not(feature = "onnx"))]
+        EmbeddingProviderType::Onnx => Err(AppError::Provider(
+            "ONNX embeddings are not available in this build (enable the `onnx` feature)"
+                .to_string(),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cosine_similarity() {
+        assert!((cosine_similarity(&[1.0, 0.0], &[2.0, 0.0]) - 1.0).abs() < 1e-6);
+        assert!(cosine_similarity(&[1.0, 0.0], &[0.0, 3.0]).abs() < 1e-6);
+        assert_eq!(cosine_similarity(&[0.0, 0.0], &[1.0, 1.0]), 0.0);
+        assert_eq!(cosine_similarity(&[1.0], &[1.0, 1.0]), 0.0);
+    }
+
+    #[test]
+    fn test_mean_pool_skips_padding() {
+        let hidden = [1.0, 2.0, 3.0, 4.0, 100.0, 100.0];
+        assert_eq!(mean_pool(&hidden, &[1, 1, 0], 2), [2.0, 3.0]);
+    }
+
+    #[test]
+    fn test_parse_openai_response_order() {
+        let mut response: OpenAiEmbedResponse = serde_json::from_str(
+            r#"{"data": [{"index": 1, "embedding": [0.5]}, {"index": 0, "embedding": [0.1]}]}"#,
+        )
+        .unwrap();
+        response.data.sort_by_key(|e| e.index);
+        assert_eq!(response.data[0].embedding, [0.1]);
+    }
+
+    #[test]
+    fn test_local_only_blocks_openai() {
+        assert!(
+            create_embedding_provider(&EmbeddingProviderType::OpenAI, "m", Some("k"), true)
+                .is_err()
+        );
+        assert!(create_embedding_provider(&EmbeddingProviderType::Ollama, "m", None, true).is_ok());
+    }
+}
@@ -2,6 +2,7 @@
 
 use crate::error::{AppError, Result};
 use crate::modes::LlmProvider as LlmProviderType;
+use crate::providers::{http_client, send_with_retry, warm_connection, CallInfo, CallRecorder};
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 
@@ -11,6 +12,16 @@ pub trait LlmProvider: Send + Sync {
     /// Generate a completion from the given prompt
     async fn complete(&self, prompt: &str) -> Result<String>;
 
+    /// Prepare for a completion that follows shortly, e.g. while the user is still speaking
+    async fn warm_up(&self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Endpoint and retries of the last completion, if the provider tracks them
+    fn last_call(&self) -> Option<CallInfo> {
+        None
+    }
+
     /// Get the provider name
     fn name(&self) -> &str;
 }
@@ -19,16 +30,64 @@ pub trait LlmProvider: Send + Sync {
 pub struct OllamaProvider {
     base_url: String,
     model: String,
+    keep_alive: Option<String>,
+    last_call: CallRecorder,
 }
 
 impl OllamaProvider {
     pub fn new(model: String) -> Self {
         Self {
-            base_url: std::env::var("OLLAMA_HOST")
-                .unwrap_or_else(|_| "http://localhost:11434".to_string()),
+            base_url: ollama_base_url(),
             model,
+            keep_alive: None,
+            last_call: CallRecorder::default(),
         }
     }
+
+    /// How long Ollama keeps the model loaded after a request (e.g. "30m", "-1")
+    pub fn with_keep_alive(mut self, keep_alive: Option<&str>) -> Self {
+        self.keep_alive = keep_alive
+            .map(str::trim)
+            .filter(|k| !k.is_empty())
+            .map(str::to_string);
+        self
+    }
+
+    /// Load the model into memory without generating anything
+    pub async fn preload(&self) -> Result<()> {
+        let client = http_client();
+        let url = format!("{}/api/generate", self.base_url);
+
+        // An empty prompt makes Ollama load the model and return immediately
+        let request = OllamaRequest {
+            model: self.model.clone(),
+            prompt: String::new(),
+            stream: false,
+            keep_alive: self.keep_alive.clone(),
+        };
+
+        let response = client
+            .post(&url)
+            .json(&request)
+            .timeout(std::time::Duration::from_secs(120))
+            .send()
+            .await
+            .map_err(|e| AppError::Provider(format!("Ollama request failed: {}", e)))?;
+
+        if !response.status().is_success() {
+            return Err(AppError::Provider(format!(
+                "Ollama failed to load {} ({})",
+                self.model,
+                response.status()
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+pub(crate) fn ollama_base_url() -> String {
+    std::env::var("OLLAMA_HOST").unwrap_or_else(|_| "http://localhost:11434".to_string())
 }
 
 #[derive(Serialize)]
@@ -36,6 +95,8 @@ struct OllamaRequest {
     model: String,
     prompt: String,
     stream: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    keep_alive: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -46,22 +107,26 @@ struct OllamaResponse {
 #[async_trait]
 impl LlmProvider for OllamaProvider {
     async fn complete(&self, prompt: &str) -> Result<String> {
-        let client = reqwest::Client::new();
+        let client = http_client();
         let url = format!("{}/api/generate", self.base_url);
 
         let request = OllamaRequest {
             model: self.model.clone(),
             prompt: prompt.to_string(),
             stream: false,
+            keep_alive: self.keep_alive.clone(),
         };
 
-        let response = client
-            .post(&url)
-            .json(&request)
-            .timeout(std::time::Duration::from_secs(120))
-            .send()
-            .await
-            .map_err(|e| AppError::Provider(format!("Ollama request failed: {}", e)))?;
+        let (response, retries) = send_with_retry(|| {
+            client
+                .post(&url)
+                .json(&request)
+                .timeout(std::time::Duration::from_secs(120))
+        })
+        .await;
+        self.last_call.record(&url, retries);
+        let response =
+            response.map_err(|e| AppError::Provider(format!("Ollama request failed: {}", e)))?;
 
         if !response.status().is_success() {
             let status = response.status();
@@ -80,20 +145,79 @@ impl LlmProvider for OllamaProvider {
         Ok(result.response.trim().to_string())
     }
 
+    async fn warm_up(&self) -> Result<()> {
+        self.preload().await
+    }
+
+    fn last_call(&self) -> Option<CallInfo> {
+        self.last_call.last()
+    }
+
     fn name(&self) -> &str {
         "Ollama"
     }
 }
 
+/// A model installed in the local Ollama instance
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OllamaModel {
+    pub name: String,
+    #[serde(default)]
+    pub size: u64,
+    #[serde(default)]
+    pub modified_at: String,
+}
+
+#[derive(Deserialize)]
+struct OllamaTagsResponse {
+    #[serde(default)]
+    models: Vec<OllamaModel>,
+}
+
+/// List models available in the local Ollama instance
+pub async fn list_ollama_models() -> Result<Vec<OllamaModel>> {
+    let url = format!("{}/api/tags", ollama_base_url());
+
+    let response = reqwest::Client::new()
+        .get(&url)
+        .timeout(std::time::Duration::from_secs(10))
+        .send()
+        .await
+        .map_err(|e| AppError::Provider(format!("Ollama is not reachable: {}", e)))?;
+
+    if !response.status().is_success() {
+        return Err(AppError::Provider(format!(
+            "Ollama error ({})",
+            response.status()
+        )));
+    }
+
+    let tags: OllamaTagsResponse = response
+        .json()
+        .await
+        .map_err(|e| AppError::Provider(format!("Failed to parse Ollama model list: {}", e)))?;
+
+    let mut models = tags.models;
+    models.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(models)
+}
+
+const OPENAI_URL: &str = "https://api.openai.com/v1/chat/completions";
+
 /// OpenAI provider
 pub struct OpenAiProvider {
     api_key: String,
     model: String,
+    last_call: CallRecorder,
 }
 
 impl OpenAiProvider {
     pub fn new(api_key: String, model: String) -> Self {
-        Self { api_key, model }
+        Self {
+            api_key,
+            model,
+            last_call: CallRecorder::default(),
+        }
     }
 }
 
@@ -128,8 +252,8 @@ struct OpenAiMessageResponse {
 #[async_trait]
 impl LlmProvider for OpenAiProvider {
     async fn complete(&self, prompt: &str) -> Result<String> {
-        let client = reqwest::Client::new();
-        let url = "https://api.openai.com/v1/chat/completions";
+        let client = http_client();
+        let url = OPENAI_URL;
 
         let request = OpenAiRequest {
             model: self.model.clone(),
@@ -140,15 +264,18 @@ impl LlmProvider for OpenAiProvider {
             max_tokens: 2048,
         };
 
-        let response = client
-            .post(url)
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .header("Content-Type", "application/json")
-            .json(&request)
-            .timeout(std::time::Duration::from_secs(60))
-            .send()
-            .await
-            .map_err(|e| AppError::Provider(format!("OpenAI request failed: {}", e)))?;
+        let (response, retries) = send_with_retry(|| {
+            client
+                .post(url)
+                .header("Authorization", format!("Bearer {}", self.api_key))
+                .header("Content-Type", "application/json")
+                .json(&request)
+                .timeout(std::time::Duration::from_secs(60))
+        })
+        .await;
+        self.last_call.record(url, retries);
+        let response =
+            response.map_err(|e| AppError::Provider(format!("OpenAI request failed: {}", e)))?;
 
         if !response.status().is_success() {
             let status = response.status();
@@ -171,20 +298,37 @@ impl LlmProvider for OpenAiProvider {
             .ok_or_else(|| AppError::Provider("No response from OpenAI".to_string()))
     }
 
+    async fn warm_up(&self) -> Result<()> {
+        warm_connection(OPENAI_URL)
+            .await
+            .map_err(|e| AppError::Provider(format!("OpenAI not reachable: {}", e)))
+    }
+
+    fn last_call(&self) -> Option<CallInfo> {
+        self.last_call.last()
+    }
+
     fn name(&self) -> &str {
         "OpenAI"
     }
 }
 
+const ANTHROPIC_URL: &str = "https://api.anthropic.com/v1/messages";
+
 /// Anthropic Claude provider
 pub struct AnthropicProvider {
     api_key: String,
     model: String,
+    last_call: CallRecorder,
 }
 
 impl AnthropicProvider {
     pub fn new(api_key: String, model: String) -> Self {
-        Self { api_key, model }
+        Self {
+            api_key,
+            model,
+            last_call: CallRecorder::default(),
+        }
     }
 }
 
@@ -214,8 +358,8 @@ struct AnthropicContent {
 #[async_trait]
 impl LlmProvider for AnthropicProvider {
     async fn complete(&self, prompt: &str) -> Result<String> {
-        let client = reqwest::Client::new();
-        let url = "https://api.anthropic.com/v1/messages";
+        let client = http_client();
+        let url = ANTHROPIC_URL;
 
         let request = AnthropicRequest {
             model: self.model.clone(),
@@ -226,16 +370,19 @@ impl LlmProvider for AnthropicProvider {
             }],
         };
 
-        let response = client
-            .post(url)
-            .header("x-api-key", &self.api_key)
-            .header("anthropic-version", "2023-06-01")
-            .header("Content-Type", "application/json")
-            .json(&request)
-            .timeout(std::time::Duration::from_secs(60))
-            .send()
-            .await
-            .map_err(|e| AppError::Provider(format!("Anthropic request failed: {}", e)))?;
+        let (response, retries) = send_with_retry(|| {
+            client
+                .post(url)
+                .header("x-api-key", &self.api_key)
+                .header("anthropic-version", "2023-06-01")
+                .header("Content-Type", "application/json")
+                .json(&request)
+                .timeout(std::time::Duration::from_secs(60))
+        })
+        .await;
+        self.last_call.record(url, retries);
+        let response =
+            response.map_err(|e| AppError::Provider(format!("Anthropic request failed: {}", e)))?;
 
         if !response.status().is_success() {
             let status = response.status();
@@ -258,6 +405,16 @@ impl LlmProvider for AnthropicProvider {
             .ok_or_else(|| AppError::Provider("No response from Anthropic".to_string()))
     }
 
+    async fn warm_up(&self) -> Result<()> {
+        warm_connection(ANTHROPIC_URL)
+            .await
+            .map_err(|e| AppError::Provider(format!("Anthropic not reachable: {}", e)))
+    }
+
+    fn last_call(&self) -> Option<CallInfo> {
+        self.last_call.last()
+    }
+
     fn name(&self) -> &str {
         "Anthropic"
     }
@@ -268,9 +425,20 @@ pub fn create_llm_provider(
     provider_type: &LlmProviderType,
     model: &str,
     api_key: Option<&str>,
+    local_only: bool,
+    ollama_keep_alive: Option<&str>,
 ) -> Result<Box<dyn LlmProvider>> {
+    if local_only && !provider_type.is_local() {
+        return Err(AppError::LocalOnly(format!(
+            "{:?} AI processing requires network access",
+            provider_type
+        )));
+    }
+
     match provider_type {
-        LlmProviderType::Ollama => Ok(Box::new(OllamaProvider::new(model.to_string()))),
+        LlmProviderType::Ollama => Ok(Box::new(
+            OllamaProvider::new(model.to_string()).with_keep_alive(ollama_keep_alive),
+        )),
         LlmProviderType::OpenAI => {
             let key = api_key
                 .ok_or_else(|| AppError::Provider("OpenAI API key required".to_string()))?;
@@ -302,4 +470,23 @@ mod tests {
         let provider = OllamaProvider::new("llama3.2".to_string());
         assert_eq!(provider.name(), "Ollama");
     }
+
+    #[test]
+    fn test_ollama_keep_alive() {
+        let provider = OllamaProvider::new("llama3.2".to_string()).with_keep_alive(Some(" 30m "));
+        assert_eq!(provider.keep_alive.as_deref(), Some("30m"));
+
+        let provider = OllamaProvider::new("llama3.2".to_string()).with_keep_alive(Some(""));
+        assert!(provider.keep_alive.is_none());
+    }
+
+    #[test]
+    fn test_parse_ollama_tags() {
+        let tags: OllamaTagsResponse = serde_json::from_str(
+            r#"{"models": [{"name": "llama3.2:latest", "size": 2019393189, "modified_at": "2024-10-01T12:00:00Z", "digest": "a80c"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(tags.models.len(), 1);
+        assert_eq!(tags.models[0].name, "llama3.2:latest");
+    }
 }
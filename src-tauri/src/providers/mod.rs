@@ -1,7 +1,168 @@
-//! Provider interfaces for STT and LLM services
+//! Provider interfaces for STT, LLM and embedding services
 
+pub mod embeddings;
 pub mod llm;
+pub mod streaming;
 pub mod stt;
 
+pub use embeddings::EmbeddingProvider;
 pub use llm::LlmProvider;
 pub use stt::SttProvider;
+
+use reqwest::{Client, RequestBuilder, Response, StatusCode, Url};
+use std::net::IpAddr;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+/// How often a request is retried after a transient failure
+const MAX_RETRIES: u32 = 2;
+
+/// Delay before the first retry, doubled for each further one
+const RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// HTTP client shared by the providers, so connections are reused between requests
+pub fn http_client() -> &'static Client {
+    static CLIENT: OnceLock<Client> = OnceLock::new();
+    CLIENT.get_or_init(Client::new)
+}
+
+/// Whether `url` points at this machine or the local network
+///
+/// Loopback, private and link-local addresses count as local, and so do
+/// `localhost` and `.local` names. Self-hosted services on a public host
+/// don't, since their traffic leaves the network.
+pub fn is_local_url(url: &str) -> bool {
+    let url = match Url::parse(url.trim()) {
+        Ok(url) => url,
+        Err(_) => return false,
+    };
+    let host = match url.host_str() {
+        Some(host) => host.trim_start_matches('[').trim_end_matches(']'),
+        None => return false,
+    };
+    let host = host.to_lowercase();
+    match host.parse::<IpAddr>() {
+        Ok(IpAddr::V4(ip)) => ip.is_loopback() || ip.is_private() || ip.is_link_local(),
+        Ok(IpAddr::V6(ip)) => {
+            let prefix = ip.segments()[0];
+            // Unique local (fc00::/7) and link-local (fe80::/10) addresses
+            ip.is_loopback() || (prefix & 0xfe00) == 0xfc00 || (prefix & 0xffc0) == 0xfe80
+        }
+        Err(_) => host == "localhost" || host.ends_with(".localhost") || host.ends_with(".local"),
+    }
+}
+
+/// Open a connection to `url` ahead of the first real request
+///
+/// The connection stays in the shared client's pool, so the request after
+/// the recording skips the TCP and TLS handshakes.
+pub async fn warm_connection(url: &str) -> reqwest::Result<()> {
+    http_client()
+        .head(url)
+        .timeout(Duration::from_secs(10))
+        .send()
+        .await
+        .map(|_| ())
+}
+
+/// Endpoint that served a provider request and how often it was retried
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CallInfo {
+    pub endpoint: String,
+    pub retries: u32,
+}
+
+/// Remembers the last request a provider sent
+#[derive(Debug, Default)]
+pub struct CallRecorder(Mutex<Option<CallInfo>>);
+
+impl CallRecorder {
+    pub fn record(&self, endpoint: &str, retries: u32) {
+        if let Ok(mut last) = self.0.lock() {
+            *last = Some(CallInfo {
+                endpoint: endpoint.to_string(),
+                retries,
+            });
+        }
+    }
+
+    pub fn last(&self) -> Option<CallInfo> {
+        self.0.lock().ok().and_then(|last| last.clone())
+    }
+}
+
+/// Whether a server response is worth retrying (rate limits and server errors)
+pub fn is_transient_status(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+/// Send a request, retrying connection failures and transient server errors
+///
+/// `build` creates the request for every attempt. Returns the final result
+/// and the number of retries it took. Timeouts are not retried, as the server
+/// may still be working on the request.
+pub async fn send_with_retry<F>(build: F) -> (reqwest::Result<Response>, u32)
+where
+    F: Fn() -> RequestBuilder,
+{
+    let mut retries = 0;
+    loop {
+        let result = build().send().await;
+        let transient = match &result {
+            Ok(response) => is_transient_status(response.status()),
+            Err(e) => e.is_connect(),
+        };
+        if !transient || retries == MAX_RETRIES {
+            return (result, retries);
+        }
+
+        let delay = RETRY_DELAY * 2u32.pow(retries);
+        retries += 1;
+        match &result {
+            Ok(response) => log::warn!("Server returned {}, retrying", response.status()),
+            Err(e) => log::warn!("Request failed ({}), retrying", e),
+        }
+        tokio::time::sleep(delay).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_transient_status() {
+        assert!(is_transient_status(StatusCode::TOO_MANY_REQUESTS));
+        assert!(is_transient_status(StatusCode::BAD_GATEWAY));
+        assert!(is_transient_status(StatusCode::SERVICE_UNAVAILABLE));
+        assert!(!is_transient_status(StatusCode::OK));
+        assert!(!is_transient_status(StatusCode::UNAUTHORIZED));
+        assert!(!is_transient_status(StatusCode::BAD_REQUEST));
+    }
+
+    #[test]
+    fn test_is_local_url() {
+        assert!(is_local_url("http://localhost:8008"));
+        assert!(is_local_url("https://192.168.1.20/dav/"));
+        assert!(is_local_url("ws://[::1]:9090"));
+        assert!(is_local_url("https://nas.local"));
+        assert!(!is_local_url("https://matrix.org"));
+        assert!(!is_local_url("ws://203.0.113.5:9090"));
+        assert!(!is_local_url(""));
+    }
+
+    #[test]
+    fn test_call_recorder() {
+        let recorder = CallRecorder::default();
+        assert_eq!(recorder.last(), None);
+        recorder.record("http://localhost:11434/api/generate", 1);
+        recorder.record("http://localhost:11434/api/generate", 0);
+        assert_eq!(
+            recorder.last(),
+            Some(CallInfo {
+                endpoint: "http://localhost:11434/api/generate".to_string(),
+                retries: 0,
+            })
+        );
+    }
+}
@@ -0,0 +1,594 @@
+//! Streaming speech-to-text over WebSocket
+//!
+//! Audio frames are sent to the server while recording and interim and final
+//! results come back with sub-second latency. Supported are Deepgram's live
+//! API, OpenAI's realtime transcription and WhisperLive servers.
+
+use crate::database::Segment;
+use crate::error::{AppError, Result};
+use crate::providers::is_local_url;
+use base64::Engine;
+use futures_util::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::HeaderValue;
+use tokio_tungstenite::tungstenite::Message;
+
+/// Deepgram live transcription endpoint
+const DEEPGRAM_URL: &str = "wss://api.deepgram.com/v1/listen";
+
+/// OpenAI realtime transcription endpoint
+const OPENAI_REALTIME_URL: &str = "wss://api.openai.com/v1/realtime?intent=transcription";
+
+/// Sample rate of the PCM audio the OpenAI realtime API expects
+const OPENAI_SAMPLE_RATE: usize = 24000;
+
+/// Message telling a WhisperLive server that no more audio follows
+const WHISPER_LIVE_END: &[u8] = b"END_OF_AUDIO";
+
+/// Streaming STT provider options
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum StreamingProviderType {
+    Deepgram,
+    #[serde(rename = "openai")]
+    OpenAI,
+    WhisperLive,
+}
+
+impl StreamingProviderType {
+    /// Whether the audio stays on the local network: only for a WhisperLive
+    /// server on this machine or a private address
+    pub fn is_local(&self, whisper_live_url: &str) -> bool {
+        matches!(self, StreamingProviderType::WhisperLive) && is_local_url(whisper_live_url)
+    }
+
+    /// Model used when none is configured
+    pub fn default_model(&self) -> &'static str {
+        match self {
+            StreamingProviderType::Deepgram => "nova-2",
+            StreamingProviderType::OpenAI => "gpt-4o-transcribe",
+            StreamingProviderType::WhisperLive => "small",
+        }
+    }
+}
+
+/// Connection settings for a streaming session
+#[derive(Debug, Clone)]
+pub struct StreamConfig {
+    pub provider: StreamingProviderType,
+    /// Server URL (WhisperLive only)
+    pub url: String,
+    pub api_key: Option<String>,
+    pub model: String,
+    pub language: String,
+    /// Terms to favor (Deepgram keywords, OpenAI prompt)
+    pub keywords: Vec<String>,
+}
+
+/// Result received from the server
+#[derive(Debug, Clone, PartialEq)]
+pub enum StreamEvent {
+    /// Hypothesis for the speech in progress; replaced by later events
+    Interim(String),
+    /// Finalized segment, timed from the start of the stream
+    Final(Segment),
+}
+
+/// Open streaming session
+///
+/// Send 16 kHz mono samples through `audio` and drop it when the recording
+/// ends; `events` closes once the server has delivered the last results.
+pub struct StreamingSession {
+    pub audio: mpsc::Sender<Vec<f32>>,
+    pub events: mpsc::Receiver<StreamEvent>,
+}
+
+/// Deepgram URL with the stream parameters in the query
+pub fn deepgram_url(model: &str, language: &str, keywords: &[String]) -> String {
+    let language = match language {
+        "" | "auto" => "detect_language=true".to_string(),
+        language => format!("language={}", language),
+    };
+    let url = format!(
+        "{}?encoding=linear16&sample_rate=16000&channels=1&interim_results=true&punctuate=true&model={}&{}",
+        DEEPGRAM_URL, model, language
+    );
+    if keywords.is_empty() {
+        return url;
+    }
+
+    // Nova-3 takes key terms; older models take keywords
+    let param = if model.starts_with("nova-3") {
+        "keyterm"
+    } else {
+        "keywords"
+    };
+    match reqwest::Url::parse(&url) {
+        Ok(mut url) => {
+            url.query_pairs_mut()
+                .extend_pairs(keywords.iter().map(|k| (param, k)));
+            url.to_string()
+        }
+        Err(_) => url,
+    }
+}
+
+/// Encode samples as 16-bit little-endian PCM
+fn to_pcm16(samples: &[f32]) -> Vec<u8> {
+    samples
+        .iter()
+        .flat_map(|s| ((s.clamp(-1.0, 1.0) * i16::MAX as f32) as i16).to_le_bytes())
+        .collect()
+}
+
+/// Resample 16 kHz audio to the 24 kHz the OpenAI realtime API expects
+fn resample_24k(samples: &[f32]) -> Vec<f32> {
+    let ratio = crate::audio::WHISPER_SAMPLE_RATE as f32 / OPENAI_SAMPLE_RATE as f32;
+    let len = samples.len() * OPENAI_SAMPLE_RATE / crate::audio::WHISPER_SAMPLE_RATE as usize;
+    (0..len)
+        .map(|i| {
+            let pos = i as f32 * ratio;
+            let index = pos as usize;
+            let next = samples.get(index + 1).unwrap_or(&samples[index]);
+            samples[index] + (next - samples[index]) * pos.fract()
+        })
+        .collect()
+}
+
+/// Encode samples as 32-bit little-endian floats
+fn to_f32le(samples: &[f32]) -> Vec<u8> {
+    samples.iter().flat_map(|s| s.to_le_bytes()).collect()
+}
+
+#[derive(Deserialize)]
+struct DeepgramResult {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    start: f64,
+    #[serde(default)]
+    duration: f64,
+    #[serde(default)]
+    is_final: bool,
+    channel: Option<DeepgramChannel>,
+}
+
+#[derive(Deserialize)]
+struct DeepgramChannel {
+    alternatives: Vec<DeepgramAlternative>,
+}
+
+#[derive(Deserialize)]
+struct DeepgramAlternative {
+    transcript: String,
+    confidence: Option<f32>,
+}
+
+/// Parse a Deepgram message; metadata and empty results yield nothing
+pub fn parse_deepgram(message: &str) -> Option<StreamEvent> {
+    let result: DeepgramResult = serde_json::from_str(message).ok()?;
+    if result.kind != "Results" {
+        return None;
+    }
+    let alternative = result.channel?.alternatives.into_iter().next()?;
+    if alternative.transcript.trim().is_empty() {
+        return None;
+    }
+
+    if !result.is_final {
+        return Some(StreamEvent::Interim(alternative.transcript));
+    }
+    Some(StreamEvent::Final(Segment {
+        start_ms: (result.start * 1000.0) as u64,
+        end_ms: ((result.start + result.duration) * 1000.0) as u64,
+        text: alternative.transcript,
+        confidence: alternative.confidence,
+        speaker: None,
+    }))
+}
+
+#[derive(Deserialize)]
+struct WhisperLiveMessage {
+    #[serde(default)]
+    segments: Vec<WhisperLiveSegment>,
+}
+
+#[derive(Deserialize)]
+struct WhisperLiveSegment {
+    start: String,
+    end: String,
+    text: String,
+    #[serde(default)]
+    completed: bool,
+}
+
+/// Parse a WhisperLive message
+///
+/// The server repeats recent segments in every message, so completed segments
+/// are only reported once: `finalized_ms` tracks the end of the last one.
+pub fn parse_whisper_live(message: &str, finalized_ms: &mut u64) -> Vec<StreamEvent> {
+    let message: WhisperLiveMessage = match serde_json::from_str(message) {
+        Ok(message) => message,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut events = Vec::new();
+    for segment in message.segments {
+        let start_ms = (segment.start.parse::<f64>().unwrap_or(0.0) * 1000.0) as u64;
+        let end_ms = (segment.end.parse::<f64>().unwrap_or(0.0) * 1000.0) as u64;
+        if segment.text.trim().is_empty() || (segment.completed && end_ms <= *finalized_ms) {
+            continue;
+        }
+
+        if segment.completed {
+            *finalized_ms = end_ms;
+            events.push(StreamEvent::Final(Segment {
+                start_ms,
+                end_ms,
+                text: segment.text,
+                confidence: None,
+                speaker: None,
+            }));
+        } else {
+            events.push(StreamEvent::Interim(segment.text));
+        }
+    }
+    events
+}
+
+/// Progress of an OpenAI realtime transcription session
+///
+/// Transcripts arrive per speech turn ("item"); timing comes from the voice
+/// activity events that precede them.
+#[derive(Debug, Default)]
+pub struct RealtimeState {
+    interim: String,
+    starts: HashMap<String, u64>,
+    ends: HashMap<String, u64>,
+    pending: HashSet<String>,
+}
+
+impl RealtimeState {
+    /// Whether every committed turn has been transcribed
+    pub fn is_idle(&self) -> bool {
+        self.pending.is_empty()
+    }
+}
+
+/// Parse an OpenAI realtime transcription event
+pub fn parse_realtime(message: &str, state: &mut RealtimeState) -> Option<StreamEvent> {
+    let event: serde_json::Value = serde_json::from_str(message).ok()?;
+    let item_id = event["item_id"].as_str().unwrap_or_default().to_string();
+
+    match event["type"].as_str()? {
+        "input_audio_buffer.speech_started" => {
+            let start = event["audio_start_ms"].as_u64().unwrap_or(0);
+            state.starts.insert(item_id, start);
+            None
+        }
+        "input_audio_buffer.speech_stopped" => {
+            let end = event["audio_end_ms"].as_u64().unwrap_or(0);
+            state.ends.insert(item_id, end);
+            None
+        }
+        "input_audio_buffer.committed" => {
+            state.pending.insert(item_id);
+            None
+        }
+        "conversation.item.input_audio_transcription.delta" => {
+            state.interim.push_str(event["delta"].as_str()?);
+            Some(StreamEvent::Interim(state.interim.clone()))
+        }
+        "conversation.item.input_audio_transcription.completed" => {
+            state.pending.remove(&item_id);
+            state.interim.clear();
+            let start_ms = state.starts.remove(&item_id).unwrap_or(0);
+            let end_ms = state.ends.remove(&item_id).unwrap_or(start_ms);
+            let text = event["transcript"].as_str()?;
+            if text.trim().is_empty() {
+                return None;
+            }
+            Some(StreamEvent::Final(Segment {
+                start_ms,
+                end_ms,
+                text: text.to_string(),
+                confidence: None,
+                speaker: None,
+            }))
+        }
+        "conversation.item.input_audio_transcription.failed" | "error" => {
+            state.pending.remove(&item_id);
+            state.interim.clear();
+            log::warn!("OpenAI realtime transcription error: {}", event["error"]);
+            None
+        }
+        _ => None,
+    }
+}
+
+/// First message of an OpenAI realtime session, configuring transcription
+fn realtime_session(model: &str, language: &str, keywords: &[String]) -> serde_json::Value {
+    let mut transcription = serde_json::json!({ "model": model });
+    if !matches!(language, "" | "auto") {
+        transcription["language"] = language.into();
+    }
+    if !keywords.is_empty() {
+        transcription["prompt"] = format!("Vocabulary: {}.", keywords.join(", ")).into();
+    }
+    serde_json::json!({
+        "type": "transcription_session.update",
+        "session": {
+            "input_audio_format": "pcm16",
+            "input_audio_transcription": transcription,
+            "turn_detection": { "type": "server_vad" },
+        },
+    })
+}
+
+/// Wrap audio for the OpenAI realtime input buffer
+fn realtime_audio(samples: &[f32]) -> String {
+    let audio = base64::engine::general_purpose::STANDARD.encode(to_pcm16(&resample_24k(samples)));
+    serde_json::json!({ "type": "input_audio_buffer.append", "audio": audio }).to_string()
+}
+
+fn ws_error(e: impl std::fmt::Display) -> AppError {
+    AppError::Provider(format!("Streaming STT connection failed: {}", e))
+}
+
+/// Connect to the streaming server and start exchanging audio and results
+pub async fn connect(config: &StreamConfig) -> Result<StreamingSession> {
+    let model = match config.model.trim() {
+        "" => config.provider.default_model(),
+        model => model,
+    };
+    let mut request = match config.provider {
+        StreamingProviderType::Deepgram => deepgram_url(model, &config.language, &config.keywords),
+        StreamingProviderType::OpenAI => OPENAI_REALTIME_URL.to_string(),
+        StreamingProviderType::WhisperLive => config.url.clone(),
+    }
+    .into_client_request()
+    .map_err(ws_error)?;
+
+    let authorization = match (&config.provider, config.api_key.as_deref()) {
+        (StreamingProviderType::WhisperLive, _) => None,
+        (StreamingProviderType::Deepgram, Some(key)) => Some(format!("Token {}", key)),
+        (StreamingProviderType::OpenAI, Some(key)) => Some(format!("Bearer {}", key)),
+        (provider, None) => {
+            return Err(AppError::Config(format!("{:?} API key not set", provider)));
+        }
+    };
+    if let Some(authorization) = authorization {
+        let header = HeaderValue::from_str(&authorization).map_err(ws_error)?;
+        request.headers_mut().insert("Authorization", header);
+    }
+    if config.provider == StreamingProviderType::OpenAI {
+        request
+            .headers_mut()
+            .insert("OpenAI-Beta", HeaderValue::from_static("realtime=v1"));
+    }
+
+    let (socket, _) = tokio_tungstenite::connect_async(request)
+        .await
+        .map_err(ws_error)?;
+    let (mut sink, mut stream) = socket.split();
+
+    let options = match config.provider {
+        StreamingProviderType::Deepgram => None,
+        StreamingProviderType::OpenAI => {
+            Some(realtime_session(model, &config.language, &config.keywords))
+        }
+        StreamingProviderType::WhisperLive => Some(serde_json::json!({
+            "uid": uuid::Uuid::new_v4().to_string(),
+            "language": config.language,
+            "task": "transcribe",
+            "model": model,
+            "use_vad": true,
+        })),
+    };
+    if let Some(options) = options {
+        sink.send(Message::Text(options.to_string()))
+            .await
+            .map_err(ws_error)?;
+    }
+
+    let (audio_tx, mut audio_rx) = mpsc::channel::<Vec<f32>>(64);
+    let (event_tx, event_rx) = mpsc::channel(64);
+    // The OpenAI server keeps the connection open, so the reader stops itself
+    // once the audio has ended and the last turn is transcribed
+    let audio_done = Arc::new(AtomicBool::new(false));
+
+    let provider = config.provider.clone();
+    let done = audio_done.clone();
+    tokio::spawn(async move {
+        while let Some(samples) = audio_rx.recv().await {
+            let frame = match provider {
+                StreamingProviderType::Deepgram => Message::Binary(to_pcm16(&samples)),
+                StreamingProviderType::OpenAI => Message::Text(realtime_audio(&samples)),
+                StreamingProviderType::WhisperLive => Message::Binary(to_f32le(&samples)),
+            };
+            if let Err(e) = sink.send(frame).await {
+                log::warn!("Failed to send audio to streaming STT: {}", e);
+                return;
+            }
+        }
+
+        let end = match provider {
+            StreamingProviderType::Deepgram => Message::Text(r#"{"type":"CloseStream"}"#.into()),
+            StreamingProviderType::OpenAI => {
+                Message::Text(r#"{"type":"input_audio_buffer.commit"}"#.into())
+            }
+            StreamingProviderType::WhisperLive => Message::Binary(WHISPER_LIVE_END.into()),
+        };
+        done.store(true, Ordering::SeqCst);
+        let _ = sink.send(end).await;
+    });
+
+    let provider = config.provider.clone();
+    tokio::spawn(async move {
+        let mut finalized_ms = 0;
+        let mut realtime = RealtimeState::default();
+        while let Some(message) = stream.next().await {
+            let text = match message {
+                Ok(Message::Text(text)) => text,
+                Ok(Message::Close(_)) => break,
+                Ok(_) => continue,
+                Err(e) => {
+                    log::warn!("Streaming STT connection lost: {}", e);
+                    break;
+                }
+            };
+
+            let events = match provider {
+                StreamingProviderType::Deepgram => parse_deepgram(&text).into_iter().collect(),
+                StreamingProviderType::OpenAI => {
+                    parse_realtime(&text, &mut realtime).into_iter().collect()
+                }
+                StreamingProviderType::WhisperLive => parse_whisper_live(&text, &mut finalized_ms),
+            };
+            for event in events {
+                if event_tx.send(event).await.is_err() {
+                    return;
+                }
+            }
+
+            if provider == StreamingProviderType::OpenAI
+                && audio_done.load(Ordering::SeqCst)
+                && realtime.is_idle()
+            {
+                break;
+            }
+        }
+    });
+
+    Ok(StreamingSession {
+        audio: audio_tx,
+        events: event_rx,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_deepgram() {
+        let interim = r#"{"type":"Results","start":1.5,"duration":0.8,"is_final":false,
+            "channel":{"alternatives":[{"transcript":"hello wor","confidence":0.7}]}}"#;
+        assert_eq!(
+            parse_deepgram(interim),
+            Some(StreamEvent::Interim("hello wor".to_string()))
+        );
+
+        let last = r#"{"type":"Results","start":1.5,"duration":1.0,"is_final":true,
+            "channel":{"alternatives":[{"transcript":"Hello world.","confidence":0.9}]}}"#;
+        match parse_deepgram(last) {
+            Some(StreamEvent::Final(segment)) => {
+                assert_eq!((segment.start_ms, segment.end_ms), (1500, 2500));
+                assert_eq!(segment.text, "Hello world.");
+            }
+            other => panic!("unexpected event: {:?}", other),
+        }
+
+        assert_eq!(parse_deepgram(r#"{"type":"Metadata"}"#), None);
+        let silence = r#"{"type":"Results","is_final":true,
+            "channel":{"alternatives":[{"transcript":""}]}}"#;
+        assert_eq!(parse_deepgram(silence), None);
+    }
+
+    #[test]
+    fn test_parse_whisper_live_reports_segments_once() {
+        let mut finalized_ms = 0;
+        let first = r#"{"uid":"x","segments":[
+            {"start":"0.000","end":"2.000","text":"First sentence.","completed":true},
+            {"start":"2.000","end":"3.100","text":"Second","completed":false}]}"#;
+        let events = parse_whisper_live(first, &mut finalized_ms);
+        assert_eq!(events.len(), 2);
+        assert!(matches!(&events[0], StreamEvent::Final(s) if s.end_ms == 2000));
+        assert_eq!(events[1], StreamEvent::Interim("Second".to_string()));
+
+        let second = r#"{"uid":"x","segments":[
+            {"start":"0.000","end":"2.000","text":"First sentence.","completed":true},
+            {"start":"2.000","end":"3.500","text":"Second sentence.","completed":true}]}"#;
+        let events = parse_whisper_live(second, &mut finalized_ms);
+        assert_eq!(events.len(), 1);
+        assert!(matches!(&events[0], StreamEvent::Final(s) if s.text == "Second sentence."));
+
+        assert!(parse_whisper_live(r#"{"message":"SERVER_READY"}"#, &mut finalized_ms).is_empty());
+    }
+
+    #[test]
+    fn test_parse_realtime() {
+        let mut state = RealtimeState::default();
+        let events = [
+            r#"{"type":"input_audio_buffer.speech_started","item_id":"a","audio_start_ms":1200}"#,
+            r#"{"type":"input_audio_buffer.speech_stopped","item_id":"a","audio_end_ms":2600}"#,
+            r#"{"type":"input_audio_buffer.committed","item_id":"a"}"#,
+        ];
+        for event in events {
+            assert_eq!(parse_realtime(event, &mut state), None);
+        }
+        assert!(!state.is_idle());
+
+        let delta = r#"{"type":"conversation.item.input_audio_transcription.delta","item_id":"a","delta":"Hel"}"#;
+        parse_realtime(delta, &mut state);
+        let delta = r#"{"type":"conversation.item.input_audio_transcription.delta","item_id":"a","delta":"lo"}"#;
+        assert_eq!(
+            parse_realtime(delta, &mut state),
+            Some(StreamEvent::Interim("Hello".to_string()))
+        );
+
+        let completed = r#"{"type":"conversation.item.input_audio_transcription.completed","item_id":"a","transcript":"Hello."}"#;
+        match parse_realtime(completed, &mut state) {
+            Some(StreamEvent::Final(segment)) => {
+                assert_eq!((segment.start_ms, segment.end_ms), (1200, 2600));
+                assert_eq!(segment.text, "Hello.");
+            }
+            other => panic!("unexpected event: {:?}", other),
+        }
+        assert!(state.is_idle());
+    }
+
+    #[test]
+    fn test_resample_24k() {
+        let resampled = resample_24k(&[0.0, 0.3]);
+        assert_eq!(resampled.len(), 3);
+        assert!((resampled[1] - 0.2).abs() < 1e-6);
+        assert_eq!(resampled[2], 0.3);
+        assert!(resample_24k(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_is_local() {
+        let whisper_live = StreamingProviderType::WhisperLive;
+        assert!(whisper_live.is_local("ws://localhost:9090"));
+        assert!(!whisper_live.is_local("wss://whisper.example.com"));
+        assert!(!StreamingProviderType::Deepgram.is_local("ws://localhost:9090"));
+    }
+
+    #[test]
+    fn test_deepgram_url() {
+        assert!(deepgram_url("nova-2", "de", &[]).ends_with("&model=nova-2&language=de"));
+        assert!(deepgram_url("nova-2", "auto", &[]).ends_with("&detect_language=true"));
+
+        let keywords = vec!["Kubernetes".to_string(), "Anna Lee".to_string()];
+        assert!(deepgram_url("nova-2", "de", &keywords)
+            .ends_with("&language=de&keywords=Kubernetes&keywords=Anna+Lee"));
+        assert!(deepgram_url("nova-3", "de", &keywords).ends_with("&keyterm=Anna+Lee"));
+
+        let session = realtime_session("gpt-4o-transcribe", "auto", &keywords);
+        assert_eq!(
+            session["session"]["input_audio_transcription"]["prompt"],
+            "Vocabulary: Kubernetes, Anna Lee."
+        );
+    }
+
+    #[test]
+    fn test_to_pcm16() {
+        assert_eq!(to_pcm16(&[0.0, 1.0, -2.0]), [0, 0, 0xff, 0x7f, 0x01, 0x80]);
+    }
+}
@@ -1,10 +1,36 @@
 //! Speech-to-Text provider implementations
 
+use crate::database::Segment;
 use crate::error::{AppError, Result};
-use crate::modes::SttProvider as SttProviderType;
+use crate::modes::{DecodingOptions, Mode, SttProvider as SttProviderType};
+use crate::providers::{http_client, send_with_retry, warm_connection, CallInfo, CallRecorder};
 use async_trait::async_trait;
-use std::path::PathBuf;
-use whisper_rs::{FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters};
+use futures_util::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+use std::ffi::{c_int, c_void};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tokio::io::AsyncWriteExt;
+use tokio_util::sync::CancellationToken;
+use whisper_rs::{
+    FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters, WhisperSysContext,
+    WhisperSysState,
+};
+
+/// Transcription result with timed segments
+#[derive(Debug, Clone, Default)]
+pub struct Transcription {
+    pub text: String,
+    pub segments: Vec<Segment>,
+    /// Spoken language, if the provider detected one
+    pub language: Option<String>,
+}
+
+/// Receives transcription progress in percent
+pub type ProgressCallback = Arc<dyn Fn(u8) + Send + Sync>;
+
+/// Awaited between the steps of a long transcription, so it can be paused
+pub type YieldPoint = Arc<dyn Fn() -> BoxFuture<'static, ()> + Send + Sync>;
 
 /// STT provider trait
 #[async_trait]
@@ -12,34 +38,121 @@ pub trait SttProvider: Send + Sync {
     /// Transcribe audio samples to text
     async fn transcribe(&self, samples: &[f32], language: Option<&str>) -> Result<String>;
 
+    /// Transcribe audio samples, keeping segment timing where the provider supports it
+    async fn transcribe_detailed(
+        &self,
+        samples: &[f32],
+        language: Option<&str>,
+    ) -> Result<Transcription> {
+        let text = self.transcribe(samples, language).await?;
+        Ok(Transcription {
+            text,
+            ..Transcription::default()
+        })
+    }
+
+    /// Report progress of the following transcriptions (ignored by providers without progress)
+    fn set_progress_callback(&mut self, _callback: ProgressCallback) {}
+
+    /// Pause between the steps of the following transcriptions (ignored by single-step providers)
+    fn set_yield_point(&mut self, _yield_point: YieldPoint) {}
+
+    /// Stop the following transcriptions when `token` fires (ignored by
+    /// providers whose requests end when the cancelled future is dropped)
+    fn set_cancellation(&mut self, _token: CancellationToken) {}
+
+    /// Prepare for a transcription that follows shortly, e.g. while the user is still speaking
+    async fn warm_up(&self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Endpoint and retries of the last transcription, if the provider tracks them
+    fn last_call(&self) -> Option<CallInfo> {
+        None
+    }
+
     /// Get the provider name
     fn name(&self) -> &str;
 }
 
+/// whisper.cpp model kept loaded between transcriptions
+static WHISPER_CONTEXT: Mutex<Option<(PathBuf, Arc<WhisperContext>)>> = Mutex::new(None);
+
+/// Load a whisper.cpp model, reusing the loaded one if it is the same file
+///
+/// Only the most recently used model stays loaded.
+fn load_context(model_path: &Path) -> Result<Arc<WhisperContext>> {
+    // Held while loading, so a transcription waits for a warm-up in progress
+    let mut loaded = WHISPER_CONTEXT.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some((path, ctx)) = loaded.as_ref() {
+        if path == model_path {
+            return Ok(ctx.clone());
+        }
+    }
+
+    let params = WhisperContextParameters::default();
+    let ctx = WhisperContext::new_with_params(&model_path.to_string_lossy(), params)
+        .map_err(|e| AppError::Transcription(format!("Failed to create context: {}", e)))?;
+    let ctx = Arc::new(ctx);
+    *loaded = Some((model_path.to_path_buf(), ctx.clone()));
+    log::info!("Loaded whisper model {}", model_path.display());
+    Ok(ctx)
+}
+
+/// whisper.cpp progress callback; `user_data` points to a `ProgressCallback`
+unsafe extern "C" fn whisper_progress(
+    _: *mut WhisperSysContext,
+    _: *mut WhisperSysState,
+    percent: c_int,
+    user_data: *mut c_void,
+) {
+    let progress = &*(user_data as *const ProgressCallback);
+    progress(percent.clamp(0, 100) as u8);
+}
+
+/// whisper.cpp abort callback; `user_data` points to a `CancellationToken`
+unsafe extern "C" fn whisper_abort(user_data: *mut c_void) -> bool {
+    let token = &*(user_data as *const CancellationToken);
+    token.is_cancelled()
+}
+
 /// Local whisper.cpp provider
 pub struct WhisperCppProvider {
     model_path: PathBuf,
+    progress: Option<ProgressCallback>,
+    cancellation: Option<CancellationToken>,
 }
 
 impl WhisperCppProvider {
     /// Create a new whisper.cpp provider
     pub fn new(model_path: PathBuf) -> Self {
-        Self { model_path }
+        Self {
+            model_path,
+            progress: None,
+            cancellation: None,
+        }
     }
 }
 
 #[async_trait]
 impl SttProvider for WhisperCppProvider {
     async fn transcribe(&self, samples: &[f32], language: Option<&str>) -> Result<String> {
+        Ok(self.transcribe_detailed(samples, language).await?.text)
+    }
+
+    async fn transcribe_detailed(
+        &self,
+        samples: &[f32],
+        language: Option<&str>,
+    ) -> Result<Transcription> {
         let model_path = self.model_path.clone();
         let samples = samples.to_vec();
         let language = language.map(|s| s.to_string());
+        let progress = self.progress.clone();
+        let cancellation = self.cancellation.clone();
 
         let result = tokio::task::spawn_blocking(move || {
-            // Create context for transcription
-            let params = WhisperContextParameters::default();
-            let ctx = WhisperContext::new_with_params(model_path.to_str().unwrap(), params)
-                .map_err(|e| AppError::Transcription(format!("Failed to create context: {}", e)))?;
+            let ctx = load_context(&model_path)?;
 
             let mut state = ctx
                 .create_state()
@@ -60,6 +173,29 @@ impl SttProvider for WhisperCppProvider {
             params.set_print_realtime(false);
             params.set_print_timestamps(false);
 
+            // whisper-rs 0.11's safe setter keeps a pointer to a moved closure
+            if let Some(progress) = progress.as_ref() {
+                // SAFETY: the callback outlives `state.full` below
+                unsafe {
+                    params.set_progress_callback(Some(whisper_progress));
+                    params.set_progress_callback_user_data(
+                        progress as *const ProgressCallback as *mut c_void,
+                    );
+                }
+            }
+
+            // The blocking task outlives a cancelled future, so whisper.cpp
+            // is asked to stop as well
+            if let Some(token) = cancellation.as_ref() {
+                // SAFETY: the token outlives `state.full` below
+                unsafe {
+                    params.set_abort_callback(Some(whisper_abort));
+                    params.set_abort_callback_user_data(
+                        token as *const CancellationToken as *mut c_void,
+                    );
+                }
+            }
+
             // Run transcription
             state
                 .full(params, &samples)
@@ -71,15 +207,35 @@ impl SttProvider for WhisperCppProvider {
             })?;
 
             let mut text = String::new();
+            let mut segments = Vec::new();
             for i in 0..num_segments {
                 if let Ok(segment) = state.full_get_segment_text(i) {
                     if !is_whisper_artifact(segment.trim()) {
                         text.push_str(&segment);
+
+                        // Timestamps are reported in centiseconds
+                        let start = state.full_get_segment_t0(i).unwrap_or(0).max(0);
+                        let end = state.full_get_segment_t1(i).unwrap_or(start).max(start);
+                        let probs: Vec<f32> = (0..state.full_n_tokens(i).unwrap_or(0))
+                            .filter_map(|t| state.full_get_token_prob(i, t).ok())
+                            .collect();
+
+                        segments.push(Segment {
+                            start_ms: start as u64 * 10,
+                            end_ms: end as u64 * 10,
+                            text: segment,
+                            confidence: average(&probs),
+                            speaker: None,
+                        });
                     }
                 }
             }
 
-            Ok::<String, AppError>(text.trim().to_string())
+            Ok::<Transcription, AppError>(Transcription {
+                text: text.trim().to_string(),
+                segments,
+                language: None,
+            })
         })
         .await
         .map_err(|e| AppError::Transcription(format!("Task failed: {}", e)))??;
@@ -87,11 +243,478 @@ impl SttProvider for WhisperCppProvider {
         Ok(result)
     }
 
+    fn set_progress_callback(&mut self, callback: ProgressCallback) {
+        self.progress = Some(callback);
+    }
+
+    fn set_cancellation(&mut self, token: CancellationToken) {
+        self.cancellation = Some(token);
+    }
+
+    async fn warm_up(&self) -> Result<()> {
+        let model_path = self.model_path.clone();
+        tokio::task::spawn_blocking(move || load_context(&model_path).map(|_| ()))
+            .await
+            .map_err(|e| AppError::Transcription(format!("Task failed: {}", e)))?
+    }
+
+    fn last_call(&self) -> Option<CallInfo> {
+        Some(CallInfo {
+            endpoint: self.model_path.to_string_lossy().to_string(),
+            retries: 0,
+        })
+    }
+
     fn name(&self) -> &str {
         "whisper.cpp"
     }
 }
 
+/// Response format requested from OpenAI-compatible servers
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ResponseFormat {
+    /// Text only
+    Json,
+    /// Text with timed segments and the detected language
+    #[default]
+    VerboseJson,
+}
+
+impl ResponseFormat {
+    fn as_str(&self) -> &'static str {
+        match self {
+            ResponseFormat::Json => "json",
+            ResponseFormat::VerboseJson => "verbose_json",
+        }
+    }
+}
+
+/// Longest audio uploaded in one request, as 16-bit samples; OpenAI rejects files over 25 MB
+const MAX_UPLOAD_SAMPLES: usize = 24 * 1024 * 1024 / 2;
+
+/// Size of the pieces an upload is streamed in
+const UPLOAD_PIECE_BYTES: usize = 64 * 1024;
+
+/// Upload progress over all chunks; 100 is left for the finished transcription
+fn upload_percent(index: usize, count: usize, sent: usize, total: usize) -> u8 {
+    let chunk = sent as f64 / total.max(1) as f64;
+    ((index as f64 + chunk) / count.max(1) as f64 * 100.0).min(99.0) as u8
+}
+
+/// Join the transcriptions of consecutive chunks, each with its offset in the audio
+fn merge_transcriptions(parts: Vec<(u64, Transcription)>) -> Transcription {
+    let mut merged = Transcription::default();
+    for (offset_ms, part) in parts {
+        if !part.text.is_empty() {
+            if !merged.text.is_empty() {
+                merged.text.push(' ');
+            }
+            merged.text.push_str(&part.text);
+        }
+        merged
+            .segments
+            .extend(part.segments.into_iter().map(|segment| Segment {
+                start_ms: segment.start_ms + offset_ms,
+                end_ms: segment.end_ms + offset_ms,
+                ..segment
+            }));
+        merged.language = merged.language.or(part.language);
+    }
+    merged
+}
+
+/// Connection to an OpenAI-compatible transcription server
+#[derive(Debug, Clone, Default)]
+pub struct SttServer {
+    /// Base URL; empty uses the OpenAI API
+    pub base_url: String,
+    pub api_key: Option<String>,
+    pub response_format: ResponseFormat,
+}
+
+/// OpenAI-compatible STT provider (OpenAI, whisper-server, faster-whisper-server)
+pub struct OpenAiSttProvider {
+    server: SttServer,
+    model: String,
+    decoding: DecodingOptions,
+    last_call: CallRecorder,
+    progress: Option<ProgressCallback>,
+    yield_point: Option<YieldPoint>,
+}
+
+impl OpenAiSttProvider {
+    /// Create a new provider for `model` on `server`
+    pub fn new(server: SttServer, model: String) -> Self {
+        Self {
+            server,
+            model,
+            decoding: DecodingOptions::default(),
+            last_call: CallRecorder::default(),
+            progress: None,
+            yield_point: None,
+        }
+    }
+
+    /// Set decoding options sent with each request
+    pub fn with_decoding(mut self, decoding: DecodingOptions) -> Self {
+        self.decoding = decoding;
+        self
+    }
+
+    /// Extra form fields for the decoding options
+    ///
+    /// Only the temperature is part of the OpenAI API, which gets the hotwords
+    /// as a prompt instead; the other options are sent to custom servers only.
+    fn decoding_fields(&self) -> Vec<(&'static str, String)> {
+        let decoding = &self.decoding;
+        let mut fields = Vec::new();
+        if let Some(temperature) = decoding.temperature {
+            fields.push(("temperature", temperature.to_string()));
+        }
+        let hotwords = decoding
+            .hotwords
+            .as_deref()
+            .map(str::trim)
+            .filter(|h| !h.is_empty());
+        if self.server.base_url.trim().is_empty() {
+            if let Some(hotwords) = hotwords {
+                fields.push(("prompt", format!("Vocabulary: {}.", hotwords)));
+            }
+            return fields;
+        }
+        if let Some(beam_size) = decoding.beam_size {
+            fields.push(("beam_size", beam_size.to_string()));
+        }
+        if let Some(vad_filter) = decoding.vad_filter {
+            fields.push(("vad_filter", vad_filter.to_string()));
+        }
+        if let Some(hotwords) = hotwords {
+            fields.push(("hotwords", hotwords.to_string()));
+        }
+        fields
+    }
+
+    /// Upload one chunk of at most `MAX_UPLOAD_SAMPLES`, chunk `index` of `count`
+    async fn transcribe_chunk(
+        &self,
+        samples: &[f32],
+        language: Option<&str>,
+        index: usize,
+        count: usize,
+    ) -> Result<Transcription> {
+        let wav = Arc::new(crate::audio::encode_wav(samples)?);
+        let endpoint = self.endpoint();
+        let client = http_client();
+
+        // A multipart body can only be sent once, so every attempt builds its own
+        let (response, retries) = send_with_retry(|| {
+            let body = self.upload_body(wav.clone(), index, count);
+            let file = reqwest::multipart::Part::stream_with_length(body, wav.len() as u64)
+                .file_name("audio.wav")
+                .mime_str("audio/wav")
+                .expect("valid MIME type");
+            let mut form = reqwest::multipart::Form::new()
+                .part("file", file)
+                .text("model", self.model.clone())
+                .text("response_format", self.server.response_format.as_str());
+            if let Some(language) = language.filter(|l| *l != "auto") {
+                form = form.text("language", language.to_string());
+            }
+            for (name, value) in self.decoding_fields() {
+                form = form.text(name, value);
+            }
+
+            let mut request = client.post(&endpoint).multipart(form);
+            if let Some(key) = &self.server.api_key {
+                request = request.header("Authorization", format!("Bearer {}", key));
+            }
+            request
+        })
+        .await;
+        self.last_call.record(&endpoint, retries);
+
+        let response =
+            response.map_err(|e| AppError::Transcription(format!("Request failed: {}", e)))?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(AppError::Transcription(format!(
+                "Server error ({}): {}",
+                status, body
+            )));
+        }
+
+        let body = response.text().await?;
+        let transcription = match self.server.response_format {
+            ResponseFormat::VerboseJson => parse_verbose_json(&body)?,
+            ResponseFormat::Json => {
+                let response: TextResponse = serde_json::from_str(&body).map_err(|e| {
+                    AppError::Transcription(format!("Failed to parse response: {}", e))
+                })?;
+                Transcription {
+                    text: response.text.trim().to_string(),
+                    ..Transcription::default()
+                }
+            }
+        };
+        Ok(transcription)
+    }
+
+    /// Body streaming `wav` in pieces, reporting how much of chunk `index` of `count` was sent
+    fn upload_body(&self, wav: Arc<Vec<u8>>, index: usize, count: usize) -> reqwest::Body {
+        let progress = self.progress.clone();
+        let pieces = (0..wav.len())
+            .step_by(UPLOAD_PIECE_BYTES)
+            .map(move |start| {
+                let end = (start + UPLOAD_PIECE_BYTES).min(wav.len());
+                if let Some(progress) = &progress {
+                    progress(upload_percent(index, count, end, wav.len()));
+                }
+                Ok::<_, std::io::Error>(wav[start..end].to_vec())
+            });
+        reqwest::Body::wrap_stream(futures_util::stream::iter(pieces))
+    }
+
+    fn endpoint(&self) -> String {
+        let base_url = match self.server.base_url.trim() {
+            "" => "https://api.openai.com",
+            url => url.trim_end_matches('/').trim_end_matches("/v1"),
+        };
+        format!("{}/v1/audio/transcriptions", base_url)
+    }
+}
+
+#[derive(Deserialize)]
+struct TextResponse {
+    text: String,
+}
+
+#[derive(Deserialize)]
+struct VerboseResponse {
+    text: String,
+    language: Option<String>,
+    #[serde(default)]
+    segments: Vec<VerboseSegment>,
+}
+
+#[derive(Deserialize)]
+struct VerboseSegment {
+    start: f64,
+    end: f64,
+    text: String,
+    avg_logprob: Option<f32>,
+}
+
+/// Parse a `verbose_json` transcription response
+fn parse_verbose_json(body: &str) -> Result<Transcription> {
+    let response: VerboseResponse = serde_json::from_str(body)
+        .map_err(|e| AppError::Transcription(format!("Failed to parse response: {}", e)))?;
+
+    let segments = response
+        .segments
+        .into_iter()
+        .filter(|s| !is_whisper_artifact(s.text.trim()))
+        .map(|s| Segment {
+            start_ms: (s.start * 1000.0) as u64,
+            end_ms: (s.end * 1000.0) as u64,
+            text: s.text,
+            confidence: s.avg_logprob.map(|p| p.exp().clamp(0.0, 1.0)),
+            speaker: None,
+        })
+        .collect();
+
+    Ok(Transcription {
+        text: response.text.trim().to_string(),
+        segments,
+        language: response.language,
+    })
+}
+
+#[async_trait]
+impl SttProvider for OpenAiSttProvider {
+    async fn transcribe(&self, samples: &[f32], language: Option<&str>) -> Result<String> {
+        Ok(self.transcribe_detailed(samples, language).await?.text)
+    }
+
+    async fn transcribe_detailed(
+        &self,
+        samples: &[f32],
+        language: Option<&str>,
+    ) -> Result<Transcription> {
+        let chunks = crate::audio::split_at_silence(samples, MAX_UPLOAD_SAMPLES);
+        if chunks.len() > 1 {
+            log::info!("Uploading the audio in {} chunks", chunks.len());
+        }
+
+        let mut parts = Vec::with_capacity(chunks.len());
+        for (index, range) in chunks.iter().enumerate() {
+            if index > 0 {
+                if let Some(yield_point) = &self.yield_point {
+                    yield_point().await;
+                }
+            }
+            let offset_ms = crate::audio::calculate_duration_ms(range.start);
+            let part = self
+                .transcribe_chunk(&samples[range.clone()], language, index, chunks.len())
+                .await?;
+            parts.push((offset_ms, part));
+        }
+
+        let transcription = merge_transcriptions(parts);
+        if let Some(language) = &transcription.language {
+            log::info!("Server detected language: {}", language);
+        }
+        Ok(transcription)
+    }
+
+    fn set_progress_callback(&mut self, callback: ProgressCallback) {
+        self.progress = Some(callback);
+    }
+
+    fn set_yield_point(&mut self, yield_point: YieldPoint) {
+        self.yield_point = Some(yield_point);
+    }
+
+    async fn warm_up(&self) -> Result<()> {
+        warm_connection(&self.endpoint())
+            .await
+            .map_err(|e| AppError::Transcription(format!("Server not reachable: {}", e)))
+    }
+
+    fn last_call(&self) -> Option<CallInfo> {
+        self.last_call.last()
+    }
+
+    fn name(&self) -> &str {
+        "OpenAI-compatible"
+    }
+}
+
+/// Languages whisper can transcribe, as (code, English name), sorted by name
+///
+/// "auto" lets whisper detect the language instead.
+pub const LANGUAGES: &[(&str, &str)] = &[
+    ("af", "Afrikaans"),
+    ("sq", "Albanian"),
+    ("am", "Amharic"),
+    ("ar", "Arabic"),
+    ("hy", "Armenian"),
+    ("as", "Assamese"),
+    ("az", "Azerbaijani"),
+    ("ba", "Bashkir"),
+    ("eu", "Basque"),
+    ("be", "Belarusian"),
+    ("bn", "Bengali"),
+    ("bs", "Bosnian"),
+    ("br", "Breton"),
+    ("bg", "Bulgarian"),
+    ("yue", "Cantonese"),
+    ("ca", "Catalan"),
+    ("zh", "Chinese"),
+    ("hr", "Croatian"),
+    ("cs", "Czech"),
+    ("da", "Danish"),
+    ("nl", "Dutch"),
+    ("en", "English"),
+    ("et", "Estonian"),
+    ("fo", "Faroese"),
+    ("fi", "Finnish"),
+    ("fr", "French"),
+    ("gl", "Galician"),
+    ("ka", "Georgian"),
+    ("de", "German"),
+    ("el", "Greek"),
+    ("gu", "Gujarati"),
+    ("ht", "Haitian Creole"),
+    ("ha", "Hausa"),
+    ("haw", "Hawaiian"),
+    ("he", "Hebrew"),
+    ("hi", "Hindi"),
+    ("hu", "Hungarian"),
+    ("is", "Icelandic"),
+    ("id", "Indonesian"),
+    ("it", "Italian"),
+    ("ja", "Japanese"),
+    ("jw", "Javanese"),
+    ("kn", "Kannada"),
+    ("kk", "Kazakh"),
+    ("km", "Khmer"),
+    ("ko", "Korean"),
+    ("lo", "Lao"),
+    ("la", "Latin"),
+    ("lv", "Latvian"),
+    ("ln", "Lingala"),
+    ("lt", "Lithuanian"),
+    ("lb", "Luxembourgish"),
+    ("mk", "Macedonian"),
+    ("mg", "Malagasy"),
+    ("ms", "Malay"),
+    ("ml", "Malayalam"),
+    ("mt", "Maltese"),
+    ("mi", "Maori"),
+    ("mr", "Marathi"),
+    ("mn", "Mongolian"),
+    ("my", "Myanmar"),
+    ("ne", "Nepali"),
+    ("no", "Norwegian"),
+    ("nn", "Nynorsk"),
+    ("oc", "Occitan"),
+    ("ps", "Pashto"),
+    ("fa", "Persian"),
+    ("pl", "Polish"),
+    ("pt", "Portuguese"),
+    ("pa", "Punjabi"),
+    ("ro", "Romanian"),
+    ("ru", "Russian"),
+    ("sa", "Sanskrit"),
+    ("sr", "Serbian"),
+    ("sn", "Shona"),
+    ("sd", "Sindhi"),
+    ("si", "Sinhala"),
+    ("sk", "Slovak"),
+    ("sl", "Slovenian"),
+    ("so", "Somali"),
+    ("es", "Spanish"),
+    ("su", "Sundanese"),
+    ("sw", "Swahili"),
+    ("sv", "Swedish"),
+    ("tl", "Tagalog"),
+    ("tg", "Tajik"),
+    ("ta", "Tamil"),
+    ("tt", "Tatar"),
+    ("te", "Telugu"),
+    ("th", "Thai"),
+    ("bo", "Tibetan"),
+    ("tr", "Turkish"),
+    ("tk", "Turkmen"),
+    ("uk", "Ukrainian"),
+    ("ur", "Urdu"),
+    ("uz", "Uzbek"),
+    ("vi", "Vietnamese"),
+    ("cy", "Welsh"),
+    ("yi", "Yiddish"),
+    ("yo", "Yoruba"),
+];
+
+/// Mean of a list of values, or `None` if empty
+fn average(values: &[f32]) -> Option<f32> {
+    (!values.is_empty()).then(|| values.iter().sum::<f32>() / values.len() as f32)
+}
+
+/// Overall confidence of a transcription, weighting segments by duration
+pub fn average_confidence(segments: &[Segment]) -> Option<f32> {
+    let (weighted, total) = segments
+        .iter()
+        .filter_map(|s| {
+            let weight = s.end_ms.saturating_sub(s.start_ms).max(1) as f32;
+            s.confidence.map(|c| (c * weight, weight))
+        })
+        .fold((0.0, 0.0), |(sum, total), (c, w)| (sum + c, total + w));
+
+    (total > 0.0).then(|| weighted / total)
+}
+
 /// Get the default models directory
 pub fn get_models_dir() -> Result<PathBuf> {
     let data_dir = directories::ProjectDirs::from("com", "whispertray", "WhisperTray")
@@ -108,8 +731,102 @@ pub fn get_model_path(model_name: &str) -> Result<PathBuf> {
     Ok(models_dir.join(format!("ggml-{}.bin", model_name)))
 }
 
+/// Check whether a whisper model has been downloaded
+pub fn is_model_downloaded(model_name: &str) -> bool {
+    get_model_path(model_name)
+        .map(|p| p.exists())
+        .unwrap_or(false)
+}
+
+/// Names of whisper models present in the models directory
+pub fn list_downloaded_models() -> Result<Vec<String>> {
+    let models_dir = get_models_dir()?;
+    let entries = match std::fs::read_dir(&models_dir) {
+        Ok(entries) => entries,
+        Err(_) => return Ok(Vec::new()),
+    };
+
+    let mut models: Vec<String> = entries
+        .filter_map(|e| e.ok())
+        .filter_map(|e| {
+            let name = e.file_name().to_string_lossy().to_string();
+            name.strip_prefix("ggml-")
+                .and_then(|n| n.strip_suffix(".bin"))
+                .map(str::to_string)
+        })
+        .collect();
+    models.sort();
+    Ok(models)
+}
+
+#[derive(Deserialize)]
+struct ModelsResponse {
+    #[serde(default)]
+    data: Vec<ModelEntry>,
+}
+
+#[derive(Deserialize)]
+struct ModelEntry {
+    id: String,
+}
+
+/// Query an OpenAI-compatible server (OpenAI, whisper-server, faster-whisper-server)
+/// for its model list.
+///
+/// On the official OpenAI API only speech-to-text models are returned.
+pub async fn list_server_models(base_url: &str, api_key: Option<&str>) -> Result<Vec<String>> {
+    let base_url = base_url.trim_end_matches('/');
+    let url = format!("{}/v1/models", base_url.trim_end_matches("/v1"));
+
+    let mut request = reqwest::Client::new()
+        .get(&url)
+        .timeout(std::time::Duration::from_secs(10));
+    if let Some(key) = api_key {
+        request = request.header("Authorization", format!("Bearer {}", key));
+    }
+
+    let response = request
+        .send()
+        .await
+        .map_err(|e| AppError::Provider(format!("Model list request failed: {}", e)))?;
+
+    if !response.status().is_success() {
+        return Err(AppError::Provider(format!(
+            "Model list request failed: HTTP {}",
+            response.status()
+        )));
+    }
+
+    let body = response.text().await?;
+    parse_models(&body, base_url.contains("api.openai.com"))
+}
+
+/// Parse a `/v1/models` response, optionally keeping only STT models
+fn parse_models(body: &str, stt_only: bool) -> Result<Vec<String>> {
+    let response: ModelsResponse = serde_json::from_str(body)
+        .map_err(|e| AppError::Provider(format!("Failed to parse model list: {}", e)))?;
+
+    let mut models: Vec<String> = response
+        .data
+        .into_iter()
+        .map(|m| m.id)
+        .filter(|id| !stt_only || id.contains("whisper") || id.contains("transcribe"))
+        .collect();
+    models.sort();
+    models.dedup();
+    Ok(models)
+}
+
 /// Download a whisper model if not present
 pub async fn ensure_model(model_name: &str) -> Result<PathBuf> {
+    ensure_model_with_progress(model_name, |_, _| {}).await
+}
+
+/// Download a model if needed, reporting downloaded and total bytes
+pub async fn ensure_model_with_progress<F>(model_name: &str, mut on_progress: F) -> Result<PathBuf>
+where
+    F: FnMut(u64, Option<u64>),
+{
     let model_path = get_model_path(model_name)?;
 
     if model_path.exists() {
@@ -129,7 +846,7 @@ pub async fn ensure_model(model_name: &str) -> Result<PathBuf> {
 
     log::info!("Downloading model from: {}", url);
 
-    let response = reqwest::get(&url).await?;
+    let mut response = reqwest::get(&url).await?;
 
     if !response.status().is_success() {
         return Err(AppError::Transcription(format!(
@@ -138,20 +855,56 @@ pub async fn ensure_model(model_name: &str) -> Result<PathBuf> {
         )));
     }
 
-    let bytes = response.bytes().await?;
-    tokio::fs::write(&model_path, &bytes).await?;
+    // Download to a partial file so an interrupted download is not mistaken for a model
+    let total = response.content_length();
+    let partial_path = model_path.with_extension("bin.part");
+    let download = async {
+        let mut file = tokio::fs::File::create(&partial_path).await?;
+        let mut downloaded = 0u64;
+        while let Some(chunk) = response.chunk().await? {
+            file.write_all(&chunk).await?;
+            downloaded += chunk.len() as u64;
+            on_progress(downloaded, total);
+        }
+        file.flush().await?;
+        drop(file);
+        tokio::fs::rename(&partial_path, &model_path).await?;
+        Ok::<_, AppError>(())
+    };
+    if let Err(e) = download.await {
+        let _ = tokio::fs::remove_file(&partial_path).await;
+        return Err(e);
+    }
 
     log::info!("Model downloaded successfully: {:?}", model_path);
     Ok(model_path)
 }
 
 /// Create an STT provider based on configuration
+///
+/// In local-only mode cloud providers are refused and missing models are not
+/// downloaded automatically; the user has to download them explicitly.
 pub async fn create_stt_provider(
-    provider_type: &SttProviderType,
-    model: &str,
+    mode: &Mode,
+    server: &SttServer,
+    local_only: bool,
 ) -> Result<Box<dyn SttProvider>> {
+    let (provider_type, model) = (&mode.stt_provider, mode.stt_model.as_str());
+    if local_only && !provider_type.is_local() {
+        return Err(AppError::LocalOnly(format!(
+            "{:?} speech recognition requires network access",
+            provider_type
+        )));
+    }
+
     match provider_type {
         SttProviderType::WhisperCpp => {
+            if local_only && !is_model_downloaded(model) {
+                return Err(AppError::LocalOnly(format!(
+                    "model '{}' is not downloaded; download it from Settings first",
+                    model
+                )));
+            }
             let model_path = ensure_model(model).await?;
             let provider = WhisperCppProvider::new(model_path);
             Ok(Box::new(provider))
@@ -160,7 +913,12 @@ pub async fn create_stt_provider(
             Err(AppError::Provider("Deepgram not yet implemented".to_string()))
         }
         SttProviderType::OpenAI => {
-            Err(AppError::Provider("OpenAI STT not yet implemented".to_string()))
+            if server.base_url.trim().is_empty() && server.api_key.is_none() {
+                return Err(AppError::Provider("OpenAI API key required".to_string()));
+            }
+            let provider = OpenAiSttProvider::new(server.clone(), model.to_string())
+                .with_decoding(mode.decoding.clone());
+            Ok(Box::new(provider))
         }
         SttProviderType::Custom(name) => {
             Err(AppError::Provider(format!("Unknown provider: {}", name)))
@@ -207,6 +965,131 @@ mod tests {
         assert!(path.to_str().unwrap().contains("ggml-base.en.bin"));
     }
 
+    #[test]
+    fn test_parse_models() {
+        let body = r#"{"object": "list", "data": [
+            {"id": "whisper-1", "object": "model"},
+            {"id": "gpt-4o-mini-transcribe", "object": "model"},
+            {"id": "gpt-4o", "object": "model"}
+        ]}"#;
+        assert_eq!(
+            parse_models(body, true).unwrap(),
+            vec!["gpt-4o-mini-transcribe", "whisper-1"]
+        );
+        assert_eq!(parse_models(body, false).unwrap().len(), 3);
+        assert!(parse_models("not json", false).is_err());
+    }
+
+    #[test]
+    fn test_parse_verbose_json() {
+        let body = r#"{"task": "transcribe", "language": "english", "duration": 4.2,
+            "text": " Hello there. General Kenobi. ",
+            "segments": [
+                {"id": 0, "start": 0.0, "end": 1.5, "text": " Hello there.", "avg_logprob": -0.1},
+                {"id": 1, "start": 1.5, "end": 3.0, "text": "[BLANK_AUDIO]", "avg_logprob": -1.0},
+                {"id": 2, "start": 3.0, "end": 4.2, "text": " General Kenobi."}
+            ]}"#;
+        let transcription = parse_verbose_json(body).unwrap();
+        assert_eq!(transcription.text, "Hello there. General Kenobi.");
+        assert_eq!(transcription.language.as_deref(), Some("english"));
+        assert_eq!(transcription.segments.len(), 2);
+        assert_eq!(transcription.segments[1].start_ms, 3000);
+        assert_eq!(transcription.segments[1].end_ms, 4200);
+        assert!((transcription.segments[0].confidence.unwrap() - 0.905).abs() < 0.001);
+        assert_eq!(transcription.segments[1].confidence, None);
+
+        let minimal = parse_verbose_json(r#"{"text": "Hi"}"#).unwrap();
+        assert!(minimal.segments.is_empty());
+    }
+
+    #[test]
+    fn test_decoding_fields() {
+        let decoding = DecodingOptions {
+            temperature: Some(0.0),
+            beam_size: Some(5),
+            vad_filter: Some(true),
+            hotwords: Some(" Kubernetes, Anna ".to_string()),
+        };
+        let server = SttServer {
+            base_url: "http://localhost:8000".to_string(),
+            ..SttServer::default()
+        };
+        let provider =
+            OpenAiSttProvider::new(server, "small".to_string()).with_decoding(decoding.clone());
+        assert_eq!(
+            provider.decoding_fields(),
+            [
+                ("temperature", "0".to_string()),
+                ("beam_size", "5".to_string()),
+                ("vad_filter", "true".to_string()),
+                ("hotwords", "Kubernetes, Anna".to_string()),
+            ]
+        );
+
+        let openai = OpenAiSttProvider::new(SttServer::default(), "whisper-1".to_string())
+            .with_decoding(decoding);
+        assert_eq!(
+            openai.decoding_fields(),
+            [
+                ("temperature", "0".to_string()),
+                ("prompt", "Vocabulary: Kubernetes, Anna.".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_merge_transcriptions() {
+        let part = |text: &str, language: Option<&str>| Transcription {
+            text: text.to_string(),
+            segments: vec![Segment {
+                start_ms: 0,
+                end_ms: 1000,
+                text: text.to_string(),
+                confidence: None,
+                speaker: None,
+            }],
+            language: language.map(str::to_string),
+        };
+        let merged = merge_transcriptions(vec![
+            (0, part("Hello", Some("en"))),
+            (60_000, part("", None)),
+            (90_000, part("world", None)),
+        ]);
+        assert_eq!(merged.text, "Hello world");
+        assert_eq!(merged.language.as_deref(), Some("en"));
+        assert_eq!(merged.segments[2].start_ms, 90_000);
+        assert_eq!(merged.segments[2].end_ms, 91_000);
+
+        assert_eq!(upload_percent(0, 1, 0, 100), 0);
+        assert_eq!(upload_percent(1, 4, 50, 100), 37);
+        assert_eq!(upload_percent(3, 4, 100, 100), 99);
+    }
+
+    #[test]
+    fn test_average() {
+        assert_eq!(average(&[]), None);
+        assert_eq!(average(&[0.5, 1.0]), Some(0.75));
+    }
+
+    #[test]
+    fn test_average_confidence() {
+        let segment = |start_ms, end_ms, confidence| Segment {
+            start_ms,
+            end_ms,
+            text: String::new(),
+            confidence,
+            speaker: None,
+        };
+
+        assert_eq!(average_confidence(&[]), None);
+        assert_eq!(average_confidence(&[segment(0, 100, None)]), None);
+
+        // The longer segment dominates
+        let segments = [segment(0, 3000, Some(0.9)), segment(3000, 4000, Some(0.5))];
+        let confidence = average_confidence(&segments).unwrap();
+        assert!((confidence - 0.8).abs() < 1e-6);
+    }
+
     #[test]
     fn test_whisper_artifacts_detected() {
         assert!(is_whisper_artifact("[BLANK_AUDIO]"));
@@ -0,0 +1,159 @@
+//! History quick-paste
+//!
+//! `QUICK_PASTE_HOTKEY` steps through the outputs of the last few dictations,
+//! newest first, showing the selected one in a notification that each press
+//! replaces. Once the presses stop for `SETTLE_DELAY`, the selected output is
+//! pasted. No window opens, so the focus stays where the text should go.
+
+use crate::database::ReaderPool;
+use crate::i18n::{t, t_args};
+use crate::notify;
+use crate::state::SharedState;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+/// Recent outputs offered for pasting
+pub const QUICK_PASTE_ITEMS: usize = 5;
+
+/// Quiet time after the last press before the selection is pasted
+const SETTLE_DELAY: Duration = Duration::from_millis(1200);
+
+/// Longest output excerpt shown in the notification
+const PREVIEW_CHARS: usize = 100;
+
+/// The quick-paste selection in progress (managed by Tauri)
+#[derive(Default)]
+pub struct QuickPaste(Mutex<Cycle>);
+
+#[derive(Default)]
+struct Cycle {
+    outputs: Vec<String>,
+    selected: usize,
+    /// Counts the presses, so only the last one pastes
+    presses: u64,
+    notification_id: Option<String>,
+}
+
+impl Cycle {
+    /// Select the next output, loading the outputs on the first press
+    ///
+    /// Returns the press number, or `None` if there is nothing to paste.
+    fn press(&mut self, load: impl FnOnce() -> Vec<String>) -> Option<u64> {
+        if self.outputs.is_empty() {
+            self.outputs = load();
+            self.selected = 0;
+        } else {
+            self.selected = (self.selected + 1) % self.outputs.len();
+        }
+        if self.outputs.is_empty() {
+            return None;
+        }
+        self.presses += 1;
+        Some(self.presses)
+    }
+}
+
+/// Outputs of the latest dictations, newest first
+fn recent_outputs(handle: &AppHandle) -> Vec<String> {
+    let readers = match handle.try_state::<ReaderPool>() {
+        Some(readers) => readers,
+        None => return Vec::new(),
+    };
+    let items = readers
+        .get()
+        .and_then(|db| db.get_history(QUICK_PASTE_ITEMS, 0));
+    match items {
+        Ok(items) => items
+            .into_iter()
+            .map(|item| item.output_final)
+            .filter(|output| !output.trim().is_empty())
+            .collect(),
+        Err(e) => {
+            log::error!("Failed to load history for quick paste: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+/// Handle a press of the quick-paste hotkey
+pub fn press(handle: &AppHandle) {
+    let handle = handle.clone();
+    tauri::async_runtime::spawn(async move {
+        let quick_paste = match handle.try_state::<QuickPaste>() {
+            Some(quick_paste) => quick_paste,
+            None => return,
+        };
+
+        let shown = {
+            let mut cycle = match quick_paste.0.lock() {
+                Ok(cycle) => cycle,
+                Err(_) => return,
+            };
+            cycle.press(|| recent_outputs(&handle)).map(|press| {
+                let title = t_args(
+                    "quick_paste.title",
+                    &[
+                        ("position", &(cycle.selected + 1).to_string()),
+                        ("count", &cycle.outputs.len().to_string()),
+                    ],
+                );
+                let body = notify::excerpt(&cycle.outputs[cycle.selected], PREVIEW_CHARS);
+                (press, title, body, cycle.notification_id.clone())
+            })
+        };
+        let (press, title, body, notification_id) = match shown {
+            Some(shown) => shown,
+            None => {
+                notify::send("WhisperTray", t("quick_paste.empty"));
+                return;
+            }
+        };
+
+        let id = tokio::task::spawn_blocking(move || {
+            notify::send_replacing(&title, &body, notification_id.as_deref())
+        })
+        .await
+        .ok()
+        .flatten();
+        if let Ok(mut cycle) = quick_paste.0.lock() {
+            if cycle.presses == press && id.is_some() {
+                cycle.notification_id = id;
+            }
+        }
+
+        tokio::time::sleep(SETTLE_DELAY).await;
+        let output = match quick_paste.0.lock() {
+            Ok(mut cycle) if cycle.presses == press => {
+                let cycle = std::mem::take(&mut *cycle);
+                cycle.outputs.into_iter().nth(cycle.selected)
+            }
+            _ => None,
+        };
+        if let (Some(output), Some(state)) = (output, handle.try_state::<SharedState>()) {
+            let state = state.lock().await;
+            if let Err(e) = state.paste_text(&output) {
+                log::error!("Failed to paste history output: {}", e);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cycle_wraps_and_loads_once() {
+        let mut cycle = Cycle::default();
+        assert_eq!(cycle.press(Vec::new), None);
+
+        let outputs = || vec!["newest".to_string(), "older".to_string()];
+        assert_eq!(cycle.press(outputs), Some(1));
+        assert_eq!(cycle.selected, 0);
+        assert_eq!(cycle.press(|| unreachable!()), Some(2));
+        assert_eq!(cycle.selected, 1);
+        assert_eq!(cycle.press(|| unreachable!()), Some(3));
+        assert_eq!(cycle.selected, 0);
+    }
+}
@@ -0,0 +1,192 @@
+//! Context from related past dictations
+//!
+//! Saved dictations are embedded in the background. Before LLM processing the
+//! new transcript is embedded too, and the most similar past outputs are added
+//! to `{{context}}` so follow-ups on ongoing topics keep the same terminology
+//! and decisions.
+
+use crate::database::HistoryItem;
+use crate::db_worker::DbHandle;
+use crate::error::Result;
+use crate::providers::embeddings::{cosine_similarity, EmbeddingProvider};
+
+/// Past dictations are shortened to this many characters in the context
+const MAX_ITEM_CHARS: usize = 600;
+
+/// Items embedded per request when indexing existing history
+const INDEX_BATCH: usize = 16;
+
+/// Key separating embeddings of different providers and models
+pub fn model_key(provider: &str, model: &str) -> String {
+    format!("{}:{}", provider.to_lowercase(), model)
+}
+
+/// The `k` most similar candidates scoring at least `min_similarity`, best first
+pub fn top_matches(
+    query: &[f32],
+    candidates: &[(String, Vec<f32>)],
+    k: usize,
+    min_similarity: f32,
+) -> Vec<(String, f32)> {
+    let mut scored: Vec<(String, f32)> = candidates
+        .iter()
+        .map(|(id, vector)| (id.clone(), cosine_similarity(query, vector)))
+        .filter(|(_, score)| *score >= min_similarity)
+        .collect();
+    scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+    scored.truncate(k);
+    scored
+}
+
+/// Format related dictations for the prompt context
+pub fn format_context(items: &[HistoryItem]) -> String {
+    let entries: Vec<String> = items
+        .iter()
+        .map(|item| {
+            let text = item.output_final.trim();
+            let text = match text.char_indices().nth(MAX_ITEM_CHARS) {
+                Some((end, _)) => format!("{}…", &text[..end]),
+                None => text.to_string(),
+            };
+            format!("[{}] {}", item.created_at.format("%Y-%m-%d"), text)
+        })
+        .collect();
+    format!("Related earlier dictations:\n{}", entries.join("\n\n"))
+}
+
+/// Find past dictations related to `text` and format them as context
+pub async fn retrieve(
+    provider: &dyn EmbeddingProvider,
+    db: &DbHandle,
+    model: &str,
+    text: &str,
+    k: usize,
+    min_similarity: f32,
+) -> Result<Option<String>> {
+    let key = model.to_string();
+    let candidates = db.call(move |db| db.get_embeddings(&key)).await?;
+    if candidates.is_empty() || k == 0 || text.trim().is_empty() {
+        return Ok(None);
+    }
+
+    let query = match provider.embed(&[text.to_string()]).await?.pop() {
+        Some(query) => query,
+        None => return Ok(None),
+    };
+    let matches = top_matches(&query, &candidates, k, min_similarity);
+
+    let ids: Vec<String> = matches.into_iter().map(|(id, _)| id).collect();
+    let items: Vec<HistoryItem> = db
+        .call(move |db| {
+            Ok(ids
+                .iter()
+                .filter_map(|id| db.get_history_item(id).ok().flatten())
+                .collect())
+        })
+        .await?;
+    if items.is_empty() {
+        return Ok(None);
+    }
+
+    log::info!("Added {} related dictations to the context", items.len());
+    Ok(Some(format_context(&items)))
+}
+
+/// Embed history items and store their vectors
+async fn embed_items(
+    provider: &dyn EmbeddingProvider,
+    db: &DbHandle,
+    model: &str,
+    items: &[HistoryItem],
+) -> Result<()> {
+    let texts: Vec<String> = items.iter().map(|i| i.output_final.clone()).collect();
+    let vectors = provider.embed(&texts).await?;
+
+    let ids: Vec<String> = items.iter().map(|i| i.id.clone()).collect();
+    let model = model.to_string();
+    db.call(move |db| {
+        for (id, vector) in ids.iter().zip(vectors) {
+            db.insert_embedding(id, &model, &vector)?;
+        }
+        Ok(())
+    })
+    .await
+}
+
+/// Embed a saved dictation in the background
+pub fn spawn_index(
+    provider: Box<dyn EmbeddingProvider>,
+    db: DbHandle,
+    model: String,
+    item: HistoryItem,
+) {
+    if item.output_final.trim().is_empty() {
+        return;
+    }
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = embed_items(provider.as_ref(), &db, &model, &[item]).await {
+            log::warn!("Failed to embed dictation: {}", e);
+        }
+    });
+}
+
+/// Embed all history items that have no vector for `model` yet
+///
+/// Returns how many items were indexed.
+pub async fn index_history(
+    provider: &dyn EmbeddingProvider,
+    db: &DbHandle,
+    model: &str,
+) -> Result<usize> {
+    let mut indexed = 0;
+    loop {
+        let key = model.to_string();
+        let items = db
+            .call(move |db| db.get_history_without_embedding(&key, INDEX_BATCH))
+            .await?;
+        if items.is_empty() {
+            break;
+        }
+
+        embed_items(provider, db, model, &items).await?;
+        indexed += items.len();
+    }
+
+    log::info!("Indexed {} history items for related context", indexed);
+    Ok(indexed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    #[test]
+    fn test_top_matches() {
+        let candidates = vec![
+            ("far".to_string(), vec![0.0, 1.0]),
+            ("close".to_string(), vec![0.9, 0.1]),
+            ("same".to_string(), vec![2.0, 0.0]),
+        ];
+        let matches = top_matches(&[1.0, 0.0], &candidates, 2, 0.5);
+        let ids: Vec<&str> = matches.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(ids, ["same", "close"]);
+
+        assert!(top_matches(&[1.0, 0.0], &candidates, 3, 0.999)
+            .iter()
+            .all(|(id, _)| id == "same"));
+    }
+
+    #[test]
+    fn test_format_context() {
+        let item = HistoryItem {
+            created_at: Utc.with_ymd_and_hms(2024, 5, 14, 9, 0, 0).unwrap(),
+            output_final: " Budget stays at 40k. ".to_string(),
+            ..HistoryItem::default()
+        };
+        assert_eq!(
+            format_context(&[item]),
+            "Related earlier dictations:\n[2024-05-14] Budget stays at 40k."
+        );
+    }
+}
@@ -0,0 +1,203 @@
+//! PII redaction for transcripts
+//!
+//! Emails, phone numbers, card-like numbers and user-listed names are replaced
+//! with placeholders such as `[EMAIL_1]` before text is sent to cloud LLMs or
+//! stored in history. The mapping is kept so LLM output can be restored to the
+//! original values locally.
+
+use crate::error::Result;
+use crate::providers::llm::LlmProvider;
+use regex::{Captures, Regex};
+use std::sync::OnceLock;
+
+/// Redacted text plus the placeholder mapping needed to restore it
+#[derive(Debug, Clone, Default)]
+pub struct Redaction {
+    pub text: String,
+    replacements: Vec<(String, String)>,
+}
+
+impl Redaction {
+    /// Replace placeholders in `text` with the original values
+    pub fn restore(&self, text: &str) -> String {
+        self.replacements
+            .iter()
+            .fold(text.to_string(), |acc, (placeholder, original)| {
+                acc.replace(placeholder, original)
+            })
+    }
+
+    /// Whether anything was redacted
+    pub fn is_empty(&self) -> bool {
+        self.replacements.is_empty()
+    }
+
+    /// Placeholder for a value, reusing the existing one for repeated values
+    fn placeholder(&mut self, kind: &str, value: &str) -> String {
+        if let Some((placeholder, _)) = self.replacements.iter().find(|(_, v)| v == value) {
+            return placeholder.clone();
+        }
+
+        let prefix = format!("[{}_", kind);
+        let index = self
+            .replacements
+            .iter()
+            .filter(|(p, _)| p.starts_with(&prefix))
+            .count()
+            + 1;
+        let placeholder = format!("{}{}]", prefix, index);
+        self.replacements
+            .push((placeholder.clone(), value.to_string()));
+        placeholder
+    }
+
+    /// Replace accepted matches of `re` with placeholders of the given kind
+    fn replace(&mut self, re: &Regex, kind: &str, accept: impl Fn(&str) -> bool) {
+        let text = std::mem::take(&mut self.text);
+        self.text = re
+            .replace_all(&text, |caps: &Captures| {
+                let value = &caps[0];
+                if accept(value) {
+                    self.placeholder(kind, value)
+                } else {
+                    value.to_string()
+                }
+            })
+            .into_owned();
+    }
+}
+
+fn email_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}").unwrap())
+}
+
+fn card_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"\b(?:\d[ -]?){12,18}\d\b").unwrap())
+}
+
+fn phone_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"\+?\(?\d[\d ().-]{5,}\d").unwrap())
+}
+
+fn date_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"^\d{4}-\d{2}-\d{2}$").unwrap())
+}
+
+fn digits(value: &str) -> Vec<u32> {
+    value.chars().filter_map(|c| c.to_digit(10)).collect()
+}
+
+/// Luhn checksum used by payment card numbers
+fn luhn_valid(digits: &[u32]) -> bool {
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &d)| match (i % 2, d * 2) {
+            (0, _) => d,
+            (_, doubled) if doubled > 9 => doubled - 9,
+            (_, doubled) => doubled,
+        })
+        .sum();
+    sum.is_multiple_of(10)
+}
+
+/// Redact PII from text
+pub fn redact(text: &str, names: &[String]) -> Redaction {
+    let mut redaction = Redaction {
+        text: text.to_string(),
+        replacements: Vec::new(),
+    };
+
+    redaction.replace(email_regex(), "EMAIL", |_| true);
+    redaction.replace(card_regex(), "CARD", |value| {
+        let digits = digits(value);
+        (13..=19).contains(&digits.len()) && luhn_valid(&digits)
+    });
+    redaction.replace(phone_regex(), "PHONE", |value| {
+        (7..=15).contains(&digits(value).len()) && !date_regex().is_match(value)
+    });
+
+    for name in names.iter().map(|n| n.trim()).filter(|n| !n.is_empty()) {
+        if let Ok(re) = Regex::new(&format!(r"(?i)\b{}\b", regex::escape(name))) {
+            redaction.replace(&re, "NAME", |_| true);
+        }
+    }
+
+    redaction
+}
+
+/// Complete a prompt, redacting PII first when `names` is given.
+///
+/// Placeholders in the response are mapped back to the original values.
+pub async fn complete(
+    provider: &dyn LlmProvider,
+    prompt: &str,
+    names: Option<&[String]>,
+) -> Result<String> {
+    let names = match names {
+        Some(names) => names,
+        None => return provider.complete(prompt).await,
+    };
+
+    let redaction = redact(prompt, names);
+    if !redaction.is_empty() {
+        log::info!("Redacted PII before sending prompt to {}", provider.name());
+    }
+
+    let output = provider.complete(&redaction.text).await?;
+    Ok(redaction.restore(&output))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact_email_and_phone() {
+        let redaction = redact(
+            "Mail jane.doe@example.com or call +1 (555) 123-4567, not on 2024-01-15",
+            &[],
+        );
+        assert_eq!(
+            redaction.text,
+            "Mail [EMAIL_1] or call [PHONE_1], not on 2024-01-15"
+        );
+    }
+
+    #[test]
+    fn test_redact_card_requires_luhn() {
+        let redaction = redact("Card 4111 1111 1111 1111, order 1234567890123", &[]);
+        assert!(redaction.text.starts_with("Card [CARD_1], order"));
+        assert!(!redaction.text.contains("[CARD_2]"));
+    }
+
+    #[test]
+    fn test_redact_names_and_reuse_placeholders() {
+        let names = vec!["Alice Smith".to_string()];
+        let redaction = redact("alice smith met Alice Smith", &names);
+        assert_eq!(redaction.text, "[NAME_1] met [NAME_2]");
+
+        let redaction = redact("Alice Smith and Alice Smith", &names);
+        assert_eq!(redaction.text, "[NAME_1] and [NAME_1]");
+    }
+
+    #[test]
+    fn test_restore() {
+        let redaction = redact("Write to bob@example.com", &[]);
+        assert_eq!(
+            redaction.restore("Dear [EMAIL_1], thanks"),
+            "Dear bob@example.com, thanks"
+        );
+    }
+
+    #[test]
+    fn test_luhn() {
+        assert!(luhn_valid(&digits("4111111111111111")));
+        assert!(!luhn_valid(&digits("4111111111111112")));
+    }
+}
@@ -0,0 +1,152 @@
+//! Regex post-processing rules
+//!
+//! An ordered list of find/replace rules applied to the final output of every
+//! dictation, e.g. collapsing double spaces, normalizing quotes or rewriting
+//! "e mail" to "email". Rules can apply globally or to a single mode.
+
+use crate::error::{AppError, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// A find/replace rule
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RewriteRule {
+    pub id: String,
+    /// Regular expression to find
+    pub pattern: String,
+    /// Replacement, may refer to groups as `$1` or `${name}`
+    pub replacement: String,
+    /// Mode the rule is limited to (none applies to all modes)
+    #[serde(default)]
+    pub mode_key: Option<String>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl RewriteRule {
+    /// Whether the rule runs for dictations in `mode_key`
+    pub fn applies_to(&self, mode_key: &str) -> bool {
+        self.enabled && self.mode_key.as_deref().is_none_or(|key| key == mode_key)
+    }
+
+    /// Check that the pattern compiles
+    pub fn validate(&self) -> Result<()> {
+        Regex::new(&self.pattern)
+            .map(|_| ())
+            .map_err(|e| AppError::Config(format!("Invalid pattern '{}': {}", self.pattern, e)))
+    }
+}
+
+/// Apply the rules for `mode_key` to `text`, in order
+///
+/// Rules whose pattern does not compile are skipped.
+pub fn apply(text: &str, rules: &[RewriteRule], mode_key: &str) -> String {
+    let mut text = text.to_string();
+    for rule in rules.iter().filter(|r| r.applies_to(mode_key)) {
+        match Regex::new(&rule.pattern) {
+            Ok(re) => {
+                text = re
+                    .replace_all(&text, rule.replacement.as_str())
+                    .into_owned()
+            }
+            Err(e) => log::warn!("Skipping rewrite rule '{}': {}", rule.id, e),
+        }
+    }
+    text
+}
+
+/// Put rules in the order of `ids`; rules not listed keep their order at the end
+pub fn reorder(rules: &mut [RewriteRule], ids: &[String]) {
+    let position = |rule: &RewriteRule| ids.iter().position(|id| *id == rule.id);
+    rules.sort_by_key(|rule| position(rule).unwrap_or(usize::MAX));
+}
+
+fn rules_path() -> Result<PathBuf> {
+    let config_dir = directories::ProjectDirs::from("com", "whispertray", "WhisperTray")
+        .ok_or_else(|| AppError::Config("Could not determine config directory".to_string()))?
+        .config_dir()
+        .to_path_buf();
+
+    Ok(config_dir.join("rewrite_rules.json"))
+}
+
+/// Load the rewrite rules
+pub fn load() -> Result<Vec<RewriteRule>> {
+    let path = rules_path()?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let content = std::fs::read_to_string(&path)?;
+    Ok(serde_json::from_str(&content)?)
+}
+
+/// Save the rewrite rules
+pub fn save(rules: &[RewriteRule]) -> Result<()> {
+    let path = rules_path()?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    std::fs::write(path, serde_json::to_string_pretty(rules)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(id: &str, pattern: &str, replacement: &str) -> RewriteRule {
+        RewriteRule {
+            id: id.to_string(),
+            pattern: pattern.to_string(),
+            replacement: replacement.to_string(),
+            mode_key: None,
+            enabled: true,
+        }
+    }
+
+    #[test]
+    fn test_apply_in_order() {
+        let rules = vec![
+            rule("email", r"(?i)\be mail\b", "email"),
+            rule("spaces", r" {2,}", " "),
+            rule("quotes", r"[“”]", "\""),
+        ];
+        assert_eq!(
+            apply("Send an  e mail about “the plan”", &rules, "note"),
+            "Send an email about \"the plan\""
+        );
+    }
+
+    #[test]
+    fn test_apply_respects_mode_and_enabled() {
+        let rules = vec![
+            RewriteRule {
+                mode_key: Some("email".to_string()),
+                ..rule("greeting", r"^hi\b", "Hello")
+            },
+            RewriteRule {
+                enabled: false,
+                ..rule("off", "Hello", "Hey")
+            },
+            rule("broken", "(", "x"),
+        ];
+        assert_eq!(apply("hi Bob", &rules, "email"), "Hello Bob");
+        assert_eq!(apply("hi Bob", &rules, "note"), "hi Bob");
+        assert!(rules[2].validate().is_err());
+    }
+
+    #[test]
+    fn test_reorder() {
+        let mut rules = vec![rule("a", "a", ""), rule("b", "b", ""), rule("c", "c", "")];
+        reorder(&mut rules, &["c".to_string(), "a".to_string()]);
+        let ids: Vec<&str> = rules.iter().map(|r| r.id.as_str()).collect();
+        assert_eq!(ids, ["c", "a", "b"]);
+    }
+}
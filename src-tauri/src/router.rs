@@ -0,0 +1,96 @@
+//! Automatic mode routing
+//!
+//! Dispatcher modes list candidate modes in `route_to`. After transcription a
+//! short LLM call classifies the transcript and the matching mode's template
+//! is used for processing.
+
+use crate::error::Result;
+use crate::modes::Mode;
+use crate::providers::llm::LlmProvider;
+use crate::redact;
+
+/// Build the classification prompt for the candidate modes
+pub fn classification_prompt(transcript: &str, candidates: &[&Mode]) -> String {
+    let options: Vec<String> = candidates
+        .iter()
+        .map(|m| format!("- {}: {}", m.key, m.description))
+        .collect();
+
+    format!(
+        "Classify the following dictated text into exactly one category.\n\nCategories:\n{}\n\nAnswer with the category key only.\n\nText:\n{}\n\nCategory:",
+        options.join("\n"),
+        transcript
+    )
+}
+
+/// Find the chosen mode key in the classifier's answer
+pub fn parse_choice(answer: &str, keys: &[&str]) -> Option<String> {
+    let answer = answer.to_lowercase();
+    let words: Vec<&str> = answer
+        .split(|c: char| !c.is_alphanumeric() && c != '_' && c != '-')
+        .filter(|w| !w.is_empty())
+        .collect();
+
+    // The first word naming a candidate wins, so "email, not message" picks email
+    words.iter().find_map(|word| {
+        keys.iter()
+            .find(|key| key.to_lowercase() == *word)
+            .map(|key| key.to_string())
+    })
+}
+
+/// Ask the LLM which candidate mode fits the transcript
+pub async fn route(
+    provider: &dyn LlmProvider,
+    transcript: &str,
+    candidates: &[&Mode],
+    redact_names: Option<&[String]>,
+) -> Result<Option<String>> {
+    if candidates.is_empty() || transcript.trim().is_empty() {
+        return Ok(None);
+    }
+
+    let prompt = classification_prompt(transcript, candidates);
+    let answer = redact::complete(provider, &prompt, redact_names).await?;
+    let keys: Vec<&str> = candidates.iter().map(|m| m.key.as_str()).collect();
+    let choice = parse_choice(&answer, &keys);
+
+    match &choice {
+        Some(key) => log::info!("Routed dictation to mode '{}'", key),
+        None => log::warn!("Could not route dictation, classifier answered: {}", answer),
+    }
+    Ok(choice)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_choice() {
+        let keys = ["message", "email", "note"];
+        assert_eq!(parse_choice("email", &keys), Some("email".to_string()));
+        assert_eq!(
+            parse_choice("Category: Note.", &keys),
+            Some("note".to_string())
+        );
+        assert_eq!(
+            parse_choice("email, not message", &keys),
+            Some("email".to_string())
+        );
+        assert_eq!(parse_choice("emails", &keys), None);
+        assert_eq!(parse_choice("", &keys), None);
+    }
+
+    #[test]
+    fn test_classification_prompt_lists_candidates() {
+        let note = Mode {
+            key: "note".to_string(),
+            description: "Organized notes".to_string(),
+            ..Mode::default()
+        };
+        let prompt = classification_prompt("buy milk", &[&note]);
+        assert!(prompt.contains("- note: Organized notes"));
+        assert!(prompt.contains("buy milk"));
+    }
+}
@@ -0,0 +1,150 @@
+//! Post-filters for LLM output
+//!
+//! Models often ignore "output only the result" instructions and wrap the
+//! text in code fences, prepend "Here is your cleaned message:" or quote the
+//! whole answer. These filters strip that before the text is pasted.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+/// Which output filters to apply
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OutputFilters {
+    /// Remove a code fence wrapping the whole output
+    pub strip_fences: bool,
+    /// Remove leading meta sentences like "Here is the cleaned text:" from the
+    /// output of AI-processed modes; off by default as it can drop a dictated line
+    pub strip_preamble: bool,
+    /// Remove quotes wrapping the whole output
+    pub trim_quotes: bool,
+}
+
+impl Default for OutputFilters {
+    fn default() -> Self {
+        Self {
+            strip_fences: true,
+            strip_preamble: false,
+            trim_quotes: true,
+        }
+    }
+}
+
+fn fence_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"(?s)^```[\w+-]*[ \t]*\n(.*?)\n?```$").unwrap())
+}
+
+fn preamble_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(
+            r"(?i)^(?:sure[,!.]?\s*|certainly[,!.]?\s*|of course[,!.]?\s*)?(?:here(?:'s| is| are)\b|below is\b|the (?:cleaned|corrected|formatted|rewritten|revised)\b)[^\n]*:[ \t]*\n+",
+        )
+        .unwrap()
+    })
+}
+
+/// Apply the enabled filters to LLM output
+pub fn sanitize(text: &str, filters: &OutputFilters) -> String {
+    let mut text = text.trim().to_string();
+
+    if filters.strip_preamble {
+        text = preamble_regex().replace(&text, "").trim().to_string();
+    }
+
+    if filters.strip_fences {
+        if let Some(caps) = fence_regex().captures(&text) {
+            text = caps[1].trim().to_string();
+        }
+    }
+
+    if filters.trim_quotes {
+        text = trim_wrapping_quotes(&text).to_string();
+    }
+
+    text
+}
+
+/// Strip one pair of quotes around the whole text, unless quotes occur inside
+fn trim_wrapping_quotes(text: &str) -> &str {
+    const PAIRS: [(char, char); 4] = [('"', '"'), ('\'', '\''), ('“', '”'), ('«', '»')];
+
+    for (open, close) in PAIRS {
+        if let Some(inner) = text.strip_prefix(open).and_then(|t| t.strip_suffix(close)) {
+            if !inner.contains(open) && !inner.contains(close) {
+                return inner.trim();
+            }
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_fences() {
+        let filters = OutputFilters::default();
+        assert_eq!(sanitize("```\nHello there\n```", &filters), "Hello there");
+        assert_eq!(
+            sanitize("```markdown\n# Title\n\nBody\n```", &filters),
+            "# Title\n\nBody"
+        );
+        // Fences inside the text are kept
+        assert_eq!(
+            sanitize("Run this:\n```\nls\n```\nthen exit", &filters),
+            "Run this:\n```\nls\n```\nthen exit"
+        );
+    }
+
+    #[test]
+    fn test_strip_preamble() {
+        let filters = OutputFilters {
+            strip_preamble: true,
+            ..OutputFilters::default()
+        };
+        assert_eq!(
+            sanitize("Here is your cleaned message:\n\nSee you at 5.", &filters),
+            "See you at 5."
+        );
+        assert_eq!(
+            sanitize(
+                "Sure! Here's the corrected text:\n```\nHi Bob\n```",
+                &filters
+            ),
+            "Hi Bob"
+        );
+        // Only a leading meta line ending with a colon is removed
+        assert_eq!(
+            sanitize("Here is the plan: we ship Friday.", &filters),
+            "Here is the plan: we ship Friday."
+        );
+    }
+
+    #[test]
+    fn test_trim_quotes() {
+        let filters = OutputFilters::default();
+        assert_eq!(
+            sanitize("\"Thanks for the update.\"", &filters),
+            "Thanks for the update."
+        );
+        assert_eq!(sanitize("“Done.”", &filters), "Done.");
+        assert_eq!(
+            sanitize("\"Yes\" she said, \"no\"", &filters),
+            "\"Yes\" she said, \"no\""
+        );
+    }
+
+    #[test]
+    fn test_filters_disabled() {
+        let filters = OutputFilters {
+            strip_fences: false,
+            strip_preamble: false,
+            trim_quotes: false,
+        };
+        assert_eq!(sanitize("  \"```\nx\n```\"  ", &filters), "\"```\nx\n```\"");
+    }
+}
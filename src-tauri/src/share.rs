@@ -0,0 +1,255 @@
+//! Shareable transcript files
+//!
+//! Renders a history item as a standalone HTML or Markdown file for handing
+//! to someone else. Only the date and mode are included unless the details
+//! (providers, models, duration) are asked for, and HTML in the output is
+//! shown as text rather than rendered.
+
+use crate::database::HistoryItem;
+use crate::error::{AppError, Result};
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// File format of a shared transcript
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ShareFormat {
+    #[default]
+    Html,
+    Markdown,
+}
+
+/// What goes into a shared transcript
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ShareOptions {
+    pub format: ShareFormat,
+    /// Add the raw transcript below the output
+    pub include_transcript: bool,
+    /// Add providers, models and duration
+    pub include_details: bool,
+}
+
+/// Directory shared transcripts are written to
+pub fn share_dir() -> Result<PathBuf> {
+    Ok(crate::journal::documents_dir()?.join("WhisperTray Shared"))
+}
+
+/// Title of a shared transcript
+fn title(item: &HistoryItem) -> String {
+    match item.title.as_deref().map(str::trim) {
+        Some(title) if !title.is_empty() => title.to_string(),
+        _ => "Transcript".to_string(),
+    }
+}
+
+/// File name from the date and title, e.g. `2024-05-14 Reply to Anna.html`
+fn file_name(item: &HistoryItem, format: ShareFormat) -> String {
+    let title: String = title(item)
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == ' ' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .take(60)
+        .collect();
+    let extension = match format {
+        ShareFormat::Html => "html",
+        ShareFormat::Markdown => "md",
+    };
+    format!(
+        "{} {}.{}",
+        item.created_at.with_timezone(&Local).format("%Y-%m-%d"),
+        title.trim(),
+        extension
+    )
+}
+
+/// Markdown for a shared transcript
+pub fn render_markdown(item: &HistoryItem, mode_name: &str, options: &ShareOptions) -> String {
+    let mut lines = vec![
+        format!("# {}", title(item)),
+        String::new(),
+        format!(
+            "{} · {}",
+            item.created_at
+                .with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M"),
+            mode_name
+        ),
+    ];
+    if options.include_details {
+        let mut details = format!(
+            "Transcribed with {}/{} · {} s",
+            item.stt_provider,
+            item.stt_model,
+            item.duration_ms / 1000
+        );
+        if let (Some(provider), Some(model)) = (&item.llm_provider, &item.llm_model) {
+            details.push_str(&format!(" · Processed with {}/{}", provider, model));
+        }
+        lines.push(String::new());
+        lines.push(details);
+    }
+    lines.push(String::new());
+    lines.push(item.output_final.trim().to_string());
+    if options.include_transcript && item.transcript_raw.trim() != item.output_final.trim() {
+        lines.push(String::new());
+        lines.push("## Transcript".to_string());
+        lines.push(String::new());
+        lines.push(item.transcript_raw.trim().to_string());
+    }
+    lines.join("\n") + "\n"
+}
+
+/// A standalone HTML page for a shared transcript
+pub fn render_html(item: &HistoryItem, mode_name: &str, options: &ShareOptions) -> String {
+    use pulldown_cmark::{html, Event, Options, Parser};
+
+    let markdown = render_markdown(item, mode_name, options);
+    // Raw HTML from the output is shown as text, never rendered
+    let parser = Parser::new_ext(
+        &markdown,
+        Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH,
+    )
+    .map(|event| match event {
+        Event::Html(html) => Event::Text(html),
+        event => event,
+    });
+    let mut body = String::new();
+    html::push_html(&mut body, parser);
+
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <title>{}</title>\n<style>\n\
+         body {{ max-width: 42rem; margin: 3rem auto; padding: 0 1rem; \
+         font: 16px/1.6 system-ui, sans-serif; color: #222; }}\n\
+         h1 + p {{ color: #777; font-size: 0.9rem; }}\n\
+         </style>\n</head>\n<body>\n{}</body>\n</html>\n",
+        escape_html(&title(item)),
+        body
+    )
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Write a shared transcript to `dir`, returning its path
+pub fn write(
+    dir: &Path,
+    item: &HistoryItem,
+    mode_name: &str,
+    options: &ShareOptions,
+) -> Result<PathBuf> {
+    let content = match options.format {
+        ShareFormat::Html => render_html(item, mode_name, options),
+        ShareFormat::Markdown => render_markdown(item, mode_name, options),
+    };
+    std::fs::create_dir_all(dir)?;
+    let path = dir.join(file_name(item, options.format));
+    std::fs::write(&path, content)?;
+    Ok(path)
+}
+
+/// Open the file manager with `path` selected
+///
+/// Uses the freedesktop `FileManager1` interface, falling back to opening
+/// the containing directory.
+pub async fn reveal(path: &Path) -> Result<()> {
+    let uri = reqwest::Url::from_file_path(path)
+        .map_err(|_| AppError::Config(format!("Not an absolute path: {}", path.display())))?;
+    let shown = match zbus::Connection::session().await {
+        Ok(connection) => connection
+            .call_method(
+                Some("org.freedesktop.FileManager1"),
+                "/org/freedesktop/FileManager1",
+                Some("org.freedesktop.FileManager1"),
+                "ShowItems",
+                &(vec![uri.as_str()], ""),
+            )
+            .await
+            .is_ok(),
+        Err(_) => false,
+    };
+    if shown {
+        return Ok(());
+    }
+
+    let dir = path.parent().unwrap_or(path);
+    std::process::Command::new("xdg-open")
+        .arg(dir)
+        .spawn()
+        .map_err(|e| AppError::Config(format!("Failed to open file manager: {}", e)))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn item() -> HistoryItem {
+        HistoryItem {
+            id: "1".to_string(),
+            created_at: Utc::now(),
+            mode_key: "email".to_string(),
+            audio_path: Some("/home/me/audio.wav".to_string()),
+            transcript_raw: "hi anna the budget is fine".to_string(),
+            output_final: "Hi Anna,\n\nthe <b>budget</b> is fine.".to_string(),
+            stt_provider: "whisper_cpp".to_string(),
+            stt_model: "base".to_string(),
+            llm_provider: Some("ollama".to_string()),
+            llm_model: Some("llama3.2".to_string()),
+            duration_ms: 4200,
+            error: None,
+            confidence: Some(0.9),
+            title: Some("Reply to Anna: budget".to_string()),
+            latencies: None,
+            provider_metadata: None,
+            clipped: false,
+            transcript_path: None,
+        }
+    }
+
+    #[test]
+    fn test_markdown_leaves_out_details_by_default() {
+        let markdown = render_markdown(&item(), "Email", &ShareOptions::default());
+        assert!(markdown.starts_with("# Reply to Anna: budget\n"));
+        assert!(markdown.contains(" · Email\n"));
+        assert!(markdown.ends_with("the <b>budget</b> is fine.\n"));
+        assert!(!markdown.contains("whisper_cpp"));
+        assert!(!markdown.contains("hi anna"));
+
+        let options = ShareOptions {
+            include_transcript: true,
+            include_details: true,
+            ..ShareOptions::default()
+        };
+        let markdown = render_markdown(&item(), "Email", &options);
+        assert!(markdown.contains("Transcribed with whisper_cpp/base · 4 s"));
+        assert!(markdown.contains("## Transcript\n\nhi anna"));
+    }
+
+    #[test]
+    fn test_html_shows_raw_html_as_text() {
+        let html = render_html(&item(), "Email", &ShareOptions::default());
+        assert!(html.contains("<title>Reply to Anna: budget</title>"));
+        assert!(html.contains("&lt;b&gt;budget&lt;/b&gt;"));
+        assert!(!html.contains("<b>"));
+    }
+
+    #[test]
+    fn test_file_name() {
+        let name = file_name(&item(), ShareFormat::Html);
+        assert!(name.ends_with(" Reply to Anna_ budget.html"));
+    }
+}
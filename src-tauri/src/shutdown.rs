@@ -0,0 +1,37 @@
+//! Cleanup when the app quits
+//!
+//! A recording in progress is stopped and its audio saved with a history item
+//! marked as interrupted. Processing in flight is cancelled, which saves the
+//! dictation with the same mark. Changes to the system, the echo-cancel module
+//! and switched Bluetooth profiles, are undone.
+
+use crate::echo_cancel;
+use crate::pipeline::ProcessingControl;
+use crate::state::SharedState;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+/// How long cancelled processing gets to save its dictation
+const GRACE_PERIOD: Duration = Duration::from_secs(3);
+
+/// Run the shutdown sequence; blocks until done or the grace period ends
+pub fn run(app: &AppHandle) {
+    if let Some(processing) = app.try_state::<Arc<ProcessingControl>>() {
+        if processing.shut_down() {
+            log::info!("Quitting during processing, cancelling it");
+        }
+    }
+
+    if let Some(state) = app.try_state::<SharedState>() {
+        let state = state.inner().clone();
+        tauri::async_runtime::block_on(async move {
+            match tokio::time::timeout(GRACE_PERIOD, state.lock()).await {
+                Ok(mut state) => state.shut_down(),
+                Err(_) => log::warn!("Processing did not stop in time, quitting anyway"),
+            }
+        });
+    }
+
+    echo_cancel::unload();
+}
@@ -0,0 +1,181 @@
+//! Voice-inserted text snippets
+//!
+//! Snippets are named text blocks such as a signature or an address. Saying
+//! "insert my signature" expands to the stored block. For AI modes the command
+//! is swapped for a placeholder before the LLM sees the transcript, so the
+//! block is inserted verbatim instead of being rewritten.
+
+use crate::error::{AppError, Result};
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// A named text block
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Snippet {
+    pub name: String,
+    pub text: String,
+}
+
+/// Transcript with snippet commands replaced by placeholders
+#[derive(Debug, Clone, Default)]
+pub struct Expansion {
+    pub text: String,
+    inserts: Vec<(String, String)>,
+}
+
+impl Expansion {
+    /// Replace placeholders in `output` with the snippet texts.
+    ///
+    /// Snippets whose placeholder was dropped (e.g. by an LLM) are appended.
+    pub fn finish(&self, output: &str) -> String {
+        let mut result = output.to_string();
+        for (placeholder, text) in &self.inserts {
+            if result.contains(placeholder.as_str()) {
+                result = result.replace(placeholder.as_str(), text);
+            } else {
+                result = format!("{}\n\n{}", result.trim_end(), text);
+            }
+        }
+        result
+    }
+
+    /// Whether any snippet command was recognized
+    pub fn is_empty(&self) -> bool {
+        self.inserts.is_empty()
+    }
+}
+
+/// Regex matching a spoken command for a snippet, e.g. "insert my home address."
+fn command_regex(name: &str) -> Option<Regex> {
+    let words: Vec<String> = name.split_whitespace().map(regex::escape).collect();
+    if words.is_empty() {
+        return None;
+    }
+    Regex::new(&format!(
+        r"(?i)\b(?:insert|paste|add)\s+(?:my\s+|the\s+|our\s+)?{}\b[.!]?",
+        words.join(r"\s+")
+    ))
+    .ok()
+}
+
+/// Replace snippet commands in a transcript with placeholders
+pub fn prepare(transcript: &str, snippets: &[Snippet]) -> Expansion {
+    let mut expansion = Expansion {
+        text: transcript.to_string(),
+        inserts: Vec::new(),
+    };
+
+    // Longer names first so "home address" wins over "address"
+    let mut ordered: Vec<&Snippet> = snippets.iter().collect();
+    ordered.sort_by_key(|s| std::cmp::Reverse(s.name.len()));
+
+    for snippet in ordered {
+        let re = match command_regex(&snippet.name) {
+            Some(re) => re,
+            None => continue,
+        };
+        let text = std::mem::take(&mut expansion.text);
+        expansion.text = re
+            .replace_all(&text, |_: &Captures| {
+                let placeholder = format!("[SNIPPET_{}]", expansion.inserts.len() + 1);
+                expansion
+                    .inserts
+                    .push((placeholder.clone(), snippet.text.clone()));
+                placeholder
+            })
+            .into_owned();
+    }
+
+    expansion
+}
+
+/// Expand snippet commands in text directly
+pub fn expand(text: &str, snippets: &[Snippet]) -> String {
+    let expansion = prepare(text, snippets);
+    expansion.finish(&expansion.text)
+}
+
+fn snippets_path() -> Result<PathBuf> {
+    let config_dir = directories::ProjectDirs::from("com", "whispertray", "WhisperTray")
+        .ok_or_else(|| AppError::Config("Could not determine config directory".to_string()))?
+        .config_dir()
+        .to_path_buf();
+
+    Ok(config_dir.join("snippets.json"))
+}
+
+/// Load the snippet library
+pub fn load() -> Result<Vec<Snippet>> {
+    let path = snippets_path()?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let content = std::fs::read_to_string(&path)?;
+    Ok(serde_json::from_str(&content)?)
+}
+
+/// Save the snippet library
+pub fn save(snippets: &[Snippet]) -> Result<()> {
+    let path = snippets_path()?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    std::fs::write(path, serde_json::to_string_pretty(snippets)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snippets() -> Vec<Snippet> {
+        vec![
+            Snippet {
+                name: "signature".to_string(),
+                text: "Best regards,\nAlex".to_string(),
+            },
+            Snippet {
+                name: "address".to_string(),
+                text: "1 Main St".to_string(),
+            },
+            Snippet {
+                name: "home address".to_string(),
+                text: "2 Side Rd".to_string(),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_expand() {
+        assert_eq!(
+            expand("Thanks for the help. Insert my signature.", &snippets()),
+            "Thanks for the help. Best regards,\nAlex"
+        );
+        assert_eq!(
+            expand("ship it to insert my home address", &snippets()),
+            "ship it to 2 Side Rd"
+        );
+        assert_eq!(
+            expand("my signature looks odd", &snippets()),
+            "my signature looks odd"
+        );
+    }
+
+    #[test]
+    fn test_placeholders_survive_llm() {
+        let expansion = prepare("see you then, add my signature", &snippets());
+        assert_eq!(expansion.text, "see you then, [SNIPPET_1]");
+        assert_eq!(
+            expansion.finish("See you then!\n\n[SNIPPET_1]"),
+            "See you then!\n\nBest regards,\nAlex"
+        );
+        // A dropped placeholder is appended
+        assert_eq!(
+            expansion.finish("See you then!"),
+            "See you then!\n\nBest regards,\nAlex"
+        );
+    }
+}
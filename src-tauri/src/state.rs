@@ -1,17 +1,61 @@
 //! Application state management
 
-use crate::audio::RecordingHandle;
-use crate::database::{get_audio_dir, get_database_path, Database, HistoryItem};
+use crate::action_items;
+use crate::audio::{AudioBackend, RecordingHandle, StreamOverride};
+use crate::bluetooth;
+use crate::calibration::InputCalibration;
+use crate::chat::{self, ChatTarget};
+use crate::corrections::{self, Correction};
+use crate::database::{get_audio_dir, get_database_path, Database, HistoryItem, ProviderMetadata};
+use crate::db_worker::DbHandle;
+use crate::echo_cancel;
 use crate::error::{AppError, Result};
-use crate::modes::{load_modes, Mode, LlmProvider as LlmProviderType};
-use crate::paste;
+use crate::final_pass;
+use crate::gallery;
+use crate::history;
+use crate::hooks::{self, HookPoint, Hooks};
+use crate::i18n;
+use crate::icon::{TrayIconStyle, TrayIconTheme, DEFAULT_ICON_SIZE};
+use crate::indicator::{self, IndicatorCorner, IndicatorPlacement};
+use crate::jobs::{JobKind, JobQueue};
+use crate::journal;
+use crate::keys::{self, KeyCache};
+use crate::live;
+use crate::mail;
+use crate::mode_check;
+use crate::modes::{
+    load_modes, LlmProvider as LlmProviderType, Mode, OutputFormat, SttProvider as SttProviderType,
+};
+use crate::notify;
+use crate::paste::{self, PasteBackend, PasteChord, PasteConfig, PasteOverride};
+use crate::pipeline::{run_cancellable, PipelineStage, ProcessingControl, ProgressReporter};
+use crate::plugins::{self, PluginHost};
+use crate::providers::embeddings::{self, EmbeddingProvider, EmbeddingProviderType};
+use crate::providers::streaming::{StreamConfig, StreamingProviderType};
 use crate::providers::{llm, stt};
+use crate::rag;
+use crate::redact;
+use crate::rewrite::{self, RewriteRule};
+use crate::router;
+use crate::sanitize::{self, OutputFilters};
+use crate::snippets::{self, Snippet};
+use crate::structured;
+use crate::style;
+use crate::tasks::{self, TaskSource, TaskTarget};
+use crate::titles;
+use crate::update::UpdateInfo;
+use crate::window::{self, FocusedWindow};
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
 use std::path::PathBuf;
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
 use tauri::AppHandle;
+use tokio_util::sync::CancellationToken;
+use tracing::Instrument;
 use uuid::Uuid;
 
 /// Recording status for the tray icon
@@ -22,6 +66,8 @@ pub enum RecordingStatus {
     Loading,
     /// Recording in progress (red)
     Recording,
+    /// Recording paused, audio is not captured (orange)
+    Paused,
     /// Processing transcription/LLM (blue)
     Processing,
     /// Idle/ready (green)
@@ -31,19 +77,22 @@ pub enum RecordingStatus {
 }
 
 impl RecordingStatus {
-    pub fn icon_name(&self) -> &'static str {
+    /// Tray icon color (RGB) for this status
+    pub fn icon_color(&self) -> [u8; 3] {
         match self {
-            RecordingStatus::Loading => "tray-yellow",
-            RecordingStatus::Recording => "tray-red",
-            RecordingStatus::Processing => "tray-blue",
-            RecordingStatus::Ready => "tray-green",
-            RecordingStatus::Error => "tray-red",
+            RecordingStatus::Loading => [0xF5, 0xC2, 0x11],
+            RecordingStatus::Recording => [0xFF, 0x41, 0x36],
+            RecordingStatus::Paused => [0xFF, 0x85, 0x1B],
+            RecordingStatus::Processing => [0x34, 0x8C, 0xF0],
+            RecordingStatus::Ready => [0x2E, 0xCC, 0x40],
+            RecordingStatus::Error => [0xFF, 0x41, 0x36],
         }
     }
 }
 
 /// Application settings
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Settings {
     pub default_stt_provider: String,
     pub default_stt_model: String,
@@ -51,9 +100,149 @@ pub struct Settings {
     pub default_llm_model: String,
     pub active_mode_key: String,
     pub input_device: String,
+    /// Sound system to record through
+    pub audio_backend: AudioBackend,
+    /// JACK ports to record from (empty for the system capture ports)
+    pub jack_ports: Vec<String>,
+    /// Devices tried in order when `input_device` is not present, before the default device
+    pub input_device_fallbacks: Vec<String>,
     pub auto_paste: bool,
     pub context_awareness: bool,
     pub language: String,
+    /// Tray icon drawing style (colored or monochrome symbolic)
+    pub tray_icon_style: TrayIconStyle,
+    /// Panel theme for tray icon contrast
+    pub tray_icon_theme: TrayIconTheme,
+    /// Tray icon size in pixels
+    pub tray_icon_size: u32,
+    /// Show a floating level indicator while recording
+    pub show_indicator: bool,
+    /// Monitor for the indicator: "focused", "primary" or a monitor name
+    pub indicator_monitor: String,
+    /// Corner of the monitor the indicator is placed in
+    pub indicator_corner: IndicatorCorner,
+    /// File kept up to date with the current status as a JSON line for bar modules (empty disables)
+    pub status_file: String,
+    /// Folder shared between machines (e.g. Syncthing) to sync modes and settings through (empty disables)
+    pub sync_folder: String,
+    /// Put rendered HTML on the clipboard alongside plain text for Markdown modes
+    pub rich_clipboard: bool,
+    /// Warn when transcription confidence falls below this value (0 disables)
+    pub low_confidence_threshold: f32,
+    /// Redact PII from prompts sent to cloud LLMs
+    pub redact_cloud_prompts: bool,
+    /// Redact PII from transcripts and output stored in history
+    pub redact_history: bool,
+    /// Names to redact in addition to emails, phone and card numbers
+    pub redact_names: Vec<String>,
+    /// Block all network providers and automatic model downloads
+    pub local_only: bool,
+    /// Keep recorded audio files (modes may override)
+    pub keep_audio: bool,
+    /// Skip auto-paste if focus moved to another window while processing
+    pub paste_focus_guard: bool,
+    /// Window classes that may always receive the paste (case-insensitive substrings)
+    pub paste_allowed_windows: Vec<String>,
+    /// Key combination used to paste
+    pub paste_chord: PasteChord,
+    /// Delay before pasting in milliseconds
+    pub paste_delay_ms: u64,
+    /// Per-application paste chord/delay keyed by window class
+    pub paste_overrides: Vec<PasteOverride>,
+    /// Paste backend picked by the paste benchmark; `None` detects one from the session
+    pub paste_backend: Option<PasteBackend>,
+    /// How long Ollama keeps models loaded between dictations (e.g. "30m", "-1" for forever)
+    pub ollama_keep_alive: String,
+    /// Load the Ollama model when recording starts so it is warm for processing
+    pub ollama_preload: bool,
+    /// Load the whisper model and connect to the STT server and LLM when recording starts
+    pub warm_up_pipeline: bool,
+    /// Load the active mode's whisper model when the app starts
+    pub preload_on_startup: bool,
+    /// Also load the active mode's Ollama model when the app starts
+    pub preload_ollama_on_startup: bool,
+    /// Read API keys from the keyring once and keep them in memory until locked
+    pub cache_api_keys: bool,
+    /// Base URL of an OpenAI-compatible STT server (e.g. whisper-server); empty uses OpenAI
+    pub stt_server_url: String,
+    /// Response format requested from the STT server
+    pub stt_response_format: stt::ResponseFormat,
+    /// Cleanup applied to AI output before pasting
+    pub output_filters: OutputFilters,
+    /// Learn from edits to history outputs and reuse the corrections
+    pub learn_corrections: bool,
+    /// URL of the mode gallery index (empty disables the gallery)
+    pub mode_gallery_url: String,
+    /// Tray tooltip template, e.g. "{mode} · {status} {elapsed}" (empty uses the default)
+    pub tray_tooltip_template: String,
+    /// Notify after each dictation with copy/open/re-run actions
+    pub completion_notifications: bool,
+    /// Mode offered by the "Re-run" notification action (empty hides it)
+    pub notification_rerun_mode: String,
+    /// Drop near-identical consecutive recordings from history (e.g. a double hotkey press)
+    pub auto_dedup: bool,
+    /// How close in time two recordings must be to count as duplicates
+    pub dedup_window_secs: u64,
+    /// Append dictations to a per-day Markdown journal
+    pub journal_enabled: bool,
+    /// Journal directory (empty uses ~/Documents/WhisperTray Journal)
+    pub journal_dir: String,
+    /// Mode keys whose dictations are journaled (empty means all)
+    pub journal_modes: Vec<String>,
+    /// Directory for live meeting transcripts (empty uses the data directory)
+    pub live_transcript_dir: String,
+    /// WebSocket provider for live transcripts (none transcribes 30 s chunks instead)
+    pub streaming_provider: Option<StreamingProviderType>,
+    /// Streaming model (empty uses the provider's default)
+    pub streaming_model: String,
+    /// WhisperLive server URL
+    pub whisper_live_url: String,
+    /// Generate a short LLM title for each history item
+    pub generate_titles: bool,
+    /// Backend computing text embeddings for semantic features
+    pub embedding_provider: EmbeddingProviderType,
+    /// Embedding model (Ollama/OpenAI model name or ONNX model directory name)
+    pub embedding_model: String,
+    /// Add similar past dictations to `{{context}}` during LLM processing
+    pub rag_enabled: bool,
+    /// How many related dictations are added at most
+    pub rag_top_k: usize,
+    /// Minimum cosine similarity for a past dictation to count as related
+    pub rag_min_similarity: f32,
+    /// External commands the text is piped through at pipeline stages
+    pub hooks: Hooks,
+    /// CalDAV task list URL for modes that create tasks
+    pub caldav_url: String,
+    /// CalDAV user name; the password is saved as `caldav` in the keyring
+    pub caldav_username: String,
+    /// Matrix homeserver URL for modes that post to a room
+    pub matrix_homeserver: String,
+    /// Ids of the WebAssembly plugins that are enabled
+    pub enabled_plugins: Vec<String>,
+    /// Look for a new release at startup (tarball and AppImage installs only)
+    pub check_for_updates: bool,
+    /// Audio kept in memory while recording, in MB; older audio goes to a temp file (0 for no limit)
+    pub recording_memory_cap_mb: usize,
+    /// Level calibration per input device name ("" for the default device)
+    pub input_calibration: HashMap<String, InputCalibration>,
+    /// Sample rate, format and buffer size per input device name ("" for the default device)
+    pub input_overrides: HashMap<String, StreamOverride>,
+    /// Record through an echo-cancelled source so playing audio stays out of the transcript
+    pub echo_cancellation: bool,
+    /// Existing echo-cancelled source to record from (empty to load module-echo-cancel)
+    pub echo_cancel_source: String,
+    /// Switch a Bluetooth headset to its microphone profile before recording, and back afterwards
+    pub bluetooth_early_switch: bool,
+    /// Recordings shorter than this are discarded with a notice (0 keeps all)
+    pub min_recording_ms: u64,
+    /// Shorter recordings are padded with silence to this length for whisper.cpp (0 disables)
+    pub silence_padding_ms: u64,
+    /// Provider of the background final transcription
+    pub final_stt_provider: SttProviderType,
+    /// Model that transcribes each dictation again after the draft was pasted (empty disables)
+    pub final_stt_model: String,
+    /// Notify when the final transcript is ready
+    pub final_pass_notify: bool,
 }
 
 impl Default for Settings {
@@ -65,13 +254,89 @@ impl Default for Settings {
             default_llm_model: "llama3.2".to_string(),
             active_mode_key: "voice_to_text".to_string(),
             input_device: String::new(), // Empty means default
+            input_device_fallbacks: Vec::new(),
+            audio_backend: AudioBackend::Default,
+            jack_ports: Vec::new(),
             auto_paste: true,
             context_awareness: false,
             language: "en".to_string(),
+            tray_icon_style: TrayIconStyle::Color,
+            tray_icon_theme: TrayIconTheme::Auto,
+            tray_icon_size: DEFAULT_ICON_SIZE,
+            show_indicator: false,
+            indicator_monitor: "focused".to_string(),
+            indicator_corner: IndicatorCorner::TopCenter,
+            status_file: String::new(),
+            sync_folder: String::new(),
+            rich_clipboard: false,
+            low_confidence_threshold: 0.5,
+            redact_cloud_prompts: false,
+            redact_history: false,
+            redact_names: Vec::new(),
+            local_only: false,
+            keep_audio: true,
+            paste_focus_guard: true,
+            paste_allowed_windows: Vec::new(),
+            paste_chord: PasteChord::CtrlV,
+            paste_delay_ms: PasteConfig::default().delay_ms,
+            paste_overrides: Vec::new(),
+            paste_backend: None,
+            ollama_keep_alive: "30m".to_string(),
+            ollama_preload: true,
+            warm_up_pipeline: true,
+            preload_on_startup: false,
+            preload_ollama_on_startup: false,
+            cache_api_keys: false,
+            stt_server_url: String::new(),
+            stt_response_format: stt::ResponseFormat::VerboseJson,
+            output_filters: OutputFilters::default(),
+            learn_corrections: true,
+            mode_gallery_url: String::new(),
+            tray_tooltip_template: String::new(),
+            completion_notifications: false,
+            notification_rerun_mode: "email".to_string(),
+            auto_dedup: false,
+            dedup_window_secs: 10,
+            journal_enabled: false,
+            journal_dir: String::new(),
+            journal_modes: Vec::new(),
+            live_transcript_dir: String::new(),
+            streaming_provider: None,
+            streaming_model: String::new(),
+            whisper_live_url: "ws://localhost:9090".to_string(),
+            generate_titles: false,
+            embedding_provider: EmbeddingProviderType::Ollama,
+            embedding_model: "nomic-embed-text".to_string(),
+            rag_enabled: false,
+            rag_top_k: 3,
+            rag_min_similarity: 0.5,
+            hooks: Hooks::default(),
+            caldav_url: String::new(),
+            caldav_username: String::new(),
+            matrix_homeserver: "https://matrix.org".to_string(),
+            enabled_plugins: Vec::new(),
+            check_for_updates: true,
+            recording_memory_cap_mb: 64,
+            input_calibration: HashMap::new(),
+            input_overrides: HashMap::new(),
+            echo_cancellation: false,
+            echo_cancel_source: String::new(),
+            bluetooth_early_switch: false,
+            min_recording_ms: 300,
+            silence_padding_ms: 1500,
+            final_stt_provider: SttProviderType::WhisperCpp,
+            final_stt_model: String::new(),
+            final_pass_notify: false,
         }
     }
 }
 
+/// Error of history items whose dictation was cut short by quitting
+pub const INTERRUPTED_ERROR: &str = "Interrupted by shutdown";
+
+/// Pause after focusing the target window before pasting into it
+const TARGET_FOCUS_DELAY: Duration = Duration::from_millis(150);
+
 /// Main application state (Send + Sync safe)
 pub struct AppState {
     /// Tauri app handle
@@ -89,20 +354,84 @@ pub struct AppState {
     /// Recording handle (Send + Sync safe)
     pub recording_handle: RecordingHandle,
 
-    /// Database connection (wrapped in Mutex for thread safety)
-    pub database: Option<Arc<Mutex<Database>>>,
+    /// Database, owned by its worker thread
+    pub database: Option<DbHandle>,
 
     /// Application settings
     pub settings: Settings,
 
     /// Last context (clipboard text)
     pub last_context: Option<String>,
+
+    /// When the current recording started, moved forward by the time spent paused
+    pub recording_started: Option<Instant>,
+
+    /// When the current recording was paused
+    pub paused_at: Option<Instant>,
+
+    /// Bluetooth card and profile to restore once the recording ends
+    bluetooth_restore: Option<(String, String)>,
+
+    /// Bluetooth headsets the user was already warned about this session
+    bluetooth_warned: HashSet<String>,
+
+    /// Cancellation handle for in-flight processing (also managed by Tauri)
+    pub processing: Arc<ProcessingControl>,
+
+    /// Dictations, file transcriptions and reprocessing runs (also managed by Tauri)
+    pub jobs: Arc<JobQueue>,
+
+    /// API keys read from the keyring this session
+    pub keys: Arc<KeyCache>,
+
+    /// Incognito dictation: no history entries or audio files are written
+    pub incognito: bool,
+
+    /// Window focused when the current recording started
+    pub focus_at_start: Option<FocusedWindow>,
+
+    /// Window chosen to paste into instead of the one focused when recording starts
+    pub target_window: Option<FocusedWindow>,
+
+    /// Most recent output, target of follow-up refinements
+    pub last_output: Option<LastOutput>,
+
+    /// The current recording is an instruction for refining `last_output`
+    pub refining: bool,
+
+    /// Live transcript file of the current recording, saved with its history item
+    pub live_transcript: Option<PathBuf>,
+
+    /// Snippet library for "insert my ..." commands
+    pub snippets: Vec<Snippet>,
+
+    /// Ordered regex rules applied to the final output
+    pub rewrite_rules: Vec<RewriteRule>,
+
+    /// WebAssembly plugins from the plugins folder
+    pub plugins: PluginHost,
+
+    /// Newer release found by the update check
+    pub update: Option<UpdateInfo>,
+
+    /// Endpoints that served the current dictation's provider requests
+    provider_calls: Mutex<ProviderMetadata>,
+}
+
+/// The most recent dictation output
+#[derive(Debug, Clone)]
+pub struct LastOutput {
+    pub history_id: String,
+    pub mode_key: String,
+    pub text: String,
 }
 
 impl AppState {
     /// Create new application state
     pub fn new(app_handle: AppHandle) -> Result<Self> {
         let settings = Self::load_settings()?;
+        let plugins = PluginHost::load_default(&settings.enabled_plugins);
+        let jobs = Arc::new(JobQueue::with_events(app_handle.clone()));
 
         Ok(Self {
             app_handle,
@@ -113,6 +442,30 @@ impl AppState {
             database: None,
             settings,
             last_context: None,
+            recording_started: None,
+            paused_at: None,
+            bluetooth_restore: None,
+            bluetooth_warned: HashSet::new(),
+            processing: Arc::new(ProcessingControl::default()),
+            jobs,
+            keys: Arc::new(KeyCache::default()),
+            incognito: false,
+            focus_at_start: None,
+            target_window: None,
+            last_output: None,
+            refining: false,
+            live_transcript: None,
+            snippets: snippets::load().unwrap_or_else(|e| {
+                log::warn!("Failed to load snippets: {}", e);
+                Vec::new()
+            }),
+            rewrite_rules: rewrite::load().unwrap_or_else(|e| {
+                log::warn!("Failed to load rewrite rules: {}", e);
+                Vec::new()
+            }),
+            plugins,
+            update: None,
+            provider_calls: Mutex::new(ProviderMetadata::default()),
         })
     }
 
@@ -171,7 +524,7 @@ impl AppState {
     pub async fn init_database(&mut self) -> Result<()> {
         let db_path = get_database_path()?;
         let db = Database::new(&db_path)?;
-        self.database = Some(Arc::new(Mutex::new(db)));
+        self.database = Some(DbHandle::spawn(db)?);
         log::info!("Database initialized at {:?}", db_path);
         Ok(())
     }
@@ -189,14 +542,176 @@ impl AppState {
         self.active_mode_key = key.to_string();
         self.settings.active_mode_key = key.to_string();
         self.save_settings()?;
+        self.spawn_mode_check(key);
+        Ok(())
+    }
+
+    /// Modes in display order (favorites first, then user-defined order)
+    pub fn ordered_modes(&self) -> Vec<&Mode> {
+        let mut modes: Vec<&Mode> = self.modes.values().collect();
+        crate::modes::sort_modes(&mut modes);
+        modes
+    }
+
+    /// Modes shown in the tray menu: ordered, without hidden ones (the active mode is always shown)
+    pub fn visible_modes(&self) -> Vec<&Mode> {
+        self.ordered_modes()
+            .into_iter()
+            .filter(|m| !m.hidden || m.key == self.active_mode_key)
+            .collect()
+    }
+
+    /// Switch to the next (or previous) mode, returning the new active mode
+    pub fn cycle_mode(&mut self, forward: bool) -> Result<Mode> {
+        let keys: Vec<String> = self.visible_modes().iter().map(|m| m.key.clone()).collect();
+        if keys.is_empty() {
+            return Err(AppError::ModeNotFound(self.active_mode_key.clone()));
+        }
+
+        let current = keys
+            .iter()
+            .position(|k| *k == self.active_mode_key)
+            .unwrap_or(0);
+        let next = if forward {
+            (current + 1) % keys.len()
+        } else {
+            (current + keys.len() - 1) % keys.len()
+        };
+
+        self.set_active_mode(&keys[next])?;
+        self.get_active_mode()
+            .cloned()
+            .ok_or_else(|| AppError::ModeNotFound(keys[next].clone()))
+    }
+
+    /// Persist a new display order for the given mode keys
+    pub async fn reorder_modes(&mut self, keys: &[String]) -> Result<()> {
+        // Check every key first so an unknown one does not leave a half-applied order
+        if let Some(key) = keys.iter().find(|key| !self.modes.contains_key(*key)) {
+            return Err(AppError::ModeNotFound(key.clone()));
+        }
+        // Modes missing from the list move behind it instead of sharing positions
+        let order = crate::modes::reordered_keys(&self.modes, keys);
+        for (index, key) in order.iter().enumerate() {
+            if let Some(mode) = self.modes.get_mut(key) {
+                if mode.sort_order != Some(index as i32) {
+                    mode.sort_order = Some(index as i32);
+                    crate::modes::save_mode(mode).await?;
+                }
+            }
+        }
         Ok(())
     }
 
+    /// Update the favorite/hidden flags of a mode
+    pub async fn set_mode_visibility(
+        &mut self,
+        key: &str,
+        favorite: bool,
+        hidden: bool,
+    ) -> Result<()> {
+        let mode = self
+            .modes
+            .get_mut(key)
+            .ok_or_else(|| AppError::ModeNotFound(key.to_string()))?;
+        mode.favorite = favorite;
+        mode.hidden = hidden;
+        crate::modes::save_mode(mode).await
+    }
+
+    /// Create or update a mode; builtin modes are stored as overrides
+    pub async fn update_mode(&mut self, mut mode: Mode) -> Result<Mode> {
+        mode.validate()?;
+        mode.mark_builtin();
+        crate::modes::save_mode(&mode).await?;
+        self.modes.insert(mode.key.clone(), mode.clone());
+        Ok(mode)
+    }
+
+    /// Restore the shipped template of a builtin mode
+    pub async fn reset_mode(&mut self, key: &str) -> Result<Mode> {
+        let current = self
+            .modes
+            .get(key)
+            .ok_or_else(|| AppError::ModeNotFound(key.to_string()))?;
+        let mode = crate::modes::reset_mode(current).await?;
+        log::info!("Reset mode to shipped template: {}", key);
+        self.modes.insert(mode.key.clone(), mode.clone());
+        Ok(mode)
+    }
+
+    /// Bring back a previous version of a mode (the current one becomes a revision)
+    pub async fn restore_mode_revision(&mut self, key: &str, id: i64) -> Result<Mode> {
+        let current = self
+            .modes
+            .get(key)
+            .ok_or_else(|| AppError::ModeNotFound(key.to_string()))?;
+        // Display preferences are not part of the history
+        let mode = Mode {
+            sort_order: current.sort_order,
+            favorite: current.favorite,
+            hidden: current.hidden,
+            ..crate::modes::load_revision(key, id).await?
+        };
+        self.update_mode(mode).await
+    }
+
+    /// Add a downloaded mode, renaming it on key collisions.
+    ///
+    /// Builtin modes are never replaced; custom modes only with `overwrite`.
+    pub async fn install_mode(&mut self, mut mode: Mode, overwrite: bool) -> Result<Mode> {
+        let replace = overwrite && self.modes.get(&mode.key).is_some_and(|m| !m.builtin);
+        if !replace {
+            mode.key = gallery::unique_key(&mode.key, |key| self.modes.contains_key(key));
+        }
+
+        crate::modes::save_mode(&mode).await?;
+        log::info!("Installed mode: {}", mode.key);
+        self.modes.insert(mode.key.clone(), mode.clone());
+        Ok(mode)
+    }
+
     /// Check if recording is in progress
     pub fn is_recording(&self) -> bool {
         self.recording_handle.is_recording()
     }
 
+    /// Time recorded so far, not counting pauses
+    pub fn recording_elapsed(&self) -> Option<Duration> {
+        let started = self.recording_started?;
+        let until = self.paused_at.unwrap_or_else(Instant::now);
+        Some(until.saturating_duration_since(started))
+    }
+
+    /// Stop capturing audio without ending the recording
+    pub fn pause_recording(&mut self) -> Result<()> {
+        if self.status != RecordingStatus::Recording {
+            return Err(AppError::NoRecordingInProgress);
+        }
+        self.recording_handle.set_paused(true);
+        self.paused_at = Some(Instant::now());
+        self.status = RecordingStatus::Paused;
+        Ok(())
+    }
+
+    /// Continue a paused recording
+    pub fn resume_recording(&mut self) -> Result<()> {
+        if self.status != RecordingStatus::Paused {
+            return Err(AppError::NoRecordingInProgress);
+        }
+        self.end_pause();
+        self.recording_handle.set_paused(false);
+        self.status = RecordingStatus::Recording;
+        Ok(())
+    }
+
+    /// Leave out the pause from the recording's elapsed time
+    fn end_pause(&mut self) {
+        if let (Some(paused_at), Some(started)) = (self.paused_at.take(), self.recording_started) {
+            self.recording_started = Some(started + paused_at.elapsed());
+        }
+    }
+
     /// Start recording
     pub fn start_recording(&mut self) -> Result<()> {
         self.start_recording_with_callback(None)
@@ -211,71 +726,539 @@ impl AppState {
             return Err(AppError::RecordingInProgress);
         }
 
-        // Capture context if enabled
-        if self.settings.context_awareness {
-            self.last_context = paste::get_clipboard_text().ok();
+        // Refuse up front rather than after the user has finished speaking
+        if let Some(mode) = self.get_active_mode() {
+            if self.settings.local_only {
+                mode.check_local_only()?;
+            }
+            let ctx = self.mode_check_context(mode);
+            if let Some(problem) = mode_check::blocking_problem(mode, &ctx) {
+                notify::mode_problem(
+                    &self.app_handle,
+                    i18n::t("notify.cannot_record.title"),
+                    &format!("{}. {}", problem.message, problem.fix),
+                );
+                return Err(AppError::Provider(format!(
+                    "{} ({})",
+                    problem.message, problem.fix
+                )));
+            }
         }
 
+        // Capture context if enabled globally or by the mode
+        let mode_context = self
+            .get_active_mode()
+            .is_some_and(|mode| mode.clipboard_context);
+        self.last_context = if self.settings.context_awareness || mode_context {
+            paste::get_clipboard_text().ok()
+        } else {
+            None
+        };
+
+        // Remember the target window so the paste can be guarded later
+        self.focus_at_start = match &self.target_window {
+            Some(target) => Some(target.clone()),
+            None if self.settings.paste_focus_guard => window::focused_window(),
+            None => None,
+        };
+
+        // Feed the indicator's level meter alongside the caller's callback
+        let level_callback = if self.settings.show_indicator {
+            let handle = self.app_handle.clone();
+            let callback: crate::audio::LevelCallback = Box::new(move |level| {
+                if let Some(callback) = &level_callback {
+                    callback(level);
+                }
+                indicator::emit_audio_level(&handle, level, level);
+            });
+            Some(callback)
+        } else {
+            level_callback
+        };
+
+        let (device, echo_source) = self.capture_device();
+        self.recording_handle
+            .set_memory_cap(self.settings.recording_memory_cap_mb * 1024 * 1024);
+        self.recording_handle.set_level_gain(
+            self.settings
+                .input_calibration
+                .get(&device)
+                .map_or(1.0, |calibration| calibration.gain),
+        );
         crate::audio::start_recording(
             self.recording_handle.clone(),
-            &self.settings.input_device,
+            &device,
+            &self.stream_override(&device),
             level_callback,
         )?;
+        if let Some(source) = echo_source {
+            echo_cancel::move_capture(source);
+        }
         self.status = RecordingStatus::Recording;
+        self.recording_started = Some(Instant::now());
+        self.paused_at = None;
+        if self.settings.show_indicator {
+            self.show_indicator();
+        }
+
+        self.warm_up_pipeline();
+        self.start_live_transcript();
+        self.refining = false;
+
+        Ok(())
+    }
+
+    /// The first preferred input device that is plugged in ("" for the default device)
+    pub fn input_device(&self) -> String {
+        if self.settings.input_device.is_empty() {
+            return String::new();
+        }
+        let available: Vec<String> = crate::audio::get_input_devices()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|device| device.name)
+            .collect();
+        let preferences: Vec<String> = std::iter::once(self.settings.input_device.clone())
+            .chain(self.settings.input_device_fallbacks.iter().cloned())
+            .collect();
+        let device = crate::audio::pick_device(&preferences, &available);
+        if device != self.settings.input_device {
+            log::info!(
+                "Input device '{}' not present, using {}",
+                self.settings.input_device,
+                if device.is_empty() {
+                    "the default device"
+                } else {
+                    &device
+                }
+            );
+        }
+        device
+    }
+
+    /// Put a headset switched by `prepare_bluetooth` back into its previous profile
+    fn restore_bluetooth(&mut self) {
+        if let Some((card, profile)) = self.bluetooth_restore.take() {
+            bluetooth::restore(&card, &profile);
+        }
+    }
+
+    /// Device to record from, and the echo-cancelled source to move the
+    /// capture stream to when enabled
+    fn capture_device(&self) -> (String, Option<String>) {
+        if !self.settings.echo_cancellation {
+            echo_cancel::unload();
+            return (self.input_device(), None);
+        }
+        match echo_cancel::ensure_source(&self.settings.echo_cancel_source) {
+            Ok(source) => {
+                log::info!("Recording through echo-cancelled source {}", source);
+                // Only streams the sound server serves on the default device can be moved
+                (String::new(), Some(source))
+            }
+            Err(e) => {
+                log::warn!("Echo cancellation unavailable, recording without it: {}", e);
+                (self.input_device(), None)
+            }
+        }
+    }
+
+    /// Stream settings configured for an input device
+    pub fn stream_override(&self, device_name: &str) -> StreamOverride {
+        self.settings
+            .input_overrides
+            .get(device_name)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Show the recording indicator where the settings place it
+    fn show_indicator(&self) {
+        let placement = IndicatorPlacement {
+            monitor: self.settings.indicator_monitor.clone(),
+            corner: self.settings.indicator_corner,
+        };
+        // Looked up before the indicator appears, while the target window still has focus
+        let focused = (placement.monitor == "focused")
+            .then(window::focused_window_rect)
+            .flatten();
+        if let Err(e) = indicator::show_indicator(&self.app_handle, &placement, focused) {
+            log::warn!("Failed to show recording indicator: {}", e);
+        }
+    }
+
+    /// Start a recording whose transcript is an instruction applied to the last output
+    pub fn start_refinement_with_callback(
+        &mut self,
+        level_callback: Option<crate::audio::LevelCallback>,
+    ) -> Result<()> {
+        if self.last_output.is_none() {
+            return Err(AppError::NothingToRefine);
+        }
 
+        self.start_recording_with_callback(level_callback)?;
+        self.refining = true;
         Ok(())
     }
 
+    /// Start writing a live transcript if the active mode keeps one
+    fn start_live_transcript(&mut self) {
+        self.live_transcript = None;
+        let mode = match self.get_active_mode() {
+            Some(mode) if mode.live_transcript && !self.incognito => mode.clone(),
+            _ => return,
+        };
+
+        let dir = match live::resolve_dir(&self.settings.live_transcript_dir) {
+            Ok(dir) => dir,
+            Err(e) => {
+                log::warn!("Failed to start live transcript: {}", e);
+                return;
+            }
+        };
+        let path = live::transcript_path(&dir, &mode.key, chrono::Local::now());
+        let redact_names = self
+            .settings
+            .redact_history
+            .then(|| self.settings.redact_names.clone());
+        let language = self.settings.language.clone();
+        let local_only = self.settings.local_only;
+        let handle = self.recording_handle.clone();
+        let server = match self.stt_server() {
+            Ok(server) => server,
+            Err(e) => {
+                log::warn!("Failed to start live transcript: {}", e);
+                return;
+            }
+        };
+
+        if let Some(provider) = &self.settings.streaming_provider {
+            let db = self.database.clone().filter(|_| {
+                self.settings.learn_corrections
+                    && !provider.is_local(&self.settings.whisper_live_url)
+            });
+            let live_path = path.clone();
+            let result = self.stream_config(provider).and_then(|mut config| {
+                std::fs::create_dir_all(&dir)?;
+                let app_handle = self.app_handle.clone();
+                tauri::async_runtime::spawn(async move {
+                    if let Some(db) = db {
+                        let corrections = db.call(|db| db.get_corrections()).await;
+                        config.keywords = corrections::vocabulary(&corrections.unwrap_or_default());
+                    }
+                    live::spawn_streaming(app_handle, handle, config, live_path, redact_names);
+                });
+                Ok(())
+            });
+            match result {
+                Ok(()) => self.live_transcript = Some(path),
+                Err(e) => log::warn!("Failed to start live transcript: {}", e),
+            }
+            return;
+        }
+
+        self.live_transcript = Some(path.clone());
+        tauri::async_runtime::spawn(async move {
+            let result = async {
+                tokio::fs::create_dir_all(&dir).await?;
+                let provider = stt::create_stt_provider(&mode, &server, local_only).await?;
+                live::spawn(handle, provider, language, path, redact_names);
+                Ok::<_, AppError>(())
+            };
+            if let Err(e) = result.await {
+                log::warn!("Failed to start live transcript: {}", e);
+            }
+        });
+    }
+
+    /// Connection settings for the configured streaming provider
+    fn stream_config(&self, provider: &StreamingProviderType) -> Result<StreamConfig> {
+        if self.settings.local_only && !provider.is_local(&self.settings.whisper_live_url) {
+            return Err(AppError::LocalOnly(format!(
+                "{:?} streaming requires network access",
+                provider
+            )));
+        }
+        let api_key = match provider {
+            StreamingProviderType::Deepgram => self.get_api_key_by_name("deepgram")?,
+            StreamingProviderType::OpenAI => self.get_api_key(&LlmProviderType::OpenAI)?,
+            StreamingProviderType::WhisperLive => None,
+        };
+
+        Ok(StreamConfig {
+            provider: provider.clone(),
+            url: self.settings.whisper_live_url.clone(),
+            api_key,
+            model: self.settings.streaming_model.clone(),
+            language: self.settings.language.clone(),
+            keywords: Vec::new(),
+        })
+    }
+
+    /// Warm up the active mode's STT and LLM while the user is speaking
+    ///
+    /// Loads the whisper model, opens the connections to the STT server and
+    /// the LLM, and preloads the Ollama model, so less of this happens between
+    /// stopping and pasting.
+    fn warm_up_pipeline(&self) {
+        if !self.settings.warm_up_pipeline {
+            return;
+        }
+        let mode = match self.get_active_mode() {
+            Some(mode) => mode.clone(),
+            None => return,
+        };
+        let warm_llm = mode.ai_processing
+            && (mode.llm_provider != LlmProviderType::Ollama || self.settings.ollama_preload);
+        tauri::async_runtime::spawn(self.warm_up(mode, warm_llm));
+    }
+
+    /// Preload the active mode's models at startup, if enabled
+    ///
+    /// The status stays `Loading` until the returned future is done.
+    pub fn startup_preload(&mut self) -> Option<impl Future<Output = ()> + Send + 'static> {
+        if !self.settings.preload_on_startup {
+            return None;
+        }
+        let mode = self.get_active_mode()?.clone();
+        let warm_llm = mode.ai_processing
+            && mode.llm_provider == LlmProviderType::Ollama
+            && self.settings.preload_ollama_on_startup;
+        log::info!("Preloading the models of {}", mode.name);
+        self.status = RecordingStatus::Loading;
+        Some(self.warm_up(mode, warm_llm))
+    }
+
+    /// Load `mode`'s whisper model and connect to its STT server, and its LLM if `with_llm`
+    fn warm_up(&self, mode: Mode, with_llm: bool) -> impl Future<Output = ()> + Send + 'static {
+        // Warming up must not start a model download
+        let stt_ready = mode.stt_provider != SttProviderType::WhisperCpp
+            || stt::is_model_downloaded(&mode.stt_model);
+        let server = self.stt_server().ok().filter(|_| stt_ready);
+        let local_only = self.settings.local_only;
+        let llm = with_llm.then(|| self.llm_provider(&mode).ok()).flatten();
+
+        async move {
+            let warm_stt = async {
+                let server = match server {
+                    Some(server) => server,
+                    None => return,
+                };
+                let result = match stt::create_stt_provider(&mode, &server, local_only).await {
+                    Ok(provider) => provider.warm_up().await,
+                    Err(e) => Err(e),
+                };
+                if let Err(e) = result {
+                    log::warn!("Failed to warm up speech recognition: {}", e);
+                }
+            };
+            let warm_llm = async {
+                if let Some(provider) = &llm {
+                    if let Err(e) = provider.warm_up().await {
+                        log::warn!("Failed to warm up {}: {}", provider.name(), e);
+                    }
+                }
+            };
+            tokio::join!(warm_stt, warm_llm);
+        }
+    }
+
     /// Stop recording and process
     pub async fn stop_recording(&mut self) -> Result<String> {
         if !self.is_recording() {
             return Err(AppError::NoRecordingInProgress);
         }
 
+        self.end_pause();
         let samples = crate::audio::stop_recording(&self.recording_handle)?;
+        self.restore_bluetooth();
+        if self.recording_handle.is_clipping() {
+            log::warn!("The input clipped during the recording");
+            notify::send(
+                i18n::t("notify.clipping.title"),
+                i18n::t("notify.clipping.body"),
+            );
+        }
+
+        let duration_ms = crate::audio::calculate_duration_ms(samples.len());
+        if duration_ms < self.settings.min_recording_ms {
+            log::info!("Discarding {} ms recording as too short", duration_ms);
+            self.refining = false;
+            self.recording_started = None;
+            self.status = RecordingStatus::Ready;
+            let _ = indicator::hide_indicator(&self.app_handle);
+            notify::send(
+                i18n::t("notify.too_short.title"),
+                &i18n::t_args(
+                    "notify.too_short.body",
+                    &[("min", &self.settings.min_recording_ms.to_string())],
+                ),
+            );
+            return Ok(String::new());
+        }
         self.status = RecordingStatus::Processing;
+        indicator::emit_processing(&self.app_handle, true);
 
+        let mut progress =
+            ProgressReporter::new(self.app_handle.clone(), self.recording_started.take());
+        let token = self.processing.begin();
+        let label = self
+            .get_active_mode()
+            .map(|mode| mode.name.clone())
+            .unwrap_or_default();
+        let job = self
+            .jobs
+            .start(JobKind::Dictation, label, token.clone())
+            .await;
+        let span = tracing::info_span!(
+            "dictation",
+            id = tracing::field::Empty,
+            mode = tracing::field::Empty,
+        );
+        let result = if std::mem::take(&mut self.refining) {
+            self.process_refinement(samples, &mut progress, &token)
+                .instrument(span)
+                .await
+        } else {
+            self.process_recording(samples, &mut progress, &token)
+                .instrument(span)
+                .await
+        };
+        self.processing.end();
+        if let Ok(job) = job {
+            job.finish(&result);
+        }
+
+        match &result {
+            Ok(_) => progress.finish(),
+            Err(e) => progress.fail(&e.to_string()),
+        }
+        self.status = RecordingStatus::Ready;
+        indicator::emit_processing(&self.app_handle, false);
+        let _ = indicator::hide_indicator(&self.app_handle);
+
+        result
+    }
+
+    /// Run captured audio through the pipeline: save, transcribe, AI processing, paste
+    async fn process_recording(
+        &mut self,
+        samples: Vec<f32>,
+        progress: &mut ProgressReporter,
+        token: &CancellationToken,
+    ) -> Result<String> {
         // Get active mode
-        let mode = self
+        let mut mode = self
             .get_active_mode()
             .cloned()
             .ok_or_else(|| AppError::ModeNotFound(self.active_mode_key.clone()))?;
 
-        // Save audio file
-        let audio_dir = get_audio_dir()?;
-        tokio::fs::create_dir_all(&audio_dir).await?;
+        *self.provider_calls() = ProviderMetadata::default();
 
+        // Save audio file
         let audio_id = Uuid::new_v4().to_string();
-        let audio_path = audio_dir.join(format!("{}.wav", audio_id));
-        crate::audio::save_wav(&samples, &audio_path)?;
+        let span = tracing::Span::current();
+        span.record("id", audio_id.as_str());
+        span.record("mode", mode.key.as_str());
+        let audio_path = if self.incognito || !mode.keeps_audio(self.settings.keep_audio) {
+            None
+        } else {
+            progress.stage(PipelineStage::Saving);
+            let audio_dir = get_audio_dir()?;
+            tokio::fs::create_dir_all(&audio_dir).await?;
+
+            let audio_path = audio_dir.join(format!("{}.wav", audio_id));
+            crate::audio::save_wav(&samples, &audio_path)?;
+            Some(audio_path)
+        };
 
         let duration_ms = crate::audio::calculate_duration_ms(samples.len());
 
         // Transcribe
+        progress.stage(PipelineStage::Transcribing);
         log::info!("Starting transcription...");
-        let transcript = self.transcribe(&samples, &mode).await?;
+        let stt_span = tracing::info_span!(
+            "stt",
+            provider = ?mode.stt_provider,
+            model = %mode.stt_model,
+        );
+        let transcribe = self.transcribe(&samples, &mode, token).instrument(stt_span);
+        let (transcription, mut cancelled) = match run_cancellable(token, transcribe).await {
+            Ok(transcription) => (transcription, false),
+            Err(AppError::Cancelled) => (stt::Transcription::default(), true),
+            Err(e) => return Err(e),
+        };
+        let transcript = transcription.text;
+        let confidence = stt::average_confidence(&transcription.segments);
         log::info!("Transcription complete: {} chars", transcript.len());
+        let hooks = &self.settings.hooks;
+        let transcript = if cancelled {
+            transcript
+        } else {
+            hooks::apply(hooks, HookPoint::PostTranscription, transcript, &mode.key).await
+        };
+
+        // Dispatcher modes hand the transcript to the mode matching its content
+        if !cancelled && !mode.route_to.is_empty() {
+            progress.stage(PipelineStage::Llm);
+            match run_cancellable(token, self.route_mode(&transcript, &mode)).await {
+                Ok(Some(target)) => {
+                    span.record("mode", target.key.as_str());
+                    mode = target
+                }
+                Ok(None) => {}
+                Err(AppError::Cancelled) => cancelled = true,
+                Err(e) => log::warn!("Mode routing failed: {}", e),
+            }
+        }
+
+        // Spoken snippet commands become placeholders the LLM passes through
+        let expansion = snippets::prepare(&transcript, &self.snippets);
 
         // AI processing if enabled
-        let output = if mode.ai_processing && !mode.prompt_template.is_empty() {
+        let output = if !cancelled && mode.ai_processing && !mode.prompt_template.is_empty() {
+            progress.stage(PipelineStage::Llm);
             log::info!("Starting AI processing...");
-            match self.process_with_llm(&transcript, &mode).await {
-                Ok(result) => result,
+            let llm_span = tracing::info_span!(
+                "llm",
+                provider = ?mode.llm_provider,
+                model = %mode.llm_model,
+            );
+            let process = self
+                .process_with_llm(&expansion.text, &mode)
+                .instrument(llm_span);
+            match run_cancellable(token, process).await {
+                Ok(result) => {
+                    let result = hooks::apply(hooks, HookPoint::PostLlm, result, &mode.key).await;
+                    expansion.finish(&result)
+                }
+                Err(AppError::Cancelled) => {
+                    cancelled = true;
+                    transcript.clone()
+                }
                 Err(e) => {
                     log::warn!("AI processing failed: {}, using raw transcript", e);
-                    transcript.clone()
+                    expansion.finish(&expansion.text)
                 }
             }
         } else {
-            transcript.clone()
+            let corrected = corrections::apply(&expansion.text, &self.corrections().await);
+            expansion.finish(&corrected)
+        };
+        let output = rewrite::apply(&output, &self.rewrite_rules, &mode.key);
+        let output = if cancelled {
+            output
+        } else {
+            let output = self.plugins.process_text(output, &mode.key).await;
+            hooks::apply(hooks, HookPoint::PrePaste, output, &mode.key).await
         };
 
         // Save to history
-        let history_item = HistoryItem {
+        let mut history_item = HistoryItem {
             id: audio_id,
             created_at: Utc::now(),
             mode_key: mode.key.clone(),
-            audio_path: Some(audio_path.to_string_lossy().to_string()),
+            audio_path: audio_path.map(|p| p.to_string_lossy().to_string()),
             transcript_raw: transcript.clone(),
             output_final: output.clone(),
             stt_provider: format!("{:?}", mode.stt_provider).to_lowercase(),
@@ -291,120 +1274,957 @@ impl AppState {
                 None
             },
             duration_ms,
-            error: None,
+            error: cancelled.then(|| self.cancel_reason().to_string()),
+            confidence,
+            title: None,
+            latencies: None,
+            provider_metadata: Some(self.provider_calls().clone()),
+            clipped: self.recording_handle.is_clipping(),
+            transcript_path: self
+                .live_transcript
+                .as_ref()
+                .map(|path| path.to_string_lossy().to_string()),
         };
 
-        if let Some(db) = &self.database {
-            let db = db.lock().unwrap();
-            let _ = db.insert_history(&history_item);
+        let mut segments = transcription.segments;
+        if self.settings.redact_history {
+            let names = &self.settings.redact_names;
+            history_item.transcript_raw = redact::redact(&transcript, names).text;
+            history_item.output_final = redact::redact(&output, names).text;
+            for segment in &mut segments {
+                segment.text = redact::redact(&segment.text, names).text;
+            }
+        }
+
+        // A repeat of the previous dictation is removed again right away and
+        // gets none of the follow-ups below
+        let mut duplicate = false;
+        if self.incognito {
+            log::info!("Incognito dictation, not saving to history");
+        } else if let Some(db) = &self.database {
+            let item = history_item.clone();
+            let dedup_window = self
+                .settings
+                .auto_dedup
+                .then_some(self.settings.dedup_window_secs);
+            let removed = db
+                .call(move |db| {
+                    let mut removed = None;
+                    if db.insert_history(&item).is_ok() {
+                        let _ = db.insert_segments(&item.id, &segments);
+                        if let Some(window) = dedup_window {
+                            match history::dedup_latest(db, window) {
+                                Ok(id) => removed = id,
+                                Err(e) => {
+                                    log::warn!("Failed to check for duplicate recordings: {}", e)
+                                }
+                            }
+                        }
+                    }
+                    Ok(removed)
+                })
+                .await
+                .ok()
+                .flatten();
+            duplicate = removed.as_deref() == Some(history_item.id.as_str());
+        }
+        let saved = !self.incognito && !duplicate;
+
+        if !cancelled && saved && self.settings.journal_enabled {
+            self.append_to_journal(&history_item, &mode);
+        }
+
+        if cancelled {
+            log::info!("Processing cancelled, skipping paste");
+            return Err(AppError::Cancelled);
         }
 
         // Copy to clipboard and paste
-        let _ = paste::copy_and_paste(&output, self.settings.auto_paste);
+        progress.stage(PipelineStage::Pasting);
+        let html = (self.settings.rich_clipboard && mode.output_format == OutputFormat::Markdown)
+            .then(|| paste::markdown_to_html(&output));
+        let composed = mode.open_in_mail_client
+            && match mail::compose(&mail::Draft::parse(&output)).await {
+                Ok(()) => true,
+                Err(e) => {
+                    log::warn!("{}, pasting instead", e);
+                    false
+                }
+            };
+        let auto_paste = self.settings.auto_paste && !composed;
+        if auto_paste {
+            self.focus_target_window().await;
+        }
+        let should_paste = auto_paste && self.paste_target_unchanged();
+        let paste_config = should_paste.then(|| self.paste_config());
+        tracing::info_span!("paste", auto_paste = should_paste).in_scope(|| {
+            let _ = paste::copy_and_paste_rich(&output, html.as_deref(), paste_config.as_ref());
+        });
+
+        let latencies = progress.latencies();
+        tracing::info!(
+            recording_ms = latencies.recording_ms,
+            transcribing_ms = latencies.transcribing_ms,
+            llm_ms = latencies.llm_ms,
+            pasting_ms = latencies.pasting_ms,
+            "Dictation complete"
+        );
+        if let Some(db) = self.database.as_ref().filter(|_| saved) {
+            let id = history_item.id.clone();
+            if let Err(e) = db
+                .call(move |db| db.set_history_latencies(&id, &latencies))
+                .await
+            {
+                log::warn!("Failed to save stage latencies: {}", e);
+            }
+        }
 
-        self.status = RecordingStatus::Ready;
+        if !self.incognito {
+            self.plugins.consume_output(&output, &mode.key);
+        }
+        if saved && !mode.post_to.is_empty() {
+            let html = (mode.output_format == OutputFormat::Markdown)
+                .then(|| paste::markdown_to_html(&output));
+            chat::spawn(self.chat_posts(&mode.post_to), output.clone(), html);
+        }
+
+        if let Some(confidence) = confidence.filter(|_| saved) {
+            if confidence < self.settings.low_confidence_threshold {
+                notify::warn_low_confidence(&self.app_handle, &history_item.id, confidence);
+            }
+        }
+
+        if self.settings.completion_notifications && saved {
+            let rerun_mode = self
+                .modes
+                .get(&self.settings.notification_rerun_mode)
+                .filter(|m| m.key != mode.key)
+                .map(|m| (m.key.clone(), m.name.clone()));
+            notify::dictation_complete(
+                &self.app_handle,
+                notify::Completion {
+                    history_id: history_item.id.clone(),
+                    output: output.clone(),
+                    rerun_mode,
+                },
+            );
+        }
+
+        if saved {
+            self.spawn_final_pass(&history_item, &mode, samples);
+        }
+
+        if self.settings.generate_titles && saved {
+            self.spawn_title(&history_item, &mode);
+        }
+
+        let delivery = match &mode.task_target {
+            Some(target) if !cancelled && saved => match self.task_delivery(target) {
+                Ok(delivery) => Some(delivery),
+                Err(e) => {
+                    log::warn!("Cannot create tasks: {}", e);
+                    None
+                }
+            },
+            _ => None,
+        };
+        let (item_tasks, output_tasks) = match delivery {
+            Some(delivery) if delivery.target.source == TaskSource::ActionItems => {
+                (Some(delivery), None)
+            }
+            delivery => (None, delivery),
+        };
+        if let Some(delivery) = output_tasks {
+            tasks::spawn(delivery, tasks::from_output(&output));
+        }
+
+        if (mode.extract_action_items || item_tasks.is_some()) && !cancelled && saved {
+            self.spawn_action_items(&history_item, &mode, item_tasks);
+        }
+
+        if self.settings.rag_enabled && !cancelled && saved {
+            self.spawn_embedding(&history_item);
+        }
+
+        // After a duplicate, the kept item stays the one to refine
+        if !duplicate {
+            self.last_output = Some(LastOutput {
+                history_id: history_item.id,
+                mode_key: mode.key.clone(),
+                text: output.clone(),
+            });
+        }
+
+        Ok(output)
+    }
+
+    /// Apply a spoken instruction to the last output.
+    ///
+    /// The refined text replaces the output of the history item and is copied
+    /// to the clipboard without pasting, since the original text was already
+    /// inserted.
+    async fn process_refinement(
+        &mut self,
+        samples: Vec<f32>,
+        progress: &mut ProgressReporter,
+        token: &CancellationToken,
+    ) -> Result<String> {
+        let last = self.last_output.clone().ok_or(AppError::NothingToRefine)?;
+
+        // Prefer the LLM of the mode that produced the text
+        let mode = self
+            .modes
+            .get(&last.mode_key)
+            .filter(|m| !m.llm_model.is_empty())
+            .or_else(|| self.get_active_mode())
+            .cloned()
+            .ok_or_else(|| AppError::ModeNotFound(last.mode_key.clone()))?;
+        if mode.llm_model.is_empty() {
+            return Err(AppError::Config(format!(
+                "Mode '{}' has no LLM model configured for refinement",
+                mode.key
+            )));
+        }
+
+        progress.stage(PipelineStage::Transcribing);
+        let instruction = run_cancellable(token, self.transcribe(&samples, &mode, token))
+            .await?
+            .text;
+        if instruction.trim().is_empty() {
+            return Err(AppError::Transcription(
+                "No instruction recognized".to_string(),
+            ));
+        }
+        log::info!("Refining last output");
+
+        progress.stage(PipelineStage::Llm);
+        let prompt = crate::modes::render_refine_prompt(&last.text, &instruction);
+        let output = run_cancellable(token, self.complete_with(&mode, &prompt)).await?;
+
+        if !self.incognito {
+            if let Some(db) = &self.database {
+                let id = last.history_id.clone();
+                if let Ok(Some(mut item)) = db.call(move |db| db.get_history_item(&id)).await {
+                    item.output_final = if self.settings.redact_history {
+                        redact::redact(&output, &self.settings.redact_names).text
+                    } else {
+                        output.clone()
+                    };
+                    db.call(move |db| db.update_history(&item)).await?;
+                }
+            }
+        }
+
+        progress.stage(PipelineStage::Pasting);
+        paste::copy_and_paste_rich(&output, None, None)?;
+
+        self.last_output = Some(LastOutput {
+            text: output.clone(),
+            ..last
+        });
 
         Ok(output)
     }
 
+    /// Run a one-off prompt over a history item's transcript without saving anything
+    ///
+    /// Uses the LLM of `mode_key`, else of the item's mode (or the active mode
+    /// if that was deleted), falling back to the default LLM for modes without
+    /// AI processing.
+    pub async fn complete_custom_prompt(
+        &self,
+        id: &str,
+        prompt: &str,
+        mode_key: Option<&str>,
+    ) -> Result<String> {
+        if prompt.trim().is_empty() {
+            return Err(AppError::Config("The prompt is empty".to_string()));
+        }
+        let db = self
+            .database
+            .as_ref()
+            .ok_or_else(|| AppError::Config("Database not initialized".to_string()))?;
+        let id = id.to_string();
+        let item = db
+            .call(move |db| db.get_history_item(&id))
+            .await?
+            .ok_or_else(|| AppError::Config("History item not found".to_string()))?;
+
+        // A mode the caller picked must exist; the item's mode may have been deleted since
+        let mode = match mode_key {
+            Some(key) => self.modes.get(key),
+            None => self
+                .modes
+                .get(&item.mode_key)
+                .or_else(|| self.get_active_mode()),
+        }
+        .ok_or_else(|| AppError::ModeNotFound(mode_key.unwrap_or(&item.mode_key).to_string()))?;
+        let mode = self.title_mode(mode);
+
+        let prompt = crate::modes::render_custom_prompt(
+            prompt,
+            &item.transcript_raw,
+            &self.settings.language,
+        );
+        self.complete_with(&mode, &prompt).await
+    }
+
+    /// Generate a title for a saved history item in the background
+    fn spawn_title(&self, item: &HistoryItem, mode: &Mode) {
+        let db = match &self.database {
+            Some(db) => db.clone(),
+            None => return,
+        };
+        let title_mode = self.title_mode(mode);
+        let provider = match self.llm_provider(&title_mode) {
+            Ok(provider) => provider,
+            Err(e) => {
+                log::warn!("Cannot generate history title: {}", e);
+                return;
+            }
+        };
+
+        titles::spawn(
+            self.app_handle.clone(),
+            db,
+            provider,
+            item.id.clone(),
+            item.output_final.clone(),
+            self.redact_names(&title_mode).map(<[String]>::to_vec),
+        );
+    }
+
+    /// Extract the action items of a saved meeting in the background
+    fn spawn_action_items(&self, item: &HistoryItem, mode: &Mode, tasks: Option<tasks::Delivery>) {
+        let db = match &self.database {
+            Some(db) => db.clone(),
+            None => return,
+        };
+        let llm_mode = self.title_mode(mode);
+        let provider = match self.llm_provider(&llm_mode) {
+            Ok(provider) => provider,
+            Err(e) => {
+                log::warn!("Cannot extract action items: {}", e);
+                return;
+            }
+        };
+
+        action_items::spawn(
+            self.app_handle.clone(),
+            db,
+            provider,
+            item.id.clone(),
+            item.transcript_raw.clone(),
+            self.redact_names(&llm_mode).map(<[String]>::to_vec),
+            tasks,
+        );
+    }
+
+    /// Transcribe a pasted draft again with the final model in the background
+    fn spawn_final_pass(&self, item: &HistoryItem, mode: &Mode, samples: Vec<f32>) {
+        let provider = &self.settings.final_stt_provider;
+        if !final_pass::is_needed(mode, provider, &self.settings.final_stt_model) {
+            return;
+        }
+        let db = match &self.database {
+            Some(db) => db.clone(),
+            None => return,
+        };
+        let server = match self.stt_server() {
+            Ok(server) => server,
+            Err(e) => {
+                log::warn!("Cannot run the final transcription: {}", e);
+                return;
+            }
+        };
+
+        let pass = final_pass::FinalPass {
+            history_id: item.id.clone(),
+            mode: Mode {
+                stt_provider: provider.clone(),
+                stt_model: self.settings.final_stt_model.trim().to_string(),
+                ..mode.clone()
+            },
+            samples,
+            server,
+            language: self.settings.language.clone(),
+            local_only: self.settings.local_only,
+            learn_corrections: self.settings.learn_corrections,
+            silence_padding_ms: self.settings.silence_padding_ms,
+            redact_names: self
+                .settings
+                .redact_history
+                .then(|| self.settings.redact_names.clone()),
+            notify: self.settings.final_pass_notify,
+        };
+        final_pass::spawn(self.app_handle.clone(), db, pass);
+    }
+
+    /// Embed a saved history item in the background for related-context lookups
+    fn spawn_embedding(&self, item: &HistoryItem) {
+        let db = match &self.database {
+            Some(db) => db.clone(),
+            None => return,
+        };
+        match self.embedding_provider() {
+            Ok(provider) => rag::spawn_index(provider, db, self.embedding_key(), item.clone()),
+            Err(e) => log::warn!("Cannot embed dictation: {}", e),
+        }
+    }
+
+    /// Key under which embeddings of the configured provider and model are stored
+    pub fn embedding_key(&self) -> String {
+        let provider = serde_json::to_value(&self.settings.embedding_provider)
+            .ok()
+            .and_then(|v| v.as_str().map(str::to_string))
+            .unwrap_or_default();
+        rag::model_key(&provider, &self.settings.embedding_model)
+    }
+
+    /// Related past dictations for the transcript, if RAG is enabled
+    ///
+    /// Failures are logged and skipped; dictation works without the extra context.
+    async fn related_context(&self, transcript: &str) -> Option<String> {
+        let db = self.database.as_ref()?;
+        if !self.settings.rag_enabled {
+            return None;
+        }
+
+        let result = match self.embedding_provider() {
+            Ok(provider) => {
+                rag::retrieve(
+                    provider.as_ref(),
+                    db,
+                    &self.embedding_key(),
+                    transcript,
+                    self.settings.rag_top_k,
+                    self.settings.rag_min_similarity,
+                )
+                .await
+            }
+            Err(e) => Err(e),
+        };
+        result.unwrap_or_else(|e| {
+            log::warn!("Failed to look up related dictations: {}", e);
+            None
+        })
+    }
+
+    /// Mode whose LLM writes titles: the dictation's own, or the default LLM for plain modes
+    fn title_mode(&self, mode: &Mode) -> Mode {
+        if mode.ai_processing {
+            return mode.clone();
+        }
+
+        let provider = serde_json::from_value(serde_json::Value::String(
+            self.settings.default_llm_provider.clone(),
+        ))
+        .unwrap_or_default();
+        Mode {
+            llm_provider: provider,
+            llm_model: self.settings.default_llm_model.clone(),
+            ..mode.clone()
+        }
+    }
+
+    /// Append a dictation to the daily journal if its mode is journaled
+    fn append_to_journal(&self, item: &HistoryItem, mode: &Mode) {
+        if item.output_final.trim().is_empty()
+            || !journal::includes_mode(&self.settings.journal_modes, &mode.key)
+        {
+            return;
+        }
+
+        let result = journal::resolve_dir(&self.settings.journal_dir)
+            .and_then(|dir| journal::append(&dir, item, &mode.name));
+        if let Err(e) = result {
+            log::warn!("Failed to append to journal: {}", e);
+        }
+    }
+
+    /// Focus the chosen target window before pasting; forget it if it is gone
+    async fn focus_target_window(&mut self) {
+        let target = match &self.target_window {
+            Some(target) => target,
+            None => return,
+        };
+        if window::focus_window(target) {
+            // Give the window manager time to move the focus
+            tokio::time::sleep(TARGET_FOCUS_DELAY).await;
+        } else {
+            log::warn!("Could not focus the target window");
+            self.target_window = None;
+        }
+    }
+
+    /// Check the focus guard: false if focus moved to a different window since recording started
+    fn paste_target_unchanged(&mut self) -> bool {
+        let at_start = self.focus_at_start.take();
+        if !self.settings.paste_focus_guard {
+            return true;
+        }
+
+        let current = window::focused_window();
+        let allowed = window::paste_allowed(
+            at_start.as_ref(),
+            current.as_ref(),
+            &self.settings.paste_allowed_windows,
+        );
+
+        if !allowed {
+            log::warn!(
+                "Focus moved from {:?} to {:?}, skipping auto-paste",
+                at_start.map(|w| w.class),
+                current.map(|w| w.class)
+            );
+            notify::send(
+                i18n::t("notify.focus_changed.title"),
+                i18n::t("notify.focus_changed.body"),
+            );
+        }
+        allowed
+    }
+
+    /// Copy `text` and paste it into the focused window
+    pub fn paste_text(&self, text: &str) -> Result<()> {
+        paste::copy_and_paste_rich(text, None, Some(&self.paste_config()))
+    }
+
+    /// Paste chord and delay for the focused application
+    fn paste_config(&self) -> PasteConfig {
+        let base = PasteConfig {
+            chord: self.settings.paste_chord,
+            delay_ms: self.settings.paste_delay_ms,
+        };
+        if self.settings.paste_overrides.is_empty() {
+            return base;
+        }
+
+        let focused = window::focused_window();
+        paste::resolve_paste_config(base, &self.settings.paste_overrides, focused.as_ref())
+    }
+
     /// Transcribe audio samples
-    async fn transcribe(&self, samples: &[f32], mode: &Mode) -> Result<String> {
-        let provider = stt::create_stt_provider(&mode.stt_provider, &mode.stt_model).await?;
-        provider
-            .transcribe(samples, Some(&self.settings.language))
-            .await
+    async fn transcribe(
+        &self,
+        samples: &[f32],
+        mode: &Mode,
+        token: &CancellationToken,
+    ) -> Result<stt::Transcription> {
+        let mode = &corrections::boost_mode(mode, &self.corrections().await);
+        let mut provider =
+            stt::create_stt_provider(mode, &self.stt_server()?, self.settings.local_only).await?;
+        provider.set_cancellation(token.clone());
+        // whisper.cpp hallucinates on very short clips
+        let samples = if mode.stt_provider == SttProviderType::WhisperCpp {
+            crate::audio::pad_with_silence(samples, self.settings.silence_padding_ms)
+        } else {
+            Cow::Borrowed(samples)
+        };
+        let result = provider
+            .transcribe_detailed(&samples, Some(&self.settings.language))
+            .await;
+        if let Some(call) = provider.last_call() {
+            let mut calls = self.provider_calls();
+            calls.stt_endpoint = Some(call.endpoint);
+            calls.stt_retries = call.retries;
+        }
+        result
     }
 
-    /// Process transcript with LLM
-    async fn process_with_llm(&self, transcript: &str, mode: &Mode) -> Result<String> {
-        // Get API key if needed
-        let api_key = self.get_api_key(&mode.llm_provider)?;
+    /// Connection to the configured OpenAI-compatible STT server
+    ///
+    /// The OpenAI key is only sent to the OpenAI API; a custom server uses the
+    /// key saved as `stt_server`, if any.
+    pub fn stt_server(&self) -> Result<stt::SttServer> {
+        let base_url = self.settings.stt_server_url.trim().to_string();
+        let api_key = if base_url.is_empty() {
+            self.get_api_key(&LlmProviderType::OpenAI)?
+        } else {
+            self.get_api_key_by_name("stt_server")?
+        };
+
+        Ok(stt::SttServer {
+            base_url,
+            api_key,
+            response_format: self.settings.stt_response_format.clone(),
+        })
+    }
 
-        let provider = llm::create_llm_provider(
+    /// What `mode_check` needs to validate `mode`
+    ///
+    /// Keys are only read from the keyring for the providers the mode uses.
+    pub fn mode_check_context(&self, mode: &Mode) -> mode_check::CheckContext {
+        let base_url = self.settings.stt_server_url.trim().to_string();
+        let stt_server = match mode.stt_provider {
+            SttProviderType::OpenAI => self.stt_server().unwrap_or_else(|e| {
+                log::warn!("Cannot read the transcription server key: {}", e);
+                stt::SttServer {
+                    base_url,
+                    ..stt::SttServer::default()
+                }
+            }),
+            _ => stt::SttServer {
+                base_url,
+                ..stt::SttServer::default()
+            },
+        };
+        let llm_api_key = if mode.ai_processing {
+            self.get_api_key(&mode.llm_provider).ok().flatten()
+        } else {
+            None
+        };
+        mode_check::CheckContext {
+            local_only: self.settings.local_only,
+            stt_server,
+            llm_api_key,
+        }
+    }
+
+    /// Validate a mode in the background, warning about problems
+    pub fn spawn_mode_check(&self, key: &str) {
+        if let Some(mode) = self.modes.get(key) {
+            let ctx = self.mode_check_context(mode);
+            mode_check::spawn(self.app_handle.clone(), mode.clone(), ctx);
+        }
+    }
+
+    /// Classify the transcript and return the dispatcher's matching target mode
+    async fn route_mode(&self, transcript: &str, mode: &Mode) -> Result<Option<Mode>> {
+        let local_only = self.settings.local_only;
+        let candidates: Vec<&Mode> = mode
+            .route_to
+            .iter()
+            .filter_map(|key| self.modes.get(key))
+            .filter(|m| m.route_to.is_empty())
+            .filter(|m| !local_only || m.check_local_only().is_ok())
+            .collect();
+
+        let provider = self.llm_provider(mode)?;
+        let key = router::route(
+            provider.as_ref(),
+            transcript,
+            &candidates,
+            self.redact_names(mode),
+        )
+        .await?;
+        Ok(key.and_then(|key| self.modes.get(&key).cloned()))
+    }
+
+    /// Create the LLM provider configured for a mode
+    fn llm_provider(&self, mode: &Mode) -> Result<Box<dyn llm::LlmProvider>> {
+        let api_key = self.get_api_key(&mode.llm_provider)?;
+        llm::create_llm_provider(
             &mode.llm_provider,
             &mode.llm_model,
             api_key.as_deref(),
-        )?;
+            self.settings.local_only,
+            Some(&self.settings.ollama_keep_alive),
+        )
+    }
 
-        let prompt = crate::modes::render_prompt(
-            &mode.prompt_template,
-            transcript,
-            self.last_context.as_deref(),
-            &self.settings.language,
+    /// Names to redact from prompts sent to the mode's LLM, if redaction applies
+    fn redact_names(&self, mode: &Mode) -> Option<&[String]> {
+        (self.settings.redact_cloud_prompts && !mode.llm_provider.is_local())
+            .then_some(self.settings.redact_names.as_slice())
+    }
+
+    /// Send a prompt to the mode's LLM and clean up the answer
+    async fn complete_with(&self, mode: &Mode, prompt: &str) -> Result<String> {
+        let provider = self.llm_provider(mode)?;
+        let output = redact::complete(provider.as_ref(), prompt, self.redact_names(mode)).await;
+        self.record_llm_call(provider.as_ref());
+        // Preambles are only stripped for modes whose output the LLM writes
+        let filters = OutputFilters {
+            strip_preamble: self.settings.output_filters.strip_preamble && mode.ai_processing,
+            ..self.settings.output_filters
+        };
+        Ok(sanitize::sanitize(&output?, &filters))
+    }
+
+    /// Provider endpoints of the current dictation, also after a panic poisoned the lock
+    fn provider_calls(&self) -> MutexGuard<'_, ProviderMetadata> {
+        self.provider_calls
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Remember where the LLM request of the current dictation was served
+    fn record_llm_call(&self, provider: &dyn llm::LlmProvider) {
+        if let Some(call) = provider.last_call() {
+            let mut calls = self.provider_calls();
+            calls.llm_endpoint = Some(call.endpoint);
+            calls.llm_retries = call.retries;
+        }
+    }
+
+    /// Learned corrections, empty if learning is disabled or the database is unavailable
+    pub async fn corrections(&self) -> Vec<Correction> {
+        let db = match &self.database {
+            Some(db) if self.settings.learn_corrections => db,
+            _ => return Vec::new(),
+        };
+        db.call(|db| db.get_corrections()).await.unwrap_or_default()
+    }
+
+    /// Process transcript with LLM
+    async fn process_with_llm(&self, transcript: &str, mode: &Mode) -> Result<String> {
+        let corrections = self.corrections().await;
+        let hint = corrections::prompt_hint(&corrections::relevant(transcript, &corrections));
+        let related = self.related_context(transcript).await;
+        let context = plugins::join_context([
+            self.last_context.clone(),
+            related,
+            self.plugins.provide_context(&mode.key).await,
+        ]);
+        let prompt = format!(
+            "{}{}{}",
+            hint,
+            crate::modes::render_prompt(
+                &mode.prompt_template,
+                transcript,
+                context.as_deref(),
+                &self.settings.language,
+            ),
+            style::prompt_suffix(&mode.style)
         );
 
-        provider.complete(&prompt).await
+        if mode.output_format == OutputFormat::Json {
+            let provider = self.llm_provider(mode)?;
+            let output = structured::complete_json(
+                provider.as_ref(),
+                &prompt,
+                mode.json_schema.as_ref(),
+                self.redact_names(mode),
+            )
+            .await;
+            self.record_llm_call(provider.as_ref());
+            return output;
+        }
+
+        let output = self.complete_with(mode, &prompt).await?;
+        Ok(style::enforce(&output, &mode.style))
     }
 
     /// Get API key for a provider from secure storage
     pub fn get_api_key(&self, provider: &LlmProviderType) -> Result<Option<String>> {
-        let service = "whispertray";
-        let key_name = match provider {
-            LlmProviderType::OpenAI => "openai_api_key",
-            LlmProviderType::Anthropic => "anthropic_api_key",
-            LlmProviderType::Ollama => return Ok(None), // Ollama doesn't need a key
-            LlmProviderType::Custom(_) => return Ok(None),
-        };
-
-        match keyring::Entry::new(service, key_name) {
-            Ok(entry) => match entry.get_password() {
-                Ok(password) => Ok(Some(password)),
-                Err(keyring::Error::NoEntry) => Ok(None),
-                Err(e) => Err(AppError::Keyring(format!("Failed to get API key: {}", e))),
-            },
-            Err(e) => Err(AppError::Keyring(format!(
-                "Failed to access keyring: {}",
-                e
-            ))),
+        match provider {
+            LlmProviderType::OpenAI => self.get_api_key_by_name("openai"),
+            LlmProviderType::Anthropic => self.get_api_key_by_name("anthropic"),
+            LlmProviderType::Ollama => Ok(None), // Ollama doesn't need a key
+            LlmProviderType::Custom(_) => Ok(None),
         }
     }
 
-    /// Save an API key to secure storage
-    pub fn save_api_key(&self, provider: &str, key: &str) -> Result<()> {
-        let service = "whispertray";
-        let key_name = format!("{}_api_key", provider.to_lowercase());
+    /// Credentials and settings for creating tasks at `target`
+    ///
+    /// A dry run needs no credentials, so the keyring is left alone.
+    pub fn task_delivery(&self, target: &TaskTarget) -> Result<tasks::Delivery> {
+        if target.dry_run {
+            return Ok(tasks::Delivery {
+                target: target.clone(),
+                secret: None,
+                caldav_url: self.settings.caldav_url.clone(),
+                caldav_username: self.settings.caldav_username.clone(),
+            });
+        }
+        if self.settings.local_only && !target.service.is_local(&self.settings.caldav_url) {
+            return Err(AppError::LocalOnly(format!(
+                "creating {} tasks requires network access",
+                target.service.label()
+            )));
+        }
+        Ok(tasks::Delivery {
+            target: target.clone(),
+            secret: self.get_api_key_by_name(target.service.key_name())?,
+            caldav_url: self.settings.caldav_url.clone(),
+            caldav_username: self.settings.caldav_username.clone(),
+        })
+    }
 
-        let entry = keyring::Entry::new(service, &key_name)
-            .map_err(|e| AppError::Keyring(format!("Failed to access keyring: {}", e)))?;
+    /// Credentials for posting to chat targets, leaving out those that can't be used
+    pub fn chat_posts(&self, targets: &[ChatTarget]) -> Vec<chat::Post> {
+        targets
+            .iter()
+            .filter(|target| {
+                let blocked =
+                    self.settings.local_only && !target.is_local(&self.settings.matrix_homeserver);
+                if blocked {
+                    log::warn!("Not posting to {} in local-only mode", target.label());
+                }
+                !blocked
+            })
+            .map(|target| chat::Post {
+                target: target.clone(),
+                secret: self
+                    .get_api_key_by_name(target.key_name())
+                    .unwrap_or_else(|e| {
+                        log::warn!("{}", e);
+                        None
+                    }),
+                matrix_homeserver: self.settings.matrix_homeserver.clone(),
+            })
+            .collect()
+    }
 
-        entry
-            .set_password(key)
-            .map_err(|e| AppError::Keyring(format!("Failed to save API key: {}", e)))?;
+    /// Get the API key saved for a provider name from secure storage
+    pub fn get_api_key_by_name(&self, provider: &str) -> Result<Option<String>> {
+        self.keys.get(provider, self.settings.cache_api_keys)
+    }
 
+    /// Save an API key to secure storage
+    pub fn save_api_key(&self, provider: &str, key: &str) -> Result<()> {
+        keys::save(provider, key)?;
+        if self.settings.cache_api_keys {
+            self.keys.remember(provider, Some(key.to_string()));
+        }
         Ok(())
     }
 
     /// Delete an API key from secure storage
     pub fn delete_api_key(&self, provider: &str) -> Result<()> {
-        let service = "whispertray";
-        let key_name = format!("{}_api_key", provider.to_lowercase());
-
-        let entry = keyring::Entry::new(service, &key_name)
-            .map_err(|e| AppError::Keyring(format!("Failed to access keyring: {}", e)))?;
-
-        match entry.delete_password() {
-            Ok(_) => Ok(()),
-            Err(keyring::Error::NoEntry) => Ok(()), // Already deleted
-            Err(e) => Err(AppError::Keyring(format!("Failed to delete API key: {}", e))),
+        keys::delete(provider)?;
+        if self.settings.cache_api_keys {
+            self.keys.remember(provider, None);
         }
+        Ok(())
+    }
+
+    /// Create the configured embedding provider
+    pub fn embedding_provider(&self) -> Result<Box<dyn EmbeddingProvider>> {
+        let api_key = match self.settings.embedding_provider {
+            EmbeddingProviderType::OpenAI => self.get_api_key(&LlmProviderType::OpenAI)?,
+            _ => None,
+        };
+        embeddings::create_embedding_provider(
+            &self.settings.embedding_provider,
+            &self.settings.embedding_model,
+            api_key.as_deref(),
+            self.settings.local_only,
+        )
     }
 
     /// Check if an API key exists
     pub fn has_api_key(&self, provider: &str) -> bool {
-        let service = "whispertray";
-        let key_name = format!("{}_api_key", provider.to_lowercase());
+        matches!(self.get_api_key_by_name(provider), Ok(Some(_)))
+    }
 
-        keyring::Entry::new(service, &key_name)
-            .and_then(|entry| entry.get_password())
-            .is_ok()
+    /// Why the current dictation was cancelled
+    fn cancel_reason(&self) -> &'static str {
+        if self.processing.is_shutting_down() {
+            INTERRUPTED_ERROR
+        } else {
+            "Cancelled by user"
+        }
+    }
+
+    /// Stop a recording in progress because the app is quitting
+    ///
+    /// The audio so far is saved with a history item marked as interrupted, so
+    /// it can be transcribed later.
+    pub fn shut_down(&mut self) {
+        if self.is_recording() {
+            self.end_pause();
+            let result = crate::audio::stop_recording(&self.recording_handle)
+                .and_then(|samples| self.save_interrupted(&samples));
+            if let Err(e) = result {
+                log::warn!("Failed to save the interrupted recording: {}", e);
+            }
+            self.recording_started = None;
+            self.status = RecordingStatus::Ready;
+        }
+        self.restore_bluetooth();
+    }
+
+    /// Save the audio of a recording cut short by quitting
+    fn save_interrupted(&self, samples: &[f32]) -> Result<()> {
+        let mode = match self.get_active_mode() {
+            Some(mode) => mode,
+            None => return Ok(()),
+        };
+        let db = match &self.database {
+            Some(db) if !self.incognito && !samples.is_empty() => db,
+            _ => return Ok(()),
+        };
+        if !mode.keeps_audio(self.settings.keep_audio) {
+            log::info!("Not keeping audio, discarding the interrupted recording");
+            return Ok(());
+        }
+
+        let id = Uuid::new_v4().to_string();
+        let audio_dir = get_audio_dir()?;
+        std::fs::create_dir_all(&audio_dir)?;
+        let audio_path = audio_dir.join(format!("{}.wav", id));
+        crate::audio::save_wav(samples, &audio_path)?;
+
+        let item = HistoryItem {
+            id,
+            created_at: Utc::now(),
+            mode_key: mode.key.clone(),
+            audio_path: Some(audio_path.to_string_lossy().to_string()),
+            stt_provider: format!("{:?}", mode.stt_provider).to_lowercase(),
+            stt_model: mode.stt_model.clone(),
+            duration_ms: crate::audio::calculate_duration_ms(samples.len()),
+            error: Some(INTERRUPTED_ERROR.to_string()),
+            clipped: self.recording_handle.is_clipping(),
+            transcript_path: self
+                .live_transcript
+                .as_ref()
+                .map(|path| path.to_string_lossy().to_string()),
+            ..HistoryItem::default()
+        };
+        let id = item.id.clone();
+        db.call_blocking(move |db| db.insert_history(&item))?;
+        log::info!("Saved interrupted recording as {}", id);
+        Ok(())
     }
 
     /// Cancel current recording
     pub fn cancel_recording(&mut self) {
         self.recording_handle.set_recording(false);
+        self.recording_handle.set_paused(false);
+        self.recording_started = None;
+        self.paused_at = None;
+        self.restore_bluetooth();
         self.status = RecordingStatus::Ready;
     }
 }
 
 /// Shared state type for Tauri
 pub type SharedState = Arc<tokio::sync::Mutex<AppState>>;
+
+/// Warn about, or switch early, a Bluetooth headset that recording moves to its headset profile
+///
+/// Called before a recording starts. The state is only locked briefly, as
+/// switching the profile takes up to two seconds.
+pub async fn prepare_bluetooth(state: &SharedState) {
+    let early_switch = {
+        let state = state.lock().await;
+        if state.is_recording() {
+            return;
+        }
+        state.settings.bluetooth_early_switch
+    };
+    let card = match bluetooth::pending_switch().await {
+        Some(card) => card,
+        None => return,
+    };
+    if !early_switch {
+        let mut state = state.lock().await;
+        if state.bluetooth_warned.insert(card.name.clone()) {
+            log::warn!(
+                "{} switches to its headset profile when recording starts",
+                card.description
+            );
+            notify::send(
+                i18n::t("notify.bluetooth.title"),
+                &i18n::t_args("notify.bluetooth.body", &[("device", &card.description)]),
+            );
+        }
+        return;
+    }
+    match bluetooth::switch_to_headset(&card).await {
+        Ok(profile) => state.lock().await.bluetooth_restore = Some((card.name, profile)),
+        Err(e) => log::warn!("Failed to switch Bluetooth profile: {}", e),
+    }
+}
@@ -0,0 +1,476 @@
+//! Published status for panels and bars
+//!
+//! The tray reports the recording state, active mode and input level here.
+//! Subscribers are the D-Bus object and a JSON lines stream for bar modules
+//! (Waybar, Polybar): a Unix socket in the runtime directory, which
+//! `whispertray status --follow` reads, and optionally a file holding the
+//! current line.
+
+use crate::state::RecordingStatus;
+use serde::Serialize;
+use std::fs::Permissions;
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::fs::{DirBuilderExt, MetadataExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+use tokio::io::AsyncWriteExt;
+use tokio::net::UnixListener;
+use tokio::sync::watch;
+
+/// Smallest level change worth an update
+const LEVEL_STEP: f32 = 0.02;
+
+/// How long `status --follow` waits before reconnecting to a stopped instance
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Name of the socket the status stream is served on
+const SOCKET_NAME: &str = "status.sock";
+
+/// File the current status line is written to (empty path disables it)
+static STATUS_FILE: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+/// Published values
+#[derive(Debug, Clone, PartialEq)]
+pub struct PublicStatus {
+    pub status: RecordingStatus,
+    pub mode: String,
+    pub mode_name: String,
+    /// Input level while recording, 0.0 to 1.0
+    pub level: f32,
+    pub recording_since: Option<Instant>,
+}
+
+impl Default for PublicStatus {
+    fn default() -> Self {
+        Self {
+            status: RecordingStatus::Ready,
+            mode: String::new(),
+            mode_name: String::new(),
+            level: 0.0,
+            recording_since: None,
+        }
+    }
+}
+
+/// Sender for status updates (managed state)
+pub struct StatusSender(watch::Sender<PublicStatus>);
+
+/// `RecordingStatus` as published to subscribers
+pub fn status_name(status: RecordingStatus) -> &'static str {
+    match status {
+        RecordingStatus::Loading => "loading",
+        RecordingStatus::Recording => "recording",
+        RecordingStatus::Paused => "paused",
+        RecordingStatus::Processing => "processing",
+        RecordingStatus::Ready => "ready",
+        RecordingStatus::Error => "error",
+    }
+}
+
+/// Round a level to `LEVEL_STEP`, so small fluctuations do not cause updates
+fn quantize_level(level: f32) -> f32 {
+    (level.clamp(0.0, 1.0) / LEVEL_STEP).round() * LEVEL_STEP
+}
+
+/// Create the status channel and start the socket and file outputs
+pub fn init(app: &tauri::App, status_file: &str) {
+    let (sender, receiver) = watch::channel(PublicStatus::default());
+    app.manage(StatusSender(sender));
+    set_status_file(status_file);
+
+    let socket_receiver = receiver.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = serve_socket(socket_receiver).await {
+            log::warn!("Status socket unavailable: {}", e);
+        }
+    });
+    tauri::async_runtime::spawn(write_status_file(receiver));
+}
+
+/// Receiver of status updates; `None` before `init`
+pub fn subscribe(handle: &AppHandle) -> Option<watch::Receiver<PublicStatus>> {
+    handle
+        .try_state::<StatusSender>()
+        .map(|sender| sender.0.subscribe())
+}
+
+/// Change the published status; does nothing before `init`
+fn modify(handle: &AppHandle, change: impl FnOnce(&mut PublicStatus) -> bool) {
+    if let Some(sender) = handle.try_state::<StatusSender>() {
+        sender.0.send_if_modified(change);
+    }
+}
+
+/// Publish a recording status (the level drops to zero outside recording)
+pub fn set_status(handle: &AppHandle, status: RecordingStatus) {
+    modify(handle, |current| {
+        if current.status == status {
+            return false;
+        }
+        current.recording_since = match status {
+            // A resumed recording keeps counting from its start
+            RecordingStatus::Recording | RecordingStatus::Paused => {
+                current.recording_since.or_else(|| Some(Instant::now()))
+            }
+            _ => None,
+        };
+        if status != RecordingStatus::Recording {
+            current.level = 0.0;
+        }
+        current.status = status;
+        true
+    });
+}
+
+/// Publish the active mode
+pub fn set_mode(handle: &AppHandle, key: &str, name: &str) {
+    modify(handle, |current| {
+        let changed = current.mode != key || current.mode_name != name;
+        current.mode = key.to_string();
+        current.mode_name = name.to_string();
+        changed
+    });
+}
+
+/// Publish the input level
+pub fn set_level(handle: &AppHandle, level: f32) {
+    let level = quantize_level(level);
+    modify(handle, |current| {
+        let changed = current.level != level;
+        current.level = level;
+        changed
+    });
+}
+
+/// One line of the status stream, also usable as a Waybar custom module
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StatusLine {
+    /// "ready", "recording", "processing", "loading", "error" or "offline"
+    pub state: String,
+    pub mode: String,
+    pub mode_name: String,
+    /// Seconds since recording started
+    pub elapsed_secs: Option<u64>,
+    /// Short label for the bar
+    pub text: String,
+    pub tooltip: String,
+    /// CSS class for Waybar (same as `state`)
+    pub class: String,
+}
+
+impl StatusLine {
+    pub fn new(status: &PublicStatus, now: Instant) -> Self {
+        let state = status_name(status.status);
+        let elapsed_secs = status
+            .recording_since
+            .map(|since| now.saturating_duration_since(since).as_secs());
+        let text = match status.status {
+            RecordingStatus::Recording => {
+                let secs = elapsed_secs.unwrap_or_default();
+                format!("● {}:{:02}", secs / 60, secs % 60)
+            }
+            RecordingStatus::Paused => {
+                let secs = elapsed_secs.unwrap_or_default();
+                format!("⏸ {}:{:02}", secs / 60, secs % 60)
+            }
+            RecordingStatus::Processing | RecordingStatus::Loading => "…".to_string(),
+            RecordingStatus::Error => "!".to_string(),
+            RecordingStatus::Ready => status.mode_name.clone(),
+        };
+        let tooltip = if status.mode_name.is_empty() {
+            format!("WhisperTray: {}", state)
+        } else {
+            format!("WhisperTray: {} ({})", state, status.mode_name)
+        };
+
+        Self {
+            state: state.to_string(),
+            mode: status.mode.clone(),
+            mode_name: status.mode_name.clone(),
+            elapsed_secs,
+            text,
+            tooltip,
+            class: state.to_string(),
+        }
+    }
+
+    /// Line printed while no instance is running
+    pub fn offline() -> Self {
+        Self {
+            state: "offline".to_string(),
+            mode: String::new(),
+            mode_name: String::new(),
+            elapsed_secs: None,
+            text: String::new(),
+            tooltip: "WhisperTray is not running".to_string(),
+            class: "offline".to_string(),
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+}
+
+/// Status lines as they change, plus one per second while recording so the
+/// elapsed time stays current
+struct StatusLines {
+    receiver: watch::Receiver<PublicStatus>,
+    last: Option<String>,
+}
+
+impl StatusLines {
+    fn new(receiver: watch::Receiver<PublicStatus>) -> Self {
+        Self {
+            receiver,
+            last: None,
+        }
+    }
+
+    /// Next changed line; `None` once the sender is gone
+    async fn next(&mut self) -> Option<String> {
+        loop {
+            if self.last.is_some() {
+                let recording = self.receiver.borrow().recording_since.is_some();
+                if recording {
+                    tokio::select! {
+                        changed = self.receiver.changed() => changed.ok()?,
+                        _ = tokio::time::sleep(Duration::from_secs(1)) => {}
+                    }
+                } else {
+                    self.receiver.changed().await.ok()?;
+                }
+            }
+
+            let status = self.receiver.borrow_and_update().clone();
+            let line = StatusLine::new(&status, Instant::now()).to_json();
+            if self.last.as_ref() != Some(&line) {
+                self.last = Some(line.clone());
+                return Some(line);
+            }
+        }
+    }
+}
+
+/// Uid of this process, the owner of its `/proc/self`
+pub fn current_uid() -> std::io::Result<u32> {
+    std::fs::metadata("/proc/self").map(|metadata| metadata.uid())
+}
+
+/// Per-user directory for the app's sockets
+pub fn runtime_dir() -> PathBuf {
+    match std::env::var_os("XDG_RUNTIME_DIR") {
+        Some(dir) => PathBuf::from(dir).join("whispertray"),
+        // The temp dir is shared between users, so each gets their own directory
+        None => std::env::temp_dir().join(format!(
+            "whispertray-{}",
+            current_uid().map(|uid| uid.to_string()).unwrap_or_default()
+        )),
+    }
+}
+
+/// Create the runtime directory, accessible only to this user
+///
+/// A directory someone else created at the path is refused, as they could
+/// replace the sockets in it.
+pub fn create_runtime_dir() -> std::io::Result<PathBuf> {
+    let dir = runtime_dir();
+    std::fs::DirBuilder::new()
+        .recursive(true)
+        .mode(0o700)
+        .create(&dir)?;
+    let metadata = std::fs::symlink_metadata(&dir)?;
+    if !metadata.is_dir() || metadata.uid() != current_uid()? {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::PermissionDenied,
+            format!("{:?} is not a directory owned by this user", dir),
+        ));
+    }
+    if metadata.mode() & 0o077 != 0 {
+        std::fs::set_permissions(&dir, Permissions::from_mode(0o700))?;
+    }
+    Ok(dir)
+}
+
+/// Bind a socket in the runtime directory that only this user can connect to
+pub fn bind_private_socket(name: &str) -> std::io::Result<(UnixListener, PathBuf)> {
+    let path = create_runtime_dir()?.join(name);
+    // Left over from a previous run; a running instance would own the single-instance lock
+    let _ = std::fs::remove_file(&path);
+    let listener = UnixListener::bind(&path)?;
+    std::fs::set_permissions(&path, Permissions::from_mode(0o600))?;
+    Ok((listener, path))
+}
+
+/// Socket the status stream is served on
+pub fn socket_path() -> PathBuf {
+    runtime_dir().join(SOCKET_NAME)
+}
+
+async fn serve_socket(receiver: watch::Receiver<PublicStatus>) -> std::io::Result<()> {
+    let (listener, path) = bind_private_socket(SOCKET_NAME)?;
+    log::info!("Serving status on {:?}", path);
+
+    loop {
+        let (mut stream, _) = listener.accept().await?;
+        let mut lines = StatusLines::new(receiver.clone());
+        tauri::async_runtime::spawn(async move {
+            while let Some(line) = lines.next().await {
+                let line = format!("{}\n", line);
+                if stream.write_all(line.as_bytes()).await.is_err() {
+                    break;
+                }
+            }
+        });
+    }
+}
+
+/// Write the current status line to `status_file` (empty disables the file)
+pub fn set_status_file(path: &str) {
+    if let Ok(mut file) = STATUS_FILE.lock() {
+        *file = (!path.trim().is_empty()).then(|| PathBuf::from(path.trim()));
+    }
+}
+
+/// Replace the file content atomically, so readers never see a partial line
+fn replace_file(path: &Path, line: &str) -> std::io::Result<()> {
+    let temp = path.with_extension("tmp");
+    std::fs::write(&temp, format!("{}\n", line))?;
+    std::fs::rename(temp, path)
+}
+
+async fn write_status_file(receiver: watch::Receiver<PublicStatus>) {
+    let mut lines = StatusLines::new(receiver);
+    while let Some(line) = lines.next().await {
+        let path = STATUS_FILE.lock().ok().and_then(|file| file.clone());
+        if let Some(path) = path {
+            if let Err(e) = replace_file(&path, &line) {
+                log::warn!("Failed to write status file {:?}: {}", path, e);
+            }
+        }
+    }
+}
+
+/// Print the status of the running instance (`whispertray status [--follow]`)
+///
+/// With `follow`, prints a line for every change and keeps reconnecting, so a
+/// bar module survives restarts of the app; an offline line is printed while
+/// no instance runs. Returns false if the status could not be read.
+pub fn print_status(follow: bool) -> bool {
+    let mut stdout = std::io::stdout();
+    let mut offline_printed = false;
+    loop {
+        match std::os::unix::net::UnixStream::connect(socket_path()) {
+            Ok(stream) => {
+                offline_printed = false;
+                for line in BufReader::new(stream).lines() {
+                    let line = match line {
+                        Ok(line) => line,
+                        Err(_) => break,
+                    };
+                    if writeln!(stdout, "{}", line)
+                        .and_then(|_| stdout.flush())
+                        .is_err()
+                    {
+                        return false;
+                    }
+                    if !follow {
+                        return true;
+                    }
+                }
+            }
+            Err(_) if !follow => {
+                println!("{}", StatusLine::offline().to_json());
+                return false;
+            }
+            Err(_) => {}
+        }
+
+        if !offline_printed {
+            let line = StatusLine::offline().to_json();
+            if writeln!(stdout, "{}", line)
+                .and_then(|_| stdout.flush())
+                .is_err()
+            {
+                return false;
+            }
+            offline_printed = true;
+        }
+        std::thread::sleep(RECONNECT_INTERVAL);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quantize_level() {
+        assert_eq!(quantize_level(0.0), 0.0);
+        assert_eq!(quantize_level(1.7), 1.0);
+        assert_eq!(quantize_level(-0.5), 0.0);
+        assert!((quantize_level(0.509) - 0.5).abs() < 1e-6);
+        assert_eq!(quantize_level(0.501), quantize_level(0.505));
+    }
+
+    #[test]
+    fn test_status_name() {
+        assert_eq!(
+            serde_json::to_value(RecordingStatus::Processing).unwrap(),
+            status_name(RecordingStatus::Processing)
+        );
+    }
+
+    #[test]
+    fn test_status_line() {
+        let now = Instant::now();
+        let status = PublicStatus {
+            status: RecordingStatus::Recording,
+            mode: "note".to_string(),
+            mode_name: "Note".to_string(),
+            level: 0.4,
+            recording_since: Some(now - Duration::from_secs(75)),
+        };
+        let line = StatusLine::new(&status, now);
+        assert_eq!(line.state, "recording");
+        assert_eq!(line.elapsed_secs, Some(75));
+        assert_eq!(line.text, "● 1:15");
+        assert_eq!(line.tooltip, "WhisperTray: recording (Note)");
+
+        let paused = StatusLine::new(
+            &PublicStatus {
+                status: RecordingStatus::Paused,
+                ..status
+            },
+            now,
+        );
+        assert_eq!(paused.class, "paused");
+        assert_eq!(paused.text, "⏸ 1:15");
+
+        let ready = StatusLine::new(&PublicStatus::default(), now);
+        assert_eq!(ready.class, "ready");
+        assert_eq!(ready.elapsed_secs, None);
+        assert_eq!(ready.tooltip, "WhisperTray: ready");
+    }
+
+    #[tokio::test]
+    async fn test_status_lines() {
+        let (sender, receiver) = watch::channel(PublicStatus::default());
+        let mut lines = StatusLines::new(receiver);
+        assert!(lines.next().await.unwrap().contains("\"state\":\"ready\""));
+
+        // Level changes alone do not produce a new line
+        sender.send_modify(|status| status.level = 0.5);
+        sender.send_modify(|status| status.status = RecordingStatus::Processing);
+        assert!(lines
+            .next()
+            .await
+            .unwrap()
+            .contains("\"state\":\"processing\""));
+
+        drop(sender);
+        assert_eq!(lines.next().await, None);
+    }
+}
@@ -0,0 +1,218 @@
+//! Structured JSON output for integration modes
+//!
+//! Modes with `OutputFormat::Json` may define a JSON schema. The LLM output
+//! is repaired (fences, surrounding prose, trailing commas), parsed and
+//! validated against a practical subset of JSON Schema: `type`, `properties`,
+//! `required`, `items`, `enum` and `additionalProperties: false`. On failure
+//! the request is retried once with the validation error as a hint.
+
+use crate::error::{AppError, Result};
+use crate::providers::llm::LlmProvider;
+use regex::Regex;
+use serde_json::Value;
+use std::sync::OnceLock;
+
+fn trailing_comma_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r",(\s*[}\]])").unwrap())
+}
+
+/// Extract and parse JSON from LLM output, repairing common mistakes
+pub fn parse_json(text: &str) -> std::result::Result<Value, String> {
+    let text = text.trim();
+    if let Ok(value) = serde_json::from_str(text) {
+        return Ok(value);
+    }
+
+    // Cut away prose and code fences around the outermost object or array
+    let start = text
+        .find(['{', '['])
+        .ok_or("no JSON object or array found")?;
+    let end = text
+        .rfind(['}', ']'])
+        .ok_or("no JSON object or array found")?;
+    if end < start {
+        return Err("no JSON object or array found".to_string());
+    }
+
+    let candidate = trailing_comma_regex().replace_all(&text[start..=end], "$1");
+    serde_json::from_str(&candidate).map_err(|e| format!("invalid JSON: {}", e))
+}
+
+fn type_matches(value: &Value, expected: &str) -> bool {
+    match expected {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        _ => true,
+    }
+}
+
+/// Validate a value against a schema, returning the first violation
+pub fn validate(value: &Value, schema: &Value) -> std::result::Result<(), String> {
+    validate_at(value, schema, "$")
+}
+
+fn validate_at(value: &Value, schema: &Value, path: &str) -> std::result::Result<(), String> {
+    match &schema["type"] {
+        Value::String(expected) if !type_matches(value, expected) => {
+            return Err(format!("{} should be of type {}", path, expected));
+        }
+        Value::Array(types)
+            if !types
+                .iter()
+                .filter_map(Value::as_str)
+                .any(|t| type_matches(value, t)) =>
+        {
+            return Err(format!("{} has an unexpected type", path));
+        }
+        _ => {}
+    }
+
+    if let Some(allowed) = schema["enum"].as_array() {
+        if !allowed.contains(value) {
+            return Err(format!("{} must be one of {}", path, schema["enum"]));
+        }
+    }
+
+    if let Some(object) = value.as_object() {
+        if let Some(required) = schema["required"].as_array() {
+            for key in required.iter().filter_map(Value::as_str) {
+                if !object.contains_key(key) {
+                    return Err(format!("{} is missing required property \"{}\"", path, key));
+                }
+            }
+        }
+
+        let properties = schema["properties"].as_object();
+        for (key, child) in object {
+            match properties.and_then(|p| p.get(key)) {
+                Some(child_schema) => {
+                    validate_at(child, child_schema, &format!("{}.{}", path, key))?
+                }
+                None if schema["additionalProperties"] == Value::Bool(false) => {
+                    return Err(format!("{} has unexpected property \"{}\"", path, key));
+                }
+                None => {}
+            }
+        }
+    }
+
+    if let (Some(items), Some(item_schema)) = (value.as_array(), schema.get("items")) {
+        for (i, item) in items.iter().enumerate() {
+            validate_at(item, item_schema, &format!("{}[{}]", path, i))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Parse LLM output and check it against the optional schema
+fn check(output: &str, schema: Option<&Value>) -> std::result::Result<Value, String> {
+    let value = parse_json(output)?;
+    if let Some(schema) = schema {
+        validate(&value, schema)?;
+    }
+    Ok(value)
+}
+
+/// Instructions appended to the prompt of JSON modes
+fn json_instructions(schema: Option<&Value>) -> String {
+    match schema {
+        Some(schema) => format!(
+            "\n\nRespond only with JSON matching this schema, without explanations or code fences:\n{}",
+            schema
+        ),
+        None => "\n\nRespond only with valid JSON, without explanations or code fences.".to_string(),
+    }
+}
+
+/// Complete a prompt expecting JSON output, retrying once with an error hint.
+///
+/// Returns the validated JSON pretty-printed.
+pub async fn complete_json(
+    provider: &dyn LlmProvider,
+    prompt: &str,
+    schema: Option<&Value>,
+    redact_names: Option<&[String]>,
+) -> Result<String> {
+    let prompt = format!("{}{}", prompt, json_instructions(schema));
+    let output = crate::redact::complete(provider, &prompt, redact_names).await?;
+
+    let value = match check(&output, schema) {
+        Ok(value) => value,
+        Err(error) => {
+            log::warn!("LLM returned invalid JSON ({}), retrying", error);
+            let retry_prompt = format!(
+                "{}\n\nYour previous response was rejected: {}. Previous response:\n{}",
+                prompt, error, output
+            );
+            let output = crate::redact::complete(provider, &retry_prompt, redact_names).await?;
+            check(&output, schema)
+                .map_err(|e| AppError::Provider(format!("Invalid JSON output: {}", e)))?
+        }
+    };
+
+    serde_json::to_string_pretty(&value).map_err(|e| AppError::Provider(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_json_repairs_output() {
+        assert_eq!(parse_json(r#"{"a": 1}"#).unwrap(), json!({"a": 1}));
+        assert_eq!(
+            parse_json("Here you go:\n```json\n{\"a\": [1, 2,],}\n```").unwrap(),
+            json!({"a": [1, 2]})
+        );
+        assert!(parse_json("no json here").is_err());
+    }
+
+    #[test]
+    fn test_validate() {
+        let schema = json!({
+            "type": "object",
+            "required": ["title", "priority"],
+            "properties": {
+                "title": {"type": "string"},
+                "priority": {"enum": ["low", "high"]},
+                "tags": {"type": "array", "items": {"type": "string"}}
+            },
+            "additionalProperties": false
+        });
+
+        assert!(validate(&json!({"title": "Call Bob", "priority": "high"}), &schema).is_ok());
+        assert_eq!(
+            validate(&json!({"title": "Call Bob"}), &schema).unwrap_err(),
+            "$ is missing required property \"priority\""
+        );
+        assert_eq!(
+            validate(
+                &json!({"title": "x", "priority": "low", "tags": ["a", 1]}),
+                &schema
+            )
+            .unwrap_err(),
+            "$.tags[1] should be of type string"
+        );
+        assert!(validate(&json!({"title": "x", "priority": "urgent"}), &schema).is_err());
+        assert!(validate(
+            &json!({"title": "x", "priority": "low", "extra": true}),
+            &schema
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_integer_type() {
+        let schema = json!({"type": "integer"});
+        assert!(validate(&json!(3), &schema).is_ok());
+        assert!(validate(&json!(3.5), &schema).is_err());
+    }
+}
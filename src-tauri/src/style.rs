@@ -0,0 +1,126 @@
+//! Per-mode style rules
+//!
+//! Declarative tone options on a mode. They are added to the LLM prompt, and
+//! the rules that can be checked mechanically (casing, emoji) are enforced on
+//! the output as well, so the style stays the same whichever model answers.
+
+use serde::{Deserialize, Serialize};
+
+/// Style options of a mode
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StyleRules {
+    /// Casual tone in all lowercase, as in chat messages
+    pub lowercase_casual: bool,
+    /// Never use emoji
+    pub no_emoji: bool,
+    /// Longest sentence, in words (prompt only)
+    pub max_sentence_words: Option<usize>,
+}
+
+/// Instructions appended to the prompt, empty if no rule is set
+pub fn prompt_suffix(rules: &StyleRules) -> String {
+    let mut lines = Vec::new();
+    if rules.lowercase_casual {
+        lines.push("- Write in a casual tone, entirely in lowercase.".to_string());
+    }
+    if rules.no_emoji {
+        lines.push("- Do not use emoji.".to_string());
+    }
+    if let Some(words) = rules.max_sentence_words {
+        lines.push(format!("- Keep every sentence under {} words.", words));
+    }
+
+    if lines.is_empty() {
+        return String::new();
+    }
+    format!("\n\nStyle rules:\n{}", lines.join("\n"))
+}
+
+/// Whether a character is an emoji or an emoji modifier
+fn is_emoji(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x1F1E6..=0x1F1FF // regional indicators (flags)
+            | 0x1F300..=0x1FAFF // pictographs, emoticons, symbols
+            | 0x2600..=0x27BF // miscellaneous symbols, dingbats
+            | 0xFE0F // emoji presentation selector
+    )
+}
+
+/// Remove emoji, along with joiners between them and a space left before punctuation
+fn strip_emoji(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut removed = false;
+
+    for c in text.chars() {
+        if is_emoji(c) || (removed && c == '\u{200D}') {
+            removed = true;
+            continue;
+        }
+        if removed && result.ends_with(' ') && (c.is_whitespace() || c.is_ascii_punctuation()) {
+            result.pop();
+        }
+        removed = false;
+        result.push(c);
+    }
+    if removed {
+        result.truncate(result.trim_end_matches(' ').len());
+    }
+    result
+}
+
+/// Enforce the rules that can be applied to finished text
+pub fn enforce(text: &str, rules: &StyleRules) -> String {
+    let mut text = text.to_string();
+    if rules.no_emoji {
+        text = strip_emoji(&text);
+    }
+    if rules.lowercase_casual {
+        text = text.to_lowercase();
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prompt_suffix() {
+        assert_eq!(prompt_suffix(&StyleRules::default()), "");
+
+        let rules = StyleRules {
+            no_emoji: true,
+            max_sentence_words: Some(15),
+            ..StyleRules::default()
+        };
+        assert_eq!(
+            prompt_suffix(&rules),
+            "\n\nStyle rules:\n- Do not use emoji.\n- Keep every sentence under 15 words."
+        );
+    }
+
+    #[test]
+    fn test_strip_emoji() {
+        assert_eq!(strip_emoji("Great job 🎉!"), "Great job!");
+        assert_eq!(strip_emoji("Ship it 🚀 today"), "Ship it today");
+        assert_eq!(strip_emoji("Thanks 👍🏽"), "Thanks");
+        assert_eq!(strip_emoji("Family: 👨‍👩‍👧 done"), "Family: done");
+        assert_eq!(
+            strip_emoji("Plain text, no change."),
+            "Plain text, no change."
+        );
+    }
+
+    #[test]
+    fn test_enforce() {
+        let rules = StyleRules {
+            lowercase_casual: true,
+            no_emoji: true,
+            ..StyleRules::default()
+        };
+        assert_eq!(enforce("See You At 5 😀", &rules), "see you at 5");
+        assert_eq!(enforce("Keep 😀 It", &StyleRules::default()), "Keep 😀 It");
+    }
+}
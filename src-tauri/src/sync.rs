@@ -0,0 +1,397 @@
+//! Configuration sync through a shared folder
+//!
+//! When `sync_folder` points at a folder kept in sync by Syncthing, Nextcloud
+//! or similar, the settings, customized modes, rewrite rules and snippets are
+//! written there as `whispertray.json` and picked up by the other machines.
+//! The folder is polled; when both sides changed since the last sync the
+//! newer change wins and the other one is saved under `backups/`.
+//!
+//! Anyone who can write to the folder can change the shared file, so the
+//! synced settings leave out hooks, plugins and the privacy switches, like
+//! configuration bundles do.
+
+use crate::config_bundle::{self, ConfigBundle, Manifest};
+use crate::error::{AppError, Result};
+use crate::modes::Mode;
+use crate::rewrite::RewriteRule;
+use crate::snippets::Snippet;
+use crate::state::{AppState, SharedState};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::AppHandle;
+
+/// Name of the shared file in the sync folder
+pub const SYNC_FILE: &str = "whispertray.json";
+
+/// How often the folder and the local configuration are checked
+const POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Contents of the shared file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncDocument {
+    pub manifest: Manifest,
+    /// When the configuration in this document was changed
+    pub updated_at: DateTime<Utc>,
+    /// Machine that wrote the document
+    pub machine: String,
+    pub settings: Option<Value>,
+    pub modes: Vec<Mode>,
+    pub rewrite_rules: Vec<RewriteRule>,
+    pub snippets: Vec<Snippet>,
+}
+
+impl SyncDocument {
+    fn new(bundle: ConfigBundle, updated_at: DateTime<Utc>) -> Self {
+        Self {
+            manifest: bundle.manifest,
+            updated_at,
+            machine: machine_name(),
+            settings: bundle.settings,
+            modes: bundle.modes,
+            rewrite_rules: bundle.rewrite_rules,
+            snippets: bundle.snippets,
+        }
+    }
+
+    fn into_bundle(self) -> ConfigBundle {
+        ConfigBundle {
+            manifest: self.manifest,
+            settings: self.settings,
+            modes: self.modes,
+            rewrite_rules: self.rewrite_rules,
+            snippets: self.snippets,
+        }
+    }
+}
+
+/// What to do after comparing both sides
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncAction {
+    Nothing,
+    /// Write the local configuration to the folder
+    Push {
+        backup_remote: bool,
+    },
+    /// Apply the folder's configuration locally
+    Pull {
+        backup_local: bool,
+    },
+}
+
+/// Decide how to sync
+///
+/// `local_changed` is when the local configuration last changed since the
+/// last sync, `remote` the time of the shared document and `seen` the time of
+/// the document this machine last wrote or applied.
+pub fn decide(
+    local_changed: Option<DateTime<Utc>>,
+    remote: Option<DateTime<Utc>>,
+    seen: Option<DateTime<Utc>>,
+) -> SyncAction {
+    let remote_changed = remote.filter(|remote| Some(*remote) != seen);
+    match (local_changed, remote_changed) {
+        (None, None) if remote.is_none() => SyncAction::Push {
+            backup_remote: false,
+        },
+        (None, None) => SyncAction::Nothing,
+        (Some(_), None) => SyncAction::Push {
+            backup_remote: false,
+        },
+        (None, Some(_)) => SyncAction::Pull {
+            backup_local: false,
+        },
+        (Some(local), Some(remote)) if local >= remote => SyncAction::Push {
+            backup_remote: true,
+        },
+        (Some(_), Some(_)) => SyncAction::Pull { backup_local: true },
+    }
+}
+
+/// Host name used to tell machines apart in backups
+fn machine_name() -> String {
+    std::fs::read_to_string("/etc/hostname")
+        .ok()
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .or_else(|| std::env::var("HOSTNAME").ok())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// The part of the configuration that is synced, for change detection
+fn fingerprint(bundle: &ConfigBundle) -> Result<String> {
+    Ok(serde_json::to_string(&(
+        &bundle.settings,
+        &bundle.modes,
+        &bundle.rewrite_rules,
+        &bundle.snippets,
+    ))?)
+}
+
+fn read_document(path: &Path) -> Result<Option<SyncDocument>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content = std::fs::read_to_string(path)?;
+    Ok(Some(serde_json::from_str(&content)?))
+}
+
+/// Write a document, replacing the file at once so other machines never see half of it
+fn write_document(path: &Path, document: &SyncDocument) -> Result<()> {
+    let content = serde_json::to_string_pretty(document)?;
+    let tmp = path.with_file_name(format!(".{}.tmp", SYNC_FILE));
+    std::fs::write(&tmp, content)?;
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+/// Keep the losing side of a conflict under `backups/`
+fn write_backup(folder: &Path, document: &SyncDocument) -> Result<PathBuf> {
+    let dir = folder.join("backups");
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join(format!(
+        "{}-{}.json",
+        document.updated_at.format("%Y%m%d-%H%M%S"),
+        document.machine
+    ));
+    std::fs::write(&path, serde_json::to_string_pretty(document)?)?;
+    Ok(path)
+}
+
+/// Sync progress for the current folder
+#[derive(Default)]
+struct Tracker {
+    folder: String,
+    fingerprint: Option<String>,
+    local_changed: Option<DateTime<Utc>>,
+    seen: Option<DateTime<Utc>>,
+    last_error: Option<String>,
+}
+
+impl Tracker {
+    /// Compare both sides and sync; the app state is only locked to read or apply the configuration
+    async fn tick(&mut self, handle: &AppHandle, state: &SharedState) -> Result<()> {
+        let (folder, local) = {
+            let guard = state.lock().await;
+            (
+                guard.settings.sync_folder.clone(),
+                config_bundle::export(&guard),
+            )
+        };
+        if folder != self.folder {
+            *self = Tracker {
+                folder,
+                ..Tracker::default()
+            };
+        }
+        if self.folder.is_empty() {
+            return Ok(());
+        }
+
+        let local_fingerprint = fingerprint(&local)?;
+        if self
+            .fingerprint
+            .as_ref()
+            .is_some_and(|previous| *previous != local_fingerprint)
+            && self.local_changed.is_none()
+        {
+            self.local_changed = Some(Utc::now());
+        }
+        self.fingerprint = Some(local_fingerprint);
+
+        let folder = PathBuf::from(&self.folder);
+        let path = folder.join(SYNC_FILE);
+        let remote = {
+            let (folder, path) = (folder.clone(), path.clone());
+            blocking(move || {
+                std::fs::create_dir_all(&folder)?;
+                read_document(&path)
+            })
+            .await?
+        };
+
+        match decide(
+            self.local_changed,
+            remote.as_ref().map(|r| r.updated_at),
+            self.seen,
+        ) {
+            SyncAction::Nothing => {}
+            SyncAction::Push { backup_remote } => {
+                let updated_at = self.local_changed.unwrap_or_else(Utc::now);
+                let remote = remote.filter(|_| backup_remote);
+                let document = SyncDocument::new(local, updated_at);
+                let written = path.clone();
+                let backup = blocking(move || {
+                    let backup = match remote {
+                        Some(remote) => Some(write_backup(&folder, &remote)?),
+                        None => None,
+                    };
+                    write_document(&written, &document)?;
+                    Ok(backup)
+                })
+                .await?;
+                if let Some(backup) = backup {
+                    log::warn!(
+                        "Sync conflict, kept local changes; the other version is in {}",
+                        backup.display()
+                    );
+                }
+                log::info!("Synced configuration to {}", path.display());
+                self.seen = Some(updated_at);
+                self.local_changed = None;
+            }
+            SyncAction::Pull { backup_local } => {
+                let remote = match remote {
+                    Some(remote) => remote,
+                    None => return Ok(()),
+                };
+                let mut warnings = config_bundle::check_manifest(&remote.manifest)?;
+                if backup_local {
+                    let updated_at = self.local_changed.unwrap_or_else(Utc::now);
+                    let document = SyncDocument::new(local, updated_at);
+                    let backup = blocking(move || write_backup(&folder, &document)).await?;
+                    warnings.push(format!(
+                        "Sync conflict, applied the newer changes from {}; local version saved in {}",
+                        remote.machine,
+                        backup.display()
+                    ));
+                }
+
+                let updated_at = remote.updated_at;
+                log::info!("Applying synced configuration from {}", remote.machine);
+                let mut guard = state.lock().await;
+                let report =
+                    config_bundle::import(&mut guard, remote.into_bundle(), warnings).await?;
+                for warning in &report.warnings {
+                    log::warn!("{}", warning);
+                }
+                refresh(handle, &mut guard).await;
+
+                self.fingerprint = Some(fingerprint(&config_bundle::export(&guard))?);
+                self.seen = Some(updated_at);
+                self.local_changed = None;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Run file system work off the async runtime
+async fn blocking<T: Send + 'static>(
+    work: impl FnOnce() -> Result<T> + Send + 'static,
+) -> Result<T> {
+    tokio::task::spawn_blocking(work)
+        .await
+        .map_err(|e| AppError::Config(format!("Sync task failed: {}", e)))?
+}
+
+/// Apply imported settings to the running app
+async fn refresh(handle: &AppHandle, state: &mut AppState) {
+    let enabled_plugins = state.settings.enabled_plugins.clone();
+    state.plugins.set_enabled(&enabled_plugins);
+    crate::i18n::set_language(&state.settings.language);
+    if let Err(e) = crate::tray::update_tray_menu(handle, state).await {
+        log::error!("Failed to update tray menu: {}", e);
+    }
+    if let Err(e) = crate::tray::apply_icon_settings(handle, state) {
+        log::error!("Failed to update tray icon: {}", e);
+    }
+}
+
+/// Watch the sync folder for as long as the app runs
+pub fn start(handle: AppHandle, state: SharedState) {
+    tauri::async_runtime::spawn(async move {
+        let mut tracker = Tracker::default();
+        loop {
+            match tracker.tick(&handle, &state).await {
+                Ok(()) => tracker.last_error = None,
+                Err(e) => {
+                    let message = e.to_string();
+                    if tracker.last_error.as_ref() != Some(&message) {
+                        log::warn!("Configuration sync failed: {}", message);
+                        tracker.last_error = Some(message);
+                    }
+                }
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::Settings;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_decide() {
+        let at = |secs| Some(Utc.timestamp_opt(secs, 0).unwrap());
+
+        // First sync into an empty folder
+        assert_eq!(
+            decide(None, None, None),
+            SyncAction::Push {
+                backup_remote: false
+            }
+        );
+        // Nothing changed
+        assert_eq!(decide(None, at(10), at(10)), SyncAction::Nothing);
+        // One side changed
+        assert_eq!(
+            decide(at(20), at(10), at(10)),
+            SyncAction::Push {
+                backup_remote: false
+            }
+        );
+        assert_eq!(
+            decide(None, at(20), at(10)),
+            SyncAction::Pull {
+                backup_local: false
+            }
+        );
+        // Both changed, newest wins
+        assert_eq!(
+            decide(at(30), at(20), at(10)),
+            SyncAction::Push {
+                backup_remote: true
+            }
+        );
+        assert_eq!(
+            decide(at(20), at(30), at(10)),
+            SyncAction::Pull { backup_local: true }
+        );
+    }
+
+    #[test]
+    fn test_document_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(SYNC_FILE);
+        assert!(read_document(&path).unwrap().is_none());
+
+        let bundle = ConfigBundle::new(Settings::default(), Vec::new(), Vec::new(), Vec::new());
+        let document = SyncDocument::new(bundle, Utc::now());
+        write_document(&path, &document).unwrap();
+        let read = read_document(&path).unwrap().unwrap();
+        assert_eq!(read.updated_at, document.updated_at);
+
+        let backup = write_backup(dir.path(), &read).unwrap();
+        assert!(backup.starts_with(dir.path().join("backups")));
+    }
+
+    #[test]
+    fn test_document_leaves_out_protected_settings() {
+        let mut settings = Settings::default();
+        settings.hooks.pre_paste = "~/bin/fix-jargon.py".to_string();
+        settings.local_only = true;
+        let bundle = ConfigBundle::new(settings, Vec::new(), Vec::new(), Vec::new());
+        let document = SyncDocument::new(bundle, Utc::now());
+        let shared = document.settings.unwrap();
+        assert!(shared.get("hooks").is_none());
+        assert!(shared.get("enabled_plugins").is_none());
+        assert!(shared.get("local_only").is_none());
+        assert!(shared.get("language").is_some());
+    }
+}
@@ -0,0 +1,409 @@
+//! Task manager output
+//!
+//! Modes with a `task_target` turn each dictation into tasks in Todoist (with
+//! the API token saved as `todoist` in the keyring) or in a CalDAV task list
+//! (VTODO, with the password saved as `caldav`). A list in the output becomes
+//! one task per item; Meeting modes can send their action items instead. With
+//! `dry_run` the tasks are only shown in a notification, so a target can be
+//! tried out before anything is created.
+
+use crate::database::ActionItem;
+use crate::error::{AppError, Result};
+use crate::i18n::t_args;
+use crate::notify;
+use crate::providers::{http_client, is_local_url};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+/// Todoist endpoint tasks are created at
+const TODOIST_TASKS_URL: &str = "https://api.todoist.com/api/v1/tasks";
+
+/// Longest task title; the rest of the text goes into the description
+const MAX_CONTENT_CHARS: usize = 200;
+
+/// Task manager tasks are created in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TaskService {
+    Todoist,
+    Caldav,
+}
+
+impl TaskService {
+    /// Name the token or password is saved under in the keyring
+    pub fn key_name(&self) -> &'static str {
+        match self {
+            TaskService::Todoist => "todoist",
+            TaskService::Caldav => "caldav",
+        }
+    }
+
+    /// Whether tasks stay on the local network: only for a CalDAV server on
+    /// this machine or a private address
+    pub fn is_local(&self, caldav_url: &str) -> bool {
+        matches!(self, TaskService::Caldav) && is_local_url(caldav_url)
+    }
+
+    /// Service name for messages
+    pub fn label(&self) -> &'static str {
+        match self {
+            TaskService::Todoist => "Todoist",
+            TaskService::Caldav => "CalDAV",
+        }
+    }
+}
+
+/// What the tasks are made from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskSource {
+    /// The final output, one task per list item
+    #[default]
+    Output,
+    /// The action items extracted from a meeting
+    ActionItems,
+}
+
+/// Where a mode's dictations become tasks
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TaskTarget {
+    pub service: TaskService,
+    #[serde(default)]
+    pub source: TaskSource,
+    /// Todoist project id (empty for the inbox)
+    #[serde(default)]
+    pub project_id: String,
+    /// Only show the tasks that would be created
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// A task to be created
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct NewTask {
+    pub content: String,
+    pub description: String,
+    /// Due date as spoken, e.g. "Friday"
+    pub due: Option<String>,
+}
+
+/// Where and with which credentials tasks are created
+#[derive(Debug, Clone)]
+pub struct Delivery {
+    pub target: TaskTarget,
+    /// Todoist API token or CalDAV password (not needed for a dry run)
+    pub secret: Option<String>,
+    pub caldav_url: String,
+    pub caldav_username: String,
+}
+
+/// Tasks from an output: one per Markdown list item, or a single task
+pub fn from_output(output: &str) -> Vec<NewTask> {
+    let items: Vec<&str> = output.lines().filter_map(list_item).collect();
+    if !items.is_empty() {
+        return items
+            .into_iter()
+            .map(|item| NewTask {
+                content: item.to_string(),
+                description: String::new(),
+                due: None,
+            })
+            .collect();
+    }
+
+    let mut lines = output.trim().lines();
+    let content = match lines.next() {
+        Some(line) => line.trim_start_matches('#').trim(),
+        None => return Vec::new(),
+    };
+    let rest = lines.collect::<Vec<_>>().join("\n");
+    let mut task = NewTask {
+        content: content.chars().take(MAX_CONTENT_CHARS).collect(),
+        description: rest.trim().to_string(),
+        due: None,
+    };
+    if content.chars().count() > MAX_CONTENT_CHARS {
+        task.description = output.trim().to_string();
+    }
+    vec![task]
+}
+
+/// Text of a Markdown list item line, including task list items
+fn list_item(line: &str) -> Option<&str> {
+    let line = line.trim();
+    let rest = ["- ", "* ", "+ "]
+        .iter()
+        .find_map(|marker| line.strip_prefix(marker))
+        .or_else(|| {
+            let digits = line.find(|c: char| !c.is_ascii_digit())?;
+            line[digits..].strip_prefix(". ").filter(|_| digits > 0)
+        })?;
+    let rest = ["[ ] ", "[x] ", "[X] "]
+        .iter()
+        .find_map(|check| rest.strip_prefix(check))
+        .unwrap_or(rest)
+        .trim();
+    Some(rest).filter(|rest| !rest.is_empty())
+}
+
+/// Tasks from meeting action items, with the owner in the description
+pub fn from_action_items(items: &[ActionItem]) -> Vec<NewTask> {
+    items
+        .iter()
+        .map(|item| NewTask {
+            content: item.task.clone(),
+            description: item
+                .owner
+                .as_ref()
+                .map(|owner| format!("Owner: {}", owner))
+                .unwrap_or_default(),
+            due: item.due.clone(),
+        })
+        .collect()
+}
+
+/// Create the tasks, returning how many were created
+pub async fn create(delivery: &Delivery, tasks: &[NewTask]) -> Result<usize> {
+    let secret = delivery.secret.as_deref().ok_or_else(|| {
+        AppError::Config(format!(
+            "No {} credentials saved as `{}` in the keyring",
+            delivery.target.service.label(),
+            delivery.target.service.key_name()
+        ))
+    })?;
+    for task in tasks {
+        match delivery.target.service {
+            TaskService::Todoist => create_todoist(&delivery.target, secret, task).await?,
+            TaskService::Caldav => create_caldav(delivery, secret, task).await?,
+        }
+    }
+    Ok(tasks.len())
+}
+
+async fn create_todoist(target: &TaskTarget, token: &str, task: &NewTask) -> Result<()> {
+    let mut body = json!({
+        "content": task.content,
+        "description": task.description,
+    });
+    if !target.project_id.trim().is_empty() {
+        body["project_id"] = json!(target.project_id.trim());
+    }
+    if let Some(due) = &task.due {
+        // Todoist understands spoken dates like "next Friday"
+        body["due_string"] = json!(due);
+    }
+
+    let response = http_client()
+        .post(TODOIST_TASKS_URL)
+        .bearer_auth(token)
+        .json(&body)
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let text = response.text().await.unwrap_or_default();
+        return Err(AppError::Provider(format!(
+            "Todoist error {}: {}",
+            status, text
+        )));
+    }
+    Ok(())
+}
+
+async fn create_caldav(delivery: &Delivery, password: &str, task: &NewTask) -> Result<()> {
+    let base = delivery.caldav_url.trim().trim_end_matches('/');
+    if base.is_empty() {
+        return Err(AppError::Config("No CalDAV task list URL set".to_string()));
+    }
+    let uid = format!("{}@whispertray", uuid::Uuid::new_v4());
+    let response = http_client()
+        .put(format!("{}/{}.ics", base, uid))
+        .basic_auth(&delivery.caldav_username, Some(password))
+        .header("Content-Type", "text/calendar; charset=utf-8")
+        .header("If-None-Match", "*")
+        .body(vtodo(task, &uid, Utc::now()))
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        return Err(AppError::Provider(format!(
+            "CalDAV error {}",
+            response.status()
+        )));
+    }
+    Ok(())
+}
+
+/// An iCalendar VTODO for a task
+///
+/// Spoken due dates can't be turned into a `DUE` date reliably, so they are
+/// added to the description.
+pub fn vtodo(task: &NewTask, uid: &str, now: DateTime<Utc>) -> String {
+    let mut description = task.description.clone();
+    if let Some(due) = &task.due {
+        if !description.is_empty() {
+            description.push('\n');
+        }
+        description.push_str(&format!("Due: {}", due));
+    }
+
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//WhisperTray//EN".to_string(),
+        "BEGIN:VTODO".to_string(),
+        format!("UID:{}", uid),
+        format!("DTSTAMP:{}", now.format("%Y%m%dT%H%M%SZ")),
+        format!("SUMMARY:{}", escape_text(&task.content)),
+    ];
+    if !description.is_empty() {
+        lines.push(format!("DESCRIPTION:{}", escape_text(&description)));
+    }
+    lines.push("STATUS:NEEDS-ACTION".to_string());
+    lines.push("END:VTODO".to_string());
+    lines.push("END:VCALENDAR".to_string());
+    lines.iter().map(|line| fold(line) + "\r\n").collect()
+}
+
+/// Escape an iCalendar TEXT value
+fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace(['\r', '\n'], "\\n")
+}
+
+/// Fold a content line to 75 octets, continuing with a space
+fn fold(line: &str) -> String {
+    let mut folded = String::new();
+    let mut octets = 0;
+    for c in line.chars() {
+        if octets + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            octets = 1;
+        }
+        folded.push(c);
+        octets += c.len_utf8();
+    }
+    folded
+}
+
+/// Create the tasks in the background, or only show them for a dry run
+pub fn spawn(delivery: Delivery, tasks: Vec<NewTask>) {
+    tauri::async_runtime::spawn(deliver(delivery, tasks));
+}
+
+/// Create the tasks, or only show them for a dry run
+pub async fn deliver(delivery: Delivery, tasks: Vec<NewTask>) {
+    if tasks.is_empty() {
+        return;
+    }
+    let service = delivery.target.service.label();
+    let count = tasks.len().to_string();
+
+    if delivery.target.dry_run {
+        let body = tasks
+            .iter()
+            .map(|task| format!("• {}", task.content))
+            .collect::<Vec<_>>()
+            .join("\n");
+        log::info!("Dry run, not creating tasks in {}:\n{}", service, body);
+        notify::send(
+            &t_args("tasks.dry_run", &[("count", &count), ("service", service)]),
+            &body,
+        );
+        return;
+    }
+
+    match create(&delivery, &tasks).await {
+        Ok(_) => notify::send(
+            "WhisperTray",
+            &t_args("tasks.created", &[("count", &count), ("service", service)]),
+        ),
+        Err(e) => {
+            log::error!("Failed to create tasks: {}", e);
+            notify::send(
+                &t_args("tasks.failed", &[("service", service)]),
+                &e.to_string(),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_tasks_from_list_output() {
+        let output = "Shopping:\n- Milk\n* [ ] Bread\n2. Call the plumber\n-\n";
+        let contents: Vec<_> = from_output(output)
+            .into_iter()
+            .map(|task| task.content)
+            .collect();
+        assert_eq!(contents, ["Milk", "Bread", "Call the plumber"]);
+    }
+
+    #[test]
+    fn test_task_from_plain_output() {
+        let tasks = from_output("# Renew passport\n\nThe old one expires in May.");
+        assert_eq!(
+            tasks,
+            vec![NewTask {
+                content: "Renew passport".to_string(),
+                description: "The old one expires in May.".to_string(),
+                due: None,
+            }]
+        );
+        assert!(from_output("  ").is_empty());
+    }
+
+    #[test]
+    fn test_tasks_from_action_items() {
+        let tasks = from_action_items(&[ActionItem {
+            owner: Some("Anna".to_string()),
+            task: "Send the budget".to_string(),
+            due: Some("Friday".to_string()),
+        }]);
+        assert_eq!(tasks[0].description, "Owner: Anna");
+        assert_eq!(tasks[0].due.as_deref(), Some("Friday"));
+    }
+
+    #[test]
+    fn test_vtodo() {
+        let task = NewTask {
+            content: "Send the budget, final version".to_string(),
+            description: "Owner: Anna".to_string(),
+            due: Some("Friday".to_string()),
+        };
+        let now = Utc.with_ymd_and_hms(2024, 5, 14, 9, 5, 7).unwrap();
+        let ics = vtodo(&task, "1@whispertray", now);
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(ics.contains("DTSTAMP:20240514T090507Z\r\n"));
+        assert!(ics.contains("SUMMARY:Send the budget\\, final version\r\n"));
+        assert!(ics.contains("DESCRIPTION:Owner: Anna\\nDue: Friday\r\n"));
+        assert!(ics.ends_with("END:VTODO\r\nEND:VCALENDAR\r\n"));
+    }
+
+    #[test]
+    fn test_escape_text_line_breaks() {
+        assert_eq!(escape_text("a\r\nb\rc\nd"), "a\\nb\\nc\\nd");
+    }
+
+    #[test]
+    fn test_is_local() {
+        assert!(TaskService::Caldav.is_local("http://192.168.1.10/remote.php/dav/"));
+        assert!(!TaskService::Caldav.is_local("https://cloud.example.com/remote.php/dav/"));
+        assert!(!TaskService::Todoist.is_local("http://localhost"));
+    }
+
+    #[test]
+    fn test_fold_long_lines() {
+        let line = format!("SUMMARY:{}", "ä".repeat(60));
+        let folded = fold(&line);
+        assert!(folded.split("\r\n").all(|part| part.len() <= 75));
+        assert_eq!(folded.replace("\r\n ", ""), line);
+    }
+}
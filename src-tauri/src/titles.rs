@@ -0,0 +1,128 @@
+//! Short titles for history items
+//!
+//! After a dictation is saved, a brief LLM call summarizes it as a title such
+//! as "Reply to Anna about Q3 budget" so the history list is scannable. The
+//! title is generated in the background and never delays the paste.
+
+use crate::db_worker::DbHandle;
+use crate::error::Result;
+use crate::providers::llm::LlmProvider;
+use crate::redact;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+/// Event emitted when a history item got its title
+pub const HISTORY_TITLE_EVENT: &str = "history-title";
+
+/// Longest title kept, in characters
+const MAX_TITLE_CHARS: usize = 80;
+
+/// Only the start of long dictations is sent to the LLM
+const MAX_PROMPT_CHARS: usize = 4000;
+
+/// Payload of `HISTORY_TITLE_EVENT`
+#[derive(Debug, Clone, Serialize)]
+pub struct TitleUpdate {
+    pub history_id: String,
+    pub title: String,
+}
+
+/// Build the prompt asking for a title
+pub fn title_prompt(text: &str) -> String {
+    let text: String = text.chars().take(MAX_PROMPT_CHARS).collect();
+    format!(
+        "Write a short title (at most 8 words) describing the following dictated text, e.g. \"Reply to Anna about Q3 budget\". Answer with the title only, without quotes.\n\nText:\n{}\n\nTitle:",
+        text
+    )
+}
+
+/// Clean up the LLM's answer: first line, no quotes, label or trailing period
+pub fn clean_title(answer: &str) -> Option<String> {
+    let line = answer.lines().map(str::trim).find(|l| !l.is_empty())?;
+    let line = line
+        .strip_prefix("Title:")
+        .unwrap_or(line)
+        .trim()
+        .trim_matches(|c| matches!(c, '"' | '\'' | '*' | '#' | '“' | '”'))
+        .trim_end_matches('.')
+        .trim();
+    if line.is_empty() {
+        return None;
+    }
+
+    let title = match line.char_indices().nth(MAX_TITLE_CHARS) {
+        Some((end, _)) => format!("{}…", line[..end].trim_end()),
+        None => line.to_string(),
+    };
+    Some(title)
+}
+
+/// Ask the LLM for a title
+pub async fn generate(
+    provider: &dyn LlmProvider,
+    text: &str,
+    redact_names: Option<&[String]>,
+) -> Result<Option<String>> {
+    if text.trim().is_empty() {
+        return Ok(None);
+    }
+    let answer = redact::complete(provider, &title_prompt(text), redact_names).await?;
+    Ok(clean_title(&answer))
+}
+
+/// Generate and store a title for a history item in the background
+pub fn spawn(
+    app_handle: AppHandle,
+    db: DbHandle,
+    provider: Box<dyn LlmProvider>,
+    history_id: String,
+    text: String,
+    redact_names: Option<Vec<String>>,
+) {
+    tauri::async_runtime::spawn(async move {
+        let title = match generate(provider.as_ref(), &text, redact_names.as_deref()).await {
+            Ok(Some(title)) => title,
+            Ok(None) => return,
+            Err(e) => {
+                log::warn!("Failed to generate history title: {}", e);
+                return;
+            }
+        };
+
+        let id = history_id.clone();
+        let stored = title.clone();
+        if let Err(e) = db.call(move |db| db.set_history_title(&id, &stored)).await {
+            log::warn!("Failed to save history title: {}", e);
+            return;
+        }
+        let _ = app_handle.emit(HISTORY_TITLE_EVENT, TitleUpdate { history_id, title });
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clean_title() {
+        assert_eq!(
+            clean_title("\n\"Reply to Anna about Q3 budget.\"\n"),
+            Some("Reply to Anna about Q3 budget".to_string())
+        );
+        assert_eq!(
+            clean_title("Title: Grocery list\nHere is why..."),
+            Some("Grocery list".to_string())
+        );
+        assert_eq!(clean_title("  \n\"\""), None);
+
+        let long = clean_title(&"word ".repeat(40)).unwrap();
+        assert!(long.ends_with('…'));
+        assert!(long.chars().count() <= MAX_TITLE_CHARS + 1);
+    }
+
+    #[test]
+    fn test_title_prompt_truncates() {
+        let prompt = title_prompt(&"a".repeat(10_000));
+        assert!(prompt.len() < MAX_PROMPT_CHARS + 500);
+    }
+}
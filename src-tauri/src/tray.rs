@@ -1,31 +1,68 @@
 //! System tray management
 
-use crate::error::Result;
-use crate::state::{AppState, RecordingStatus};
+use crate::error::{AppError, Result};
+use crate::i18n::{t, t_args};
+use crate::icon::{render_icon, Badge, IconOptions, IconState, PrivacyMark};
+use crate::pipeline::ProcessingControl;
+use crate::state::{AppState, RecordingStatus, Settings};
+use crate::status;
 use log::info;
-use tauri::menu::{MenuBuilder, MenuItemBuilder, SubmenuBuilder};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri::menu::{CheckMenuItemBuilder, MenuBuilder, MenuItemBuilder, SubmenuBuilder};
 use tauri::tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent};
 use tauri::{image::Image, AppHandle, Emitter, Manager};
 
 const TRAY_ID: &str = "main-tray";
 
+/// Event emitted with the new settings after they were changed from the tray
+pub const SETTINGS_CHANGED_EVENT: &str = "settings-changed";
+
+/// Longest last-result snippet shown in the tooltip
+const TOOLTIP_SNIPPET_CHARS: usize = 40;
+
+/// Current tray icon rendering options (managed state)
+pub struct TrayIconOptions(std::sync::Mutex<IconOptions>);
+
+/// Values for the tooltip template (managed state)
+pub struct TrayTooltip(std::sync::Mutex<TooltipContext>);
+
+#[derive(Debug, Default)]
+pub struct TooltipContext {
+    template: String,
+    mode: String,
+    device: String,
+    last: String,
+    recording_since: Option<Instant>,
+    ticking: bool,
+    status: Option<RecordingStatus>,
+    /// Progress of a long operation, shown instead of the status
+    progress: Option<String>,
+}
+
 /// Set up the system tray
-pub fn setup_tray(app: &tauri::App) -> Result<()> {
+pub fn setup_tray(app: &tauri::App, settings: &Settings) -> Result<()> {
     info!("Setting up system tray...");
 
     let handle = app.handle();
+    let options = icon_options(settings, false);
+    app.manage(TrayIconOptions(std::sync::Mutex::new(options)));
+    app.manage(TrayTooltip(std::sync::Mutex::new(TooltipContext {
+        template: settings.tray_tooltip_template.clone(),
+        ..TooltipContext::default()
+    })));
 
     // Build initial menu
     let menu = build_tray_menu(handle)?;
 
-    // Load initial icon (green = ready)
-    let icon = load_tray_icon("tray-green")?;
+    // Render initial icon (yellow = loading, green once the modes are loaded)
+    let icon = render_tray_icon(RecordingStatus::Loading, None, &options);
 
     // Create tray icon
     let _tray = TrayIconBuilder::with_id(TRAY_ID)
         .icon(icon)
         .menu(&menu)
-        .tooltip("WhisperTray - Click to record")
+        .tooltip(format!("WhisperTray - {}", t("tooltip.click_to_record")))
         .on_menu_event(move |app, event| {
             handle_menu_event(app, event.id.as_ref());
         })
@@ -49,25 +86,42 @@ pub fn setup_tray(app: &tauri::App) -> Result<()> {
 /// Build the tray menu
 fn build_tray_menu(handle: &AppHandle) -> Result<tauri::menu::Menu<tauri::Wry>> {
     let menu = MenuBuilder::new(handle)
-        .item(&MenuItemBuilder::with_id("start_recording", "Start Recording").build(handle)?)
-        .item(&MenuItemBuilder::with_id("stop_recording", "Stop Recording").build(handle)?)
+        .item(
+            &MenuItemBuilder::with_id("start_recording", t("tray.start_recording"))
+                .build(handle)?,
+        )
+        .item(&MenuItemBuilder::with_id("stop_recording", t("tray.stop_recording")).build(handle)?)
         .separator()
         .item(
-            &SubmenuBuilder::with_id(handle, "modes", "Mode")
+            &SubmenuBuilder::with_id(handle, "modes", t("tray.mode"))
                 .item(&MenuItemBuilder::with_id("mode_voice_to_text", "Voice to Text").build(handle)?)
                 .build()?,
         )
         .item(
-            &SubmenuBuilder::with_id(handle, "devices", "Input Device")
-                .item(&MenuItemBuilder::with_id("device_default", "Default").build(handle)?)
+            &SubmenuBuilder::with_id(handle, "devices", t("tray.input_device"))
+                .item(
+                    &MenuItemBuilder::with_id("device_default", t("tray.default_device"))
+                        .build(handle)?,
+                )
+                .build()?,
+        )
+        .item(
+            &SubmenuBuilder::with_id(handle, "languages", t("tray.language"))
+                .item(
+                    &MenuItemBuilder::with_id("lang_auto", t("tray.auto_language"))
+                        .build(handle)?,
+                )
                 .build()?,
         )
         .separator()
-        .item(&MenuItemBuilder::with_id("transcribe_file", "Transcribe File...").build(handle)?)
-        .item(&MenuItemBuilder::with_id("history", "History...").build(handle)?)
-        .item(&MenuItemBuilder::with_id("settings", "Settings...").build(handle)?)
+        .item(
+            &MenuItemBuilder::with_id("transcribe_file", t("tray.transcribe_file"))
+                .build(handle)?,
+        )
+        .item(&MenuItemBuilder::with_id("history", t("tray.history")).build(handle)?)
+        .item(&MenuItemBuilder::with_id("settings", t("tray.settings")).build(handle)?)
         .separator()
-        .item(&MenuItemBuilder::with_id("quit", "Quit").build(handle)?)
+        .item(&MenuItemBuilder::with_id("quit", t("tray.quit")).build(handle)?)
         .build()?;
 
     Ok(menu)
@@ -76,12 +130,20 @@ fn build_tray_menu(handle: &AppHandle) -> Result<tauri::menu::Menu<tauri::Wry>>
 /// Update the tray menu with current modes and devices
 pub async fn update_tray_menu(handle: &AppHandle, state: &AppState) -> Result<()> {
     // Build modes submenu
-    let mut modes_builder = SubmenuBuilder::with_id(handle, "modes", "Mode");
+    let mut modes_builder = SubmenuBuilder::with_id(handle, "modes", t("tray.mode"));
+
+    let modes = state.visible_modes();
+    for (index, mode) in modes.iter().enumerate() {
+        // Separate favorites from the remaining modes
+        if index > 0 && modes[index - 1].favorite && !mode.favorite {
+            modes_builder = modes_builder.separator();
+        }
 
-    for mode in state.modes.values() {
         let id = format!("mode_{}", mode.key);
         let label = if mode.key == state.active_mode_key {
             format!("✓ {}", mode.name)
+        } else if mode.favorite {
+            format!("★ {}", mode.name)
         } else {
             mode.name.clone()
         };
@@ -92,18 +154,18 @@ pub async fn update_tray_menu(handle: &AppHandle, state: &AppState) -> Result<()
 
     // Build devices submenu
     let devices = crate::audio::get_input_devices().unwrap_or_default();
-    let mut devices_builder = SubmenuBuilder::with_id(handle, "devices", "Input Device");
+    let mut devices_builder = SubmenuBuilder::with_id(handle, "devices", t("tray.input_device"));
 
     let current_device = &state.settings.input_device;
 
     // Add default option
     let default_label = if current_device.is_empty() {
-        "✓ Default"
+        format!("✓ {}", t("tray.default_device"))
     } else {
-        "Default"
+        t("tray.default_device").to_string()
     };
     devices_builder =
-        devices_builder.item(&MenuItemBuilder::with_id("device_default", default_label).build(handle)?);
+        devices_builder.item(&MenuItemBuilder::with_id("device_default", &default_label).build(handle)?);
 
     for device in devices {
         let id = format!("device_{}", device.name.replace(' ', "_"));
@@ -117,24 +179,106 @@ pub async fn update_tray_menu(handle: &AppHandle, state: &AppState) -> Result<()
 
     let devices_menu = devices_builder.build()?;
 
-    // Rebuild menu
-    let recording_label = if state.status == RecordingStatus::Recording {
-        "Stop Recording"
+    // Build languages submenu
+    let current_language = state.settings.language.as_str();
+    let mut languages_builder = SubmenuBuilder::with_id(handle, "languages", t("tray.language"));
+    let auto_label = if current_language == "auto" {
+        format!("✓ {}", t("tray.auto_language"))
     } else {
-        "Start Recording"
+        t("tray.auto_language").to_string()
+    };
+    languages_builder = languages_builder
+        .item(&MenuItemBuilder::with_id("lang_auto", &auto_label).build(handle)?)
+        .separator();
+
+    for (code, name) in crate::providers::stt::LANGUAGES {
+        let id = format!("lang_{}", code);
+        let label = if *code == current_language {
+            format!("✓ {}", name)
+        } else {
+            name.to_string()
+        };
+        languages_builder =
+            languages_builder.item(&MenuItemBuilder::with_id(&id, &label).build(handle)?);
+    }
+
+    let languages_menu = languages_builder.build()?;
+
+    // Rebuild menu
+    let recording_label = match state.status {
+        RecordingStatus::Recording | RecordingStatus::Paused => t("tray.stop_recording"),
+        _ => t("tray.start_recording"),
+    };
+    let pause_label = match state.status {
+        RecordingStatus::Recording => Some(t("tray.pause_recording")),
+        RecordingStatus::Paused => Some(t("tray.resume_recording")),
+        _ => None,
+    };
+
+    let toggle = |id: &str, label: &str, checked: bool| {
+        CheckMenuItemBuilder::with_id(id, label)
+            .checked(checked)
+            .build(handle)
     };
 
     let menu = MenuBuilder::new(handle)
-        .item(&MenuItemBuilder::with_id("toggle_recording", recording_label).build(handle)?)
+        .item(&MenuItemBuilder::with_id("toggle_recording", recording_label).build(handle)?);
+    let menu = match pause_label {
+        Some(label) => menu.item(&MenuItemBuilder::with_id("toggle_pause", label).build(handle)?),
+        None => menu,
+    };
+    let menu = menu
+        .item(
+            &MenuItemBuilder::with_id("cancel_processing", t("tray.cancel_processing"))
+                .build(handle)?,
+        )
         .separator()
         .item(&modes_menu)
         .item(&devices_menu)
+        .item(&languages_menu)
+        .separator()
+        .item(&toggle(
+            "toggle_auto_paste",
+            t("tray.auto_paste"),
+            state.settings.auto_paste,
+        )?)
+        .item(&toggle(
+            "toggle_context_awareness",
+            t("tray.context_awareness"),
+            state.settings.context_awareness,
+        )?)
+        .item(&toggle(
+            "toggle_local_only",
+            t("tray.local_only"),
+            state.settings.local_only,
+        )?)
+        .item(&toggle(
+            "toggle_incognito",
+            t("tray.incognito"),
+            state.incognito,
+        )?)
         .separator()
-        .item(&MenuItemBuilder::with_id("transcribe_file", "Transcribe File...").build(handle)?)
-        .item(&MenuItemBuilder::with_id("history", "History...").build(handle)?)
-        .item(&MenuItemBuilder::with_id("settings", "Settings...").build(handle)?)
+        .item(
+            &MenuItemBuilder::with_id("transcribe_file", t("tray.transcribe_file"))
+                .build(handle)?,
+        )
+        .item(&MenuItemBuilder::with_id("history", t("tray.history")).build(handle)?)
+        .item(&MenuItemBuilder::with_id("settings", t("tray.settings")).build(handle)?);
+
+    let menu = match &state.update {
+        Some(update) => {
+            let label = t_args(
+                "tray.update_available",
+                &[("version", &update.latest_version)],
+            );
+            menu.item(&MenuItemBuilder::with_id("update_available", &label).build(handle)?)
+        }
+        None => menu,
+    };
+
+    let menu = menu
         .separator()
-        .item(&MenuItemBuilder::with_id("quit", "Quit").build(handle)?)
+        .item(&MenuItemBuilder::with_id("quit", t("tray.quit")).build(handle)?)
         .build()?;
 
     // Update tray menu
@@ -142,68 +286,385 @@ pub async fn update_tray_menu(handle: &AppHandle, state: &AppState) -> Result<()
         tray.set_menu(Some(menu))?;
     }
 
-    Ok(())
+    sync_tooltip_context(handle, state);
+    let mode_name = state
+        .get_active_mode()
+        .map(|m| m.name.as_str())
+        .unwrap_or_default();
+    status::set_mode(handle, &state.active_mode_key, mode_name);
+    status::set_status(handle, state.status);
+    refresh_tooltip(handle, state.status)
 }
 
 /// Update the tray icon based on status
 pub fn update_tray_icon(handle: &AppHandle, status: RecordingStatus) -> Result<()> {
-    let icon_name = status.icon_name();
-    let icon = load_tray_icon(icon_name)?;
+    let options = current_icon_options(handle);
+    let icon = render_tray_icon(status, None, &options);
 
     if let Some(tray) = handle.tray_by_id(TRAY_ID) {
         tray.set_icon(Some(icon))?;
+    }
+    status::set_status(handle, status);
+    refresh_tooltip(handle, status)?;
 
-        let tooltip = match status {
-            RecordingStatus::Loading => "WhisperTray - Loading model...",
-            RecordingStatus::Recording => "WhisperTray - Recording...",
-            RecordingStatus::Processing => "WhisperTray - Processing...",
-            RecordingStatus::Ready => "WhisperTray - Ready (click to record)",
-            RecordingStatus::Error => "WhisperTray - Error",
-        };
-        tray.set_tooltip(Some(tooltip))?;
+    Ok(())
+}
+
+/// Redraw the tooltip for a status
+fn refresh_tooltip(handle: &AppHandle, status: RecordingStatus) -> Result<()> {
+    update_tooltip_context(handle, |context| context.status = Some(status));
+
+    if let Some(tray) = handle.tray_by_id(TRAY_ID) {
+        let options = current_icon_options(handle);
+        tray.set_tooltip(Some(tooltip_text(handle, status, &options)))?;
     }
 
+    if track_recording(handle, status) {
+        spawn_elapsed_ticker(handle);
+    }
     Ok(())
 }
 
-/// Update the tray icon based on audio level (during recording)
-/// level: 0.0 to 1.0
-pub fn update_tray_icon_for_level(handle: &AppHandle, level: f32) -> Result<()> {
-    // Map level to color:
-    // Low (< 0.2): red (recording but quiet)
-    // Medium (0.2-0.5): yellow
-    // High (> 0.5): green (good level)
-    let icon_name = if level < 0.15 {
-        "tray-red"      // Very quiet / no input
-    } else if level < 0.3 {
-        "tray-yellow"   // Low level
-    } else if level < 0.6 {
-        "tray-green"    // Good level
+fn update_tooltip_context<R>(
+    handle: &AppHandle,
+    update: impl FnOnce(&mut TooltipContext) -> R,
+) -> Option<R> {
+    let tooltip = handle.try_state::<TrayTooltip>()?;
+    let mut context = tooltip.0.lock().ok()?;
+    Some(update(&mut context))
+}
+
+/// Show the progress of a long operation in the tooltip
+pub fn show_progress(handle: &AppHandle, text: &str) {
+    let status = update_tooltip_context(handle, |context| {
+        context.progress = Some(text.to_string());
+        context.status
+    });
+    let _ = refresh_tooltip(handle, status.flatten().unwrap_or(RecordingStatus::Ready));
+}
+
+/// Go back to the status tooltip after a long operation
+pub fn clear_progress(handle: &AppHandle) {
+    let status = update_tooltip_context(handle, |context| {
+        context.progress = None;
+        context.status
+    });
+    let _ = refresh_tooltip(handle, status.flatten().unwrap_or(RecordingStatus::Ready));
+}
+
+/// Copy the values shown by the tooltip template from the app state
+fn sync_tooltip_context(handle: &AppHandle, state: &AppState) {
+    let tooltip = match handle.try_state::<TrayTooltip>() {
+        Some(tooltip) => tooltip,
+        None => return,
+    };
+    let mut context = match tooltip.0.lock() {
+        Ok(context) => context,
+        Err(_) => return,
+    };
+    context.template = state.settings.tray_tooltip_template.clone();
+    context.mode = state
+        .get_active_mode()
+        .map(|m| m.name.clone())
+        .unwrap_or_default();
+    context.device = if state.settings.input_device.is_empty() {
+        t("tray.default_device").to_string()
     } else {
-        "tray-blue"     // High level (maybe too loud)
+        state.settings.input_device.clone()
+    };
+    context.last = state
+        .last_output
+        .as_ref()
+        .map(|last| snippet(&last.text, TOOLTIP_SNIPPET_CHARS))
+        .unwrap_or_default();
+    // Moved forward after a pause, so {elapsed} leaves out paused time
+    if state.recording_started.is_some() {
+        context.recording_since = state.recording_started;
+    }
+}
+
+/// Remember when recording started; returns true if an elapsed-time ticker should start
+fn track_recording(handle: &AppHandle, status: RecordingStatus) -> bool {
+    let tooltip = match handle.try_state::<TrayTooltip>() {
+        Some(tooltip) => tooltip,
+        None => return false,
     };
+    let mut context = match tooltip.0.lock() {
+        Ok(context) => context,
+        Err(_) => return false,
+    };
+
+    if status == RecordingStatus::Paused {
+        return false;
+    }
+    if status != RecordingStatus::Recording {
+        context.recording_since = None;
+        return false;
+    }
+    if context.recording_since.is_none() {
+        context.recording_since = Some(Instant::now());
+    }
+
+    let start = context.template.contains("{elapsed}") && !context.ticking;
+    if start {
+        context.ticking = true;
+    }
+    start
+}
+
+/// Refresh the tooltip every second while recording so {elapsed} stays current
+fn spawn_elapsed_ticker(handle: &AppHandle) {
+    let handle = handle.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            let recording = handle
+                .try_state::<TrayTooltip>()
+                .and_then(|tooltip| tooltip.0.lock().ok().map(|c| c.recording_since.is_some()))
+                .unwrap_or(false);
+            if !recording {
+                break;
+            }
+            let _ = refresh_tooltip(&handle, RecordingStatus::Recording);
+        }
+
+        if let Some(tooltip) = handle.try_state::<TrayTooltip>() {
+            if let Ok(mut context) = tooltip.0.lock() {
+                context.ticking = false;
+            }
+        }
+    });
+}
+
+/// Tooltip for a status, using the configured template if there is one
+fn tooltip_text(handle: &AppHandle, status: RecordingStatus, options: &IconOptions) -> String {
+    let status_text = match status {
+        RecordingStatus::Loading => t("tooltip.loading"),
+        RecordingStatus::Recording => t("tooltip.recording"),
+        RecordingStatus::Paused => t("tooltip.paused"),
+        RecordingStatus::Processing => t("tooltip.processing"),
+        RecordingStatus::Ready => t("tooltip.ready"),
+        RecordingStatus::Error => t("tooltip.error"),
+    };
+    let privacy = match options.privacy {
+        PrivacyMark::None => "",
+        PrivacyMark::Lock => t("tooltip.local_only"),
+        PrivacyMark::Incognito => t("tooltip.incognito"),
+    };
+
+    let tooltip = handle.try_state::<TrayTooltip>();
+    let context = tooltip.as_ref().and_then(|tooltip| tooltip.0.lock().ok());
+    if let Some(progress) = context.as_ref().and_then(|c| c.progress.as_ref()) {
+        return format!("WhisperTray - {}", progress);
+    }
+
+    match context {
+        Some(context) if !context.template.trim().is_empty() => {
+            let elapsed = context
+                .recording_since
+                .map(|since| format_elapsed(since.elapsed()))
+                .unwrap_or_default();
+            render_tooltip(
+                &context.template,
+                &[
+                    ("status", status_text),
+                    ("mode", &context.mode),
+                    ("device", &context.device),
+                    ("elapsed", &elapsed),
+                    ("last", &context.last),
+                    ("privacy", privacy),
+                ],
+            )
+        }
+        _ if privacy.is_empty() => format!("WhisperTray - {}", status_text),
+        _ => format!("WhisperTray - {} {}", status_text, privacy),
+    }
+}
+
+/// Fill in `{name}` placeholders of a tooltip template
+fn render_tooltip(template: &str, vars: &[(&str, &str)]) -> String {
+    let rendered = vars
+        .iter()
+        .fold(template.to_string(), |text, (name, value)| {
+            text.replace(&format!("{{{}}}", name), value)
+        });
+    // Templates may use "\n" for multi-line tooltips
+    rendered.replace("\\n", "\n").trim().to_string()
+}
+
+/// Elapsed time as m:ss
+fn format_elapsed(elapsed: Duration) -> String {
+    let secs = elapsed.as_secs();
+    format!("{}:{:02}", secs / 60, secs % 60)
+}
+
+/// First line of a text, shortened with an ellipsis
+fn snippet(text: &str, max_chars: usize) -> String {
+    let line = text
+        .lines()
+        .find(|l| !l.trim().is_empty())
+        .unwrap_or("")
+        .trim();
+    if line.chars().count() <= max_chars {
+        line.to_string()
+    } else {
+        let cut: String = line.chars().take(max_chars).collect();
+        format!("{}…", cut.trim_end())
+    }
+}
+
+/// Show the active mode in the tooltip for a moment, then restore the status tooltip
+pub fn flash_mode_tooltip(handle: &AppHandle, mode_name: &str) {
+    if let Some(tray) = handle.tray_by_id(TRAY_ID) {
+        let _ = tray.set_tooltip(Some(format!(
+            "WhisperTray - {}",
+            t_args("tooltip.mode", &[("mode", mode_name)])
+        )));
+    }
 
-    let icon = load_tray_icon(icon_name)?;
+    let handle = handle.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+        // The status may have changed meanwhile, e.g. a recording started
+        if let Some(state) = handle.try_state::<crate::state::SharedState>() {
+            let status = state.lock().await.status;
+            let _ = update_tray_icon(&handle, status);
+        }
+    });
+}
+
+/// Cycle the active mode (from `--next-mode`/`--previous-mode`) and refresh tray state
+pub async fn cycle_mode(handle: &AppHandle, forward: bool) -> Result<String> {
+    let state = handle
+        .try_state::<crate::state::SharedState>()
+        .ok_or_else(|| AppError::Config("Application state not initialized".to_string()))?;
+
+    let mut state = state.lock().await;
+    let mode = state.cycle_mode(forward)?;
+    info!("Mode changed to: {}", mode.key);
+
+    update_tray_menu(handle, &state).await?;
+    flash_mode_tooltip(handle, &mode.name);
+
+    Ok(mode.key)
+}
+
+/// Toggle incognito dictation and refresh tray state
+pub async fn toggle_incognito(handle: &AppHandle) -> Result<bool> {
+    let state = handle
+        .try_state::<crate::state::SharedState>()
+        .ok_or_else(|| AppError::Config("Application state not initialized".to_string()))?;
+
+    let mut state = state.lock().await;
+    state.incognito = !state.incognito;
+    info!("Incognito dictation: {}", state.incognito);
+
+    apply_icon_settings(handle, &state)?;
+    update_tray_menu(handle, &state).await?;
+
+    Ok(state.incognito)
+}
+
+/// Pause or resume the current recording, returning whether it is now paused
+pub async fn toggle_pause(handle: &AppHandle) -> Result<bool> {
+    let state = handle
+        .try_state::<crate::state::SharedState>()
+        .ok_or_else(|| AppError::Config("Application state not initialized".to_string()))?;
+
+    let mut state = state.lock().await;
+    if state.status == RecordingStatus::Paused {
+        state.resume_recording()?;
+    } else {
+        state.pause_recording()?;
+    }
+    let paused = state.status == RecordingStatus::Paused;
+    info!("Recording paused: {}", paused);
+
+    update_tray_icon(handle, state.status)?;
+    update_tray_menu(handle, &state).await?;
+    let _ = handle.emit("recording-paused", paused);
+
+    Ok(paused)
+}
+
+/// Update the tray icon based on audio level (during recording)
+/// level: 0.0 to 1.0
+pub fn update_tray_icon_for_level(handle: &AppHandle, level: f32) -> Result<()> {
+    let icon = render_tray_icon(
+        RecordingStatus::Recording,
+        Some(level),
+        &current_icon_options(handle),
+    );
 
     if let Some(tray) = handle.tray_by_id(TRAY_ID) {
         tray.set_icon(Some(icon))?;
     }
+    status::set_level(handle, level);
 
     Ok(())
 }
 
-/// Load a tray icon by name
-fn load_tray_icon(name: &str) -> Result<Image<'static>> {
-    // For now, we'll use colored PNGs
-    let icon_bytes = match name {
-        "tray-yellow" => include_bytes!("../icons/tray-yellow.png").to_vec(),
-        "tray-red" => include_bytes!("../icons/tray-red.png").to_vec(),
-        "tray-blue" => include_bytes!("../icons/tray-blue.png").to_vec(),
-        "tray-green" => include_bytes!("../icons/tray-green.png").to_vec(),
-        _ => include_bytes!("../icons/tray-green.png").to_vec(),
+/// Apply changed icon settings or privacy state and redraw the tray icon
+pub fn apply_icon_settings(handle: &AppHandle, state: &AppState) -> Result<()> {
+    if let Some(options) = handle.try_state::<TrayIconOptions>() {
+        if let Ok(mut options) = options.0.lock() {
+            *options = icon_options(&state.settings, state.incognito);
+        }
+    }
+    update_tray_icon(handle, state.status)
+}
+
+/// Icon rendering options from settings
+///
+/// An `Auto` theme is detected here rather than on every redraw, since the
+/// level meter redraws the icon many times a second.
+fn icon_options(settings: &Settings, incognito: bool) -> IconOptions {
+    // Incognito is transient, so it takes precedence over the local-only lock
+    let privacy = if incognito {
+        PrivacyMark::Incognito
+    } else if settings.local_only {
+        PrivacyMark::Lock
+    } else {
+        PrivacyMark::None
+    };
+
+    IconOptions {
+        style: settings.tray_icon_style,
+        dark: settings.tray_icon_theme.is_dark(),
+        size: settings.tray_icon_size,
+        privacy,
+    }
+}
+
+fn current_icon_options(handle: &AppHandle) -> IconOptions {
+    handle
+        .try_state::<TrayIconOptions>()
+        .and_then(|options| options.0.lock().ok().map(|o| *o))
+        .unwrap_or_default()
+}
+
+/// Render a tray icon for a status and optional input level
+fn render_tray_icon(
+    status: RecordingStatus,
+    level: Option<f32>,
+    options: &IconOptions,
+) -> Image<'static> {
+    let badge = match status {
+        RecordingStatus::Recording | RecordingStatus::Error => Badge::Filled,
+        RecordingStatus::Loading | RecordingStatus::Processing | RecordingStatus::Paused => {
+            Badge::Ring
+        }
+        RecordingStatus::Ready => Badge::None,
+    };
+
+    let state = IconState {
+        color: status.icon_color(),
+        badge,
+        level,
     };
 
-    Image::from_bytes(&icon_bytes).map_err(|e| crate::error::AppError::Tauri(e.to_string()))
+    let size = options.pixel_size();
+    Image::new_owned(render_icon(&state, options), size, size)
 }
 
 /// Handle menu events
@@ -233,6 +694,47 @@ fn handle_menu_event(handle: &AppHandle, id: &str) {
                 }
             });
         }
+        "toggle_pause" => {
+            let handle = handle.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = toggle_pause(&handle).await {
+                    log::error!("Failed to pause recording: {}", e);
+                }
+            });
+        }
+        "cancel_processing" => {
+            if let Some(processing) = handle.try_state::<Arc<ProcessingControl>>() {
+                if !processing.cancel() {
+                    info!("Nothing to cancel");
+                }
+            }
+        }
+        "toggle_auto_paste" => {
+            change_setting(handle, |settings| {
+                settings.auto_paste = !settings.auto_paste;
+                info!("Auto-paste: {}", settings.auto_paste);
+            });
+        }
+        "toggle_context_awareness" => {
+            change_setting(handle, |settings| {
+                settings.context_awareness = !settings.context_awareness;
+                info!("Context awareness: {}", settings.context_awareness);
+            });
+        }
+        "toggle_local_only" => {
+            change_setting(handle, |settings| {
+                settings.local_only = !settings.local_only;
+                info!("Local-only mode: {}", settings.local_only);
+            });
+        }
+        "toggle_incognito" => {
+            let handle = handle.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = toggle_incognito(&handle).await {
+                    log::error!("Failed to toggle incognito: {}", e);
+                }
+            });
+        }
         "transcribe_file" => {
             let handle = handle.clone();
             tauri::async_runtime::spawn(async move {
@@ -241,7 +743,7 @@ fn handle_menu_event(handle: &AppHandle, id: &str) {
                 handle
                     .dialog()
                     .file()
-                    .add_filter("Audio Files", &["wav", "mp3", "m4a", "ogg", "flac"])
+                    .add_filter(t("tray.audio_files"), &["wav", "mp3", "m4a", "ogg", "flac"])
                     .pick_file(move |path| {
                         if let Some(path) = path {
                             info!("Selected file for transcription: {:?}", path);
@@ -260,6 +762,19 @@ fn handle_menu_event(handle: &AppHandle, id: &str) {
             // Navigate to settings view
             let _ = handle.emit("navigate", "/settings");
         }
+        "update_available" => {
+            let handle = handle.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Some(state) = handle.try_state::<crate::state::SharedState>() {
+                    let url = state.lock().await.update.as_ref().map(|u| u.url.clone());
+                    if let Some(url) = url {
+                        if let Err(e) = std::process::Command::new("xdg-open").arg(&url).spawn() {
+                            log::error!("Failed to open {}: {}", url, e);
+                        }
+                    }
+                }
+            });
+        }
         "quit" => {
             handle.exit(0);
         }
@@ -282,34 +797,53 @@ fn handle_menu_event(handle: &AppHandle, id: &str) {
             }
             // Handle device selection
             else if let Some(device) = id.strip_prefix("device_") {
-                let handle = handle.clone();
                 let device_name = if device == "default" {
                     String::new()
                 } else {
                     device.replace('_', " ")
                 };
-                tauri::async_runtime::spawn(async move {
-                    if let Some(state) = handle.try_state::<crate::state::SharedState>() {
-                        let mut state = state.lock().await;
-                        state.settings.input_device = device_name.clone();
-                        if let Err(e) = state.save_settings() {
-                            log::error!("Failed to save settings: {}", e);
-                        } else {
-                            info!("Input device changed to: {}", device_name);
-                            let _ = update_tray_menu(&handle, &state).await;
-                        }
-                    }
+                change_setting(handle, move |settings| {
+                    info!("Input device changed to: {}", device_name);
+                    settings.input_device = device_name;
+                });
+            }
+            // Handle language selection
+            else if let Some(language) = id.strip_prefix("lang_") {
+                let language = language.to_string();
+                change_setting(handle, move |settings| {
+                    info!("Language changed to: {}", language);
+                    crate::i18n::set_language(&language);
+                    settings.language = language;
                 });
             }
         }
     }
 }
 
+/// Change settings from the tray, save them and tell the frontend
+fn change_setting(handle: &AppHandle, change: impl FnOnce(&mut Settings) + Send + 'static) {
+    let handle = handle.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Some(state) = handle.try_state::<crate::state::SharedState>() {
+            let mut state = state.lock().await;
+            change(&mut state.settings);
+            if let Err(e) = state.save_settings() {
+                log::error!("Failed to save settings: {}", e);
+            }
+            let _ = handle.emit(SETTINGS_CHANGED_EVENT, &state.settings);
+            let _ = apply_icon_settings(&handle, &state);
+            let _ = update_tray_menu(&handle, &state).await;
+        }
+    });
+}
+
 /// Handle tray icon click (toggle recording)
 fn handle_tray_click(handle: &AppHandle) {
     let handle = handle.clone();
     tauri::async_runtime::spawn(async move {
         if let Some(state) = handle.try_state::<crate::state::SharedState>() {
+            // Checks by itself whether a recording is starting
+            crate::state::prepare_bluetooth(&state).await;
             let mut state = state.lock().await;
 
             if state.is_recording() {
@@ -350,9 +884,43 @@ fn handle_tray_click(handle: &AppHandle) {
 }
 
 /// Show a window
-fn show_window(handle: &AppHandle, label: &str) {
+pub(crate) fn show_window(handle: &AppHandle, label: &str) {
     if let Some(window) = handle.get_webview_window(label) {
         let _ = window.show();
         let _ = window.set_focus();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_tooltip() {
+        assert_eq!(
+            render_tooltip(
+                "{mode} · {status} {elapsed}\\n{device}",
+                &[
+                    ("status", "Recording..."),
+                    ("mode", "Email"),
+                    ("elapsed", "0:07"),
+                    ("device", "USB Mic"),
+                ],
+            ),
+            "Email · Recording... 0:07\nUSB Mic"
+        );
+        assert_eq!(render_tooltip("{last} ", &[("last", "")]), "");
+    }
+
+    #[test]
+    fn test_format_elapsed() {
+        assert_eq!(format_elapsed(Duration::from_secs(7)), "0:07");
+        assert_eq!(format_elapsed(Duration::from_secs(125)), "2:05");
+    }
+
+    #[test]
+    fn test_snippet() {
+        assert_eq!(snippet("\nHello there\nsecond line", 40), "Hello there");
+        assert_eq!(snippet("abcdefghij", 4), "abcd…");
+    }
+}
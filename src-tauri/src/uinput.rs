@@ -0,0 +1,213 @@
+//! ydotool/uinput setup checks
+//!
+//! Typing on Wayland through ydotool needs the ydotoold daemon and write
+//! access to /dev/uinput. Without them typing fails silently, so these checks
+//! report what is missing and how to fix it.
+
+use crate::error::{AppError, Result};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const UINPUT_DEVICE: &str = "/dev/uinput";
+
+/// udev rule installed by `install_udev_rule`
+const UDEV_RULE_PATH: &str = "/etc/udev/rules.d/80-whispertray-uinput.rules";
+
+/// Grants the logged-in seat user access to uinput without group changes
+const UDEV_RULE: &str =
+    r#"KERNEL=="uinput", SUBSYSTEM=="misc", TAG+="uaccess", OPTIONS+="static_node=uinput""#;
+
+/// Result of a single setup check
+#[derive(Debug, Clone, Serialize)]
+pub struct SetupCheck {
+    pub name: String,
+    pub ok: bool,
+    pub detail: String,
+    /// What the user can do to fix a failed check
+    pub fix: Option<String>,
+}
+
+impl SetupCheck {
+    fn new(name: &str, ok: bool, detail: String, fix: Option<&str>) -> Self {
+        Self {
+            name: name.to_string(),
+            ok,
+            detail,
+            fix: if ok { None } else { fix.map(str::to_string) },
+        }
+    }
+}
+
+/// Overall paste backend readiness
+#[derive(Debug, Clone, Serialize)]
+pub struct PasteBackendCheck {
+    pub ok: bool,
+    pub checks: Vec<SetupCheck>,
+    /// Whether `install_udev_rule` would help
+    pub can_install_rule: bool,
+}
+
+/// Check ydotool, ydotoold and uinput permissions
+pub fn check_paste_backend() -> PasteBackendCheck {
+    let mut checks = Vec::new();
+
+    let installed = Command::new("which")
+        .arg("ydotool")
+        .output()
+        .map(|o| o.status.success())
+        .unwrap_or(false);
+    checks.push(SetupCheck::new(
+        "ydotool installed",
+        installed,
+        if installed {
+            "ydotool found in PATH".to_string()
+        } else {
+            "ydotool not found in PATH".to_string()
+        },
+        Some("Install ydotool from your distribution's package manager"),
+    ));
+
+    let daemon = daemon_running();
+    checks.push(SetupCheck::new(
+        "ydotoold running",
+        daemon,
+        if daemon {
+            "ydotoold daemon is running".to_string()
+        } else {
+            "ydotoold daemon is not running".to_string()
+        },
+        Some("Start it with: systemctl --user enable --now ydotool"),
+    ));
+
+    let access = uinput_access(Path::new(UINPUT_DEVICE));
+    let writable = access.is_ok();
+    checks.push(SetupCheck::new(
+        "uinput access",
+        writable,
+        match &access {
+            Ok(()) => format!("{} is writable", UINPUT_DEVICE),
+            Err(e) => format!("{} is not writable: {}", UINPUT_DEVICE, e),
+        },
+        Some("Install the udev rule (requires administrator password) or add yourself to the input group"),
+    ));
+
+    let groups = user_groups();
+    let in_input_group = groups.iter().any(|g| g == "input");
+    checks.push(SetupCheck::new(
+        "input group",
+        in_input_group || writable,
+        if in_input_group {
+            "User is in the input group".to_string()
+        } else {
+            format!("User groups: {}", groups.join(", "))
+        },
+        Some("Run: sudo usermod -aG input $USER, then log out and back in"),
+    ));
+
+    let rule_installed = Path::new(UDEV_RULE_PATH).exists();
+
+    PasteBackendCheck {
+        ok: checks.iter().all(|c| c.ok),
+        can_install_rule: !writable && !rule_installed,
+        checks,
+    }
+}
+
+/// Check whether the ydotoold socket or process exists
+fn daemon_running() -> bool {
+    if socket_paths().iter().any(|p| p.exists()) {
+        return true;
+    }
+
+    Command::new("pgrep")
+        .args(["-x", "ydotoold"])
+        .output()
+        .map(|o| o.status.success())
+        .unwrap_or(false)
+}
+
+/// Socket locations used by ydotoold versions
+fn socket_paths() -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+    if let Ok(socket) = std::env::var("YDOTOOL_SOCKET") {
+        paths.push(PathBuf::from(socket));
+    }
+    if let Ok(runtime) = std::env::var("XDG_RUNTIME_DIR") {
+        paths.push(Path::new(&runtime).join(".ydotool_socket"));
+    }
+    paths.push(PathBuf::from("/tmp/.ydotool_socket"));
+    paths
+}
+
+fn uinput_access(path: &Path) -> std::io::Result<()> {
+    std::fs::OpenOptions::new()
+        .write(true)
+        .open(path)
+        .map(|_| ())
+}
+
+fn user_groups() -> Vec<String> {
+    Command::new("id")
+        .arg("-nG")
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| parse_groups(&String::from_utf8_lossy(&o.stdout)))
+        .unwrap_or_default()
+}
+
+fn parse_groups(output: &str) -> Vec<String> {
+    output.split_whitespace().map(str::to_string).collect()
+}
+
+/// Install the uinput udev rule through pkexec and reload udev
+pub fn install_udev_rule() -> Result<()> {
+    let script = format!(
+        "echo '{}' > {} && udevadm control --reload-rules && udevadm trigger --name-match=uinput",
+        UDEV_RULE, UDEV_RULE_PATH
+    );
+
+    let status = Command::new("pkexec")
+        .args(["sh", "-c", &script])
+        .status()
+        .map_err(|e| AppError::Config(format!("Failed to run pkexec: {}", e)))?;
+
+    if status.success() {
+        log::info!("Installed udev rule at {}", UDEV_RULE_PATH);
+        Ok(())
+    } else {
+        Err(AppError::Config(
+            "Installing the udev rule was cancelled or failed".to_string(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_groups() {
+        assert_eq!(
+            parse_groups("alice wheel input\n"),
+            vec!["alice", "wheel", "input"]
+        );
+        assert!(parse_groups("").is_empty());
+    }
+
+    #[test]
+    fn test_failed_check_has_fix() {
+        let failed = SetupCheck::new("x", false, String::new(), Some("do y"));
+        assert_eq!(failed.fix.as_deref(), Some("do y"));
+
+        let passed = SetupCheck::new("x", true, String::new(), Some("do y"));
+        assert!(passed.fix.is_none());
+    }
+
+    #[test]
+    fn test_udev_rule_is_single_quoted_safely() {
+        // The rule is embedded in a single-quoted shell string
+        assert!(!UDEV_RULE.contains('\''));
+    }
+}
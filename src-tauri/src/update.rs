@@ -0,0 +1,187 @@
+//! Update check
+//!
+//! Looks up the latest release of the repository in `Cargo.toml` via the
+//! GitHub releases API. Meant for tarball and AppImage installs; installs
+//! managed by a package manager are updated by it instead. Builds without a
+//! GitHub repository in `Cargo.toml` don't check.
+
+use crate::error::{AppError, Result};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::time::Duration;
+
+const GITHUB_API_URL: &str = "https://api.github.com/repos";
+
+/// Result of an update check
+#[derive(Debug, Clone, Serialize)]
+pub struct UpdateInfo {
+    pub current_version: String,
+    pub latest_version: String,
+    pub update_available: bool,
+    /// Release page
+    pub url: String,
+    /// Release notes (Markdown)
+    pub changelog: String,
+    pub published_at: Option<String>,
+}
+
+/// Release as returned by the GitHub API
+#[derive(Debug, Deserialize)]
+struct Release {
+    tag_name: String,
+    html_url: String,
+    #[serde(default)]
+    body: Option<String>,
+    #[serde(default)]
+    published_at: Option<String>,
+}
+
+/// `owner/name` of a GitHub repository URL
+fn repository_slug(url: &str) -> Option<String> {
+    let path = url.trim().strip_prefix("https://github.com/")?;
+    let path = path.trim_end_matches('/').trim_end_matches(".git");
+    let mut parts = path.split('/');
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(owner), Some(name), None) if !owner.is_empty() && !name.is_empty() => {
+            Some(format!("{}/{}", owner, name))
+        }
+        _ => None,
+    }
+}
+
+/// Numeric components and pre-release suffix of a version such as `v1.2.0-beta.1`
+fn parse_version(version: &str) -> Option<(Vec<u64>, Option<&str>)> {
+    let version = version.trim().trim_start_matches('v');
+    let (numbers, pre) = match version.split_once('-') {
+        Some((numbers, pre)) => (numbers, Some(pre)),
+        None => (version, None),
+    };
+    let numbers = numbers
+        .split('.')
+        .map(|n| n.parse().ok())
+        .collect::<Option<Vec<u64>>>()?;
+    Some((numbers, pre))
+}
+
+/// Compare two versions; a pre-release sorts before its release
+pub fn compare_versions(a: &str, b: &str) -> Option<Ordering> {
+    let (mut a_numbers, a_pre) = parse_version(a)?;
+    let (mut b_numbers, b_pre) = parse_version(b)?;
+    let len = a_numbers.len().max(b_numbers.len());
+    a_numbers.resize(len, 0);
+    b_numbers.resize(len, 0);
+
+    let ordering = a_numbers.cmp(&b_numbers).then(match (a_pre, b_pre) {
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => Ordering::Greater,
+        (Some(_), None) => Ordering::Less,
+        (Some(a), Some(b)) => a.cmp(b),
+    });
+    Some(ordering)
+}
+
+/// Whether the app was installed without a package manager (AppImage or outside `/usr`)
+pub fn is_self_managed() -> bool {
+    std::env::var_os("APPIMAGE").is_some()
+        || std::env::current_exe().is_ok_and(|exe| !exe.starts_with("/usr"))
+}
+
+/// `owner/name` of the repository releases are looked up in, if it is set
+pub fn repository() -> Option<String> {
+    repository_slug(env!("CARGO_PKG_REPOSITORY"))
+}
+
+/// Look up the latest release
+pub async fn check() -> Result<UpdateInfo> {
+    let slug = repository().ok_or_else(|| {
+        AppError::Config(format!(
+            "No GitHub repository to check: '{}'",
+            env!("CARGO_PKG_REPOSITORY")
+        ))
+    })?;
+
+    let response = reqwest::Client::new()
+        .get(format!("{}/{}/releases/latest", GITHUB_API_URL, slug))
+        .header(
+            "User-Agent",
+            concat!("WhisperTray/", env!("CARGO_PKG_VERSION")),
+        )
+        .header("Accept", "application/vnd.github+json")
+        .timeout(Duration::from_secs(15))
+        .send()
+        .await
+        .map_err(|e| AppError::Provider(format!("Update check failed: {}", e)))?;
+    if !response.status().is_success() {
+        return Err(AppError::Provider(format!(
+            "Update check failed: {}",
+            response.status()
+        )));
+    }
+
+    let release: Release = response
+        .json()
+        .await
+        .map_err(|e| AppError::Provider(format!("Invalid release data: {}", e)))?;
+    Ok(update_info(env!("CARGO_PKG_VERSION"), release))
+}
+
+fn update_info(current_version: &str, release: Release) -> UpdateInfo {
+    let latest_version = release.tag_name.trim_start_matches('v').to_string();
+    UpdateInfo {
+        current_version: current_version.to_string(),
+        update_available: compare_versions(&latest_version, current_version)
+            == Some(Ordering::Greater),
+        latest_version,
+        url: release.html_url,
+        changelog: release.body.unwrap_or_default(),
+        published_at: release.published_at,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repository_slug() {
+        assert_eq!(
+            repository_slug("https://github.com/owner/whispertray.git").as_deref(),
+            Some("owner/whispertray")
+        );
+        assert_eq!(
+            repository_slug("https://github.com/owner/whispertray/").as_deref(),
+            Some("owner/whispertray")
+        );
+        assert_eq!(
+            repository_slug("https://gitlab.com/owner/whispertray"),
+            None
+        );
+        assert_eq!(repository_slug(""), None);
+    }
+
+    #[test]
+    fn test_compare_versions() {
+        assert_eq!(compare_versions("v0.2.0", "0.1.9"), Some(Ordering::Greater));
+        assert_eq!(compare_versions("0.1", "0.1.0"), Some(Ordering::Equal));
+        assert_eq!(
+            compare_versions("1.0.0-rc.1", "1.0.0"),
+            Some(Ordering::Less)
+        );
+        assert_eq!(compare_versions("0.10.0", "0.9.0"), Some(Ordering::Greater));
+        assert_eq!(compare_versions("nightly", "0.1.0"), None);
+    }
+
+    #[test]
+    fn test_update_info() {
+        let release = Release {
+            tag_name: "v0.3.0".to_string(),
+            html_url: "https://github.com/owner/whispertray/releases/tag/v0.3.0".to_string(),
+            body: Some("- Faster transcription".to_string()),
+            published_at: None,
+        };
+        let info = update_info("0.2.1", release);
+        assert!(info.update_available);
+        assert_eq!(info.latest_version, "0.3.0");
+        assert_eq!(info.changelog, "- Faster transcription");
+    }
+}
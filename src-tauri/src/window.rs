@@ -0,0 +1,482 @@
+//! Focused window detection
+//!
+//! Used to make sure pasted text lands in the window the user dictated into
+//! and to look up per-application paste settings. X11 is queried via xprop;
+//! on Wayland only compositors with an IPC for it (Sway, Hyprland) are
+//! supported, elsewhere the focused window is unknown. The same sources give
+//! the focused window's position, used to place the recording indicator.
+//! They also list and focus windows, so a recording can be pasted into a
+//! window chosen beforehand.
+
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+
+/// Identity of a toplevel window
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FocusedWindow {
+    /// Compositor/X11 window identifier
+    pub id: String,
+    /// Window class (X11 WM_CLASS or Wayland app_id)
+    pub class: String,
+    pub title: String,
+}
+
+impl FocusedWindow {
+    /// Case-insensitive substring match of the class against a pattern
+    pub fn class_matches(&self, pattern: &str) -> bool {
+        let pattern = pattern.trim().to_lowercase();
+        !pattern.is_empty() && self.class.to_lowercase().contains(&pattern)
+    }
+}
+
+/// Class pattern of WhisperTray's own windows
+const OWN_CLASS: &str = "whispertray";
+
+/// Screen area in pixels
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Rect {
+    pub fn center(&self) -> (i32, i32) {
+        (
+            self.x + self.width as i32 / 2,
+            self.y + self.height as i32 / 2,
+        )
+    }
+
+    pub fn contains(&self, (x, y): (i32, i32)) -> bool {
+        x >= self.x
+            && y >= self.y
+            && x < self.x + self.width as i32
+            && y < self.y + self.height as i32
+    }
+}
+
+/// Get the currently focused window, if the session allows querying it
+pub fn focused_window() -> Option<FocusedWindow> {
+    if crate::paste::is_wayland() {
+        if std::env::var("SWAYSOCK").is_ok() {
+            return run("swaymsg", &["-t", "get_tree"]).and_then(|out| parse_sway_tree(&out));
+        }
+        if std::env::var("HYPRLAND_INSTANCE_SIGNATURE").is_ok() {
+            return run("hyprctl", &["activewindow", "-j"]).and_then(|out| parse_hyprland(&out));
+        }
+        return None;
+    }
+
+    let id = run("xprop", &["-root", "_NET_ACTIVE_WINDOW"]).and_then(|out| parse_xprop_id(&out))?;
+    let props = run("xprop", &["-id", &id, "WM_CLASS", "_NET_WM_NAME"]).unwrap_or_default();
+    let (class, title) = parse_xprop_props(&props);
+
+    Some(FocusedWindow { id, class, title })
+}
+
+/// Get the position and size of the focused window
+pub fn focused_window_rect() -> Option<Rect> {
+    if crate::paste::is_wayland() {
+        if std::env::var("SWAYSOCK").is_ok() {
+            return run("swaymsg", &["-t", "get_tree"]).and_then(|out| parse_sway_rect(&out));
+        }
+        if std::env::var("HYPRLAND_INSTANCE_SIGNATURE").is_ok() {
+            return run("hyprctl", &["activewindow", "-j"])
+                .and_then(|out| parse_hyprland_rect(&out));
+        }
+        return None;
+    }
+
+    let id = run("xprop", &["-root", "_NET_ACTIVE_WINDOW"]).and_then(|out| parse_xprop_id(&out))?;
+    run("xwininfo", &["-id", &id]).and_then(|out| parse_xwininfo(&out))
+}
+
+/// List toplevel windows of other applications, most recently focused first
+///
+/// Sway and X11 only approximate the focus order: sway by its per-container
+/// focus history, X11 by the stacking order.
+pub fn list_windows() -> Vec<FocusedWindow> {
+    let windows = if crate::paste::is_wayland() {
+        if std::env::var("SWAYSOCK").is_ok() {
+            run("swaymsg", &["-t", "get_tree"])
+                .map(|out| parse_sway_windows(&out))
+                .unwrap_or_default()
+        } else if std::env::var("HYPRLAND_INSTANCE_SIGNATURE").is_ok() {
+            run("hyprctl", &["clients", "-j"])
+                .map(|out| parse_hyprland_clients(&out))
+                .unwrap_or_default()
+        } else {
+            Vec::new()
+        }
+    } else {
+        run("xprop", &["-root", "_NET_CLIENT_LIST_STACKING"])
+            .map(|out| parse_xprop_id_list(&out))
+            .unwrap_or_default()
+            .into_iter()
+            .rev()
+            .map(|id| {
+                let props = run("xprop", &["-id", &id, "WM_CLASS", "_NET_WM_NAME"]);
+                let (class, title) = parse_xprop_props(&props.unwrap_or_default());
+                FocusedWindow { id, class, title }
+            })
+            .collect()
+    };
+
+    windows
+        .into_iter()
+        .filter(|w| !w.class_matches(OWN_CLASS))
+        .collect()
+}
+
+/// The most recently focused window of another application
+pub fn last_focused_window() -> Option<FocusedWindow> {
+    list_windows().into_iter().next()
+}
+
+/// Give a window the input focus; false if the session does not allow it
+pub fn focus_window(window: &FocusedWindow) -> bool {
+    if crate::paste::is_wayland() {
+        if std::env::var("SWAYSOCK").is_ok() {
+            let criteria = format!("[con_id={}]", window.id);
+            return run("swaymsg", &[&criteria, "focus"]).is_some();
+        }
+        if std::env::var("HYPRLAND_INSTANCE_SIGNATURE").is_ok() {
+            let address = format!("address:{}", window.id);
+            return run("hyprctl", &["dispatch", "focuswindow", &address]).is_some();
+        }
+        return false;
+    }
+
+    run("xdotool", &["windowactivate", "--sync", &window.id]).is_some()
+        || run("wmctrl", &["-i", "-a", &window.id]).is_some()
+}
+
+fn run(cmd: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(cmd).args(args).output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Parse `_NET_ACTIVE_WINDOW(WINDOW): window id # 0x3a00007`
+fn parse_xprop_id(output: &str) -> Option<String> {
+    let id = output.rsplit('#').next()?.trim();
+    (id.starts_with("0x") && id != "0x0").then(|| id.to_string())
+}
+
+/// Parse `_NET_CLIENT_LIST_STACKING(WINDOW): window id # 0x1e00003, 0x3a00007`
+fn parse_xprop_id_list(output: &str) -> Vec<String> {
+    output
+        .rsplit('#')
+        .next()
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|id| id.starts_with("0x") && *id != "0x0")
+        .map(str::to_string)
+        .collect()
+}
+
+/// Parse WM_CLASS (second entry is the class) and _NET_WM_NAME from xprop output
+fn parse_xprop_props(output: &str) -> (String, String) {
+    let mut class = String::new();
+    let mut title = String::new();
+
+    for line in output.lines() {
+        let (key, value) = match line.split_once(" = ") {
+            Some(pair) => pair,
+            None => continue,
+        };
+        let values: Vec<&str> = value
+            .split("\", \"")
+            .map(|v| v.trim().trim_matches('"'))
+            .collect();
+
+        if key.starts_with("WM_CLASS") {
+            class = values.last().copied().unwrap_or_default().to_string();
+        } else if key.starts_with("_NET_WM_NAME") {
+            title = values.join("\", \"");
+        }
+    }
+
+    (class, title)
+}
+
+/// Parse the absolute position and size from `xwininfo -id` output
+fn parse_xwininfo(output: &str) -> Option<Rect> {
+    let value = |key: &str| {
+        output
+            .lines()
+            .find_map(|line| line.trim().strip_prefix(key)?.trim().parse::<i64>().ok())
+    };
+    Some(Rect {
+        x: value("Absolute upper-left X:")? as i32,
+        y: value("Absolute upper-left Y:")? as i32,
+        width: value("Width:")? as u32,
+        height: value("Height:")? as u32,
+    })
+}
+
+/// Find the focused window node in a sway tree
+fn find_sway_focused(node: &serde_json::Value) -> Option<&serde_json::Value> {
+    if node["focused"].as_bool() == Some(true) && node["type"] == "con" {
+        return Some(node);
+    }
+
+    ["nodes", "floating_nodes"]
+        .iter()
+        .filter_map(|key| node[*key].as_array())
+        .flatten()
+        .find_map(find_sway_focused)
+}
+
+/// Window identity of a sway container
+fn sway_window(node: &serde_json::Value) -> FocusedWindow {
+    let class = node["app_id"]
+        .as_str()
+        .or_else(|| node["window_properties"]["class"].as_str())
+        .unwrap_or_default();
+    FocusedWindow {
+        id: node["id"].to_string(),
+        class: class.to_string(),
+        title: node["name"].as_str().unwrap_or_default().to_string(),
+    }
+}
+
+/// Find the focused node in `swaymsg -t get_tree` output
+fn parse_sway_tree(output: &str) -> Option<FocusedWindow> {
+    let tree: serde_json::Value = serde_json::from_str(output).ok()?;
+    find_sway_focused(&tree).map(sway_window)
+}
+
+/// Collect application windows below `node`, following each container's focus history
+fn collect_sway_windows(node: &serde_json::Value, windows: &mut Vec<FocusedWindow>) {
+    if node["pid"].is_u64() {
+        windows.push(sway_window(node));
+    }
+
+    let mut children: Vec<&serde_json::Value> = ["nodes", "floating_nodes"]
+        .iter()
+        .filter_map(|key| node[*key].as_array())
+        .flatten()
+        .collect();
+    if let Some(focus) = node["focus"].as_array() {
+        children.sort_by_key(|child| {
+            focus
+                .iter()
+                .position(|id| *id == child["id"])
+                .unwrap_or(usize::MAX)
+        });
+    }
+    for child in children {
+        collect_sway_windows(child, windows);
+    }
+}
+
+/// Application windows in `swaymsg -t get_tree` output
+fn parse_sway_windows(output: &str) -> Vec<FocusedWindow> {
+    let mut windows = Vec::new();
+    if let Ok(tree) = serde_json::from_str::<serde_json::Value>(output) {
+        collect_sway_windows(&tree, &mut windows);
+    }
+    windows
+}
+
+/// Rect of the focused node in `swaymsg -t get_tree` output
+fn parse_sway_rect(output: &str) -> Option<Rect> {
+    let tree: serde_json::Value = serde_json::from_str(output).ok()?;
+    let rect = &find_sway_focused(&tree)?["rect"];
+    Some(Rect {
+        x: rect["x"].as_i64()? as i32,
+        y: rect["y"].as_i64()? as i32,
+        width: rect["width"].as_u64()? as u32,
+        height: rect["height"].as_u64()? as u32,
+    })
+}
+
+/// Window identity of a hyprland client
+fn hyprland_window(window: &serde_json::Value) -> Option<FocusedWindow> {
+    Some(FocusedWindow {
+        id: window["address"].as_str()?.to_string(),
+        class: window["class"].as_str().unwrap_or_default().to_string(),
+        title: window["title"].as_str().unwrap_or_default().to_string(),
+    })
+}
+
+/// Parse `hyprctl activewindow -j` output
+fn parse_hyprland(output: &str) -> Option<FocusedWindow> {
+    let window: serde_json::Value = serde_json::from_str(output).ok()?;
+    hyprland_window(&window)
+}
+
+/// Parse `hyprctl clients -j` output, ordered by focus history
+fn parse_hyprland_clients(output: &str) -> Vec<FocusedWindow> {
+    let clients: Vec<serde_json::Value> = serde_json::from_str(output).unwrap_or_default();
+    let mut clients: Vec<(i64, FocusedWindow)> = clients
+        .iter()
+        .filter(|client| client["mapped"].as_bool() != Some(false))
+        .filter_map(|client| {
+            let order = client["focusHistoryID"].as_i64().unwrap_or(i64::MAX);
+            Some((order, hyprland_window(client)?))
+        })
+        .collect();
+    clients.sort_by_key(|(order, _)| *order);
+    clients.into_iter().map(|(_, window)| window).collect()
+}
+
+/// Parse `at` and `size` from `hyprctl activewindow -j` output
+fn parse_hyprland_rect(output: &str) -> Option<Rect> {
+    let window: serde_json::Value = serde_json::from_str(output).ok()?;
+    Some(Rect {
+        x: window["at"][0].as_i64()? as i32,
+        y: window["at"][1].as_i64()? as i32,
+        width: window["size"][0].as_u64()? as u32,
+        height: window["size"][1].as_u64()? as u32,
+    })
+}
+
+/// Decide whether auto-paste may go ahead.
+///
+/// Pasting is allowed when focus could not be determined, when the focused
+/// window is unchanged since recording started, or when it matches one of
+/// the allowed class patterns.
+pub fn paste_allowed(
+    at_start: Option<&FocusedWindow>,
+    current: Option<&FocusedWindow>,
+    allowed: &[String],
+) -> bool {
+    match (at_start, current) {
+        (Some(start), Some(current)) => {
+            start.id == current.id || allowed.iter().any(|p| current.class_matches(p))
+        }
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window(id: &str, class: &str) -> FocusedWindow {
+        FocusedWindow {
+            id: id.to_string(),
+            class: class.to_string(),
+            title: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_parse_xprop() {
+        assert_eq!(
+            parse_xprop_id("_NET_ACTIVE_WINDOW(WINDOW): window id # 0x3a00007\n"),
+            Some("0x3a00007".to_string())
+        );
+        assert_eq!(
+            parse_xprop_id("_NET_ACTIVE_WINDOW(WINDOW): window id # 0x0"),
+            None
+        );
+
+        let (class, title) = parse_xprop_props(
+            "WM_CLASS(STRING) = \"navigator\", \"firefox\"\n_NET_WM_NAME(UTF8_STRING) = \"Inbox - Mail\"\n",
+        );
+        assert_eq!(class, "firefox");
+        assert_eq!(title, "Inbox - Mail");
+    }
+
+    #[test]
+    fn test_parse_sway_tree() {
+        let tree = r#"{"id": 1, "type": "root", "focused": false, "nodes": [
+            {"id": 4, "type": "workspace", "focused": false, "nodes": [
+                {"id": 7, "type": "con", "focused": true, "app_id": "foot", "name": "~"}
+            ], "floating_nodes": []}
+        ]}"#;
+        let focused = parse_sway_tree(tree).unwrap();
+        assert_eq!(focused.id, "7");
+        assert_eq!(focused.class, "foot");
+
+        let tree = r#"{"type": "root", "nodes": [{"type": "con", "focused": true,
+            "rect": {"x": 1920, "y": 30, "width": 960, "height": 1050}}]}"#;
+        assert_eq!(
+            parse_sway_rect(tree),
+            Some(Rect {
+                x: 1920,
+                y: 30,
+                width: 960,
+                height: 1050
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_window_rect() {
+        let xwininfo = "\n  Absolute upper-left X:  2560\n  Absolute upper-left Y:  120\n  Relative upper-left X:  0\n  Width: 800\n  Height: 600\n";
+        let rect = parse_xwininfo(xwininfo).unwrap();
+        assert_eq!(rect.center(), (2960, 420));
+
+        let rect = parse_hyprland_rect(r#"{"at": [10, 20], "size": [100, 50]}"#).unwrap();
+        assert!(rect.contains((10, 20)));
+        assert!(!rect.contains((110, 20)));
+    }
+
+    #[test]
+    fn test_parse_hyprland() {
+        let focused =
+            parse_hyprland(r#"{"address": "0x55d3", "class": "kitty", "title": "vim"}"#).unwrap();
+        assert_eq!(focused.id, "0x55d3");
+        assert_eq!(focused.class, "kitty");
+        assert_eq!(focused.title, "vim");
+    }
+
+    #[test]
+    fn test_parse_window_lists() {
+        assert_eq!(
+            parse_xprop_id_list(
+                "_NET_CLIENT_LIST_STACKING(WINDOW): window id # 0x1e00003, 0x3a00007\n"
+            ),
+            vec!["0x1e00003", "0x3a00007"]
+        );
+
+        let clients = r#"[
+            {"address": "0x1", "class": "firefox", "title": "Docs", "focusHistoryID": 1},
+            {"address": "0x2", "class": "kitty", "title": "vim", "focusHistoryID": 0},
+            {"address": "0x3", "class": "hidden", "title": "", "mapped": false}
+        ]"#;
+        let ids: Vec<String> = parse_hyprland_clients(clients)
+            .into_iter()
+            .map(|w| w.id)
+            .collect();
+        assert_eq!(ids, vec!["0x2", "0x1"]);
+
+        let tree = r#"{"id": 1, "type": "root", "focus": [4], "nodes": [
+            {"id": 4, "type": "workspace", "focus": [8, 7], "nodes": [
+                {"id": 7, "type": "con", "pid": 10, "app_id": "foot", "name": "~"},
+                {"id": 8, "type": "con", "pid": 11, "app_id": "firefox", "name": "Docs"}
+            ]}
+        ]}"#;
+        let classes: Vec<String> = parse_sway_windows(tree)
+            .into_iter()
+            .map(|w| w.class)
+            .collect();
+        assert_eq!(classes, vec!["firefox", "foot"]);
+    }
+
+    #[test]
+    fn test_paste_allowed() {
+        let editor = window("1", "code");
+        let terminal = window("2", "org.gnome.Terminal");
+
+        assert!(paste_allowed(Some(&editor), Some(&editor), &[]));
+        assert!(!paste_allowed(Some(&editor), Some(&terminal), &[]));
+        assert!(paste_allowed(
+            Some(&editor),
+            Some(&terminal),
+            &["terminal".to_string()]
+        ));
+        // Unknown focus never blocks pasting
+        assert!(paste_allowed(None, Some(&terminal), &[]));
+        assert!(paste_allowed(Some(&editor), None, &[]));
+    }
+}
@@ -0,0 +1,85 @@
+;; Minimal plugin component for the plugin host tests
+;;
+;; Describes itself as a text processor and context provider, replaces every
+;; text with "rewritten" (logging it first), and spins forever when asked for
+;; context so the call limits can be checked.
+(component
+  (type $capability (enum "text-processor" "output-sink" "context-provider"))
+  (export $capability-export "capability" (type $capability))
+  (type $plugin-info (record
+    (field "name" string)
+    (field "version" string)
+    (field "description" string)
+    (field "capabilities" (list $capability-export))))
+  (export $plugin-info-export "plugin-info" (type $plugin-info))
+
+  (import "log" (func $log (param "message" string)))
+
+  (core module $memory
+    (memory (export "memory") 1))
+  (core instance $memory (instantiate $memory))
+  (core func $log-lowered (canon lower (func $log) (memory $memory "memory")))
+
+  (core module $plugin
+    (import "env" "memory" (memory 1))
+    (import "host" "log" (func $log (param i32 i32)))
+    (global $heap (mut i32) (i32.const 1024))
+    (data (i32.const 0) "Test")
+    (data (i32.const 8) "1.0.0")
+    (data (i32.const 16) "Rewrites every text")
+    (data (i32.const 40) "\00\02")
+    (data (i32.const 48) "rewritten")
+    (data (i32.const 64) "plugin context")
+
+    (func (export "realloc") (param i32 i32 i32 i32) (result i32)
+      (local $ptr i32)
+      (local.set $ptr
+        (i32.and
+          (i32.add (global.get $heap) (i32.sub (local.get 2) (i32.const 1)))
+          (i32.sub (i32.const 0) (local.get 2))))
+      (global.set $heap (i32.add (local.get $ptr) (local.get 3)))
+      (local.get $ptr))
+
+    (func (export "info") (result i32)
+      (i32.store (i32.const 128) (i32.const 0))
+      (i32.store (i32.const 132) (i32.const 4))
+      (i32.store (i32.const 136) (i32.const 8))
+      (i32.store (i32.const 140) (i32.const 5))
+      (i32.store (i32.const 144) (i32.const 16))
+      (i32.store (i32.const 148) (i32.const 19))
+      (i32.store (i32.const 152) (i32.const 40))
+      (i32.store (i32.const 156) (i32.const 2))
+      (i32.const 128))
+
+    (func (export "process-text") (param i32 i32 i32 i32) (result i32)
+      (call $log (i32.const 48) (i32.const 9))
+      (i32.store8 (i32.const 192) (i32.const 0))
+      (i32.store (i32.const 196) (i32.const 48))
+      (i32.store (i32.const 200) (i32.const 9))
+      (i32.const 192))
+
+    (func (export "consume-output") (param i32 i32 i32 i32) (result i32)
+      (i32.store8 (i32.const 208) (i32.const 0))
+      (i32.const 208))
+
+    (func (export "provide-context") (param i32 i32) (result i32)
+      (loop $forever (br $forever))
+      (i32.const 0)))
+
+  (core instance $plugin (instantiate $plugin
+    (with "env" (instance $memory))
+    (with "host" (instance (export "log" (func $log-lowered))))))
+
+  (func (export "info") (result $plugin-info-export)
+    (canon lift (core func $plugin "info") (memory $memory "memory")))
+  (func (export "process-text")
+    (param "text" string) (param "mode-key" string) (result (result string (error string)))
+    (canon lift (core func $plugin "process-text")
+      (memory $memory "memory") (realloc (func $plugin "realloc"))))
+  (func (export "consume-output")
+    (param "text" string) (param "mode-key" string) (result (result (error string)))
+    (canon lift (core func $plugin "consume-output")
+      (memory $memory "memory") (realloc (func $plugin "realloc"))))
+  (func (export "provide-context") (param "mode-key" string) (result (option string))
+    (canon lift (core func $plugin "provide-context")
+      (memory $memory "memory") (realloc (func $plugin "realloc")))))
@@ -7,6 +7,8 @@ import HistoryPage from "./pages/HistoryPage";
 import SettingsPage from "./pages/SettingsPage";
 import ModesPage from "./pages/ModesPage";
 import RecordingIndicator from "./pages/RecordingIndicator";
+import ControlWindow from "./pages/ControlWindow";
+import PasteTestWindow from "./pages/PasteTestWindow";
 
 function App() {
   const navigate = useNavigate();
@@ -28,6 +30,16 @@ function App() {
     return <RecordingIndicator />;
   }
 
+  // Fallback control window (no system tray) has no layout
+  if (location.pathname === "/control") {
+    return <ControlWindow />;
+  }
+
+  // Paste benchmark target window has no layout
+  if (location.pathname === "/paste-test") {
+    return <PasteTestWindow />;
+  }
+
   return (
     <Layout>
       <Routes>
@@ -1,12 +1,38 @@
 import { invoke } from "@tauri-apps/api/core";
 import type {
   Mode,
+  ModeRevision,
+  ModeProblem,
   AudioDevice,
   HistoryItem,
+  Segment,
+  ActionItem,
+  NewTask,
+  AudioPart,
+  OrphanReport,
   Settings,
+  FocusedWindow,
+  Job,
   RecordingStatusResponse,
   HistoryQuery,
   ExportFormat,
+  FlashcardOptions,
+  ShareOptions,
+  PasteBackendCheck,
+  PasteDiagnostics,
+  PasteBenchmarkReport,
+  OllamaModel,
+  SttProvider,
+  Correction,
+  Snippet,
+  RewriteRule,
+  PluginInfo,
+  UpdateInfo,
+  GalleryEntry,
+  EmbeddingCheck,
+  ConfigImportReport,
+  InputCalibration,
+  SupportedInputConfig,
 } from "../types";
 
 // Recording
@@ -14,10 +40,52 @@ export async function startRecording(): Promise<void> {
   return invoke("start_recording");
 }
 
+export async function startRefinement(): Promise<void> {
+  return invoke("start_refinement");
+}
+
 export async function stopRecording(): Promise<string> {
   return invoke("stop_recording");
 }
 
+export async function togglePause(): Promise<boolean> {
+  return invoke("toggle_pause");
+}
+
+export async function toggleIncognito(): Promise<boolean> {
+  return invoke("toggle_incognito");
+}
+
+export async function listWindows(): Promise<FocusedWindow[]> {
+  return invoke("list_windows");
+}
+
+export async function getTargetWindow(): Promise<FocusedWindow | null> {
+  return invoke("get_target_window");
+}
+
+export async function setTargetWindow(
+  window: FocusedWindow | null
+): Promise<void> {
+  return invoke("set_target_window", { window });
+}
+
+export async function targetLastFocusedWindow(): Promise<FocusedWindow | null> {
+  return invoke("target_last_focused_window");
+}
+
+export async function cancelProcessing(): Promise<boolean> {
+  return invoke("cancel_processing");
+}
+
+export async function listJobs(): Promise<Job[]> {
+  return invoke("list_jobs");
+}
+
+export async function cancelJob(id: string): Promise<boolean> {
+  return invoke("cancel_job", { id });
+}
+
 export async function getRecordingStatus(): Promise<RecordingStatusResponse> {
   return invoke("get_recording_status");
 }
@@ -31,6 +99,45 @@ export async function setActiveMode(modeKey: string): Promise<void> {
   return invoke("set_active_mode", { modeKey });
 }
 
+export async function reorderModes(modeKeys: string[]): Promise<void> {
+  return invoke("reorder_modes", { modeKeys });
+}
+
+export async function setModeVisibility(
+  modeKey: string,
+  favorite: boolean,
+  hidden: boolean
+): Promise<void> {
+  return invoke("set_mode_visibility", { modeKey, favorite, hidden });
+}
+
+export async function saveMode(mode: Mode): Promise<Mode> {
+  return invoke("save_mode", { mode });
+}
+
+export async function validateMode(mode: Mode): Promise<ModeProblem[]> {
+  return invoke("validate_mode", { mode });
+}
+
+export async function resetMode(modeKey: string): Promise<Mode> {
+  return invoke("reset_mode", { modeKey });
+}
+
+export async function getModeRevisions(modeKey: string): Promise<ModeRevision[]> {
+  return invoke("get_mode_revisions", { modeKey });
+}
+
+export async function restoreModeRevision(
+  modeKey: string,
+  revisionId: number
+): Promise<Mode> {
+  return invoke("restore_mode_revision", { modeKey, revisionId });
+}
+
+export async function cycleMode(forward: boolean): Promise<string> {
+  return invoke("cycle_mode", { forward });
+}
+
 export async function getActiveMode(): Promise<Mode | null> {
   return invoke("get_active_mode");
 }
@@ -40,6 +147,23 @@ export async function getInputDevices(): Promise<AudioDevice[]> {
   return invoke("get_input_devices");
 }
 
+export async function getJackPorts(): Promise<string[]> {
+  return invoke("get_jack_ports");
+}
+
+export async function probeInputDevice(
+  deviceName: string
+): Promise<SupportedInputConfig[]> {
+  return invoke("probe_input_device", { deviceName });
+}
+
+export async function calibrateInputLevel(
+  deviceName: string,
+  seconds?: number
+): Promise<InputCalibration> {
+  return invoke("calibrate_input_level", { deviceName, seconds });
+}
+
 export async function setInputDevice(deviceName: string): Promise<void> {
   return invoke("set_input_device", { deviceName });
 }
@@ -49,6 +173,34 @@ export async function transcribeFile(filePath: string): Promise<string> {
   return invoke("transcribe_file", { filePath });
 }
 
+export async function isModelDownloaded(model: string): Promise<boolean> {
+  return invoke("is_model_downloaded", { model });
+}
+
+export async function downloadModel(model: string): Promise<string> {
+  return invoke("download_model", { model });
+}
+
+export async function listSttModels(provider: SttProvider): Promise<string[]> {
+  return invoke("list_stt_models", { provider });
+}
+
+export async function listLanguages(): Promise<[string, string][]> {
+  return invoke("list_languages");
+}
+
+export async function listOllamaModels(): Promise<OllamaModel[]> {
+  return invoke("list_ollama_models");
+}
+
+export async function browseModeGallery(): Promise<GalleryEntry[]> {
+  return invoke("browse_mode_gallery");
+}
+
+export async function installModeFromUrl(url: string, overwrite: boolean): Promise<Mode> {
+  return invoke("install_mode_from_url", { url, overwrite });
+}
+
 // History
 export async function getHistory(query?: HistoryQuery): Promise<HistoryItem[]> {
   return invoke("get_history", { query });
@@ -58,6 +210,22 @@ export async function getHistoryItem(id: string): Promise<HistoryItem | null> {
   return invoke("get_history_item", { id });
 }
 
+export async function getHistorySegments(id: string): Promise<Segment[]> {
+  return invoke("get_history_segments", { id });
+}
+
+export async function getActionItems(id: string): Promise<ActionItem[]> {
+  return invoke("get_action_items", { id });
+}
+
+export async function previewTasks(id: string): Promise<NewTask[]> {
+  return invoke("preview_tasks", { id });
+}
+
+export async function createTasks(id: string): Promise<number> {
+  return invoke("create_tasks", { id });
+}
+
 export async function reprocessHistoryItem(
   id: string,
   modeKey: string
@@ -65,10 +233,86 @@ export async function reprocessHistoryItem(
   return invoke("reprocess_history_item", { id, modeKey });
 }
 
+export async function reprocessWithPrompt(
+  id: string,
+  prompt: string,
+  modeKey?: string
+): Promise<string> {
+  return invoke("reprocess_with_prompt", { id, prompt, modeKey });
+}
+
+export async function updateHistoryOutput(id: string, output: string): Promise<void> {
+  return invoke("update_history_output", { id, output });
+}
+
+export async function getCorrections(): Promise<Correction[]> {
+  return invoke("get_corrections");
+}
+
+export async function deleteCorrection(original: string, corrected: string): Promise<void> {
+  return invoke("delete_correction", { original, corrected });
+}
+
+export async function getSnippets(): Promise<Snippet[]> {
+  return invoke("get_snippets");
+}
+
+export async function saveSnippet(snippet: Snippet): Promise<void> {
+  return invoke("save_snippet", { snippet });
+}
+
+export async function deleteSnippet(name: string): Promise<void> {
+  return invoke("delete_snippet", { name });
+}
+
+export async function getRewriteRules(): Promise<RewriteRule[]> {
+  return invoke("get_rewrite_rules");
+}
+
+export async function saveRewriteRule(rule: RewriteRule): Promise<RewriteRule> {
+  return invoke("save_rewrite_rule", { rule });
+}
+
+export async function deleteRewriteRule(id: string): Promise<void> {
+  return invoke("delete_rewrite_rule", { id });
+}
+
+export async function reorderRewriteRules(ids: string[]): Promise<void> {
+  return invoke("reorder_rewrite_rules", { ids });
+}
+
+export async function testRewriteRules(
+  sample: string,
+  modeKey: string,
+  rules?: RewriteRule[]
+): Promise<string> {
+  return invoke("test_rewrite_rules", { sample, modeKey, rules: rules ?? null });
+}
+
+export async function listPlugins(): Promise<PluginInfo[]> {
+  return invoke("list_plugins");
+}
+
+export async function reloadPlugins(): Promise<PluginInfo[]> {
+  return invoke("reload_plugins");
+}
+
+export async function setPluginEnabled(id: string, enabled: boolean): Promise<void> {
+  return invoke("set_plugin_enabled", { id, enabled });
+}
+
 export async function deleteHistoryItem(id: string): Promise<void> {
   return invoke("delete_history_item", { id });
 }
 
+export async function deleteHistoryItems(ids: string[]): Promise<number> {
+  return invoke("delete_history_items", { ids });
+}
+
+export async function clearHistory(beforeDate?: string): Promise<number> {
+  return invoke("clear_history", { beforeDate: beforeDate ?? null });
+}
+
 export async function exportHistoryItem(
   id: string,
   format: ExportFormat
@@ -76,6 +320,76 @@ export async function exportHistoryItem(
   return invoke("export_history_item", { id, format });
 }
 
+export async function shareHistoryItem(
+  id: string,
+  options?: ShareOptions
+): Promise<string> {
+  return invoke("share_history_item", { id, options: options ?? null });
+}
+
+export async function composeHistoryEmail(id: string): Promise<void> {
+  return invoke("compose_history_email", { id });
+}
+
+export async function postHistoryItem(id: string): Promise<void> {
+  return invoke("post_history_item", { id });
+}
+
+export async function exportFlashcards(
+  ids: string[],
+  options: FlashcardOptions
+): Promise<string> {
+  return invoke("export_flashcards", { ids, options });
+}
+
+export async function exportHistoryZip(
+  id: string,
+  path: string
+): Promise<string> {
+  return invoke("export_history_zip", { id, path });
+}
+
+export async function createDebugBundle(path: string): Promise<string> {
+  return invoke("create_debug_bundle", { path });
+}
+
+export async function exportConfigBundle(path: string): Promise<string> {
+  return invoke("export_config_bundle", { path });
+}
+
+export async function importConfigBundle(
+  path: string
+): Promise<ConfigImportReport> {
+  return invoke("import_config_bundle", { path });
+}
+
+export async function checkForUpdates(): Promise<UpdateInfo> {
+  return invoke("check_for_updates");
+}
+
+export async function importTranscript(
+  path: string,
+  audioPath?: string
+): Promise<HistoryItem> {
+  return invoke("import_transcript", { path, audioPath });
+}
+
+export async function mergeHistoryItems(ids: string[]): Promise<HistoryItem> {
+  return invoke("merge_history_items", { ids });
+}
+
+export async function removeDuplicateHistory(): Promise<number> {
+  return invoke("remove_duplicate_history");
+}
+
+export async function scanOrphanedAudio(clean: boolean): Promise<OrphanReport> {
+  return invoke("scan_orphaned_audio", { clean });
+}
+
+export async function getHistoryAudio(id: string): Promise<AudioPart[]> {
+  return invoke("get_history_audio", { id });
+}
+
 // Settings
 export async function getSettings(): Promise<Settings> {
   return invoke("get_settings");
@@ -97,3 +411,33 @@ export async function deleteApiKey(provider: string): Promise<void> {
 export async function hasApiKey(provider: string): Promise<boolean> {
   return invoke("has_api_key", { provider });
 }
+
+export async function lockKeys(): Promise<number> {
+  return invoke("lock_keys");
+}
+
+// Embeddings
+export async function testEmbeddingProvider(): Promise<EmbeddingCheck> {
+  return invoke("test_embedding_provider");
+}
+
+export async function indexHistoryEmbeddings(): Promise<number> {
+  return invoke("index_history_embeddings");
+}
+
+// Paste backend setup
+export async function checkPasteBackend(): Promise<PasteBackendCheck> {
+  return invoke("check_paste_backend");
+}
+
+export async function getPasteDiagnostics(): Promise<PasteDiagnostics> {
+  return invoke("get_paste_diagnostics");
+}
+
+export async function benchmarkPasteBackends(): Promise<PasteBenchmarkReport> {
+  return invoke("benchmark_paste_backends");
+}
+
+export async function installUinputRule(): Promise<void> {
+  return invoke("install_uinput_rule");
+}
@@ -0,0 +1,61 @@
+import { useEffect } from "react";
+import clsx from "clsx";
+import { useAppStore } from "../stores/appStore";
+import StatusIndicator from "../components/StatusIndicator";
+
+export default function ControlWindow() {
+  const {
+    status,
+    isRecording,
+    activeMode,
+    modes,
+    initialize,
+    startRecording,
+    stopRecording,
+    setActiveMode,
+  } = useAppStore();
+
+  useEffect(() => {
+    initialize();
+  }, [initialize]);
+
+  const handleRecordClick = () => {
+    if (isRecording) {
+      stopRecording();
+    } else {
+      startRecording();
+    }
+  };
+
+  return (
+    <div className="h-screen bg-gray-900 p-4 flex flex-col gap-3">
+      <div className="flex items-center justify-between">
+        <StatusIndicator status={status} size="sm" />
+        <button
+          onClick={handleRecordClick}
+          disabled={status === "processing" || status === "loading"}
+          className={clsx(
+            "px-4 py-2 rounded-lg text-white font-medium",
+            isRecording
+              ? "bg-red-600 hover:bg-red-700"
+              : "bg-green-600 hover:bg-green-700 disabled:bg-gray-600"
+          )}
+        >
+          {isRecording ? "Stop" : "Record"}
+        </button>
+      </div>
+
+      <select
+        value={activeMode?.key || ""}
+        onChange={(e) => setActiveMode(e.target.value)}
+        className="w-full bg-gray-800 text-white rounded-lg px-3 py-2"
+      >
+        {modes.map((mode) => (
+          <option key={mode.key} value={mode.key}>
+            {mode.name}
+          </option>
+        ))}
+      </select>
+    </div>
+  );
+}
@@ -1,8 +1,16 @@
 import { useEffect, useState } from "react";
+import { listen } from "@tauri-apps/api/event";
 import { format } from "date-fns";
 import { useAppStore } from "../stores/appStore";
 import * as api from "../lib/api";
-import type { ExportFormat } from "../types";
+import type {
+  ActionItem,
+  ActionItemsUpdate,
+  CardField,
+  ExportFormat,
+  FlashcardOptions,
+  NewTask,
+} from "../types";
 import clsx from "clsx";
 
 export default function HistoryPage() {
@@ -14,15 +22,53 @@ export default function HistoryPage() {
     selectHistoryItem,
     reprocessHistoryItem,
     deleteHistoryItem,
+    clearHistory,
   } = useAppStore();
 
   const [searchQuery, setSearchQuery] = useState("");
   const [reprocessMode, setReprocessMode] = useState("");
+  const [customPrompt, setCustomPrompt] = useState("");
+  const [customResult, setCustomResult] = useState<string | null>(null);
+  const [customRunning, setCustomRunning] = useState(false);
+  const [clearBefore, setClearBefore] = useState("");
+  const [actionItems, setActionItems] = useState<ActionItem[]>([]);
+  const [taskPreview, setTaskPreview] = useState<NewTask[] | null>(null);
+  const [taskStatus, setTaskStatus] = useState<string | null>(null);
+  const [cardIds, setCardIds] = useState<string[]>([]);
+  const [cardOptions, setCardOptions] = useState<FlashcardOptions>({
+    format: "anki",
+    front: "transcript",
+    back: "output",
+    deck: "WhisperTray",
+    tags: [],
+  });
 
   useEffect(() => {
     loadHistory();
   }, [loadHistory]);
 
+  useEffect(() => {
+    setCustomResult(null);
+    setTaskPreview(null);
+    setTaskStatus(null);
+  }, [selectedHistoryItem?.id]);
+
+  // Action items are extracted after saving, so they may arrive later
+  useEffect(() => {
+    const id = selectedHistoryItem?.id;
+    setActionItems([]);
+    if (!id) return;
+    api.getActionItems(id).then(setActionItems).catch(() => setActionItems([]));
+    const unlisten = listen<ActionItemsUpdate>("action-items", (event) => {
+      if (event.payload.history_id === id) {
+        setActionItems(event.payload.items);
+      }
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [selectedHistoryItem?.id]);
+
   const handleSearch = () => {
     loadHistory(searchQuery || undefined);
   };
@@ -44,12 +90,76 @@ export default function HistoryPage() {
     }
   };
 
+  const handlePreviewTasks = async () => {
+    if (!selectedHistoryItem) return;
+    try {
+      setTaskPreview(await api.previewTasks(selectedHistoryItem.id));
+      setTaskStatus(null);
+    } catch (error) {
+      setTaskStatus(`Error: ${error}`);
+    }
+  };
+
+  const handleCreateTasks = async () => {
+    if (!selectedHistoryItem) return;
+    try {
+      const count = await api.createTasks(selectedHistoryItem.id);
+      setTaskPreview(null);
+      setTaskStatus(`Created ${count} tasks`);
+    } catch (error) {
+      setTaskStatus(`Error: ${error}`);
+    }
+  };
+
+  const toggleCard = (id: string) => {
+    setCardIds((ids) =>
+      ids.includes(id) ? ids.filter((i) => i !== id) : [...ids, id],
+    );
+  };
+
+  const handleExportCards = async () => {
+    try {
+      const content = await api.exportFlashcards(cardIds, cardOptions);
+      const blob = new Blob([content], { type: "text/plain" });
+      const url = URL.createObjectURL(blob);
+      const a = document.createElement("a");
+      a.href = url;
+      a.download = `flashcards.${cardOptions.format === "anki" ? "txt" : "csv"}`;
+      a.click();
+      URL.revokeObjectURL(url);
+    } catch (error) {
+      console.error("Flashcard export failed:", error);
+    }
+  };
+
+  const cardFields: [CardField, string][] = [
+    ["transcript", "Transcript"],
+    ["output", "Output"],
+    ["title", "Title"],
+    ["mode", "Mode"],
+    ["date", "Date"],
+  ];
+
   const handleReprocess = async () => {
     if (!selectedHistoryItem || !reprocessMode) return;
     await reprocessHistoryItem(selectedHistoryItem.id, reprocessMode);
     setReprocessMode("");
   };
 
+  const handleCustomPrompt = async () => {
+    if (!selectedHistoryItem || !customPrompt.trim()) return;
+    setCustomRunning(true);
+    try {
+      setCustomResult(
+        await api.reprocessWithPrompt(selectedHistoryItem.id, customPrompt)
+      );
+    } catch (error) {
+      setCustomResult(`Error: ${error}`);
+    } finally {
+      setCustomRunning(false);
+    }
+  };
+
   const handleDelete = async () => {
     if (!selectedHistoryItem) return;
     if (confirm("Are you sure you want to delete this item?")) {
@@ -57,6 +167,20 @@ export default function HistoryPage() {
     }
   };
 
+  const handleClear = async () => {
+    const message = clearBefore
+      ? `Delete all items from before ${clearBefore}?`
+      : "Delete the entire history?";
+    if (confirm(message)) {
+      await clearHistory(clearBefore || undefined);
+    }
+  };
+
+  const selectedMode = modes.find(
+    (mode) => mode.key === selectedHistoryItem?.mode_key,
+  );
+  const taskTarget = selectedMode?.task_target;
+
   const formatDuration = (ms: number) => {
     const seconds = Math.floor(ms / 1000);
     const minutes = Math.floor(seconds / 60);
@@ -120,11 +244,116 @@ export default function HistoryPage() {
                   {item.error && (
                     <span className="text-xs text-red-400">Error</span>
                   )}
+                  {item.clipped && (
+                    <span className="text-xs text-yellow-400">Clipped</span>
+                  )}
+                  {cardIds.includes(item.id) && (
+                    <span className="text-xs text-green-400">Card</span>
+                  )}
                 </div>
               </button>
             ))
           )}
         </div>
+
+        {/* Flashcards */}
+        {cardIds.length > 0 && (
+          <div className="mt-4 p-3 bg-gray-800 rounded-lg space-y-2 text-sm">
+            <div className="flex items-center justify-between">
+              <span className="text-gray-300">
+                {cardIds.length} flashcard{cardIds.length === 1 ? "" : "s"}
+              </span>
+              <button
+                onClick={() => setCardIds([])}
+                className="text-xs text-gray-400 hover:text-gray-300"
+              >
+                Clear
+              </button>
+            </div>
+            <div className="flex gap-2">
+              {(["front", "back"] as const).map((side) => (
+                <select
+                  key={side}
+                  value={cardOptions[side]}
+                  onChange={(e) =>
+                    setCardOptions({
+                      ...cardOptions,
+                      [side]: e.target.value as CardField,
+                    })
+                  }
+                  className="flex-1 bg-gray-700 border border-gray-600 rounded px-2 py-1 text-white"
+                >
+                  {cardFields.map(([field, label]) => (
+                    <option key={field} value={field}>
+                      {side === "front" ? "Front" : "Back"}: {label}
+                    </option>
+                  ))}
+                </select>
+              ))}
+            </div>
+            <div className="flex gap-2">
+              <input
+                type="text"
+                value={cardOptions.deck}
+                onChange={(e) =>
+                  setCardOptions({ ...cardOptions, deck: e.target.value })
+                }
+                placeholder="Deck"
+                className="flex-1 bg-gray-700 border border-gray-600 rounded px-2 py-1 text-white"
+              />
+              <input
+                type="text"
+                value={cardOptions.tags.join(" ")}
+                onChange={(e) =>
+                  setCardOptions({
+                    ...cardOptions,
+                    tags: e.target.value.split(" ").filter(Boolean),
+                  })
+                }
+                placeholder="Tags"
+                className="flex-1 bg-gray-700 border border-gray-600 rounded px-2 py-1 text-white"
+              />
+            </div>
+            <div className="flex gap-2">
+              <select
+                value={cardOptions.format}
+                onChange={(e) =>
+                  setCardOptions({
+                    ...cardOptions,
+                    format: e.target.value as FlashcardOptions["format"],
+                  })
+                }
+                className="flex-1 bg-gray-700 border border-gray-600 rounded px-2 py-1 text-white"
+              >
+                <option value="anki">Anki</option>
+                <option value="csv">CSV</option>
+              </select>
+              <button
+                onClick={handleExportCards}
+                className="px-3 py-1 bg-blue-600 text-white rounded hover:bg-blue-700"
+              >
+                Export
+              </button>
+            </div>
+          </div>
+        )}
+
+        {/* Clear */}
+        <div className="flex gap-2 mt-4">
+          <input
+            type="date"
+            value={clearBefore}
+            onChange={(e) => setClearBefore(e.target.value)}
+            className="flex-1 bg-gray-800 border border-gray-700 rounded-lg px-3 py-2 text-white text-sm focus:outline-none focus:ring-2 focus:ring-blue-500"
+          />
+          <button
+            onClick={handleClear}
+            disabled={history.length === 0}
+            className="px-4 py-2 text-red-400 hover:text-red-300 text-sm disabled:opacity-50"
+          >
+            {clearBefore ? "Clear older" : "Clear all"}
+          </button>
+        </div>
       </div>
 
       {/* Detail view */}
@@ -189,6 +418,112 @@ export default function HistoryPage() {
                   {selectedHistoryItem.output_final}
                 </div>
               </div>
+
+              {/* Action items */}
+              {actionItems.length > 0 && (
+                <div>
+                  <h3 className="text-sm font-medium text-gray-400 mb-2">
+                    Action Items
+                  </h3>
+                  <ul className="bg-gray-900 rounded p-3 text-sm text-white space-y-1">
+                    {actionItems.map((item, i) => (
+                      <li key={i}>
+                        {item.task}
+                        {(item.owner || item.due) && (
+                          <span className="text-gray-400">
+                            {" "}
+                            ({[item.owner, item.due && `due ${item.due}`]
+                              .filter(Boolean)
+                              .join(", ")})
+                          </span>
+                        )}
+                      </li>
+                    ))}
+                  </ul>
+                </div>
+              )}
+
+              {/* Tasks */}
+              {taskTarget && (
+                <div>
+                  <div className="flex items-center justify-between mb-2">
+                    <h3 className="text-sm font-medium text-gray-400">
+                      Tasks ({taskTarget.service === "todoist" ? "Todoist" : "CalDAV"})
+                    </h3>
+                    <div className="flex gap-2">
+                      <button
+                        onClick={handlePreviewTasks}
+                        className="text-xs text-blue-400 hover:text-blue-300"
+                      >
+                        Preview
+                      </button>
+                      <button
+                        onClick={handleCreateTasks}
+                        className="text-xs text-blue-400 hover:text-blue-300"
+                      >
+                        Create
+                      </button>
+                    </div>
+                  </div>
+                  {taskPreview && (
+                    <ul className="bg-gray-900 rounded p-3 text-sm text-white space-y-1">
+                      {taskPreview.length === 0 && (
+                        <li className="text-gray-400">No tasks found</li>
+                      )}
+                      {taskPreview.map((task, i) => (
+                        <li key={i}>
+                          {task.content}
+                          {task.due && (
+                            <span className="text-gray-400"> (due {task.due})</span>
+                          )}
+                        </li>
+                      ))}
+                    </ul>
+                  )}
+                  {taskStatus && (
+                    <p className="text-xs text-gray-400 mt-1">{taskStatus}</p>
+                  )}
+                </div>
+              )}
+
+              {/* One-off prompt */}
+              <div>
+                <h3 className="text-sm font-medium text-gray-400 mb-2">
+                  Custom Prompt
+                </h3>
+                <div className="flex gap-2">
+                  <input
+                    type="text"
+                    value={customPrompt}
+                    onChange={(e) => setCustomPrompt(e.target.value)}
+                    onKeyDown={(e) => e.key === "Enter" && handleCustomPrompt()}
+                    placeholder="e.g. Turn this into a bullet list"
+                    className="flex-1 bg-gray-800 border border-gray-700 rounded-lg px-3 py-2 text-white text-sm focus:outline-none focus:ring-2 focus:ring-blue-500"
+                  />
+                  <button
+                    onClick={handleCustomPrompt}
+                    disabled={!customPrompt.trim() || customRunning}
+                    className="px-3 py-1 bg-blue-600 text-white rounded text-sm hover:bg-blue-700 disabled:opacity-50 disabled:cursor-not-allowed"
+                  >
+                    {customRunning ? "Running..." : "Run"}
+                  </button>
+                </div>
+                {customResult !== null && (
+                  <div className="mt-2">
+                    <div className="flex justify-end mb-1">
+                      <button
+                        onClick={() => navigator.clipboard.writeText(customResult)}
+                        className="text-xs text-blue-400 hover:text-blue-300"
+                      >
+                        Copy
+                      </button>
+                    </div>
+                    <div className="bg-gray-900 rounded p-3 text-sm text-white whitespace-pre-wrap">
+                      {customResult}
+                    </div>
+                  </div>
+                )}
+              </div>
             </div>
 
             {/* Actions */}
@@ -228,6 +563,51 @@ export default function HistoryPage() {
                     .{fmt}
                   </button>
                 ))}
+                {(["html", "markdown"] as const).map((fmt) => (
+                  <button
+                    key={fmt}
+                    onClick={() =>
+                      api.shareHistoryItem(selectedHistoryItem.id, {
+                        format: fmt,
+                        include_transcript: false,
+                        include_details: false,
+                      })
+                    }
+                    className="px-2 py-1 bg-gray-700 text-gray-300 rounded text-xs hover:bg-gray-600"
+                  >
+                    Share as {fmt === "html" ? "HTML" : "Markdown"}
+                  </button>
+                ))}
+                <button
+                  onClick={() =>
+                    api
+                      .composeHistoryEmail(selectedHistoryItem.id)
+                      .catch((error) => console.error("Compose failed:", error))
+                  }
+                  className="px-2 py-1 bg-gray-700 text-gray-300 rounded text-xs hover:bg-gray-600"
+                >
+                  Open in Mail
+                </button>
+                {selectedMode && selectedMode.post_to.length > 0 && (
+                  <button
+                    onClick={() =>
+                      api
+                        .postHistoryItem(selectedHistoryItem.id)
+                        .catch((error) => console.error("Posting failed:", error))
+                    }
+                    className="px-2 py-1 bg-gray-700 text-gray-300 rounded text-xs hover:bg-gray-600"
+                  >
+                    Post to Chat
+                  </button>
+                )}
+                <button
+                  onClick={() => toggleCard(selectedHistoryItem.id)}
+                  className="px-2 py-1 bg-gray-700 text-gray-300 rounded text-xs hover:bg-gray-600"
+                >
+                  {cardIds.includes(selectedHistoryItem.id)
+                    ? "Remove flashcard"
+                    : "Add as flashcard"}
+                </button>
               </div>
             </div>
           </div>
@@ -1,6 +1,15 @@
+import { useEffect, useState } from "react";
+import { listen } from "@tauri-apps/api/event";
 import { useAppStore } from "../stores/appStore";
+import * as api from "../lib/api";
+import type { FocusedWindow, Job, RecordingStatusResponse } from "../types";
 import clsx from "clsx";
 
+function formatElapsed(ms: number) {
+  const seconds = Math.floor(ms / 1000);
+  return `${Math.floor(seconds / 60)}:${(seconds % 60).toString().padStart(2, "0")}`;
+}
+
 export default function HomePage() {
   const {
     status,
@@ -10,9 +19,69 @@ export default function HomePage() {
     modes,
     startRecording,
     stopRecording,
+    togglePause,
     setActiveMode,
   } = useAppStore();
 
+  const [hud, setHud] = useState<RecordingStatusResponse | null>(null);
+  const [windows, setWindows] = useState<FocusedWindow[]>([]);
+  const [target, setTarget] = useState<FocusedWindow | null>(null);
+  const [jobs, setJobs] = useState<Job[]>([]);
+
+  const refreshWindows = () => {
+    api.listWindows().then(setWindows).catch(() => setWindows([]));
+  };
+
+  useEffect(() => {
+    refreshWindows();
+    api.getTargetWindow().then(setTarget);
+  }, []);
+
+  // Keep the job list in sync with progress events
+  useEffect(() => {
+    api.listJobs().then(setJobs).catch(() => setJobs([]));
+    const unlisten = listen<Job>("job-progress", (event) => {
+      const job = event.payload;
+      setJobs((jobs) =>
+        jobs.some((j) => j.id === job.id)
+          ? jobs.map((j) => (j.id === job.id ? job : j))
+          : [job, ...jobs],
+      );
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  const activeJobs = jobs.filter(
+    (job) =>
+      job.status === "queued" ||
+      job.status === "running" ||
+      job.status === "paused",
+  );
+
+  const handleTargetChange = async (value: string) => {
+    if (value === "last") {
+      setTarget(await api.targetLastFocusedWindow());
+      return;
+    }
+    const window = windows.find((w) => w.id === value) ?? null;
+    await api.setTargetWindow(window);
+    setTarget(window);
+  };
+
+  // Poll the backend while recording; it owns the clock, so the timer never drifts
+  useEffect(() => {
+    if (!isRecording) {
+      setHud(null);
+      return;
+    }
+    const poll = () => api.getRecordingStatus().then(setHud).catch(() => {});
+    poll();
+    const timer = setInterval(poll, 250);
+    return () => clearInterval(timer);
+  }, [isRecording]);
+
   const handleRecordClick = () => {
     if (isRecording) {
       stopRecording();
@@ -31,7 +100,9 @@ export default function HomePage() {
           className={clsx(
             "w-32 h-32 rounded-full text-white font-semibold text-lg transition-all",
             "focus:outline-none focus:ring-4",
-            isRecording
+            status === "paused"
+              ? "bg-orange-500 hover:bg-orange-600 focus:ring-orange-500/50"
+              : isRecording
               ? "bg-red-600 hover:bg-red-700 focus:ring-red-500/50 animate-pulse"
               : status === "processing"
               ? "bg-blue-600 cursor-not-allowed"
@@ -48,8 +119,34 @@ export default function HomePage() {
             ? "Loading"
             : "Record"}
         </button>
+        {isRecording && (
+          <div className="mt-3">
+            <button
+              onClick={togglePause}
+              className="px-3 py-1 bg-gray-700 text-gray-200 rounded text-sm hover:bg-gray-600"
+            >
+              {status === "paused" ? "Resume" : "Pause"}
+            </button>
+          </div>
+        )}
+        {hud && hud.is_recording && (
+          <div className="mt-4 mx-auto max-w-xs space-y-1">
+            <div className="flex justify-between text-sm text-gray-300">
+              <span>{formatElapsed(hud.elapsed_ms ?? 0)}</span>
+              <span className="truncate ml-2">{hud.device}</span>
+            </div>
+            <div className="h-1.5 bg-gray-700 rounded">
+              <div
+                className="h-1.5 bg-red-500 rounded"
+                style={{ width: `${Math.round(hud.level * 100)}%` }}
+              />
+            </div>
+          </div>
+        )}
         <p className="mt-4 text-gray-400 text-sm">
-          {isRecording
+          {status === "paused"
+            ? "Paused - resume or stop to finish"
+            : isRecording
             ? "Click to stop recording"
             : "Click to start recording, or use the tray icon"}
         </p>
@@ -80,6 +177,75 @@ export default function HomePage() {
         )}
       </div>
 
+      {/* Paste target */}
+      <div className="bg-gray-800 rounded-lg p-4">
+        <div className="flex items-center justify-between mb-2">
+          <label className="text-sm font-medium text-gray-300">
+            Paste Into
+          </label>
+          <button
+            onClick={refreshWindows}
+            className="text-xs text-blue-400 hover:text-blue-300"
+          >
+            Refresh
+          </button>
+        </div>
+        <select
+          value={target?.id ?? ""}
+          onChange={(e) => handleTargetChange(e.target.value)}
+          className="w-full bg-gray-700 border border-gray-600 rounded-lg px-3 py-2 text-white focus:outline-none focus:ring-2 focus:ring-blue-500"
+        >
+          <option value="">Window focused when recording starts</option>
+          <option value="last">Last focused window</option>
+          {target && !windows.some((w) => w.id === target.id) && (
+            <option value={target.id}>
+              {target.title || target.class}
+            </option>
+          )}
+          {windows.map((w) => (
+            <option key={w.id} value={w.id}>
+              {w.title || w.class} ({w.class})
+            </option>
+          ))}
+        </select>
+        {target && (
+          <p className="mt-2 text-sm text-gray-400">
+            The result is pasted into {target.class} after processing
+          </p>
+        )}
+      </div>
+
+      {/* Jobs */}
+      {activeJobs.length > 0 && (
+        <div className="bg-gray-800 rounded-lg p-4">
+          <h3 className="text-sm font-medium text-gray-300 mb-2">Jobs</h3>
+          <ul className="space-y-2">
+            {activeJobs.map((job) => (
+              <li key={job.id} className="flex items-center gap-3 text-sm">
+                <span className="flex-1 truncate text-gray-100">
+                  {job.label || job.kind}
+                </span>
+                <span className="text-gray-400">
+                  {job.status === "queued"
+                    ? "Queued"
+                    : job.status === "paused"
+                      ? "Paused for dictation"
+                      : job.progress !== null
+                      ? `${job.progress}%`
+                      : "Running"}
+                </span>
+                <button
+                  onClick={() => api.cancelJob(job.id)}
+                  className="text-xs text-red-400 hover:text-red-300"
+                >
+                  Cancel
+                </button>
+              </li>
+            ))}
+          </ul>
+        </div>
+      )}
+
       {/* Last output */}
       {lastOutput && (
         <div className="bg-gray-800 rounded-lg p-4">
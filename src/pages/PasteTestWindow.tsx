@@ -0,0 +1,36 @@
+import { useEffect, useRef, useState } from "react";
+import { emit, listen } from "@tauri-apps/api/event";
+
+// Target of the paste benchmark: reports what arrives in the text field
+export default function PasteTestWindow() {
+  const [text, setText] = useState("");
+  const inputRef = useRef<HTMLInputElement>(null);
+
+  useEffect(() => {
+    const unlisten = listen("paste-test-reset", () => {
+      setText("");
+      inputRef.current?.focus();
+    });
+
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  useEffect(() => {
+    emit("paste-test-input", text);
+  }, [text]);
+
+  return (
+    <div className="h-screen bg-gray-900 p-4 flex items-center">
+      <input
+        ref={inputRef}
+        autoFocus
+        value={text}
+        onChange={(e) => setText(e.target.value)}
+        placeholder="Paste test"
+        className="w-full px-3 py-2 rounded-lg bg-gray-800 text-white"
+      />
+    </div>
+  );
+}
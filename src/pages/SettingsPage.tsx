@@ -1,6 +1,12 @@
 import { useState, useEffect } from "react";
 import { useAppStore } from "../stores/appStore";
 import * as api from "../lib/api";
+import type {
+  AudioBackend,
+  InputCalibration,
+  StreamOverride,
+  SupportedInputConfig,
+} from "../types";
 
 export default function SettingsPage() {
   const { settings, devices, updateSettings, saveApiKey, deleteApiKey } =
@@ -16,6 +22,13 @@ export default function SettingsPage() {
     anthropic: false,
   });
   const [saving, setSaving] = useState(false);
+  const [calibrating, setCalibrating] = useState(false);
+  const [calibration, setCalibration] = useState<InputCalibration | null>(null);
+  const [calibrationError, setCalibrationError] = useState<string | null>(null);
+  const [probe, setProbe] = useState<SupportedInputConfig[] | null>(null);
+  const [jackPorts, setJackPorts] = useState<string[]>([]);
+  const [jackError, setJackError] = useState<string | null>(null);
+  const [languages, setLanguages] = useState<[string, string][]>([]);
 
   useEffect(() => {
     if (settings) {
@@ -53,6 +66,83 @@ export default function SettingsPage() {
     }
   };
 
+  const streamOverride: StreamOverride = localSettings?.input_overrides[
+    localSettings.input_device
+  ] ?? { sample_rate: null, sample_format: null, buffer_size: null };
+
+  const setStreamOverride = (changes: Partial<StreamOverride>) => {
+    if (!localSettings) return;
+    setLocalSettings({
+      ...localSettings,
+      input_overrides: {
+        ...localSettings.input_overrides,
+        [localSettings.input_device]: { ...streamOverride, ...changes },
+      },
+    });
+  };
+
+  useEffect(() => {
+    api.listLanguages().then(setLanguages);
+  }, []);
+
+  useEffect(() => {
+    if (localSettings?.audio_backend !== "jack") return;
+    api
+      .getJackPorts()
+      .then((ports) => {
+        setJackPorts(ports);
+        setJackError(null);
+      })
+      .catch((error) => setJackError(String(error)));
+  }, [localSettings?.audio_backend]);
+
+  const toggleJackPort = (port: string, enabled: boolean) => {
+    if (!localSettings) return;
+    setLocalSettings({
+      ...localSettings,
+      jack_ports: enabled
+        ? [...localSettings.jack_ports, port]
+        : localSettings.jack_ports.filter((p) => p !== port),
+    });
+  };
+
+  const handleProbe = async () => {
+    if (!localSettings) return;
+    try {
+      setProbe(await api.probeInputDevice(localSettings.input_device));
+    } catch (error) {
+      console.error("Failed to probe input device:", error);
+    }
+  };
+
+  const handleCalibrate = async () => {
+    if (!localSettings) return;
+    setCalibrating(true);
+    setCalibration(null);
+    setCalibrationError(null);
+    try {
+      setCalibration(await api.calibrateInputLevel(localSettings.input_device));
+    } catch (error) {
+      setCalibrationError(String(error));
+    } finally {
+      setCalibrating(false);
+    }
+  };
+
+  const handleApplyCalibration = async () => {
+    if (!localSettings || !calibration) return;
+    const updated = {
+      ...localSettings,
+      input_calibration: {
+        ...localSettings.input_calibration,
+        [localSettings.input_device]: calibration,
+      },
+    };
+    setLocalSettings(updated);
+    await updateSettings(updated);
+    setCalibration(null);
+  };
+
   const handleDeleteKey = async (provider: "openai" | "anthropic") => {
     if (confirm(`Delete ${provider} API key?`)) {
       await deleteApiKey(provider);
@@ -73,6 +163,48 @@ export default function SettingsPage() {
         <h2 className="text-lg font-medium text-white mb-4">Audio</h2>
 
         <div className="space-y-4">
+          <div>
+            <label className="block text-sm font-medium text-gray-300 mb-1">
+              Audio Backend
+            </label>
+            <select
+              value={localSettings.audio_backend}
+              onChange={(e) =>
+                setLocalSettings({
+                  ...localSettings,
+                  audio_backend: e.target.value as AudioBackend,
+                })
+              }
+              className="w-full bg-gray-700 border border-gray-600 rounded-lg px-3 py-2 text-white"
+            >
+              <option value="default">Default (PipeWire, PulseAudio, ALSA)</option>
+              <option value="jack">JACK</option>
+            </select>
+          </div>
+
+          {localSettings.audio_backend === "jack" && (
+            <div>
+              <label className="block text-sm font-medium text-gray-300 mb-1">
+                JACK Ports
+              </label>
+              <p className="text-xs text-gray-500 mb-2">
+                Record from these ports; with none selected the system capture ports are used.
+              </p>
+              {jackError && <p className="text-sm text-red-400">{jackError}</p>}
+              {jackPorts.map((port) => (
+                <label key={port} className="flex items-center gap-3 py-1">
+                  <input
+                    type="checkbox"
+                    checked={localSettings.jack_ports.includes(port)}
+                    onChange={(e) => toggleJackPort(port, e.target.checked)}
+                    className="w-4 h-4 rounded bg-gray-700 border-gray-600 text-blue-600 focus:ring-blue-500"
+                  />
+                  <span className="text-sm text-gray-300">{port}</span>
+                </label>
+              ))}
+            </div>
+          )}
+
           <div>
             <label className="block text-sm font-medium text-gray-300 mb-1">
               Input Device
@@ -93,6 +225,215 @@ export default function SettingsPage() {
             </select>
           </div>
 
+          {localSettings.input_device && (
+            <div>
+              <label className="block text-sm font-medium text-gray-300 mb-1">
+                Fallback Devices
+              </label>
+              <p className="text-xs text-gray-500 mb-2">
+                Tried in order when the input device is not plugged in; the default device
+                comes last.
+              </p>
+              {localSettings.input_device_fallbacks.map((name, index) => (
+                <div key={name} className="flex items-center justify-between py-1">
+                  <span className="text-sm text-gray-300">
+                    {index + 1}. {name}
+                  </span>
+                  <button
+                    onClick={() =>
+                      setLocalSettings({
+                        ...localSettings,
+                        input_device_fallbacks: localSettings.input_device_fallbacks.filter(
+                          (fallback) => fallback !== name
+                        ),
+                      })
+                    }
+                    className="text-sm text-red-400 hover:text-red-300"
+                  >
+                    Remove
+                  </button>
+                </div>
+              ))}
+              <select
+                value=""
+                onChange={(e) =>
+                  e.target.value &&
+                  setLocalSettings({
+                    ...localSettings,
+                    input_device_fallbacks: [
+                      ...localSettings.input_device_fallbacks,
+                      e.target.value,
+                    ],
+                  })
+                }
+                className="w-full bg-gray-700 border border-gray-600 rounded-lg px-3 py-2 text-white"
+              >
+                <option value="">Add a fallback device...</option>
+                {devices
+                  .filter(
+                    (device) =>
+                      device.name !== localSettings.input_device &&
+                      !localSettings.input_device_fallbacks.includes(device.name)
+                  )
+                  .map((device) => (
+                    <option key={device.name} value={device.name}>
+                      {device.name}
+                    </option>
+                  ))}
+              </select>
+            </div>
+          )}
+
+          <label className="flex items-center gap-3">
+            <input
+              type="checkbox"
+              checked={localSettings.echo_cancellation}
+              onChange={(e) =>
+                setLocalSettings({
+                  ...localSettings,
+                  echo_cancellation: e.target.checked,
+                })
+              }
+              className="w-4 h-4 rounded bg-gray-700 border-gray-600 text-blue-600 focus:ring-blue-500"
+            />
+            <div>
+              <span className="text-white">Echo cancellation</span>
+              <p className="text-xs text-gray-500">
+                Keep audio playing on the speakers out of the transcript (PulseAudio or PipeWire)
+              </p>
+            </div>
+          </label>
+
+          <label className="flex items-center gap-3">
+            <input
+              type="checkbox"
+              checked={localSettings.bluetooth_early_switch}
+              onChange={(e) =>
+                setLocalSettings({
+                  ...localSettings,
+                  bluetooth_early_switch: e.target.checked,
+                })
+              }
+              className="w-4 h-4 rounded bg-gray-700 border-gray-600 text-blue-600 focus:ring-blue-500"
+            />
+            <div>
+              <span className="text-white">Early Bluetooth switching</span>
+              <p className="text-xs text-gray-500">
+                Switch a Bluetooth headset to its microphone profile before recording starts, and
+                back afterwards
+              </p>
+            </div>
+          </label>
+
+          <div>
+            <label className="block text-sm font-medium text-gray-300 mb-1">
+              Stream Settings
+            </label>
+            <p className="text-xs text-gray-500 mb-2">
+              Only needed for devices that misbehave with their default configuration.
+            </p>
+            <div className="grid grid-cols-3 gap-2">
+              <input
+                type="number"
+                placeholder="Sample rate"
+                value={streamOverride.sample_rate ?? ""}
+                onChange={(e) =>
+                  setStreamOverride({
+                    sample_rate: e.target.value ? parseInt(e.target.value) : null,
+                  })
+                }
+                className="bg-gray-700 border border-gray-600 rounded-lg px-3 py-2 text-white"
+              />
+              <select
+                value={streamOverride.sample_format ?? ""}
+                onChange={(e) =>
+                  setStreamOverride({
+                    sample_format: (e.target.value || null) as StreamOverride["sample_format"],
+                  })
+                }
+                className="bg-gray-700 border border-gray-600 rounded-lg px-3 py-2 text-white"
+              >
+                <option value="">Default format</option>
+                <option value="f32">f32</option>
+                <option value="i16">i16</option>
+                <option value="u16">u16</option>
+              </select>
+              <input
+                type="number"
+                placeholder="Buffer (frames)"
+                value={streamOverride.buffer_size ?? ""}
+                onChange={(e) =>
+                  setStreamOverride({
+                    buffer_size: e.target.value ? parseInt(e.target.value) : null,
+                  })
+                }
+                className="bg-gray-700 border border-gray-600 rounded-lg px-3 py-2 text-white"
+              />
+            </div>
+            <button
+              onClick={handleProbe}
+              className="mt-2 text-sm text-blue-400 hover:text-blue-300"
+            >
+              Show supported configurations
+            </button>
+            {probe && (
+              <ul className="mt-2 bg-gray-900 rounded p-3 text-xs text-gray-400 space-y-1">
+                {probe.map((config, index) => (
+                  <li key={index}>
+                    {config.sample_format}, {config.channels} ch,{" "}
+                    {config.min_sample_rate === config.max_sample_rate
+                      ? `${config.min_sample_rate} Hz`
+                      : `${config.min_sample_rate}-${config.max_sample_rate} Hz`}
+                    {config.min_buffer_size !== null &&
+                      `, buffer ${config.min_buffer_size}-${config.max_buffer_size}`}
+                  </li>
+                ))}
+              </ul>
+            )}
+          </div>
+
+          <div>
+            <div className="flex items-center justify-between">
+              <span className="text-sm text-gray-400">
+                {localSettings.input_calibration[localSettings.input_device]
+                  ? `Level gain ${localSettings.input_calibration[
+                      localSettings.input_device
+                    ].gain.toFixed(2)}`
+                  : "Level meter not calibrated for this device"}
+              </span>
+              <button
+                onClick={handleCalibrate}
+                disabled={calibrating}
+                className="px-3 py-1 bg-gray-700 text-gray-200 rounded text-sm hover:bg-gray-600 disabled:opacity-50"
+              >
+                {calibrating ? "Speak normally for 5 seconds..." : "Calibrate"}
+              </button>
+            </div>
+            {calibrationError && (
+              <p className="mt-2 text-sm text-red-400">{calibrationError}</p>
+            )}
+            {calibration && (
+              <div className="mt-2 bg-gray-900 rounded p-3 text-sm text-gray-300 space-y-1">
+                <p>
+                  Speech level {calibration.speech_rms.toFixed(3)}, background{" "}
+                  {calibration.noise_rms.toFixed(3)}. Recommended gain{" "}
+                  {calibration.gain.toFixed(2)}.
+                </p>
+                {calibration.clipping && (
+                  <p className="text-yellow-400">
+                    The input clipped; lower the microphone volume in the system settings.
+                  </p>
+                )}
+                <button
+                  onClick={handleApplyCalibration}
+                  className="px-3 py-1 bg-blue-600 text-white rounded text-sm hover:bg-blue-700"
+                >
+                  Apply
+                </button>
+              </div>
+            )}
+          </div>
+
           <div>
             <label className="block text-sm font-medium text-gray-300 mb-1">
               Language
@@ -104,15 +445,12 @@ export default function SettingsPage() {
               }
               className="w-full bg-gray-700 border border-gray-600 rounded-lg px-3 py-2 text-white"
             >
-              <option value="en">English</option>
-              <option value="es">Spanish</option>
-              <option value="fr">French</option>
-              <option value="de">German</option>
-              <option value="it">Italian</option>
-              <option value="pt">Portuguese</option>
-              <option value="ja">Japanese</option>
-              <option value="ko">Korean</option>
-              <option value="zh">Chinese</option>
+              <option value="auto">Auto-detect</option>
+              {languages.map(([code, name]) => (
+                <option key={code} value={code}>
+                  {name}
+                </option>
+              ))}
             </select>
           </div>
         </div>
@@ -169,6 +507,100 @@ export default function SettingsPage() {
               Models are downloaded automatically on first use
             </p>
           </div>
+
+          <div className="grid grid-cols-2 gap-4">
+            <div>
+              <label className="block text-sm font-medium text-gray-300 mb-1">
+                Minimum Recording (ms)
+              </label>
+              <input
+                type="number"
+                min={0}
+                value={localSettings.min_recording_ms}
+                onChange={(e) =>
+                  setLocalSettings({
+                    ...localSettings,
+                    min_recording_ms: parseInt(e.target.value) || 0,
+                  })
+                }
+                className="w-full bg-gray-700 border border-gray-600 rounded-lg px-3 py-2 text-white"
+              />
+              <p className="text-xs text-gray-500 mt-1">
+                Shorter recordings are discarded as accidental
+              </p>
+            </div>
+            <div>
+              <label className="block text-sm font-medium text-gray-300 mb-1">
+                Silence Padding (ms)
+              </label>
+              <input
+                type="number"
+                min={0}
+                value={localSettings.silence_padding_ms}
+                onChange={(e) =>
+                  setLocalSettings({
+                    ...localSettings,
+                    silence_padding_ms: parseInt(e.target.value) || 0,
+                  })
+                }
+                className="w-full bg-gray-700 border border-gray-600 rounded-lg px-3 py-2 text-white"
+              />
+              <p className="text-xs text-gray-500 mt-1">
+                whisper.cpp gets shorter recordings padded with silence to this length
+              </p>
+            </div>
+          </div>
+
+          <div>
+            <label className="block text-sm font-medium text-gray-300 mb-1">
+              Final Transcription
+            </label>
+            <div className="grid grid-cols-2 gap-2">
+              <select
+                value={localSettings.final_stt_provider}
+                onChange={(e) =>
+                  setLocalSettings({
+                    ...localSettings,
+                    final_stt_provider: e.target.value,
+                  })
+                }
+                className="bg-gray-700 border border-gray-600 rounded-lg px-3 py-2 text-white"
+              >
+                <option value="whispercpp">whisper.cpp (Local)</option>
+                <option value="openai">OpenAI-compatible server</option>
+              </select>
+              <input
+                type="text"
+                value={localSettings.final_stt_model}
+                onChange={(e) =>
+                  setLocalSettings({
+                    ...localSettings,
+                    final_stt_model: e.target.value,
+                  })
+                }
+                placeholder="Off (e.g., large-v3)"
+                className="bg-gray-700 border border-gray-600 rounded-lg px-3 py-2 text-white"
+              />
+            </div>
+            <p className="text-xs text-gray-500 mt-1">
+              Paste a quick draft from the mode's model, then transcribe again with this model in
+              the background and update the history item
+            </p>
+            <label className="flex items-center gap-3 mt-2">
+              <input
+                type="checkbox"
+                checked={localSettings.final_pass_notify}
+                onChange={(e) =>
+                  setLocalSettings({
+                    ...localSettings,
+                    final_pass_notify: e.target.checked,
+                  })
+                }
+                className="w-4 h-4 rounded bg-gray-700 border-gray-600 text-blue-600 focus:ring-blue-500"
+              />
+              <span className="text-white">Notify when the final transcript is ready</span>
+            </label>
+          </div>
         </div>
       </section>
 
@@ -284,6 +716,35 @@ export default function SettingsPage() {
               )}
             </div>
           </div>
+
+          <label className="flex items-center gap-3">
+            <input
+              type="checkbox"
+              checked={localSettings.cache_api_keys}
+              onChange={(e) =>
+                setLocalSettings({
+                  ...localSettings,
+                  cache_api_keys: e.target.checked,
+                })
+              }
+              className="w-4 h-4 rounded bg-gray-700 border-gray-600 text-blue-600 focus:ring-blue-500"
+            />
+            <div>
+              <span className="text-white">Unlock keys once per session</span>
+              <p className="text-xs text-gray-500">
+                Read the keys at startup and keep them in memory, for keyrings
+                that ask for a password on every access
+              </p>
+            </div>
+          </label>
+          {localSettings.cache_api_keys && (
+            <button
+              onClick={() => api.lockKeys()}
+              className="px-3 py-2 bg-gray-700 text-white rounded-lg text-sm hover:bg-gray-600"
+            >
+              Lock Keys Now
+            </button>
+          )}
         </div>
       </section>
 
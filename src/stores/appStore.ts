@@ -38,12 +38,14 @@ interface AppState {
   initialize: () => Promise<void>;
   startRecording: () => Promise<void>;
   stopRecording: () => Promise<void>;
+  togglePause: () => Promise<void>;
   setActiveMode: (modeKey: string) => Promise<void>;
   setInputDevice: (deviceName: string) => Promise<void>;
   loadHistory: (search?: string) => Promise<void>;
   selectHistoryItem: (item: HistoryItem | null) => void;
   reprocessHistoryItem: (id: string, modeKey: string) => Promise<void>;
   deleteHistoryItem: (id: string) => Promise<void>;
+  clearHistory: (beforeDate?: string) => Promise<void>;
   updateSettings: (settings: Settings) => Promise<void>;
   saveApiKey: (provider: string, key: string) => Promise<void>;
   deleteApiKey: (provider: string) => Promise<void>;
@@ -105,6 +107,14 @@ export const useAppStore = create<AppState>((set, get) => ({
       listen("recording-started", () => {
         set({ status: "recording", isRecording: true });
       });
+
+      listen<boolean>("recording-paused", (event) => {
+        set({ status: event.payload ? "paused" : "recording" });
+      });
+
+      listen<Settings>("settings-changed", (event) => {
+        set({ settings: event.payload });
+      });
     } catch (error) {
       set({
         error: error instanceof Error ? error.message : "Failed to initialize",
@@ -127,6 +137,18 @@ export const useAppStore = create<AppState>((set, get) => ({
     }
   },
 
+  // Pause or resume recording
+  togglePause: async () => {
+    try {
+      const paused = await api.togglePause();
+      set({ status: paused ? "paused" : "recording", error: null });
+    } catch (error) {
+      set({
+        error: error instanceof Error ? error.message : "Failed to pause recording",
+      });
+    }
+  },
+
   // Stop recording
   stopRecording: async () => {
     try {
@@ -227,6 +249,20 @@ export const useAppStore = create<AppState>((set, get) => ({
     }
   },
 
+  // Clear history, optionally only items before a date
+  clearHistory: async (beforeDate?: string) => {
+    try {
+      set({ error: null });
+      await api.clearHistory(beforeDate);
+      set({ selectedHistoryItem: null });
+      get().loadHistory();
+    } catch (error) {
+      set({
+        error: error instanceof Error ? error.message : "Failed to clear history",
+      });
+    }
+  },
+
   // Update settings
   updateSettings: async (settings: Settings) => {
     try {
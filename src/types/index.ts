@@ -1,5 +1,5 @@
 // Recording status
-export type RecordingStatus = "loading" | "recording" | "processing" | "ready" | "error";
+export type RecordingStatus = "loading" | "recording" | "paused" | "processing" | "ready" | "error";
 
 // STT provider types
 export type SttProvider = "whispercpp" | "deepgram" | "openai" | string;
@@ -7,8 +7,17 @@ export type SttProvider = "whispercpp" | "deepgram" | "openai" | string;
 // LLM provider types
 export type LlmProvider = "openai" | "anthropic" | "ollama" | string;
 
+// Embedding provider types
+export type EmbeddingProviderType = "ollama" | "openai" | "onnx";
+
+// Response format requested from OpenAI-compatible STT servers
+export type SttResponseFormat = "json" | "verbose_json";
+
+// Streaming STT provider types
+export type StreamingProviderType = "deepgram" | "openai" | "whisper_live";
+
 // Output format
-export type OutputFormat = "plain" | "markdown";
+export type OutputFormat = "plain" | "markdown" | "json";
 
 // Mode definition
 export interface Mode {
@@ -23,6 +32,54 @@ export interface Mode {
   prompt_template: string;
   output_format: OutputFormat;
   builtin: boolean;
+  sort_order: number | null;
+  favorite: boolean;
+  hidden: boolean;
+  keep_audio: boolean | null;
+  json_schema: Record<string, unknown> | null;
+  route_to: string[];
+  clipboard_context: boolean;
+  live_transcript: boolean;
+  extract_action_items: boolean;
+  task_target: TaskTarget | null;
+  open_in_mail_client: boolean;
+  post_to: ChatTarget[];
+  decoding: DecodingOptions;
+  style: StyleRules;
+  customized: boolean;
+}
+
+// Tone rules of a mode, added to the prompt and enforced on the output
+export interface StyleRules {
+  lowercase_casual: boolean;
+  no_emoji: boolean;
+  max_sentence_words: number | null;
+}
+
+// Decoding options for OpenAI-compatible STT servers (unset keeps server defaults)
+export interface DecodingOptions {
+  temperature: number | null;
+  beam_size: number | null;
+  vad_filter: boolean | null;
+  hotwords: string | null;
+}
+
+// Previous version of a mode
+export interface ModeRevision {
+  id: number;
+  mode: Mode;
+}
+
+export interface ModeProblem {
+  stage: "stt" | "llm";
+  message: string;
+  fix: string;
+  blocking: boolean;
+}
+
+export interface ModeProblems {
+  mode_key: string;
+  problems: ModeProblem[];
 }
 
 // Audio device
@@ -45,6 +102,129 @@ export interface HistoryItem {
   llm_model: string | null;
   duration_ms: number;
   error: string | null;
+  confidence: number | null;
+  title: string | null;
+  latencies: StageLatencies | null;
+  provider_metadata: ProviderMetadata | null;
+  clipped: boolean;
+  transcript_path: string | null;
+}
+
+// Milliseconds a dictation spent in each pipeline stage
+export interface StageLatencies {
+  recording_ms: number;
+  saving_ms: number;
+  transcribing_ms: number;
+  llm_ms: number;
+  pasting_ms: number;
+}
+
+// Endpoints that served a dictation's provider requests
+export interface ProviderMetadata {
+  stt_endpoint: string | null;
+  stt_retries: number;
+  llm_endpoint: string | null;
+  llm_retries: number;
+}
+
+// Timed transcription segment
+export interface Segment {
+  start_ms: number;
+  end_ms: number;
+  text: string;
+  confidence: number | null;
+  speaker: string | null;
+}
+
+export interface ActionItem {
+  owner: string | null;
+  task: string;
+  due: string | null;
+}
+
+export type ChatTarget =
+  | { service: "slack" }
+  | { service: "matrix"; room_id: string };
+
+export type TaskService = "todoist" | "caldav";
+
+export type TaskSource = "output" | "action_items";
+
+export interface TaskTarget {
+  service: TaskService;
+  source: TaskSource;
+  project_id: string;
+  dry_run: boolean;
+}
+
+// A task a mode would create from a dictation
+export interface NewTask {
+  content: string;
+  description: string;
+  due: string | null;
+}
+
+// Payload of the "action-items" event
+export interface ActionItemsUpdate {
+  history_id: string;
+  items: ActionItem[];
+}
+
+// Payload of the "history-title" event
+export interface TitleUpdate {
+  history_id: string;
+  title: string;
+}
+
+// Payload of the "history-final" event
+export interface FinalTranscript {
+  history_id: string;
+  output: string;
+}
+
+// Payload of the "live-caption" event
+export interface Caption {
+  text: string;
+  is_final: boolean;
+}
+
+// Audio file of a merged history item
+export interface AudioPart {
+  offset_ms: number;
+  path: string;
+}
+
+export interface MissingAudio {
+  history_id: string;
+  path: string;
+}
+
+export interface OrphanReport {
+  orphaned_files: string[];
+  orphaned_bytes: number;
+  missing_audio: MissingAudio[];
+  cleaned: boolean;
+}
+
+// Tray icon appearance
+export type TrayIconStyle = "color" | "symbolic";
+export type TrayIconTheme = "auto" | "dark" | "light";
+
+export type IndicatorCorner =
+  | "top_left"
+  | "top_center"
+  | "top_right"
+  | "bottom_left"
+  | "bottom_center"
+  | "bottom_right";
+
+// Paste key combination and per-application overrides
+export type PasteChord = "ctrl_v" | "ctrl_shift_v" | "shift_insert";
+
+export interface PasteOverride {
+  window_class: string;
+  chord: PasteChord | null;
+  delay_ms: number | null;
 }
 
 // Settings
@@ -55,23 +235,330 @@ export interface Settings {
   default_llm_model: string;
   active_mode_key: string;
   input_device: string;
+  input_device_fallbacks: string[];
+  audio_backend: AudioBackend;
+  jack_ports: string[];
   auto_paste: boolean;
   context_awareness: boolean;
   language: string;
+  tray_icon_style: TrayIconStyle;
+  tray_icon_theme: TrayIconTheme;
+  tray_icon_size: number;
+  show_indicator: boolean;
+  indicator_monitor: string;
+  indicator_corner: IndicatorCorner;
+  status_file: string;
+  sync_folder: string;
+  rich_clipboard: boolean;
+  low_confidence_threshold: number;
+  redact_cloud_prompts: boolean;
+  redact_history: boolean;
+  redact_names: string[];
+  local_only: boolean;
+  keep_audio: boolean;
+  paste_focus_guard: boolean;
+  paste_allowed_windows: string[];
+  paste_chord: PasteChord;
+  paste_delay_ms: number;
+  paste_overrides: PasteOverride[];
+  paste_backend: PasteBackend | null;
+  ollama_keep_alive: string;
+  ollama_preload: boolean;
+  warm_up_pipeline: boolean;
+  preload_on_startup: boolean;
+  preload_ollama_on_startup: boolean;
+  cache_api_keys: boolean;
+  stt_server_url: string;
+  stt_response_format: SttResponseFormat;
+  output_filters: OutputFilters;
+  learn_corrections: boolean;
+  mode_gallery_url: string;
+  tray_tooltip_template: string;
+  completion_notifications: boolean;
+  notification_rerun_mode: string;
+  auto_dedup: boolean;
+  dedup_window_secs: number;
+  journal_enabled: boolean;
+  journal_dir: string;
+  journal_modes: string[];
+  live_transcript_dir: string;
+  streaming_provider: StreamingProviderType | null;
+  streaming_model: string;
+  whisper_live_url: string;
+  generate_titles: boolean;
+  embedding_provider: EmbeddingProviderType;
+  embedding_model: string;
+  rag_enabled: boolean;
+  rag_top_k: number;
+  rag_min_similarity: number;
+  hooks: Hooks;
+  caldav_url: string;
+  caldav_username: string;
+  matrix_homeserver: string;
+  enabled_plugins: string[];
+  check_for_updates: boolean;
+  recording_memory_cap_mb: number;
+  input_calibration: Record<string, InputCalibration>;
+  input_overrides: Record<string, StreamOverride>;
+  echo_cancellation: boolean;
+  echo_cancel_source: string;
+  bluetooth_early_switch: boolean;
+  min_recording_ms: number;
+  silence_padding_ms: number;
+  final_stt_provider: SttProvider;
+  final_stt_model: string;
+  final_pass_notify: boolean;
+}
+
+// Latest release found by the update check
+export interface UpdateInfo {
+  current_version: string;
+  latest_version: string;
+  update_available: boolean;
+  url: string;
+  changelog: string;
+  published_at: string | null;
+}
+
+// External commands the text is piped through (empty commands are skipped)
+export interface Hooks {
+  post_transcription: string;
+  post_llm: string;
+  pre_paste: string;
+  timeout_secs: number;
+}
+
+// Result of an embedding provider self-test
+export interface EmbeddingCheck {
+  provider: string;
+  dimensions: number;
+  elapsed_ms: number;
+}
+
+export interface GalleryEntry {
+  key: string;
+  name: string;
+  description: string;
+  author: string;
+  url: string;
+  installed: boolean;
+}
+
+export interface Snippet {
+  name: string;
+  text: string;
+}
+
+// Regex find/replace rule applied to the final output (empty id creates a new rule)
+export interface RewriteRule {
+  id: string;
+  pattern: string;
+  replacement: string;
+  mode_key: string | null;
+  enabled: boolean;
+}
+
+export type PluginCapability = "text_processor" | "output_sink" | "context_provider";
+
+// WebAssembly plugin from the plugins folder (error is set if it could not be loaded)
+export interface PluginInfo {
+  id: string;
+  name: string;
+  version: string;
+  description: string;
+  capabilities: PluginCapability[];
+  enabled: boolean;
+  error: string | null;
+}
+
+export interface Correction {
+  original: string;
+  corrected: string;
+  count: number;
+}
+
+export interface OutputFilters {
+  strip_fences: boolean;
+  strip_preamble: boolean;
+  trim_quotes: boolean;
+}
+
+export interface OllamaModel {
+  name: string;
+  size: number;
+  modified_at: string;
+}
+
+// Window a recording can be pasted into
+export interface FocusedWindow {
+  id: string;
+  class: string;
+  title: string;
+}
+
+// Tracked dictation, file transcription or reprocessing run
+export type JobKind = "dictation" | "file" | "reprocess";
+
+export type JobStatus =
+  | "queued"
+  | "running"
+  | "paused"
+  | "done"
+  | "failed"
+  | "cancelled";
+
+export interface Job {
+  id: string;
+  kind: JobKind;
+  label: string;
+  status: JobStatus;
+  progress: number | null;
+  error: string | null;
+  created_at: string;
 }
 
 // Recording status response
 export interface RecordingStatusResponse {
   status: RecordingStatus;
   is_recording: boolean;
+  incognito: boolean;
+  elapsed_ms: number | null;
+  captured_samples: number;
+  level: number;
+  device: string | null;
 }
 
 // Export format
 export type ExportFormat = "txt" | "md" | "srt" | "vtt";
 
+// Shareable transcript file
+export interface ShareOptions {
+  format: "html" | "markdown";
+  include_transcript: boolean;
+  include_details: boolean;
+}
+
+// Flashcard export of history items
+export type FlashcardFormat = "anki" | "csv";
+
+export type CardField = "transcript" | "output" | "title" | "mode" | "date";
+
+export interface FlashcardOptions {
+  format: FlashcardFormat;
+  front: CardField;
+  back: CardField;
+  deck: string;
+  tags: string[];
+}
+
 // History query
 export interface HistoryQuery {
   limit?: number;
   offset?: number;
   search?: string;
 }
+
+// Pipeline progress (emitted as "pipeline-progress")
+export type PipelineStage =
+  | "recording"
+  | "saving"
+  | "transcribing"
+  | "llm"
+  | "pasting"
+  | "done"
+  | "failed";
+
+export interface StageTiming {
+  stage: PipelineStage;
+  duration_ms: number;
+}
+
+export interface PipelineProgress {
+  stage: PipelineStage;
+  previous: StageTiming | null;
+  elapsed_ms: number;
+  error: string | null;
+}
+
+// Payload of the `operation-progress` event (model downloads, long file transcriptions)
+export interface OperationProgressUpdate {
+  title: string;
+  percent: number;
+  done: boolean;
+}
+
+// Paste backend setup diagnostics
+export interface SetupCheck {
+  name: string;
+  ok: boolean;
+  detail: string;
+  fix: string | null;
+}
+
+export interface PasteBackendCheck {
+  ok: boolean;
+  checks: SetupCheck[];
+  can_install_rule: boolean;
+}
+
+export interface PasteTools {
+  xdotool: boolean;
+  wtype: boolean;
+  ydotool: boolean;
+  wl_copy: boolean;
+}
+
+export type PasteBackend = "enigo" | "wtype" | "ydotool" | "clipboard_only";
+
+export interface PasteDiagnostics {
+  session_type: "wayland" | "x11";
+  compositor: string;
+  tools: PasteTools;
+  backend: PasteBackend;
+  clipboard: "arboard" | "wl_copy";
+  notes: string[];
+}
+
+export interface PasteBackendResult {
+  backend: PasteBackend;
+  success: boolean;
+  latency_ms: number | null;
+  error: string | null;
+}
+
+export interface PasteBenchmarkReport {
+  results: PasteBackendResult[];
+  selected: PasteBackend | null;
+}
+
+export interface InputCalibration {
+  gain: number;
+  speech_rms: number;
+  noise_rms: number;
+  clipping: boolean;
+  measured_at: string;
+}
+
+export type AudioBackend = "default" | "jack";
+
+export interface StreamOverride {
+  sample_rate: number | null;
+  sample_format: "f32" | "i16" | "u16" | null;
+  buffer_size: number | null;
+}
+
+export interface SupportedInputConfig {
+  channels: number;
+  min_sample_rate: number;
+  max_sample_rate: number;
+  sample_format: string;
+  min_buffer_size: number | null;
+  max_buffer_size: number | null;
+}
+
+export interface ConfigImportReport {
+  modes: number;
+  rewrite_rules: number;
+  snippets: number;
+  warnings: string[];
+}